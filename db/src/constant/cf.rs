@@ -9,6 +9,7 @@ pub enum ColumnFamily {
 	VertexProperty,
 	Property,
 	Vertex,
+	Ttl,
 }
 
 lazy_static! {
@@ -17,7 +18,8 @@ lazy_static! {
 		(ColumnFamily::Edge, "edges:v1".to_string()),
 		(ColumnFamily::VertexProperty, "vertex-properties:v1".to_string()),
 		(ColumnFamily::Property, "properties:v1".to_string()),
-		(ColumnFamily::Vertex, "vertices:v1".to_string())
+		(ColumnFamily::Vertex, "vertices:v1".to_string()),
+		(ColumnFamily::Ttl, "ttl:v1".to_string())
 	]);
 	pub static ref CF_NAMES: Vec<&'static String> = COLUMN_FAMILIES.values().collect();
 }
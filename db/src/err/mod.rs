@@ -45,13 +45,25 @@ pub enum Error {
 	#[error("The key is not in the database")]
 	TxnKeyNotFound,
 
+	/// A validator registered on the datastore rejected the write
+	#[error("The write was rejected: {0}")]
+	TxRejected(String),
+
 	/// The key being inserted in the transaction already exists
 	#[error("The key being inserted already exists")]
 	TxKeyAlreadyExists,
 
+	/// The datastore doesn't support two-phase commits
+	#[error("Transactions of this datastore can't be prepared for a two-phase commit")]
+	TxPrepareUnsupported,
+
 	/// It's is not possible to convert between the two types
 	#[error("Cannot convert from '{0}' to '{1}'")]
 	TryFromError(String, &'static str),
+
+	/// The bytes can't be read by the value decoder
+	#[error("Unable to decode value: {0}")]
+	Decode(String),
 }
 
 #[cfg(feature = "kv-rocksdb")]
@@ -67,3 +79,17 @@ impl From<redb::Error> for Error {
 		Error::Tx(e.to_string())
 	}
 }
+
+#[cfg(feature = "kv-sled")]
+impl From<sled::Error> for Error {
+	fn from(e: sled::Error) -> Error {
+		Error::Tx(e.to_string())
+	}
+}
+
+#[cfg(feature = "kv-lmdb")]
+impl From<heed::Error> for Error {
+	fn from(e: heed::Error) -> Error {
+		Error::Tx(e.to_string())
+	}
+}
@@ -7,10 +7,18 @@ mod model;
 mod storage;
 mod util;
 
+#[cfg(feature = "kv-lmdb")]
+pub use crate::storage::kvs::LmdbAdapter;
+#[cfg(feature = "kv-memory")]
+pub use crate::storage::kvs::MemoryAdapter;
 #[cfg(feature = "kv-redb")]
 pub use crate::storage::kvs::ReDBAdapter;
 #[cfg(feature = "kv-rocksdb")]
-pub use crate::storage::kvs::RocksDBAdapter;
+pub use crate::storage::kvs::{RocksDBAdapter, TwoPhaseRocksDBAdapter};
+#[cfg(feature = "kv-sled")]
+pub use crate::storage::kvs::SledAdapter;
+#[cfg(feature = "kv-sim")]
+pub use crate::storage::kvs::{SimAdapter, SimFaults};
 #[macro_use]
 #[cfg(test)]
 pub mod tests;
@@ -18,4 +26,12 @@ pub mod tests;
 pub use crate::err::*;
 pub use crate::interface::*;
 pub use crate::model::*;
-pub use crate::storage::{Datastore, DatastoreRef, Transaction};
+pub use crate::storage::{
+	bincode_decoder, clear_expiry, decode_cbor, decode_msgpack, decode_protobuf, decoder_names,
+	get_comparator, get_decoder, get_expiry, hash_key, is_recording, protobuf_decoder,
+	purge_expired, range_shards, read_trace, register_comparator, register_decoder, replay,
+	set_expiry, start_recording, stop_recording, write_trace, BincodeType, CommitHookFn, CompareFn,
+	Datastore, DatastoreRef, DecodeFn, HookFn, Hooks, Overlay, PendingWrite, RecordRepository,
+	ReplicaSet, Snapshot, Throttle, TraceEntry, TraceOperation, Transaction, ValidateFn,
+	Validators, WriteBatch, WriteEvent, WriteLimit, WriteOperation,
+};
@@ -18,9 +18,29 @@ macro_rules! full_adapter_test_impl {
 		#[cfg(test)]
 		define_test!(should_delete_key, $code);
 		#[cfg(test)]
+		define_test!(should_delete_range, $code);
+		#[cfg(test)]
 		define_test!(should_set_key, $code);
 		#[cfg(test)]
 		define_test!(should_put_key, $code);
+		#[cfg(test)]
+		define_test!(should_estimate_count, $code);
+		#[cfg(test)]
+		define_test!(should_prefix_iterate, $code);
+		#[cfg(test)]
+		define_test!(should_range_iterate, $code);
+		#[cfg(test)]
+		define_test!(should_reverse_iterate, $code);
+		#[cfg(test)]
+		define_test!(should_page_iterate, $code);
+		#[cfg(test)]
+		define_test!(should_scan_range, $code);
+		#[cfg(test)]
+		define_test!(should_iterate_with_cursor, $code);
+		#[cfg(test)]
+		define_test!(should_report_column_family_options, $code);
+		#[cfg(test)]
+		define_test!(should_report_properties, $code);
 	};
 }
 
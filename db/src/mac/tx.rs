@@ -17,6 +17,7 @@ macro_rules! impl_global_transaction {
 
 			// Cancel a transaction
 			async fn cancel(&mut self) -> Result<(), Error> {
+				self.trace(&None, || TraceOperation::Cancel);
 				match self {
 					$(
 						#[cfg(feature = $feat)]
@@ -30,6 +31,7 @@ macro_rules! impl_global_transaction {
 
 			// Count number of items
 			async fn count(&mut self, cf: CF) -> Result<usize, Error> {
+				self.trace(&cf, || TraceOperation::Count);
 				match self {
 					$(
 						#[cfg(feature = $feat)]
@@ -41,9 +43,23 @@ macro_rules! impl_global_transaction {
 				}
 			}
 
+			// Estimate number of items
+			async fn estimate_count(&mut self, cf: CF) -> Result<usize, Error> {
+				match self {
+					$(
+						#[cfg(feature = $feat)]
+						Transaction {
+							inner: Inner::$x(ds),
+							..
+						} => ds.estimate_count(cf).await,
+					)*
+				}
+			}
+
 			// Commit a transaction
 			async fn commit(&mut self) -> Result<(), Error> {
-				match self {
+				self.trace(&None, || TraceOperation::Commit);
+				let result = match self {
 					$(
 						#[cfg(feature = $feat)]
 						Transaction {
@@ -51,11 +67,30 @@ macro_rules! impl_global_transaction {
 							..
 						} => ds.commit().await,
 					)*
+				};
+				if result.is_ok() {
+					self.hooks.fire(&std::mem::take(&mut self.writes));
+				}
+				result
+			}
+
+			// Prepare a transaction for a two-phase commit, hooks are fired once it is committed
+			async fn prepare(&mut self) -> Result<(), Error> {
+				match self {
+					$(
+						#[cfg(feature = $feat)]
+						Transaction {
+							inner: Inner::$x(ds),
+							..
+						} => ds.prepare().await,
+					)*
 				}
 			}
 
 			// Check if a key exists
 			async fn exi<K: Into<Key> + Send>(&self, cf: CF, key: K) -> Result<bool, Error> {
+				let key: Key = key.into();
+				self.trace(&cf, || TraceOperation::Exists { key: key.clone() });
 				match self {
 					$(
 						#[cfg(feature = $feat)]
@@ -69,6 +104,8 @@ macro_rules! impl_global_transaction {
 
 			/// Fetch a key from the database
 			async fn get<K: Into<Key> + Send>(&self, cf: CF, key: K) -> Result<Option<Val>, Error> {
+				let key: Key = key.into();
+				self.trace(&cf, || TraceOperation::Get { key: key.clone() });
 				match self {
 					$(
 						#[cfg(feature = $feat)]
@@ -86,6 +123,9 @@ macro_rules! impl_global_transaction {
 				cf: CF,
 				keys: Vec<K>,
 			) -> Result<Vec<Option<Val>>, Error> {
+				for key in keys.iter() {
+					self.trace(&cf, || TraceOperation::Get { key: key.as_ref().to_vec() });
+				}
 				match self {
 					$(
 						#[cfg(feature = $feat)]
@@ -104,15 +144,22 @@ macro_rules! impl_global_transaction {
 				key: K,
 				val: V,
 			) -> Result<(), Error> {
-				match self {
+				let key: Key = key.into();
+				let val: Val = val.into();
+				self.trace(&cf, || TraceOperation::Set { key: key.clone(), size: val.len() });
+				self.validate(WriteOperation::Set, &cf, &key, Some(&val))?;
+				self.throttle.acquire(key.len() + val.len()).await;
+				let result = match self {
 					$(
 						#[cfg(feature = $feat)]
 						Transaction {
 							inner: Inner::$x(ds),
 							..
-						} => ds.set(cf, key, val).await,
+						} => ds.set(cf.clone(), key.clone(), val.clone()).await,
 					)*
-				}
+				};
+				self.record(&result, WriteOperation::Set, cf, key);
+				result
 			}
 
 			/// Insert a key if it doesn't exist in the database
@@ -122,28 +169,41 @@ macro_rules! impl_global_transaction {
 				key: K,
 				val: V,
 			) -> Result<(), Error> {
-				match self {
+				let key: Key = key.into();
+				let val: Val = val.into();
+				self.trace(&cf, || TraceOperation::Put { key: key.clone(), size: val.len() });
+				self.validate(WriteOperation::Put, &cf, &key, Some(&val))?;
+				self.throttle.acquire(key.len() + val.len()).await;
+				let result = match self {
 					$(
 						#[cfg(feature = $feat)]
 						Transaction {
 							inner: Inner::$x(ds),
 							..
-						} => ds.put(cf, key, val).await,
+						} => ds.put(cf.clone(), key.clone(), val.clone()).await,
 					)*
-				}
+				};
+				self.record(&result, WriteOperation::Put, cf, key);
+				result
 			}
 
 			/// Delete a key
 			async fn del<K: Into<Key> + Send>(&mut self, cf: CF, key: K) -> Result<(), Error> {
-				match self {
+				let key: Key = key.into();
+				self.trace(&cf, || TraceOperation::Delete { key: key.clone() });
+				self.validate(WriteOperation::Delete, &cf, &key, None)?;
+				self.throttle.acquire(key.len()).await;
+				let result = match self {
 					$(
 						#[cfg(feature = $feat)]
 						Transaction {
 							inner: Inner::$x(ds),
 							..
-						} => ds.del(cf, key ).await,
+						} => ds.del(cf.clone(), key.clone()).await,
 					)*
-				}
+				};
+				self.record(&result, WriteOperation::Delete, cf, key);
+				result
 			}
 
 			async fn prefix_iterate<P>(
@@ -154,6 +214,8 @@ macro_rules! impl_global_transaction {
 			where
 				P: Into<Key> + Send,
 			{
+				let prefix: Key = prefix.into();
+				self.trace(&cf, || TraceOperation::PrefixIterate { prefix: prefix.clone() });
 				match self {
 					$(
 						#[cfg(feature = $feat)]
@@ -173,6 +235,8 @@ macro_rules! impl_global_transaction {
 			where
 				S: Into<Key> + Send,
 			{
+				let suffix: Key = suffix.into();
+				self.trace(&cf, || TraceOperation::SuffixIterate { suffix: suffix.clone() });
 				match self {
 					$(
 						#[cfg(feature = $feat)]
@@ -184,7 +248,29 @@ macro_rules! impl_global_transaction {
 				}
 			}
 
+			async fn range_iterate(
+				&self,
+				cf: CF,
+				start: Bound<Key>,
+				end: Bound<Key>,
+			) -> Result<Vec<Result<(Val, Val), Error>>, Error> {
+				self.trace(&cf, || TraceOperation::RangeIterate {
+					start: start.clone(),
+					end: end.clone(),
+				});
+				match self {
+					$(
+						#[cfg(feature = $feat)]
+						Transaction {
+							inner: Inner::$x(ds),
+							..
+						} => ds.range_iterate(cf, start, end).await,
+					)*
+				}
+			}
+
 			async fn iterate(&self, cf: CF) -> Result<Vec<Result<(Val, Val), Error>>, Error> {
+				self.trace(&cf, || TraceOperation::Iterate { reverse: false });
 				match self {
 					$(
 						Transaction {
@@ -194,6 +280,75 @@ macro_rules! impl_global_transaction {
 					)*
 				}
 			}
+
+			async fn reverse_iterate(
+				&self,
+				cf: CF,
+			) -> Result<Vec<Result<(Val, Val), Error>>, Error> {
+				self.trace(&cf, || TraceOperation::Iterate { reverse: true });
+				match self {
+					$(
+						#[cfg(feature = $feat)]
+						Transaction {
+							inner: Inner::$x(ds),
+							..
+						} => ds.reverse_iterate(cf).await,
+					)*
+				}
+			}
+
+			async fn page_iterate(
+				&self,
+				cf: CF,
+				page: Page,
+				reverse: bool,
+			) -> Result<Vec<Result<(Val, Val), Error>>, Error> {
+				self.trace(&cf, || TraceOperation::Iterate { reverse });
+				match self {
+					$(
+						#[cfg(feature = $feat)]
+						Transaction {
+							inner: Inner::$x(ds),
+							..
+						} => ds.page_iterate(cf, page, reverse).await,
+					)*
+				}
+			}
+
+			async fn scan_range(
+				&self,
+				cf: CF,
+				start: Bound<Key>,
+				end: Bound<Key>,
+				page: Page,
+				reverse: bool,
+			) -> Result<Vec<Result<(Val, Val), Error>>, Error> {
+				self.trace(&cf, || TraceOperation::RangeIterate {
+					start: start.clone(),
+					end: end.clone(),
+				});
+				match self {
+					$(
+						#[cfg(feature = $feat)]
+						Transaction {
+							inner: Inner::$x(ds),
+							..
+						} => ds.scan_range(cf, start, end, page, reverse).await,
+					)*
+				}
+			}
+
+			async fn snapshot(&self, cfs: Vec<CF>) -> Result<Snapshot, Error> {
+				match self {
+					$(
+						#[cfg(feature = $feat)]
+						Transaction {
+							inner: Inner::$x(ds),
+							..
+						} => ds.snapshot(cfs).await,
+					)*
+				}
+			}
 		}
 	}
 }
@@ -1,7 +1,15 @@
 use async_trait::async_trait;
 
-use crate::{err::Error, util::get_absolute_path, SimpleTransaction};
-use std::{pin::Pin, sync::Arc};
+use crate::{
+	err::Error,
+	util::{copy_path, disk_size, get_absolute_path},
+	SimpleTransaction,
+};
+use std::{
+	path::Path,
+	pin::Pin,
+	sync::{atomic::AtomicU64, Arc},
+};
 
 #[derive(Debug, Clone)]
 pub enum StorageVariant {
@@ -13,6 +21,9 @@ pub enum StorageVariant {
 pub enum StorageAdapterName {
 	RocksDB,
 	ReDB,
+	Sled,
+	Lmdb,
+	Sim,
 	EchoDB,
 }
 
@@ -40,6 +51,49 @@ impl<T> StorageAdapter<T> {
 	}
 }
 
+/// Storage options of a column family as configured in the backend
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ColumnFamilyOptions {
+	pub name: String,
+	pub compression: String,
+	pub comparator: String,
+	pub merge_operator: Option<String>,
+	pub block_size: Option<u64>,
+}
+
+/// Storage statistics of a column family, counters a backend doesn't report are `None`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ColumnFamilyStats {
+	pub name: String,
+	/// Number of keys, estimated by RocksDB
+	pub keys: Option<u64>,
+	/// Bytes of the SST files for RocksDB, of the keys and values for other backends
+	pub size: Option<u64>,
+	/// Number of SST files of a RocksDB column family
+	pub files: Option<u64>,
+}
+
+/// Storage statistics of a datastore
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DatastoreStats {
+	/// Bytes of the database files
+	pub disk_size: u64,
+	pub column_families: Vec<ColumnFamilyStats>,
+}
+
+/// Features of a backend, checked before they are offered instead of failing once used
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Capabilities {
+	/// Column families can be created and dropped
+	pub column_families: bool,
+	/// Snapshots read every column family at the same point in time
+	pub snapshots: bool,
+	/// The database can be written to a backup and restored from it
+	pub backup: bool,
+	/// Column families can be exported to and ingested from SST files
+	pub sst_files: bool,
+}
+
 #[async_trait]
 pub trait DatastoreAdapter {
 	type Transaction: SimpleTransaction;
@@ -62,4 +116,59 @@ pub trait DatastoreAdapter {
 	}
 
 	fn path(&self) -> &str;
+
+	/// Features of the backend, those of a key-value store without managed column
+	/// families by default
+	fn capabilities() -> Capabilities
+	where
+		Self: Sized,
+	{
+		Capabilities {
+			snapshots: true,
+			backup: true,
+			..Default::default()
+		}
+	}
+
+	/// Report the storage options of every column family of the datastore
+	fn column_family_options(&self) -> Result<Vec<ColumnFamilyOptions>, Error>;
+
+	/// Report runtime properties of the storage engine as name and value pairs. Backends
+	/// without such properties report none.
+	fn properties(&self) -> Result<Vec<(String, String)>, Error> {
+		Ok(vec![])
+	}
+
+	/// Write a copy of the database at `backup`, with the native mechanism of the backend.
+	/// Backends without one copy their files. The written bytes are added to `progress`.
+	fn backup(&self, backup: &Path, progress: &AtomicU64) -> Result<(), Error> {
+		copy_path(Path::new(self.path()), backup, progress)
+	}
+
+	/// Write the database of a copy made by `backup` at `path`, which must not exist yet.
+	/// The written bytes are added to `progress`.
+	fn restore(path: &Path, backup: &Path, progress: &AtomicU64) -> Result<(), Error>
+	where
+		Self: Sized,
+	{
+		copy_path(backup, path, progress)
+	}
+
+	/// Report the size of the database files and the statistics of every column family,
+	/// read from the storage engine without iterating through a transaction. Backends
+	/// without statistics only report the names of their column families.
+	fn stats(&self) -> Result<DatastoreStats, Error> {
+		let column_families = self
+			.column_family_options()?
+			.into_iter()
+			.map(|options| ColumnFamilyStats {
+				name: options.name,
+				..Default::default()
+			})
+			.collect();
+		Ok(DatastoreStats {
+			disk_size: disk_size(Path::new(self.path())),
+			column_families,
+		})
+	}
 }
@@ -0,0 +1,69 @@
+use std::ops::Bound;
+
+use crate::{
+	err::Error,
+	interface::{kv::Key, KeyValuePair},
+	model::{Page, SimpleTransaction, CF},
+};
+
+/// # Cursor
+/// Resumable iteration over the keys of a column family between two bounds, read in
+/// batches. The cursor keeps the last key read and the next batch is scanned after it, so
+/// a batch costs the same anywhere in the keyspace and no pairs are kept between batches.
+#[derive(Clone, Debug)]
+pub struct Cursor {
+	cf: CF,
+	start: Bound<Key>,
+	end: Bound<Key>,
+	reverse: bool,
+	last: Option<Key>,
+	done: bool,
+}
+
+impl Cursor {
+	pub fn new(cf: CF, start: Bound<Key>, end: Bound<Key>, reverse: bool) -> Self {
+		Cursor {
+			cf,
+			start,
+			end,
+			reverse,
+			last: None,
+			done: false,
+		}
+	}
+
+	/// Whether a batch may still hold pairs, false once a batch came back short
+	pub fn has_next(&self) -> bool {
+		!self.done
+	}
+
+	/// Read the next `size` pairs, from the largest key when the cursor is reversed
+	pub async fn next_batch<T: SimpleTransaction>(
+		&mut self,
+		tx: &T,
+		size: usize,
+	) -> Result<Vec<KeyValuePair>, Error> {
+		if self.done || size == 0 {
+			return Ok(vec![]);
+		}
+		let (start, end) = match (&self.last, self.reverse) {
+			(Some(last), false) => (Bound::Excluded(last.clone()), self.end.clone()),
+			(Some(last), true) => (self.start.clone(), Bound::Excluded(last.clone())),
+			(None, _) => (self.start.clone(), self.end.clone()),
+		};
+		let page = Page {
+			offset: 0,
+			limit: Some(size),
+		};
+		let pairs = tx
+			.scan_range(self.cf.clone(), start, end, page, self.reverse)
+			.await?
+			.into_iter()
+			.collect::<Result<Vec<_>, _>>()?;
+		self.done = pairs.len() < size;
+		if let Some((key, _)) = pairs.last() {
+			self.last = Some(key.clone());
+		}
+		Ok(pairs)
+	}
+}
@@ -1,6 +1,8 @@
 /// Model
 mod adapter;
+mod cursor;
 mod tx;
 
 pub use adapter::*;
+pub use cursor::*;
 pub use tx::*;
@@ -5,13 +5,32 @@ use crate::{
 		KeyValuePair,
 	},
 	util::now,
+	Snapshot,
 };
 use async_trait::async_trait;
 use futures::lock::Mutex;
-use std::{pin::Pin, sync::Arc};
+use std::{
+	iter::{Skip, Take},
+	ops::Bound,
+	pin::Pin,
+	sync::Arc,
+};
 
 pub type CF = Option<Vec<u8>>;
 
+/// Window of an iteration, applied on the backend iterator before pairs are collected
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Page {
+	pub offset: usize,
+	pub limit: Option<usize>,
+}
+
+impl Page {
+	pub fn apply<I: Iterator>(&self, iterator: I) -> Take<Skip<I>> {
+		iterator.skip(self.offset).take(self.limit.unwrap_or(usize::MAX))
+	}
+}
+
 /// # Distributed Database Transaction
 /// ## Atomically reference counter
 /// Shared references in Rust disallow mutation by default, and Arc is no exception: you cannot
@@ -61,9 +80,18 @@ pub trait SimpleTransaction {
 	// Count number of items
 	async fn count(&mut self, cf: CF) -> Result<usize, Error>;
 
+	/// Estimate the number of items from storage statistics without iterating them
+	async fn estimate_count(&mut self, cf: CF) -> Result<usize, Error>;
+
 	// Commit a transaction
 	async fn commit(&mut self) -> Result<(), Error>;
 
+	/// Prepare the transaction for a two-phase commit: its writes are persisted and it can
+	/// only be committed or cancelled afterwards, also after a restart of the datastore
+	async fn prepare(&mut self) -> Result<(), Error> {
+		Err(Error::TxPrepareUnsupported)
+	}
+
 	// Check if a key exists
 	async fn exi<K: Into<Key> + Send>(&self, cf: CF, key: K) -> Result<bool, Error>;
 
@@ -89,6 +117,24 @@ pub trait SimpleTransaction {
 	/// Delete a key
 	async fn del<K: Into<Key> + Send>(&mut self, cf: CF, key: K) -> Result<(), Error>;
 
+	/// Delete the keys between the `start` and `end` bounds, returns the number of deleted
+	/// keys. Keys are deleted one by one so write hooks and throttling apply to each of them.
+	async fn delete_range(
+		&mut self,
+		cf: CF,
+		start: Bound<Key>,
+		end: Bound<Key>,
+	) -> Result<usize, Error> {
+		let pairs = self.range_iterate(cf.clone(), start, end).await?;
+		let mut deleted = 0;
+		for pair in pairs {
+			let (key, _) = pair?;
+			self.del(cf.clone(), key).await?;
+			deleted += 1;
+		}
+		Ok(deleted)
+	}
+
 	// OPTIONAL Fetch multiple keys from the database
 	async fn multi_get<K: Into<Key> + Send + AsRef<[u8]>>(
 		&self,
@@ -101,6 +147,17 @@ pub trait SimpleTransaction {
 	// Iterate elements in key value store
 	async fn iterate(&self, cf: CF) -> Result<Vec<Result<KeyValuePair, Error>>, Error>;
 
+	/// Iterate elements from the largest key to the smallest with the backend reverse iterator
+	async fn reverse_iterate(&self, cf: CF) -> Result<Vec<Result<KeyValuePair, Error>>, Error>;
+
+	/// Iterate the elements of `page`, from the largest key when `reverse` is set
+	async fn page_iterate(
+		&self,
+		cf: CF,
+		page: Page,
+		reverse: bool,
+	) -> Result<Vec<Result<KeyValuePair, Error>>, Error>;
+
 	// Iterate elements with prefixx in key value store
 	async fn prefix_iterate<P: Into<Key> + Send>(
 		&self,
@@ -114,4 +171,30 @@ pub trait SimpleTransaction {
 		cf: CF,
 		suffix: S,
 	) -> Result<Vec<Result<KeyValuePair, Error>>, Error>;
+
+	/// Iterate elements with keys between the `start` and `end` bounds, in key order
+	async fn range_iterate(
+		&self,
+		cf: CF,
+		start: Bound<Key>,
+		end: Bound<Key>,
+	) -> Result<Vec<Result<KeyValuePair, Error>>, Error>;
+
+	/// Iterate the elements of `page` among the keys between the `start` and `end` bounds,
+	/// from the largest key when `reverse` is set. The iteration stops at the end of the page.
+	async fn scan_range(
+		&self,
+		cf: CF,
+		start: Bound<Key>,
+		end: Bound<Key>,
+		page: Page,
+		reverse: bool,
+	) -> Result<Vec<Result<KeyValuePair, Error>>, Error>;
+
+	/// Copy the column families `cfs` at one point in time, scans of the snapshot ignore
+	/// the writes committed after it. The default copies them through the reads of the
+	/// transaction, which are isolated on Redb and LMDB.
+	async fn snapshot(&self, cfs: Vec<CF>) -> Result<Snapshot, Error> {
+		Snapshot::take(self, cfs).await
+	}
 }
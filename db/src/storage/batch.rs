@@ -0,0 +1,125 @@
+use crate::{
+	interface::{Key, Val},
+	Datastore, Error, SimpleTransaction, Transaction, CF,
+};
+
+/// # Write batch
+/// Bulk writes committed by batches of `size` items instead of one transaction per write.
+/// Mutations go to a write transaction opened on the first one, an item is one or several
+/// mutations ended by `advance` and the transaction is committed every `size` items, so the
+/// mutations of an item are always committed together. `commit` writes the last items.
+/// The write transaction stays open between two commits, so the batch holds the writer lock
+/// of backends with a single writer, like ReDB where every transaction is a write one:
+/// opening another transaction on the datastore waits until the batch is committed or
+/// dropped. Dropping the batch discards its uncommitted items.
+pub struct WriteBatch<'a> {
+	ds: &'a Datastore,
+	size: usize,
+	tx: Option<Transaction>,
+	pending: usize,
+	committed: usize,
+}
+
+impl<'a> WriteBatch<'a> {
+	pub fn new(ds: &'a Datastore, size: usize) -> Self {
+		WriteBatch {
+			ds,
+			size: size.max(1),
+			tx: None,
+			pending: 0,
+			committed: 0,
+		}
+	}
+
+	async fn tx(&mut self) -> Result<&mut Transaction, Error> {
+		if self.tx.is_none() {
+			self.tx = Some(self.ds.transaction(true).await?);
+		}
+		Ok(self.tx.as_mut().unwrap())
+	}
+
+	/// Insert or update a key in the current batch
+	pub async fn set<K: Into<Key> + Send, V: Into<Val> + Send>(
+		&mut self,
+		cf: CF,
+		key: K,
+		val: V,
+	) -> Result<(), Error> {
+		self.tx().await?.set(cf, key, val).await
+	}
+
+	/// Delete a key in the current batch
+	pub async fn del<K: Into<Key> + Send>(&mut self, cf: CF, key: K) -> Result<(), Error> {
+		self.tx().await?.del(cf, key).await
+	}
+
+	/// End an item, the batch is committed once it holds `size` items. Returns whether it
+	/// was committed.
+	pub async fn advance(&mut self) -> Result<bool, Error> {
+		self.pending += 1;
+		if self.pending < self.size {
+			return Ok(false);
+		}
+		self.commit().await
+	}
+
+	/// Commit the items of the current batch, if any. Returns whether a batch was committed.
+	pub async fn commit(&mut self) -> Result<bool, Error> {
+		let pending = std::mem::take(&mut self.pending);
+		match self.tx.take() {
+			Some(mut tx) => {
+				tx.commit().await?;
+				self.committed += pending;
+				Ok(true)
+			}
+			None => Ok(false),
+		}
+	}
+
+	/// Number of items committed so far
+	pub fn committed(&self) -> usize {
+		self.committed
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use crate::{util::generate_path, Datastore, SimpleTransaction};
+
+	use super::WriteBatch;
+
+	#[tokio::test]
+	async fn should_commit_by_batch() {
+		let db = Datastore::new(&generate_path("redb", None));
+		let cf = Some("test_suite:v1".into());
+
+		let mut batch = WriteBatch::new(&db, 2);
+		let mut commits = vec![];
+		for key in ["a", "b", "c"] {
+			batch.set(cf.clone(), key, "1").await.unwrap();
+			commits.push(batch.advance().await.unwrap());
+		}
+		assert_eq!(commits, vec![false, true, false]);
+		assert_eq!(batch.committed(), 2);
+		// The batch holds the write transaction until it is dropped
+		drop(batch);
+		{
+			let tx = db.transaction(false).await.unwrap();
+			assert!(tx.exi(cf.clone(), "b").await.unwrap());
+			assert!(!tx.exi(cf.clone(), "c").await.unwrap());
+		}
+
+		let mut batch = WriteBatch::new(&db, 2);
+		batch.set(cf.clone(), "c", "1").await.unwrap();
+		batch.del(cf.clone(), "a").await.unwrap();
+		assert!(!batch.advance().await.unwrap());
+		assert!(batch.commit().await.unwrap());
+		assert!(!batch.commit().await.unwrap());
+		assert_eq!(batch.committed(), 1);
+
+		let tx = db.transaction(false).await.unwrap();
+		assert!(!tx.exi(cf.clone(), "a").await.unwrap());
+		assert!(tx.exi(cf.clone(), "b").await.unwrap());
+		assert!(tx.exi(cf, "c").await.unwrap());
+	}
+}
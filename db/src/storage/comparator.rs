@@ -0,0 +1,78 @@
+use std::{cmp::Ordering, collections::HashMap, sync::RwLock};
+
+use lazy_static::lazy_static;
+
+/// Key ordering function. RocksDB only accepts plain functions as comparators.
+pub type CompareFn = fn(&[u8], &[u8]) -> Ordering;
+
+/// Name RocksDB persists for its default comparator
+pub const BYTEWISE_COMPARATOR: &str = "leveldb.BytewiseComparator";
+
+fn bytewise(a: &[u8], b: &[u8]) -> Ordering {
+	a.cmp(b)
+}
+
+fn reverse_lex(a: &[u8], b: &[u8]) -> Ordering {
+	b.cmp(a)
+}
+
+/// Unsigned big-endian integer of the first 8 bytes of a key, shorter keys included
+fn leading_u64(key: &[u8]) -> u64 {
+	key.iter().take(8).fold(0, |n, byte| (n << 8) | *byte as u64)
+}
+
+/// Compare keys as big-endian unsigned integers, so `[0x01]` sorts before `[0x00, 0x02]`.
+/// Keys longer than 8 bytes are compared by the integer of their first 8 bytes, then by
+/// the remaining bytes.
+fn u64_be(a: &[u8], b: &[u8]) -> Ordering {
+	let (a_len, b_len) = (a.len().min(8), b.len().min(8));
+	leading_u64(a)
+		.cmp(&leading_u64(b))
+		.then_with(|| a_len.cmp(&b_len))
+		.then_with(|| a[a_len..].cmp(&b[b_len..]))
+}
+
+lazy_static! {
+	static ref COMPARATORS: RwLock<HashMap<String, CompareFn>> = RwLock::new(HashMap::from([
+		("bytewise".to_string(), bytewise as CompareFn),
+		(BYTEWISE_COMPARATOR.to_string(), bytewise as CompareFn),
+		("reverse-lex".to_string(), reverse_lex as CompareFn),
+		("rocksdb.ReverseBytewiseComparator".to_string(), reverse_lex as CompareFn),
+		("u64-be".to_string(), u64_be as CompareFn),
+	]));
+}
+
+/// # Key comparators
+/// Register a comparator under the name a database was created with. RocksDB databases
+/// persisting a non-default comparator name are opened with the registered comparator,
+/// opening them fails when no comparator is registered under that name.
+pub fn register_comparator(name: &str, compare: CompareFn) {
+	COMPARATORS.write().unwrap().insert(name.to_string(), compare);
+}
+
+/// Fetch a registered or built-in comparator: `bytewise`, `reverse-lex` or `u64-be`
+pub fn get_comparator(name: &str) -> Option<CompareFn> {
+	COMPARATORS.read().unwrap().get(name).copied()
+}
+
+#[cfg(test)]
+mod test {
+	use std::cmp::Ordering;
+
+	use super::{get_comparator, register_comparator};
+
+	#[test]
+	fn should_order_with_built_in_comparators() {
+		let u64_be = get_comparator("u64-be").unwrap();
+		assert_eq!(u64_be(&[0x01], &[0x00, 0x02]), Ordering::Less);
+		assert_eq!(u64_be(&[0x00, 0x01], &[0x01]), Ordering::Greater);
+		assert_eq!(u64_be(&[0; 9], &[0; 10]), Ordering::Less);
+
+		let reverse = get_comparator("reverse-lex").unwrap();
+		assert_eq!(reverse(b"a", b"b"), Ordering::Greater);
+
+		register_comparator("by-length", |a, b| a.len().cmp(&b.len()));
+		assert_eq!(get_comparator("by-length").unwrap()(b"bb", b"a"), Ordering::Greater);
+		assert!(get_comparator("unknown").is_none());
+	}
+}
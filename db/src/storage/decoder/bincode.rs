@@ -0,0 +1,88 @@
+use std::sync::Arc;
+
+use serde_json::{json, Map, Value};
+
+use super::{hex, text, DecodeFn, Reader};
+use crate::err::Error;
+
+/// Field types of a bincode encoded struct
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BincodeType {
+	Bool,
+	U8,
+	U16,
+	U32,
+	U64,
+	I8,
+	I16,
+	I32,
+	I64,
+	F32,
+	F64,
+	String,
+	Bytes,
+}
+
+impl BincodeType {
+	pub fn from_string(s: &str) -> Option<BincodeType> {
+		Some(match s.to_lowercase().as_str() {
+			"bool" => BincodeType::Bool,
+			"u8" => BincodeType::U8,
+			"u16" => BincodeType::U16,
+			"u32" => BincodeType::U32,
+			"u64" => BincodeType::U64,
+			"i8" => BincodeType::I8,
+			"i16" => BincodeType::I16,
+			"i32" => BincodeType::I32,
+			"i64" => BincodeType::I64,
+			"f32" => BincodeType::F32,
+			"f64" => BincodeType::F64,
+			"string" => BincodeType::String,
+			"bytes" => BincodeType::Bytes,
+			_ => return None,
+		})
+	}
+}
+
+fn bincode_value(reader: &mut Reader, kind: BincodeType) -> Result<Value, String> {
+	Ok(match kind {
+		BincodeType::Bool => match reader.byte()? {
+			0 => json!(false),
+			1 => json!(true),
+			byte => return Err(format!("Invalid bincode bool {}", byte)),
+		},
+		BincodeType::U8 => json!(reader.byte()?),
+		BincodeType::U16 => json!(u16::from_le_bytes(reader.array()?)),
+		BincodeType::U32 => json!(u32::from_le_bytes(reader.array()?)),
+		BincodeType::U64 => json!(u64::from_le_bytes(reader.array()?)),
+		BincodeType::I8 => json!(reader.byte()? as i8),
+		BincodeType::I16 => json!(i16::from_le_bytes(reader.array()?)),
+		BincodeType::I32 => json!(i32::from_le_bytes(reader.array()?)),
+		BincodeType::I64 => json!(i64::from_le_bytes(reader.array()?)),
+		BincodeType::F32 => json!(f32::from_le_bytes(reader.array()?)),
+		BincodeType::F64 => json!(f64::from_le_bytes(reader.array()?)),
+		BincodeType::String | BincodeType::Bytes => {
+			let size = u64::from_le_bytes(reader.array()?) as usize;
+			let bytes = reader.take(size)?;
+			match kind {
+				BincodeType::String => text(bytes)?,
+				_ => hex(bytes),
+			}
+		}
+	})
+}
+
+/// Build a decoder of a struct encoded by bincode 1 with its default options: fixed size
+/// little endian integers and `u64` lengths. Bincode is not self-describing, the fields
+/// are given in declaration order.
+pub fn bincode_decoder(fields: Vec<(String, BincodeType)>) -> DecodeFn {
+	Arc::new(move |bytes: &[u8]| {
+		let mut reader = Reader::new(bytes);
+		let mut object = Map::new();
+		for (name, kind) in fields.iter() {
+			let value = bincode_value(&mut reader, *kind).map_err(Error::Decode)?;
+			object.insert(name.clone(), value);
+		}
+		reader.finish(Value::Object(object)).map_err(Error::Decode)
+	})
+}
@@ -0,0 +1,121 @@
+use serde_json::{json, Map, Value};
+
+use super::{hex, object_key, text, Reader};
+use crate::err::Error;
+
+/// Marker closing an indefinite length item
+const BREAK: u8 = 0xff;
+
+/// Argument of an initial byte, `None` for an indefinite length
+fn argument(reader: &mut Reader, info: u8) -> Result<Option<u64>, String> {
+	match info {
+		0..=23 => Ok(Some(info as u64)),
+		24..=27 => reader.uint(1 << (info - 24)).map(Some),
+		31 => Ok(None),
+		_ => Err(format!("Invalid CBOR additional information {}", info)),
+	}
+}
+
+/// Whether the next byte closes an indefinite length item, which is then consumed
+fn at_break(reader: &mut Reader) -> Result<bool, String> {
+	match reader.peek() {
+		Some(BREAK) => reader.byte().map(|_| true),
+		Some(_) => Ok(false),
+		None => Err("Missing CBOR break".to_string()),
+	}
+}
+
+/// Whether an array or map of `size` items, `None` for an indefinite length, has an item
+/// after the `read` ones. The break of an indefinite length is only consumed there.
+fn has_item(reader: &mut Reader, size: Option<u64>, read: usize) -> Result<bool, String> {
+	match size {
+		Some(size) => Ok((read as u64) < size),
+		None => Ok(!at_break(reader)?),
+	}
+}
+
+/// Definite length chunks of an indefinite byte or text string
+fn chunks(reader: &mut Reader, major: u8) -> Result<Vec<u8>, String> {
+	let mut data = vec![];
+	while !at_break(reader)? {
+		let initial = reader.byte()?;
+		if initial >> 5 != major {
+			return Err("Invalid chunk of an indefinite CBOR string".to_string());
+		}
+		let size = argument(reader, initial & 0x1f)?.ok_or("Nested indefinite CBOR string")?;
+		data.extend_from_slice(reader.take(size as usize)?);
+	}
+	Ok(data)
+}
+
+/// Half precision float
+fn f16(bits: u16) -> f64 {
+	let (exponent, fraction) = ((bits >> 10) & 0x1f, (bits & 0x3ff) as f64);
+	let magnitude = match exponent {
+		0 => fraction * 2f64.powi(-24),
+		31 if fraction == 0.0 => f64::INFINITY,
+		31 => f64::NAN,
+		_ => (1.0 + fraction / 1024.0) * 2f64.powi(exponent as i32 - 15),
+	};
+	match bits >> 15 {
+		1 => -magnitude,
+		_ => magnitude,
+	}
+}
+
+/// Simple values and floats of major type 7
+fn cbor_simple(reader: &mut Reader, info: u8) -> Result<Value, String> {
+	Ok(match info {
+		20 => json!(false),
+		21 => json!(true),
+		22 | 23 => Value::Null,
+		24 => json!({ "simple": reader.byte()? }),
+		25 => json!(f16(reader.uint(2)? as u16)),
+		26 => json!(f32::from_be_bytes(reader.array()?)),
+		27 => json!(f64::from_be_bytes(reader.array()?)),
+		0..=19 => json!({ "simple": info }),
+		_ => return Err(format!("Unexpected CBOR simple value {}", info)),
+	})
+}
+
+fn cbor_value(reader: &mut Reader) -> Result<Value, String> {
+	let initial = reader.byte()?;
+	let (major, info) = (initial >> 5, initial & 0x1f);
+	if major == 7 {
+		return cbor_simple(reader, info);
+	}
+	Ok(match (major, argument(reader, info)?) {
+		(0, Some(n)) => json!(n),
+		(1, Some(n)) if n <= i64::MAX as u64 => json!(-1 - n as i64),
+		(1, Some(n)) => json!(-1.0 - n as f64),
+		(2, Some(size)) => hex(reader.take(size as usize)?),
+		(2, None) => hex(&chunks(reader, 2)?),
+		(3, Some(size)) => text(reader.take(size as usize)?)?,
+		(3, None) => text(&chunks(reader, 3)?)?,
+		(4, size) => {
+			let mut items = vec![];
+			while has_item(reader, size, items.len())? {
+				items.push(cbor_value(reader)?);
+			}
+			Value::Array(items)
+		}
+		(5, size) => {
+			let (mut object, mut read) = (Map::new(), 0);
+			while has_item(reader, size, read)? {
+				let key = object_key(cbor_value(reader)?);
+				object.insert(key, cbor_value(reader)?);
+				read += 1;
+			}
+			Value::Object(object)
+		}
+		// Tags are kept with the tagged item
+		(6, Some(tag)) => json!({ "tag": tag, "value": cbor_value(reader)? }),
+		_ => return Err(format!("Indefinite length of CBOR major type {}", major)),
+	})
+}
+
+/// Decode a CBOR document
+pub fn decode_cbor(bytes: &[u8]) -> Result<Value, Error> {
+	let mut reader = Reader::new(bytes);
+	cbor_value(&mut reader).and_then(|value| reader.finish(value)).map_err(Error::Decode)
+}
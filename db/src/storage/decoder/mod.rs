@@ -0,0 +1,221 @@
+/// Value decoders
+mod bincode;
+mod cbor;
+mod msgpack;
+mod protobuf;
+
+pub use bincode::*;
+pub use cbor::*;
+pub use msgpack::*;
+pub use protobuf::*;
+
+use std::{
+	collections::HashMap,
+	sync::{Arc, RwLock},
+};
+
+use lazy_static::lazy_static;
+use serde_json::Value;
+
+use crate::err::Error;
+
+/// Decode value bytes into a JSON document
+pub type DecodeFn = Arc<dyn Fn(&[u8]) -> Result<Value, Error> + Send + Sync>;
+
+lazy_static! {
+	static ref DECODERS: RwLock<HashMap<String, DecodeFn>> = RwLock::new(HashMap::from([
+		("msgpack".to_string(), Arc::new(decode_msgpack) as DecodeFn),
+		("cbor".to_string(), Arc::new(decode_cbor) as DecodeFn),
+		("protobuf".to_string(), Arc::new(decode_protobuf) as DecodeFn),
+	]));
+}
+
+/// # Value decoders
+/// Register a decoder of self-describing or schema based value encodings under a name.
+/// Schema based decoders are built with `bincode_decoder` or `protobuf_decoder`.
+pub fn register_decoder(name: &str, decode: DecodeFn) {
+	DECODERS.write().unwrap().insert(name.to_string(), decode);
+}
+
+/// Fetch a registered or built-in decoder: `msgpack`, `cbor` or `protobuf`. The built-in
+/// `protobuf` decoder reads the wire format without schema, fields are named by number.
+pub fn get_decoder(name: &str) -> Option<DecodeFn> {
+	DECODERS.read().unwrap().get(name).cloned()
+}
+
+/// Names of the registered and built-in decoders, sorted
+pub fn decoder_names() -> Vec<String> {
+	let mut names: Vec<_> = DECODERS.read().unwrap().keys().cloned().collect();
+	names.sort();
+	names
+}
+
+/// Cursor over the bytes of a decoded value
+struct Reader<'a> {
+	bytes: &'a [u8],
+	position: usize,
+}
+
+impl<'a> Reader<'a> {
+	fn new(bytes: &'a [u8]) -> Self {
+		Reader {
+			bytes,
+			position: 0,
+		}
+	}
+
+	fn is_empty(&self) -> bool {
+		self.position == self.bytes.len()
+	}
+
+	fn peek(&self) -> Option<u8> {
+		self.bytes.get(self.position).copied()
+	}
+
+	fn take(&mut self, size: usize) -> Result<&'a [u8], String> {
+		let end = self.position.checked_add(size).filter(|end| *end <= self.bytes.len());
+		let end =
+			end.ok_or_else(|| format!("Unexpected end of value at byte {}", self.position))?;
+		let bytes = &self.bytes[self.position..end];
+		self.position = end;
+		Ok(bytes)
+	}
+
+	fn byte(&mut self) -> Result<u8, String> {
+		Ok(self.take(1)?[0])
+	}
+
+	fn array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+		Ok(self.take(N)?.try_into().unwrap())
+	}
+
+	/// Big endian unsigned integer of `size` bytes, up to 8
+	fn uint(&mut self, size: usize) -> Result<u64, String> {
+		Ok(self.take(size)?.iter().fold(0, |n, byte| (n << 8) | *byte as u64))
+	}
+
+	/// Big endian two's complement integer of `size` bytes, up to 8
+	fn int(&mut self, size: usize) -> Result<i64, String> {
+		let shift = 64 - 8 * size as u32;
+		Ok(((self.uint(size)? << shift) as i64) >> shift)
+	}
+
+	fn len(&mut self, size: usize) -> Result<usize, String> {
+		Ok(self.uint(size)? as usize)
+	}
+
+	fn varint(&mut self) -> Result<u64, String> {
+		let mut value = 0u64;
+		for shift in (0..64).step_by(7) {
+			let byte = self.byte()?;
+			value |= ((byte & 0x7f) as u64) << shift;
+			if byte & 0x80 == 0 {
+				return Ok(value);
+			}
+		}
+		Err("Varint longer than 10 bytes".to_string())
+	}
+
+	/// The decoded value when every byte was read
+	fn finish(&self, value: Value) -> Result<Value, String> {
+		match self.bytes.len() - self.position {
+			0 => Ok(value),
+			trailing => Err(format!("{} trailing bytes", trailing)),
+		}
+	}
+}
+
+/// Bytes are shown as `0x` prefixed hex in decoded documents
+fn hex(bytes: &[u8]) -> Value {
+	let digits: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+	Value::String(format!("0x{}", digits))
+}
+
+/// JSON object keys are strings, other keys are written as JSON
+fn object_key(key: Value) -> String {
+	match key {
+		Value::String(key) => key,
+		key => key.to_string(),
+	}
+}
+
+fn text(bytes: &[u8]) -> Result<Value, String> {
+	String::from_utf8(bytes.to_vec())
+		.map(Value::String)
+		.map_err(|_| "Invalid UTF-8 text".to_string())
+}
+
+#[cfg(test)]
+mod test {
+	use serde_json::json;
+
+	use super::{bincode_decoder, decoder_names, get_decoder, protobuf_decoder, BincodeType};
+
+	#[test]
+	fn should_decode_msgpack() {
+		let decode = get_decoder("msgpack").unwrap();
+		// {"id": 7, "tags": ["a", -1], "ok": true}
+		let bytes = [
+			0x83, 0xa2, b'i', b'd', 0x07, 0xa4, b't', b'a', b'g', b's', 0x92, 0xa1, b'a', 0xff,
+			0xa2, b'o', b'k', 0xc3,
+		];
+		assert_eq!(decode(&bytes).unwrap(), json!({"id": 7, "tags": ["a", -1], "ok": true}));
+		assert_eq!(decode(&[0xcd, 0x01, 0x00]).unwrap(), json!(256));
+		assert!(decode(&[0x92, 0x01]).is_err());
+	}
+
+	#[test]
+	fn should_decode_cbor() {
+		let decode = get_decoder("cbor").unwrap();
+		// {"a": [1, -2, 0.5]} with a half precision float
+		let bytes = [0xa1, 0x61, b'a', 0x83, 0x01, 0x21, 0xf9, 0x38, 0x00];
+		assert_eq!(decode(&bytes).unwrap(), json!({"a": [1, -2, 0.5]}));
+		// Indefinite length text
+		assert_eq!(decode(&[0x7f, 0x61, b'a', 0x61, b'b', 0xff]).unwrap(), json!("ab"));
+		assert!(decode(&[0x01, 0x02]).is_err());
+	}
+
+	#[test]
+	fn should_decode_protobuf() {
+		// id = 150, name = "ab", scores = [1, 2] packed, nested { flag = true }
+		let bytes = [
+			0x08, 0x96, 0x01, 0x12, 0x02, b'a', b'b', 0x1a, 0x02, 0x01, 0x02, 0x22, 0x02, 0x08,
+			0x01,
+		];
+		let raw = get_decoder("protobuf").unwrap()(&bytes).unwrap();
+		assert_eq!(raw["1"], json!(150));
+		assert_eq!(raw["2"], json!("ab"));
+
+		let proto = r#"
+			syntax = "proto3";
+			// A user
+			message User {
+				int64 id = 1;
+				string name = 2;
+				repeated uint32 scores = 3;
+				Flags flags = 4;
+				message Flags { bool flag = 1; }
+			}
+		"#;
+		let decode = protobuf_decoder(proto, "User").unwrap();
+		assert_eq!(
+			decode(&bytes).unwrap(),
+			json!({"id": 150, "name": "ab", "scores": [1, 2], "flags": {"flag": true}})
+		);
+		assert!(protobuf_decoder(proto, "Group").is_err());
+	}
+
+	#[test]
+	fn should_decode_bincode() {
+		let fields = vec![
+			("id".to_string(), BincodeType::U32),
+			("name".to_string(), BincodeType::String),
+			("active".to_string(), BincodeType::Bool),
+		];
+		let decode = bincode_decoder(fields);
+		let bytes = [7, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, b'a', b'b', 1];
+		assert_eq!(decode(&bytes).unwrap(), json!({"id": 7, "name": "ab", "active": true}));
+		assert!(decode(&bytes[..6]).is_err());
+		assert!(decoder_names().contains(&"cbor".to_string()));
+	}
+}
@@ -0,0 +1,73 @@
+use serde_json::{json, Map, Value};
+
+use super::{hex, object_key, text, Reader};
+use crate::err::Error;
+
+fn msgpack_array(reader: &mut Reader, size: usize) -> Result<Value, String> {
+	let mut items = vec![];
+	for _ in 0..size {
+		items.push(msgpack_value(reader)?);
+	}
+	Ok(Value::Array(items))
+}
+
+fn msgpack_map(reader: &mut Reader, size: usize) -> Result<Value, String> {
+	let mut object = Map::new();
+	for _ in 0..size {
+		let key = object_key(msgpack_value(reader)?);
+		object.insert(key, msgpack_value(reader)?);
+	}
+	Ok(Value::Object(object))
+}
+
+/// Extension types are shown with their type and data
+fn msgpack_ext(reader: &mut Reader, size: usize) -> Result<Value, String> {
+	let kind = reader.byte()? as i8;
+	Ok(json!({ "type": kind, "data": hex(reader.take(size)?) }))
+}
+
+fn msgpack_value(reader: &mut Reader) -> Result<Value, String> {
+	let marker = reader.byte()?;
+	Ok(match marker {
+		0x00..=0x7f => json!(marker),
+		0x80..=0x8f => msgpack_map(reader, (marker & 0x0f) as usize)?,
+		0x90..=0x9f => msgpack_array(reader, (marker & 0x0f) as usize)?,
+		0xa0..=0xbf => text(reader.take((marker & 0x1f) as usize)?)?,
+		0xc0 => Value::Null,
+		0xc1 => return Err("Invalid MessagePack marker 0xc1".to_string()),
+		0xc2 => json!(false),
+		0xc3 => json!(true),
+		0xc4..=0xc6 => {
+			let size = reader.len(1 << (marker - 0xc4))?;
+			hex(reader.take(size)?)
+		}
+		0xc7..=0xc9 => {
+			let size = reader.len(1 << (marker - 0xc7))?;
+			msgpack_ext(reader, size)?
+		}
+		0xca => json!(f32::from_be_bytes(reader.array()?)),
+		0xcb => json!(f64::from_be_bytes(reader.array()?)),
+		0xcc..=0xcf => json!(reader.uint(1 << (marker - 0xcc))?),
+		0xd0..=0xd3 => json!(reader.int(1 << (marker - 0xd0))?),
+		0xd4..=0xd8 => msgpack_ext(reader, 1 << (marker - 0xd4))?,
+		0xd9..=0xdb => {
+			let size = reader.len(1 << (marker - 0xd9))?;
+			text(reader.take(size)?)?
+		}
+		0xdc | 0xdd => {
+			let size = reader.len(2 << (marker - 0xdc))?;
+			msgpack_array(reader, size)?
+		}
+		0xde | 0xdf => {
+			let size = reader.len(2 << (marker - 0xde))?;
+			msgpack_map(reader, size)?
+		}
+		0xe0..=0xff => json!(marker as i8),
+	})
+}
+
+/// Decode a MessagePack document
+pub fn decode_msgpack(bytes: &[u8]) -> Result<Value, Error> {
+	let mut reader = Reader::new(bytes);
+	msgpack_value(&mut reader).and_then(|value| reader.finish(value)).map_err(Error::Decode)
+}
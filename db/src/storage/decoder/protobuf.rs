@@ -0,0 +1,396 @@
+use std::{collections::HashMap, sync::Arc};
+
+use serde_json::{json, Map, Value};
+
+use super::{hex, object_key, DecodeFn, Reader};
+use crate::err::Error;
+
+/// Value of a field as read from the wire, before its type is known
+enum Wire<'a> {
+	Varint(u64),
+	Fixed64([u8; 8]),
+	Bytes(&'a [u8]),
+	Fixed32([u8; 4]),
+}
+
+/// Fields of a message in wire order, by field number
+fn wire_fields(bytes: &[u8]) -> Result<Vec<(u64, Wire)>, String> {
+	let mut reader = Reader::new(bytes);
+	let mut fields = vec![];
+	while !reader.is_empty() {
+		let tag = reader.varint()?;
+		let (number, wire) = (tag >> 3, tag & 0x07);
+		if number == 0 {
+			return Err("Invalid protobuf field number 0".to_string());
+		}
+		let value = match wire {
+			0 => Wire::Varint(reader.varint()?),
+			1 => Wire::Fixed64(reader.array()?),
+			2 => {
+				let size = reader.varint()? as usize;
+				Wire::Bytes(reader.take(size)?)
+			}
+			5 => Wire::Fixed32(reader.array()?),
+			_ => return Err(format!("Unsupported protobuf wire type {}", wire)),
+		};
+		fields.push((number, value));
+	}
+	Ok(fields)
+}
+
+/// Fields read without schema: length delimited fields are nested messages when they
+/// parse as such, then text, then bytes
+fn raw_value(wire: &Wire) -> Value {
+	match wire {
+		Wire::Varint(n) => json!(n),
+		Wire::Fixed64(bytes) => json!(u64::from_le_bytes(*bytes)),
+		Wire::Fixed32(bytes) => json!(u32::from_le_bytes(*bytes)),
+		Wire::Bytes(bytes) => match raw_message(bytes) {
+			Ok(message) if !bytes.is_empty() => message,
+			_ => match std::str::from_utf8(bytes) {
+				Ok(text) => json!(text),
+				Err(_) => hex(bytes),
+			},
+		},
+	}
+}
+
+/// Add a field to a decoded message, fields seen more than once become arrays
+fn insert_field(object: &mut Map<String, Value>, name: String, value: Value, repeated: bool) {
+	match object.get_mut(&name) {
+		Some(Value::Array(items)) => items.push(value),
+		Some(previous) => *previous = Value::Array(vec![previous.take(), value]),
+		None if repeated => {
+			object.insert(name, Value::Array(vec![value]));
+		}
+		None => {
+			object.insert(name, value);
+		}
+	}
+}
+
+fn raw_message(bytes: &[u8]) -> Result<Value, String> {
+	let mut object = Map::new();
+	for (number, wire) in wire_fields(bytes)? {
+		insert_field(&mut object, number.to_string(), raw_value(&wire), false);
+	}
+	Ok(Value::Object(object))
+}
+
+/// Decode a protobuf message without schema, fields are named by their number
+pub fn decode_protobuf(bytes: &[u8]) -> Result<Value, Error> {
+	raw_message(bytes).map_err(Error::Decode)
+}
+
+#[derive(Clone, Debug)]
+struct Field {
+	name: String,
+	number: u64,
+	kind: String,
+	repeated: bool,
+	/// Key type of a `map<K, V>` field, `kind` is then the value type
+	key: Option<String>,
+}
+
+/// Messages and enums of a `.proto` file. Names are kept fully qualified within the
+/// file, e.g. `User.Flags`, types are resolved by their last segment.
+#[derive(Default)]
+struct Schema {
+	messages: HashMap<String, Vec<Field>>,
+	enums: Vec<String>,
+}
+
+/// Words and symbols of a `.proto` file, comments left out
+fn proto_tokens(proto: &str) -> Vec<String> {
+	let mut tokens = vec![];
+	let mut chars = proto.chars().peekable();
+	while let Some(c) = chars.next() {
+		match c {
+			'/' if chars.peek() == Some(&'/') => {
+				for c in chars.by_ref() {
+					if c == '\n' {
+						break;
+					}
+				}
+			}
+			'/' if chars.peek() == Some(&'*') => {
+				chars.next();
+				let mut previous = ' ';
+				for c in chars.by_ref() {
+					if previous == '*' && c == '/' {
+						break;
+					}
+					previous = c;
+				}
+			}
+			'"' | '\'' => {
+				let text: String = chars.by_ref().take_while(|q| *q != c).collect();
+				tokens.push(format!("\"{}\"", text));
+			}
+			c if c.is_alphanumeric() || c == '_' || c == '.' => {
+				let mut word = c.to_string();
+				while let Some(&c) =
+					chars.peek().filter(|c| c.is_alphanumeric() || **c == '_' || **c == '.')
+				{
+					word.push(c);
+					chars.next();
+				}
+				tokens.push(word);
+			}
+			c if c.is_whitespace() => {}
+			c => tokens.push(c.to_string()),
+		}
+	}
+	tokens
+}
+
+struct ProtoParser {
+	tokens: Vec<String>,
+	position: usize,
+}
+
+impl ProtoParser {
+	fn next(&mut self) -> Result<String, String> {
+		let token = self.tokens.get(self.position).cloned();
+		self.position += 1;
+		token.ok_or_else(|| "Unexpected end of the .proto file".to_string())
+	}
+
+	fn expect(&mut self, expected: &str) -> Result<(), String> {
+		match self.next()? {
+			token if token == expected => Ok(()),
+			token => Err(format!("Expected {} in the .proto file, found {}", expected, token)),
+		}
+	}
+
+	/// Skip a statement up to its `;`, or a block with its nested blocks
+	fn skip(&mut self) -> Result<(), String> {
+		let mut depth = 0;
+		loop {
+			match self.next()?.as_str() {
+				";" if depth == 0 => return Ok(()),
+				"{" => depth += 1,
+				"}" if depth == 1 => return Ok(()),
+				"}" => depth -= 1,
+				_ => {}
+			}
+		}
+	}
+
+	fn definitions(&mut self, schema: &mut Schema, scope: &str) -> Result<(), String> {
+		while self.position < self.tokens.len() {
+			match self.tokens[self.position].as_str() {
+				"message" => self.message(schema, scope)?,
+				"enum" => {
+					self.position += 1;
+					schema.enums.push(self.next()?);
+					self.skip()?;
+				}
+				_ => self.skip()?,
+			}
+		}
+		Ok(())
+	}
+
+	fn message(&mut self, schema: &mut Schema, scope: &str) -> Result<(), String> {
+		self.expect("message")?;
+		let name = match scope {
+			"" => self.next()?,
+			scope => format!("{}.{}", scope, self.next()?),
+		};
+		self.expect("{")?;
+		let mut fields = vec![];
+		loop {
+			match self.tokens.get(self.position).map(|t| t.as_str()) {
+				Some("}") => {
+					self.position += 1;
+					break;
+				}
+				Some("message") => self.message(schema, &name)?,
+				Some("enum") => {
+					self.position += 1;
+					schema.enums.push(self.next()?);
+					self.skip()?;
+				}
+				// Fields of a oneof are fields of the message
+				Some("oneof") => {
+					self.position += 2;
+					self.expect("{")?;
+					while self.tokens.get(self.position).map(|t| t.as_str()) != Some("}") {
+						match self.tokens.get(self.position).map(|t| t.as_str()) {
+							Some("option") => self.skip()?,
+							_ => fields.push(self.field()?),
+						}
+					}
+					self.position += 1;
+				}
+				Some("option" | "reserved" | "extensions" | "extend") => self.skip()?,
+				Some(";") => self.position += 1,
+				Some(_) => fields.push(self.field()?),
+				None => return Err(format!("Unclosed message {}", name)),
+			}
+		}
+		schema.messages.insert(name, fields);
+		Ok(())
+	}
+
+	/// `[repeated|optional|required] type name = number [options];` or
+	/// `map<key, value> name = number;`
+	fn field(&mut self) -> Result<Field, String> {
+		let mut token = self.next()?;
+		let repeated = token == "repeated";
+		if ["repeated", "optional", "required"].contains(&token.as_str()) {
+			token = self.next()?;
+		}
+		let (kind, key) = match token.as_str() {
+			"map" => {
+				self.expect("<")?;
+				let key = self.next()?;
+				self.expect(",")?;
+				let value = self.next()?;
+				self.expect(">")?;
+				(value, Some(key))
+			}
+			_ => (token, None),
+		};
+		let name = self.next()?;
+		self.expect("=")?;
+		let number = self.next()?;
+		let number = number.parse().map_err(|_| format!("Invalid field number {}", number))?;
+		self.skip()?;
+		Ok(Field {
+			name,
+			number,
+			repeated: repeated || key.is_some(),
+			kind,
+			key,
+		})
+	}
+}
+
+fn parse_proto(proto: &str) -> Result<Schema, String> {
+	let mut parser = ProtoParser {
+		tokens: proto_tokens(proto),
+		position: 0,
+	};
+	let mut schema = Schema::default();
+	parser.definitions(&mut schema, "")?;
+	Ok(schema)
+}
+
+/// Last segment of a type name, `.pkg.User` is `User`
+fn short_name(name: &str) -> &str {
+	name.rsplit('.').next().unwrap_or(name)
+}
+
+impl Schema {
+	fn message(&self, name: &str) -> Option<&Vec<Field>> {
+		self.messages.get(name).or_else(|| {
+			let name = short_name(name);
+			self.messages.iter().find(|(n, _)| short_name(n) == name).map(|(_, fields)| fields)
+		})
+	}
+
+	fn is_enum(&self, name: &str) -> bool {
+		self.enums.iter().any(|e| e == short_name(name))
+	}
+
+	/// Values of a field, several when a repeated scalar is packed
+	fn field_values(&self, kind: &str, wire: &Wire) -> Result<Vec<Value>, String> {
+		let zigzag = |n: u64| ((n >> 1) as i64) ^ -((n & 1) as i64);
+		let value = match (kind, wire) {
+			("int32" | "int64", Wire::Varint(n)) => json!(*n as i64),
+			("uint32" | "uint64", Wire::Varint(n)) => json!(n),
+			("sint32" | "sint64", Wire::Varint(n)) => json!(zigzag(*n)),
+			("bool", Wire::Varint(n)) => json!(*n != 0),
+			(kind, Wire::Varint(n)) if self.is_enum(kind) => json!(n),
+			("fixed64", Wire::Fixed64(b)) => json!(u64::from_le_bytes(*b)),
+			("sfixed64", Wire::Fixed64(b)) => json!(i64::from_le_bytes(*b)),
+			("double", Wire::Fixed64(b)) => json!(f64::from_le_bytes(*b)),
+			("fixed32", Wire::Fixed32(b)) => json!(u32::from_le_bytes(*b)),
+			("sfixed32", Wire::Fixed32(b)) => json!(i32::from_le_bytes(*b)),
+			("float", Wire::Fixed32(b)) => json!(f32::from_le_bytes(*b)),
+			("string", Wire::Bytes(b)) => json!(String::from_utf8_lossy(b)),
+			("bytes", Wire::Bytes(b)) => hex(b),
+			(kind, Wire::Bytes(b)) if self.message(kind).is_some() => {
+				self.decode(self.message(kind).unwrap(), b)?
+			}
+			// Packed repeated scalars
+			(kind, Wire::Bytes(b)) => return self.packed(kind, b),
+			(kind, _) => return Err(format!("Unexpected wire type for a {} field", kind)),
+		};
+		Ok(vec![value])
+	}
+
+	fn packed(&self, kind: &str, bytes: &[u8]) -> Result<Vec<Value>, String> {
+		let mut reader = Reader::new(bytes);
+		let mut values = vec![];
+		while !reader.is_empty() {
+			let wire = match kind {
+				"fixed64" | "sfixed64" | "double" => Wire::Fixed64(reader.array()?),
+				"fixed32" | "sfixed32" | "float" => Wire::Fixed32(reader.array()?),
+				_ => Wire::Varint(reader.varint()?),
+			};
+			values.extend(self.field_values(kind, &wire)?);
+		}
+		Ok(values)
+	}
+
+	fn decode(&self, fields: &[Field], bytes: &[u8]) -> Result<Value, String> {
+		let mut object = Map::new();
+		for (number, wire) in wire_fields(bytes)? {
+			let field = match fields.iter().find(|f| f.number == number) {
+				Some(field) => field,
+				None => {
+					insert_field(&mut object, number.to_string(), raw_value(&wire), false);
+					continue;
+				}
+			};
+			let values = match (&field.key, &wire) {
+				(Some(key), Wire::Bytes(entry)) => vec![self.map_entry(key, &field.kind, entry)?],
+				_ => self.field_values(&field.kind, &wire)?,
+			};
+			for value in values {
+				insert_field(&mut object, field.name.clone(), value, field.repeated);
+			}
+		}
+		// Map fields are read as entries, shown as an object
+		for field in fields.iter().filter(|f| f.key.is_some()) {
+			if let Some(Value::Array(entries)) = object.remove(&field.name) {
+				let entries = entries.into_iter().filter_map(|entry| match entry {
+					Value::Array(mut pair) if pair.len() == 2 => {
+						let value = pair.pop().unwrap();
+						Some((object_key(pair.pop().unwrap()), value))
+					}
+					_ => None,
+				});
+				object.insert(field.name.clone(), Value::Object(entries.collect()));
+			}
+		}
+		Ok(Value::Object(object))
+	}
+
+	/// Key and value of a map entry, fields 1 and 2 of the entry message
+	fn map_entry(&self, key: &str, value: &str, entry: &[u8]) -> Result<Value, String> {
+		let (mut key_value, mut value_value) = (Value::Null, Value::Null);
+		for (number, wire) in wire_fields(entry)? {
+			match number {
+				1 => key_value = self.field_values(key, &wire)?.pop().unwrap_or_default(),
+				2 => value_value = self.field_values(value, &wire)?.pop().unwrap_or_default(),
+				_ => {}
+			}
+		}
+		Ok(Value::Array(vec![key_value, value_value]))
+	}
+}
+
+/// Build a decoder of `message` defined in the source of a `.proto` file. Imports are not
+/// followed, the message and the types of its fields must be defined in the file.
+pub fn protobuf_decoder(proto: &str, message: &str) -> Result<DecodeFn, Error> {
+	let schema = parse_proto(proto).map_err(Error::Decode)?;
+	let fields = schema
+		.message(message)
+		.cloned()
+		.ok_or_else(|| Error::Decode(format!("No message {} in the .proto file", message)))?;
+	Ok(Arc::new(move |bytes: &[u8]| schema.decode(&fields, bytes).map_err(Error::Decode)))
+}
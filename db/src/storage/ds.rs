@@ -1,12 +1,37 @@
-use crate::model::DatastoreAdapter;
+use std::{fs, path::Path, sync::atomic::AtomicU64};
+
+use futures::channel::mpsc::{unbounded, UnboundedReceiver};
+
+use crate::interface::{Key, KeyValuePair};
+use crate::model::{Capabilities, ColumnFamilyOptions, DatastoreAdapter, DatastoreStats};
+use crate::util::remove_path;
 use crate::Error;
 use crate::Transaction;
+use crate::CF;
+
+use super::{begin_trace, Hooks, PendingWrite, Throttle, Validators, WriteEvent, WriteLimit};
 
 #[cfg(feature = "kv-redb")]
 use super::ReDBAdapter;
 
 #[cfg(feature = "kv-rocksdb")]
-use super::RocksDBAdapter;
+use super::{RocksDBAdapter, TwoPhaseRocksDBAdapter};
+
+#[cfg(feature = "kv-sled")]
+use super::SledAdapter;
+
+#[cfg(feature = "kv-lmdb")]
+use super::LmdbAdapter;
+
+#[cfg(feature = "kv-memory")]
+use super::MemoryAdapter;
+
+/// Error of column family management on backends other than RocksDB
+const UNMANAGED_COLUMN_FAMILIES: &str =
+	"Column families can only be created and dropped on RocksDB";
+
+/// Error of two-phase commits on backends other than `rocksdb-2pc`
+const NO_TWO_PHASE_COMMIT: &str = "Two-phase commits need a database opened as rocksdb-2pc:PATH";
 
 #[derive(Copy, Clone)]
 pub struct DatastoreRef<'a> {
@@ -25,12 +50,23 @@ impl<'a> DatastoreRef<'a> {
 pub enum Inner {
 	#[cfg(feature = "kv-rocksdb")]
 	RocksDB(RocksDBAdapter),
+	#[cfg(feature = "kv-rocksdb")]
+	TwoPhaseRocksDB(TwoPhaseRocksDBAdapter),
 	#[cfg(feature = "kv-redb")]
 	ReDB(ReDBAdapter),
+	#[cfg(feature = "kv-sled")]
+	Sled(SledAdapter),
+	#[cfg(feature = "kv-lmdb")]
+	Lmdb(LmdbAdapter),
+	#[cfg(feature = "kv-memory")]
+	Memory(MemoryAdapter),
 }
 
 pub struct Datastore {
 	pub inner: Inner,
+	hooks: Hooks,
+	validators: Validators,
+	throttle: Throttle,
 }
 
 impl Default for Datastore {
@@ -41,25 +77,120 @@ impl Default for Datastore {
 
 impl Datastore {
 	pub fn new(path: &str) -> Datastore {
+		Datastore::open(path).unwrap()
+	}
+
+	/// Open the database at `path`, returning the failure instead of panicking like `new`.
+	/// Missing databases are created.
+	pub fn open(path: &str) -> Result<Datastore, Error> {
+		let inner = match path {
+			#[cfg(feature = "kv-rocksdb")]
+			s if s.starts_with("default:") | s.starts_with("rocksdb:") | s.eq("default") => {
+				Inner::RocksDB(RocksDBAdapter::new(s, None)?)
+			}
+			#[cfg(feature = "kv-rocksdb")]
+			s if s.starts_with("rocksdb-2pc:") => {
+				Inner::TwoPhaseRocksDB(TwoPhaseRocksDBAdapter::new(s, None)?)
+			}
+			#[cfg(feature = "kv-redb")]
+			s if s.starts_with("redb:") => Inner::ReDB(ReDBAdapter::new(s)?),
+			#[cfg(feature = "kv-sled")]
+			s if s.starts_with("sled:") => Inner::Sled(SledAdapter::new(s)?),
+			#[cfg(feature = "kv-lmdb")]
+			s if s.starts_with("lmdb:") => Inner::Lmdb(LmdbAdapter::new(s)?),
+			#[cfg(feature = "kv-memory")]
+			s if s.starts_with("memory:") | s.starts_with("echodb:") => {
+				Inner::Memory(MemoryAdapter::new(s)?)
+			}
+			_ => return Err(Error::Ds(format!("Unsupported datastore {}", path))),
+		};
+		Ok(Datastore {
+			inner,
+			hooks: Hooks::default(),
+			validators: Validators::default(),
+			throttle: Throttle::default(),
+		})
+	}
+
+	/// Open and close the database at `path`, returning the failure instead of panicking
+	/// like `new`. Missing databases are created.
+	pub fn check(path: &str) -> Result<(), Error> {
 		match path {
 			#[cfg(feature = "kv-rocksdb")]
 			s if s.starts_with("default:") | s.starts_with("rocksdb:") | s.eq("default") => {
-				let db = RocksDBAdapter::new(s, None).unwrap();
+				RocksDBAdapter::new(s, None).map(|_| ())
+			}
+			#[cfg(feature = "kv-rocksdb")]
+			s if s.starts_with("rocksdb-2pc:") => TwoPhaseRocksDBAdapter::new(s, None).map(|_| ()),
+			#[cfg(feature = "kv-redb")]
+			s if s.starts_with("redb:") => ReDBAdapter::new(s).map(|_| ()),
+			#[cfg(feature = "kv-sled")]
+			s if s.starts_with("sled:") => SledAdapter::new(s).map(|_| ()),
+			#[cfg(feature = "kv-lmdb")]
+			s if s.starts_with("lmdb:") => LmdbAdapter::new(s).map(|_| ()),
+			#[cfg(feature = "kv-memory")]
+			s if s.starts_with("memory:") | s.starts_with("echodb:") => Ok(()),
+			_ => Err(Error::Ds(format!("Unsupported datastore {}", path))),
+		}
+	}
 
-				Datastore {
-					inner: Inner::RocksDB(db),
-				}
+	/// Features of the backend of the database at `path`, addressed like `new`, without
+	/// opening it. `None` for a backend this build doesn't support.
+	pub fn capabilities(path: &str) -> Option<Capabilities> {
+		match path {
+			#[cfg(feature = "kv-rocksdb")]
+			s if s.starts_with("default:") | s.starts_with("rocksdb:") | s.eq("default") => {
+				Some(RocksDBAdapter::capabilities())
 			}
+			#[cfg(feature = "kv-rocksdb")]
+			s if s.starts_with("rocksdb-2pc:") => Some(TwoPhaseRocksDBAdapter::capabilities()),
 			#[cfg(feature = "kv-redb")]
-			s if s.starts_with("redb:") => {
-				let db = ReDBAdapter::new(s).unwrap();
+			s if s.starts_with("redb:") => Some(ReDBAdapter::capabilities()),
+			#[cfg(feature = "kv-sled")]
+			s if s.starts_with("sled:") => Some(SledAdapter::capabilities()),
+			#[cfg(feature = "kv-lmdb")]
+			s if s.starts_with("lmdb:") => Some(LmdbAdapter::capabilities()),
+			#[cfg(feature = "kv-memory")]
+			s if s.starts_with("memory:") | s.starts_with("echodb:") => Some(MemoryAdapter::capabilities()),
+			_ => None,
+		}
+	}
 
-				Datastore {
-					inner: Inner::ReDB(db),
-				}
+	/// Replace the database at `path`, addressed like `new`, with a backup written by
+	/// `backup`. The backup is restored next to the database first, the database is only
+	/// replaced once it succeeded. The database must not be open, in this process or another.
+	pub fn restore(path: &str, backup: &str, progress: &AtomicU64) -> Result<(), Error> {
+		if path.starts_with("memory:") | path.starts_with("echodb:") {
+			return Err(Error::Ds("Memory databases have no backup to restore".to_string()));
+		}
+		if !Path::new(backup).exists() {
+			return Err(Error::Ds(format!("No backup found at {}", backup)));
+		}
+		// A database locked by another process fails to open
+		Self::check(path)?;
+		let target = path.split_once(':').map_or(path, |(_, target)| target);
+		let staging = format!("{}.restore", target);
+		let (staging, backup) = (Path::new(&staging), Path::new(backup));
+		remove_path(staging)?;
+		let restored = match path {
+			#[cfg(feature = "kv-rocksdb")]
+			s if s.starts_with("default:") | s.starts_with("rocksdb:") => {
+				RocksDBAdapter::restore(staging, backup, progress)
 			}
-			_ => unimplemented!(),
+			#[cfg(feature = "kv-redb")]
+			s if s.starts_with("redb:") => ReDBAdapter::restore(staging, backup, progress),
+			#[cfg(feature = "kv-sled")]
+			s if s.starts_with("sled:") => SledAdapter::restore(staging, backup, progress),
+			#[cfg(feature = "kv-lmdb")]
+			s if s.starts_with("lmdb:") => LmdbAdapter::restore(staging, backup, progress),
+			_ => Err(Error::Ds(format!("Unsupported datastore {}", path))),
+		};
+		if let Err(err) = restored {
+			remove_path(staging)?;
+			return Err(err);
 		}
+		remove_path(Path::new(target))?;
+		fs::rename(staging, target).map_err(|err| Error::Ds(err.to_string()))
 	}
 
 	pub fn borrow(&self) -> DatastoreRef {
@@ -81,10 +212,275 @@ impl Datastore {
 		}
 		impl_transaction_method!(
 			RocksDB feat "kv-rocksdb",
-			ReDB feat "kv-redb"
+			TwoPhaseRocksDB feat "kv-rocksdb",
+			ReDB feat "kv-redb",
+			Sled feat "kv-sled",
+			Lmdb feat "kv-lmdb",
+			Memory feat "kv-memory"
 		)
 	}
 
+	/// Report the storage options of every column family, to compare configurations
+	pub fn column_family_options(&self) -> Result<Vec<ColumnFamilyOptions>, Error> {
+		match &self.inner {
+			#[cfg(feature = "kv-rocksdb")]
+			Inner::RocksDB(v) => v.column_family_options(),
+			#[cfg(feature = "kv-rocksdb")]
+			Inner::TwoPhaseRocksDB(v) => v.column_family_options(),
+			#[cfg(feature = "kv-redb")]
+			Inner::ReDB(v) => v.column_family_options(),
+			#[cfg(feature = "kv-sled")]
+			Inner::Sled(v) => v.column_family_options(),
+			#[cfg(feature = "kv-lmdb")]
+			Inner::Lmdb(v) => v.column_family_options(),
+			#[cfg(feature = "kv-memory")]
+			Inner::Memory(v) => v.column_family_options(),
+		}
+	}
+
+	/// Create an empty column family, only RocksDB column families are managed
+	#[cfg_attr(not(feature = "kv-rocksdb"), allow(unused_variables))]
+	pub fn create_column_family(&self, name: &str) -> Result<(), Error> {
+		match &self.inner {
+			#[cfg(feature = "kv-rocksdb")]
+			Inner::RocksDB(v) => v.create_cf(name),
+			#[cfg(feature = "kv-rocksdb")]
+			Inner::TwoPhaseRocksDB(_) => Err(Error::Ds(UNMANAGED_COLUMN_FAMILIES.to_string())),
+			#[cfg(feature = "kv-redb")]
+			Inner::ReDB(_) => Err(Error::Ds(UNMANAGED_COLUMN_FAMILIES.to_string())),
+			#[cfg(feature = "kv-sled")]
+			Inner::Sled(_) => Err(Error::Ds(UNMANAGED_COLUMN_FAMILIES.to_string())),
+			#[cfg(feature = "kv-lmdb")]
+			Inner::Lmdb(_) => Err(Error::Ds(UNMANAGED_COLUMN_FAMILIES.to_string())),
+			#[cfg(feature = "kv-memory")]
+			Inner::Memory(_) => Err(Error::Ds(UNMANAGED_COLUMN_FAMILIES.to_string())),
+		}
+	}
+
+	/// Drop a column family with its keys, only RocksDB column families are managed
+	#[cfg_attr(not(feature = "kv-rocksdb"), allow(unused_variables))]
+	pub fn drop_column_family(&self, name: &str) -> Result<(), Error> {
+		match &self.inner {
+			#[cfg(feature = "kv-rocksdb")]
+			Inner::RocksDB(v) => v.drop_cf(name),
+			#[cfg(feature = "kv-rocksdb")]
+			Inner::TwoPhaseRocksDB(_) => Err(Error::Ds(UNMANAGED_COLUMN_FAMILIES.to_string())),
+			#[cfg(feature = "kv-redb")]
+			Inner::ReDB(_) => Err(Error::Ds(UNMANAGED_COLUMN_FAMILIES.to_string())),
+			#[cfg(feature = "kv-sled")]
+			Inner::Sled(_) => Err(Error::Ds(UNMANAGED_COLUMN_FAMILIES.to_string())),
+			#[cfg(feature = "kv-lmdb")]
+			Inner::Lmdb(_) => Err(Error::Ds(UNMANAGED_COLUMN_FAMILIES.to_string())),
+			#[cfg(feature = "kv-memory")]
+			Inner::Memory(_) => Err(Error::Ds(UNMANAGED_COLUMN_FAMILIES.to_string())),
+		}
+	}
+
+	/// Load pairs into a column family without the WAL, by ingesting an SST file. Hooks and
+	/// write limits don't apply. Returns false without writing when the backend can't ingest
+	/// files, only RocksDB column families with the bytewise comparator can, the pairs are
+	/// then written with transactions by the caller.
+	#[cfg_attr(not(feature = "kv-rocksdb"), allow(unused_variables))]
+	pub fn ingest(&self, cf: CF, pairs: Vec<KeyValuePair>) -> Result<bool, Error> {
+		match &self.inner {
+			#[cfg(feature = "kv-rocksdb")]
+			Inner::RocksDB(v) => {
+				let name = cf.map(|cf| String::from_utf8_lossy(&cf).to_string());
+				v.ingest(name.as_deref().unwrap_or("default"), pairs)
+			}
+			#[allow(unreachable_patterns)]
+			_ => Ok(false),
+		}
+	}
+
+	/// Write a copy of the database at `backup` with the native mechanism of the backend:
+	/// a RocksDB checkpoint, a sled export, an LMDB environment copy or a copy of the
+	/// ReDB file. The written bytes are added to `progress`, see `restore`.
+	pub fn backup(&self, backup: &str, progress: &AtomicU64) -> Result<(), Error> {
+		let backup = Path::new(backup);
+		match &self.inner {
+			#[cfg(feature = "kv-rocksdb")]
+			Inner::RocksDB(v) => v.backup(backup, progress),
+			#[cfg(feature = "kv-rocksdb")]
+			Inner::TwoPhaseRocksDB(v) => v.backup(backup, progress),
+			#[cfg(feature = "kv-redb")]
+			Inner::ReDB(v) => v.backup(backup, progress),
+			#[cfg(feature = "kv-sled")]
+			Inner::Sled(v) => v.backup(backup, progress),
+			#[cfg(feature = "kv-lmdb")]
+			Inner::Lmdb(v) => v.backup(backup, progress),
+			#[cfg(feature = "kv-memory")]
+			Inner::Memory(v) => v.backup(backup, progress),
+		}
+	}
+
+	/// Export the column family to SST files of at most `batch` pairs in `dir`, see
+	/// `ingest_sst`. Only RocksDB stores SST files.
+	#[cfg_attr(not(feature = "kv-rocksdb"), allow(unused_variables))]
+	pub fn export_sst(&self, cf: CF, dir: &str, batch: usize) -> Result<Vec<String>, Error> {
+		match &self.inner {
+			#[cfg(feature = "kv-rocksdb")]
+			Inner::RocksDB(v) => {
+				let name = cf.map(|cf| String::from_utf8_lossy(&cf).to_string());
+				v.export_sst(name.as_deref().unwrap_or("default"), dir, batch)
+			}
+			#[allow(unreachable_patterns)]
+			_ => Err(Error::Ds("SST files are only supported by RocksDB".to_string())),
+		}
+	}
+
+	/// Ingest SST files written by `export_sst` into the column family. Like `ingest`, the
+	/// files skip the WAL, hooks and write limits.
+	#[cfg_attr(not(feature = "kv-rocksdb"), allow(unused_variables))]
+	pub fn ingest_sst(&self, cf: CF, files: &[String]) -> Result<(), Error> {
+		match &self.inner {
+			#[cfg(feature = "kv-rocksdb")]
+			Inner::RocksDB(v) => {
+				let name = cf.map(|cf| String::from_utf8_lossy(&cf).to_string());
+				v.ingest_sst(name.as_deref().unwrap_or("default"), files)
+			}
+			#[allow(unreachable_patterns)]
+			_ => Err(Error::Ds("SST files are only supported by RocksDB".to_string())),
+		}
+	}
+
+	/// Names of the column families of the database. Backends without managed column
+	/// families report those of `column_family_options`.
+	pub fn column_families(&self) -> Result<Vec<String>, Error> {
+		match &self.inner {
+			#[cfg(feature = "kv-rocksdb")]
+			Inner::RocksDB(v) => v.list_cf(),
+			#[allow(unreachable_patterns)]
+			_ => Ok(self.column_family_options()?.into_iter().map(|o| o.name).collect()),
+		}
+	}
+
+	/// Report runtime properties of the storage engine, e.g. RocksDB files per level,
+	/// memtable sizes and write stalls. Other backends report none.
+	pub fn properties(&self) -> Result<Vec<(String, String)>, Error> {
+		match &self.inner {
+			#[cfg(feature = "kv-rocksdb")]
+			Inner::RocksDB(v) => v.properties(),
+			#[cfg(feature = "kv-rocksdb")]
+			Inner::TwoPhaseRocksDB(v) => v.properties(),
+			#[cfg(feature = "kv-redb")]
+			Inner::ReDB(v) => v.properties(),
+			#[cfg(feature = "kv-sled")]
+			Inner::Sled(v) => v.properties(),
+			#[cfg(feature = "kv-lmdb")]
+			Inner::Lmdb(v) => v.properties(),
+			#[cfg(feature = "kv-memory")]
+			Inner::Memory(v) => v.properties(),
+		}
+	}
+
+	/// Size on disk and per column family statistics of the datastore
+	pub fn stats(&self) -> Result<DatastoreStats, Error> {
+		match &self.inner {
+			#[cfg(feature = "kv-rocksdb")]
+			Inner::RocksDB(v) => v.stats(),
+			#[cfg(feature = "kv-rocksdb")]
+			Inner::TwoPhaseRocksDB(v) => v.stats(),
+			#[cfg(feature = "kv-redb")]
+			Inner::ReDB(v) => v.stats(),
+			#[cfg(feature = "kv-sled")]
+			Inner::Sled(v) => v.stats(),
+			#[cfg(feature = "kv-lmdb")]
+			Inner::Lmdb(v) => v.stats(),
+			#[cfg(feature = "kv-memory")]
+			Inner::Memory(v) => v.stats(),
+		}
+	}
+
+	/// Register a callback fired after committed writes of `cf` whose key starts with `prefix`
+	pub fn register_hook<P, F>(&mut self, cf: CF, prefix: P, callback: F)
+	where
+		P: Into<Key>,
+		F: Fn(&WriteEvent) + Send + Sync + 'static,
+	{
+		self.hooks.register(cf, prefix, callback);
+	}
+
+	/// Register a callback fired once per committed transaction with its writes of `cf` whose
+	/// key starts with `prefix`
+	pub fn register_commit_hook<P, F>(&mut self, cf: CF, prefix: P, callback: F)
+	where
+		P: Into<Key>,
+		F: Fn(&[WriteEvent]) + Send + Sync + 'static,
+	{
+		self.hooks.register_commit(cf, prefix, callback);
+	}
+
+	/// Register a check run before each write of `cf` whose key starts with `prefix`, a
+	/// write it rejects fails with `Error::TxRejected` and is not applied
+	pub fn register_validator<P, F>(&mut self, cf: CF, prefix: P, check: F)
+	where
+		P: Into<Key>,
+		F: Fn(&PendingWrite) -> Result<(), String> + Send + Sync + 'static,
+	{
+		self.validators.register(cf, prefix, check);
+	}
+
+	/// Stream of the committed writes of `cf` whose key starts with `prefix`, delivered by a
+	/// write hook. Only the writes of transactions created from this datastore are seen,
+	/// not those of another process. The hook stays registered once the stream is dropped.
+	pub fn subscribe<P: Into<Key>>(&mut self, cf: CF, prefix: P) -> UnboundedReceiver<WriteEvent> {
+		let (sender, receiver) = unbounded();
+		self.register_hook(cf, prefix, move |event| {
+			// A dropped stream no longer receives events
+			let _ = sender.unbounded_send(event.clone());
+		});
+		receiver
+	}
+
+	/// Limit the rate of writes of every transaction created from this datastore
+	pub fn set_write_limit(&mut self, limit: WriteLimit) {
+		self.throttle = Throttle::new(limit);
+	}
+
+	/// Begin a write transaction named `name` that can be prepared for a two-phase commit,
+	/// only on databases opened as `rocksdb-2pc:PATH`
+	#[cfg_attr(not(feature = "kv-rocksdb"), allow(unused_variables))]
+	pub fn named_transaction(&self, name: &str) -> Result<Transaction, Error> {
+		match &self.inner {
+			#[cfg(feature = "kv-rocksdb")]
+			Inner::TwoPhaseRocksDB(v) => {
+				let tx = v.named_transaction(name)?;
+				Ok(self.wrap(super::tx::Inner::TwoPhaseRocksDB(tx), true))
+			}
+			#[allow(unreachable_patterns)]
+			_ => Err(Error::Ds(NO_TWO_PHASE_COMMIT.to_string())),
+		}
+	}
+
+	/// Transactions prepared before the database was last closed, with their names, to be
+	/// committed or cancelled as decided by the transaction manager. Hooks fired on commit
+	/// only see the writes made since the database was opened, none for these.
+	pub fn prepared_transactions(&self) -> Result<Vec<(String, Transaction)>, Error> {
+		match &self.inner {
+			#[cfg(feature = "kv-rocksdb")]
+			Inner::TwoPhaseRocksDB(v) => Ok(v
+				.prepared_transactions()?
+				.into_iter()
+				.map(|(name, tx)| (name, self.wrap(super::tx::Inner::TwoPhaseRocksDB(tx), true)))
+				.collect()),
+			#[allow(unreachable_patterns)]
+			_ => Err(Error::Ds(NO_TWO_PHASE_COMMIT.to_string())),
+		}
+	}
+
+	/// Transaction of the backend with the hooks, validators and write limit of the datastore
+	fn wrap(&self, inner: super::tx::Inner, write: bool) -> Transaction {
+		Transaction {
+			inner,
+			hooks: self.hooks.clone(),
+			validators: self.validators.clone(),
+			throttle: self.throttle.clone(),
+			writes: vec![],
+			trace: begin_trace(self.path(), write).map(|tx| (self.path().to_string(), tx)),
+		}
+	}
+
 	pub async fn transaction(&self, write: bool) -> Result<Transaction, Error> {
 		macro_rules! impl_transaction_method {
 			($($x: ident feat $f: expr),*) => {
@@ -93,9 +489,7 @@ impl Datastore {
 						#[cfg(feature = $f)]
 						Inner::$x(v) => {
 							let tx = v.transaction(write).await?;
-							Ok(Transaction {
-								inner: super::tx::Inner::$x(tx),
-							})
+							Ok(self.wrap(super::tx::Inner::$x(tx), write))
 						}
 					)*
 				}
@@ -103,7 +497,11 @@ impl Datastore {
 		}
 		impl_transaction_method!(
 			RocksDB feat "kv-rocksdb",
-			ReDB feat "kv-redb"
+			TwoPhaseRocksDB feat "kv-rocksdb",
+			ReDB feat "kv-redb",
+			Sled feat "kv-sled",
+			Lmdb feat "kv-lmdb",
+			Memory feat "kv-memory"
 		)
 	}
 }
@@ -112,9 +510,12 @@ impl Datastore {
 mod test {
 	use crate::{
 		constant::{ColumnFamily, COLUMN_FAMILIES},
-		SimpleTransaction,
+		util::{generate_path, generate_random_i32},
+		Error, SimpleTransaction,
 	};
 
+	use std::sync::atomic::{AtomicU64, Ordering};
+
 	use super::Datastore;
 
 	#[tokio::test]
@@ -163,4 +564,87 @@ mod test {
 		tx.set(cf.clone(), key3, val3).await.unwrap();
 		tx.commit().await.unwrap();
 	}
+
+	#[tokio::test]
+	async fn should_ingest_pairs() {
+		let db = Datastore::new(&generate_path("rocksdb", None));
+		let pairs = vec![
+			(b"b".to_vec(), b"1".to_vec()),
+			(b"a".to_vec(), b"2".to_vec()),
+			(b"b".to_vec(), b"3".to_vec()),
+		];
+		assert!(db.ingest(None, pairs).unwrap());
+		let tx = db.transaction(false).await.unwrap();
+		assert_eq!(tx.get(None, "a").await.unwrap(), Some(b"2".to_vec()));
+		assert_eq!(tx.get(None, "b").await.unwrap(), Some(b"3".to_vec()));
+
+		// Other backends are written with transactions
+		let db = Datastore::new(&generate_path("redb", None));
+		assert!(!db.ingest(None, vec![(b"a".to_vec(), b"1".to_vec())]).unwrap());
+	}
+
+	#[tokio::test]
+	async fn should_export_and_ingest_sst_files() {
+		let source = Datastore::new(&generate_path("rocksdb", None));
+		let pairs = (0..5u8).map(|i| (vec![i], vec![i])).collect();
+		assert!(source.ingest(None, pairs).unwrap());
+		let dir = std::env::temp_dir().join(format!("edma-sst-{}", generate_random_i32()));
+		let dir = dir.to_string_lossy().to_string();
+		let files = source.export_sst(None, &dir, 2).unwrap();
+		assert_eq!(files.len(), 3);
+
+		let target = Datastore::new(&generate_path("rocksdb", None));
+		let cf = Some(b"copied".to_vec());
+		target.ingest_sst(cf.clone(), &files).unwrap();
+		let mut tx = target.transaction(false).await.unwrap();
+		assert_eq!(tx.get(cf.clone(), vec![4u8]).await.unwrap(), Some(vec![4u8]));
+		assert_eq!(tx.count(cf).await.unwrap(), 5);
+	}
+
+	#[tokio::test]
+	async fn should_refuse_two_phase_commits() {
+		let db = Datastore::new(&generate_path("redb", None));
+		assert!(db.named_transaction("order-1").is_err());
+		assert!(db.prepared_transactions().is_err());
+		let mut tx = db.transaction(true).await.unwrap();
+		assert!(matches!(tx.prepare().await, Err(Error::TxPrepareUnsupported)));
+	}
+
+	#[test]
+	fn should_report_capabilities() {
+		let rocksdb = Datastore::capabilities("rocksdb:/data/app").unwrap();
+		assert!(rocksdb.column_families && rocksdb.sst_files);
+		let sled = Datastore::capabilities("sled:/data/app").unwrap();
+		assert!(!sled.column_families && !sled.snapshots && sled.backup);
+		assert!(!Datastore::capabilities("memory:scratch").unwrap().backup);
+		assert_eq!(Datastore::capabilities("indexeddb:/data/app"), None);
+	}
+
+	#[tokio::test]
+	async fn should_backup_and_restore() {
+		for name in ["rocksdb", "redb", "sled"] {
+			let path = generate_path(name, None);
+			let backup = format!("{}-backup", path.split_once(':').unwrap().1);
+			let progress = AtomicU64::new(0);
+			{
+				let db = Datastore::new(&path);
+				let mut tx = db.transaction(true).await.unwrap();
+				tx.set(None, "kept", "1").await.unwrap();
+				tx.commit().await.unwrap();
+				db.backup(&backup, &progress).unwrap();
+				assert!(db.backup(&backup, &progress).is_err());
+
+				let mut tx = db.transaction(true).await.unwrap();
+				tx.set(None, "dropped", "2").await.unwrap();
+				tx.commit().await.unwrap();
+			}
+			assert!(progress.load(Ordering::Relaxed) > 0);
+
+			Datastore::restore(&path, &backup, &progress).unwrap();
+			let db = Datastore::new(&path);
+			let tx = db.transaction(false).await.unwrap();
+			assert_eq!(tx.get(None, "kept").await.unwrap(), Some(b"1".to_vec()));
+			assert_eq!(tx.get(None, "dropped").await.unwrap(), None);
+		}
+	}
 }
@@ -0,0 +1,184 @@
+use std::{fmt, sync::Arc};
+
+use crate::{interface::Key, CF};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WriteOperation {
+	Set,
+	Put,
+	Delete,
+}
+
+impl fmt::Display for WriteOperation {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			WriteOperation::Set => write!(f, "set"),
+			WriteOperation::Put => write!(f, "put"),
+			WriteOperation::Delete => write!(f, "delete"),
+		}
+	}
+}
+
+/// Write applied by a committed transaction
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WriteEvent {
+	pub operation: WriteOperation,
+	pub cf: CF,
+	pub key: Key,
+}
+
+pub type HookFn = Arc<dyn Fn(&WriteEvent) + Send + Sync>;
+pub type CommitHookFn = Arc<dyn Fn(&[WriteEvent]) + Send + Sync>;
+
+#[derive(Clone)]
+enum Callback {
+	/// Called once per matching write
+	Write(HookFn),
+	/// Called once per transaction with its matching writes
+	Commit(CommitHookFn),
+}
+
+#[derive(Clone)]
+struct Hook {
+	cf: CF,
+	prefix: Key,
+	callback: Callback,
+}
+
+impl Hook {
+	fn matches(&self, event: &WriteEvent) -> bool {
+		self.cf == event.cf && event.key.starts_with(&self.prefix)
+	}
+}
+
+/// # Write hooks
+/// Callbacks registered on a datastore, called after the transaction holding the writes
+/// committed, either once per write or once per transaction. A hook only receives writes of
+/// its column family whose key starts with its prefix. Transactions only record writes when
+/// hooks are registered.
+#[derive(Clone, Default)]
+pub struct Hooks {
+	hooks: Vec<Hook>,
+}
+
+impl Hooks {
+	pub fn is_empty(&self) -> bool {
+		self.hooks.is_empty()
+	}
+
+	/// Register a callback for writes of `cf` whose key starts with `prefix`
+	pub fn register<P, F>(&mut self, cf: CF, prefix: P, callback: F)
+	where
+		P: Into<Key>,
+		F: Fn(&WriteEvent) + Send + Sync + 'static,
+	{
+		self.hooks.push(Hook {
+			cf,
+			prefix: prefix.into(),
+			callback: Callback::Write(Arc::new(callback)),
+		});
+	}
+
+	/// Register a callback receiving the writes of a committed transaction to `cf` whose key
+	/// starts with `prefix`, not called when the transaction holds none
+	pub fn register_commit<P, F>(&mut self, cf: CF, prefix: P, callback: F)
+	where
+		P: Into<Key>,
+		F: Fn(&[WriteEvent]) + Send + Sync + 'static,
+	{
+		self.hooks.push(Hook {
+			cf,
+			prefix: prefix.into(),
+			callback: Callback::Commit(Arc::new(callback)),
+		});
+	}
+
+	/// Call every hook matching the committed writes
+	pub fn fire(&self, events: &[WriteEvent]) {
+		for hook in self.hooks.iter() {
+			let matched = events.iter().filter(|event| hook.matches(event));
+			match &hook.callback {
+				Callback::Write(callback) => matched.for_each(|event| callback(event)),
+				Callback::Commit(callback) => {
+					let matched: Vec<WriteEvent> = matched.cloned().collect();
+					if !matched.is_empty() {
+						callback(&matched);
+					}
+				}
+			}
+		}
+	}
+}
+
+impl fmt::Debug for Hooks {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Hooks").field("count", &self.hooks.len()).finish()
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::sync::{
+		atomic::{AtomicUsize, Ordering},
+		Arc, Mutex,
+	};
+
+	use futures::StreamExt;
+
+	use crate::{util::generate_path, Datastore, SimpleTransaction, WriteOperation};
+
+	#[tokio::test]
+	async fn should_fire_after_commit() {
+		let mut db = Datastore::new(&generate_path("redb", None));
+		let cf = None;
+		let fired = Arc::new(AtomicUsize::new(0));
+		let counter = fired.clone();
+		db.register_hook(cf.clone(), "user:", move |_| {
+			counter.fetch_add(1, Ordering::SeqCst);
+		});
+
+		let mut tx = db.transaction(true).await.unwrap();
+		tx.set(cf.clone(), "user:1", "a").await.unwrap();
+		tx.set(cf.clone(), "order:1", "b").await.unwrap();
+		tx.del(cf.clone(), "user:1").await.unwrap();
+		assert_eq!(fired.load(Ordering::SeqCst), 0);
+		tx.commit().await.unwrap();
+		assert_eq!(fired.load(Ordering::SeqCst), 2);
+	}
+
+	#[tokio::test]
+	async fn should_fire_commit_hooks_once_per_transaction() {
+		let mut db = Datastore::new(&generate_path("redb", None));
+		let batches = Arc::new(Mutex::new(vec![]));
+		let recorded = batches.clone();
+		db.register_commit_hook(None, "user:", move |events| {
+			recorded.lock().unwrap().push(events.len());
+		});
+
+		let mut tx = db.transaction(true).await.unwrap();
+		tx.set(None, "user:1", "a").await.unwrap();
+		tx.set(None, "user:2", "b").await.unwrap();
+		tx.commit().await.unwrap();
+		let mut tx = db.transaction(true).await.unwrap();
+		tx.set(None, "order:1", "c").await.unwrap();
+		tx.commit().await.unwrap();
+		assert_eq!(*batches.lock().unwrap(), vec![2]);
+	}
+
+	#[tokio::test]
+	async fn should_stream_subscribed_writes() {
+		let mut db = Datastore::new(&generate_path("redb", None));
+		let mut changes = db.subscribe(None, "user:");
+
+		let mut tx = db.transaction(true).await.unwrap();
+		tx.set(None, "user:1", "a").await.unwrap();
+		tx.set(None, "order:1", "b").await.unwrap();
+		tx.commit().await.unwrap();
+
+		let event = changes.next().await.unwrap();
+		assert_eq!(event.operation, WriteOperation::Set);
+		assert_eq!(event.key, b"user:1".to_vec());
+		// Nothing else was written under the prefix
+		assert!(changes.try_next().is_err());
+	}
+}
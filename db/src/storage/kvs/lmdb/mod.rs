@@ -0,0 +1,188 @@
+pub mod tx;
+pub mod ty;
+
+use std::{
+	collections::HashMap,
+	fs,
+	path::Path,
+	sync::atomic::{AtomicU64, Ordering},
+};
+
+use async_trait::async_trait;
+use heed::{types::ByteSlice, CompactionOption, Env, EnvOpenOptions, RoTxn, RwTxn};
+pub use tx::*;
+pub use ty::*;
+
+use crate::{
+	constant::CF_NAMES,
+	util::{disk_size, ensure_new_path, generate_path},
+	ColumnFamilyOptions, ColumnFamilyStats, DBTransaction, DatastoreAdapter, DatastoreStats, Error,
+	StorageAdapter, StorageAdapterName, StorageVariant,
+};
+
+/// Virtual address space reserved for the environment, the file only grows with data
+const MAP_SIZE: usize = 1 << 30;
+const MAX_DBS: u32 = 128;
+
+pub struct LmdbAdapter(StorageAdapter<DBType>);
+
+#[cfg(feature = "test-suite")]
+crate::full_adapter_test_impl!(LmdbAdapter::default());
+
+impl LmdbAdapter {
+	impl_new_type_adapter!(DBType);
+
+	pub fn new(path: &str) -> Result<LmdbAdapter, Error> {
+		let path = &path["lmdb:".len()..];
+		fs::create_dir_all(path).map_err(|err| Error::Ds(err.to_string()))?;
+		let env = EnvOpenOptions::new().map_size(MAP_SIZE).max_dbs(MAX_DBS).open(path)?;
+
+		let mut names = list_databases(&env)?;
+		names.extend(CF_NAMES.iter().map(|name| name.to_string()));
+		names.sort_unstable();
+		names.dedup();
+
+		let mut wtxn = env.write_txn()?;
+		let mut dbs = HashMap::new();
+		dbs.insert(None, env.create_database_with_txn(None, &mut wtxn)?);
+		for name in names {
+			let db = env.create_database_with_txn(Some(name.as_str()), &mut wtxn)?;
+			dbs.insert(Some(name.into_bytes()), db);
+		}
+		wtxn.commit()?;
+
+		Ok(LmdbAdapter(StorageAdapter::<DBType>::new(
+			StorageAdapterName::Lmdb,
+			path.to_string(),
+			LmdbEnv {
+				env,
+				dbs,
+			},
+			StorageVariant::KeyValueStore,
+		)?))
+	}
+}
+
+/// Names of the databases of an environment. LMDB stores them as keys of the main
+/// database, keys that don't open as a database are plain data.
+fn list_databases(env: &Env) -> Result<Vec<String>, Error> {
+	let main = match env.open_database::<ByteSlice, ByteSlice>(None)? {
+		Some(main) => main,
+		None => return Ok(vec![]),
+	};
+	let keys: Vec<String> = {
+		let rtxn = env.read_txn()?;
+		let iter = main.iter(&rtxn)?;
+		iter.filter_map(|item| item.ok())
+			.filter_map(|(k, _)| String::from_utf8(k.to_vec()).ok())
+			.collect()
+	};
+	Ok(keys
+		.into_iter()
+		.filter(|name| {
+			matches!(env.open_database::<ByteSlice, ByteSlice>(Some(name.as_str())), Ok(Some(_)))
+		})
+		.collect())
+}
+
+#[async_trait]
+impl DatastoreAdapter for LmdbAdapter {
+	type Transaction = LmdbTransaction;
+
+	fn default() -> Self {
+		let path = &generate_path("lmdb", None);
+		LmdbAdapter::new(path).unwrap()
+	}
+
+	fn spawn(&self) -> Self {
+		LmdbAdapter::default()
+	}
+
+	fn path(&self) -> &str {
+		&self.0.path
+	}
+
+	/// LMDB writes a compacted copy of a read snapshot of the environment into the
+	/// `data.mdb` file of the backup directory
+	fn backup(&self, backup: &Path, progress: &AtomicU64) -> Result<(), Error> {
+		ensure_new_path(backup)?;
+		let inner = self.get_initialized_inner()?;
+		fs::create_dir_all(backup).map_err(|err| Error::Ds(err.to_string()))?;
+		inner.db_instance.env.copy_to_path(backup.join("data.mdb"), CompactionOption::Enabled)?;
+		progress.fetch_add(disk_size(backup), Ordering::Relaxed);
+		Ok(())
+	}
+
+	fn column_family_options(&self) -> Result<Vec<ColumnFamilyOptions>, Error> {
+		// LMDB databases share the environment settings, keys are compared bytewise
+		let inner = self.get_initialized_inner()?;
+		let mut names: Vec<String> = inner
+			.db_instance
+			.dbs
+			.keys()
+			.flatten()
+			.map(|name| String::from_utf8_lossy(name).to_string())
+			.collect();
+		names.sort_unstable();
+		names.insert(0, "default".to_string());
+		Ok(names
+			.into_iter()
+			.map(|name| ColumnFamilyOptions {
+				name,
+				compression: "none".to_string(),
+				comparator: "bytewise".to_string(),
+				merge_operator: None,
+				block_size: None,
+			})
+			.collect())
+	}
+
+	// Databases are counted and their keys and values summed in one read transaction
+	fn stats(&self) -> Result<DatastoreStats, Error> {
+		let inner = self.get_initialized_inner()?;
+		let rtxn = inner.db_instance.env.read_txn()?;
+		let mut column_families = vec![];
+		for (cf, db) in inner.db_instance.dbs.iter() {
+			let (mut keys, mut size) = (0, 0);
+			for pair in db.iter(&rtxn)? {
+				let (key, value) = pair?;
+				keys += 1;
+				size += (key.len() + value.len()) as u64;
+			}
+			column_families.push(ColumnFamilyStats {
+				name: match cf {
+					Some(name) => String::from_utf8_lossy(name).to_string(),
+					None => "default".to_string(),
+				},
+				keys: Some(keys),
+				size: Some(size),
+				files: None,
+			});
+		}
+		column_families.sort_unstable_by_key(|cf| (cf.name != "default", cf.name.clone()));
+		Ok(DatastoreStats {
+			disk_size: disk_size(Path::new(self.path())),
+			column_families,
+		})
+	}
+
+	async fn transaction(&self, w: bool) -> Result<Self::Transaction, Error> {
+		let inner = self.get_initialized_inner()?;
+		let db = &inner.db_instance;
+		let tx = match w {
+			true => LmdbTx::Write(unsafe { extend_rw_lifetime(db.env.write_txn()?) }),
+			false => LmdbTx::Read(unsafe { extend_ro_lifetime(db.env.read_txn()?) }),
+		};
+
+		DBTransaction::<DBType, TxType>::new(tx, db.clone(), w)
+	}
+}
+
+// The environment is kept alive by the transaction, see the RocksDB adapter
+unsafe fn extend_rw_lifetime(tx: RwTxn<'_, '_>) -> RwTxn<'static, 'static> {
+	std::mem::transmute::<RwTxn<'_, '_>, RwTxn<'static, 'static>>(tx)
+}
+
+unsafe fn extend_ro_lifetime(tx: RoTxn<'_>) -> RoTxn<'static> {
+	std::mem::transmute::<RoTxn<'_>, RoTxn<'static>>(tx)
+}
@@ -0,0 +1,323 @@
+use std::ops::Bound;
+
+use async_trait::async_trait;
+
+use crate::{
+	interface::{Key, KeyValuePair, Val},
+	DBTransaction, Error, Page, SimpleTransaction, CF,
+};
+
+use super::ty::{DBType, LmdbDatabase, LmdbTx, TxType};
+
+fn get_database(db: &DBType, cf: &CF) -> Result<LmdbDatabase, Error> {
+	db.dbs.get(cf).copied().ok_or(Error::DsNoColumnFamilyFound)
+}
+
+fn to_pair(item: heed::Result<(&[u8], &[u8])>) -> Result<KeyValuePair, Error> {
+	let (k, v) = item?;
+	Ok((k.to_vec(), v.to_vec()))
+}
+
+fn as_slice(bound: &Bound<Key>) -> Bound<&[u8]> {
+	match bound {
+		Bound::Included(key) => Bound::Included(key.as_slice()),
+		Bound::Excluded(key) => Bound::Excluded(key.as_slice()),
+		Bound::Unbounded => Bound::Unbounded,
+	}
+}
+
+#[async_trait(?Send)]
+impl SimpleTransaction for DBTransaction<DBType, TxType> {
+	fn closed(&self) -> bool {
+		self.ok
+	}
+
+	async fn count(&mut self, cf: CF) -> Result<usize, Error> {
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		let guarded_tx = self.tx.lock().await;
+		let tx = guarded_tx.as_ref().unwrap();
+
+		let db = get_database(&self._db, &cf)?;
+		Ok(db.len(tx.read())? as usize)
+	}
+
+	// LMDB has no statistics to estimate from, the estimate is an exact count
+	async fn estimate_count(&mut self, cf: CF) -> Result<usize, Error> {
+		self.count(cf).await
+	}
+
+	async fn cancel(&mut self) -> Result<(), Error> {
+		if self.ok {
+			return Err(Error::TxFinished);
+		}
+
+		// Mark this transaction as done
+		self.ok = true;
+
+		// Dropping a LMDB transaction aborts it
+		let mut tx = self.tx.lock().await;
+		match tx.take() {
+			Some(tx) => drop(tx),
+			None => unreachable!(),
+		}
+
+		Ok(())
+	}
+
+	async fn commit(&mut self) -> Result<(), Error> {
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		// Check to see if transaction is writable
+		if !self.writable {
+			return Err(Error::TxReadonly);
+		}
+
+		// Mark this transaction as done
+		self.ok = true;
+
+		let mut tx = self.tx.lock().await;
+		match tx.take() {
+			Some(LmdbTx::Write(tx)) => tx.commit()?,
+			Some(LmdbTx::Read(_)) => return Err(Error::TxReadonly),
+			None => unreachable!(),
+		}
+
+		Ok(())
+	}
+
+	async fn exi<K>(&self, cf: CF, key: K) -> Result<bool, Error>
+	where
+		K: Into<Key> + Send,
+	{
+		Ok(self.get(cf, key).await?.is_some())
+	}
+
+	// Fetch a key from the database [column family]
+	async fn get<K>(&self, cf: CF, key: K) -> Result<Option<Val>, Error>
+	where
+		K: Into<Key> + Send,
+	{
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		let guarded_tx = self.tx.lock().await;
+		let tx = guarded_tx.as_ref().unwrap();
+
+		let db = get_database(&self._db, &cf)?;
+		let key: Key = key.into();
+		Ok(db.get(tx.read(), &key)?.map(|v| v.to_vec()))
+	}
+
+	// Insert or update a key in the database
+	async fn set<K, V>(&mut self, cf: CF, key: K, val: V) -> Result<(), Error>
+	where
+		K: Into<Key> + Send,
+		V: Into<Key> + Send,
+	{
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		// Check to see if transaction is writable
+		if !self.writable {
+			return Err(Error::TxReadonly);
+		}
+
+		let mut guarded_tx = self.tx.lock().await;
+		let db = get_database(&self._db, &cf)?;
+		let (key, val) = (key.into(), val.into());
+		match guarded_tx.as_mut().unwrap() {
+			LmdbTx::Write(tx) => db.put(tx, &key, &val)?,
+			LmdbTx::Read(_) => return Err(Error::TxReadonly),
+		};
+
+		Ok(())
+	}
+
+	// Insert a key if it doesn't exist in the database
+	async fn put<K, V>(&mut self, cf: CF, key: K, val: V) -> Result<(), Error>
+	where
+		K: Into<Key> + Send,
+		V: Into<Key> + Send,
+	{
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		// Check to see if transaction is writable
+		if !self.writable {
+			return Err(Error::TxReadonly);
+		}
+
+		let key: Key = key.into();
+		if self.exi(cf.clone(), key.clone()).await? {
+			return Err(Error::TxConditionNotMet);
+		}
+
+		self.set(cf, key, val).await
+	}
+
+	// Delete a key
+	async fn del<K>(&mut self, cf: CF, key: K) -> Result<(), Error>
+	where
+		K: Into<Key> + Send,
+	{
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		// Check to see if transaction is writable
+		if !self.writable {
+			return Err(Error::TxReadonly);
+		}
+
+		let mut guarded_tx = self.tx.lock().await;
+		let db = get_database(&self._db, &cf)?;
+		let key: Key = key.into();
+		match guarded_tx.as_mut().unwrap() {
+			LmdbTx::Write(tx) => db.delete(tx, &key)?,
+			LmdbTx::Read(_) => return Err(Error::TxReadonly),
+		};
+
+		Ok(())
+	}
+
+	async fn iterate(&self, cf: CF) -> Result<Vec<Result<KeyValuePair, Error>>, Error> {
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		let guarded_tx = self.tx.lock().await;
+		let tx = guarded_tx.as_ref().unwrap();
+
+		let db = get_database(&self._db, &cf)?;
+		let iterator = db.iter(tx.read())?;
+		Ok(iterator.map(to_pair).collect())
+	}
+
+	async fn reverse_iterate(&self, cf: CF) -> Result<Vec<Result<KeyValuePair, Error>>, Error> {
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		let guarded_tx = self.tx.lock().await;
+		let tx = guarded_tx.as_ref().unwrap();
+
+		let db = get_database(&self._db, &cf)?;
+		let iterator = db.rev_iter(tx.read())?;
+		Ok(iterator.map(to_pair).collect())
+	}
+
+	async fn page_iterate(
+		&self,
+		cf: CF,
+		page: Page,
+		reverse: bool,
+	) -> Result<Vec<Result<KeyValuePair, Error>>, Error> {
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		let guarded_tx = self.tx.lock().await;
+		let tx = guarded_tx.as_ref().unwrap();
+
+		let db = get_database(&self._db, &cf)?;
+		Ok(match reverse {
+			true => page.apply(db.rev_iter(tx.read())?).map(to_pair).collect(),
+			false => page.apply(db.iter(tx.read())?).map(to_pair).collect(),
+		})
+	}
+
+	async fn prefix_iterate<P>(
+		&self,
+		cf: CF,
+		prefix: P,
+	) -> Result<Vec<Result<KeyValuePair, Error>>, Error>
+	where
+		P: Into<Key> + Send,
+	{
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		let guarded_tx = self.tx.lock().await;
+		let tx = guarded_tx.as_ref().unwrap();
+
+		let db = get_database(&self._db, &cf)?;
+		let prefix: Key = prefix.into();
+		let iterator = db.prefix_iter(tx.read(), &prefix)?;
+		Ok(iterator.map(to_pair).collect())
+	}
+
+	async fn suffix_iterate<S>(
+		&self,
+		cf: CF,
+		suffix: S,
+	) -> Result<Vec<Result<KeyValuePair, Error>>, Error>
+	where
+		S: Into<Key> + Send,
+	{
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		let guarded_tx = self.tx.lock().await;
+		let tx = guarded_tx.as_ref().unwrap();
+
+		let db = get_database(&self._db, &cf)?;
+		let suffix: Key = suffix.into();
+		let iterator = db.iter(tx.read())?;
+		Ok(iterator
+			.map(to_pair)
+			.filter(|pair| pair.as_ref().map_or(true, |(k, _)| k.ends_with(&suffix)))
+			.collect())
+	}
+
+	async fn range_iterate(
+		&self,
+		cf: CF,
+		start: Bound<Key>,
+		end: Bound<Key>,
+	) -> Result<Vec<Result<KeyValuePair, Error>>, Error> {
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		let guarded_tx = self.tx.lock().await;
+		let tx = guarded_tx.as_ref().unwrap();
+
+		let db = get_database(&self._db, &cf)?;
+		let range = (as_slice(&start), as_slice(&end));
+		let iterator = db.range(tx.read(), &range)?;
+		Ok(iterator.map(to_pair).collect())
+	}
+
+	async fn scan_range(
+		&self,
+		cf: CF,
+		start: Bound<Key>,
+		end: Bound<Key>,
+		page: Page,
+		reverse: bool,
+	) -> Result<Vec<Result<KeyValuePair, Error>>, Error> {
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		let guarded_tx = self.tx.lock().await;
+		let tx = guarded_tx.as_ref().unwrap();
+
+		let db = get_database(&self._db, &cf)?;
+		let range = (as_slice(&start), as_slice(&end));
+		Ok(match reverse {
+			true => page.apply(db.rev_range(tx.read(), &range)?).map(to_pair).collect(),
+			false => page.apply(db.range(tx.read(), &range)?).map(to_pair).collect(),
+		})
+	}
+}
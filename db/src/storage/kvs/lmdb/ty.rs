@@ -0,0 +1,33 @@
+use std::collections::HashMap;
+
+use heed::{types::ByteSlice, Database, Env, RoTxn, RwTxn};
+
+use crate::{DBTransaction, CF};
+
+pub type LmdbDatabase = Database<ByteSlice, ByteSlice>;
+
+/// LMDB environment with its named databases, opened once as LMDB doesn't allow opening
+/// a database while a write transaction is running. `None` is the main database.
+pub struct LmdbEnv {
+	pub env: Env,
+	pub dbs: HashMap<CF, LmdbDatabase>,
+}
+
+/// LMDB has a single writer, read transactions don't wait for it
+pub enum LmdbTx {
+	Read(RoTxn<'static>),
+	Write(RwTxn<'static, 'static>),
+}
+
+impl LmdbTx {
+	pub fn read(&self) -> &RoTxn<'static> {
+		match self {
+			LmdbTx::Read(tx) => tx,
+			LmdbTx::Write(tx) => tx,
+		}
+	}
+}
+
+pub type DBType = LmdbEnv;
+pub type TxType = LmdbTx;
+pub type LmdbTransaction = DBTransaction<DBType, TxType>;
@@ -0,0 +1,114 @@
+use std::{
+	collections::HashMap,
+	path::Path,
+	pin::Pin,
+	sync::{atomic::AtomicU64, Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use lazy_static::lazy_static;
+
+use super::{SimAdapter, SimFaults, SimStore, SimTransaction};
+use crate::{
+	Capabilities, ColumnFamilyOptions, DatastoreAdapter, DatastoreStats, Error, StorageAdapterName,
+};
+
+lazy_static! {
+	static ref STORES: Mutex<HashMap<String, Pin<Arc<SimStore>>>> = Mutex::new(HashMap::new());
+}
+
+pub type MemoryTransaction = SimTransaction;
+
+/// # Memory adapter
+/// Scratch datastore living in the memory of the process, to experiment without touching a
+/// database on disk. Stores are kept by name until the process exits: every datastore opened
+/// on the same `memory:NAME` path reads and writes the same keys, until `discard`.
+pub struct MemoryAdapter(SimAdapter);
+
+impl MemoryAdapter {
+	pub fn new(path: &str) -> Result<MemoryAdapter, Error> {
+		let name = path.split_once(':').map_or(path, |(_, name)| name);
+		let store = STORES
+			.lock()
+			.unwrap()
+			.entry(name.to_string())
+			.or_insert_with(|| Arc::pin(SimStore::new(SimFaults::default())))
+			.clone();
+		let path = format!("memory:{}", name);
+		Ok(MemoryAdapter(SimAdapter::with_store(StorageAdapterName::EchoDB, path, store)))
+	}
+
+	/// Drop the keys of the store at `path`, the next datastore opened on it is empty.
+	/// Returns whether a store was found.
+	pub fn discard(path: &str) -> bool {
+		let name = path.split_once(':').map_or(path, |(_, name)| name);
+		STORES.lock().unwrap().remove(name).is_some()
+	}
+}
+
+#[async_trait]
+impl DatastoreAdapter for MemoryAdapter {
+	type Transaction = MemoryTransaction;
+
+	fn capabilities() -> Capabilities {
+		Capabilities {
+			snapshots: true,
+			..Default::default()
+		}
+	}
+
+	fn default() -> Self {
+		MemoryAdapter::new("memory:default").unwrap()
+	}
+
+	fn spawn(&self) -> Self {
+		MemoryAdapter::new(self.path()).unwrap()
+	}
+
+	fn path(&self) -> &str {
+		self.0.path()
+	}
+
+	fn column_family_options(&self) -> Result<Vec<ColumnFamilyOptions>, Error> {
+		self.0.column_family_options()
+	}
+
+	fn stats(&self) -> Result<DatastoreStats, Error> {
+		self.0.stats()
+	}
+
+	// Nothing on disk to copy, the keys are written to another backend with COPY
+	fn backup(&self, _backup: &Path, _progress: &AtomicU64) -> Result<(), Error> {
+		Err(Error::Ds("Memory databases are saved with COPY INTO".to_string()))
+	}
+
+	async fn transaction(&self, w: bool) -> Result<Self::Transaction, Error> {
+		self.0.transaction(w).await
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use crate::{DatastoreAdapter, SimpleTransaction};
+
+	use super::MemoryAdapter;
+
+	#[tokio::test]
+	async fn should_share_store_by_path() {
+		let first = MemoryAdapter::new("memory:shared").unwrap();
+		let mut tx = first.transaction(true).await.unwrap();
+		tx.set(None, "key", "value").await.unwrap();
+		tx.commit().await.unwrap();
+
+		let second = MemoryAdapter::new("memory:shared").unwrap();
+		let tx = second.transaction(false).await.unwrap();
+		assert_eq!(tx.get(None, "key").await.unwrap(), Some(b"value".to_vec()));
+		let other = MemoryAdapter::new("memory:other").unwrap();
+		assert!(!other.transaction(false).await.unwrap().exi(None, "key").await.unwrap());
+
+		assert!(MemoryAdapter::discard("memory:shared"));
+		let discarded = MemoryAdapter::new("memory:shared").unwrap();
+		assert!(!discarded.transaction(false).await.unwrap().exi(None, "key").await.unwrap());
+		assert!(first.transaction(false).await.unwrap().exi(None, "key").await.unwrap());
+	}
+}
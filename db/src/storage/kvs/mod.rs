@@ -1,11 +1,27 @@
+#[cfg(feature = "kv-lmdb")]
+mod lmdb;
+#[cfg(feature = "kv-memory")]
+mod memory;
 #[cfg(feature = "kv-redb")]
 mod redb;
 #[cfg(feature = "kv-rocksdb")]
 mod rocksdb;
+#[cfg(feature = "kv-sim")]
+mod sim;
+#[cfg(feature = "kv-sled")]
+mod sled;
 
 pub const LOG: &str = "edma::kvs";
 
+#[cfg(feature = "kv-lmdb")]
+pub use self::lmdb::*;
+#[cfg(feature = "kv-memory")]
+pub use self::memory::*;
 #[cfg(feature = "kv-redb")]
 pub use self::redb::*;
 #[cfg(feature = "kv-rocksdb")]
 pub use self::rocksdb::*;
+#[cfg(feature = "kv-sim")]
+pub use self::sim::*;
+#[cfg(feature = "kv-sled")]
+pub use self::sled::*;
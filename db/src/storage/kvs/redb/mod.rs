@@ -1,13 +1,17 @@
 pub mod tx;
 pub mod ty;
 
+use std::{path::Path, sync::atomic::AtomicU64};
+
 use async_trait::async_trait;
 use redb::Database;
 pub use tx::*;
 pub use ty::*;
 
 use crate::{
-	util::generate_path, DBTransaction, DatastoreAdapter, Error, StorageAdapter,
+	constant::CF_NAMES,
+	util::{copy_path, generate_path},
+	ColumnFamilyOptions, DBTransaction, DatastoreAdapter, Error, StorageAdapter,
 	StorageAdapterName, StorageVariant,
 };
 pub struct ReDBAdapter(StorageAdapter<DBType>);
@@ -20,7 +24,7 @@ impl ReDBAdapter {
 
 	pub fn new(path: &str) -> Result<ReDBAdapter, Error> {
 		let path = &path["redb:".len()..];
-		let db_instance = unsafe { Database::create(path).unwrap() };
+		let db_instance = unsafe { Database::create(path)? };
 
 		Ok(ReDBAdapter(StorageAdapter::<DBType>::new(
 			StorageAdapterName::ReDB,
@@ -48,6 +52,33 @@ impl DatastoreAdapter for ReDBAdapter {
 		&self.0.path
 	}
 
+	/// The database file is copied while a write transaction is held, so no write of this
+	/// process lands in the middle of the copy
+	fn backup(&self, backup: &Path, progress: &AtomicU64) -> Result<(), Error> {
+		let inner = self.get_initialized_inner()?;
+		let tx = inner.db_instance.begin_write()?;
+		let copied = copy_path(Path::new(self.path()), backup, progress);
+		tx.abort()?;
+		copied
+	}
+
+	fn column_family_options(&self) -> Result<Vec<ColumnFamilyOptions>, Error> {
+		// Redb tables are not configurable, every column family shares the same storage
+		let mut names: Vec<&str> = CF_NAMES.iter().map(|n| n.as_str()).collect();
+		names.sort_unstable();
+		names.insert(0, "default");
+		Ok(names
+			.into_iter()
+			.map(|name| ColumnFamilyOptions {
+				name: name.to_string(),
+				compression: "none".to_string(),
+				comparator: "bytewise".to_string(),
+				merge_operator: None,
+				block_size: None,
+			})
+			.collect())
+	}
+
 	async fn transaction(&self, w: bool) -> Result<Self::Transaction, Error> {
 		let inner = self.get_initialized_inner().unwrap();
 		let db = &inner.db_instance;
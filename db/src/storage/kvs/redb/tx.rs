@@ -1,9 +1,11 @@
+use std::ops::Bound;
+
 use async_trait::async_trait;
 use redb::{RangeIter, ReadableTable, TableDefinition};
 
 use crate::{
 	interface::{Key, KeyValuePair, Val},
-	DBTransaction, Error, SimpleTransaction, CF,
+	DBTransaction, Error, Page, SimpleTransaction, CF,
 };
 
 use super::ty::{DBType, TxType};
@@ -21,6 +23,20 @@ fn filter_with_prefix(
 	})
 }
 
+fn in_range(key: &[u8], start: &Bound<Key>, end: &Bound<Key>) -> bool {
+	let after_start = match start {
+		Bound::Included(start) => key >= start.as_slice(),
+		Bound::Excluded(start) => key > start.as_slice(),
+		Bound::Unbounded => true,
+	};
+	let before_end = match end {
+		Bound::Included(end) => key <= end.as_slice(),
+		Bound::Excluded(end) => key < end.as_slice(),
+		Bound::Unbounded => true,
+	};
+	after_start && before_end
+}
+
 fn filter_with_suffix(
 	iterator: RangeIter<TableKey, TableValue>,
 	suffix: Vec<u8>,
@@ -60,6 +76,11 @@ impl SimpleTransaction for DBTransaction<DBType, TxType> {
 		}
 	}
 
+	// Table length is stored in ReDB metadata, the estimate is exact
+	async fn estimate_count(&mut self, cf: CF) -> Result<usize, Error> {
+		self.count(cf).await
+	}
+
 	async fn cancel(&mut self) -> Result<(), Error> {
 		if self.ok {
 			return Err(Error::TxFinished);
@@ -259,6 +280,56 @@ impl SimpleTransaction for DBTransaction<DBType, TxType> {
 			.collect())
 	}
 
+	async fn reverse_iterate(&self, cf: CF) -> Result<Vec<Result<KeyValuePair, Error>>, Error> {
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		let guarded_tx = self.tx.lock().await;
+		let tx = guarded_tx.as_ref().unwrap();
+
+		let name = get_table_name(cf);
+		let def = TableDefinition::<TableKey, TableValue>::new(&name);
+		let table = tx.open_table(def);
+
+		let iterator = match table.as_ref() {
+			Ok(t) => t.iter()?,
+			Err(_) => return Err(Error::DsNoColumnFamilyFound),
+		};
+
+		Ok(iterator.rev().map(|(k, v)| Ok((k.to_vec(), v.to_vec()))).collect())
+	}
+
+	async fn page_iterate(
+		&self,
+		cf: CF,
+		page: Page,
+		reverse: bool,
+	) -> Result<Vec<Result<KeyValuePair, Error>>, Error> {
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		let guarded_tx = self.tx.lock().await;
+		let tx = guarded_tx.as_ref().unwrap();
+
+		let name = get_table_name(cf);
+		let def = TableDefinition::<TableKey, TableValue>::new(&name);
+		let table = tx.open_table(def);
+
+		let iterator = match table.as_ref() {
+			Ok(t) => t.iter()?,
+			Err(_) => return Err(Error::DsNoColumnFamilyFound),
+		};
+		let iterator: Box<dyn Iterator<Item = (&[u8], &[u8])> + '_> = if reverse {
+			Box::new(iterator.rev())
+		} else {
+			Box::new(iterator)
+		};
+
+		Ok(page.apply(iterator).map(|(k, v)| Ok((k.to_vec(), v.to_vec()))).collect())
+	}
+
 	async fn prefix_iterate<P>(
 		&self,
 		cf: CF,
@@ -321,4 +392,68 @@ impl SimpleTransaction for DBTransaction<DBType, TxType> {
 			})
 			.collect())
 	}
+
+	async fn range_iterate(
+		&self,
+		cf: CF,
+		start: Bound<Key>,
+		end: Bound<Key>,
+	) -> Result<Vec<Result<KeyValuePair, Error>>, Error> {
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		let guarded_tx = self.tx.lock().await;
+		let tx = guarded_tx.as_ref().unwrap();
+
+		let name = get_table_name(cf);
+		let def = TableDefinition::<TableKey, TableValue>::new(&name);
+		let table = tx.open_table(def);
+
+		let iterator = match table.as_ref() {
+			Ok(t) => t.iter()?,
+			Err(_) => return Err(Error::DsNoColumnFamilyFound),
+		};
+
+		Ok(iterator
+			.filter(|(k, _)| in_range(k, &start, &end))
+			.map(|(k, v)| Ok((k.to_vec(), v.to_vec())))
+			.collect())
+	}
+
+	async fn scan_range(
+		&self,
+		cf: CF,
+		start: Bound<Key>,
+		end: Bound<Key>,
+		page: Page,
+		reverse: bool,
+	) -> Result<Vec<Result<KeyValuePair, Error>>, Error> {
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		let guarded_tx = self.tx.lock().await;
+		let tx = guarded_tx.as_ref().unwrap();
+
+		let name = get_table_name(cf);
+		let def = TableDefinition::<TableKey, TableValue>::new(&name);
+		let table = tx.open_table(def);
+
+		let iterator = match table.as_ref() {
+			Ok(t) => t.iter()?,
+			Err(_) => return Err(Error::DsNoColumnFamilyFound),
+		};
+		let iterator: Box<dyn Iterator<Item = (&[u8], &[u8])> + '_> = if reverse {
+			Box::new(iterator.rev())
+		} else {
+			Box::new(iterator)
+		};
+		// Keys in range are contiguous, the iteration stops after the last one
+		let pairs = iterator
+			.skip_while(|(k, _)| !in_range(k, &start, &end))
+			.take_while(|(k, _)| in_range(k, &start, &end));
+
+		Ok(page.apply(pairs).map(|(k, v)| Ok((k.to_vec(), v.to_vec()))).collect())
+	}
 }
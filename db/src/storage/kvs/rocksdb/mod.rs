@@ -1,18 +1,60 @@
+pub mod two_phase;
 pub mod tx;
 pub mod ty;
 
+use std::{
+	fs,
+	io::ErrorKind,
+	path::Path,
+	sync::atomic::{AtomicU64, Ordering},
+	time::{SystemTime, UNIX_EPOCH},
+};
+
 use async_trait::async_trait;
+pub use two_phase::*;
 pub use tx::*;
 pub use ty::*;
 
 use crate::{
 	constant::CF_NAMES,
 	err::Error,
-	model::{DBTransaction, DatastoreAdapter, StorageAdapter, StorageAdapterName},
-	util::generate_path,
+	interface::KeyValuePair,
+	model::{
+		Capabilities, ColumnFamilyOptions, ColumnFamilyStats, DBTransaction, DatastoreAdapter,
+		DatastoreStats, StorageAdapter, StorageAdapterName,
+	},
+	storage::{get_comparator, BYTEWISE_COMPARATOR},
+	util::{disk_size, ensure_new_path, generate_path},
 	StorageVariant,
 };
-use rocksdb::{DBCompactionStyle, OptimisticTransactionDB, Options};
+use rocksdb::{
+	checkpoint::Checkpoint, ColumnFamilyDescriptor, DBCompactionStyle, IteratorMode,
+	OptimisticTransactionDB, Options, SstFileWriter, DB,
+};
+
+/// DB properties reported by `properties`: keys and SST files of the default column family,
+/// files per level, memtables, compactions and the write stall state
+pub const ROCKSDB_PROPERTIES: [&str; 16] = [
+	"rocksdb.estimate-num-keys",
+	"rocksdb.total-sst-files-size",
+	"rocksdb.num-files-at-level0",
+	"rocksdb.num-files-at-level1",
+	"rocksdb.num-files-at-level2",
+	"rocksdb.num-files-at-level3",
+	"rocksdb.cur-size-all-mem-tables",
+	"rocksdb.num-immutable-mem-table",
+	"rocksdb.mem-table-flush-pending",
+	"rocksdb.num-running-flushes",
+	"rocksdb.compaction-pending",
+	"rocksdb.num-running-compactions",
+	"rocksdb.estimate-pending-compaction-bytes",
+	"rocksdb.actual-delayed-write-rate",
+	"rocksdb.is-write-stopped",
+	"rocksdb.background-errors",
+];
+
+/// Levels of the LSM tree
+const LEVELS: usize = 4;
 
 #[derive(Debug)]
 pub struct RocksDBAdapter(StorageAdapter<DBType>);
@@ -25,9 +67,8 @@ impl RocksDBAdapter {
 
 	pub fn new(path: &str, max_open_files: Option<i32>) -> Result<RocksDBAdapter, Error> {
 		let path = &path["rocksdb:".len()..];
-		let opts = get_options(max_open_files);
-		let cf_names = CF_NAMES.iter();
-		let db_instance = OptimisticTransactionDB::open_cf(&opts, path, cf_names)?;
+		let (opts, descriptors) = open_options(path, max_open_files)?;
+		let db_instance = OptimisticTransactionDB::open_cf_descriptors(&opts, path, descriptors)?;
 		Ok(RocksDBAdapter(StorageAdapter::<DBType>::new(
 			StorageAdapterName::RocksDB,
 			path.to_string(),
@@ -35,12 +76,153 @@ impl RocksDBAdapter {
 			StorageVariant::KeyValueStore,
 		)?))
 	}
+
+	/// Create an empty column family with the default options
+	pub fn create_cf(&self, name: &str) -> Result<(), Error> {
+		let inner = self.get_initialized_inner()?;
+		if inner.db_instance.cf_handle(name).is_some() {
+			return Err(Error::Ds(format!("Column family {} already exists", name)));
+		}
+		inner.db_instance.create_cf(name, &get_options(None))?;
+		Ok(())
+	}
+
+	/// Drop a column family with its keys. The default column family can't be dropped.
+	pub fn drop_cf(&self, name: &str) -> Result<(), Error> {
+		let inner = self.get_initialized_inner()?;
+		if name == "default" {
+			return Err(Error::Ds("The default column family can't be dropped".to_string()));
+		}
+		if inner.db_instance.cf_handle(name).is_none() {
+			return Err(Error::DsNoColumnFamilyFound);
+		}
+		inner.db_instance.drop_cf(name)?;
+		Ok(())
+	}
+
+	/// Write the pairs into an SST file ingested into the column family, without going through
+	/// the WAL and the memtables. Keys are sorted and the last value of a duplicated key is
+	/// kept. Returns false without writing when the column family uses a custom comparator,
+	/// the file would be sorted differently.
+	pub fn ingest(&self, name: &str, mut pairs: Vec<KeyValuePair>) -> Result<bool, Error> {
+		let inner = self.get_initialized_inner()?;
+		if self.has_custom_comparator(name)? {
+			return Ok(false);
+		}
+		if pairs.is_empty() {
+			return Ok(true);
+		}
+		// The sort is stable, duplicated keys keep their order and the last one wins
+		pairs.sort_by(|(a, _), (b, _)| a.cmp(b));
+		pairs.reverse();
+		pairs.dedup_by(|(a, _), (b, _)| a == b);
+		pairs.reverse();
+
+		let db = &inner.db_instance;
+		let cf = db.cf_handle(name).ok_or(Error::DsNoColumnFamilyFound)?;
+		let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_nanos());
+		let file = Path::new(&self.0.path).join(format!("ingest-{}.sst", nanos));
+		let opts = get_options(None);
+		let mut writer = SstFileWriter::create(&opts);
+		writer.open(&file)?;
+		for (key, value) in pairs.iter() {
+			writer.put(key, value)?;
+		}
+		writer.finish()?;
+		let ingested = db.ingest_external_file_cf(&cf, vec![&file]);
+		let _ = fs::remove_file(&file);
+		ingested?;
+		Ok(true)
+	}
+
+	/// Write the pairs of the column family into SST files of at most `batch` pairs, named
+	/// `<name>-<n>.sst` in `dir`. Returns the paths of the files, none for an empty column
+	/// family.
+	pub fn export_sst(&self, name: &str, dir: &str, batch: usize) -> Result<Vec<String>, Error> {
+		if batch == 0 {
+			return Err(Error::Ds("SST batch size must be at least 1".to_string()));
+		}
+		let inner = self.get_initialized_inner()?;
+		if self.has_custom_comparator(name)? {
+			return Err(Error::Ds(format!("{} uses a custom comparator", name)));
+		}
+		fs::create_dir_all(dir).map_err(|e| Error::Ds(e.to_string()))?;
+		let db = &inner.db_instance;
+		let cf = db.cf_handle(name).ok_or(Error::DsNoColumnFamilyFound)?;
+		let opts = get_options(None);
+		let mut files = vec![];
+		let mut writer: Option<SstFileWriter> = None;
+		let mut written = 0;
+		for pair in db.iterator_cf(&cf, IteratorMode::Start) {
+			let (key, value) = pair?;
+			if writer.is_none() {
+				let file = Path::new(dir).join(format!("{}-{}.sst", name, files.len()));
+				let mut next = SstFileWriter::create(&opts);
+				next.open(&file)?;
+				files.push(file.to_string_lossy().to_string());
+				writer = Some(next);
+			}
+			if let Some(w) = writer.as_mut() {
+				w.put(key, value)?;
+			}
+			written += 1;
+			if written % batch == 0 {
+				if let Some(mut w) = writer.take() {
+					w.finish()?;
+				}
+			}
+		}
+		if let Some(mut w) = writer.take() {
+			w.finish()?;
+		}
+		Ok(files)
+	}
+
+	/// Ingest SST files into the column family, created when missing. The files are copied
+	/// into the database and left in place.
+	pub fn ingest_sst(&self, name: &str, files: &[String]) -> Result<(), Error> {
+		let inner = self.get_initialized_inner()?;
+		if self.has_custom_comparator(name)? {
+			return Err(Error::Ds(format!("{} uses a custom comparator", name)));
+		}
+		let db = &inner.db_instance;
+		if db.cf_handle(name).is_none() {
+			db.create_cf(name, &get_options(None))?;
+		}
+		let cf = db.cf_handle(name).ok_or(Error::DsNoColumnFamilyFound)?;
+		db.ingest_external_file_cf(&cf, files.iter().collect())?;
+		Ok(())
+	}
+
+	/// Whether the keys of the column family are sorted by a custom comparator, SST files
+	/// written with the default options would be sorted differently
+	fn has_custom_comparator(&self, name: &str) -> Result<bool, Error> {
+		Ok(read_options_file(&self.0.path)?.into_iter().any(|o| {
+			o.name == name && !o.comparator.is_empty() && o.comparator != BYTEWISE_COMPARATOR
+		}))
+	}
+
+	/// Names of the column families of the database, `default` first
+	pub fn list_cf(&self) -> Result<Vec<String>, Error> {
+		let mut names = DB::list_cf(&Options::default(), &self.0.path)?;
+		names.sort_unstable_by_key(|name| (name != "default", name.clone()));
+		Ok(names)
+	}
 }
 
 #[async_trait]
 impl DatastoreAdapter for RocksDBAdapter {
 	type Transaction = RocksDBTransaction;
 
+	fn capabilities() -> Capabilities {
+		Capabilities {
+			column_families: true,
+			snapshots: true,
+			backup: true,
+			sst_files: true,
+		}
+	}
+
 	fn default() -> Self {
 		let path = &generate_path("rocksdb", None);
 		RocksDBAdapter::new(path, None).unwrap()
@@ -54,6 +236,59 @@ impl DatastoreAdapter for RocksDBAdapter {
 		&self.0.path
 	}
 
+	/// Backups are RocksDB checkpoints, written after a flush of the memtables. The backup is
+	/// a database directory of its own, its SST files are hard links when it is on the same
+	/// file system.
+	fn backup(&self, backup: &Path, progress: &AtomicU64) -> Result<(), Error> {
+		ensure_new_path(backup)?;
+		let inner = self.get_initialized_inner()?;
+		Checkpoint::new(&*inner.db_instance)?.create_checkpoint(backup)?;
+		progress.fetch_add(disk_size(backup), Ordering::Relaxed);
+		Ok(())
+	}
+
+	fn column_family_options(&self) -> Result<Vec<ColumnFamilyOptions>, Error> {
+		read_options_file(&self.0.path)
+	}
+
+	fn properties(&self) -> Result<Vec<(String, String)>, Error> {
+		let inner = self.get_initialized_inner()?;
+		let mut properties = vec![];
+		for name in ROCKSDB_PROPERTIES {
+			// Properties unknown to the linked RocksDB version are skipped
+			if let Some(value) = inner.db_instance.property_value(name)? {
+				properties.push((name.to_string(), value));
+			}
+		}
+		Ok(properties)
+	}
+
+	// Column family statistics are read from the properties of each column family
+	fn stats(&self) -> Result<DatastoreStats, Error> {
+		let inner = self.get_initialized_inner()?;
+		let db = &inner.db_instance;
+		let mut column_families = vec![];
+		for name in self.list_cf()? {
+			let cf = db.cf_handle(&name).ok_or(Error::DsNoColumnFamilyFound)?;
+			let files = (0..LEVELS)
+				.map(|level| {
+					let property = format!("rocksdb.num-files-at-level{}", level);
+					db.property_int_value_cf(&cf, property.as_str())
+				})
+				.collect::<Result<Vec<_>, _>>()?;
+			column_families.push(ColumnFamilyStats {
+				keys: db.property_int_value_cf(&cf, "rocksdb.estimate-num-keys")?,
+				size: db.property_int_value_cf(&cf, "rocksdb.total-sst-files-size")?,
+				files: files.into_iter().sum(),
+				name,
+			});
+		}
+		Ok(DatastoreStats {
+			disk_size: disk_size(Path::new(&self.0.path)),
+			column_families,
+		})
+	}
+
 	async fn transaction(&self, rw: bool) -> Result<RocksDBTransaction, Error> {
 		let inner = self.get_initialized_inner().unwrap();
 		let db = &inner.db_instance;
@@ -77,6 +312,115 @@ unsafe fn extend_tx_lifetime(
 	>(tx)
 }
 
+/// Options of the database at `path` and descriptors of the column families to open with
+/// it: the EDMA ones and those created with `create_cf`
+fn open_options(
+	path: &str,
+	max_open_files: Option<i32>,
+) -> Result<(Options, Vec<ColumnFamilyDescriptor>), Error> {
+	// Column families created with a custom comparator must be opened with it
+	let existing = read_options_file(path)?;
+	let cf_options = |name: &str| -> Result<Options, Error> {
+		let mut opts = get_options(max_open_files);
+		let comparator = existing.iter().find(|o| o.name == name).map(|o| &o.comparator);
+		match comparator {
+			Some(comparator) if !comparator.is_empty() && comparator != BYTEWISE_COMPARATOR => {
+				let compare = get_comparator(comparator).ok_or_else(|| {
+					Error::Ds(format!("Comparator {} is not registered", comparator))
+				})?;
+				opts.set_comparator(comparator, compare);
+			}
+			_ => {}
+		}
+		Ok(opts)
+	};
+
+	let opts = cf_options("default")?;
+	let mut names: Vec<&str> = CF_NAMES.iter().map(|name| name.as_str()).collect();
+	names.extend(existing.iter().map(|o| o.name.as_str()).filter(|name| *name != "default"));
+	names.sort_unstable();
+	names.dedup();
+	let descriptors = names
+		.into_iter()
+		.map(|name| Ok(ColumnFamilyDescriptor::new(name, cf_options(name)?)))
+		.collect::<Result<Vec<_>, Error>>()?;
+	Ok((opts, descriptors))
+}
+
+/// Rust bindings don't expose option getters, read the latest OPTIONS file RocksDB
+/// persists in the database directory on every open instead. A database not created
+/// yet has no options.
+fn read_options_file(path: &str) -> Result<Vec<ColumnFamilyOptions>, Error> {
+	let entries = match fs::read_dir(path) {
+		Ok(entries) => entries,
+		Err(err) if err.kind() == ErrorKind::NotFound => return Ok(vec![]),
+		Err(err) => return Err(Error::Ds(err.to_string())),
+	};
+	let latest = entries
+		.filter_map(|entry| entry.ok())
+		.filter_map(|entry| {
+			let name = entry.file_name().into_string().ok()?;
+			let number = name.strip_prefix("OPTIONS-")?.parse::<u64>().ok()?;
+			Some((number, entry.path()))
+		})
+		.max_by_key(|(number, _)| *number);
+
+	match latest {
+		Some((_, file)) => {
+			let content = fs::read_to_string(file).map_err(|e| Error::Ds(e.to_string()))?;
+			Ok(parse_options_file(&content))
+		}
+		None => Ok(vec![]),
+	}
+}
+
+/// Parse the column family sections of a RocksDB OPTIONS file
+fn parse_options_file(content: &str) -> Vec<ColumnFamilyOptions> {
+	let mut options: Vec<ColumnFamilyOptions> = vec![];
+	// Column family of the current section, flagged when it holds table options
+	let mut section: Option<(String, bool)> = None;
+	for line in content.lines().map(str::trim) {
+		if line.starts_with('[') {
+			let name = line.split('"').nth(1).map(str::to_string);
+			section = match name {
+				Some(name) if line.starts_with("[CFOptions") => {
+					options.push(ColumnFamilyOptions {
+						name: name.clone(),
+						..Default::default()
+					});
+					Some((name, false))
+				}
+				Some(name) if line.starts_with("[TableOptions") => Some((name, true)),
+				_ => None,
+			};
+			continue;
+		}
+
+		let (name, table) = match &section {
+			Some(section) => section,
+			None => continue,
+		};
+		let (key, value) = match line.split_once('=') {
+			Some((key, value)) => (key.trim(), value.trim().to_string()),
+			None => continue,
+		};
+		let cf = match options.iter_mut().find(|o| &o.name == name) {
+			Some(cf) => cf,
+			None => continue,
+		};
+		match (key, table) {
+			("compression", false) => cf.compression = value,
+			("comparator", false) => cf.comparator = value,
+			("merge_operator", false) => {
+				cf.merge_operator = Some(value).filter(|v| v != "nullptr");
+			}
+			("block_size", true) => cf.block_size = value.parse().ok(),
+			_ => {}
+		}
+	}
+	options
+}
+
 pub fn get_options(max_open_files: Option<i32>) -> Options {
 	// Current tuning based off of the total ordered example, flash
 	// storage example on
@@ -93,7 +437,7 @@ pub fn get_options(max_open_files: Option<i32>) -> Options {
 	opts.set_level_zero_file_num_compaction_trigger(8);
 	opts.set_level_zero_slowdown_writes_trigger(17);
 	opts.set_level_zero_stop_writes_trigger(24);
-	opts.set_num_levels(4);
+	opts.set_num_levels(LEVELS as i32);
 	opts.set_max_bytes_for_level_base(536_870_912); // 512mb
 	opts.set_max_bytes_for_level_multiplier(8.0);
 
@@ -103,3 +447,27 @@ pub fn get_options(max_open_files: Option<i32>) -> Options {
 
 	opts
 }
+
+#[cfg(test)]
+mod test {
+	use crate::{util::generate_path, RocksDBAdapter};
+
+	#[test]
+	fn should_create_and_drop_column_family() {
+		let path = generate_path("rocksdb", None);
+		let adapter = RocksDBAdapter::new(&path, None).unwrap();
+		adapter.create_cf("orders:v1").unwrap();
+		assert!(adapter.create_cf("orders:v1").is_err());
+		assert!(adapter.list_cf().unwrap().contains(&"orders:v1".to_string()));
+		drop(adapter);
+
+		// Created column families are opened again with the database
+		let adapter = RocksDBAdapter::new(&path, None).unwrap();
+		let names = adapter.list_cf().unwrap();
+		assert_eq!(names[0], "default");
+		assert!(names.contains(&"orders:v1".to_string()));
+		adapter.drop_cf("orders:v1").unwrap();
+		assert!(!adapter.list_cf().unwrap().contains(&"orders:v1".to_string()));
+		assert!(adapter.drop_cf("default").is_err());
+	}
+}
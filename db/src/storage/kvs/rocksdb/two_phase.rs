@@ -0,0 +1,148 @@
+use std::{path::Path, sync::atomic::AtomicU64};
+
+use async_trait::async_trait;
+use rocksdb::{TransactionDB, TransactionDBOptions};
+
+use super::{open_options, read_options_file, TwoPhaseDBType, TwoPhaseRocksDBTransaction};
+use crate::{
+	err::Error,
+	model::{
+		Capabilities, ColumnFamilyOptions, DBTransaction, DatastoreAdapter, StorageAdapter,
+		StorageAdapterName,
+	},
+	util::generate_path,
+	StorageVariant,
+};
+
+/// RocksDB opened with pessimistic transactions, addressed as `rocksdb-2pc:PATH`. Its
+/// transactions can be prepared for a two-phase commit, the prepared ones are recovered
+/// when the database is opened again.
+pub struct TwoPhaseRocksDBAdapter(StorageAdapter<TwoPhaseDBType>);
+
+#[cfg(feature = "test-suite")]
+crate::full_adapter_test_impl!(TwoPhaseRocksDBAdapter::default());
+
+impl TwoPhaseRocksDBAdapter {
+	impl_new_type_adapter!(TwoPhaseDBType);
+
+	pub fn new(path: &str, max_open_files: Option<i32>) -> Result<TwoPhaseRocksDBAdapter, Error> {
+		let path = &path["rocksdb-2pc:".len()..];
+		let (opts, descriptors) = open_options(path, max_open_files)?;
+		let txn_opts = TransactionDBOptions::default();
+		let db_instance = TransactionDB::open_cf_descriptors(&opts, &txn_opts, path, descriptors)?;
+		Ok(TwoPhaseRocksDBAdapter(StorageAdapter::<TwoPhaseDBType>::new(
+			StorageAdapterName::RocksDB,
+			path.to_string(),
+			db_instance,
+			StorageVariant::KeyValueStore,
+		)?))
+	}
+
+	/// Begin a write transaction named `name`, a name is required to prepare it. The name
+	/// must be unique among the running and prepared transactions.
+	pub fn named_transaction(&self, name: &str) -> Result<TwoPhaseRocksDBTransaction, Error> {
+		let inner = self.get_initialized_inner()?;
+		let db = &inner.db_instance;
+		let tx = unsafe { extend_tx_lifetime(db.transaction()) };
+		tx.set_name(name.as_bytes())?;
+		DBTransaction::new(tx, db.clone(), true)
+	}
+
+	/// Transactions prepared before the database was last closed, with their names. They
+	/// are still to be committed or cancelled, as decided by the transaction manager.
+	pub fn prepared_transactions(
+		&self,
+	) -> Result<Vec<(String, TwoPhaseRocksDBTransaction)>, Error> {
+		let inner = self.get_initialized_inner()?;
+		let db = &inner.db_instance;
+		db.prepared_transactions()
+			.into_iter()
+			.map(|tx| -> Result<_, Error> {
+				let name = String::from_utf8_lossy(&tx.get_name().unwrap_or_default()).to_string();
+				let tx = unsafe { extend_tx_lifetime(tx) };
+				Ok((name, DBTransaction::new(tx, db.clone(), true)?))
+			})
+			.collect()
+	}
+}
+
+#[async_trait]
+impl DatastoreAdapter for TwoPhaseRocksDBAdapter {
+	type Transaction = TwoPhaseRocksDBTransaction;
+
+	// The bindings don't expose checkpoints, properties or column family management of
+	// a TransactionDB
+	fn capabilities() -> Capabilities {
+		Capabilities {
+			snapshots: true,
+			..Default::default()
+		}
+	}
+
+	fn default() -> Self {
+		let path = &generate_path("rocksdb-2pc", None);
+		TwoPhaseRocksDBAdapter::new(path, None).unwrap()
+	}
+
+	fn spawn(&self) -> Self {
+		TwoPhaseRocksDBAdapter::default()
+	}
+
+	fn path(&self) -> &str {
+		&self.0.path
+	}
+
+	fn backup(&self, _backup: &Path, _progress: &AtomicU64) -> Result<(), Error> {
+		Err(Error::Ds("Backups of rocksdb-2pc databases are not supported".to_string()))
+	}
+
+	fn column_family_options(&self) -> Result<Vec<ColumnFamilyOptions>, Error> {
+		read_options_file(&self.0.path)
+	}
+
+	async fn transaction(&self, rw: bool) -> Result<TwoPhaseRocksDBTransaction, Error> {
+		let inner = self.get_initialized_inner()?;
+		let db = &inner.db_instance;
+		let tx = unsafe { extend_tx_lifetime(db.transaction()) };
+		DBTransaction::new(tx, db.clone(), rw)
+	}
+}
+
+// The database reference must always outlive the transaction, see the optimistic adapter
+unsafe fn extend_tx_lifetime(
+	tx: rocksdb::Transaction<'_, TransactionDB>,
+) -> rocksdb::Transaction<'static, TransactionDB> {
+	std::mem::transmute::<
+		rocksdb::Transaction<'_, TransactionDB>,
+		rocksdb::Transaction<'static, TransactionDB>,
+	>(tx)
+}
+
+#[cfg(test)]
+mod test {
+	use crate::{util::generate_path, DatastoreAdapter, SimpleTransaction, TwoPhaseRocksDBAdapter};
+
+	#[tokio::test]
+	async fn should_recover_prepared_transactions() {
+		let path = generate_path("rocksdb-2pc", None);
+		let adapter = TwoPhaseRocksDBAdapter::new(&path, None).unwrap();
+		let mut tx = adapter.named_transaction("order-1").unwrap();
+		tx.set(None, "order:1", "paid").await.unwrap();
+		tx.prepare().await.unwrap();
+		// Dropped without a decision, like a crash between the two phases
+		drop(tx);
+		drop(adapter);
+
+		let adapter = TwoPhaseRocksDBAdapter::new(&path, None).unwrap();
+		let mut prepared = adapter.prepared_transactions().unwrap();
+		assert_eq!(prepared.len(), 1);
+		let (name, mut tx) = prepared.remove(0);
+		assert_eq!(name, "order-1");
+		tx.commit().await.unwrap();
+
+		let tx = adapter.transaction(false).await.unwrap();
+		assert_eq!(tx.get(None, "order:1").await.unwrap(), Some(b"paid".to_vec()));
+		let mut tx = adapter.transaction(true).await.unwrap();
+		assert!(tx.prepare().await.is_err());
+	}
+}
@@ -1,24 +1,28 @@
-use std::sync::Arc;
+use std::{ops::Bound, sync::Arc};
 
 use async_trait::async_trait;
-use rocksdb::{BoundColumnFamily, DBAccess, DBIteratorWithThreadMode, IteratorMode};
+use rocksdb::{
+	BoundColumnFamily, DBAccess, DBIteratorWithThreadMode, Direction, IteratorMode, ReadOptions,
+};
 
-use super::ty::{DBType, TxType};
+use super::ty::TransactionDBType;
 use crate::{
 	err::Error,
 	interface::{
 		kv::{Key, Val},
 		KeyValuePair,
 	},
-	model::{DBTransaction, SimpleTransaction},
-	CF,
+	model::{DBTransaction, Page, SimpleTransaction},
+	Snapshot, CF,
 };
 
-fn take_with_prefix<T: DBAccess>(
+// Keys sharing a prefix are only contiguous under the bytewise comparator, filter the
+// whole column family to support databases created with custom comparators
+fn filter_with_prefix<T: DBAccess>(
 	iterator: DBIteratorWithThreadMode<T>,
 	prefix: Vec<u8>,
 ) -> impl Iterator<Item = Result<(Box<[u8]>, Box<[u8]>), rocksdb::Error>> + '_ {
-	iterator.take_while(move |item| -> bool {
+	iterator.filter(move |item| -> bool {
 		if let Ok((ref k, _)) = *item {
 			k.starts_with(&prefix)
 		} else {
@@ -27,11 +31,11 @@ fn take_with_prefix<T: DBAccess>(
 	})
 }
 
-fn take_with_suffix<T: DBAccess>(
+fn filter_with_suffix<T: DBAccess>(
 	iterator: DBIteratorWithThreadMode<T>,
 	suffix: Vec<u8>,
 ) -> impl Iterator<Item = Result<(Box<[u8]>, Box<[u8]>), rocksdb::Error>> + '_ {
-	iterator.take_while(move |item| -> bool {
+	iterator.filter(move |item| -> bool {
 		if let Ok((ref k, _)) = *item {
 			k.ends_with(&suffix)
 		} else {
@@ -40,13 +44,13 @@ fn take_with_suffix<T: DBAccess>(
 	})
 }
 
-impl DBTransaction<DBType, TxType> {
+impl<D: TransactionDBType> DBTransaction<D, rocksdb::Transaction<'static, D>> {
 	fn get_column_family(&self, cf: CF) -> Result<Arc<BoundColumnFamily>, Error> {
 		if cf.is_none() {
 			return Err(Error::DsColumnFamilyIsNotValid);
 		}
 		let cf_name = String::from_utf8(cf.unwrap()).unwrap();
-		let bounded_cf = self._db.cf_handle(&cf_name);
+		let bounded_cf = self._db.column_family(&cf_name);
 
 		match bounded_cf {
 			Some(cf) => Ok(cf),
@@ -56,7 +60,9 @@ impl DBTransaction<DBType, TxType> {
 }
 
 #[async_trait(?Send)]
-impl SimpleTransaction for DBTransaction<DBType, TxType> {
+impl<D: TransactionDBType> SimpleTransaction
+	for DBTransaction<D, rocksdb::Transaction<'static, D>>
+{
 	fn closed(&self) -> bool {
 		self.ok
 	}
@@ -68,10 +74,30 @@ impl SimpleTransaction for DBTransaction<DBType, TxType> {
 
 		let guarded_tx = self.tx.lock().await;
 		let tx = guarded_tx.as_ref().unwrap();
-		let cf = &self.get_column_family(cf).unwrap();
+		let cf = &self.get_column_family(cf)?;
 		Ok(tx.iterator_cf(cf, IteratorMode::Start).count())
 	}
 
+	// RocksDB estimate includes deleted and overwritten keys until they are compacted. Keys
+	// are counted when the database doesn't estimate them.
+	async fn estimate_count(&mut self, cf: CF) -> Result<usize, Error> {
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		let estimate = match cf {
+			Some(_) => {
+				let column_family = self.get_column_family(cf.clone())?;
+				self._db.estimate_keys(Some(&column_family))?
+			}
+			None => self._db.estimate_keys(None)?,
+		};
+		match estimate {
+			Some(estimate) => Ok(estimate as usize),
+			None => self.count(cf).await,
+		}
+	}
+
 	async fn cancel(&mut self) -> Result<(), Error> {
 		if self.ok {
 			return Err(Error::TxFinished);
@@ -111,6 +137,26 @@ impl SimpleTransaction for DBTransaction<DBType, TxType> {
 		Ok(())
 	}
 
+	// Only transactions of a TransactionDB can be prepared, optimistic ones are validated
+	// when they are committed
+	async fn prepare(&mut self) -> Result<(), Error> {
+		if !D::TWO_PHASE {
+			return Err(Error::TxPrepareUnsupported);
+		}
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		// Check to see if transaction is writable
+		if !self.writable {
+			return Err(Error::TxReadonly);
+		}
+
+		let tx = self.tx.lock().await;
+		tx.as_ref().unwrap().prepare()?;
+		Ok(())
+	}
+
 	async fn exi<K>(&self, cf: CF, key: K) -> Result<bool, Error>
 	where
 		K: Into<Key> + Send,
@@ -122,7 +168,7 @@ impl SimpleTransaction for DBTransaction<DBType, TxType> {
 		let tx = self.tx.lock().await;
 		match cf {
 			Some(_) => {
-				let cf = &self.get_column_family(cf).unwrap();
+				let cf = &self.get_column_family(cf)?;
 				let result = tx.as_ref().unwrap().get_cf(cf, key.into()).unwrap().is_some();
 				Ok(result)
 			}
@@ -145,7 +191,7 @@ impl SimpleTransaction for DBTransaction<DBType, TxType> {
 		let tx = guarded_tx.as_ref().unwrap();
 		Ok(match cf {
 			Some(_) => {
-				let cf = &self.get_column_family(cf).unwrap();
+				let cf = &self.get_column_family(cf)?;
 				tx.get_cf(cf, key.into()).unwrap()
 			}
 			None => tx.get(key.into()).unwrap(),
@@ -195,7 +241,7 @@ impl SimpleTransaction for DBTransaction<DBType, TxType> {
 		let tx = guarded_tx.as_ref().unwrap();
 		match cf {
 			Some(_) => {
-				let cf = &self.get_column_family(cf).unwrap();
+				let cf = &self.get_column_family(cf)?;
 				tx.put_cf(cf, key.into(), val.into())?;
 			}
 			None => tx.put(key.into(), val.into())?,
@@ -225,7 +271,7 @@ impl SimpleTransaction for DBTransaction<DBType, TxType> {
 
 		match cf {
 			Some(_) => {
-				let cf = &self.get_column_family(cf).unwrap();
+				let cf = &self.get_column_family(cf)?;
 				match tx.get_cf(cf, &key)? {
 					None => tx.put_cf(cf, key, val)?,
 					_ => return Err(Error::TxConditionNotMet),
@@ -260,10 +306,18 @@ impl SimpleTransaction for DBTransaction<DBType, TxType> {
 		let guarded_tx = self.tx.lock().await;
 		let tx = guarded_tx.as_ref().unwrap();
 
-		let cf = &self.get_column_family(cf).unwrap();
-		match tx.get_cf(cf, &key)? {
-			Some(_v) => tx.delete_cf(cf, key)?,
-			None => return Err(Error::TxnKeyNotFound),
+		match cf {
+			Some(_) => {
+				let cf = &self.get_column_family(cf)?;
+				match tx.get_cf(cf, &key)? {
+					Some(_v) => tx.delete_cf(cf, key)?,
+					None => return Err(Error::TxnKeyNotFound),
+				};
+			}
+			None => match tx.get(&key)? {
+				Some(_v) => tx.delete(key)?,
+				None => return Err(Error::TxnKeyNotFound),
+			},
 		};
 
 		Ok(())
@@ -300,6 +354,59 @@ impl SimpleTransaction for DBTransaction<DBType, TxType> {
 		}
 	}
 
+	async fn reverse_iterate(&self, cf: CF) -> Result<Vec<Result<KeyValuePair, Error>>, Error> {
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		let guarded_tx = self.tx.lock().await;
+		let tx = guarded_tx.as_ref().unwrap();
+
+		let iterator = match cf {
+			Some(_) => tx.iterator_cf(&self.get_column_family(cf)?, IteratorMode::End),
+			None => tx.iterator(IteratorMode::End),
+		};
+
+		Ok(iterator
+			.map(|pair| {
+				let (k, v) = pair?;
+				Ok((k.to_vec(), v.to_vec()))
+			})
+			.collect())
+	}
+
+	async fn page_iterate(
+		&self,
+		cf: CF,
+		page: Page,
+		reverse: bool,
+	) -> Result<Vec<Result<KeyValuePair, Error>>, Error> {
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		let guarded_tx = self.tx.lock().await;
+		let tx = guarded_tx.as_ref().unwrap();
+
+		let mode = if reverse {
+			IteratorMode::End
+		} else {
+			IteratorMode::Start
+		};
+		let iterator = match cf {
+			Some(_) => tx.iterator_cf(&self.get_column_family(cf)?, mode),
+			None => tx.iterator(mode),
+		};
+
+		Ok(page
+			.apply(iterator)
+			.map(|pair| {
+				let (k, v) = pair?;
+				Ok((k.to_vec(), v.to_vec()))
+			})
+			.collect())
+	}
+
 	async fn suffix_iterate<S>(
 		&self,
 		cf: CF,
@@ -317,12 +424,12 @@ impl SimpleTransaction for DBTransaction<DBType, TxType> {
 		let suffix: Key = suffix.into();
 		let iterator = match cf {
 			Some(_) => {
-				let cf = &self.get_column_family(cf).unwrap();
+				let cf = &self.get_column_family(cf)?;
 				tx.iterator_cf(cf, IteratorMode::Start)
 			}
 			None => tx.iterator(IteratorMode::Start),
 		};
-		let taken_iterator = take_with_suffix(iterator, suffix);
+		let taken_iterator = filter_with_suffix(iterator, suffix);
 
 		Ok(taken_iterator
 			.map(|pair| {
@@ -350,12 +457,12 @@ impl SimpleTransaction for DBTransaction<DBType, TxType> {
 		let prefix: Key = prefix.into();
 		let iterator = match cf {
 			Some(_) => {
-				let cf = &self.get_column_family(cf).unwrap();
+				let cf = &self.get_column_family(cf)?;
 				tx.iterator_cf(cf, IteratorMode::Start)
 			}
 			None => tx.iterator(IteratorMode::Start),
 		};
-		let taken_iterator = take_with_prefix(iterator, prefix);
+		let taken_iterator = filter_with_prefix(iterator, prefix);
 
 		Ok(taken_iterator
 			.map(|v| {
@@ -364,4 +471,149 @@ impl SimpleTransaction for DBTransaction<DBType, TxType> {
 			})
 			.collect())
 	}
+
+	async fn range_iterate(
+		&self,
+		cf: CF,
+		start: Bound<Key>,
+		end: Bound<Key>,
+	) -> Result<Vec<Result<KeyValuePair, Error>>, Error> {
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		let guarded_tx = self.tx.lock().await;
+		let tx = guarded_tx.as_ref().unwrap();
+		// Bounds are applied by the iterator to follow the comparator of the column family
+		let mut opts = ReadOptions::default();
+		if let Bound::Included(end) | Bound::Excluded(end) = &end {
+			opts.set_iterate_upper_bound(end.clone());
+		}
+		let mode = match &start {
+			Bound::Included(start) | Bound::Excluded(start) => {
+				IteratorMode::From(start, Direction::Forward)
+			}
+			Bound::Unbounded => IteratorMode::Start,
+		};
+		let iterator = match cf {
+			Some(_) => {
+				let cf = &self.get_column_family(cf.clone())?;
+				tx.iterator_cf_opt(cf, opts, mode)
+			}
+			None => tx.iterator_opt(mode, opts),
+		};
+
+		let mut pairs: Vec<_> = iterator
+			.map(|pair| -> Result<KeyValuePair, Error> {
+				let (k, v) = pair?;
+				Ok((k.to_vec(), v.to_vec()))
+			})
+			.filter(
+				|pair| !matches!((pair, &start), (Ok((k, _)), Bound::Excluded(start)) if k == start),
+			)
+			.collect();
+
+		// The upper bound of the iterator is exclusive, an included end key is read apart
+		if let Bound::Included(end) = end {
+			if !matches!(&start, Bound::Excluded(start) if start == &end) {
+				let value = match cf {
+					Some(_) => {
+						let cf = &self.get_column_family(cf)?;
+						tx.get_cf(cf, &end)?
+					}
+					None => tx.get(&end)?,
+				};
+				if let Some(value) = value {
+					pairs.push(Ok((end, value)));
+				}
+			}
+		}
+		Ok(pairs)
+	}
+
+	async fn scan_range(
+		&self,
+		cf: CF,
+		start: Bound<Key>,
+		end: Bound<Key>,
+		page: Page,
+		reverse: bool,
+	) -> Result<Vec<Result<KeyValuePair, Error>>, Error> {
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		let guarded_tx = self.tx.lock().await;
+		let tx = guarded_tx.as_ref().unwrap();
+
+		// The upper bound of the iterator is exclusive, an included end key is read apart
+		let end_pair = match &end {
+			Bound::Included(end) if !matches!(&start, Bound::Excluded(start) if start == end) => {
+				let value = match cf {
+					Some(_) => tx.get_cf(&self.get_column_family(cf.clone())?, end)?,
+					None => tx.get(end)?,
+				};
+				value.map(|value| Ok((end.clone(), value)))
+			}
+			_ => None,
+		};
+
+		let mut opts = ReadOptions::default();
+		if let Bound::Included(start) | Bound::Excluded(start) = &start {
+			opts.set_iterate_lower_bound(start.clone());
+		}
+		if let Bound::Included(end) | Bound::Excluded(end) = &end {
+			opts.set_iterate_upper_bound(end.clone());
+		}
+		let mode = if reverse {
+			IteratorMode::End
+		} else {
+			IteratorMode::Start
+		};
+		let iterator = match cf {
+			Some(_) => {
+				let cf = &self.get_column_family(cf)?;
+				tx.iterator_cf_opt(cf, opts, mode)
+			}
+			None => tx.iterator_opt(mode, opts),
+		};
+
+		let pairs = iterator
+			.map(|pair| -> Result<KeyValuePair, Error> {
+				let (k, v) = pair?;
+				Ok((k.to_vec(), v.to_vec()))
+			})
+			.filter(
+				|pair| !matches!((pair, &start), (Ok((k, _)), Bound::Excluded(start)) if k == start),
+			);
+		let pairs: Box<dyn Iterator<Item = Result<KeyValuePair, Error>> + '_> = match reverse {
+			true => Box::new(end_pair.into_iter().chain(pairs)),
+			false => Box::new(pairs.chain(end_pair)),
+		};
+
+		Ok(page.apply(pairs).collect())
+	}
+
+	// The iterators of a transaction each read the latest state, a database snapshot pins
+	// one sequence number for every column family
+	async fn snapshot(&self, cfs: Vec<CF>) -> Result<Snapshot, Error> {
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		let db_snapshot = self._db.db_snapshot();
+		let mut snapshot = Snapshot::new();
+		for cf in cfs {
+			let iterator = match cf {
+				Some(_) => db_snapshot
+					.iterator_cf(&self.get_column_family(cf.clone())?, IteratorMode::Start),
+				None => db_snapshot.iterator(IteratorMode::Start),
+			};
+			for pair in iterator {
+				let (k, v) = pair?;
+				snapshot.insert(cf.clone(), k.to_vec(), v.to_vec());
+			}
+		}
+		Ok(snapshot)
+	}
 }
@@ -1,6 +1,10 @@
 extern crate rocksdb;
 
-use rocksdb::OptimisticTransactionDB;
+use std::sync::Arc;
+
+use rocksdb::{
+	BoundColumnFamily, DBAccess, OptimisticTransactionDB, SnapshotWithThreadMode, TransactionDB,
+};
 
 use crate::DBTransaction;
 
@@ -10,3 +14,68 @@ use crate::DBTransaction;
 pub type DBType = OptimisticTransactionDB;
 pub type TxType = rocksdb::Transaction<'static, DBType>;
 pub type RocksDBTransaction = DBTransaction<DBType, TxType>;
+
+/// TransactionDB
+/// Pessimistic transactions, locking the keys they write. Unlike optimistic ones they can
+/// be prepared for a two-phase commit coordinated by an external transaction manager.
+pub type TwoPhaseDBType = TransactionDB;
+pub type TwoPhaseTxType = rocksdb::Transaction<'static, TwoPhaseDBType>;
+pub type TwoPhaseRocksDBTransaction = DBTransaction<TwoPhaseDBType, TwoPhaseTxType>;
+
+/// Database types RocksDB transactions are opened on
+pub trait TransactionDBType: DBAccess + Sized + 'static {
+	/// Whether transactions of the database can be prepared
+	const TWO_PHASE: bool;
+
+	fn column_family(&self, name: &str) -> Option<Arc<BoundColumnFamily<'_>>>;
+
+	/// Estimated number of keys, `None` when the database doesn't expose its properties
+	fn estimate_keys(
+		&self,
+		cf: Option<&Arc<BoundColumnFamily<'_>>>,
+	) -> Result<Option<u64>, rocksdb::Error>;
+
+	fn db_snapshot(&self) -> SnapshotWithThreadMode<'_, Self>;
+}
+
+impl TransactionDBType for DBType {
+	const TWO_PHASE: bool = false;
+
+	fn column_family(&self, name: &str) -> Option<Arc<BoundColumnFamily<'_>>> {
+		self.cf_handle(name)
+	}
+
+	fn estimate_keys(
+		&self,
+		cf: Option<&Arc<BoundColumnFamily<'_>>>,
+	) -> Result<Option<u64>, rocksdb::Error> {
+		match cf {
+			Some(cf) => self.property_int_value_cf(cf, "rocksdb.estimate-num-keys"),
+			None => self.property_int_value("rocksdb.estimate-num-keys"),
+		}
+	}
+
+	fn db_snapshot(&self) -> SnapshotWithThreadMode<'_, Self> {
+		self.snapshot()
+	}
+}
+
+impl TransactionDBType for TwoPhaseDBType {
+	const TWO_PHASE: bool = true;
+
+	fn column_family(&self, name: &str) -> Option<Arc<BoundColumnFamily<'_>>> {
+		self.cf_handle(name)
+	}
+
+	// The bindings don't expose the properties of a TransactionDB
+	fn estimate_keys(
+		&self,
+		_cf: Option<&Arc<BoundColumnFamily<'_>>>,
+	) -> Result<Option<u64>, rocksdb::Error> {
+		Ok(None)
+	}
+
+	fn db_snapshot(&self) -> SnapshotWithThreadMode<'_, Self> {
+		self.snapshot()
+	}
+}
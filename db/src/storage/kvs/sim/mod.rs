@@ -0,0 +1,271 @@
+pub mod tx;
+pub mod ty;
+
+use std::{pin::Pin, sync::Arc};
+
+use async_trait::async_trait;
+pub use tx::*;
+pub use ty::*;
+
+use crate::{
+	constant::CF_NAMES, ColumnFamilyOptions, ColumnFamilyStats, DBTransaction, DatastoreAdapter,
+	DatastoreStats, Error, Overlay, StorageAdapter, StorageAdapterName, StorageVariant,
+};
+
+/// # Simulation adapter
+/// In-memory datastore injecting IO errors, commit conflicts and latency drawn from a
+/// seed, to exercise the error paths of the code running on top of `SimpleTransaction`.
+/// A failing seed reproduces the same faults at the same operations.
+pub struct SimAdapter(StorageAdapter<DBType>);
+
+#[cfg(feature = "test-suite")]
+crate::full_adapter_test_impl!(SimAdapter::default());
+
+impl SimAdapter {
+	impl_new_type_adapter!(DBType);
+
+	pub fn new(faults: SimFaults) -> SimAdapter {
+		// The store lives in memory, the path only names the seed
+		SimAdapter(StorageAdapter {
+			name: StorageAdapterName::Sim,
+			path: format!("sim:{}", faults.seed),
+			db_instance: Arc::pin(SimStore::new(faults)),
+			variant: StorageVariant::KeyValueStore,
+		})
+	}
+
+	/// Adapter on a store shared with other adapters, like the memory adapter's
+	pub(crate) fn with_store(
+		name: StorageAdapterName,
+		path: String,
+		store: Pin<Arc<SimStore>>,
+	) -> SimAdapter {
+		SimAdapter(StorageAdapter {
+			name,
+			path,
+			db_instance: store,
+			variant: StorageVariant::KeyValueStore,
+		})
+	}
+
+	/// Replace the injected faults, e.g. once fixtures are written without faults
+	pub fn set_faults(&self, faults: SimFaults) {
+		self.0.db_instance.set_faults(faults);
+	}
+
+	/// Number of faults injected so far
+	pub fn injected(&self) -> usize {
+		self.0.db_instance.injected()
+	}
+}
+
+#[async_trait]
+impl DatastoreAdapter for SimAdapter {
+	type Transaction = SimTransaction;
+
+	fn default() -> Self {
+		SimAdapter::new(SimFaults::default())
+	}
+
+	fn spawn(&self) -> Self {
+		SimAdapter::default()
+	}
+
+	fn path(&self) -> &str {
+		&self.0.path
+	}
+
+	fn column_family_options(&self) -> Result<Vec<ColumnFamilyOptions>, Error> {
+		let inner = self.get_initialized_inner()?;
+		let mut names: Vec<String> = CF_NAMES.iter().map(|n| n.to_string()).collect();
+		for cf in inner.db_instance.column_families().into_iter().flatten() {
+			names.push(String::from_utf8_lossy(&cf).to_string());
+		}
+		names.sort_unstable();
+		names.dedup();
+		names.insert(0, "default".to_string());
+		Ok(names
+			.into_iter()
+			.map(|name| ColumnFamilyOptions {
+				name,
+				compression: "none".to_string(),
+				comparator: "bytewise".to_string(),
+				merge_operator: None,
+				block_size: None,
+			})
+			.collect())
+	}
+
+	// The store lives in memory, column families not written yet hold no key
+	fn stats(&self) -> Result<DatastoreStats, Error> {
+		let inner = self.get_initialized_inner()?;
+		let sizes = inner.db_instance.sizes();
+		let column_families = self
+			.column_family_options()?
+			.into_iter()
+			.map(|options| {
+				let cf = match options.name.as_str() {
+					"default" => None,
+					name => Some(name.as_bytes().to_vec()),
+				};
+				let (keys, size) = sizes
+					.iter()
+					.find(|(name, ..)| *name == cf)
+					.map(|(_, keys, size)| (*keys, *size))
+					.unwrap_or_default();
+				ColumnFamilyStats {
+					name: options.name,
+					keys: Some(keys),
+					size: Some(size),
+					files: None,
+				}
+			})
+			.collect();
+		Ok(DatastoreStats {
+			disk_size: 0,
+			column_families,
+		})
+	}
+
+	async fn transaction(&self, w: bool) -> Result<Self::Transaction, Error> {
+		let inner = self.get_initialized_inner()?;
+		let db = &inner.db_instance;
+		db.operation().await?;
+
+		DBTransaction::<DBType, TxType>::new(Overlay::new(), db.clone(), w)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::{ops::Bound, time::Duration};
+
+	use crate::{get_expiry, set_expiry, Cursor, DatastoreAdapter, Overlay, SimpleTransaction};
+
+	use super::{SimAdapter, SimFaults};
+
+	fn faults(seed: u64) -> SimFaults {
+		SimFaults {
+			seed,
+			io_error: 0.2,
+			conflict: 0.3,
+			latency: Some(Duration::from_micros(100)),
+		}
+	}
+
+	/// Write `count` keys without faults, then inject the faults of `seed`
+	async fn adapter_with_keys(seed: u64, count: usize) -> SimAdapter {
+		let adapter = SimAdapter::default();
+		let mut tx = adapter.transaction(true).await.unwrap();
+		for i in 0..count {
+			tx.set(None, format!("key {:03}", i), "value").await.unwrap();
+		}
+		tx.commit().await.unwrap();
+		adapter.set_faults(faults(seed));
+		adapter
+	}
+
+	/// Outcome of every step of a fixed workload, failing steps are skipped
+	async fn run_workload(adapter: &SimAdapter) -> Vec<bool> {
+		let mut outcomes = vec![];
+		for i in 0..20 {
+			let written = match adapter.transaction(true).await {
+				Ok(mut tx) => match tx.set(None, format!("key {}", i), "value").await {
+					Ok(()) => tx.commit().await.is_ok(),
+					Err(_) => false,
+				},
+				Err(_) => false,
+			};
+			outcomes.push(written);
+		}
+		outcomes
+	}
+
+	#[tokio::test]
+	async fn should_inject_the_same_faults_for_a_seed() {
+		let first = SimAdapter::new(faults(7));
+		let second = SimAdapter::new(faults(7));
+		let outcomes = run_workload(&first).await;
+		assert_eq!(outcomes, run_workload(&second).await);
+		assert!(outcomes.contains(&true) && outcomes.contains(&false));
+		assert_eq!(first.injected(), second.injected());
+
+		// Only committed writes are visible
+		first.set_faults(SimFaults::default());
+		let mut tx = first.transaction(false).await.unwrap();
+		let committed = outcomes.iter().filter(|written| **written).count();
+		assert_eq!(tx.count(None).await.unwrap(), committed);
+	}
+
+	#[tokio::test]
+	async fn should_resume_cursor_after_io_errors() {
+		let adapter = adapter_with_keys(11, 50).await;
+		let mut cursor = Cursor::new(None, Bound::Unbounded, Bound::Unbounded, false);
+		let mut keys = vec![];
+		while cursor.has_next() {
+			// A failed batch leaves the cursor where it was, the batch is read again
+			if let Ok(tx) = adapter.transaction(false).await {
+				if let Ok(pairs) = cursor.next_batch(&tx, 8).await {
+					keys.extend(pairs.into_iter().map(|(k, _)| k));
+				}
+			}
+		}
+		let expected: Vec<_> = (0..50).map(|i| format!("key {:03}", i).into_bytes()).collect();
+		assert_eq!(keys, expected);
+		assert!(adapter.injected() > 0);
+	}
+
+	#[tokio::test]
+	async fn should_keep_overlay_after_failed_commit() {
+		let adapter = SimAdapter::new(SimFaults {
+			conflict: 1.0,
+			..SimFaults::default()
+		});
+		let mut overlay = Overlay::new();
+		overlay.set(None, "a", "1");
+		overlay.set(None, "b", "2");
+		let mut tx = adapter.transaction(true).await.unwrap();
+		assert!(overlay.commit(&mut tx).await.is_err());
+		// The pending writes survive the conflict and are committed by the next attempt
+		assert_eq!(overlay.len(), 2);
+
+		adapter.set_faults(SimFaults::default());
+		let mut tx = adapter.transaction(true).await.unwrap();
+		overlay.commit(&mut tx).await.unwrap();
+		assert!(overlay.is_empty());
+		let tx = adapter.transaction(false).await.unwrap();
+		assert_eq!(tx.get(None, "a").await.unwrap(), Some(b"1".to_vec()));
+	}
+
+	#[tokio::test]
+	async fn should_surface_io_errors_of_expiry() {
+		let adapter = adapter_with_keys(5, 1).await;
+		adapter.set_faults(SimFaults {
+			io_error: 1.0,
+			..SimFaults::default()
+		});
+		assert!(adapter.transaction(false).await.is_err());
+
+		adapter.set_faults(SimFaults::default());
+		let mut tx = adapter.transaction(true).await.unwrap();
+		set_expiry(&mut tx, None, "key 000", 42).await.unwrap();
+		adapter.set_faults(SimFaults {
+			io_error: 1.0,
+			..SimFaults::default()
+		});
+		// A failed read is an error, not a key without expiration
+		assert!(get_expiry(&tx, None, "key 000").await.is_err());
+	}
+
+	#[tokio::test]
+	async fn should_report_stats() {
+		let adapter = adapter_with_keys(0, 3).await;
+		let stats = adapter.stats().unwrap();
+		let default = &stats.column_families[0];
+		assert_eq!(default.name, "default");
+		assert_eq!(default.keys, Some(3));
+		// "key 000" and "value" for every key
+		assert_eq!(default.size, Some(36));
+		assert!(stats.column_families[1..].iter().all(|cf| cf.keys == Some(0)));
+	}
+}
@@ -0,0 +1,251 @@
+use std::ops::{Bound, RangeBounds};
+
+use async_trait::async_trait;
+
+use crate::{
+	interface::{Key, KeyValuePair, Val},
+	DBTransaction, Error, Page, SimpleTransaction, CF,
+};
+
+use super::ty::{DBType, TxType};
+
+/// Every key of a column family
+fn all() -> (Bound<Key>, Bound<Key>) {
+	(Bound::Unbounded, Bound::Unbounded)
+}
+
+fn to_results(pairs: Vec<KeyValuePair>) -> Vec<Result<KeyValuePair, Error>> {
+	pairs.into_iter().map(Ok).collect()
+}
+
+impl DBTransaction<DBType, TxType> {
+	/// Merge the pending writes in the pairs of the store between two bounds
+	async fn merged(
+		&self,
+		cf: &CF,
+		range: (Bound<Key>, Bound<Key>),
+	) -> Result<Vec<KeyValuePair>, Error> {
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+		self._db.operation().await?;
+
+		let guarded_tx = self.tx.lock().await;
+		let overlay = guarded_tx.as_ref().unwrap();
+
+		let pairs = to_results(self._db.range(cf, range.clone()));
+		overlay.merge(cf, pairs, |k| range.contains(&k.to_vec()))
+	}
+}
+
+#[async_trait(?Send)]
+impl SimpleTransaction for DBTransaction<DBType, TxType> {
+	fn closed(&self) -> bool {
+		self.ok
+	}
+
+	async fn count(&mut self, cf: CF) -> Result<usize, Error> {
+		Ok(self.merged(&cf, all()).await?.len())
+	}
+
+	// The simulated store keeps no statistics, the estimate is an exact count
+	async fn estimate_count(&mut self, cf: CF) -> Result<usize, Error> {
+		self.count(cf).await
+	}
+
+	async fn cancel(&mut self) -> Result<(), Error> {
+		if self.ok {
+			return Err(Error::TxFinished);
+		}
+
+		// Mark this transaction as done
+		self.ok = true;
+
+		let mut tx = self.tx.lock().await;
+		match tx.take() {
+			Some(mut overlay) => overlay.discard(),
+			None => unreachable!(),
+		}
+
+		Ok(())
+	}
+
+	async fn commit(&mut self) -> Result<(), Error> {
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		// Check to see if transaction is writable
+		if !self.writable {
+			return Err(Error::TxReadonly);
+		}
+
+		// Mark this transaction as done
+		self.ok = true;
+
+		let overlay = match self.tx.lock().await.take() {
+			Some(overlay) => overlay,
+			None => unreachable!(),
+		};
+		self._db.operation().await?;
+		self._db.apply(&overlay)
+	}
+
+	async fn exi<K>(&self, cf: CF, key: K) -> Result<bool, Error>
+	where
+		K: Into<Key> + Send,
+	{
+		Ok(self.get(cf, key).await?.is_some())
+	}
+
+	async fn get<K>(&self, cf: CF, key: K) -> Result<Option<Val>, Error>
+	where
+		K: Into<Key> + Send,
+	{
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+		self._db.operation().await?;
+
+		let guarded_tx = self.tx.lock().await;
+		let overlay = guarded_tx.as_ref().unwrap();
+
+		let key: Key = key.into();
+		match overlay.lookup(&cf, &key) {
+			Some(change) => Ok(change),
+			None => Ok(self._db.get(&cf, &key)),
+		}
+	}
+
+	async fn set<K, V>(&mut self, cf: CF, key: K, val: V) -> Result<(), Error>
+	where
+		K: Into<Key> + Send,
+		V: Into<Key> + Send,
+	{
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		// Check to see if transaction is writable
+		if !self.writable {
+			return Err(Error::TxReadonly);
+		}
+		self._db.operation().await?;
+
+		let mut guarded_tx = self.tx.lock().await;
+		let overlay = guarded_tx.as_mut().unwrap();
+		overlay.set(cf, key.into(), val.into());
+
+		Ok(())
+	}
+
+	async fn put<K, V>(&mut self, cf: CF, key: K, val: V) -> Result<(), Error>
+	where
+		K: Into<Key> + Send,
+		V: Into<Key> + Send,
+	{
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		// Check to see if transaction is writable
+		if !self.writable {
+			return Err(Error::TxReadonly);
+		}
+
+		let key: Key = key.into();
+		if self.exi(cf.clone(), key.clone()).await? {
+			return Err(Error::TxConditionNotMet);
+		}
+
+		self.set(cf, key, val).await
+	}
+
+	async fn del<K>(&mut self, cf: CF, key: K) -> Result<(), Error>
+	where
+		K: Into<Key> + Send,
+	{
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		// Check to see if transaction is writable
+		if !self.writable {
+			return Err(Error::TxReadonly);
+		}
+		self._db.operation().await?;
+
+		let mut guarded_tx = self.tx.lock().await;
+		let overlay = guarded_tx.as_mut().unwrap();
+		overlay.del(cf, key.into());
+
+		Ok(())
+	}
+
+	async fn iterate(&self, cf: CF) -> Result<Vec<Result<KeyValuePair, Error>>, Error> {
+		Ok(to_results(self.merged(&cf, all()).await?))
+	}
+
+	async fn reverse_iterate(&self, cf: CF) -> Result<Vec<Result<KeyValuePair, Error>>, Error> {
+		Ok(self.merged(&cf, all()).await?.into_iter().rev().map(Ok).collect())
+	}
+
+	async fn page_iterate(
+		&self,
+		cf: CF,
+		page: Page,
+		reverse: bool,
+	) -> Result<Vec<Result<KeyValuePair, Error>>, Error> {
+		self.scan_range(cf, Bound::Unbounded, Bound::Unbounded, page, reverse).await
+	}
+
+	async fn prefix_iterate<P>(
+		&self,
+		cf: CF,
+		prefix: P,
+	) -> Result<Vec<Result<KeyValuePair, Error>>, Error>
+	where
+		P: Into<Key> + Send,
+	{
+		let prefix: Key = prefix.into();
+		let pairs = self.merged(&cf, all()).await?;
+		Ok(to_results(pairs.into_iter().filter(|(k, _)| k.starts_with(&prefix)).collect()))
+	}
+
+	async fn suffix_iterate<S>(
+		&self,
+		cf: CF,
+		suffix: S,
+	) -> Result<Vec<Result<KeyValuePair, Error>>, Error>
+	where
+		S: Into<Key> + Send,
+	{
+		let suffix: Key = suffix.into();
+		let pairs = self.merged(&cf, all()).await?;
+		Ok(to_results(pairs.into_iter().filter(|(k, _)| k.ends_with(&suffix)).collect()))
+	}
+
+	async fn range_iterate(
+		&self,
+		cf: CF,
+		start: Bound<Key>,
+		end: Bound<Key>,
+	) -> Result<Vec<Result<KeyValuePair, Error>>, Error> {
+		Ok(to_results(self.merged(&cf, (start, end)).await?))
+	}
+
+	async fn scan_range(
+		&self,
+		cf: CF,
+		start: Bound<Key>,
+		end: Bound<Key>,
+		page: Page,
+		reverse: bool,
+	) -> Result<Vec<Result<KeyValuePair, Error>>, Error> {
+		let merged = self.merged(&cf, (start, end)).await?;
+		Ok(match reverse {
+			true => page.apply(merged.into_iter().rev()).map(Ok).collect(),
+			false => page.apply(merged.into_iter()).map(Ok).collect(),
+		})
+	}
+}
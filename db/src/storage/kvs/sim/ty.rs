@@ -0,0 +1,138 @@
+use std::{
+	collections::BTreeMap,
+	ops::{Bound, RangeBounds},
+	sync::{
+		atomic::{AtomicUsize, Ordering},
+		Mutex,
+	},
+	time::Duration,
+};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+	interface::{Key, KeyValuePair, Val},
+	DBTransaction, Error, Overlay, CF,
+};
+
+/// Faults injected by the simulated store, drawn from a generator seeded with `seed`. The
+/// same seed and the same sequence of operations inject the same faults.
+#[derive(Clone, Debug, Default)]
+pub struct SimFaults {
+	pub seed: u64,
+	/// Probability, between 0 and 1, of an operation failing with an IO error
+	pub io_error: f64,
+	/// Probability, between 0 and 1, of a commit failing with a conflict. The writes of a
+	/// conflicting transaction are dropped.
+	pub conflict: f64,
+	/// Upper bound of the latency added before every operation
+	pub latency: Option<Duration>,
+}
+
+/// In-memory keyspace of the simulation, column families are created on first write
+pub struct SimStore {
+	data: Mutex<BTreeMap<CF, BTreeMap<Key, Val>>>,
+	faults: Mutex<(SimFaults, StdRng)>,
+	injected: AtomicUsize,
+}
+
+impl SimStore {
+	pub fn new(faults: SimFaults) -> Self {
+		SimStore {
+			data: Mutex::new(BTreeMap::new()),
+			faults: Mutex::new((faults.clone(), StdRng::seed_from_u64(faults.seed))),
+			injected: AtomicUsize::new(0),
+		}
+	}
+
+	/// Replace the injected faults, the generator is seeded again
+	pub fn set_faults(&self, faults: SimFaults) {
+		*self.faults.lock().unwrap() = (faults.clone(), StdRng::seed_from_u64(faults.seed));
+	}
+
+	/// Number of faults injected so far
+	pub fn injected(&self) -> usize {
+		self.injected.load(Ordering::Relaxed)
+	}
+
+	/// Draw with probability `p`. Every draw consumes the generator, so faults only depend
+	/// on the seed and the order of the operations.
+	fn draw(&self, p: impl Fn(&SimFaults) -> f64) -> bool {
+		let (faults, rng) = &mut *self.faults.lock().unwrap();
+		let failed = rng.gen::<f64>() < p(faults);
+		if failed {
+			self.injected.fetch_add(1, Ordering::Relaxed);
+		}
+		failed
+	}
+
+	/// Wait the simulated latency, then fail with an IO error when one is drawn
+	pub(crate) async fn operation(&self) -> Result<(), Error> {
+		let latency = {
+			let (faults, rng) = &mut *self.faults.lock().unwrap();
+			let ratio = rng.gen::<f64>();
+			faults.latency.map(|max| max.mul_f64(ratio))
+		};
+		if let Some(latency) = latency {
+			tokio::time::sleep(latency).await;
+		}
+		match self.draw(|faults| faults.io_error) {
+			true => Err(Error::Ds("Simulated IO error".to_string())),
+			false => Ok(()),
+		}
+	}
+
+	pub(crate) fn get(&self, cf: &CF, key: &[u8]) -> Option<Val> {
+		self.data.lock().unwrap().get(cf)?.get(key).cloned()
+	}
+
+	/// Pairs of a column family between two bounds, in key order
+	pub(crate) fn range(&self, cf: &CF, range: (Bound<Key>, Bound<Key>)) -> Vec<KeyValuePair> {
+		match self.data.lock().unwrap().get(cf) {
+			Some(pairs) => pairs
+				.iter()
+				.filter(|(k, _)| range.contains(*k))
+				.map(|(k, v)| (k.clone(), v.clone()))
+				.collect(),
+			None => vec![],
+		}
+	}
+
+	pub(crate) fn column_families(&self) -> Vec<CF> {
+		self.data.lock().unwrap().keys().cloned().collect()
+	}
+
+	/// Number of keys and bytes of keys and values of every column family
+	pub(crate) fn sizes(&self) -> Vec<(CF, u64, u64)> {
+		let data = self.data.lock().unwrap();
+		data.iter()
+			.map(|(cf, pairs)| {
+				let size = pairs.iter().map(|(k, v)| (k.len() + v.len()) as u64).sum();
+				(cf.clone(), pairs.len() as u64, size)
+			})
+			.collect()
+	}
+
+	/// Apply the writes of a transaction, unless a commit conflict is drawn
+	pub(crate) fn apply(&self, overlay: &Overlay) -> Result<(), Error> {
+		if self.draw(|faults| faults.conflict) {
+			return Err(Error::Tx("Simulated commit conflict".to_string()));
+		}
+		let mut data = self.data.lock().unwrap();
+		for cf in overlay.column_families() {
+			let pairs = data.entry(cf.clone()).or_default();
+			for (k, v) in overlay.changes(&cf) {
+				match v {
+					Some(v) => pairs.insert(k, v),
+					None => pairs.remove(&k),
+				};
+			}
+		}
+		Ok(())
+	}
+}
+
+pub type DBType = SimStore;
+/// Writes are buffered until the commit, like Sled transactions
+pub type TxType = Overlay;
+pub type SimTransaction = DBTransaction<DBType, TxType>;
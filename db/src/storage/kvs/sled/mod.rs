@@ -0,0 +1,148 @@
+pub mod tx;
+pub mod ty;
+
+use std::{
+	path::Path,
+	sync::atomic::{AtomicU64, Ordering},
+};
+
+use async_trait::async_trait;
+pub use tx::*;
+pub use ty::*;
+
+use crate::{
+	constant::CF_NAMES,
+	util::{disk_size, ensure_new_path, generate_path},
+	Capabilities, ColumnFamilyOptions, ColumnFamilyStats, DBTransaction, DatastoreAdapter,
+	DatastoreStats, Error, Overlay, StorageAdapter, StorageAdapterName, StorageVariant,
+};
+
+/// Name sled gives to the tree of the database itself
+const DEFAULT_TREE: &[u8] = b"__sled__default";
+
+pub struct SledAdapter(StorageAdapter<DBType>);
+
+#[cfg(feature = "test-suite")]
+crate::full_adapter_test_impl!(SledAdapter::default());
+
+impl SledAdapter {
+	impl_new_type_adapter!(DBType);
+
+	pub fn new(path: &str) -> Result<SledAdapter, Error> {
+		let path = &path["sled:".len()..];
+		let db_instance = sled::open(path)?;
+
+		Ok(SledAdapter(StorageAdapter::<DBType>::new(
+			StorageAdapterName::Sled,
+			path.to_string(),
+			db_instance,
+			StorageVariant::KeyValueStore,
+		)?))
+	}
+}
+
+#[async_trait]
+impl DatastoreAdapter for SledAdapter {
+	type Transaction = SledTransaction;
+
+	// Trees are copied one after the other, a snapshot is not taken at one point in time
+	fn capabilities() -> Capabilities {
+		Capabilities {
+			backup: true,
+			..Default::default()
+		}
+	}
+
+	fn default() -> Self {
+		let path = &generate_path("sled", None);
+		SledAdapter::new(path).unwrap()
+	}
+
+	fn spawn(&self) -> Self {
+		SledAdapter::default()
+	}
+
+	fn path(&self) -> &str {
+		&self.0.path
+	}
+
+	/// Backups are sled databases every tree is exported into
+	fn backup(&self, backup: &Path, progress: &AtomicU64) -> Result<(), Error> {
+		ensure_new_path(backup)?;
+		let inner = self.get_initialized_inner()?;
+		let copy = sled::open(backup)?;
+		copy.import(inner.db_instance.export());
+		copy.flush()?;
+		progress.fetch_add(disk_size(backup), Ordering::Relaxed);
+		Ok(())
+	}
+
+	fn restore(path: &Path, backup: &Path, progress: &AtomicU64) -> Result<(), Error> {
+		ensure_new_path(path)?;
+		let db = sled::open(path)?;
+		db.import(sled::open(backup)?.export());
+		db.flush()?;
+		progress.fetch_add(disk_size(path), Ordering::Relaxed);
+		Ok(())
+	}
+
+	fn column_family_options(&self) -> Result<Vec<ColumnFamilyOptions>, Error> {
+		// Trees are created on first use, the known column families are listed even when empty
+		let inner = self.get_initialized_inner()?;
+		let mut names: Vec<String> = CF_NAMES.iter().map(|n| n.to_string()).collect();
+		for name in inner.db_instance.tree_names() {
+			if &*name != DEFAULT_TREE {
+				names.push(String::from_utf8_lossy(&name).to_string());
+			}
+		}
+		names.sort_unstable();
+		names.dedup();
+		names.insert(0, "default".to_string());
+		Ok(names
+			.into_iter()
+			.map(|name| ColumnFamilyOptions {
+				name,
+				compression: "none".to_string(),
+				comparator: "bytewise".to_string(),
+				merge_operator: None,
+				block_size: None,
+			})
+			.collect())
+	}
+
+	// Sled keeps no statistics, trees are counted and their keys and values summed
+	fn stats(&self) -> Result<DatastoreStats, Error> {
+		let inner = self.get_initialized_inner()?;
+		let db = &inner.db_instance;
+		let mut column_families = vec![];
+		for name in db.tree_names() {
+			let tree = db.open_tree(&name)?;
+			let mut size = 0;
+			for pair in tree.iter() {
+				let (key, value) = pair?;
+				size += (key.len() + value.len()) as u64;
+			}
+			let name = match &*name {
+				DEFAULT_TREE => "default".to_string(),
+				name => String::from_utf8_lossy(name).to_string(),
+			};
+			column_families.push(ColumnFamilyStats {
+				name,
+				keys: Some(tree.len() as u64),
+				size: Some(size),
+				files: None,
+			});
+		}
+		Ok(DatastoreStats {
+			disk_size: db.size_on_disk()?,
+			column_families,
+		})
+	}
+
+	async fn transaction(&self, w: bool) -> Result<Self::Transaction, Error> {
+		let inner = self.get_initialized_inner()?;
+		let db = &inner.db_instance;
+
+		DBTransaction::<DBType, TxType>::new(Overlay::new(), db.clone(), w)
+	}
+}
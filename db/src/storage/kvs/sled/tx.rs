@@ -0,0 +1,369 @@
+use std::ops::{Bound, RangeBounds};
+
+use async_trait::async_trait;
+use sled::{
+	transaction::{ConflictableTransactionError, TransactionError, Transactional},
+	Batch, IVec, Tree,
+};
+
+use crate::{
+	interface::{Key, KeyValuePair, Val},
+	DBTransaction, Error, Page, SimpleTransaction, CF,
+};
+
+use super::ty::{DBType, TxType};
+
+/// Tree of a column family, the default tree of the database when `cf` is empty
+fn get_tree(db: &DBType, cf: CF) -> Result<Tree, Error> {
+	match cf {
+		Some(name) => Ok(db.open_tree(name)?),
+		None => Ok((**db).clone()),
+	}
+}
+
+fn to_pair(item: sled::Result<(IVec, IVec)>) -> Result<KeyValuePair, Error> {
+	let (k, v) = item?;
+	Ok((k.to_vec(), v.to_vec()))
+}
+
+#[async_trait(?Send)]
+impl SimpleTransaction for DBTransaction<DBType, TxType> {
+	fn closed(&self) -> bool {
+		self.ok
+	}
+
+	// Sled counts by iterating the tree, pending writes are merged in the iteration
+	async fn count(&mut self, cf: CF) -> Result<usize, Error> {
+		Ok(self.iterate(cf).await?.len())
+	}
+
+	// Sled keeps no statistics, the estimate is an exact count
+	async fn estimate_count(&mut self, cf: CF) -> Result<usize, Error> {
+		self.count(cf).await
+	}
+
+	async fn cancel(&mut self) -> Result<(), Error> {
+		if self.ok {
+			return Err(Error::TxFinished);
+		}
+
+		// Mark this transaction as done
+		self.ok = true;
+
+		let mut tx = self.tx.lock().await;
+		match tx.take() {
+			Some(mut overlay) => overlay.discard(),
+			None => unreachable!(),
+		}
+
+		Ok(())
+	}
+
+	async fn commit(&mut self) -> Result<(), Error> {
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		// Check to see if transaction is writable
+		if !self.writable {
+			return Err(Error::TxReadonly);
+		}
+
+		// Mark this transaction as done
+		self.ok = true;
+
+		let mut tx = self.tx.lock().await;
+		let overlay = match tx.take() {
+			Some(overlay) => overlay,
+			None => unreachable!(),
+		};
+
+		let mut trees = vec![];
+		let mut batches = vec![];
+		for cf in overlay.column_families() {
+			let mut batch = Batch::default();
+			for (k, v) in overlay.changes(&cf) {
+				match v {
+					Some(v) => batch.insert(k, v),
+					None => batch.remove(k),
+				}
+			}
+			trees.push(get_tree(&self._db, cf)?);
+			batches.push(batch);
+		}
+
+		// Batches of every tree are applied in one sled transaction
+		trees
+			.as_slice()
+			.transaction(|trees| {
+				for (tree, batch) in trees.iter().zip(batches.iter()) {
+					tree.apply_batch(batch)?;
+				}
+				Ok::<(), ConflictableTransactionError<sled::Error>>(())
+			})
+			.map_err(|e: TransactionError<sled::Error>| Error::Tx(e.to_string()))?;
+		self._db.flush()?;
+
+		Ok(())
+	}
+
+	async fn exi<K>(&self, cf: CF, key: K) -> Result<bool, Error>
+	where
+		K: Into<Key> + Send,
+	{
+		Ok(self.get(cf, key).await?.is_some())
+	}
+
+	// Fetch a key from the database [column family]
+	async fn get<K>(&self, cf: CF, key: K) -> Result<Option<Val>, Error>
+	where
+		K: Into<Key> + Send,
+	{
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		let guarded_tx = self.tx.lock().await;
+		let overlay = guarded_tx.as_ref().unwrap();
+
+		let key: Key = key.into();
+		match overlay.lookup(&cf, &key) {
+			Some(change) => Ok(change),
+			None => Ok(get_tree(&self._db, cf)?.get(key)?.map(|v| v.to_vec())),
+		}
+	}
+
+	// Insert or update a key in the database
+	async fn set<K, V>(&mut self, cf: CF, key: K, val: V) -> Result<(), Error>
+	where
+		K: Into<Key> + Send,
+		V: Into<Key> + Send,
+	{
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		// Check to see if transaction is writable
+		if !self.writable {
+			return Err(Error::TxReadonly);
+		}
+
+		let mut guarded_tx = self.tx.lock().await;
+		let overlay = guarded_tx.as_mut().unwrap();
+		overlay.set(cf, key.into(), val.into());
+
+		Ok(())
+	}
+
+	// Insert a key if it doesn't exist in the database
+	async fn put<K, V>(&mut self, cf: CF, key: K, val: V) -> Result<(), Error>
+	where
+		K: Into<Key> + Send,
+		V: Into<Key> + Send,
+	{
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		// Check to see if transaction is writable
+		if !self.writable {
+			return Err(Error::TxReadonly);
+		}
+
+		let key: Key = key.into();
+		if self.exi(cf.clone(), key.clone()).await? {
+			return Err(Error::TxConditionNotMet);
+		}
+
+		self.set(cf, key, val).await
+	}
+
+	// Delete a key
+	async fn del<K>(&mut self, cf: CF, key: K) -> Result<(), Error>
+	where
+		K: Into<Key> + Send,
+	{
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		// Check to see if transaction is writable
+		if !self.writable {
+			return Err(Error::TxReadonly);
+		}
+
+		let mut guarded_tx = self.tx.lock().await;
+		let overlay = guarded_tx.as_mut().unwrap();
+		overlay.del(cf, key.into());
+
+		Ok(())
+	}
+
+	async fn iterate(&self, cf: CF) -> Result<Vec<Result<KeyValuePair, Error>>, Error> {
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		let guarded_tx = self.tx.lock().await;
+		let overlay = guarded_tx.as_ref().unwrap();
+
+		let tree = get_tree(&self._db, cf.clone())?;
+		let pairs = tree.iter().map(to_pair).collect();
+		let merged = overlay.merge(&cf, pairs, |_| true)?;
+
+		Ok(merged.into_iter().map(Ok).collect())
+	}
+
+	// Pending writes are merged in key order, the merged pairs are then reversed
+	async fn reverse_iterate(&self, cf: CF) -> Result<Vec<Result<KeyValuePair, Error>>, Error> {
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		let guarded_tx = self.tx.lock().await;
+		let overlay = guarded_tx.as_ref().unwrap();
+
+		let tree = get_tree(&self._db, cf.clone())?;
+		if overlay.changes(&cf).is_empty() {
+			return Ok(tree.iter().rev().map(to_pair).collect());
+		}
+		let pairs = tree.iter().map(to_pair).collect();
+		let merged = overlay.merge(&cf, pairs, |_| true)?;
+
+		Ok(merged.into_iter().rev().map(Ok).collect())
+	}
+
+	async fn page_iterate(
+		&self,
+		cf: CF,
+		page: Page,
+		reverse: bool,
+	) -> Result<Vec<Result<KeyValuePair, Error>>, Error> {
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		let guarded_tx = self.tx.lock().await;
+		let overlay = guarded_tx.as_ref().unwrap();
+
+		let tree = get_tree(&self._db, cf.clone())?;
+		if overlay.changes(&cf).is_empty() {
+			return Ok(match reverse {
+				true => page.apply(tree.iter().rev()).map(to_pair).collect(),
+				false => page.apply(tree.iter()).map(to_pair).collect(),
+			});
+		}
+		// Pending writes shift the page, it is taken from the merged pairs
+		let pairs = tree.iter().map(to_pair).collect();
+		let merged = overlay.merge(&cf, pairs, |_| true)?;
+
+		Ok(match reverse {
+			true => page.apply(merged.into_iter().rev()).map(Ok).collect(),
+			false => page.apply(merged.into_iter()).map(Ok).collect(),
+		})
+	}
+
+	async fn prefix_iterate<P>(
+		&self,
+		cf: CF,
+		prefix: P,
+	) -> Result<Vec<Result<KeyValuePair, Error>>, Error>
+	where
+		P: Into<Key> + Send,
+	{
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		let guarded_tx = self.tx.lock().await;
+		let overlay = guarded_tx.as_ref().unwrap();
+
+		let prefix: Key = prefix.into();
+		let tree = get_tree(&self._db, cf.clone())?;
+		let pairs = tree.scan_prefix(&prefix).map(to_pair).collect();
+		let merged = overlay.merge(&cf, pairs, |k| k.starts_with(&prefix))?;
+
+		Ok(merged.into_iter().map(Ok).collect())
+	}
+
+	async fn suffix_iterate<S>(
+		&self,
+		cf: CF,
+		suffix: S,
+	) -> Result<Vec<Result<KeyValuePair, Error>>, Error>
+	where
+		S: Into<Key> + Send,
+	{
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		let guarded_tx = self.tx.lock().await;
+		let overlay = guarded_tx.as_ref().unwrap();
+
+		let suffix: Key = suffix.into();
+		let tree = get_tree(&self._db, cf.clone())?;
+		let pairs = tree
+			.iter()
+			.map(to_pair)
+			.filter(|pair| pair.as_ref().map_or(true, |(k, _)| k.ends_with(&suffix)))
+			.collect();
+		let merged = overlay.merge(&cf, pairs, |k| k.ends_with(&suffix))?;
+
+		Ok(merged.into_iter().map(Ok).collect())
+	}
+
+	async fn range_iterate(
+		&self,
+		cf: CF,
+		start: Bound<Key>,
+		end: Bound<Key>,
+	) -> Result<Vec<Result<KeyValuePair, Error>>, Error> {
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		let guarded_tx = self.tx.lock().await;
+		let overlay = guarded_tx.as_ref().unwrap();
+
+		let range = (start, end);
+		let tree = get_tree(&self._db, cf.clone())?;
+		let pairs = tree.range(range.clone()).map(to_pair).collect();
+		let merged = overlay.merge(&cf, pairs, |k| range.contains(&k.to_vec()))?;
+
+		Ok(merged.into_iter().map(Ok).collect())
+	}
+
+	async fn scan_range(
+		&self,
+		cf: CF,
+		start: Bound<Key>,
+		end: Bound<Key>,
+		page: Page,
+		reverse: bool,
+	) -> Result<Vec<Result<KeyValuePair, Error>>, Error> {
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		let guarded_tx = self.tx.lock().await;
+		let overlay = guarded_tx.as_ref().unwrap();
+
+		let range = (start, end);
+		let tree = get_tree(&self._db, cf.clone())?;
+		if overlay.changes(&cf).is_empty() {
+			return Ok(match reverse {
+				true => page.apply(tree.range(range).rev()).map(to_pair).collect(),
+				false => page.apply(tree.range(range)).map(to_pair).collect(),
+			});
+		}
+		// Pending writes shift the page, it is taken from the merged pairs
+		let pairs = tree.range(range.clone()).map(to_pair).collect();
+		let merged = overlay.merge(&cf, pairs, |k| range.contains(&k.to_vec()))?;
+
+		Ok(match reverse {
+			true => page.apply(merged.into_iter().rev()).map(Ok).collect(),
+			false => page.apply(merged.into_iter()).map(Ok).collect(),
+		})
+	}
+}
@@ -0,0 +1,6 @@
+use crate::{DBTransaction, Overlay};
+
+pub type DBType = sled::Db;
+/// Sled transactions are closure based, writes are buffered until the commit
+pub type TxType = Overlay;
+pub type SledTransaction = DBTransaction<DBType, TxType>;
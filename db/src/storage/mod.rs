@@ -1,8 +1,32 @@
 /// Storage
+mod batch;
+mod comparator;
+mod decoder;
 mod ds;
+mod hook;
 pub mod kvs;
+mod overlay;
+mod record;
+mod replica;
+mod snapshot;
+mod throttle;
+mod trace;
+mod ttl;
 mod tx;
+mod validate;
 
+pub use batch::*;
+pub use comparator::*;
+pub use decoder::*;
 pub use ds::*;
+pub use hook::*;
 pub use kvs::*;
+pub use overlay::*;
+pub use record::*;
+pub use replica::*;
+pub use snapshot::*;
+pub use throttle::*;
+pub use trace::*;
+pub use ttl::*;
 pub use tx::*;
+pub use validate::*;
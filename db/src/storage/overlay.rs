@@ -0,0 +1,217 @@
+use std::collections::BTreeMap;
+
+use crate::{
+	interface::{Key, KeyValuePair, Val},
+	Error, SimpleTransaction, CF,
+};
+
+/// # Session overlay
+/// In-memory write layer stacked on top of a persistent datastore. Mutations are
+/// buffered in the overlay instead of the underlying store, reads merge both views
+/// and the whole layer can later be committed in one transaction or discarded.
+///
+/// A `None` value marks a key deleted in the overlay.
+#[derive(Default, Debug, Clone)]
+pub struct Overlay {
+	changes: BTreeMap<CF, BTreeMap<Key, Option<Val>>>,
+}
+
+impl Overlay {
+	pub fn new() -> Self {
+		Overlay::default()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.changes.values().all(|c| c.is_empty())
+	}
+
+	/// Number of pending mutations across all column families
+	pub fn len(&self) -> usize {
+		self.changes.values().map(|c| c.len()).sum()
+	}
+
+	/// Insert or update a key in the overlay
+	pub fn set<K: Into<Key>, V: Into<Val>>(&mut self, cf: CF, key: K, val: V) {
+		self.changes.entry(cf).or_default().insert(key.into(), Some(val.into()));
+	}
+
+	/// Mark a key as deleted in the overlay
+	pub fn del<K: Into<Key>>(&mut self, cf: CF, key: K) {
+		self.changes.entry(cf).or_default().insert(key.into(), None);
+	}
+
+	/// Pending mutations of a column family, `None` values are deletions
+	pub fn changes(&self, cf: &CF) -> Vec<(Key, Option<Val>)> {
+		match self.changes.get(cf) {
+			Some(c) => c.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+			None => vec![],
+		}
+	}
+
+	/// Pending mutation of a key, `Some(None)` when the key is deleted in the overlay
+	pub fn lookup(&self, cf: &CF, key: &[u8]) -> Option<Option<Val>> {
+		self.changes.get(cf)?.get(key).cloned()
+	}
+
+	/// Column families with pending mutations
+	pub fn column_families(&self) -> Vec<CF> {
+		self.changes.keys().cloned().collect()
+	}
+
+	/// Drop every pending mutation
+	pub fn discard(&mut self) {
+		self.changes.clear();
+	}
+
+	/// Fetch a key, preferring the overlay over the underlying transaction
+	pub async fn get<T, K>(&self, tx: &T, cf: CF, key: K) -> Result<Option<Val>, Error>
+	where
+		T: SimpleTransaction,
+		K: Into<Key> + Send,
+	{
+		let key: Key = key.into();
+		if let Some(change) = self.lookup(&cf, &key) {
+			return Ok(change);
+		}
+		tx.get(cf, key).await
+	}
+
+	/// Check if a key exists in the merged view
+	pub async fn exi<T, K>(&self, tx: &T, cf: CF, key: K) -> Result<bool, Error>
+	where
+		T: SimpleTransaction,
+		K: Into<Key> + Send,
+	{
+		Ok(self.get(tx, cf, key).await?.is_some())
+	}
+
+	/// Iterate the merged view of a column family
+	pub async fn iterate<T>(&self, tx: &T, cf: CF) -> Result<Vec<KeyValuePair>, Error>
+	where
+		T: SimpleTransaction,
+	{
+		let pairs = tx.iterate(cf.clone()).await?;
+		self.merge(&cf, pairs, |_| true)
+	}
+
+	/// Iterate the merged view of keys starting with `prefix`
+	pub async fn prefix_iterate<T, P>(
+		&self,
+		tx: &T,
+		cf: CF,
+		prefix: P,
+	) -> Result<Vec<KeyValuePair>, Error>
+	where
+		T: SimpleTransaction,
+		P: Into<Key> + Send,
+	{
+		let prefix: Key = prefix.into();
+		let pairs = tx.prefix_iterate(cf.clone(), prefix.clone()).await?;
+		self.merge(&cf, pairs, |k| k.starts_with(&prefix))
+	}
+
+	/// Iterate the merged view of keys ending with `suffix`
+	pub async fn suffix_iterate<T, S>(
+		&self,
+		tx: &T,
+		cf: CF,
+		suffix: S,
+	) -> Result<Vec<KeyValuePair>, Error>
+	where
+		T: SimpleTransaction,
+		S: Into<Key> + Send,
+	{
+		let suffix: Key = suffix.into();
+		let pairs = tx.suffix_iterate(cf.clone(), suffix.clone()).await?;
+		self.merge(&cf, pairs, |k| k.ends_with(&suffix))
+	}
+
+	/// Apply the overlay on top of scanned pairs. Keys matching `filter` are taken from
+	/// the overlay, the result keeps the byte order of the underlying store.
+	pub fn merge<F>(
+		&self,
+		cf: &CF,
+		pairs: Vec<Result<KeyValuePair, Error>>,
+		filter: F,
+	) -> Result<Vec<KeyValuePair>, Error>
+	where
+		F: Fn(&[u8]) -> bool,
+	{
+		let mut merged = BTreeMap::<Key, Val>::new();
+		for pair in pairs {
+			let (k, v) = pair?;
+			merged.insert(k, v);
+		}
+
+		if let Some(changes) = self.changes.get(cf) {
+			for (k, v) in changes.iter().filter(|(k, _)| filter(k)) {
+				match v {
+					Some(v) => merged.insert(k.clone(), v.clone()),
+					None => merged.remove(k),
+				};
+			}
+		}
+
+		Ok(merged.into_iter().collect())
+	}
+
+	/// Write every pending mutation through a writable transaction and commit it.
+	/// The overlay is only cleared once the commit succeeded.
+	pub async fn commit<T>(&mut self, tx: &mut T) -> Result<(), Error>
+	where
+		T: SimpleTransaction,
+	{
+		for (cf, changes) in self.changes.iter() {
+			for (k, v) in changes.iter() {
+				match v {
+					Some(v) => tx.set(cf.clone(), k.clone(), v.clone()).await?,
+					None => {
+						if tx.exi(cf.clone(), k.clone()).await? {
+							tx.del(cf.clone(), k.clone()).await?;
+						}
+					}
+				}
+			}
+		}
+		tx.commit().await?;
+		self.discard();
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use crate::{util::generate_path, Datastore, SimpleTransaction};
+
+	use super::Overlay;
+
+	#[tokio::test]
+	async fn should_merge_and_commit() {
+		let db = Datastore::new(&generate_path("redb", None));
+		let cf = None;
+
+		let mut tx = db.transaction(true).await.unwrap();
+		tx.set(cf.clone(), "a", "1").await.unwrap();
+		tx.set(cf.clone(), "b", "2").await.unwrap();
+		tx.commit().await.unwrap();
+
+		let mut overlay = Overlay::new();
+		overlay.set(cf.clone(), "c", "3");
+		overlay.del(cf.clone(), "a");
+
+		let tx = db.transaction(false).await.unwrap();
+		let pairs = overlay.iterate(&tx, cf.clone()).await.unwrap();
+		let keys: Vec<_> = pairs.iter().map(|(k, _)| k.clone()).collect();
+		assert_eq!(keys, vec![b"b".to_vec(), b"c".to_vec()]);
+		assert!(!overlay.exi(&tx, cf.clone(), "a").await.unwrap());
+		drop(tx);
+
+		let mut tx = db.transaction(true).await.unwrap();
+		overlay.commit(&mut tx).await.unwrap();
+		assert!(overlay.is_empty());
+
+		let tx = db.transaction(false).await.unwrap();
+		assert_eq!(tx.get(cf.clone(), "c").await.unwrap(), Some(b"3".to_vec()));
+		assert_eq!(tx.get(cf, "a").await.unwrap(), None);
+	}
+}
@@ -0,0 +1,148 @@
+use std::marker::PhantomData;
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+use crate::{interface::Key, Datastore, Error, SimpleTransaction, CF};
+
+fn serde_error(err: serde_json::Error) -> Error {
+	Error::Ds(err.to_string())
+}
+
+/// # Record repositories
+/// Typed records of an application embedding the library, stored under `prefix` in a column
+/// family. Values are stored as JSON, so EDMA shows their fields and row templates can
+/// reference them. Keys are appended to the prefix as JSON, except strings which are stored
+/// as their text: a `user:` repository keyed by `"42"` stores `user:42`, which layouts
+/// decode like any other key.
+pub struct RecordRepository<'a, K, V> {
+	ds: &'a Datastore,
+	cf: CF,
+	prefix: Vec<u8>,
+	marker: PhantomData<(K, V)>,
+}
+
+impl<'a, K, V> RecordRepository<'a, K, V>
+where
+	K: Serialize + DeserializeOwned,
+	V: Serialize + DeserializeOwned,
+{
+	pub fn new<P: Into<Key>>(ds: &'a Datastore, cf: CF, prefix: P) -> Self {
+		RecordRepository {
+			ds,
+			cf,
+			prefix: prefix.into(),
+			marker: PhantomData,
+		}
+	}
+
+	/// Stored key of a record
+	fn encode_key(&self, key: &K) -> Result<Key, Error> {
+		let mut encoded = self.prefix.clone();
+		match serde_json::to_value(key).map_err(serde_error)? {
+			Value::String(text) => encoded.extend(text.into_bytes()),
+			json => encoded.extend(json.to_string().into_bytes()),
+		}
+		Ok(encoded)
+	}
+
+	/// Record key of a stored key, `None` for a key outside of the repository
+	fn decode_key(&self, key: &[u8]) -> Option<K> {
+		let encoded = key.strip_prefix(self.prefix.as_slice())?;
+		// Keys stored as text first, so a string key is never read back as JSON
+		let text = String::from_utf8(encoded.to_vec()).ok();
+		text.and_then(|text| serde_json::from_value(Value::String(text)).ok())
+			.or_else(|| serde_json::from_slice(encoded).ok())
+	}
+
+	/// Insert or replace a record
+	pub async fn set(&self, key: &K, value: &V) -> Result<(), Error> {
+		let value = serde_json::to_vec(value).map_err(serde_error)?;
+		let mut tx = self.ds.transaction(true).await?;
+		tx.set(self.cf.clone(), self.encode_key(key)?, value).await?;
+		tx.commit().await
+	}
+
+	pub async fn get(&self, key: &K) -> Result<Option<V>, Error> {
+		let tx = self.ds.transaction(false).await?;
+		match tx.get(self.cf.clone(), self.encode_key(key)?).await? {
+			Some(value) => serde_json::from_slice(&value).map(Some).map_err(serde_error),
+			None => Ok(None),
+		}
+	}
+
+	pub async fn delete(&self, key: &K) -> Result<(), Error> {
+		let mut tx = self.ds.transaction(true).await?;
+		tx.del(self.cf.clone(), self.encode_key(key)?).await?;
+		tx.commit().await
+	}
+
+	/// Every record of the repository in key order. A pair under the prefix which doesn't
+	/// decode as a record is an error.
+	pub async fn list(&self) -> Result<Vec<(K, V)>, Error> {
+		let tx = self.ds.transaction(false).await?;
+		let pairs = tx.prefix_iterate(self.cf.clone(), self.prefix.clone()).await?;
+		pairs
+			.into_iter()
+			.map(|pair| {
+				let (key, value) = pair?;
+				let record = self.decode_key(&key).ok_or_else(|| {
+					Error::Ds(format!("Invalid record key {}", String::from_utf8_lossy(&key)))
+				})?;
+				Ok((record, serde_json::from_slice(&value).map_err(serde_error)?))
+			})
+			.collect()
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use serde::{Deserialize, Serialize};
+
+	use crate::{util::generate_path, Datastore, SimpleTransaction};
+
+	use super::RecordRepository;
+
+	#[derive(Debug, PartialEq, Serialize, Deserialize)]
+	struct User {
+		name: String,
+		age: u32,
+	}
+
+	#[tokio::test]
+	async fn should_store_typed_records() {
+		let db = Datastore::new(&generate_path("redb", None));
+		let users = RecordRepository::<String, User>::new(&db, None, "user:");
+		let alice = User {
+			name: "alice".to_string(),
+			age: 30,
+		};
+		users.set(&"42".to_string(), &alice).await.unwrap();
+		users
+			.set(
+				&"7".to_string(),
+				&User {
+					name: "bob".to_string(),
+					age: 25,
+				},
+			)
+			.await
+			.unwrap();
+		assert_eq!(users.get(&"42".to_string()).await.unwrap(), Some(alice));
+
+		// String keys are stored as text, values as JSON
+		let tx = db.transaction(false).await.unwrap();
+		let stored = tx.get(None, "user:42").await.unwrap().unwrap();
+		assert_eq!(stored, br#"{"name":"alice","age":30}"#.to_vec());
+		drop(tx);
+
+		let ids = RecordRepository::<u64, User>::new(&db, None, "id:");
+		ids.set(&1, &users.get(&"7".to_string()).await.unwrap().unwrap()).await.unwrap();
+		let listed: Vec<_> = users.list().await.unwrap().into_iter().map(|(k, _)| k).collect();
+		assert_eq!(listed, vec!["42".to_string(), "7".to_string()]);
+		assert_eq!(ids.list().await.unwrap()[0].0, 1);
+
+		users.delete(&"42".to_string()).await.unwrap();
+		assert_eq!(users.get(&"42".to_string()).await.unwrap(), None);
+	}
+}
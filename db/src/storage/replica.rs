@@ -0,0 +1,147 @@
+use std::{
+	ops::Bound,
+	sync::atomic::{AtomicUsize, Ordering},
+};
+
+use futures::future::try_join_all;
+
+use crate::{
+	interface::{Key, KeyValuePair},
+	Datastore, Error, Page, SimpleTransaction, Transaction, CF,
+};
+
+/// # Replica sets
+/// Read-only copies of the same database, e.g. RocksDB checkpoints or copies of a ReDB file,
+/// spreading heavy analytical scans across them. Every transaction is opened on the next
+/// replica in turn, and `scan_shards` reads each range shard of a keyspace from its own
+/// replica concurrently. The replicas are expected to hold the same keys: nothing is ever
+/// written through the set, so they don't drift apart.
+pub struct ReplicaSet {
+	replicas: Vec<Datastore>,
+	next: AtomicUsize,
+}
+
+impl ReplicaSet {
+	/// Open the replicas at `paths`, addressed like `Datastore::new`
+	pub fn new(paths: &[&str]) -> Result<ReplicaSet, Error> {
+		for path in paths {
+			Datastore::check(path)?;
+		}
+		ReplicaSet::from_datastores(paths.iter().map(|path| Datastore::new(path)).collect())
+	}
+
+	pub fn from_datastores(replicas: Vec<Datastore>) -> Result<ReplicaSet, Error> {
+		if replicas.is_empty() {
+			return Err(Error::Ds("A replica set needs at least one replica".to_string()));
+		}
+		Ok(ReplicaSet {
+			replicas,
+			next: AtomicUsize::new(0),
+		})
+	}
+
+	pub fn len(&self) -> usize {
+		self.replicas.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.replicas.is_empty()
+	}
+
+	/// Replica serving the next read, round-robin
+	fn next_replica(&self) -> &Datastore {
+		let index = self.next.fetch_add(1, Ordering::Relaxed);
+		&self.replicas[index % self.replicas.len()]
+	}
+
+	/// Read transaction on the next replica
+	pub async fn transaction(&self) -> Result<Transaction, Error> {
+		self.next_replica().transaction(false).await
+	}
+
+	/// Pairs of the range shards of a keyspace, each shard read from the next replica with
+	/// all shards in flight at once. Pairs are returned in the order of the shards.
+	pub async fn scan_shards(
+		&self,
+		cf: CF,
+		shards: Vec<(Bound<Key>, Bound<Key>)>,
+	) -> Result<Vec<KeyValuePair>, Error> {
+		let scans = shards.into_iter().map(|(start, end)| {
+			let cf = cf.clone();
+			async move {
+				let tx = self.transaction().await?;
+				let page = Page {
+					offset: 0,
+					limit: None,
+				};
+				tx.scan_range(cf, start, end, page, false)
+					.await?
+					.into_iter()
+					.collect::<Result<Vec<_>, Error>>()
+			}
+		});
+		Ok(try_join_all(scans).await?.into_iter().flatten().collect())
+	}
+}
+
+/// Contiguous range shards of a whole keyspace split at `boundaries`, given in key order:
+/// every boundary starts a shard and ends the previous one
+pub fn range_shards(boundaries: Vec<Key>) -> Vec<(Bound<Key>, Bound<Key>)> {
+	let mut shards = vec![];
+	let mut start = Bound::Unbounded;
+	for boundary in boundaries {
+		shards.push((start, Bound::Excluded(boundary.clone())));
+		start = Bound::Included(boundary);
+	}
+	shards.push((start, Bound::Unbounded));
+	shards
+}
+
+#[cfg(test)]
+mod test {
+	use crate::{Datastore, SimpleTransaction};
+
+	use super::{range_shards, ReplicaSet};
+
+	async fn replica(path: &str, keys: &[&str]) -> Datastore {
+		let db = Datastore::new(path);
+		let mut tx = db.transaction(true).await.unwrap();
+		for key in keys {
+			tx.set(None, *key, "value").await.unwrap();
+		}
+		tx.commit().await.unwrap();
+		db
+	}
+
+	#[tokio::test]
+	async fn should_read_replicas_in_turn() {
+		let replicas = vec![
+			replica("memory:replica_turn_a", &["a"]).await,
+			replica("memory:replica_turn_b", &["b"]).await,
+		];
+		let set = ReplicaSet::from_datastores(replicas).unwrap();
+		let mut served = vec![];
+		for _ in 0..4 {
+			let tx = set.transaction().await.unwrap();
+			served.push(tx.exi(None, "a").await.unwrap());
+		}
+		assert_eq!(served, vec![true, false, true, false]);
+		assert!(ReplicaSet::from_datastores(vec![]).is_err());
+	}
+
+	#[tokio::test]
+	async fn should_scan_shards_in_order() {
+		let keys = ["a", "b", "c", "d", "e"];
+		let replicas = vec![
+			replica("memory:replica_scan_a", &keys).await,
+			replica("memory:replica_scan_b", &keys).await,
+		];
+		let set = ReplicaSet::from_datastores(replicas).unwrap();
+		let shards = range_shards(vec![b"b".to_vec(), b"d".to_vec()]);
+		assert_eq!(shards.len(), 3);
+		let pairs = set.scan_shards(None, shards).await.unwrap();
+		let scanned: Vec<_> = pairs.into_iter().map(|(key, _)| key).collect();
+		let expected: Vec<_> = keys.iter().map(|key| key.as_bytes().to_vec()).collect();
+		assert_eq!(scanned, expected);
+	}
+}
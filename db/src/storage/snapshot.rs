@@ -0,0 +1,159 @@
+use std::{
+	collections::BTreeMap,
+	ops::{Bound, RangeBounds},
+};
+
+use crate::{
+	interface::{Key, KeyValuePair, Val},
+	util::now,
+	Error, Page, SimpleTransaction, CF,
+};
+
+/// # Point-in-time snapshot
+/// Copy of column families read at one point in time. Scans of a snapshot return the
+/// pairs as they were when it was taken, whatever is written to the database afterwards.
+///
+/// RocksDB snapshots pin a sequence number, Redb and LMDB transactions are isolated, so
+/// every column family is read at the same point. Sled has no snapshot, its trees are
+/// copied one after the other.
+#[derive(Default, Debug, Clone)]
+pub struct Snapshot {
+	timestamp: i64,
+	data: BTreeMap<CF, BTreeMap<Key, Val>>,
+}
+
+impl Snapshot {
+	pub fn new() -> Self {
+		Snapshot {
+			timestamp: now(),
+			data: BTreeMap::new(),
+		}
+	}
+
+	/// Copy the column families `cfs` through the reads of `tx`
+	pub async fn take<T>(tx: &T, cfs: Vec<CF>) -> Result<Snapshot, Error>
+	where
+		T: SimpleTransaction + ?Sized,
+	{
+		let mut snapshot = Snapshot::new();
+		for cf in cfs {
+			for pair in tx.iterate(cf.clone()).await? {
+				let (k, v) = pair?;
+				snapshot.insert(cf.clone(), k, v);
+			}
+		}
+		Ok(snapshot)
+	}
+
+	pub(crate) fn insert(&mut self, cf: CF, key: Key, val: Val) {
+		self.data.entry(cf).or_default().insert(key, val);
+	}
+
+	/// Time the snapshot was taken at
+	pub fn timestamp(&self) -> i64 {
+		self.timestamp
+	}
+
+	/// Number of pairs across all column families
+	pub fn len(&self) -> usize {
+		self.data.values().map(|pairs| pairs.len()).sum()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Bytes of the keys and values across all column families
+	pub fn size(&self) -> usize {
+		let pairs = self.data.values().flat_map(|pairs| pairs.iter());
+		pairs.map(|(k, v)| k.len() + v.len()).sum()
+	}
+
+	/// Column families holding at least one pair
+	pub fn column_families(&self) -> Vec<CF> {
+		self.data.keys().cloned().collect()
+	}
+
+	pub fn get(&self, cf: &CF, key: &[u8]) -> Option<Val> {
+		self.data.get(cf)?.get(key).cloned()
+	}
+
+	/// Pairs of a column family in key order
+	pub fn iterate(&self, cf: &CF) -> Vec<KeyValuePair> {
+		self.filter(cf, |_| true)
+	}
+
+	pub fn prefix_iterate(&self, cf: &CF, prefix: &[u8]) -> Vec<KeyValuePair> {
+		self.filter(cf, |k| k.starts_with(prefix))
+	}
+
+	pub fn suffix_iterate(&self, cf: &CF, suffix: &[u8]) -> Vec<KeyValuePair> {
+		self.filter(cf, |k| k.ends_with(suffix))
+	}
+
+	/// Pairs of `page` among the keys between the `start` and `end` bounds, from the
+	/// largest key when `reverse` is set
+	pub fn scan_range(
+		&self,
+		cf: &CF,
+		start: Bound<Key>,
+		end: Bound<Key>,
+		page: Page,
+		reverse: bool,
+	) -> Vec<KeyValuePair> {
+		let pairs = self.filter(cf, |k| (start.clone(), end.clone()).contains(&k.to_vec()));
+		match reverse {
+			true => page.apply(pairs.into_iter().rev()).collect(),
+			false => page.apply(pairs.into_iter()).collect(),
+		}
+	}
+
+	fn filter<F: Fn(&[u8]) -> bool>(&self, cf: &CF, keep: F) -> Vec<KeyValuePair> {
+		match self.data.get(cf) {
+			Some(pairs) => {
+				pairs.iter().filter(|(k, _)| keep(k)).map(|(k, v)| (k.clone(), v.clone())).collect()
+			}
+			None => vec![],
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::ops::Bound;
+
+	use crate::{util::generate_path, Datastore, Page, SimpleTransaction};
+
+	#[tokio::test]
+	async fn should_read_at_the_snapshot() {
+		let db = Datastore::new(&generate_path("redb", None));
+		let cf = None;
+
+		let mut tx = db.transaction(true).await.unwrap();
+		tx.set(cf.clone(), "a", "1").await.unwrap();
+		tx.set(cf.clone(), "b", "2").await.unwrap();
+		tx.commit().await.unwrap();
+
+		let tx = db.transaction(false).await.unwrap();
+		let snapshot = tx.snapshot(vec![cf.clone()]).await.unwrap();
+		drop(tx);
+
+		let mut tx = db.transaction(true).await.unwrap();
+		tx.set(cf.clone(), "a", "3").await.unwrap();
+		tx.set(cf.clone(), "c", "4").await.unwrap();
+		tx.commit().await.unwrap();
+
+		// Writes after the snapshot are not visible in it
+		assert_eq!(snapshot.len(), 2);
+		assert_eq!(snapshot.get(&cf, b"a"), Some(b"1".to_vec()));
+		assert_eq!(snapshot.get(&cf, b"c"), None);
+		let pairs = snapshot.scan_range(
+			&cf,
+			Bound::Included(b"b".to_vec()),
+			Bound::Unbounded,
+			Page::default(),
+			false,
+		);
+		assert_eq!(pairs, vec![(b"b".to_vec(), b"2".to_vec())]);
+	}
+}
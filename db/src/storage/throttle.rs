@@ -0,0 +1,115 @@
+use std::{
+	sync::{Arc, Mutex},
+	time::{Duration, Instant},
+};
+
+/// Limits on mutating operations of a datastore, `None` leaves a dimension unlimited
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WriteLimit {
+	pub ops_per_sec: Option<u64>,
+	pub bytes_per_sec: Option<u64>,
+}
+
+struct Bucket {
+	rate: f64,
+	tokens: f64,
+}
+
+impl Bucket {
+	fn new(rate: u64) -> Self {
+		Bucket {
+			rate: rate as f64,
+			tokens: rate as f64,
+		}
+	}
+
+	/// Refill the bucket, take `cost` tokens and return the time to wait for the debt
+	fn take(&mut self, elapsed: f64, cost: f64) -> f64 {
+		self.tokens = (self.tokens + elapsed * self.rate).min(self.rate) - cost;
+		if self.tokens < 0.0 {
+			-self.tokens / self.rate
+		} else {
+			0.0
+		}
+	}
+}
+
+struct Buckets {
+	ops: Option<Bucket>,
+	bytes: Option<Bucket>,
+	last: Instant,
+}
+
+/// # Write throttling
+/// Token buckets shared by every transaction of a datastore. Each write takes one
+/// operation token and one byte token per byte of its key and value. When a bucket runs
+/// dry the write waits until the bucket refilled. Buckets hold at most one second of
+/// budget, so idle periods don't allow bursts above the limit.
+#[derive(Clone, Default)]
+pub struct Throttle {
+	buckets: Option<Arc<Mutex<Buckets>>>,
+}
+
+impl Throttle {
+	pub fn new(limit: WriteLimit) -> Self {
+		if limit.ops_per_sec.is_none() && limit.bytes_per_sec.is_none() {
+			return Throttle::default();
+		}
+		Throttle {
+			buckets: Some(Arc::new(Mutex::new(Buckets {
+				ops: limit.ops_per_sec.filter(|r| *r > 0).map(Bucket::new),
+				bytes: limit.bytes_per_sec.filter(|r| *r > 0).map(Bucket::new),
+				last: Instant::now(),
+			}))),
+		}
+	}
+
+	/// Reserve tokens for a write of `bytes` and return the delay before it can proceed
+	fn reserve(&self, bytes: usize) -> Duration {
+		let buckets = match &self.buckets {
+			Some(buckets) => buckets,
+			None => return Duration::ZERO,
+		};
+		let mut buckets = buckets.lock().unwrap();
+		let now = Instant::now();
+		let elapsed = now.duration_since(buckets.last).as_secs_f64();
+		buckets.last = now;
+
+		let mut wait: f64 = 0.0;
+		if let Some(ops) = buckets.ops.as_mut() {
+			wait = wait.max(ops.take(elapsed, 1.0));
+		}
+		if let Some(size) = buckets.bytes.as_mut() {
+			wait = wait.max(size.take(elapsed, bytes as f64));
+		}
+		Duration::from_secs_f64(wait)
+	}
+
+	/// Wait until a write of `bytes` is allowed by the limits
+	pub async fn acquire(&self, bytes: usize) {
+		let wait = self.reserve(bytes);
+		if !wait.is_zero() {
+			tokio::time::sleep(wait).await;
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::{Throttle, WriteLimit};
+
+	#[test]
+	fn should_delay_writes_over_limit() {
+		let throttle = Throttle::new(WriteLimit {
+			ops_per_sec: Some(10),
+			bytes_per_sec: None,
+		});
+		for _ in 0..10 {
+			assert!(throttle.reserve(100).is_zero());
+		}
+		assert!(!throttle.reserve(100).is_zero());
+
+		let unlimited = Throttle::new(WriteLimit::default());
+		assert!(unlimited.reserve(usize::MAX).is_zero());
+	}
+}
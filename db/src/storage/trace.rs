@@ -0,0 +1,337 @@
+use std::{
+	collections::HashMap,
+	io::{BufRead, Write},
+	ops::Bound,
+	sync::Mutex,
+};
+
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+use crate::{interface::Key, Error, SimpleTransaction, Transaction, CF};
+
+use super::Datastore;
+
+/// Storage operation of a recorded transaction. Values are never recorded, writes keep the
+/// size of the written value only.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum TraceOperation {
+	Begin {
+		write: bool,
+	},
+	Get {
+		key: Key,
+	},
+	Exists {
+		key: Key,
+	},
+	Set {
+		key: Key,
+		size: usize,
+	},
+	Put {
+		key: Key,
+		size: usize,
+	},
+	Delete {
+		key: Key,
+	},
+	Iterate {
+		reverse: bool,
+	},
+	PrefixIterate {
+		prefix: Key,
+	},
+	SuffixIterate {
+		suffix: Key,
+	},
+	RangeIterate {
+		start: Bound<Key>,
+		end: Bound<Key>,
+	},
+	Count,
+	Commit,
+	Cancel,
+}
+
+impl TraceOperation {
+	/// Replace the keys of the operation by their hash
+	fn hashed(self) -> Self {
+		let bound = |bound: Bound<Key>| match bound {
+			Bound::Included(key) => Bound::Included(hash_key(&key)),
+			Bound::Excluded(key) => Bound::Excluded(hash_key(&key)),
+			Bound::Unbounded => Bound::Unbounded,
+		};
+		match self {
+			TraceOperation::Get {
+				key,
+			} => TraceOperation::Get {
+				key: hash_key(&key),
+			},
+			TraceOperation::Exists {
+				key,
+			} => TraceOperation::Exists {
+				key: hash_key(&key),
+			},
+			TraceOperation::Set {
+				key,
+				size,
+			} => TraceOperation::Set {
+				key: hash_key(&key),
+				size,
+			},
+			TraceOperation::Put {
+				key,
+				size,
+			} => TraceOperation::Put {
+				key: hash_key(&key),
+				size,
+			},
+			TraceOperation::Delete {
+				key,
+			} => TraceOperation::Delete {
+				key: hash_key(&key),
+			},
+			TraceOperation::PrefixIterate {
+				prefix,
+			} => TraceOperation::PrefixIterate {
+				prefix: hash_key(&prefix),
+			},
+			TraceOperation::SuffixIterate {
+				suffix,
+			} => TraceOperation::SuffixIterate {
+				suffix: hash_key(&suffix),
+			},
+			TraceOperation::RangeIterate {
+				start,
+				end,
+			} => TraceOperation::RangeIterate {
+				start: bound(start),
+				end: bound(end),
+			},
+			operation => operation,
+		}
+	}
+}
+
+/// Operation of transaction `tx` on the database at `db`, in recorded order
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceEntry {
+	pub db: String,
+	pub tx: u64,
+	pub cf: CF,
+	#[serde(flatten)]
+	pub operation: TraceOperation,
+}
+
+struct Recorder {
+	hash_keys: bool,
+	next_tx: u64,
+	entries: Vec<TraceEntry>,
+}
+
+lazy_static! {
+	static ref RECORDER: Mutex<Option<Recorder>> = Mutex::new(None);
+}
+
+/// Keys of a trace recorded with `hash_keys` are replaced by the 8 bytes of their 64-bit
+/// FNV-1a hash, equal keys keep equal hashes but prefixes and order are lost
+pub fn hash_key(key: &[u8]) -> Key {
+	let hash = key.iter().fold(0xcbf29ce484222325u64, |hash, byte| {
+		(hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+	});
+	hash.to_be_bytes().to_vec()
+}
+
+/// # Operation traces
+/// Record the operations of every transaction begun on any datastore of the process until
+/// `stop_recording`, to reproduce a session with `replay`. Keys are hashed with `hash_key`
+/// when they must not leave the machine.
+pub fn start_recording(hash_keys: bool) {
+	*RECORDER.lock().unwrap() = Some(Recorder {
+		hash_keys,
+		next_tx: 0,
+		entries: vec![],
+	});
+}
+
+/// Stop the recording and return the trace
+pub fn stop_recording() -> Vec<TraceEntry> {
+	RECORDER.lock().unwrap().take().map(|r| r.entries).unwrap_or_default()
+}
+
+pub fn is_recording() -> bool {
+	RECORDER.lock().unwrap().is_some()
+}
+
+/// Number of a transaction begun while recording, `None` when no recording runs
+pub(crate) fn begin_trace(db: &str, write: bool) -> Option<u64> {
+	let mut recorder = RECORDER.lock().unwrap();
+	let recorder = recorder.as_mut()?;
+	let tx = recorder.next_tx;
+	recorder.next_tx += 1;
+	recorder.entries.push(TraceEntry {
+		db: db.to_string(),
+		tx,
+		cf: None,
+		operation: TraceOperation::Begin {
+			write,
+		},
+	});
+	Some(tx)
+}
+
+pub(crate) fn record_operation(db: &str, tx: u64, cf: &CF, operation: TraceOperation) {
+	if let Some(recorder) = RECORDER.lock().unwrap().as_mut() {
+		let operation = match recorder.hash_keys {
+			true => operation.hashed(),
+			false => operation,
+		};
+		recorder.entries.push(TraceEntry {
+			db: db.to_string(),
+			tx,
+			cf: cf.clone(),
+			operation,
+		});
+	}
+}
+
+/// Write a trace as one JSON entry per line
+pub fn write_trace<W: Write>(mut writer: W, trace: &[TraceEntry]) -> Result<(), Error> {
+	for entry in trace.iter() {
+		let line = serde_json::to_string(entry).map_err(|e| Error::Ds(e.to_string()))?;
+		writeln!(writer, "{}", line).map_err(|e| Error::Ds(e.to_string()))?;
+	}
+	Ok(())
+}
+
+/// Read a trace written by `write_trace`
+pub fn read_trace<R: BufRead>(reader: R) -> Result<Vec<TraceEntry>, Error> {
+	let mut trace = vec![];
+	for (index, line) in reader.lines().enumerate() {
+		let line = line.map_err(|e| Error::Ds(e.to_string()))?;
+		if line.trim().is_empty() {
+			continue;
+		}
+		let entry = serde_json::from_str(&line)
+			.map_err(|e| Error::Ds(format!("Invalid trace entry on line {}: {}", index + 1, e)))?;
+		trace.push(entry);
+	}
+	Ok(trace)
+}
+
+/// Run the operations of a trace in recorded order against `ds`, whatever database they
+/// were recorded on. Transactions interleave like in the recording, written values are
+/// zeroed bytes of the recorded size and paged scans are replayed whole. Stops at the first
+/// failing operation, returns the number of replayed operations.
+pub async fn replay(ds: &Datastore, trace: &[TraceEntry]) -> Result<usize, Error> {
+	let mut transactions: HashMap<u64, Transaction> = HashMap::new();
+	for entry in trace.iter() {
+		let cf = entry.cf.clone();
+		if let TraceOperation::Begin {
+			write,
+		} = entry.operation
+		{
+			transactions.insert(entry.tx, ds.transaction(write).await?);
+			continue;
+		}
+		let tx = transactions
+			.get_mut(&entry.tx)
+			.ok_or_else(|| Error::Tx(format!("Transaction {} was not begun", entry.tx)))?;
+		match &entry.operation {
+			TraceOperation::Get {
+				key,
+			} => tx.get(cf, key.clone()).await.map(|_| ())?,
+			TraceOperation::Exists {
+				key,
+			} => tx.exi(cf, key.clone()).await.map(|_| ())?,
+			TraceOperation::Set {
+				key,
+				size,
+			} => tx.set(cf, key.clone(), vec![0; *size]).await?,
+			TraceOperation::Put {
+				key,
+				size,
+			} => tx.put(cf, key.clone(), vec![0; *size]).await?,
+			TraceOperation::Delete {
+				key,
+			} => tx.del(cf, key.clone()).await?,
+			TraceOperation::Iterate {
+				reverse: false,
+			} => tx.iterate(cf).await.map(|_| ())?,
+			TraceOperation::Iterate {
+				reverse: true,
+			} => tx.reverse_iterate(cf).await.map(|_| ())?,
+			TraceOperation::PrefixIterate {
+				prefix,
+			} => tx.prefix_iterate(cf, prefix.clone()).await.map(|_| ())?,
+			TraceOperation::SuffixIterate {
+				suffix,
+			} => tx.suffix_iterate(cf, suffix.clone()).await.map(|_| ())?,
+			TraceOperation::RangeIterate {
+				start,
+				end,
+			} => tx.range_iterate(cf, start.clone(), end.clone()).await.map(|_| ())?,
+			TraceOperation::Count => tx.count(cf).await.map(|_| ())?,
+			TraceOperation::Commit => tx.commit().await?,
+			TraceOperation::Cancel => tx.cancel().await?,
+			TraceOperation::Begin {
+				..
+			} => unreachable!(),
+		}
+	}
+	Ok(trace.len())
+}
+
+#[cfg(test)]
+mod test {
+	use crate::{Datastore, SimpleTransaction};
+
+	use super::{
+		hash_key, read_trace, replay, start_recording, stop_recording, write_trace, TraceOperation,
+	};
+
+	#[tokio::test]
+	async fn should_record_and_replay() {
+		let path = "redb:../temp/trace.redb";
+		let db = Datastore::new(path);
+		start_recording(false);
+		let mut tx = db.transaction(true).await.unwrap();
+		tx.set(None, "trace key", "secret value").await.unwrap();
+		tx.get(None, "trace key").await.unwrap();
+		tx.commit().await.unwrap();
+		let trace: Vec<_> =
+			stop_recording().into_iter().filter(|entry| entry.db == db.path()).collect();
+
+		let operations: Vec<_> = trace.iter().map(|entry| entry.operation.clone()).collect();
+		assert_eq!(
+			operations,
+			vec![
+				TraceOperation::Begin {
+					write: true
+				},
+				TraceOperation::Set {
+					key: b"trace key".to_vec(),
+					size: 12
+				},
+				TraceOperation::Get {
+					key: b"trace key".to_vec()
+				},
+				TraceOperation::Commit,
+			]
+		);
+
+		let mut lines = vec![];
+		write_trace(&mut lines, &trace).unwrap();
+		assert!(!String::from_utf8_lossy(&lines).contains("secret"));
+		let read = read_trace(lines.as_slice()).unwrap();
+		assert_eq!(read, trace);
+
+		let target = Datastore::new("redb:../temp/replay.redb");
+		assert_eq!(replay(&target, &read).await.unwrap(), 4);
+		let tx = target.transaction(false).await.unwrap();
+		assert_eq!(tx.get(None, "trace key").await.unwrap(), Some(vec![0; 12]));
+		assert_eq!(hash_key(b"trace key").len(), 8);
+	}
+}
@@ -0,0 +1,130 @@
+use crate::{
+	constant::{ColumnFamily, COLUMN_FAMILIES},
+	interface::Key,
+	Datastore, Error, SimpleTransaction, CF,
+};
+
+/// Sidecar column family holding key expiration metadata
+fn ttl_cf() -> CF {
+	let name = COLUMN_FAMILIES.get(&ColumnFamily::Ttl).unwrap();
+	Some(name.as_bytes().to_vec())
+}
+
+/// Sidecar key: length prefixed column family name followed by the key
+fn encode_ttl_key(cf: &CF, key: &[u8]) -> Key {
+	let cf = cf.clone().unwrap_or_default();
+	[&(cf.len() as u32).to_be_bytes()[..], &cf, key].concat()
+}
+
+fn decode_ttl_key(raw: &[u8]) -> Option<(CF, Key)> {
+	let len = u32::from_be_bytes(raw.get(..4)?.try_into().ok()?) as usize;
+	let cf = raw.get(4..4 + len)?.to_vec();
+	let key = raw.get(4 + len..)?.to_vec();
+	Some((Some(cf).filter(|c| !c.is_empty()), key))
+}
+
+fn decode_expiry(raw: &[u8]) -> Option<i64> {
+	Some(i64::from_be_bytes(raw.try_into().ok()?))
+}
+
+/// # Key expiration
+/// Set the unix timestamp, in seconds, a key expires at. TTL metadata is written by EDMA
+/// in a sidecar keyspace, giving TTL semantics to backends without native expiration.
+/// Expired keys are only removed by `purge_expired`, reads are not filtered.
+pub async fn set_expiry<T, K>(tx: &mut T, cf: CF, key: K, expires_at: i64) -> Result<(), Error>
+where
+	T: SimpleTransaction,
+	K: Into<Key> + Send,
+{
+	let ttl_key = encode_ttl_key(&cf, &key.into());
+	tx.set(ttl_cf(), ttl_key, expires_at.to_be_bytes().to_vec()).await
+}
+
+/// Fetch the unix timestamp a key expires at, `None` if the key never expires
+pub async fn get_expiry<T, K>(tx: &T, cf: CF, key: K) -> Result<Option<i64>, Error>
+where
+	T: SimpleTransaction,
+	K: Into<Key> + Send,
+{
+	let ttl_key = encode_ttl_key(&cf, &key.into());
+	match tx.get(ttl_cf(), ttl_key).await {
+		Ok(value) => Ok(value.and_then(|v| decode_expiry(&v))),
+		Err(Error::DsNoColumnFamilyFound) => Ok(None),
+		Err(err) => Err(err),
+	}
+}
+
+/// Remove the expiration of a key
+pub async fn clear_expiry<T, K>(tx: &mut T, cf: CF, key: K) -> Result<(), Error>
+where
+	T: SimpleTransaction,
+	K: Into<Key> + Send,
+{
+	let ttl_key = encode_ttl_key(&cf, &key.into());
+	match tx.exi(ttl_cf(), ttl_key.clone()).await {
+		Ok(true) => tx.del(ttl_cf(), ttl_key).await,
+		Ok(false) | Err(Error::DsNoColumnFamilyFound) => Ok(()),
+		Err(err) => Err(err),
+	}
+}
+
+/// Delete every key expired at `now` together with its metadata, committing one
+/// transaction per `batch_size` keys. Returns the number of expired keys.
+pub async fn purge_expired(ds: &Datastore, now: i64, batch_size: usize) -> Result<usize, Error> {
+	let tx = ds.transaction(false).await?;
+	let entries = match tx.iterate(ttl_cf()).await {
+		Ok(entries) => entries,
+		Err(Error::DsNoColumnFamilyFound) => return Ok(0),
+		Err(err) => return Err(err),
+	};
+	let mut expired = vec![];
+	for entry in entries {
+		let (ttl_key, value) = entry?;
+		if decode_expiry(&value).map_or(false, |expires_at| expires_at <= now) {
+			expired.push(ttl_key);
+		}
+	}
+	drop(tx);
+
+	for batch in expired.chunks(batch_size.max(1)) {
+		let mut tx = ds.transaction(true).await?;
+		for ttl_key in batch {
+			if let Some((cf, key)) = decode_ttl_key(ttl_key) {
+				if tx.exi(cf.clone(), key.clone()).await? {
+					tx.del(cf, key).await?;
+				}
+			}
+			tx.del(ttl_cf(), ttl_key.clone()).await?;
+		}
+		tx.commit().await?;
+	}
+	Ok(expired.len())
+}
+
+#[cfg(test)]
+mod test {
+	use crate::{util::generate_path, Datastore, SimpleTransaction};
+
+	use super::{get_expiry, purge_expired, set_expiry};
+
+	#[tokio::test]
+	async fn should_purge_expired_keys() {
+		let db = Datastore::new(&generate_path("redb", None));
+		let cf = Some("test_suite:v1".into());
+
+		let mut tx = db.transaction(true).await.unwrap();
+		tx.set(cf.clone(), "expired", "1").await.unwrap();
+		tx.set(cf.clone(), "alive", "2").await.unwrap();
+		set_expiry(&mut tx, cf.clone(), "expired", 100).await.unwrap();
+		set_expiry(&mut tx, cf.clone(), "alive", 300).await.unwrap();
+		tx.commit().await.unwrap();
+
+		assert_eq!(purge_expired(&db, 200, 1).await.unwrap(), 1);
+
+		let tx = db.transaction(false).await.unwrap();
+		assert!(!tx.exi(cf.clone(), "expired").await.unwrap());
+		assert!(tx.exi(cf.clone(), "alive").await.unwrap());
+		assert_eq!(get_expiry(&tx, cf.clone(), "expired").await.unwrap(), None);
+		assert_eq!(get_expiry(&tx, cf, "alive").await.unwrap(), Some(300));
+	}
+}
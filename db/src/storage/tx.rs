@@ -1,29 +1,102 @@
+use std::ops::Bound;
+
 use async_trait::async_trait;
 
 use crate::{
 	interface::{Key, Val},
-	Error, SimpleTransaction, CF,
+	Error, Page, SimpleTransaction, Snapshot, CF,
+};
+
+use super::{
+	record_operation, Hooks, PendingWrite, Throttle, TraceOperation, Validators, WriteEvent,
+	WriteOperation,
 };
 
 #[cfg(feature = "kv-redb")]
 use super::ReDBTransaction;
 
 #[cfg(feature = "kv-rocksdb")]
-use super::RocksDBTransaction;
+use super::{RocksDBTransaction, TwoPhaseRocksDBTransaction};
+
+#[cfg(feature = "kv-sled")]
+use super::SledTransaction;
+
+#[cfg(feature = "kv-lmdb")]
+use super::LmdbTransaction;
+
+#[cfg(feature = "kv-memory")]
+use super::MemoryTransaction;
 
 #[allow(clippy::large_enum_variant)]
 pub(super) enum Inner {
 	#[cfg(feature = "kv-rocksdb")]
 	RocksDB(RocksDBTransaction),
+	#[cfg(feature = "kv-rocksdb")]
+	TwoPhaseRocksDB(TwoPhaseRocksDBTransaction),
 	#[cfg(feature = "kv-redb")]
 	ReDB(ReDBTransaction),
+	#[cfg(feature = "kv-sled")]
+	Sled(SledTransaction),
+	#[cfg(feature = "kv-lmdb")]
+	Lmdb(LmdbTransaction),
+	#[cfg(feature = "kv-memory")]
+	Memory(MemoryTransaction),
 }
 
 pub struct Transaction {
 	pub(super) inner: Inner,
+	pub(super) hooks: Hooks,
+	pub(super) validators: Validators,
+	pub(super) throttle: Throttle,
+	pub(super) writes: Vec<WriteEvent>,
+	/// Database and number of the transaction in the running recording
+	pub(super) trace: Option<(String, u64)>,
+}
+
+impl Transaction {
+	/// Add an operation to the running recording, if any
+	fn trace<F: FnOnce() -> TraceOperation>(&self, cf: &CF, operation: F) {
+		if let Some((db, tx)) = &self.trace {
+			record_operation(db, *tx, cf, operation());
+		}
+	}
+
+	/// Run the validators of the datastore on a write before it is applied
+	fn validate(
+		&self,
+		operation: WriteOperation,
+		cf: &CF,
+		key: &[u8],
+		value: Option<&[u8]>,
+	) -> Result<(), Error> {
+		if self.validators.is_empty() {
+			return Ok(());
+		}
+		self.validators.validate(&PendingWrite {
+			operation,
+			cf,
+			key,
+			value,
+		})
+	}
+
+	/// Keep track of a successful write for the hooks fired on commit
+	fn record(&mut self, result: &Result<(), Error>, operation: WriteOperation, cf: CF, key: Key) {
+		if result.is_ok() && !self.hooks.is_empty() {
+			self.writes.push(WriteEvent {
+				operation,
+				cf,
+				key,
+			});
+		}
+	}
 }
 
 impl_global_transaction!(
 	RocksDB; feat "kv-rocksdb",
-	ReDB; feat "kv-redb"
+	TwoPhaseRocksDB; feat "kv-rocksdb",
+	ReDB; feat "kv-redb",
+	Sled; feat "kv-sled",
+	Lmdb; feat "kv-lmdb",
+	Memory; feat "kv-memory"
 );
@@ -0,0 +1,98 @@
+use std::{fmt, sync::Arc};
+
+use crate::{interface::Key, Error, CF};
+
+use super::WriteOperation;
+
+/// Write checked by the validators before the transaction applies it
+#[derive(Clone, Copy, Debug)]
+pub struct PendingWrite<'a> {
+	pub operation: WriteOperation,
+	pub cf: &'a CF,
+	pub key: &'a [u8],
+	/// Written value, none for a deletion
+	pub value: Option<&'a [u8]>,
+}
+
+pub type ValidateFn = Arc<dyn Fn(&PendingWrite) -> Result<(), String> + Send + Sync>;
+
+#[derive(Clone)]
+struct Validator {
+	cf: CF,
+	prefix: Key,
+	check: ValidateFn,
+}
+
+/// # Validators
+/// Checks registered on a datastore, run by a transaction before each write of their column
+/// family whose key starts with their prefix. A rejected write is not applied and fails
+/// with the reason given by the check, the transaction can still be committed or dropped.
+#[derive(Clone, Default)]
+pub struct Validators {
+	validators: Vec<Validator>,
+}
+
+impl Validators {
+	pub fn is_empty(&self) -> bool {
+		self.validators.is_empty()
+	}
+
+	/// Register a check of the writes of `cf` whose key starts with `prefix`
+	pub fn register<P, F>(&mut self, cf: CF, prefix: P, check: F)
+	where
+		P: Into<Key>,
+		F: Fn(&PendingWrite) -> Result<(), String> + Send + Sync + 'static,
+	{
+		self.validators.push(Validator {
+			cf,
+			prefix: prefix.into(),
+			check: Arc::new(check),
+		});
+	}
+
+	/// Run every matching check, failing on the first rejection
+	pub fn validate(&self, write: &PendingWrite) -> Result<(), Error> {
+		for validator in self.validators.iter() {
+			if &validator.cf == write.cf && write.key.starts_with(&validator.prefix) {
+				(validator.check)(write).map_err(|reason| {
+					let key = String::from_utf8_lossy(write.key);
+					Error::TxRejected(format!("{} of {}: {}", write.operation, key, reason))
+				})?;
+			}
+		}
+		Ok(())
+	}
+}
+
+impl fmt::Debug for Validators {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Validators").field("count", &self.validators.len()).finish()
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use crate::{util::generate_path, Datastore, Error, SimpleTransaction};
+
+	#[tokio::test]
+	async fn should_reject_invalid_writes() {
+		let mut db = Datastore::new(&generate_path("redb", None));
+		let cf = None;
+		db.register_validator(cf.clone(), "user:", |write| match write.value {
+			Some(value) if value.is_empty() => Err("empty value".to_string()),
+			_ => Ok(()),
+		});
+
+		let mut tx = db.transaction(true).await.unwrap();
+		tx.set(cf.clone(), "user:1", "a").await.unwrap();
+		tx.set(cf.clone(), "order:1", "").await.unwrap();
+		let rejected = tx.set(cf.clone(), "user:2", "").await;
+		assert!(matches!(rejected, Err(Error::TxRejected(_))));
+		tx.del(cf.clone(), "user:1").await.unwrap();
+		tx.commit().await.unwrap();
+
+		let tx = db.transaction(false).await.unwrap();
+		assert_eq!(tx.get(cf.clone(), "user:2").await.unwrap(), None);
+		assert_eq!(tx.get(cf.clone(), "order:1").await.unwrap(), Some(vec![]));
+	}
+}
@@ -1,8 +1,8 @@
-use std::str::from_utf8;
+use std::{ops::Bound, str::from_utf8};
 
 use crate::{
 	constant::{ColumnFamily, COLUMN_FAMILIES},
-	DatastoreAdapter, SimpleTransaction,
+	Cursor, DatastoreAdapter, Page, SimpleTransaction,
 };
 
 pub async fn should_set_key(adapter: impl DatastoreAdapter) {
@@ -65,3 +65,197 @@ pub async fn should_put_key(adapter: impl DatastoreAdapter) {
 
 	assert!(tx.put(cf.clone(), key, val).await.is_err());
 }
+
+pub async fn should_estimate_count(adapter: impl DatastoreAdapter) {
+	let adapter = adapter.spawn();
+	let cf = Some("test_suite:v1".into());
+	let mut tx = adapter.transaction(true).await.unwrap();
+
+	for key in ["mock key 1", "mock key 2", "mock key 3"] {
+		tx.set(cf.clone(), key, "mock value").await.unwrap();
+	}
+	tx.commit().await.unwrap();
+
+	let mut tx = adapter.transaction(false).await.unwrap();
+	assert_eq!(tx.count(cf.clone()).await.unwrap(), 3);
+	assert!(tx.estimate_count(cf.clone()).await.unwrap() > 0);
+}
+
+pub async fn should_prefix_iterate(adapter: impl DatastoreAdapter) {
+	let adapter = adapter.spawn();
+	let cf = Some("test_suite:v1".into());
+	let mut tx = adapter.transaction(true).await.unwrap();
+
+	for key in ["a:1", "b:1", "b:2", "c:1"] {
+		tx.set(cf.clone(), key, "mock value").await.unwrap();
+	}
+	tx.commit().await.unwrap();
+
+	let tx = adapter.transaction(false).await.unwrap();
+	assert_eq!(tx.prefix_iterate(cf.clone(), "b:").await.unwrap().len(), 2);
+	assert_eq!(tx.suffix_iterate(cf, ":1").await.unwrap().len(), 3);
+}
+
+pub async fn should_range_iterate(adapter: impl DatastoreAdapter) {
+	let adapter = adapter.spawn();
+	let cf = Some("test_suite:v1".into());
+	let mut tx = adapter.transaction(true).await.unwrap();
+
+	for key in ["a", "b", "c", "d"] {
+		tx.set(cf.clone(), key, "mock value").await.unwrap();
+	}
+	tx.commit().await.unwrap();
+
+	let tx = adapter.transaction(false).await.unwrap();
+	let keys = |pairs: Vec<Result<(Vec<u8>, Vec<u8>), crate::Error>>| -> Vec<Vec<u8>> {
+		pairs.into_iter().map(|pair| pair.unwrap().0).collect()
+	};
+	let (b, d) = (b"b".to_vec(), b"d".to_vec());
+	let range =
+		tx.range_iterate(cf.clone(), Bound::Included(b.clone()), Bound::Excluded(d.clone()));
+	assert_eq!(keys(range.await.unwrap()), vec![b"b".to_vec(), b"c".to_vec()]);
+	let range = tx.range_iterate(cf.clone(), Bound::Excluded(b), Bound::Included(d));
+	assert_eq!(keys(range.await.unwrap()), vec![b"c".to_vec(), b"d".to_vec()]);
+	let range = tx.range_iterate(cf, Bound::Unbounded, Bound::Excluded(b"b".to_vec()));
+	assert_eq!(keys(range.await.unwrap()), vec![b"a".to_vec()]);
+}
+
+pub async fn should_delete_range(adapter: impl DatastoreAdapter) {
+	let adapter = adapter.spawn();
+	let cf = Some("test_suite:v1".into());
+	let mut tx = adapter.transaction(true).await.unwrap();
+
+	for key in ["a", "b", "c", "d"] {
+		tx.set(cf.clone(), key, "mock value").await.unwrap();
+	}
+	tx.commit().await.unwrap();
+
+	let mut tx = adapter.transaction(true).await.unwrap();
+	let range = (Bound::Included(b"b".to_vec()), Bound::Excluded(b"d".to_vec()));
+	assert_eq!(tx.delete_range(cf.clone(), range.0, range.1).await.unwrap(), 2);
+	tx.commit().await.unwrap();
+
+	let tx = adapter.transaction(false).await.unwrap();
+	let pairs = tx.iterate(cf).await.unwrap();
+	let keys: Vec<Vec<u8>> = pairs.into_iter().map(|pair| pair.unwrap().0).collect();
+	assert_eq!(keys, vec![b"a".to_vec(), b"d".to_vec()]);
+}
+
+pub async fn should_reverse_iterate(adapter: impl DatastoreAdapter) {
+	let adapter = adapter.spawn();
+	let cf = Some("test_suite:v1".into());
+	let mut tx = adapter.transaction(true).await.unwrap();
+
+	for key in ["b", "a", "c"] {
+		tx.set(cf.clone(), key, "mock value").await.unwrap();
+	}
+	tx.commit().await.unwrap();
+
+	let tx = adapter.transaction(false).await.unwrap();
+	let pairs = tx.reverse_iterate(cf).await.unwrap();
+	let keys: Vec<Vec<u8>> = pairs.into_iter().map(|pair| pair.unwrap().0).collect();
+	assert_eq!(keys, vec![b"c".to_vec(), b"b".to_vec(), b"a".to_vec()]);
+}
+
+pub async fn should_page_iterate(adapter: impl DatastoreAdapter) {
+	let adapter = adapter.spawn();
+	let cf = Some("test_suite:v1".into());
+	let mut tx = adapter.transaction(true).await.unwrap();
+
+	for key in ["a", "b", "c", "d"] {
+		tx.set(cf.clone(), key, "mock value").await.unwrap();
+	}
+	tx.commit().await.unwrap();
+
+	let tx = adapter.transaction(false).await.unwrap();
+	let keys = |pairs: Vec<Result<(Vec<u8>, Vec<u8>), crate::Error>>| -> Vec<Vec<u8>> {
+		pairs.into_iter().map(|pair| pair.unwrap().0).collect()
+	};
+	let page = Page {
+		offset: 1,
+		limit: Some(2),
+	};
+	let pairs = tx.page_iterate(cf.clone(), page, false).await.unwrap();
+	assert_eq!(keys(pairs), vec![b"b".to_vec(), b"c".to_vec()]);
+	let pairs = tx.page_iterate(cf.clone(), page, true).await.unwrap();
+	assert_eq!(keys(pairs), vec![b"c".to_vec(), b"b".to_vec()]);
+	let pairs = tx.page_iterate(cf, Page::default(), false).await.unwrap();
+	assert_eq!(keys(pairs).len(), 4);
+}
+
+pub async fn should_scan_range(adapter: impl DatastoreAdapter) {
+	let adapter = adapter.spawn();
+	let cf = Some("test_suite:v1".into());
+	let mut tx = adapter.transaction(true).await.unwrap();
+
+	for key in ["a", "b", "c", "d", "e"] {
+		tx.set(cf.clone(), key, "mock value").await.unwrap();
+	}
+	tx.commit().await.unwrap();
+
+	let tx = adapter.transaction(false).await.unwrap();
+	let keys = |pairs: Vec<Result<(Vec<u8>, Vec<u8>), crate::Error>>| -> Vec<Vec<u8>> {
+		pairs.into_iter().map(|pair| pair.unwrap().0).collect()
+	};
+	let (start, end) = (Bound::Excluded(b"a".to_vec()), Bound::Included(b"d".to_vec()));
+	let page = Page {
+		offset: 0,
+		limit: Some(2),
+	};
+	let pairs = tx.scan_range(cf.clone(), start.clone(), end.clone(), page, false).await.unwrap();
+	assert_eq!(keys(pairs), vec![b"b".to_vec(), b"c".to_vec()]);
+	let pairs = tx.scan_range(cf.clone(), start.clone(), end.clone(), page, true).await.unwrap();
+	assert_eq!(keys(pairs), vec![b"d".to_vec(), b"c".to_vec()]);
+	let pairs = tx.scan_range(cf.clone(), start, end, Page::default(), true).await.unwrap();
+	assert_eq!(keys(pairs), vec![b"d".to_vec(), b"c".to_vec(), b"b".to_vec()]);
+	let start = Bound::Included(b"c".to_vec());
+	let pairs = tx.scan_range(cf, start, Bound::Unbounded, Page::default(), false).await.unwrap();
+	assert_eq!(keys(pairs), vec![b"c".to_vec(), b"d".to_vec(), b"e".to_vec()]);
+}
+
+pub async fn should_iterate_with_cursor(adapter: impl DatastoreAdapter) {
+	let adapter = adapter.spawn();
+	let cf = Some("test_suite:v1".into());
+	let mut tx = adapter.transaction(true).await.unwrap();
+
+	for key in ["a", "b", "c", "d", "e"] {
+		tx.set(cf.clone(), key, "mock value").await.unwrap();
+	}
+	tx.commit().await.unwrap();
+
+	let tx = adapter.transaction(false).await.unwrap();
+	let keys = |pairs: Vec<(Vec<u8>, Vec<u8>)>| -> Vec<Vec<u8>> {
+		pairs.into_iter().map(|(key, _)| key).collect()
+	};
+	let mut cursor = Cursor::new(cf.clone(), Bound::Unbounded, Bound::Unbounded, false);
+	let batch = cursor.next_batch(&tx, 2).await.unwrap();
+	assert_eq!(keys(batch), vec![b"a".to_vec(), b"b".to_vec()]);
+	let batch = cursor.next_batch(&tx, 2).await.unwrap();
+	assert_eq!(keys(batch), vec![b"c".to_vec(), b"d".to_vec()]);
+	assert!(cursor.has_next());
+	let batch = cursor.next_batch(&tx, 2).await.unwrap();
+	assert_eq!(keys(batch), vec![b"e".to_vec()]);
+	assert!(!cursor.has_next());
+
+	let mut cursor = Cursor::new(cf, Bound::Unbounded, Bound::Excluded(b"e".to_vec()), true);
+	let batch = cursor.next_batch(&tx, 3).await.unwrap();
+	assert_eq!(keys(batch), vec![b"d".to_vec(), b"c".to_vec(), b"b".to_vec()]);
+	let batch = cursor.next_batch(&tx, 3).await.unwrap();
+	assert_eq!(keys(batch), vec![b"a".to_vec()]);
+}
+
+pub async fn should_report_column_family_options(adapter: impl DatastoreAdapter) {
+	let adapter = adapter.spawn();
+	let options = adapter.column_family_options().unwrap();
+	let test_suite = options.iter().find(|o| o.name == "test_suite:v1").unwrap();
+	assert!(!test_suite.compression.is_empty());
+	assert!(!test_suite.comparator.is_empty());
+}
+
+pub async fn should_report_properties(adapter: impl DatastoreAdapter) {
+	let adapter = adapter.spawn();
+	let properties = adapter.properties().unwrap();
+	let mut names: Vec<_> = properties.iter().map(|(name, _)| name.clone()).collect();
+	names.dedup();
+	assert_eq!(names.len(), properties.len());
+}
@@ -1,7 +1,9 @@
 use path_absolutize::*;
 use std::{
 	env::{self, temp_dir},
+	fs, io,
 	path::Path,
+	sync::atomic::{AtomicU64, Ordering},
 };
 
 use crate::Error;
@@ -22,10 +24,62 @@ pub fn get_absolute_path(path: &str) -> String {
 	p.absolutize_from(&cwd).unwrap().to_str().unwrap().to_string()
 }
 
+/// Bytes of the files under `path`, or of the file itself. Missing paths are empty.
+pub fn disk_size(path: &Path) -> u64 {
+	match std::fs::metadata(path) {
+		Ok(meta) if meta.is_dir() => std::fs::read_dir(path)
+			.map(|entries| entries.flatten().map(|entry| disk_size(&entry.path())).sum())
+			.unwrap_or(0),
+		Ok(meta) => meta.len(),
+		Err(_) => 0,
+	}
+}
+
+/// Copy the file, or the files under the directory, at `from` to `to`, which must not exist
+/// yet. The copied bytes are added to `progress` file by file.
+pub fn copy_path(from: &Path, to: &Path, progress: &AtomicU64) -> Result<(), Error> {
+	ensure_new_path(to)?;
+	copy_files(from, to, progress).map_err(|err| Error::Ds(err.to_string()))
+}
+
+fn copy_files(from: &Path, to: &Path, progress: &AtomicU64) -> io::Result<()> {
+	if !from.is_dir() {
+		progress.fetch_add(fs::copy(from, to)?, Ordering::Relaxed);
+		return Ok(());
+	}
+	fs::create_dir_all(to)?;
+	for entry in fs::read_dir(from)? {
+		let entry = entry?;
+		copy_files(&entry.path(), &to.join(entry.file_name()), progress)?;
+	}
+	Ok(())
+}
+
+/// Fail when a file or a directory exists at `path`, so it isn't overwritten
+pub fn ensure_new_path(path: &Path) -> Result<(), Error> {
+	match path.exists() {
+		true => Err(Error::Ds(format!("{} already exists", path.display()))),
+		false => Ok(()),
+	}
+}
+
+/// Remove the file, or the directory with its files, at `path`. Missing paths are ignored.
+pub fn remove_path(path: &Path) -> Result<(), Error> {
+	let removed = match fs::metadata(path) {
+		Ok(meta) if meta.is_dir() => fs::remove_dir_all(path),
+		Ok(_) => fs::remove_file(path),
+		Err(_) => Ok(()),
+	};
+	removed.map_err(|err| Error::Ds(err.to_string()))
+}
+
 pub fn generate_path(name: &str, id: Option<i32>) -> String {
 	match name {
 		"rocksdb" => generate_rocksdb_path(id),
+		"rocksdb-2pc" => generate_rocksdb_path(id).replacen("rocksdb:", "rocksdb-2pc:", 1),
 		"redb" => generate_redb_path(id),
+		"sled" => generate_sled_path(id),
+		"lmdb" => generate_lmdb_path(id),
 		"indxdb" => generate_indxdb_path(id),
 		_ => unimplemented!(),
 	}
@@ -72,3 +126,31 @@ fn generate_redb_path(id: Option<i32>) -> String {
 
 	String::from("redb:") + &path_to_string(&path).unwrap()
 }
+
+/// Generate a path to store data for Sled
+fn generate_sled_path(id: Option<i32>) -> String {
+	let random_id: i32 = generate_random_i32();
+	let id = &id.unwrap_or(random_id).to_string();
+	let path = if cfg!(target_os = "linux") {
+		"/dev/shm/".into()
+	} else {
+		temp_dir()
+	}
+	.join(format!("edma-sled-{}", id));
+
+	String::from("sled:") + &path_to_string(&path).unwrap()
+}
+
+/// Generate a path to store data for LMDB
+fn generate_lmdb_path(id: Option<i32>) -> String {
+	let random_id: i32 = generate_random_i32();
+	let id = &id.unwrap_or(random_id).to_string();
+	let path = if cfg!(target_os = "linux") {
+		"/dev/shm/".into()
+	} else {
+		temp_dir()
+	}
+	.join(format!("edma-lmdb-{}", id));
+
+	String::from("lmdb:") + &path_to_string(&path).unwrap()
+}
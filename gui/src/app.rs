@@ -1,5 +1,8 @@
 use crate::{
-	components::{render_container, LayoutTabComponent, MenuItem, RenderAbleComponent},
+	components::{
+		render_container, render_panel, BookmarksTabComponent, LayoutTabComponent, MenuItem, Panel,
+		PanelRegistry, RenderAbleComponent, StatsTabComponent,
+	},
 	config::Config,
 	constants::{Focus, NO_DATABASES_BANNER},
 	events::EventState,
@@ -23,6 +26,9 @@ pub struct AppComponent<'a> {
 	database: DatabaseTabComponent<'a>,
 	menu: MenuContainerComponent,
 	layout: LayoutTabComponent<'a>,
+	stats: StatsTabComponent,
+	bookmarks: BookmarksTabComponent,
+	panels: PanelRegistry,
 	focus: Focus,
 	config: Config,
 }
@@ -34,11 +40,25 @@ impl<'a> AppComponent<'a> {
 			database: DatabaseTabComponent::new(config.clone()),
 			menu: MenuContainerComponent::new(config.clone()),
 			layout: LayoutTabComponent::new(config.clone()),
+			stats: StatsTabComponent::new(config.clone()),
+			bookmarks: BookmarksTabComponent::new(config.clone()),
+			panels: PanelRegistry::default(),
 			focus: Focus::MenuContainer,
 			config,
 		}
 	}
 
+	/// Config saved by a panel, the app is rebuilt with it
+	pub fn take_config(&mut self) -> Option<Config> {
+		self.panels.take_config()
+	}
+
+	/// Add a custom panel as a menu tab, replacing the panel already registered with `id`
+	pub fn register_panel(&mut self, id: &str, panel: Box<dyn Panel>) {
+		self.panels.register(id, panel);
+		self.menu.set_panels(self.panels.tabs());
+	}
+
 	pub fn render<B: Backend>(&self, f: &mut tui::Frame<B>) -> Result<()> {
 		let window = f.size();
 
@@ -92,11 +112,36 @@ impl<'a> AppComponent<'a> {
 				MenuItem::Layout => {
 					self.layout.render(f, mid, matches!(self.focus(), Focus::LayoutTabBody))?
 				}
+				MenuItem::Stats => {
+					self.stats.render(f, mid, matches!(self.focus(), Focus::StatsTabBody))?
+				}
+				MenuItem::Bookmarks => self.bookmarks.render(
+					f,
+					mid,
+					matches!(self.focus(), Focus::BookmarksTabBody),
+				)?,
+				MenuItem::Panel(index) => {
+					if let Some(panel) = self.panels.get(index) {
+						render_panel(f, panel, mid, matches!(self.focus(), Focus::PanelBody))
+					}
+				}
 			};
 		}
 		Ok(())
 	}
 
+	pub async fn tick(&mut self) {
+		self.database.tick().await;
+		// Statistics are only read while the tab is displayed
+		if let MenuItem::Stats = self.menu.active_menu_item {
+			self.stats.tick();
+		}
+		if let MenuItem::Bookmarks = self.menu.active_menu_item {
+			self.bookmarks.tick();
+		}
+		self.panels.tick();
+	}
+
 	fn focus(&self) -> Focus {
 		self.focus.clone()
 	}
@@ -135,6 +180,31 @@ impl<'a> AppComponent<'a> {
 					return Ok(EventState::Consumed);
 				}
 			}
+			Focus::StatsTabBody => {
+				if self.stats.event(key).await?.is_consumed() {
+					return Ok(EventState::Consumed);
+				}
+			}
+			// A picked bookmark runs in the database tab
+			Focus::BookmarksTabBody => {
+				if self.bookmarks.event(key).await?.is_consumed() {
+					if let Some(bookmark) = self.bookmarks.take_picked() {
+						self.menu.set_active(MenuItem::Database);
+						self.focus = Focus::DatabaseTabBody;
+						self.database.open_bookmark(&bookmark).await;
+					}
+					return Ok(EventState::Consumed);
+				}
+			}
+			Focus::PanelBody => {
+				if let MenuItem::Panel(index) = self.menu.active_menu_item {
+					if let Some(panel) = self.panels.get_mut(index) {
+						if panel.event(key)?.is_consumed() {
+							return Ok(EventState::Consumed);
+						}
+					}
+				}
+			}
 		}
 		Ok(EventState::NotConsumed)
 	}
@@ -161,6 +231,21 @@ impl<'a> AppComponent<'a> {
 					self.focus = Focus::MenuContainer
 				}
 			}
+			Focus::StatsTabBody => {
+				if key == self.config.key_config.up {
+					self.focus = Focus::MenuContainer
+				}
+			}
+			Focus::BookmarksTabBody => {
+				if key == self.config.key_config.up {
+					self.focus = Focus::MenuContainer
+				}
+			}
+			Focus::PanelBody => {
+				if key == self.config.key_config.up {
+					self.focus = Focus::MenuContainer
+				}
+			}
 		}
 		Ok(EventState::NotConsumed)
 	}
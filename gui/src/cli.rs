@@ -1,5 +1,22 @@
-use crate::config::CliConfig;
-use structopt::StructOpt;
+use std::{
+	fs::{self, File},
+	io::{self, BufReader},
+	path::Path,
+};
+
+use crate::{
+	config::{load_config, CliConfig, Config},
+	constants::INGEST_BATCH_SIZE,
+	utils::{
+		compare_column, compared_column_families, copy_column, create_archive, extract_archive,
+		format_byte_literal, open_datastore, parse_command, parse_records, resolve_database,
+		run_retention, update_executable, write_manifest, write_query_pairs, ArchiveKeyspace,
+		ExportFormat, ExportOrder, ImportJob, PairChange, Query,
+	},
+};
+use anyhow::{anyhow, Result};
+use db::{read_trace, replay, stop_recording, write_trace, Datastore};
+use structopt::{clap::Shell, StructOpt};
 
 /// A cross-platform TUI database management tool written in Rust
 #[derive(StructOpt, Debug)]
@@ -7,8 +24,595 @@ use structopt::StructOpt;
 pub struct Cli {
 	#[structopt(flatten)]
 	pub config: CliConfig,
+
+	/// Print frames as plain text lines instead of drawing on the alternate screen, for
+	/// tmux capture and CI logs
+	#[structopt(long)]
+	pub plain: bool,
+
+	/// Record the storage operations of the session into a trace file, keys only, to
+	/// reproduce a bug with `edma replay`
+	#[structopt(long)]
+	pub record: Option<String>,
+
+	/// Hash the keys of the recorded trace
+	#[structopt(long, requires = "record")]
+	pub hash_keys: bool,
+
+	#[structopt(subcommand)]
+	pub command: Option<Command>,
+}
+
+/// Commands executed without starting the terminal interface
+#[derive(StructOpt, Debug)]
+pub enum Command {
+	/// Copy every key of a column family into another column family
+	Copy {
+		/// Source database, e.g. rocksdb:./path/to/db
+		#[structopt(long)]
+		from: String,
+		/// Source column family (RocksDB) or table (Redb)
+		#[structopt(long)]
+		column: Option<String>,
+		/// Target column family (RocksDB) or table (Redb)
+		#[structopt(long)]
+		to: String,
+		/// Target database, defaults to the source database
+		#[structopt(long)]
+		into: Option<String>,
+	},
+	/// Import the records of a JSON, NDJSON or CSV file, like the IMPORT command
+	Import {
+		/// File of records with `Key` and `Value` fields, or the segments of the layouts
+		file: String,
+		/// Database alias or address, e.g. rocksdb:./path/to/db
+		#[structopt(long)]
+		into: String,
+		/// Column family (RocksDB) or table (Redb)
+		#[structopt(long)]
+		column: Option<String>,
+		/// Format of the file: json, ndjson or csv, inferred from the extension by default
+		#[structopt(long)]
+		format: Option<String>,
+		/// Layout template encoding the key segments
+		#[structopt(long)]
+		key_layout: Option<String>,
+		/// Layout template encoding the value segments
+		#[structopt(long)]
+		value_layout: Option<String>,
+		/// Ingest RocksDB SST files without the WAL, other databases are written with
+		/// transactions. Hooks and write limits are skipped.
+		#[structopt(long)]
+		ingest: bool,
+	},
+	/// Export a RocksDB column family to SST files of one million pairs
+	ExportSst {
+		/// Database alias or address, e.g. rocksdb:./path/to/db
+		#[structopt(long)]
+		from: String,
+		/// Column family, defaults to the default column family
+		#[structopt(long)]
+		column: Option<String>,
+		/// Directory the SST files are written to, created when missing
+		#[structopt(long)]
+		dir: String,
+	},
+	/// Ingest SST files written by `edma export-sst` into a RocksDB column family
+	IngestSst {
+		/// SST files
+		#[structopt(required = true)]
+		files: Vec<String>,
+		/// Database alias or address, e.g. rocksdb:./path/to/db
+		#[structopt(long)]
+		into: String,
+		/// Column family, created when missing
+		#[structopt(long)]
+		column: Option<String>,
+	},
+	/// Compare two databases key by key with ordered scans, printing the keys which differ
+	/// and a summary of each column family
+	Compare {
+		/// First database alias or address, e.g. rocksdb:./path/to/db
+		#[structopt(long)]
+		a: String,
+		/// Second database alias or address
+		#[structopt(long)]
+		b: String,
+		/// Only compare this column family (RocksDB) or table (Redb)
+		#[structopt(long)]
+		column: Option<String>,
+		/// Write the keys and checksums of both sides to a JSON manifest
+		#[structopt(long)]
+		manifest: Option<String>,
+	},
+	/// Run the storage operations of a trace recorded with `--record` against a database,
+	/// written values are zeroed
+	Replay {
+		/// Trace file
+		trace: String,
+		/// Database the trace is replayed into, e.g. redb:./scratch.redb
+		#[structopt(long)]
+		into: String,
+		/// Only replay the operations recorded on this database path
+		#[structopt(long)]
+		from: Option<String>,
+	},
+	/// Run a read command of the command editor and print the pairs to stdout, without
+	/// starting the terminal interface
+	Query {
+		/// Database alias or address, e.g. rocksdb:./path/to/db
+		#[structopt(long)]
+		db: String,
+		/// Command tokens, e.g. "COLUMN=accounts PREFIX=user: LIMIT=10"
+		#[structopt(long)]
+		command: String,
+		/// Output format: json, ndjson, csv, markdown or html
+		#[structopt(long, default_value = "json")]
+		format: String,
+		/// Row order: key, the byte order of the keys, or insertion, the scan order
+		#[structopt(long, default_value = "key")]
+		order: String,
+	},
+	/// Run the retention rules of the config, printing the keys each rule expires
+	Retention {
+		/// Delete the expired keys instead of only counting them
+		#[structopt(long)]
+		apply: bool,
+	},
+	/// Write a portable archive of a database with the layout templates of the config, or
+	/// extract one into a database
+	Archive {
+		#[structopt(subcommand)]
+		action: ArchiveAction,
+	},
+	/// Replace the executable with the latest release binary for the platform
+	SelfUpdate,
+	/// Print the completion script of a shell, database flags complete the configured databases
+	Completions {
+		#[structopt(possible_values = &["bash", "zsh", "fish"])]
+		shell: String,
+	},
+}
+
+#[derive(StructOpt, Debug)]
+pub enum ArchiveAction {
+	/// Archive every column family of a database
+	Create {
+		/// Archive file
+		file: String,
+		/// Database alias or address, e.g. rocksdb:./path/to/db
+		#[structopt(long)]
+		from: String,
+	},
+	/// Write the pairs of an archive into a database
+	Extract {
+		/// Archive file
+		file: String,
+		/// Database alias or address, e.g. redb:./path/to/db
+		#[structopt(long)]
+		into: String,
+		/// Write the layout templates of the archive to a JSON file
+		#[structopt(long)]
+		templates: Option<String>,
+	},
 }
 
 pub fn parse() -> Cli {
 	Cli::from_args()
 }
+
+pub async fn run(command: Command, config: &CliConfig) -> Result<()> {
+	match command {
+		Command::Copy {
+			from,
+			column,
+			to,
+			into,
+		} => {
+			let source = Datastore::open(&from)?;
+			let from_cf = column.map(|c| c.as_bytes().to_vec());
+			let to_cf = Some(to.as_bytes().to_vec());
+			let progress = |copied| println!("Copied {} pairs", copied);
+			let copied = match into {
+				Some(into) if into != from => {
+					let target = Datastore::open(&into)?;
+					copy_column(&source, from_cf, &target, to_cf, progress).await?
+				}
+				_ => copy_column(&source, from_cf, &source, to_cf, progress).await?,
+			};
+			println!("Done: {} pairs copied into {}", copied, to);
+		}
+		Command::Import {
+			file,
+			into,
+			column,
+			format,
+			key_layout,
+			value_layout,
+			ingest,
+		} => {
+			let config = cli_config(config);
+			let format = match format {
+				Some(format) => ExportFormat::from_name(&format),
+				None => ExportFormat::from_path(&file),
+			}
+			.ok_or_else(|| anyhow!("Unknown format of {}", file))?;
+			let records =
+				parse_records(&fs::read_to_string(&file)?, format).map_err(|err| anyhow!(err))?;
+			let template = |name: Option<String>| match name {
+				Some(name) => match config.templates.iter().find(|t| t.name == name) {
+					Some(template) => Ok(Some(template)),
+					None => Err(anyhow!("Unknown layout {}", name)),
+				},
+				None => Ok(None),
+			};
+			let (key_template, value_template) = (template(key_layout)?, template(value_layout)?);
+			let (name, path) = resolve_database(&config, &into)
+				.ok_or_else(|| anyhow!("Unknown database {}", into))?;
+			let cf = column.map(|c| c.as_bytes().to_vec());
+			let mut job = ImportJob::new(cf, records, key_template, value_template);
+			let ds = open_datastore(&config, &format!("{}:{}", name, path))?;
+			let ingested = ingest && job.ingest(&ds)?;
+			if ingest && !ingested {
+				println!("{} can't ingest SST files, writing with transactions", name);
+			}
+			while !job.is_done() {
+				job.write_batch(&ds).await?;
+				println!("Imported {}/{} pairs", job.written(), job.total());
+			}
+			for (row, err) in job.errors.iter() {
+				println!("Skipped record {}: {}", row, err);
+			}
+			let written = if ingested {
+				"ingested"
+			} else {
+				"written"
+			};
+			println!("Done: {} pairs {} into {}", job.total(), written, into);
+		}
+		Command::ExportSst {
+			from,
+			column,
+			dir,
+		} => {
+			let config = cli_config(config);
+			let (name, path) = resolve_database(&config, &from)
+				.ok_or_else(|| anyhow!("Unknown database {}", from))?;
+			require_sst_files(&name, &path)?;
+			let ds = Datastore::open(&format!("{}:{}", name, path))?;
+			let cf = column.map(|c| c.as_bytes().to_vec());
+			let files = ds.export_sst(cf, &dir, INGEST_BATCH_SIZE)?;
+			for file in files.iter() {
+				println!("{}", file);
+			}
+			println!("Done: {} SST files written into {}", files.len(), dir);
+		}
+		Command::IngestSst {
+			files,
+			into,
+			column,
+		} => {
+			let config = cli_config(config);
+			let (name, path) = resolve_database(&config, &into)
+				.ok_or_else(|| anyhow!("Unknown database {}", into))?;
+			require_sst_files(&name, &path)?;
+			let ds = Datastore::open(&format!("{}:{}", name, path))?;
+			ds.ingest_sst(column.map(|c| c.as_bytes().to_vec()), &files)?;
+			println!("Done: {} SST files ingested into {}", files.len(), into);
+		}
+		Command::Compare {
+			a,
+			b,
+			column,
+			manifest,
+		} => {
+			let config = cli_config(config);
+			let resolve = |db: &String| {
+				resolve_database(&config, db)
+					.map(|(name, path)| format!("{}:{}", name, path))
+					.ok_or_else(|| anyhow!("Unknown database {}", db))
+			};
+			let (a_path, b_path) = (resolve(&a)?, resolve(&b)?);
+			if a_path == b_path {
+				return Err(anyhow!("{} is compared with itself", a));
+			}
+			let (ds_a, ds_b) = (Datastore::open(&a_path)?, Datastore::open(&b_path)?);
+			let sides = match column {
+				Some(column) => vec![(column, true, true)],
+				None => compared_column_families(&ds_a, &ds_b)?,
+			};
+			let (mut comparisons, mut missing) = (vec![], 0);
+			for (name, in_a, in_b) in sides {
+				if !in_a || !in_b {
+					println!(
+						"{}: only in {}",
+						name,
+						if in_a {
+							&a
+						} else {
+							&b
+						}
+					);
+					missing += 1;
+					continue;
+				}
+				let cf = match name.as_str() {
+					"default" => None,
+					_ => Some(name.as_bytes().to_vec()),
+				};
+				let report = |change: &PairChange| match change {
+					PairChange::Removed((key, _)) => {
+						println!("{} {}: only in {}", name, format_byte_literal(key), a)
+					}
+					PairChange::Added((key, _)) => {
+						println!("{} {}: only in {}", name, format_byte_literal(key), b)
+					}
+					PairChange::Changed {
+						key,
+						..
+					} => println!("{} {}: values differ", name, format_byte_literal(key)),
+				};
+				let comparison = compare_column(&ds_a, &ds_b, cf, report).await?;
+				println!(
+					"{}: {} keys in {}, {} in {}, {} only in {}, {} only in {}, {} changed",
+					name,
+					comparison.a.keys,
+					a,
+					comparison.b.keys,
+					b,
+					comparison.only_in_a,
+					a,
+					comparison.only_in_b,
+					b,
+					comparison.changed
+				);
+				comparisons.push(comparison);
+			}
+			if let Some(manifest) = manifest {
+				write_manifest(io::BufWriter::new(File::create(&manifest)?), &a, &b, &comparisons)?;
+				println!("Manifest written to {}", manifest);
+			}
+			let differ = missing + comparisons.iter().filter(|c| !c.is_identical()).count();
+			println!(
+				"Done: {} column families compared, {} differ",
+				comparisons.len() + missing,
+				differ
+			);
+		}
+		Command::Replay {
+			trace,
+			into,
+			from,
+		} => {
+			let mut trace = read_trace(BufReader::new(File::open(&trace)?))?;
+			if let Some(from) = from {
+				trace.retain(|entry| entry.db == from);
+			}
+			let replayed = replay(&Datastore::open(&into)?, &trace).await?;
+			println!("Done: {} operations replayed into {}", replayed, into);
+		}
+		Command::Query {
+			db,
+			command,
+			format,
+			order,
+		} => {
+			let format = ExportFormat::from_name(&format)
+				.ok_or_else(|| anyhow!("Unknown format {}", format))?;
+			let order =
+				ExportOrder::from_name(&order).ok_or_else(|| anyhow!("Unknown order {}", order))?;
+			let commands = parse_command(&command).map_err(|err| anyhow!(err))?;
+			let config = cli_config(config);
+			let query =
+				Query::from_commands(&config, &db, &commands).map_err(|err| anyhow!(err))?;
+			let key_display =
+				config.key_display(&format!("{}:{}", query.name, query.path), &query.cf);
+			let pairs = query.run().await?;
+			write_query_pairs(io::stdout().lock(), format, order, key_display, &pairs)?;
+		}
+		Command::Retention {
+			apply,
+		} => {
+			let config = cli_config(config);
+			for rule in config.retention.iter() {
+				let column = rule.column.as_deref().unwrap_or("default");
+				match run_retention(&config, rule, apply).await {
+					Ok(outcome) => println!(
+						"{} {}: {} of {} keys {}",
+						outcome.db_path,
+						column,
+						outcome.expired,
+						outcome.scanned,
+						if apply {
+							"deleted"
+						} else {
+							"expired"
+						}
+					),
+					Err(err) => println!("{} {}: {}", rule.database, column, err),
+				}
+			}
+		}
+		Command::Archive {
+			action: ArchiveAction::Create {
+				file,
+				from,
+			},
+		} => {
+			let config = cli_config(config);
+			let (name, path) = resolve_database(&config, &from)
+				.ok_or_else(|| anyhow!("Unknown database {}", from))?;
+			let source = format!("{}:{}", name, path);
+			let ds = open_datastore(&config, &source)?;
+			let progress = |k: &ArchiveKeyspace| println!("{}: archived {} pairs", k.name, k.pairs);
+			let writer = io::BufWriter::new(File::create(&file)?);
+			let manifest =
+				create_archive(&ds, &source, config_templates(&config), writer, progress).await?;
+			println!(
+				"Done: {} pairs of {} keyspaces archived into {}",
+				manifest.pairs(),
+				manifest.keyspaces.len(),
+				file
+			);
+		}
+		Command::Archive {
+			action: ArchiveAction::Extract {
+				file,
+				into,
+				templates,
+			},
+		} => {
+			let config = cli_config(config);
+			let (name, path) = resolve_database(&config, &into)
+				.ok_or_else(|| anyhow!("Unknown database {}", into))?;
+			let ds = open_datastore(&config, &format!("{}:{}", name, path))?;
+			let progress =
+				|k: &ArchiveKeyspace| println!("{}: extracted {} pairs", k.name, k.pairs);
+			let reader = BufReader::new(File::open(&file)?);
+			let manifest = extract_archive(reader, &ds, progress).await?;
+			if let Some(templates) = templates {
+				fs::write(&templates, serde_json::to_string_pretty(&manifest.templates)?)?;
+				println!("Layout templates written to {}", templates);
+			}
+			println!(
+				"Done: {} pairs of {} keyspaces archived from {} extracted into {}",
+				manifest.pairs(),
+				manifest.keyspaces.len(),
+				manifest.source,
+				into
+			);
+		}
+		// The download is blocking, it runs outside of the async runtime threads
+		Command::SelfUpdate => match tokio::task::spawn_blocking(update_executable).await?? {
+			(version, true) => println!("Updated to EDMA {}", version),
+			(version, false) => println!("EDMA {} is up to date", version),
+		},
+		Command::Completions {
+			shell,
+		} => print!("{}", completion_script(&shell, &configured_databases(config))?),
+	}
+	Ok(())
+}
+
+/// Write the trace of the session recorded with `--record`
+pub fn save_recording(path: &str) -> Result<()> {
+	let trace = stop_recording();
+	write_trace(io::BufWriter::new(File::create(path)?), &trace)?;
+	println!("{} storage operations recorded to {}", trace.len(), path);
+	Ok(())
+}
+
+/// Config of the command line, the default config when there is no config file yet
+fn cli_config(cli: &CliConfig) -> Config {
+	match Path::new(&Config::new(cli).path).exists() {
+		true => load_config(cli),
+		false => Config::new(cli),
+	}
+}
+
+/// Layout templates of the config file as written, none when there is no config file yet
+fn config_templates(config: &Config) -> serde_json::Value {
+	fs::read_to_string(&config.path)
+		.ok()
+		.and_then(|data| serde_json::from_str::<serde_json::Value>(&data).ok())
+		.and_then(|json| json.get("templates").cloned())
+		.unwrap_or_else(|| serde_json::json!([]))
+}
+
+/// Fail before opening a database whose backend has no SST files
+fn require_sst_files(name: &str, path: &str) -> Result<()> {
+	match Datastore::capabilities(&format!("{}:{}", name, path)) {
+		Some(capabilities) if capabilities.sst_files => Ok(()),
+		Some(_) => Err(anyhow!("SST files are only supported by RocksDB, not {}", name)),
+		None => Err(anyhow!("{} databases are not supported by this build", name)),
+	}
+}
+
+/// Databases of the config addressed as `name:path`, none when there is no config file yet
+fn configured_databases(cli: &CliConfig) -> Vec<String> {
+	let config = cli_config(cli);
+	let mut databases: Vec<String> = config
+		.databases
+		.iter()
+		.flat_map(|(name, dbs)| dbs.iter().map(move |db| format!("{}:{}", name, db.path)))
+		.collect();
+	databases.sort();
+	databases
+}
+
+/// Completion script generated from the command definitions, the values of `--from` and
+/// `--into` are read from the config when the script is generated
+pub fn completion_script(shell: &str, databases: &[String]) -> Result<String> {
+	let target = match shell {
+		"bash" => Shell::Bash,
+		"zsh" => Shell::Zsh,
+		"fish" => Shell::Fish,
+		_ => return Err(anyhow!("Unsupported shell {}", shell)),
+	};
+	let mut script = io::Cursor::new(Vec::new());
+	Cli::clap().gen_completions_to("edma", target, &mut script);
+	let script = String::from_utf8(script.into_inner())?;
+	if databases.is_empty() {
+		return Ok(script);
+	}
+	Ok(match target {
+		Shell::Bash => script + &bash_databases(databases),
+		Shell::Zsh => zsh_databases(&script, databases),
+		_ => script + &fish_databases(databases),
+	})
+}
+
+/// Wrap the generated completion function to offer the databases after a database flag
+fn bash_databases(databases: &[String]) -> String {
+	format!(
+		r#"
+_edma_databases() {{
+    case "${{COMP_WORDS[COMP_CWORD-1]}}" in
+        --from|--into)
+            COMPREPLY=($(compgen -W "{}" -- "${{COMP_WORDS[COMP_CWORD]}}"))
+            return 0
+            ;;
+    esac
+    _edma "$@"
+}}
+
+complete -F _edma_databases -o bashdefault -o default edma
+"#,
+		databases.join(" ")
+	)
+}
+
+/// Set the databases as the action of the database flag specs
+fn zsh_databases(script: &str, databases: &[String]) -> String {
+	let values: Vec<String> = databases.iter().map(|d| d.replace(' ', "\\ ")).collect();
+	let action = format!(":database:({})", values.join(" "));
+	script
+		.lines()
+		.map(|line| {
+			let spec = line.find("'--from=[").or_else(|| line.find("'--into=["));
+			let quote = spec.and_then(|start| {
+				let close = start + line[start..].find(']')?;
+				Some((close, close + line[close..].find('\'')?))
+			});
+			match quote {
+				Some((close, quote)) => format!("{}{}{}", &line[..=close], action, &line[quote..]),
+				None => line.to_string(),
+			}
+		})
+		.collect::<Vec<_>>()
+		.join("\n")
+		+ "\n"
+}
+
+fn fish_databases(databases: &[String]) -> String {
+	let values = databases.join(" ");
+	["from", "into"]
+		.iter()
+		.map(|flag| {
+			format!(
+				"complete -c edma -n \"__fish_seen_subcommand_from copy\" -l {} -x -a \"{}\"\n",
+				flag, values
+			)
+		})
+		.collect()
+}
@@ -0,0 +1,127 @@
+use anyhow::Result;
+use tui::{backend::Backend, layout::Rect, Frame};
+
+use crate::{
+	components::{RenderAbleComponent, ReportComponent},
+	config::Config,
+	events::{EventState, Key},
+	ui::StatefulTable,
+	utils::{bookmarks_path, delete_bookmark, load_bookmarks, Bookmark},
+};
+
+enum Focus {
+	Container,
+	Inner,
+}
+
+const TITLE: &str = "Bookmarks [ENTER run, BACKSPACE delete]";
+const WIDTHS: [u16; 4] = [15, 30, 15, 40];
+
+fn bookmarks_table(bookmarks: &[Bookmark]) -> StatefulTable {
+	let items = bookmarks
+		.iter()
+		.map(|b| {
+			let column = b.column.clone().unwrap_or_else(|| "default".to_string());
+			vec![b.name.clone(), b.database.clone(), column, b.command.clone()]
+		})
+		.collect();
+	StatefulTable::default()
+		.with_items(items)
+		.with_headers(vec!["Name", "Database", "Column", "Command"])
+		.build()
+}
+
+/// Bookmarks saved with the BOOKMARK command. ENTER on a bookmark switches to the database
+/// tab and runs its command, BACKSPACE deletes it.
+pub struct BookmarksTabComponent {
+	config: Config,
+	bookmarks: Vec<Bookmark>,
+	table: ReportComponent,
+	picked: Option<Bookmark>,
+	focus: Focus,
+}
+
+impl BookmarksTabComponent {
+	pub fn new(config: Config) -> Self {
+		BookmarksTabComponent {
+			table: ReportComponent::new(
+				config.clone(),
+				TITLE,
+				bookmarks_table(&[]),
+				WIDTHS.to_vec(),
+			),
+			config,
+			bookmarks: vec![],
+			picked: None,
+			focus: Focus::Container,
+		}
+	}
+
+	/// Reload the bookmarks file, bookmarks are saved from the command editor while the tab
+	/// is not displayed
+	pub fn tick(&mut self) {
+		let bookmarks = load_bookmarks(&bookmarks_path(&self.config.path));
+		if bookmarks != self.bookmarks {
+			self.set_bookmarks(bookmarks);
+		}
+	}
+
+	/// Rows pick the name of their bookmark
+	fn set_bookmarks(&mut self, bookmarks: Vec<Bookmark>) {
+		let names = bookmarks.iter().map(|b| Some(b.name.clone())).collect();
+		let table = bookmarks_table(&bookmarks);
+		self.table = ReportComponent::new(self.config.clone(), TITLE, table, WIDTHS.to_vec())
+			.with_actions(names);
+		self.bookmarks = bookmarks;
+	}
+
+	/// Take the bookmark picked with ENTER, if any
+	pub fn take_picked(&mut self) -> Option<Bookmark> {
+		self.picked.take()
+	}
+
+	pub async fn event(&mut self, key: Key) -> Result<EventState> {
+		match self.focus {
+			Focus::Container => {
+				if key == self.config.key_config.enter {
+					self.focus = Focus::Inner;
+					return Ok(EventState::Consumed);
+				}
+			}
+			Focus::Inner => {
+				if key == self.config.key_config.escape {
+					self.focus = Focus::Container;
+					return Ok(EventState::Consumed);
+				}
+				if key == self.config.key_config.backspace {
+					let selected = self.table.selected().and_then(|i| self.bookmarks.get(i));
+					if let Some(bookmark) = selected {
+						let path = bookmarks_path(&self.config.path);
+						if delete_bookmark(&path, &bookmark.name).is_ok() {
+							self.tick();
+						}
+					}
+					return Ok(EventState::Consumed);
+				}
+				if self.table.event(key).await?.is_consumed() {
+					if let Some(name) = self.table.take_action() {
+						self.picked = self.bookmarks.iter().find(|b| b.name == name).cloned();
+					}
+					return Ok(EventState::Consumed);
+				}
+			}
+		}
+		Ok(EventState::NotConsumed)
+	}
+}
+
+impl RenderAbleComponent for BookmarksTabComponent {
+	fn render<B: Backend>(
+		&self,
+		f: &mut Frame<B>,
+		rect: Rect,
+		focused: bool,
+	) -> Result<(), anyhow::Error> {
+		self.table.render(f, rect, focused)
+	}
+}
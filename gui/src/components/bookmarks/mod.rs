@@ -0,0 +1,3 @@
+mod container;
+
+pub use container::*;
@@ -0,0 +1,251 @@
+use std::{cell::Cell, fs};
+
+use tui::{
+	buffer::Buffer,
+	layout::Rect,
+	style::{Color, Modifier, Style},
+	text::{Span, Spans},
+	widgets::{Paragraph, Widget},
+};
+
+use crate::{
+	config::{validate_config, Config},
+	constants::HIGHLIGHT_COLOR,
+	events::{EventState, Key},
+};
+
+use super::{render_container, Panel};
+
+/// Color of a JSON token: object keys, strings and other values
+fn token_style(token: &str, is_key: bool) -> Style {
+	match token.chars().next() {
+		Some('"') if is_key => Style::default().fg(HIGHLIGHT_COLOR),
+		Some('"') => Style::default().fg(Color::Green),
+		Some(c) if c.is_ascii_digit() || c == '-' || c.is_ascii_alphabetic() => {
+			Style::default().fg(Color::Cyan)
+		}
+		_ => Style::default(),
+	}
+}
+
+/// Spans of a line of JSON. A string is a key when a colon follows it.
+fn highlight_json(line: &str) -> Spans<'static> {
+	let chars: Vec<char> = line.chars().collect();
+	let mut spans = vec![];
+	let mut index = 0;
+	while index < chars.len() {
+		let start = index;
+		match chars[index] {
+			'"' => {
+				index += 1;
+				while index < chars.len() && chars[index] != '"' {
+					index += if chars[index] == '\\' {
+						2
+					} else {
+						1
+					};
+				}
+				index = (index + 1).min(chars.len());
+			}
+			c if c.is_ascii_alphanumeric() || c == '-' || c == '.' => {
+				while index < chars.len()
+					&& (chars[index].is_ascii_alphanumeric() || "-+.".contains(chars[index]))
+				{
+					index += 1;
+				}
+			}
+			_ => index += 1,
+		}
+		let token: String = chars[start..index].iter().collect();
+		let is_key = chars[index..].iter().find(|c| !c.is_whitespace()) == Some(&':');
+		spans.push(Span::styled(token.clone(), token_style(&token, is_key)));
+	}
+	Spans::from(spans)
+}
+
+/// Panel of the File tab editing the config file. The text is checked by loading it like
+/// at startup before it is written, then the loaded config is applied to the app.
+pub struct ConfigPanel {
+	config: Config,
+	lines: Vec<String>,
+	/// Line and char of the cursor
+	cursor: (usize, usize),
+	editing: bool,
+	changed: bool,
+	message: Option<String>,
+	/// Config written by the last save, taken by the app
+	saved: Option<Config>,
+	/// First displayed line, moved by the render to keep the cursor visible
+	top: Cell<usize>,
+}
+
+impl ConfigPanel {
+	pub fn new(config: Config) -> Self {
+		let text = fs::read_to_string(&config.path).unwrap_or_default();
+		let mut lines: Vec<String> = text.lines().map(|l| l.to_string()).collect();
+		if lines.is_empty() {
+			lines.push(String::new());
+		}
+		ConfigPanel {
+			config,
+			lines,
+			cursor: (0, 0),
+			editing: false,
+			changed: false,
+			message: None,
+			saved: None,
+			top: Cell::new(0),
+		}
+	}
+
+	fn line_len(&self, line: usize) -> usize {
+		self.lines[line].chars().count()
+	}
+
+	/// Byte index of the char of the cursor in its line
+	fn byte_index(&self) -> usize {
+		let (line, column) = self.cursor;
+		self.lines[line].char_indices().nth(column).map_or(self.lines[line].len(), |(i, _)| i)
+	}
+
+	fn move_to(&mut self, line: usize, column: usize) {
+		let line = line.min(self.lines.len() - 1);
+		self.cursor = (line, column.min(self.line_len(line)));
+	}
+
+	fn insert(&mut self, c: char) {
+		let index = self.byte_index();
+		self.lines[self.cursor.0].insert(index, c);
+		self.cursor.1 += 1;
+		self.changed = true;
+	}
+
+	fn split_line(&mut self) {
+		let index = self.byte_index();
+		let (line, _) = self.cursor;
+		let rest = self.lines[line].split_off(index);
+		// The new line keeps the indentation of the split one
+		let indent: String = self.lines[line].chars().take_while(|c| c.is_whitespace()).collect();
+		self.lines.insert(line + 1, format!("{}{}", indent, rest));
+		self.cursor = (line + 1, indent.chars().count());
+		self.changed = true;
+	}
+
+	fn delete_backward(&mut self) {
+		let (line, column) = self.cursor;
+		if column > 0 {
+			self.cursor.1 -= 1;
+			let index = self.byte_index();
+			self.lines[line].remove(index);
+		} else if line > 0 {
+			let removed = self.lines.remove(line);
+			self.cursor = (line - 1, self.line_len(line - 1));
+			self.lines[line - 1].push_str(&removed);
+		} else {
+			return;
+		}
+		self.changed = true;
+	}
+
+	/// Write the text once it loads like a config file at startup
+	fn save(&mut self) {
+		let data = self.lines.join("\n") + "\n";
+		let config = match validate_config(&self.config, &data) {
+			Ok(config) => config,
+			Err(err) => {
+				self.message = Some(format!("Not saved, {}", err));
+				return;
+			}
+		};
+		self.message = Some(match fs::write(&self.config.path, data) {
+			Ok(()) => {
+				self.changed = false;
+				self.config = config.clone();
+				self.saved = Some(config);
+				"Config saved and applied".to_string()
+			}
+			Err(err) => format!("Unable to write the config: {}", err),
+		});
+	}
+
+	fn edit_event(&mut self, key: Key) -> EventState {
+		let (line, column) = self.cursor;
+		match key {
+			Key::Esc => self.editing = false,
+			k if k == self.config.key_config.save_config => self.save(),
+			Key::Up => self.move_to(line.saturating_sub(1), column),
+			Key::Down => self.move_to(line + 1, column),
+			Key::Left if column > 0 => self.cursor.1 -= 1,
+			Key::Left if line > 0 => self.move_to(line - 1, usize::MAX),
+			Key::Right if column < self.line_len(line) => self.cursor.1 += 1,
+			Key::Right if line + 1 < self.lines.len() => self.move_to(line + 1, 0),
+			Key::Home => self.cursor.1 = 0,
+			Key::End => self.cursor.1 = self.line_len(line),
+			Key::Enter => self.split_line(),
+			Key::Backspace => self.delete_backward(),
+			Key::Tab => (0..2).for_each(|_| self.insert(' ')),
+			Key::Char(c) => self.insert(c),
+			_ => {}
+		}
+		EventState::Consumed
+	}
+
+	fn status(&self) -> String {
+		if let Some(message) = &self.message {
+			return message.clone();
+		}
+		let mode = match self.editing {
+			true => format!("ESC stops editing, {} saves", self.config.key_config.save_config),
+			false => "ENTER edits".to_string(),
+		};
+		match self.changed {
+			true => format!("modified, {}", mode),
+			false => mode,
+		}
+	}
+}
+
+impl Panel for ConfigPanel {
+	fn title(&self) -> &str {
+		"File"
+	}
+
+	fn key(&self) -> Key {
+		Key::Char('f')
+	}
+
+	fn render(&self, area: Rect, buf: &mut Buffer, focused: bool) {
+		let visible = area.height.saturating_sub(2).max(1) as usize;
+		let (line, column) = self.cursor;
+		let top = self.top.get().min(line).max((line + 1).saturating_sub(visible));
+		self.top.set(top);
+
+		let lines: Vec<_> =
+			self.lines.iter().skip(top).take(visible).map(|l| highlight_json(l)).collect();
+		let title = format!("{} ({})", self.config.path, self.status());
+		Widget::render(Paragraph::new(lines).block(render_container(&title, focused)), area, buf);
+
+		if self.editing {
+			let (x, y) = (area.x + 1 + column as u16, area.y + 1 + (line - top) as u16);
+			if x < area.right().saturating_sub(1) && y < area.bottom().saturating_sub(1) {
+				buf.get_mut(x, y).set_style(Style::default().add_modifier(Modifier::REVERSED));
+			}
+		}
+	}
+
+	fn event(&mut self, key: Key) -> anyhow::Result<EventState> {
+		self.message = None;
+		if self.editing {
+			return Ok(self.edit_event(key));
+		}
+		if key == self.config.key_config.enter {
+			self.editing = true;
+			return Ok(EventState::Consumed);
+		}
+		Ok(EventState::NotConsumed)
+	}
+
+	fn take_config(&mut self) -> Option<Config> {
+		self.saved.take()
+	}
+}
@@ -1,11 +1,42 @@
 use tui::{
+	layout::{Constraint, Direction, Layout, Rect},
 	style::{Color, Style},
 	widgets::{Block, Borders},
 };
 
-use crate::constants::{BORDER_TYPE, PRIMARY_COLOR};
+use crate::{
+	constants::{BORDER_TYPE, NARROW_WIDTH, PRIMARY_COLOR, SIDEBAR_OVERLAY_WIDTH},
+	ui::text_marker,
+};
+
+/// Direction of panels displayed side by side, stacked in narrow terminals
+pub fn panel_direction(rect: Rect) -> Direction {
+	match rect.width < NARROW_WIDTH {
+		true => Direction::Vertical,
+		false => Direction::Horizontal,
+	}
+}
+
+/// Sidebar and main area of a tab, with whether the sidebar is an overlay. In narrow
+/// terminals the main area takes the whole width and the sidebar is drawn over its left side.
+pub fn sidebar_layout(rect: Rect) -> (Rect, Rect, bool) {
+	if rect.width < NARROW_WIDTH {
+		let sidebar = Rect::new(rect.x, rect.y, rect.width.min(SIDEBAR_OVERLAY_WIDTH), rect.height);
+		return (sidebar, rect, true);
+	}
+	let chunks = Layout::default()
+		.direction(Direction::Horizontal)
+		.constraints([Constraint::Percentage(20), Constraint::Percentage(80)])
+		.split(rect);
+	(chunks[0], chunks[1], false)
+}
 
 pub fn render_container<'a>(title: &'a str, focused: bool) -> Block<'a> {
+	let marker = if focused {
+		text_marker("> ")
+	} else {
+		""
+	};
 	Block::<'a>::default()
 		.borders(Borders::ALL)
 		.style(Style::default().fg(if focused {
@@ -13,6 +44,6 @@ pub fn render_container<'a>(title: &'a str, focused: bool) -> Block<'a> {
 		} else {
 			PRIMARY_COLOR
 		}))
-		.title(title)
+		.title(format!("{}{}", marker, title))
 		.border_type(BORDER_TYPE)
 }
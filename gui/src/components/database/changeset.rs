@@ -0,0 +1,82 @@
+use db::{Key, Val, CF};
+use tui::{
+	backend::Backend,
+	layout::{Constraint, Rect},
+	widgets::{Row, Table},
+	Frame,
+};
+
+use crate::{
+	components::{render_container, RenderAbleComponent},
+	constants::CHANGESET_PANEL_ROWS,
+	utils::column_label,
+};
+
+/// Panel listing the writes queued in the pending changeset
+pub struct ChangesetComponent {
+	/// Database of the pending changeset, addressed as `name:path`
+	database: Option<String>,
+	changes: Vec<(CF, Key, Option<Val>)>,
+}
+
+impl ChangesetComponent {
+	pub fn new() -> Self {
+		ChangesetComponent {
+			database: None,
+			changes: vec![],
+		}
+	}
+
+	pub fn set_changeset(
+		&mut self,
+		database: Option<String>,
+		changes: Vec<(CF, Key, Option<Val>)>,
+	) {
+		self.database = database;
+		self.changes = changes;
+	}
+
+	/// Rows taken by the panel, none without pending changeset
+	pub fn height(&self) -> u16 {
+		match self.database {
+			Some(_) => self.changes.len().min(CHANGESET_PANEL_ROWS) as u16 + 2,
+			None => 0,
+		}
+	}
+}
+
+impl RenderAbleComponent for ChangesetComponent {
+	fn render<B: Backend>(
+		&self,
+		f: &mut Frame<B>,
+		rect: Rect,
+		focused: bool,
+	) -> Result<(), anyhow::Error> {
+		let database = match &self.database {
+			Some(database) => database,
+			None => return Ok(()),
+		};
+		let skipped = self.changes.len().saturating_sub(CHANGESET_PANEL_ROWS);
+		let rows = self.changes[skipped..].iter().map(|(cf, key, value)| {
+			let (operation, value) = match value {
+				Some(value) => ("SET", format!("{:?}", value)),
+				None => ("DELETE", String::new()),
+			};
+			Row::new(vec![column_label(cf), operation.to_string(), format!("{:?}", key), value])
+		});
+		let title = format!(
+			"Changeset on {} ({} pending, COMMIT or DISCARD)",
+			database,
+			self.changes.len()
+		);
+		let widths = [
+			Constraint::Percentage(15),
+			Constraint::Percentage(10),
+			Constraint::Percentage(35),
+			Constraint::Percentage(40),
+		];
+		let table = Table::new(rows).block(render_container(&title, focused)).widths(&widths);
+		f.render_widget(table, rect);
+		Ok(())
+	}
+}
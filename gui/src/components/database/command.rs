@@ -3,8 +3,14 @@ use crate::{
 	config::Config,
 	constants::{BORDER_TYPE, HIGHLIGHT_COLOR},
 	events::{EventState, Key},
+	ui::{no_color, text_marker},
+	utils::{
+		history_path, is_byte_literal, load_history, now_timestamp, parse_byte_literal,
+		parse_command, record_command, save_history, split_quoted, Command, HistoryEntry,
+	},
 };
 use anyhow::Result;
+use std::io;
 use tui::{
 	backend::Backend,
 	layout::Rect,
@@ -20,28 +26,29 @@ enum Focus {
 	Textarea,
 }
 
-#[derive(Clone, Debug)]
-pub struct Command {
-	pub token: String,
-	pub value: String,
-}
-
 pub struct CommandComponent {
 	config: Config,
 	text: Vec<char>,
 	focus: Focus,
 	invalid: (bool, String),
 	pub commands: Vec<Command>,
+	history: Vec<HistoryEntry>,
+	/// History entry displayed by Up and Down, the typed text is kept as a draft
+	recall: Option<usize>,
+	draft: Vec<char>,
 }
 
 impl CommandComponent {
 	pub fn new(config: Config) -> Self {
 		CommandComponent {
-			config,
 			text: vec![],
 			focus: Focus::Container,
 			invalid: (false, "".to_string()),
 			commands: vec![],
+			history: load_history(&history_path(&config.path)),
+			recall: None,
+			draft: vec![],
+			config,
 		}
 	}
 
@@ -49,57 +56,108 @@ impl CommandComponent {
 		self.invalid = (invalid, err.to_string());
 	}
 
-	fn add_command(&mut self, command: Command) {
-		self.commands.push(command);
-	}
-
 	fn handle_command(&mut self) {
-		let mapped: Vec<_> = self.text.iter().map(|t| t.to_string()).collect();
-		let complete = mapped.join("");
-		let splitted = complete.split(' ');
-		for token in splitted {
-			match token {
-				t if token.starts_with("COLUMN") | token.starts_with("TABLE") => {
-					let value = t.split('=').nth(1);
-					match value {
-						Some(v) => self.add_command(Command {
-							token: "COLUMN".to_string(),
-							value: v.to_string(),
-						}),
-						None => {
-							return self.set_invalid(true, "No COLUMN value found");
-						}
-					}
-				}
-				t if token.starts_with("PREFIX") => {
-					let value = t.split('=').nth(1);
-					match value {
-						Some(v) => self.add_command(Command {
-							token: "PREFIX".to_string(),
-							value: v.replace('"', "").to_string(),
-						}),
-						None => {
-							return self.set_invalid(true, "No PREFIX value found");
-						}
-					}
-				}
-				t if token.starts_with("SUFFIX") => {
-					let value = t.split('=').nth(1);
-					match value {
-						Some(v) => self.add_command(Command {
-							token: "SUFFIX".to_string(),
-							value: v.replace('"', "").to_string(),
-						}),
-						None => {
-							return self.set_invalid(true, "No SUFFIX value found");
-						}
-					}
-				}
-				_ => return self.set_invalid(true, "Mismatch command"),
+		let complete: String = self.text.iter().collect();
+		record_command(&complete);
+		match parse_command(&complete) {
+			Ok(commands) => {
+				self.commands = commands;
+				self.set_invalid(false, "");
 			}
+			Err(err) => self.set_invalid(true, &err),
+		}
+	}
+
+	/// Replace the command text and focus the textarea to let the user review it
+	pub fn set_text(&mut self, text: &str) {
+		self.text = text.chars().collect();
+		self.recall = None;
+		self.focus = Focus::Textarea;
+		self.set_invalid(false, "");
+	}
+
+	/// Text of the written command
+	pub fn text(&self) -> String {
+		self.text.iter().collect()
+	}
+
+	/// Write a command and parse it like ENTER does. Returns false when it is invalid.
+	pub fn submit(&mut self, text: &str) -> bool {
+		self.set_text(text);
+		self.handle_command();
+		!self.invalid.0
+	}
+
+	/// Commands run from the editor, oldest first
+	pub fn history(&self) -> &[HistoryEntry] {
+		&self.history
+	}
+
+	/// Add the command that just ran against `database` to the history file
+	pub fn remember(&mut self, database: &str) -> io::Result<()> {
+		self.recall = None;
+		let command: String = self.text.iter().collect();
+		let command = command.trim().to_string();
+		let last = self.history.last();
+		if command.is_empty()
+			|| last.map_or(false, |e| e.command == command && e.database == database)
+		{
+			return Ok(());
 		}
+		self.history.push(HistoryEntry {
+			command,
+			database: database.to_string(),
+			timestamp: now_timestamp(),
+		});
+		save_history(&history_path(&self.config.path), &self.history)
+	}
+
+	/// Display the command run before the recalled one. Returns false without history.
+	fn recall_previous(&mut self) -> bool {
+		let index = match self.recall {
+			Some(index) => index.saturating_sub(1),
+			None if self.history.is_empty() => return false,
+			None => {
+				self.draft = self.text.clone();
+				self.history.len() - 1
+			}
+		};
+		self.recall = Some(index);
+		self.text = self.history[index].command.chars().collect();
+		self.set_invalid(false, "");
+		true
+	}
 
+	/// Display the command run after the recalled one, then the draft. Returns false when
+	/// no command is recalled.
+	fn recall_next(&mut self) -> bool {
+		let index = match self.recall {
+			Some(index) => index + 1,
+			None => return false,
+		};
+		match self.history.get(index) {
+			Some(entry) => {
+				self.recall = Some(index);
+				self.text = entry.command.chars().collect();
+			}
+			None => {
+				self.recall = None;
+				self.text = std::mem::take(&mut self.draft);
+			}
+		}
 		self.set_invalid(false, "");
+		true
+	}
+
+	/// Length of the bytes pasted in the last key value being typed
+	fn literal_length(&self) -> Option<usize> {
+		let text: String = self.text.iter().collect();
+		let token = split_quoted(&text).pop()?;
+		let (keyword, value) = token.split_once('=')?;
+		if !["PREFIX", "SUFFIX", "KEY"].contains(&keyword) || !is_byte_literal(value) {
+			return None;
+		}
+		parse_byte_literal(value.trim_matches('"')).ok().map(|bytes| bytes.len())
 	}
 
 	pub fn reset_command(&mut self) {
@@ -123,12 +181,22 @@ impl CommandComponent {
 					self.handle_command();
 					return Ok(EventState::Consumed);
 				}
+				// An edited command is a new command, not a recalled one. Characters are
+				// typed even when they are bound, e.g. to navigate with the vim profile.
 				Key::Char(v) => {
 					self.text.push(v);
+					self.recall = None;
+					return Ok(EventState::Consumed);
+				}
+				k if k == self.config.key_config.up && self.recall_previous() => {
+					return Ok(EventState::Consumed);
+				}
+				k if k == self.config.key_config.down && self.recall_next() => {
 					return Ok(EventState::Consumed);
 				}
 				k if k == self.config.key_config.backspace => {
 					self.text.pop();
+					self.recall = None;
 					return Ok(EventState::Consumed);
 				}
 				_ => {}
@@ -150,7 +218,10 @@ impl RenderAbleComponent for CommandComponent {
 			Focus::Container => "ENTER",
 			Focus::Textarea => "ESC",
 		};
-		let label = &format!("Command [{}]", keycode);
+		let label = &match self.literal_length() {
+			Some(length) => format!("Command [{}] {} bytes", keycode, length),
+			None => format!("Command [{}]", keycode),
+		};
 		if matches!(self.focus, Focus::Container) && self.text.is_empty() {
 			let mut placeholder = Text::from("Press Enter to write a command");
 			placeholder.patch_style(Style::default().fg(Color::DarkGray));
@@ -158,13 +229,17 @@ impl RenderAbleComponent for CommandComponent {
 			let widget = Paragraph::new(placeholder).block(render_container(label, focused));
 			f.render_widget(widget, rect);
 		} else if matches!(self.focus, Focus::Textarea) || !self.text.is_empty() {
-			let style = Style::default().bg(HIGHLIGHT_COLOR).add_modifier(Modifier::BOLD);
+			let mut style = Style::default().bg(HIGHLIGHT_COLOR).add_modifier(Modifier::BOLD);
+			if no_color() {
+				// The background color marks the cursor otherwise
+				style = style.add_modifier(Modifier::REVERSED);
+			}
 			let mut textarea = TextArea::default();
 			textarea.set_cursor_style(style);
 
 			let (invalid, err) = &self.invalid;
 			if *invalid {
-				let label = &format!("{} [{}]", err, keycode);
+				let label = &format!("{}{} [{}]", text_marker("[ERR] "), err, keycode);
 				let container = Block::default()
 					.borders(Borders::ALL)
 					.style(Style::default().fg(Color::Red))
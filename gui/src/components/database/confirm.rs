@@ -0,0 +1,85 @@
+use anyhow::Result;
+use tui::{
+	backend::Backend,
+	layout::{Alignment, Rect},
+	text::{Span, Spans},
+	widgets::{Clear, Paragraph, Wrap},
+	Frame,
+};
+
+use crate::{
+	components::{render_container, RenderAbleComponent},
+	config::Config,
+	events::{EventState, Key},
+};
+
+/// Prompt answered before a destructive write is committed, `y` or ENTER confirms and
+/// `n` or ESC cancels
+pub struct ConfirmComponent {
+	config: Config,
+	message: Option<String>,
+	answer: Option<bool>,
+}
+
+impl ConfirmComponent {
+	pub fn new(config: Config) -> Self {
+		ConfirmComponent {
+			config,
+			message: None,
+			answer: None,
+		}
+	}
+
+	pub fn open(&mut self, message: String) {
+		self.message = Some(message);
+		self.answer = None;
+	}
+
+	/// Take the answer of the prompt once it is closed
+	pub fn take_answer(&mut self) -> Option<bool> {
+		self.answer.take()
+	}
+
+	pub async fn event(&mut self, key: Key) -> Result<EventState> {
+		let answer = match key {
+			Key::Char('y') => true,
+			k if k == self.config.key_config.enter => true,
+			Key::Char('n') => false,
+			k if k == self.config.key_config.escape => false,
+			_ => return Ok(EventState::NotConsumed),
+		};
+		self.message = None;
+		self.answer = Some(answer);
+		Ok(EventState::Consumed)
+	}
+}
+
+impl RenderAbleComponent for ConfirmComponent {
+	fn render<B: Backend>(
+		&self,
+		f: &mut Frame<B>,
+		rect: Rect,
+		focused: bool,
+	) -> Result<(), anyhow::Error> {
+		let message = match &self.message {
+			Some(message) => message,
+			None => return Ok(()),
+		};
+		let width = rect.width.min(60);
+		let area = Rect::new(rect.x + (rect.width - width) / 2, rect.y + rect.height / 3, width, 6)
+			.intersection(rect);
+		let prompt = Paragraph::new(vec![
+			Spans::from(vec![Span::raw(message.as_str())]),
+			Spans::from(vec![Span::raw("")]),
+			Spans::from(vec![Span::raw("y: confirm, n: cancel")]),
+		])
+		.alignment(Alignment::Center)
+		.wrap(Wrap {
+			trim: true,
+		})
+		.block(render_container("Confirm", focused));
+		f.render_widget(Clear, area);
+		f.render_widget(prompt, area);
+		Ok(())
+	}
+}
@@ -1,26 +1,105 @@
 use crate::{
-	components::RenderAbleComponent,
+	components::{sidebar_layout, RenderAbleComponent},
 	config::Config,
 	events::{EventState, Key},
-	utils::get_absolute_path,
+	utils::{
+		bookmarks_path, column_label, format_bytes, get_absolute_path, now_timestamp,
+		parse_byte_literal, relative_time, resolve_database, save_bookmark, set_relative_time,
+		split_quoted, write_timeline, BackgroundBackup, BackgroundCopy, BackgroundCount,
+		BackgroundDiff, BackgroundGlobalSearch, BackgroundImport, BackgroundSearch, Bookmark,
+		CfAction, Command, CopyJob, CountJob, CountMode, DatabaseHealth, ExpiryWorker,
+		HealthChecker, Job, JobEvent, Jobs, KeyspaceDiff, SearchScope, SnapshotAction,
+		StatsRefresher, TimelineEntry, UpdateChecker, Weight,
+	},
 };
 use anyhow::Result;
+use db::{Capabilities, Datastore, Overlay, Page, CF};
+use std::{
+	mem,
+	path::Path,
+	sync::atomic::{AtomicU64, Ordering},
+	time::Instant,
+};
 use tui::{
 	backend::Backend,
 	layout::{Constraint, Direction, Layout, Rect},
-	text::Span,
+	style::{Modifier, Style},
+	text::{Span, Spans},
+	widgets::{Clear, Tabs},
 	Frame,
 };
 
 use super::{
-	database_explorer::DatabaseExplorerComponent, CommandComponent, DatabaseEditorComponent,
-	DatabaseSelectionComponent, StatusComponent,
+	database_explorer::{DatabaseExplorerComponent, ExplorerItem},
+	ChangesetComponent, CommandComponent, ConfirmComponent, DatabaseEditorComponent,
+	DatabaseSelectionComponent, Deletion, HistoryComponent, JobsComponent, StatusComponent,
 };
 
 enum Focus {
 	Explorer,
 	Editor,
 	Command,
+	Confirm,
+	History,
+}
+
+/// CF DROP waiting for the answer of the confirmation prompt
+struct PendingDrop {
+	name: String,
+	path: String,
+	cf: String,
+}
+
+/// DELETE waiting for the answer of the confirmation prompt
+struct PendingDelete {
+	cf: CF,
+	name: String,
+	path: String,
+	deletion: Deletion,
+}
+
+/// RESTORE waiting for the answer of the confirmation prompt
+struct PendingRestore {
+	name: String,
+	path: String,
+	backup: String,
+}
+
+/// Writes queued by SET and DELETE between BEGIN and COMMIT or DISCARD
+struct Changeset {
+	name: String,
+	path: String,
+	overlay: Overlay,
+}
+
+/// Database and column selection, command and results of a workspace tab. The slot of the
+/// active workspace holds the components swapped out when it was activated.
+struct Workspace<'a> {
+	databases: DatabaseSelectionComponent<'a>,
+	explorer: DatabaseExplorerComponent<'a>,
+	editor: DatabaseEditorComponent<'a>,
+	command: String,
+}
+
+impl Workspace<'_> {
+	fn new(config: &Config) -> Self {
+		Workspace {
+			databases: DatabaseSelectionComponent::new(config.clone()),
+			explorer: DatabaseExplorerComponent::new(config.clone()),
+			editor: DatabaseEditorComponent::new(config.clone()),
+			command: String::new(),
+		}
+	}
+}
+
+/// Whether the backend supports what the command needs, commands run on every backend
+/// otherwise
+fn is_supported(command: &Command, capabilities: &Capabilities) -> bool {
+	match command {
+		Command::Cf(CfAction::Create(_) | CfAction::Drop(_)) => capabilities.column_families,
+		Command::Backup(_) | Command::Restore(_) => capabilities.backup,
+		_ => true,
+	}
 }
 
 pub struct DatabaseTabComponent<'a> {
@@ -31,21 +110,255 @@ pub struct DatabaseTabComponent<'a> {
 	explorer: DatabaseExplorerComponent<'a>,
 	editor: DatabaseEditorComponent<'a>,
 	status: StatusComponent<'a>,
+	confirm: ConfirmComponent,
+	history: HistoryComponent,
+	pending_delete: Option<PendingDelete>,
+	pending_drop: Option<PendingDrop>,
+	pending_restore: Option<PendingRestore>,
+	stats: StatsRefresher,
+	expiry: ExpiryWorker,
+	health: HealthChecker,
+	update: UpdateChecker,
+	jobs: Jobs,
+	jobs_panel: JobsComponent,
+	changeset: Option<Changeset>,
+	changeset_panel: ChangesetComponent,
+	workspaces: Vec<Workspace<'a>>,
+	workspace: usize,
+	/// Commands of the session shared by the workspaces, oldest first
+	timeline: Vec<TimelineEntry>,
 }
 
 impl<'a> DatabaseTabComponent<'a> {
 	pub fn new(config: Config) -> Self {
-		DatabaseTabComponent {
+		let mut tab = DatabaseTabComponent {
 			explorer: DatabaseExplorerComponent::new(config.clone()),
 			editor: DatabaseEditorComponent::new(config.clone()),
 			status: StatusComponent::new(config.clone()),
+			confirm: ConfirmComponent::new(config.clone()),
+			history: HistoryComponent::new(config.clone()),
+			pending_delete: None,
+			pending_drop: None,
+			pending_restore: None,
 			databases: DatabaseSelectionComponent::new(config.clone()),
 			command: CommandComponent::new(config.clone()),
+			stats: StatsRefresher::new(&config),
+			expiry: ExpiryWorker::new(&config),
+			health: HealthChecker::new(&config),
+			update: UpdateChecker::new(&config),
+			jobs: Jobs::default(),
+			jobs_panel: JobsComponent::new(config.clone()),
+			changeset: None,
+			changeset_panel: ChangesetComponent::new(),
+			workspaces: vec![Workspace::new(&config)],
+			workspace: 0,
+			timeline: vec![],
 			focus: Focus::Explorer,
 			config,
+		};
+		// A database which fails to open is marked before anything opens it, the others
+		// stay usable
+		tab.health.check_all(&tab.config);
+		tab.set_health();
+		tab
+	}
+
+	fn set_health(&mut self) {
+		self.databases.set_health(self.health.health());
+		self.explorer.set_health(self.health.health());
+	}
+
+	/// Refresh the stats and health and purge expired keys of the next configured database
+	/// when a round is due, and advance the background jobs. A newer release found at startup
+	/// is reported in the status bar.
+	pub async fn tick(&mut self) {
+		if self.health.tick(&self.config).await {
+			self.set_health();
+		}
+		if self.stats.tick(&self.config).await {
+			self.explorer.set_stats(self.stats.stats().clone());
+		}
+		if self.expiry.tick(&self.config).await > 0 {
+			let text = format!("{} expired keys deleted", self.expiry.expired());
+			self.status.set_text(Span::raw(text));
+		}
+		self.editor.tick_metrics();
+		self.editor.tick_memory();
+		if let Some(text) = self.editor.tick_watch().await {
+			self.status.set_text(Span::raw(text));
+		}
+		for event in self.jobs.tick(&self.config).await {
+			self.handle_job_event(event).await;
+		}
+		self.jobs_panel.set_jobs(self.jobs.summaries());
+		if let Some(version) = self.update.tick() {
+			let text = format!("EDMA {} is available, run `edma self-update`", version);
+			self.status.set_text(Span::raw(text));
 		}
 	}
 
+	/// Report a job after a tick in the status bar, finished and cancelled jobs show their
+	/// result in the editor
+	async fn handle_job_event(&mut self, event: JobEvent) {
+		let text = match event {
+			JobEvent::Progress(text) => text,
+			JobEvent::Finished(Job::Copy(copy)) => {
+				let column = column_label(copy.job.to());
+				let text = format!("Copied {} pairs into {}", copy.job.copied(), column);
+				self.editor.show_copied(&copy.target, copy.job.to()).await;
+				text
+			}
+			JobEvent::Finished(Job::Import(import)) => {
+				let text = format!(
+					"Imported {} pairs into {}, {} malformed records skipped",
+					import.job.total(),
+					column_label(import.job.cf()),
+					import.job.errors.len()
+				);
+				self.editor.show_imported(&import.name, &import.path, &import.job).await;
+				text
+			}
+			JobEvent::Finished(Job::Generate(generate)) => {
+				let column = column_label(generate.job.cf());
+				let text = format!("Generated {} pairs into {}", generate.job.total(), column);
+				self.editor.show_imported(&generate.name, &generate.path, &generate.job).await;
+				text
+			}
+			JobEvent::Finished(Job::Search(search)) => {
+				let text = format!(
+					"Found {} matches in {} pairs",
+					search.job.hits().len(),
+					search.job.scanned()
+				);
+				self.editor.show_search(&search.job);
+				text
+			}
+			// A cancelled search still shows the matches found so far
+			JobEvent::Cancelled(Job::Search(search)) => {
+				let text = format!(
+					"Search cancelled after {} pairs, {} matches",
+					search.job.scanned(),
+					search.job.hits().len()
+				);
+				self.editor.show_search(&search.job);
+				text
+			}
+			JobEvent::Finished(Job::SearchAll(search)) => {
+				let text = format!(
+					"Found {} matches in {} pairs of {} column families",
+					search.job.matches(),
+					search.job.scanned(),
+					search.job.jobs().len()
+				);
+				self.editor.show_global_search(&search.name, &search.path, &search.job);
+				text
+			}
+			JobEvent::Cancelled(Job::SearchAll(search)) => {
+				let text = format!(
+					"Search cancelled after {} pairs, {} matches",
+					search.job.scanned(),
+					search.job.matches()
+				);
+				self.editor.show_global_search(&search.name, &search.path, &search.job);
+				text
+			}
+			JobEvent::Finished(Job::SearchDatabases(search)) => {
+				let text = format!("Found {} matches: {}", search.matches(), search.progress());
+				self.editor.show_databases_search(&search);
+				text
+			}
+			// Databases still searched when cancelled keep the matches found so far
+			JobEvent::Cancelled(Job::SearchDatabases(search)) => {
+				let text = format!("Search cancelled: {}", search.progress());
+				self.editor.show_databases_search(&search);
+				text
+			}
+			JobEvent::Finished(Job::Diff(diff)) if diff.job.shared().is_some() => {
+				self.editor.show_keyspace_overlap(&diff)
+			}
+			JobEvent::Finished(Job::Diff(diff)) => {
+				let text = format!(
+					"Compared {} keys, {} differences",
+					diff.job.compared(),
+					diff.job.changes().len()
+				);
+				self.editor.show_keyspace_diff(&diff);
+				text
+			}
+			JobEvent::Finished(Job::Backup(backup)) => {
+				format!("Backup of {} written to {}", backup.db_path, backup.path)
+			}
+			JobEvent::Finished(Job::Count(count)) => {
+				format!("{} keys in {}", count.job.counted(), column_label(count.job.cf()))
+			}
+			JobEvent::Failed(job, err) => format!("{} failed: {}", job.label(), err),
+			JobEvent::Cancelled(job) => format!("{} cancelled: {}", job.label(), job.progress()),
+		};
+		self.status.set_text(Span::raw(text));
+	}
+
+	/// Exchange the components of the displayed workspace with those of the slot `index`
+	fn swap_workspace(&mut self, index: usize) {
+		let slot = &mut self.workspaces[index];
+		mem::swap(&mut self.databases, &mut slot.databases);
+		mem::swap(&mut self.explorer, &mut slot.explorer);
+		mem::swap(&mut self.editor, &mut slot.editor);
+		let command = self.command.text();
+		self.command.set_text(&slot.command);
+		slot.command = command;
+	}
+
+	/// Display the workspace `index`, the others keep their state untouched. Databases are
+	/// only opened by the commands of a workspace, an inactive workspace holds no file lock.
+	fn switch_workspace(&mut self, index: usize) {
+		if index == self.workspace {
+			return;
+		}
+		self.swap_workspace(self.workspace);
+		self.swap_workspace(index);
+		self.workspace = index;
+		// Stats and health are only refreshed on the displayed panels
+		self.explorer.set_stats(self.stats.stats().clone());
+		self.set_health();
+		let text = format!("Workspace {} of {}", index + 1, self.workspaces.len());
+		self.status.set_text(Span::raw(text));
+	}
+
+	fn open_workspace(&mut self) {
+		self.workspaces.push(Workspace::new(&self.config));
+		self.switch_workspace(self.workspaces.len() - 1);
+		self.focus = Focus::Explorer;
+	}
+
+	fn close_workspace(&mut self) {
+		let closed = self.workspace;
+		if self.workspaces.len() == 1 {
+			return self.status.set_text(Span::raw("The last workspace can't be closed"));
+		}
+		self.editor.stop_watch();
+		let next = if closed + 1 < self.workspaces.len() {
+			closed + 1
+		} else {
+			closed - 1
+		};
+		self.switch_workspace(next);
+		self.workspaces.remove(closed);
+		if next > closed {
+			self.workspace -= 1;
+		}
+	}
+
+	/// Name of the database selected in the workspace `index`
+	fn workspace_label(&self, index: usize) -> String {
+		let databases = match index == self.workspace {
+			true => &self.databases,
+			false => &self.workspaces[index].databases,
+		};
+		let selected = databases.state().selected().unwrap_or(0);
+		let name = self.config.databases.keys().nth(selected).cloned().unwrap_or_default();
+		format!("{} {}", index + 1, name)
+	}
+
 	fn get_selected_database(&self) -> String {
 		let database_index = self.databases.state().selected().unwrap();
 		let databases: Vec<_> = self.config.databases.keys().collect();
@@ -54,7 +367,7 @@ impl<'a> DatabaseTabComponent<'a> {
 	}
 
 	fn get_database_info(&self) -> (String, String, String) {
-		let selected_file = self.explorer.state().selected().unwrap_or(0);
+		let selected_file = self.explorer.selected().unwrap_or(0);
 		let selected_db = self.get_selected_database();
 		let databases = self.config.databases.get(&selected_db).unwrap();
 		let database = &databases[selected_file];
@@ -63,49 +376,951 @@ impl<'a> DatabaseTabComponent<'a> {
 		(name, path, abs_p)
 	}
 
+	/// Run the submitted command and add it to the session timeline
+	async fn run_command(&mut self) {
+		let (started_at, started, status) = (now_timestamp(), Instant::now(), self.status.text());
+		let command = self.command.text();
+		self.handle_command_event().await;
+		self.record_timeline(command, started_at, started, &status);
+	}
+
+	/// Add a command to the session timeline with the status it ended with, none when it left
+	/// the status unchanged
+	fn record_timeline(
+		&mut self,
+		command: String,
+		started_at: i64,
+		started: Instant,
+		status: &str,
+	) {
+		let (name, path, _) = self.get_database_info();
+		let outcome = Some(self.status.text()).filter(|text| text != status).unwrap_or_default();
+		self.timeline.push(TimelineEntry {
+			started_at,
+			duration: started.elapsed(),
+			database: format!("{}:{}", name, path),
+			command,
+			outcome,
+		});
+	}
+
 	async fn handle_command_event(&mut self) {
 		let commands = self.command.commands.to_vec();
+		// Any command stops the watched scan, WATCH starts a new one
+		self.editor.stop_watch();
 		let mut cf_handle = None;
-		let (name, path, _) = self.get_database_info();
-		for command in commands {
-			match command.token.as_str() {
+		let (mut name, mut path, _) = self.get_database_info();
+		// LIMIT and OFFSET window the scans of the command, alone they window the displayed one
+		let page = Page {
+			offset: commands
+				.iter()
+				.find_map(|c| match c {
+					Command::Offset(offset) => Some(*offset),
+					_ => None,
+				})
+				.unwrap_or(0),
+			limit: commands.iter().find_map(|c| match c {
+				Command::Limit(limit) => Some(*limit),
+				_ => None,
+			}),
+		};
+		self.editor.set_page(page);
+		self.editor.set_expired_filter(commands.iter().find_map(|c| match c {
+			Command::Expired(filter) => Some(*filter),
+			_ => None,
+		}));
+		let rescans =
+			|c: &Command| matches!(c, Command::Limit(_) | Command::Offset(_) | Command::Expired(_));
+		if commands.iter().all(rescans) {
+			self.editor.refresh_view().await;
+		}
+		for command in commands.iter() {
+			// Backends this build doesn't open, or without the feature, are reported instead
+			match Datastore::capabilities(&format!("{}:{}", name, path)) {
+				_ if matches!(command, Command::Db(_)) => {}
+				None => {
+					let text = format!("{} databases are not supported by this build", name);
+					self.status.set_text(Span::raw(text));
+					break;
+				}
+				Some(capabilities) if !is_supported(command, &capabilities) => {
+					let text =
+						format!("{} is not supported by {} databases", command.keyword(), name);
+					self.status.set_text(Span::raw(text));
+					break;
+				}
+				_ => {}
+			}
+			match command {
+				// DB routes the following tokens to another database
+				Command::Db(database) => match resolve_database(&self.config, database) {
+					Some((db_name, db_path)) => {
+						(name, path) = (db_name, db_path);
+						cf_handle = None;
+						self.editor.scan_database(None, &name, &path).await;
+					}
+					None => {
+						let text = format!("Unknown database {}", database);
+						self.status.set_text(Span::raw(text));
+						break;
+					}
+				},
 				// COLUMN is specified for RocksDB, Redb should be TABLE
-				"COLUMN" => {
-					let cf = Some(&command.value);
-					cf_handle = Some(cf.unwrap().as_bytes().to_vec());
+				Command::Column(cf) => {
+					cf_handle = Some(cf.as_bytes().to_vec());
 					self.editor.scan_database(cf_handle.clone(), &name, &path).await;
 				}
-				// PREFIX and SUFFIX scan only support key traversal not value traversal
-				"PREFIX" => {
-					let prefix = &command.value;
-					let bytes = prefix.as_bytes().to_vec();
+				// PREFIX and SUFFIX scan only support key traversal not value traversal, their
+				// byte literals are checked by the grammar
+				Command::Prefix(prefix) => {
+					let bytes = parse_byte_literal(prefix).unwrap_or_default();
 					self.editor.prefix_scan_database(cf_handle.clone(), &name, &path, bytes).await;
 				}
-				"SUFFIX" => {
-					let suffix = &command.value;
-					let bytes = suffix.as_bytes().to_vec();
+				Command::Suffix(suffix) => {
+					let bytes = parse_byte_literal(suffix).unwrap_or_default();
 					self.editor.suffix_scan_database(cf_handle.clone(), &name, &path, bytes).await;
 				}
+				// RANGE scans unless it is the target of DELETE
+				Command::Range(range) if !commands.contains(&Command::Delete) => {
+					self.editor.range_scan_database(cf_handle.clone(), &name, &path, range).await;
+				}
+				// KEY and RANGE are the targets of DELETE, the write waits for the prompt
+				Command::Delete => {
+					let key = commands.iter().find_map(|c| match c {
+						Command::Key(key) => Some(key),
+						_ => None,
+					});
+					let range = commands.iter().find_map(|c| match c {
+						Command::Range(range) => Some(range),
+						_ => None,
+					});
+					let deletion = match self.editor.deletion(key, range) {
+						Some(deletion) => deletion,
+						None => continue,
+					};
+					// A pending changeset queues the deletion without prompt, COMMIT writes it
+					if let Some(changeset) = self.changeset.as_mut() {
+						if (&changeset.name, &changeset.path) != (&name, &path) {
+							let text = "The changeset is pending on another database";
+							self.status.set_text(Span::raw(text));
+							break;
+						}
+						let cf = cf_handle.clone();
+						if self.editor.queue_deletion(&mut changeset.overlay, cf, &deletion) {
+							self.status
+								.set_text(Span::raw(format!("Queued delete of {}", deletion)));
+						}
+					} else {
+						let column = match &cf_handle {
+							Some(cf) => String::from_utf8_lossy(cf).to_string(),
+							None => "default".to_string(),
+						};
+						self.confirm.open(format!("Delete {} from {}?", deletion, column));
+						self.pending_delete = Some(PendingDelete {
+							cf: cf_handle.clone(),
+							name: name.clone(),
+							path: path.clone(),
+							deletion,
+						});
+						self.focus = Focus::Confirm;
+					}
+				}
+				// KEY and VALUE are the pair written by SET
+				Command::Set => {
+					let key = commands.iter().find_map(|c| match c {
+						Command::Key(key) => Some(key.clone()),
+						_ => None,
+					});
+					let value = commands.iter().find_map(|c| match c {
+						Command::Value(value) => Some(value.clone()),
+						_ => None,
+					});
+					// TTL makes the key expire after that many seconds
+					let ttl = commands.iter().find_map(|c| match c {
+						Command::Ttl(ttl) => Some(*ttl),
+						_ => None,
+					});
+					let (key, value) = (key.unwrap_or_default(), value.unwrap_or_default());
+					if let Some(changeset) = self.changeset.as_mut() {
+						if (&changeset.name, &changeset.path) != (&name, &path) {
+							let text = "The changeset is pending on another database";
+							self.status.set_text(Span::raw(text));
+							break;
+						}
+						if ttl.is_some() {
+							self.status.set_text(Span::raw("TTL is not queued in a changeset"));
+							break;
+						}
+						let (cf, db_path) = (cf_handle.clone(), format!("{}:{}", name, path));
+						let overlay = &mut changeset.overlay;
+						if self.editor.queue_set(overlay, cf, &db_path, &key, &value) {
+							self.status.set_text(Span::raw(format!("Queued set of {}", key)));
+						}
+					} else if self
+						.editor
+						.set_database(cf_handle.clone(), &name, &path, &key, &value, ttl)
+						.await
+					{
+						self.status.set_text(Span::raw(format!("Set {}", key)));
+					}
+				}
+				// BEGIN queues the following writes on the database until COMMIT or DISCARD
+				Command::Begin => {
+					let text = match &self.changeset {
+						Some(changeset) => format!(
+							"A changeset is already pending on {}:{}, COMMIT or DISCARD it first",
+							changeset.name, changeset.path
+						),
+						None => {
+							self.changeset = Some(Changeset {
+								name: name.clone(),
+								path: path.clone(),
+								overlay: Overlay::new(),
+							});
+							format!("Changeset started on {}:{}", name, path)
+						}
+					};
+					self.status.set_text(Span::raw(text));
+				}
+				Command::Commit => {
+					let text = match self.changeset.take() {
+						Some(mut changeset) => {
+							let changes = changeset.overlay.len();
+							let (name, path) = (changeset.name.clone(), changeset.path.clone());
+							if self
+								.editor
+								.commit_changeset(&name, &path, &mut changeset.overlay)
+								.await
+							{
+								format!("Committed {} changes", changes)
+							} else {
+								self.changeset = Some(changeset);
+								"Commit failed, the changeset is kept".to_string()
+							}
+						}
+						None => "No pending changeset, BEGIN starts one".to_string(),
+					};
+					self.status.set_text(Span::raw(text));
+				}
+				Command::Discard => {
+					let text = match self.changeset.take() {
+						Some(changeset) => format!("Discarded {} changes", changeset.overlay.len()),
+						None => "No pending changeset, BEGIN starts one".to_string(),
+					};
+					self.status.set_text(Span::raw(text));
+				}
+				Command::Order(order) => {
+					self.editor.set_order(*order).await;
+				}
+				Command::Sizes(scan) => {
+					self.editor.sizes_database(cf_handle.clone(), &name, &path, scan).await;
+				}
+				Command::Infer(target) => {
+					let save = commands.iter().find_map(|c| match c {
+						Command::Save(label) => Some(label.as_str()),
+						_ => None,
+					});
+					let cf = cf_handle.clone();
+					if self.editor.infer_layout_database(cf, &name, &path, target, save).await {
+						let text = format!(
+							"Template {} saved to templates.json, restart EDMA to select it",
+							save.unwrap_or_default()
+						);
+						self.status.set_text(Span::raw(text));
+					}
+				}
+				Command::Note(text) => {
+					let key = commands.iter().find_map(|c| match c {
+						Command::Key(key) => Some(key.clone()),
+						_ => None,
+					});
+					let cf = cf_handle.clone();
+					let annotated = match key {
+						Some(key) => self.editor.annotate_key(cf, &name, &path, &key, text),
+						None => None,
+					};
+					if let Some(text) = annotated {
+						self.status.set_text(Span::raw(text));
+					}
+				}
+				Command::Notes(term) => {
+					let found = self.editor.show_notes(term);
+					self.status.set_text(Span::raw(format!("{} notes found", found)));
+				}
+				Command::Timeline(None) => self.editor.show_timeline(&self.timeline),
+				Command::Timeline(Some(report)) => {
+					let text = match write_timeline(
+						Path::new(report),
+						&self.timeline,
+						&self.config.display,
+					) {
+						Ok(()) => format!(
+							"Session report of {} commands written to {}",
+							self.timeline.len(),
+							report
+						),
+						Err(err) => format!("Failed to write the session report: {}", err),
+					};
+					self.status.set_text(Span::raw(text));
+				}
+				Command::Duplicates(target) => {
+					self.editor.duplicates_database(cf_handle.clone(), &name, &path, target).await;
+				}
+				Command::Age(segment) => {
+					self.editor.age_database(cf_handle.clone(), &name, &path, segment).await;
+				}
+				Command::Purge {
+					segment,
+					days,
+				} => {
+					let deleted = self
+						.editor
+						.purge_database(cf_handle.clone(), &name, &path, segment, *days)
+						.await;
+					self.status.set_text(Span::raw(format!("Deleted {} keys", deleted)));
+				}
+				// BOOKMARK saves the other tokens with the database and column family they run
+				// against
+				Command::Bookmark(label) => {
+					let kept = |token: &String| {
+						let keyword = token.split('=').next().unwrap_or_default();
+						!["BOOKMARK", "DB", "COLUMN", "TABLE", "TREE"].contains(&keyword)
+					};
+					let tokens: Vec<_> =
+						split_quoted(&self.command.text()).into_iter().filter(kept).collect();
+					let column = commands.iter().rev().find_map(|c| match c {
+						Command::Column(cf) => Some(cf.clone()),
+						_ => None,
+					});
+					let bookmark = Bookmark {
+						name: label.clone(),
+						database: format!("{}:{}", name, path),
+						column,
+						command: tokens.join(" "),
+					};
+					let text = match save_bookmark(&bookmarks_path(&self.config.path), bookmark) {
+						Ok(_) => format!("Bookmarked {}", label),
+						Err(err) => format!("Failed to save bookmark: {}", err),
+					};
+					self.status.set_text(Span::raw(text));
+				}
+				Command::Retention(apply) => {
+					let expired = self.editor.retention_database(*apply).await;
+					let status = match apply {
+						true => format!("Retention deleted {} keys", expired),
+						false => format!("Retention expires {} keys", expired),
+					};
+					self.status.set_text(Span::raw(status));
+				}
+				Command::Expire(seconds) => {
+					let expiring =
+						self.editor.expire_view(cf_handle.clone(), &name, &path, *seconds).await;
+					self.status.set_text(Span::raw(format!(
+						"{} keys expire in {} seconds",
+						expiring, seconds
+					)));
+				}
+				Command::Snapshot(label) => {
+					let captured =
+						self.editor.snapshot_database(cf_handle.clone(), &name, &path, label).await;
+					self.status.set_text(Span::raw(format!(
+						"Snapshot {} taken ({} pairs)",
+						label, captured
+					)));
+				}
+				// SNAPSHOT CREATE copies every column family, DIFF compares the selected one
+				Command::PointInTime(action) => {
+					let text = self
+						.editor
+						.point_in_time_database(cf_handle.clone(), &name, &path, action)
+						.await;
+					// Snapshots of backends without one copy the column families one by one
+					let consistent = Datastore::capabilities(&format!("{}:{}", name, path))
+						.map_or(true, |capabilities| capabilities.snapshots);
+					if let Some(text) = text {
+						let text = match (consistent, action) {
+							(false, SnapshotAction::Create(_)) => {
+								format!("{}, {} copies its column families one by one", text, name)
+							}
+							_ => text,
+						};
+						self.status.set_text(Span::raw(text));
+					}
+				}
+				Command::Diff(label) => {
+					self.editor.diff_database(label).await;
+				}
+				Command::Internals => {
+					self.editor.internals_database(&name, &path);
+				}
+				Command::Metrics => {
+					self.editor.metrics_database(&name, &path);
+				}
+				Command::Export {
+					path,
+					format,
+					order,
+				} => {
+					if let Some(rows) = self.editor.export_view(path, *format, *order) {
+						self.status
+							.set_text(Span::raw(format!("Exported {} rows to {}", rows, path)));
+					}
+				}
+				// IMPORT writes in the background into the selected column family
+				Command::Import {
+					path: source,
+					format,
+				} => {
+					if let Some(job) = self.editor.import_file(cf_handle.clone(), source, *format) {
+						self.jobs.spawn(Job::Import(BackgroundImport {
+							name: name.clone(),
+							path: path.clone(),
+							job,
+						}));
+					}
+				}
+				// SEED makes the generated pairs reproducible
+				Command::Generate(count) => {
+					let seed = commands.iter().find_map(|c| match c {
+						Command::Seed(seed) => Some(*seed),
+						_ => None,
+					});
+					if let Some(job) = self.editor.generate_job(cf_handle.clone(), *count, seed) {
+						self.jobs.spawn(Job::Generate(BackgroundImport {
+							name: name.clone(),
+							path: path.clone(),
+							job,
+						}));
+					}
+				}
+				// EXACT scans the whole keyspace, it runs in the background
+				Command::Count(CountMode::Exact) => {
+					self.jobs.spawn(Job::Count(BackgroundCount {
+						name: name.clone(),
+						path: path.clone(),
+						job: CountJob::new(cf_handle.clone()),
+					}));
+				}
+				Command::Count(mode) => {
+					let count =
+						self.editor.count_database(cf_handle.clone(), &name, &path, mode).await;
+					if let Some(text) = count {
+						self.status.set_text(Span::raw(text));
+					}
+				}
+				// WITH, SEGMENT and CONFIRM are options of REWRITE
+				Command::Rewrite(pattern) => {
+					let replacement = commands
+						.iter()
+						.find_map(|c| match c {
+							Command::With(replacement) => Some(replacement.clone()),
+							_ => None,
+						})
+						.unwrap_or_default();
+					let segment = commands.iter().find_map(|c| match c {
+						Command::Segment(segment) => Some(segment.clone()),
+						_ => None,
+					});
+					let confirm = commands.contains(&Command::Confirm);
+					let rewritten = self
+						.editor
+						.rewrite_database(
+							cf_handle.clone(),
+							&name,
+							&path,
+							pattern,
+							&replacement,
+							segment.as_deref(),
+							confirm,
+						)
+						.await;
+					if confirm {
+						self.status.set_text(Span::raw(format!("Rewrote {} keys", rewritten)));
+					}
+				}
+				// COPY runs in the background from the selected or SOURCE database into the
+				// INTO or TARGET database, DIFF and OVERLAP compare the same two keyspaces
+				Command::Copy | Command::DiffKeyspaces | Command::Overlap => {
+					let source = commands.iter().find_map(|c| match c {
+						Command::Source(db) => Some(db),
+						_ => None,
+					});
+					let target = commands.iter().find_map(|c| match c {
+						Command::Target(db) | Command::Into(db) => Some(db),
+						_ => None,
+					});
+					let resolve = |database: Option<&String>| match database {
+						Some(database) => resolve_database(&self.config, database)
+							.map(|(name, path)| format!("{}:{}", name, path)),
+						None => Some(format!("{}:{}", name, path)),
+					};
+					let (source, target) = match (resolve(source), resolve(target)) {
+						(Some(source), Some(target)) => (source, target),
+						_ => {
+							let text = "Unknown SOURCE or TARGET database";
+							self.status.set_text(Span::raw(text));
+							break;
+						}
+					};
+					let from = commands
+						.iter()
+						.find_map(|c| match c {
+							Command::Column(cf) => Some(Some(cf.as_bytes().to_vec())),
+							_ => None,
+						})
+						.unwrap_or_else(|| cf_handle.clone());
+					let to = commands
+						.iter()
+						.find_map(|c| match c {
+							Command::To(column) => Some(Some(column.as_bytes().to_vec())),
+							_ => None,
+						})
+						.unwrap_or_else(|| from.clone());
+					let prefix = commands
+						.iter()
+						.find_map(|c| match c {
+							Command::Prefix(prefix) => parse_byte_literal(prefix).ok(),
+							_ => None,
+						})
+						.unwrap_or_default();
+					if let Command::DiffKeyspaces = command {
+						self.jobs.spawn(Job::Diff(BackgroundDiff {
+							left: source,
+							right: target,
+							job: KeyspaceDiff::new(from, to, &prefix),
+						}));
+					} else if let Command::Overlap = command {
+						self.jobs.spawn(Job::Diff(BackgroundDiff {
+							left: source,
+							right: target,
+							job: KeyspaceDiff::new(from, to, &prefix).overlap(),
+						}));
+					} else {
+						self.jobs.spawn(Job::Copy(BackgroundCopy {
+							source,
+							target,
+							job: CopyJob::new(from, to, &prefix),
+						}));
+					}
+				}
+				// SEARCH scans the selected or COLUMN column family in the background, SEARCH=ALL
+				// every column family of the database
+				Command::Search | Command::SearchAll | Command::SearchDatabases => {
+					let term = commands
+						.iter()
+						.find_map(|c| match c {
+							Command::Term(term) => Some(term.clone()),
+							_ => None,
+						})
+						.unwrap_or_default();
+					let scope = commands
+						.iter()
+						.find_map(|c| match c {
+							Command::In(scope) => Some(*scope),
+							_ => None,
+						})
+						.unwrap_or(SearchScope::Both);
+					let cf = commands
+						.iter()
+						.find_map(|c| match c {
+							Command::Column(cf) => Some(Some(cf.as_bytes().to_vec())),
+							_ => None,
+						})
+						.unwrap_or_else(|| cf_handle.clone());
+					if let Command::SearchDatabases = command {
+						let job = self.editor.databases_search_job(&term, scope);
+						self.jobs.spawn(Job::SearchDatabases(job));
+					} else if let Command::Search = command {
+						let job =
+							self.editor.search_job(&format!("{}:{}", name, path), cf, &term, scope);
+						self.jobs.spawn(Job::Search(BackgroundSearch {
+							name: name.clone(),
+							path: path.clone(),
+							job,
+						}));
+					} else if let Some(job) =
+						self.editor.global_search_job(&name, &path, &term, scope)
+					{
+						self.jobs.spawn(Job::SearchAll(BackgroundGlobalSearch {
+							name: name.clone(),
+							path: path.clone(),
+							job,
+						}));
+					}
+				}
+				Command::Where(expression) => {
+					self.editor.filter_database(cf_handle.clone(), &name, &path, expression).await;
+					let save = commands.iter().find_map(|c| match c {
+						Command::Save(label) => Some(label),
+						_ => None,
+					});
+					if let Some(label) = save {
+						if self.editor.save_filter(label, expression) {
+							self.status.set_text(Span::raw(format!("Filter {} saved", label)));
+						}
+					}
+				}
+				Command::Filter(label) => match self.editor.saved_filter(label) {
+					Some(expression) => {
+						self.editor
+							.filter_database(cf_handle.clone(), &name, &path, &expression)
+							.await;
+					}
+					None => {
+						let text = format!("No filter named {}", label);
+						self.status.set_text(Span::raw(text));
+					}
+				},
+				// ON is an option of JOIN
+				Command::Join(right) => {
+					let segment = commands.iter().find_map(|c| match c {
+						Command::On(segment) => Some(segment.clone()),
+						_ => None,
+					});
+					self.editor
+						.join_database(
+							cf_handle.clone(),
+							&name,
+							&path,
+							right,
+							&segment.unwrap_or_default(),
+						)
+						.await;
+				}
+				// CONTAINS is an option of DEFINE
+				Command::Define(label) => {
+					let prefix = commands.iter().find_map(|c| match c {
+						Command::Prefix(prefix) => Some(prefix.clone()),
+						_ => None,
+					});
+					let contains = commands.iter().find_map(|c| match c {
+						Command::Contains(contains) => Some(contains.clone()),
+						_ => None,
+					});
+					if self.editor.define_view(
+						cf_handle.clone(),
+						&name,
+						&path,
+						label,
+						prefix,
+						contains,
+					) {
+						self.status.set_text(Span::raw(format!("View {} saved", label)));
+					}
+				}
+				Command::View(label) => {
+					self.editor.browse_view(label).await;
+				}
+				// BY is an option of HITTERS
+				Command::Hitters(selector) => {
+					let weight = commands
+						.iter()
+						.find_map(|c| match c {
+							Command::By(weight) => Some(weight.clone()),
+							_ => None,
+						})
+						.unwrap_or(Weight::Keys);
+					self.editor
+						.heavy_hitters_database(cf_handle.clone(), &name, &path, selector, &weight)
+						.await;
+				}
+				Command::Histogram(selector) => {
+					self.editor.histogram_database(cf_handle.clone(), &name, &path, selector).await;
+				}
+				// Dropping a column family deletes its keys, it waits for the prompt
+				Command::Cf(CfAction::Drop(cf)) => {
+					self.confirm.open(format!("Drop column family {} and its keys?", cf));
+					self.pending_drop = Some(PendingDrop {
+						name: name.clone(),
+						path: path.clone(),
+						cf: cf.clone(),
+					});
+					self.focus = Focus::Confirm;
+				}
+				Command::Backup(backup) => {
+					self.jobs.spawn(Job::Backup(BackgroundBackup::new(
+						&format!("{}:{}", name, path),
+						backup,
+					)));
+				}
+				// Restoring replaces the database, it can't run under a job or a changeset
+				Command::Restore(backup) => {
+					if !self.jobs.is_empty() || self.changeset.is_some() {
+						let text = "Wait for the jobs and COMMIT or DISCARD the changeset first";
+						self.status.set_text(Span::raw(text));
+						break;
+					}
+					self.confirm.open(format!(
+						"Restore {}:{} from {}? Its current keys are replaced",
+						name, path, backup
+					));
+					self.pending_restore = Some(PendingRestore {
+						name: name.clone(),
+						path: path.clone(),
+						backup: backup.clone(),
+					});
+					self.focus = Focus::Confirm;
+				}
+				Command::Cf(action) => {
+					if let Some(text) = self.editor.column_families_database(&name, &path, action) {
+						self.status.set_text(Span::raw(text));
+					}
+				}
 				_ => {}
 			}
 		}
+		if !commands.is_empty() {
+			if let Err(err) = self.command.remember(&format!("{}:{}", name, path)) {
+				self.status.set_text(Span::raw(format!("Failed to save history: {}", err)));
+			}
+		}
+		self.update_changeset_panel();
+		// WATCH repeats the scan displayed by the other tokens
+		let watch = commands.iter().find_map(|c| match c {
+			Command::Watch(interval) => Some(*interval),
+			_ => None,
+		});
+		if let Some(interval) = watch {
+			let text = match self.editor.watch_view(interval) {
+				true => format!("Watching every {:?}, run another command to stop", interval),
+				false => "WATCH needs a displayed scan".to_string(),
+			};
+			self.status.set_text(Span::raw(text));
+		}
 		self.command.reset_command();
 	}
 
+	/// List the queued writes of the pending changeset in its panel
+	fn update_changeset_panel(&mut self) {
+		let (database, changes) = match &self.changeset {
+			Some(changeset) => {
+				let overlay = &changeset.overlay;
+				let changes = overlay
+					.column_families()
+					.into_iter()
+					.flat_map(|cf| {
+						overlay.changes(&cf).into_iter().map(move |(k, v)| (cf.clone(), k, v))
+					})
+					.collect();
+				(Some(format!("{}:{}", changeset.name, changeset.path)), changes)
+			}
+			None => (None, vec![]),
+		};
+		self.changeset_panel.set_changeset(database, changes);
+	}
+
+	async fn handle_confirm(&mut self, confirmed: bool) {
+		if let Some(pending) = self.pending_restore.take() {
+			if !confirmed {
+				self.status.set_text(Span::raw("Restore cancelled"));
+				return;
+			}
+			let db_path = format!("{}:{}", pending.name, pending.path);
+			let written = AtomicU64::new(0);
+			let text = match Datastore::restore(&db_path, &pending.backup, &written) {
+				Ok(()) => {
+					let bytes = written.load(Ordering::Relaxed) as usize;
+					format!("Restored {} from {}", format_bytes(bytes), pending.backup)
+				}
+				Err(err) => format!("Restore failed: {}", err),
+			};
+			self.editor.scan_database(None, &pending.name, &pending.path).await;
+			self.status.set_text(Span::raw(text));
+			return;
+		}
+		if let Some(pending) = self.pending_drop.take() {
+			let text = match confirmed {
+				true => {
+					let action = CfAction::Drop(pending.cf);
+					self.editor.column_families_database(&pending.name, &pending.path, &action)
+				}
+				false => Some("Drop cancelled".to_string()),
+			};
+			if let Some(text) = text {
+				self.status.set_text(Span::raw(text));
+			}
+			return;
+		}
+		let pending = match self.pending_delete.take() {
+			Some(pending) => pending,
+			None => return,
+		};
+		if !confirmed {
+			self.status.set_text(Span::raw("Delete cancelled"));
+			return;
+		}
+		let PendingDelete {
+			cf,
+			name,
+			path,
+			deletion,
+		} = pending;
+		let deleted = self.editor.delete_database(cf, &name, &path, &deletion).await;
+		self.status.set_text(Span::raw(format!("Deleted {} keys", deleted)));
+	}
+
+	/// Run the command of a bookmark picked in the Bookmarks tab
+	pub async fn open_bookmark(&mut self, bookmark: &Bookmark) {
+		self.focus = Focus::Command;
+		if self.command.submit(&bookmark.command_text()) {
+			self.run_command().await;
+		}
+	}
+
 	async fn handle_explorer_event(&mut self) {
-		if self.explorer.state().selected().is_some() {
+		self.editor.stop_watch();
+		if let Some((index, item)) = self.explorer.selection() {
 			let (name, path, abs_p) = self.get_database_info();
-			self.status.set_text(Span::raw(abs_p));
-			self.editor.scan_database(None, &name, &path).await;
+			let db_path = format!("{}:{}", name, path);
+			if Datastore::capabilities(&db_path).is_none() {
+				let text = format!("{} databases are not supported by this build", name);
+				return self.status.set_text(Span::raw(text));
+			}
+			let failure = match self.health.health().get(&db_path) {
+				Some(DatabaseHealth::Failed(err)) => Some(err.clone()),
+				Some(DatabaseHealth::Locked) => {
+					Some("Database is locked by another process".to_string())
+				}
+				_ => None,
+			};
+			if let Some(err) = failure {
+				self.editor.set_err(err);
+				let text = format!("{} failed to open, press ENTER to retry", abs_p);
+				return self.status.set_text(Span::raw(text));
+			}
+			self.editor.set_page(Page::default());
+			let database = &self.config.databases[&name][index];
+			match item {
+				Some(ExplorerItem::Partition(p)) => {
+					let partition = database.partitions[p].clone();
+					self.status.set_text(Span::raw(format!("{} {}", abs_p, partition.name)));
+					self.editor.partition_scan_database(&name, &path, &partition).await;
+				}
+				Some(ExplorerItem::Column(c)) => {
+					let column = database.columns[c].clone();
+					let text = match &column.description {
+						Some(description) => format!("{} {}: {}", abs_p, column.name, description),
+						None => format!("{} {}", abs_p, column.name),
+					};
+					self.status.set_text(Span::raw(text));
+					let cf = Some(column.name.into_bytes());
+					self.editor.scan_database(cf, &name, &path).await;
+				}
+				None => {
+					self.status.set_text(Span::raw(abs_p));
+					self.editor.scan_database(None, &name, &path).await;
+				}
+			}
 		} else {
 			self.status.reset();
 		}
 	}
 
+	/// Check the selected database again and scan it once it opens
+	async fn retry_database(&mut self) {
+		let (name, path, _) = self.get_database_info();
+		let healthy = !matches!(
+			self.health.recheck(&format!("{}:{}", name, path)),
+			DatabaseHealth::Failed(_) | DatabaseHealth::Locked
+		);
+		self.set_health();
+		if healthy {
+			self.editor.clear_err();
+		}
+		self.handle_explorer_event().await;
+	}
+
 	pub async fn event(&mut self, key: Key) -> Result<EventState> {
+		// The most recent job is cancelled from any panel but the modal prompt
+		if key == self.config.key_config.cancel_job
+			&& !self.jobs.is_empty()
+			&& !matches!(self.focus, Focus::Confirm | Focus::History)
+		{
+			if let Some(event) = self.jobs.cancel_last() {
+				self.handle_job_event(event).await;
+				self.jobs_panel.set_jobs(self.jobs.summaries());
+			}
+			return Ok(EventState::Consumed);
+		}
+		// Workspace tabs are switched from any panel but the modal prompts
+		let keys = &self.config.key_config;
+		let workspace_keys = [keys.new_workspace, keys.next_workspace, keys.close_workspace];
+		if workspace_keys.contains(&key) && !matches!(self.focus, Focus::Confirm | Focus::History) {
+			match workspace_keys.iter().position(|k| *k == key) {
+				Some(0) => self.open_workspace(),
+				Some(1) => self.switch_workspace((self.workspace + 1) % self.workspaces.len()),
+				_ => self.close_workspace(),
+			}
+			return Ok(EventState::Consumed);
+		}
+		// Timestamps and durations are re-rendered by every table on the next draw
+		if key == self.config.key_config.relative_time
+			&& !matches!(self.focus, Focus::Confirm | Focus::History)
+		{
+			set_relative_time(!relative_time());
+			let text = match relative_time() {
+				true => "Timestamps and durations shown relative to now",
+				false => "Timestamps shown with the date format",
+			};
+			self.status.set_text(Span::raw(text));
+			self.editor.rebuild_table();
+			return Ok(EventState::Consumed);
+		}
+		// The sidebar is an overlay in narrow terminals, shown while the explorer is focused
+		if key == self.config.key_config.toggle_sidebar
+			&& !matches!(self.focus, Focus::Confirm | Focus::History)
+		{
+			self.focus = match self.focus {
+				Focus::Explorer => Focus::Command,
+				_ => Focus::Explorer,
+			};
+			return Ok(EventState::Consumed);
+		}
 		match self.focus {
+			// The prompt is modal, every key is consumed until it is answered
+			Focus::Confirm => {
+				self.confirm.event(key).await?;
+				if let Some(confirmed) = self.confirm.take_answer() {
+					let (started_at, started) = (now_timestamp(), Instant::now());
+					let status = self.status.text();
+					self.handle_confirm(confirmed).await;
+					let answer = if confirmed {
+						"CONFIRM"
+					} else {
+						"CANCEL"
+					};
+					self.record_timeline(answer.to_string(), started_at, started, &status);
+					self.focus = Focus::Command;
+				}
+				Ok(EventState::Consumed)
+			}
+			// The popup is modal like the prompt, the picked command is written in the editor
+			Focus::History => {
+				self.history.event(key).await?;
+				if !self.history.is_open() {
+					if let Some(command) = self.history.take_picked() {
+						self.command.set_text(&command);
+					}
+					self.focus = Focus::Command;
+				}
+				Ok(EventState::Consumed)
+			}
+			// Keys typed in the explorer filter don't move the focus
+			Focus::Explorer if self.explorer.is_searching() => {
+				let selected = self.explorer.selection();
+				let state = self.explorer.event(key).await?;
+				if self.explorer.selection() != selected || selected.is_none() {
+					self.handle_explorer_event().await;
+				}
+				Ok(state)
+			}
 			Focus::Explorer => {
-				if key == Key::Right {
+				if key == self.config.key_config.right {
+					self.focus = Focus::Command;
+					return Ok(EventState::Consumed);
+				}
+				if key == self.config.key_config.command_mode {
+					self.command.set_text("");
 					self.focus = Focus::Command;
 					return Ok(EventState::Consumed);
 				}
@@ -116,22 +1331,35 @@ impl<'a> DatabaseTabComponent<'a> {
 					self.explorer.set_database(db);
 					return Ok(EventState::Consumed);
 				}
+				if key == self.config.key_config.enter && self.explorer.selection().is_some() {
+					self.retry_database().await;
+					return Ok(EventState::Consumed);
+				}
+				// Typing in the explorer filter only rescans when the selection changes
+				let selected = self.explorer.selection();
 				if self.explorer.event(key).await?.is_consumed() {
-					self.handle_explorer_event().await;
+					if self.explorer.selection() != selected || selected.is_none() {
+						self.handle_explorer_event().await;
+					}
 					return Ok(EventState::Consumed);
 				}
 				Ok(EventState::NotConsumed)
 			}
 			Focus::Command => {
+				if key == self.config.key_config.history_search {
+					self.history.open(self.command.history().to_vec());
+					self.focus = Focus::History;
+					return Ok(EventState::Consumed);
+				}
 				if self.command.event(key).await?.is_consumed() {
-					self.handle_command_event().await;
+					self.run_command().await;
 					return Ok(EventState::Consumed);
 				}
-				if key == Key::Left {
+				if key == self.config.key_config.left {
 					self.focus = Focus::Explorer;
 					return Ok(EventState::Consumed);
 				}
-				if key == Key::Down {
+				if key == self.config.key_config.down {
 					self.focus = Focus::Editor;
 					return Ok(EventState::Consumed);
 				}
@@ -139,13 +1367,22 @@ impl<'a> DatabaseTabComponent<'a> {
 			}
 			Focus::Editor => {
 				if self.editor.event(key).await?.is_consumed() {
+					if let Some(command) = self.editor.take_generated_command() {
+						self.command.set_text(&command);
+						self.focus = Focus::Command;
+					}
 					return Ok(EventState::Consumed);
 				}
-				if key == Key::Left {
+				if key == self.config.key_config.command_mode {
+					self.command.set_text("");
+					self.focus = Focus::Command;
+					return Ok(EventState::Consumed);
+				}
+				if key == self.config.key_config.left {
 					self.focus = Focus::Explorer;
 					return Ok(EventState::Consumed);
 				}
-				if key == Key::Up {
+				if key == self.config.key_config.up {
 					self.focus = Focus::Command;
 					return Ok(EventState::Consumed);
 				}
@@ -162,35 +1399,45 @@ impl<'a> RenderAbleComponent for DatabaseTabComponent<'a> {
 		rect: Rect,
 		focused: bool,
 	) -> Result<(), anyhow::Error> {
-		let main_chunks = Layout::default()
-			.direction(Direction::Horizontal)
-			.constraints([Constraint::Percentage(20), Constraint::Percentage(80)])
-			.split(rect);
-
+		let (sidebar, main, overlay) = sidebar_layout(rect);
+		// Workspace tabs are shown above the command once a second workspace is opened
+		let main = match self.workspaces.len() > 1 {
+			true => {
+				let chunks = Layout::default()
+					.direction(Direction::Vertical)
+					.constraints([Constraint::Length(1), Constraint::Min(0)])
+					.split(main);
+				let titles = (0..self.workspaces.len())
+					.map(|index| Spans::from(self.workspace_label(index)))
+					.collect();
+				let tabs = Tabs::new(titles)
+					.select(self.workspace)
+					.highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+					.divider(Span::raw("|"));
+				f.render_widget(tabs, chunks[0]);
+				chunks[1]
+			}
+			false => main,
+		};
 		let left_stack_chunks = Layout::default()
 			.direction(Direction::Vertical)
 			.constraints([Constraint::Percentage(30), Constraint::Percentage(70)])
-			.split(main_chunks[0]);
+			.split(sidebar);
+
+		let right_stack_chunks =
+			Layout::default()
+				.direction(Direction::Vertical)
+				.constraints([
+					Constraint::Length(3),
+					Constraint::Length(main.height.saturating_sub(
+						6 + self.jobs_panel.height() + self.changeset_panel.height(),
+					)),
+					Constraint::Length(self.changeset_panel.height()),
+					Constraint::Length(self.jobs_panel.height()),
+					Constraint::Length(2),
+				])
+				.split(main);
 
-		let right_stack_chunks = Layout::default()
-			.direction(Direction::Vertical)
-			.constraints([
-				Constraint::Length(3),
-				Constraint::Length(main_chunks[0].height.saturating_sub(6)),
-				Constraint::Length(2),
-			])
-			.split(main_chunks[1]);
-
-		self.databases.render(
-			f,
-			left_stack_chunks[0],
-			focused && matches!(self.focus, Focus::Explorer),
-		)?;
-		self.explorer.render(
-			f,
-			left_stack_chunks[1],
-			focused && matches!(self.focus, Focus::Explorer),
-		)?;
 		self.command.render(
 			f,
 			right_stack_chunks[0],
@@ -201,7 +1448,24 @@ impl<'a> RenderAbleComponent for DatabaseTabComponent<'a> {
 			right_stack_chunks[1],
 			focused && matches!(self.focus, Focus::Editor),
 		)?;
-		self.status.render(f, right_stack_chunks[2], false)?;
+		if self.changeset_panel.height() > 0 {
+			self.changeset_panel.render(f, right_stack_chunks[2], false)?;
+		}
+		if self.jobs_panel.height() > 0 {
+			self.jobs_panel.render(f, right_stack_chunks[3], false)?;
+		}
+		self.status.render(f, right_stack_chunks[4], false)?;
+		// The overlay of narrow terminals is drawn over the other panels
+		let explorer_focused = matches!(self.focus, Focus::Explorer);
+		if !overlay || explorer_focused {
+			if overlay {
+				f.render_widget(Clear, sidebar);
+			}
+			self.databases.render(f, left_stack_chunks[0], focused && explorer_focused)?;
+			self.explorer.render(f, left_stack_chunks[1], focused && explorer_focused)?;
+		}
+		self.confirm.render(f, main, focused && matches!(self.focus, Focus::Confirm))?;
+		self.history.render(f, main, focused && matches!(self.focus, Focus::History))?;
 
 		Ok(())
 	}
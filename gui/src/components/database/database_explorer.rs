@@ -3,14 +3,15 @@ use crate::{
 	config::Config,
 	constants::HIGHLIGHT_COLOR,
 	events::{EventState, Key},
-	ui::StatefulList,
-	utils::get_absolute_path,
+	ui::{selection_symbol, StatefulList},
+	utils::{format_byte_literal, format_bytes, get_absolute_path, DatabaseHealth, DatabaseStats},
 };
 use anyhow::Result;
+use std::collections::HashMap;
 use tui::{
 	backend::Backend,
 	layout::Rect,
-	style::{Modifier, Style},
+	style::{Color, Modifier, Style},
 	text::{Span, Spans},
 	widgets::{List, ListItem, ListState},
 	Frame,
@@ -24,70 +25,251 @@ enum Focus {
 pub struct DatabaseExplorerComponent<'a> {
 	config: Config,
 	pub list: StatefulList<'a>,
+	database: String,
+	stats: HashMap<String, DatabaseStats>,
+	health: HashMap<String, DatabaseHealth>,
+	/// Type-ahead filter of the list
+	query: String,
+	/// Typed characters go to the filter, started by the search key or a character which
+	/// isn't bound
+	searching: bool,
+	/// Config index of the database of each displayed item, with the partition or column
+	/// family listed under it
+	visible: Vec<(usize, Option<ExplorerItem>)>,
 	focus: Focus,
 }
 
-fn build_list(config: Config, database: String) -> StatefulList<'static> {
+/// Item listed under a database, by index in its config
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExplorerItem {
+	Partition(usize),
+	Column(usize),
+}
+
+/// Build the list of databases matching `query` by path, alias, partition name or the name,
+/// description or tags of a documented column family, case insensitive, each followed by its
+/// partitions and column families. Returns the list with the config indexes of each item.
+fn build_list(
+	config: Config,
+	database: String,
+	stats: &HashMap<String, DatabaseStats>,
+	health: &HashMap<String, DatabaseHealth>,
+	query: &str,
+) -> (StatefulList<'static>, Vec<(usize, Option<ExplorerItem>)>) {
 	let databases: Vec<_> = config.databases.get(&database).unwrap().to_vec();
-	let items: Vec<_> = databases
+	let query = query.to_lowercase();
+	let visible: Vec<_> = databases
 		.iter()
-		.map(|database| {
-			ListItem::new(Spans::from(vec![Span::styled(
-				get_absolute_path(&database.path.clone()),
-				Style::default(),
-			)]))
+		.enumerate()
+		.filter(|(_, db)| {
+			let alias = db.alias.clone().unwrap_or_default();
+			db.path.to_lowercase().contains(&query)
+				|| alias.to_lowercase().contains(&query)
+				|| db.partitions.iter().any(|p| p.name.to_lowercase().contains(&query))
+				|| db.columns.iter().any(|c| c.matches(&query))
+		})
+		.flat_map(|(index, db)| {
+			let partitions = (0..db.partitions.len()).map(ExplorerItem::Partition);
+			let columns = (0..db.columns.len()).map(ExplorerItem::Column);
+			let items = partitions.chain(columns).map(move |item| (index, Some(item)));
+			std::iter::once((index, None)).chain(items)
+		})
+		.collect();
+	let items: Vec<_> = visible
+		.iter()
+		.map(|(index, item)| {
+			let db = &databases[*index];
+			// Memory databases are named, their path is not on disk
+			let path = match database.as_str() {
+				"memory" => format!("memory:{}", db.path),
+				_ => get_absolute_path(&db.path),
+			};
+			// Databases of the discovery roots are told apart from the configured ones
+			let path = match db.discovered {
+				true => format!("{} [discovered]", path),
+				false => path,
+			};
+			// Databases which fail to open are marked, selecting them shows the error
+			let (path, style) = match health.get(&format!("{}:{}", database, db.path)) {
+				Some(DatabaseHealth::Failed(_)) => {
+					(format!("{} [error]", path), Style::default().fg(Color::Red))
+				}
+				Some(DatabaseHealth::Locked) => {
+					(format!("{} [locked]", path), Style::default().fg(Color::Yellow))
+				}
+				_ => (path, Style::default()),
+			};
+			let label = match item {
+				Some(ExplorerItem::Partition(p)) => {
+					let partition = &db.partitions[*p];
+					format!("  {} [{}]", partition.name, format_byte_literal(&partition.prefix))
+				}
+				// Column families are told apart from partitions by their tags and description
+				Some(ExplorerItem::Column(c)) => {
+					let column = &db.columns[*c];
+					let mut label = format!("  {} (column)", column.name);
+					if let Some(description) = &column.description {
+						label = format!("{} {}", label, description);
+					}
+					for tag in column.tags.iter() {
+						label = format!("{} #{}", label, tag);
+					}
+					label
+				}
+				None => match stats.get(&format!("{}:{}", database, db.path)) {
+					Some(s) => format!("{} ({} keys, {})", path, s.keys, format_bytes(s.size)),
+					None => path,
+				},
+			};
+			ListItem::new(Spans::from(vec![Span::styled(label, style)]))
 		})
 		.collect();
 
-	let mut list_state = ListState::default();
-	list_state.select(Some(0));
-	StatefulList::with_items(items, None)
+	(StatefulList::with_items(items, None), visible)
 }
 
 impl<'a> DatabaseExplorerComponent<'a> {
-	pub fn state(&self) -> ListState {
-		self.list.state.clone()
+	/// Config index of the selected database, or of the database of the selected item
+	pub fn selected(&self) -> Option<usize> {
+		self.selection().map(|(database, _)| database)
+	}
+
+	/// Config indexes of the selected database and of the item selected under it
+	pub fn selection(&self) -> Option<(usize, Option<ExplorerItem>)> {
+		self.list.state.selected().and_then(|index| self.visible.get(index).copied())
 	}
 
 	pub fn set_database(&mut self, database: String) {
-		self.list = build_list(self.config.clone(), database);
+		self.query.clear();
+		self.searching = false;
+		(self.list, self.visible) = build_list(
+			self.config.clone(),
+			database.clone(),
+			&self.stats,
+			&self.health,
+			&self.query,
+		);
+		self.database = database;
+	}
+
+	/// Update the stats displayed next to each database path, keeping the selection
+	pub fn set_stats(&mut self, stats: HashMap<String, DatabaseStats>) {
+		self.stats = stats;
+		if !self.database.is_empty() {
+			let (list, _) = build_list(
+				self.config.clone(),
+				self.database.clone(),
+				&self.stats,
+				&self.health,
+				&self.query,
+			);
+			self.list.items = list.items;
+		}
+	}
+
+	/// Update the markers of the databases which fail to open, keeping the selection
+	pub fn set_health(&mut self, health: &HashMap<String, DatabaseHealth>) {
+		self.health = health.clone();
+		if !self.database.is_empty() {
+			let (list, _) = build_list(
+				self.config.clone(),
+				self.database.clone(),
+				&self.stats,
+				&self.health,
+				&self.query,
+			);
+			self.list.items = list.items;
+		}
+	}
+
+	/// Whether typed characters go to the filter instead of the key bindings
+	pub fn is_searching(&self) -> bool {
+		self.searching
+	}
+
+	/// Narrow the list to the databases matching the query and select the first one
+	fn set_query(&mut self, query: String) {
+		self.query = query;
+		(self.list, self.visible) = build_list(
+			self.config.clone(),
+			self.database.clone(),
+			&self.stats,
+			&self.health,
+			&self.query,
+		);
+		if !self.visible.is_empty() {
+			self.list.first();
+		}
 	}
 
 	pub fn new(config: Config) -> Self {
-		let list = if !config.databases.is_empty() {
+		let ((list, visible), database) = if !config.databases.is_empty() {
 			let databases: Vec<_> = config.databases.keys().collect();
 			let db = databases[0].to_string();
-			build_list(config.clone(), db)
+			(build_list(config.clone(), db.clone(), &HashMap::new(), &HashMap::new(), ""), db)
 		} else {
-			StatefulList::default()
+			((StatefulList::default(), vec![]), String::new())
 		};
 		DatabaseExplorerComponent {
 			list,
+			database,
+			stats: HashMap::new(),
+			health: HashMap::new(),
+			query: String::new(),
+			searching: false,
+			visible,
 			config,
 			focus: Focus::Container,
 		}
 	}
 
 	pub async fn event(&mut self, key: Key) -> Result<EventState> {
+		let keys = &self.config.key_config;
 		match self.focus {
 			Focus::Container => {
-				if key == Key::Enter {
+				if key == keys.enter {
 					self.focus = Focus::List;
 					self.list.first();
 					return Ok(EventState::Consumed);
 				}
 			}
 			Focus::List => match key {
-				Key::Esc => {
+				// ENTER keeps the filter and gives the characters back to the key bindings
+				k if k == keys.enter && self.searching => {
+					self.searching = false;
+					return Ok(EventState::Consumed);
+				}
+				// The first ESC clears the type-ahead filter
+				k if k == keys.escape && (self.searching || !self.query.is_empty()) => {
+					self.searching = false;
+					self.set_query(String::new());
+					return Ok(EventState::Consumed);
+				}
+				k if k == keys.search && !self.searching => {
+					self.searching = true;
+					return Ok(EventState::Consumed);
+				}
+				Key::Char(c) if self.searching || ![keys.up, keys.down].contains(&key) => {
+					self.searching = true;
+					self.set_query(format!("{}{}", self.query, c));
+					return Ok(EventState::Consumed);
+				}
+				k if k == keys.backspace && !self.query.is_empty() => {
+					let mut query = self.query.clone();
+					query.pop();
+					self.set_query(query);
+					return Ok(EventState::Consumed);
+				}
+				k if k == keys.escape => {
 					self.list.state = ListState::default();
 					self.focus = Focus::Container;
 					return Ok(EventState::Consumed);
 				}
-				Key::Up => {
+				k if k == keys.up => {
 					self.list.previous();
 					return Ok(EventState::Consumed);
 				}
-				Key::Down => {
+				k if k == keys.down => {
 					self.list.next();
 					return Ok(EventState::Consumed);
 				}
@@ -109,10 +291,14 @@ impl<'a> RenderAbleComponent for DatabaseExplorerComponent<'a> {
 			Focus::Container => "ENTER",
 			Focus::List => "ESC",
 		};
-		let label = &format!("Explorer [{}]", keycode);
+		let label = &match self.query.as_str() {
+			"" => format!("Explorer [{}]", keycode),
+			query => format!("Explorer [{}] /{}", keycode, query),
+		};
 		let list = List::new(self.list.items.clone())
 			.block(render_container(label, focused))
-			.highlight_style(Style::default().fg(HIGHLIGHT_COLOR).add_modifier(Modifier::BOLD));
+			.highlight_style(Style::default().fg(HIGHLIGHT_COLOR).add_modifier(Modifier::BOLD))
+			.highlight_symbol(selection_symbol());
 
 		f.render_stateful_widget(list, rect, &mut self.list.state.clone());
 		Ok(())
@@ -3,14 +3,15 @@ use crate::{
 	config::Config,
 	constants::HIGHLIGHT_COLOR,
 	events::{EventState, Key},
-	ui::StatefulList,
-	utils::get_key_char,
+	ui::{selection_symbol, text_marker, StatefulList},
+	utils::{get_key_char, DatabaseHealth},
 };
 use anyhow::Result;
+use std::collections::HashMap;
 use tui::{
 	backend::Backend,
 	layout::Rect,
-	style::{Modifier, Style},
+	style::{Color, Modifier, Style},
 	text::{Span, Spans},
 	widgets::{List, ListItem, ListState},
 	Frame,
@@ -38,7 +39,55 @@ fn build_list(config: Config) -> StatefulList<'static> {
 	StatefulList::with_items(items, Some(state.clone()))
 }
 
+/// Row marker of a database kind: the worst health of its databases, with a summary of the
+/// problems. Databases not checked yet are unknown.
+fn health_row(
+	config: &Config,
+	name: &str,
+	health: &HashMap<String, DatabaseHealth>,
+) -> ListItem<'static> {
+	let (mut locked, mut missing, mut failed, mut unknown) = (0, 0, 0, 0);
+	for database in config.databases.get(name).into_iter().flatten() {
+		match health.get(&format!("{}:{}", name, database.path)) {
+			Some(DatabaseHealth::Openable) => {}
+			Some(DatabaseHealth::Locked) => locked += 1,
+			Some(DatabaseHealth::Missing) => missing += 1,
+			Some(DatabaseHealth::Failed(_)) => failed += 1,
+			None => unknown += 1,
+		}
+	}
+	let (color, marker) = match (locked, missing + failed, unknown) {
+		(_, problems, _) if problems > 0 => (Color::Red, "[ERR] "),
+		(locked, _, _) if locked > 0 => (Color::Yellow, "[LOCK] "),
+		(_, _, unknown) if unknown > 0 => (Color::DarkGray, "[?] "),
+		_ => (Color::Green, "[OK] "),
+	};
+	let marker = match text_marker(marker) {
+		"" => "● ",
+		marker => marker,
+	};
+	let problems: Vec<_> = [(locked, "locked"), (missing, "missing"), (failed, "failed")]
+		.iter()
+		.filter(|(count, _)| *count > 0)
+		.map(|(count, label)| format!("{} {}", count, label))
+		.collect();
+	let mut spans = vec![
+		Span::styled(marker, Style::default().fg(color)),
+		Span::styled(name.to_string(), Style::default()),
+	];
+	if !problems.is_empty() {
+		spans.push(Span::styled(format!(" ({})", problems.join(", ")), Style::default().fg(color)));
+	}
+	ListItem::new(Spans::from(spans))
+}
+
 impl<'a> DatabaseSelectionComponent<'a> {
+	/// Display health markers next to each kind of database, keeping the selection
+	pub fn set_health(&mut self, health: &HashMap<String, DatabaseHealth>) {
+		let names: Vec<_> = self.config.databases.keys().cloned().collect();
+		self.list.items = names.iter().map(|name| health_row(&self.config, name, health)).collect();
+	}
+
 	pub fn state(&self) -> ListState {
 		self.list.state.clone()
 	}
@@ -78,7 +127,8 @@ impl<'a> RenderAbleComponent for DatabaseSelectionComponent<'a> {
 		let label = &format!("Databases [{}-{}]", up_key, down_key);
 		let list = List::new(self.list.items.clone())
 			.block(render_container(label, focused))
-			.highlight_style(Style::default().fg(HIGHLIGHT_COLOR).add_modifier(Modifier::BOLD));
+			.highlight_style(Style::default().fg(HIGHLIGHT_COLOR).add_modifier(Modifier::BOLD))
+			.highlight_symbol(selection_symbol());
 
 		f.render_stateful_widget(list, rect, &mut self.list.state.clone());
 		Ok(())
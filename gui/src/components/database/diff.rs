@@ -0,0 +1,165 @@
+use anyhow::Result;
+use tui::{
+	backend::Backend,
+	layout::{Constraint, Layout, Rect},
+	style::{Color, Modifier, Style},
+	text::{Span, Spans, Text},
+	widgets::{Cell, Row, Table, TableState},
+	Frame,
+};
+
+use crate::{
+	components::{panel_direction, render_container, RenderAbleComponent},
+	config::Config,
+	constants::HIGHLIGHT_COLOR,
+	events::{EventState, Key},
+	ui::{selection_symbol, text_marker},
+	utils::{changed_range, KeyDisplay, PairChange},
+};
+
+/// Bytes of a value as text when both values of a changed pair are UTF-8, hex otherwise.
+/// The bytes `start..end` are highlighted.
+fn value_spans(value: &[u8], start: usize, end: usize, text: bool, color: Color) -> Spans<'static> {
+	let style = Style::default().fg(color).add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+	let render = |bytes: &[u8]| match text {
+		true => String::from_utf8_lossy(bytes).to_string(),
+		false => bytes.iter().map(|b| format!("{:02x} ", b)).collect(),
+	};
+	Spans::from(vec![
+		Span::raw(render(&value[..start])),
+		Span::styled(render(&value[start..end]), style),
+		Span::raw(render(&value[end..])),
+	])
+}
+
+fn pair_row(key: String, value: &[u8]) -> Row<'static> {
+	Row::new(vec![Cell::from(key), Cell::from(format!("{:?}", value))])
+}
+
+/// Changed pair on two lines, the bytes which differ highlighted in both values
+fn changed_row(key: String, before: &[u8], after: &[u8]) -> Row<'static> {
+	let (start, before_end, after_end) = changed_range(before, after);
+	let text = std::str::from_utf8(before).is_ok() && std::str::from_utf8(after).is_ok();
+	let mut before = value_spans(before, start, before_end, text, Color::Red);
+	let mut after = value_spans(after, start, after_end, text, Color::Green);
+	before.0.insert(0, Span::raw("- "));
+	after.0.insert(0, Span::raw("+ "));
+	let value = Text::from(vec![before, after]);
+	Row::new(vec![Cell::from(key), Cell::from(value)]).height(2)
+}
+
+/// Comparison of two keyspaces in three panes: keys only in the left keyspace, keys only in
+/// the right one and keys whose values differ. LEFT and RIGHT move between the panes.
+pub struct DiffComponent {
+	config: Config,
+	left: String,
+	right: String,
+	changes: [Vec<PairChange>; 3],
+	/// Rendering of the keys, those of the left keyspace
+	key_display: KeyDisplay,
+	states: [TableState; 3],
+	focused: usize,
+}
+
+impl DiffComponent {
+	pub fn new(
+		config: Config,
+		left: &str,
+		right: &str,
+		changes: &[PairChange],
+		key_display: KeyDisplay,
+	) -> Self {
+		let mut panes: [Vec<PairChange>; 3] = Default::default();
+		for change in changes {
+			let pane = match change {
+				PairChange::Removed(_) => 0,
+				PairChange::Added(_) => 1,
+				PairChange::Changed {
+					..
+				} => 2,
+			};
+			panes[pane].push(change.clone());
+		}
+		let mut states: [TableState; 3] = Default::default();
+		for (state, pane) in states.iter_mut().zip(panes.iter()) {
+			state.select(if pane.is_empty() {
+				None
+			} else {
+				Some(0)
+			});
+		}
+		DiffComponent {
+			config,
+			left: left.to_string(),
+			right: right.to_string(),
+			changes: panes,
+			key_display,
+			states,
+			focused: 0,
+		}
+	}
+
+	pub async fn event(&mut self, key: Key) -> Result<EventState> {
+		let keys = &self.config.key_config;
+		let len = self.changes[self.focused].len();
+		let state = &mut self.states[self.focused];
+		let selected = state.selected().unwrap_or(0);
+		match key {
+			k if k == keys.left => self.focused = (self.focused + 2) % 3,
+			k if k == keys.right => self.focused = (self.focused + 1) % 3,
+			k if k == keys.up && len > 0 => state.select(Some(selected.saturating_sub(1))),
+			k if k == keys.down && len > 0 => state.select(Some((selected + 1).min(len - 1))),
+			_ => return Ok(EventState::NotConsumed),
+		}
+		Ok(EventState::Consumed)
+	}
+}
+
+impl RenderAbleComponent for DiffComponent {
+	fn render<B: Backend>(
+		&self,
+		f: &mut Frame<B>,
+		rect: Rect,
+		focused: bool,
+	) -> Result<(), anyhow::Error> {
+		let chunks = Layout::default()
+			.direction(panel_direction(rect))
+			.constraints([
+				Constraint::Percentage(30),
+				Constraint::Percentage(30),
+				Constraint::Percentage(40),
+			])
+			.split(rect);
+		let titles = [
+			format!("Only in {}", self.left),
+			format!("Only in {}", self.right),
+			"Changed".to_string(),
+		];
+		for (pane, changes) in self.changes.iter().enumerate() {
+			let rows = changes.iter().map(|change| match change {
+				PairChange::Removed((key, value)) | PairChange::Added((key, value)) => {
+					pair_row(self.key_display.render(key), value)
+				}
+				PairChange::Changed {
+					key,
+					before,
+					after,
+				} => changed_row(self.key_display.render(key), before, after),
+			});
+			let label =
+				format!("{}{} ({}) [ESC]", text_marker("[RO] "), titles[pane], changes.len());
+			let table = Table::new(rows)
+				.block(render_container(&label, focused && pane == self.focused))
+				.highlight_style(
+					Style::default()
+						.bg(HIGHLIGHT_COLOR)
+						.fg(Color::Black)
+						.add_modifier(Modifier::BOLD),
+				)
+				.highlight_symbol(selection_symbol())
+				.widths(&[Constraint::Percentage(40), Constraint::Percentage(60)]);
+			f.render_stateful_widget(table, chunks[pane], &mut self.states[pane].clone());
+		}
+		Ok(())
+	}
+}
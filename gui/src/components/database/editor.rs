@@ -1,140 +1,2751 @@
 use anyhow::Result;
-use db::{Datastore, KeyValuePair, SimpleTransaction, CF};
+use db::{
+	get_comparator, get_expiry, set_expiry, Cursor, Datastore, Error, KeyValuePair, Overlay, Page,
+	SimpleTransaction, CF,
+};
+use regex::bytes::Regex;
+use std::{
+	cmp::min,
+	collections::{HashMap, HashSet},
+	fmt,
+	fs::{self, File},
+	io::BufWriter,
+	ops::Bound,
+	time::{Duration, Instant},
+};
 use tui::{
 	backend::Backend,
 	layout::{Alignment, Constraint, Direction, Layout, Rect},
 	style::{Color, Modifier, Style},
 	text::{Span, Spans},
-	widgets::{Cell, Paragraph, Row, Table, TableState},
+	widgets::{Cell, Paragraph, Row, Table, TableState, Wrap},
 	Frame,
 };
 
-use crate::{
-	components::{render_container, RenderAbleComponent},
-	config::Config,
-	constants::HIGHLIGHT_COLOR,
-	events::{EventState, Key},
-	ui::StatefulTable,
-};
+use crate::{
+	components::{render_container, RenderAbleComponent},
+	config::{Config, PartitionConfig},
+	constants::{HIGHLIGHT_COLOR, METRICS_INTERVAL, SCAN_BATCH_SIZE, WRITE_BATCH_SIZE},
+	events::{EventState, Key},
+	ui::{selection_symbol, text_marker, StatefulTable},
+	utils::{
+		age_buckets, append_ttl_suffix, apply_rewrites, cells_size, column_label, column_notes,
+		delete_keys, diagnose, diff_pairs, duplicate_values, encode_text, filters_path,
+		find_layout, format_byte_literal, format_bytes, generate_pairs, get_key_char, hash_join,
+		infer_layout, inferred_templates_path, is_conflict, is_older_than, largest_values,
+		layouts_path, load_filters, load_notes, load_views, notes_path, now_timestamp,
+		open_datastore, pairs_size, parse_filter, parse_records, plan_rewrites, prefix_histogram,
+		record_database, remember_layout, render_bar, run_retention, sample_pairs, save_filter,
+		save_inferred_template, save_note, save_view, search_notes, segment_bytes,
+		size_distribution, split_ttl_suffix, views_path, write_pair_table, BackgroundDiff,
+		ByteLayout, CfAction, ColumnScope, CountMode, DatabaseSearch, DatabasesSearch, DerivedView,
+		ExpiredFilter, ExportFormat, ExportOrder, FilterExpr, FromLayoutVariant, GlobalSearch,
+		ImportJob, InferTarget, KeyDisplay, KeyNote, KeyRange, KeySelector, LayoutTemplate,
+		MemoryAccount, MemoryComponent, Order, PairChange, RememberedLayout, RowTemplate,
+		SavedFilter, SearchJob, SearchScope, SizeScan, SnapshotAction, SpaceSaving, TimelineEntry,
+		Weight, WriteConflict, AGE_THRESHOLDS, ANALYSIS_SAMPLE_SIZE, ANALYSIS_TOP_ROWS,
+		HEAVY_HITTERS_CAPACITY, INFER_SAMPLE_SIZE, LAYOUT_SAMPLE_SIZE,
+	},
+};
+
+use super::{
+	DiffComponent, HexViewComponent, PreviewComponent, ReportComponent, ValueFormComponent,
+};
+
+enum Focus {
+	Table,
+	Container,
+}
+
+pub struct DatabaseEditorComponent<'a> {
+	config: Config,
+	preview: PreviewComponent<'a>,
+	/// Popup editing the value of the selected pair
+	value_form: ValueFormComponent,
+	table: StatefulTable,
+	err: Option<String>,
+	pairs: Vec<KeyValuePair>,
+	report: Option<ReportComponent>,
+	/// Three-pane comparison of two keyspaces displayed by DIFF
+	diff: Option<DiffComponent>,
+	/// Hex dump of the selected value, opened with the hex view key
+	hex: Option<HexViewComponent>,
+	snapshots: HashMap<String, Snapshot>,
+	/// Snapshot read by the scans of its database in place of the live state
+	used_snapshot: Option<String>,
+	view: Option<View>,
+	order: Order,
+	page: Page,
+	/// Keys after the displayed page of a LIMIT scan, loaded on demand
+	more: Option<Cursor>,
+	/// Time every row expires at, from EDMA TTL metadata or the value suffix of a RocksDB
+	/// TTL database
+	expiries: Vec<Option<i64>>,
+	/// FILTER EXPIRED of the scans of the command
+	expired_filter: Option<ExpiredFilter>,
+	/// Rows whose checksum segments do not match
+	corrupted: HashSet<usize>,
+	/// Notes attached to the displayed rows
+	notes: HashMap<usize, String>,
+	/// Database of the Metrics panel and time of its last refresh
+	metrics: Option<(String, Instant)>,
+	watch: Option<Watch>,
+	sample: Option<LayoutSample>,
+	/// Command prepared by a key binding for the command editor
+	generated: Option<String>,
+	/// Term of the SEARCH whose hits are displayed
+	searched: Option<String>,
+	/// Cell and char span of the SEARCH match by row
+	highlights: HashMap<usize, (usize, usize, usize)>,
+	/// Memory held by the rows, reports and snapshots, shown in the Memory tab
+	memory: MemoryAccount,
+	focus: Focus,
+}
+
+#[derive(Clone)]
+enum ViewScan {
+	All,
+	Prefix(Vec<u8>),
+	Suffix(Vec<u8>),
+	Range(Bound<Vec<u8>>, Bound<Vec<u8>>),
+	Derived(DerivedView),
+	Filter(String),
+}
+
+/// Keys deleted by the DELETE command
+#[derive(Clone)]
+pub enum Deletion {
+	Key(Vec<u8>),
+	Range(Bound<Vec<u8>>, Bound<Vec<u8>>),
+}
+
+impl fmt::Display for Deletion {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let hex = |key: &Vec<u8>| key.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+		let bound = |bound: &Bound<Vec<u8>>| match bound {
+			Bound::Included(key) | Bound::Excluded(key) => format!("0x{}", hex(key)),
+			Bound::Unbounded => String::new(),
+		};
+		match self {
+			Deletion::Key(key) => write!(f, "key 0x{}", hex(key)),
+			Deletion::Range(start, end) => {
+				let open = if matches!(start, Bound::Included(_)) {
+					'['
+				} else {
+					'('
+				};
+				let close = if matches!(end, Bound::Included(_)) {
+					']'
+				} else {
+					')'
+				};
+				write!(f, "keys in {}{},{}{}", open, bound(start), bound(end), close)
+			}
+		}
+	}
+}
+
+/// Scan the displayed pairs come from. It is re-run after writes of the session so the
+/// view reflects them immediately.
+#[derive(Clone)]
+struct View {
+	path: String,
+	cf: CF,
+	scan: ViewScan,
+}
+
+/// Scan repeated by the WATCH command, with the rows added and changed by its last run
+struct Watch {
+	interval: Duration,
+	refreshed: Instant,
+	added: HashSet<usize>,
+	changed: HashSet<usize>,
+	removed: usize,
+}
+
+/// Pairs sampled across the displayed keyspace, decoded with the layouts while they are
+/// switched
+struct LayoutSample {
+	path: String,
+	cf: CF,
+	pairs: Vec<KeyValuePair>,
+	/// Decoded pairs, displayed in place of the table until ESC
+	report: Option<ReportComponent>,
+}
+
+/// Keyspace captured by the SNAPSHOT command for the current session
+struct Snapshot {
+	path: String,
+	/// Column family compared by DIFF
+	cf: CF,
+	/// Compared column family of `SNAPSHOT=`, every column family of `SNAPSHOT CREATE`
+	data: db::Snapshot,
+}
+
+/// Row template of the selected key layout, otherwise of the value layout
+fn row_template<'a>(columns: &ColumnScope<'a>) -> Option<&'a RowTemplate> {
+	columns.key.and_then(|t| t.row.as_ref()).or_else(|| columns.value.and_then(|t| t.row.as_ref()))
+}
+
+/// Table of the pairs, followed by the computed columns of the selected key and value
+/// layouts
+fn build_table(
+	pairs: Vec<KeyValuePair>,
+	columns: ColumnScope,
+	key_display: KeyDisplay,
+) -> StatefulTable {
+	let row = row_template(&columns);
+	let value_header = if row.is_some() {
+		"Summary"
+	} else {
+		"Value"
+	};
+	let mut headers = vec!["#".to_string(), "Key".to_string(), value_header.to_string()];
+	for template in [columns.key, columns.value].into_iter().flatten() {
+		headers.extend(template.columns.iter().map(|c| c.name.clone()));
+	}
+	let mut items = vec![];
+	for (index, (key, value)) in pairs.iter().enumerate() {
+		let index = format!("{:?}", index);
+		let value_cell = match row {
+			Some(row) => row.render(columns, key, value),
+			None => format!("{:?}", value.to_vec()),
+		};
+		let mut item = vec![index, key_display.render(key), value_cell];
+		if let Some(template) = columns.key {
+			item.extend(template.compute_all(key));
+		}
+		if let Some(template) = columns.value {
+			item.extend(template.compute_all(value));
+		}
+		items.push(item)
+	}
+	StatefulTable::default().with_items(items.to_vec()).with_headers(headers).build()
+}
+
+/// Text of a cell with the chars `start..end` highlighted
+fn highlight_span(text: &str, start: usize, end: usize) -> Spans<'static> {
+	let chars: Vec<char> = text.chars().collect();
+	let (start, end) = (start.min(chars.len()), end.min(chars.len()));
+	let part = |range: &[char]| range.iter().collect::<String>();
+	let style =
+		Style::default().fg(HIGHLIGHT_COLOR).add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+	Spans::from(vec![
+		Span::raw(part(&chars[..start])),
+		Span::styled(part(&chars[start..end]), style),
+		Span::raw(part(&chars[end..])),
+	])
+}
+
+/// Table of storage engine properties, sizes are shown in bytes units
+pub fn metrics_table(properties: Vec<(String, String)>) -> StatefulTable {
+	let items = properties
+		.into_iter()
+		.map(|(name, value)| {
+			let sized = ["mem-tables", "bytes", "write-rate", "files-size"]
+				.iter()
+				.any(|s| name.ends_with(s));
+			let value = match value.parse::<usize>() {
+				Ok(size) if sized => format_bytes(size),
+				_ => value,
+			};
+			vec![name, value]
+		})
+		.collect();
+	StatefulTable::default().with_items(items).with_headers(vec!["Property", "Value"]).build()
+}
+
+impl DatabaseEditorComponent<'_> {
+	/// Pairs of a scan. A failure of the transaction or of a pair is shown as an error in
+	/// place of the table rather than aborting the interface.
+	fn scan_result(
+		&mut self,
+		data: Result<Vec<Result<KeyValuePair, Error>>, Error>,
+	) -> Vec<KeyValuePair> {
+		self.clear_err();
+		match data.and_then(|pairs| pairs.into_iter().collect()) {
+			Ok(pairs) => pairs,
+			Err(err) => {
+				self.set_err(err.to_string());
+				vec![]
+			}
+		}
+	}
+
+	/// Snapshot selected with SNAPSHOT USE when it was taken on `path`, the scans of the
+	/// database read it instead of the live state
+	fn used_snapshot(&self, path: &str) -> Option<&db::Snapshot> {
+		let snapshot = self.snapshots.get(self.used_snapshot.as_ref()?)?;
+		Some(&snapshot.data).filter(|_| snapshot.path == path)
+	}
+
+	async fn suffix_scan_from_path(
+		&mut self,
+		cf: CF,
+		path: &str,
+		prefix: Vec<u8>,
+	) -> Vec<KeyValuePair> {
+		if let Some(snapshot) = self.used_snapshot(path) {
+			let pairs = snapshot.suffix_iterate(&cf, &prefix);
+			return self.scan_result(Ok(pairs.into_iter().map(Ok).collect()));
+		}
+		let data = async {
+			Datastore::open(path)?.transaction(false).await?.suffix_iterate(cf, prefix).await
+		};
+		self.scan_result(data.await)
+	}
+
+	async fn prefix_scan_from_path(
+		&mut self,
+		cf: CF,
+		path: &str,
+		prefix: Vec<u8>,
+	) -> Vec<KeyValuePair> {
+		if let Some(snapshot) = self.used_snapshot(path) {
+			let pairs = snapshot.prefix_iterate(&cf, &prefix);
+			return self.scan_result(Ok(pairs.into_iter().map(Ok).collect()));
+		}
+		let data = async {
+			Datastore::open(path)?.transaction(false).await?.prefix_iterate(cf, prefix).await
+		};
+		self.scan_result(data.await)
+	}
+
+	async fn range_scan_from_path(
+		&mut self,
+		cf: CF,
+		path: &str,
+		(start, end): (Bound<Vec<u8>>, Bound<Vec<u8>>),
+		page: Page,
+		reverse: bool,
+	) -> Vec<KeyValuePair> {
+		if let Some(snapshot) = self.used_snapshot(path) {
+			let pairs = snapshot.scan_range(&cf, start, end, page, reverse);
+			return self.scan_result(Ok(pairs.into_iter().map(Ok).collect()));
+		}
+		let data = async {
+			let ds = Datastore::open(path)?;
+			let tx = ds.transaction(false).await?;
+			tx.scan_range(cf, start, end, page, reverse).await
+		};
+		self.scan_result(data.await)
+	}
+
+	/// Scan keeping only the pairs matching `expr`, read one batch of `SCAN_BATCH_SIZE` pairs
+	/// at a time so only the matches are held
+	async fn filter_scan_from_path(
+		&mut self,
+		cf: CF,
+		path: &str,
+		expr: &FilterExpr,
+	) -> Vec<KeyValuePair> {
+		let (key, value) = (self.preview.key_template(), self.preview.value_template());
+		let columns = ColumnScope {
+			key: key.as_ref(),
+			value: value.as_ref(),
+		};
+		let matched = match self.used_snapshot(path) {
+			Some(snapshot) => Ok(expr.apply(snapshot.iterate(&cf), columns)),
+			None => {
+				let scan = async {
+					let tx = Datastore::open(path)?.transaction(false).await?;
+					let mut cursor = Cursor::new(cf, Bound::Unbounded, Bound::Unbounded, false);
+					let mut matched = vec![];
+					while cursor.has_next() {
+						let batch = cursor.next_batch(&tx, SCAN_BATCH_SIZE).await?;
+						matched.extend(expr.apply(batch, columns));
+					}
+					Ok::<_, Error>(matched)
+				};
+				scan.await
+			}
+		};
+		self.clear_err();
+		matched.unwrap_or_else(|err| {
+			self.set_err(err.to_string());
+			vec![]
+		})
+	}
+
+	async fn scan_from_path(&mut self, cf: CF, path: &str) -> Vec<KeyValuePair> {
+		if let Some(snapshot) = self.used_snapshot(path) {
+			let pairs = snapshot.iterate(&cf);
+			return self.scan_result(Ok(pairs.into_iter().map(Ok).collect()));
+		}
+		let data = async { Datastore::open(path)?.transaction(false).await?.iterate(cf).await };
+		self.scan_result(data.await)
+	}
+
+	async fn page_scan_from_path(
+		&mut self,
+		cf: CF,
+		path: &str,
+		page: Page,
+		reverse: bool,
+	) -> Vec<KeyValuePair> {
+		if let Some(snapshot) = self.used_snapshot(path) {
+			let (start, end) = (Bound::Unbounded, Bound::Unbounded);
+			let pairs = snapshot.scan_range(&cf, start, end, page, reverse);
+			return self.scan_result(Ok(pairs.into_iter().map(Ok).collect()));
+		}
+		let data = async {
+			Datastore::open(path)?.transaction(false).await?.page_iterate(cf, page, reverse).await
+		};
+		self.scan_result(data.await)
+	}
+
+	async fn show_view(&mut self, view: View) {
+		record_database(&view.path);
+		let comparator = self.config.database(&view.path).and_then(|d| d.comparator.clone());
+		let descending = self.order == Order::Desc;
+		// Full and range scans are ordered and paged by the backend iterator, the other scans
+		// and the scans sorted with a comparator once filtered
+		let paged =
+			matches!(view.scan, ViewScan::All | ViewScan::Range(..)) && comparator.is_none();
+		let mut pairs = match &view.scan {
+			ViewScan::All if paged => {
+				let page = self.page;
+				self.page_scan_from_path(view.cf.clone(), &view.path, page, descending).await
+			}
+			ViewScan::All => self.scan_from_path(view.cf.clone(), &view.path).await,
+			ViewScan::Prefix(prefix) => {
+				self.prefix_scan_from_path(view.cf.clone(), &view.path, prefix.clone()).await
+			}
+			ViewScan::Suffix(suffix) => {
+				self.suffix_scan_from_path(view.cf.clone(), &view.path, suffix.clone()).await
+			}
+			ViewScan::Range(start, end) => {
+				let bounds = (start.clone(), end.clone());
+				let (page, reverse) = match paged {
+					true => (self.page, descending),
+					false => (Page::default(), false),
+				};
+				self.range_scan_from_path(view.cf.clone(), &view.path, bounds, page, reverse).await
+			}
+			ViewScan::Filter(expression) => match parse_filter(expression) {
+				Ok(expr) => self.filter_scan_from_path(view.cf.clone(), &view.path, &expr).await,
+				Err(err) => {
+					self.set_err(err);
+					vec![]
+				}
+			},
+			ViewScan::Derived(derived) => {
+				let prefix = derived.prefix.clone().unwrap_or_default().into_bytes();
+				let pairs = self.prefix_scan_from_path(view.cf.clone(), &view.path, prefix).await;
+				derived.evaluate(pairs, &self.config.templates).collect()
+			}
+		};
+		if let Some(name) = comparator {
+			match get_comparator(&name) {
+				Some(compare) if descending => pairs.sort_by(|(a, _), (b, _)| compare(b, a)),
+				Some(compare) => pairs.sort_by(|(a, _), (b, _)| compare(a, b)),
+				None => self.set_err(format!("Comparator {} is not registered", name)),
+			}
+		} else if descending && !paged {
+			pairs.reverse();
+		}
+		let expiries = self.read_expiries(&view.path, &view.cf, &mut pairs).await;
+		let (mut pairs, mut expiries) = self.filter_expired(pairs, expiries);
+		if !paged {
+			let windowed: Vec<_> = self.page.apply(pairs.into_iter().zip(expiries)).collect();
+			(pairs, expiries) = windowed.into_iter().unzip();
+		}
+		// The cursor of LOAD MORE reads the live database, not the used snapshot
+		let live = self.used_snapshot(&view.path).is_none();
+		self.more = match (paged && live, self.page.limit, pairs.last()) {
+			(true, Some(limit), Some((last, _))) if pairs.len() == limit => {
+				let (start, end) = match &view.scan {
+					ViewScan::Range(start, end) => (start.clone(), end.clone()),
+					_ => (Bound::Unbounded, Bound::Unbounded),
+				};
+				let last = Bound::Excluded(last.clone());
+				Some(match descending {
+					true => Cursor::new(view.cf.clone(), start, last, true),
+					false => Cursor::new(view.cf.clone(), last, end, false),
+				})
+			}
+			_ => None,
+		};
+		if !self.is_viewing(&view.path, &view.cf) {
+			self.apply_remembered_layout(&view.path, &view.cf);
+		}
+		self.pairs = pairs;
+		self.expiries = expiries;
+		self.highlights.clear();
+		if let Some(sample) = self.sample.as_mut() {
+			sample.report = None;
+		}
+		if let Some(watch) = self.watch.as_mut() {
+			watch.added.clear();
+			watch.changed.clear();
+			watch.removed = 0;
+		}
+		self.rebuild_table();
+		self.report = None;
+		self.diff = None;
+		self.hex = None;
+		self.searched = None;
+		self.view = Some(view);
+	}
+
+	/// Rebuild the table of the pairs with the computed columns of the selected layouts and
+	/// check their checksum segments
+	pub fn rebuild_table(&mut self) {
+		let (key, value) = (self.preview.key_template(), self.preview.value_template());
+		let columns = ColumnScope {
+			key: key.as_ref(),
+			value: value.as_ref(),
+		};
+		self.table = build_table(self.pairs.to_vec(), columns, self.key_display());
+		// Matches in computed columns follow the layouts they were found with, and matches in
+		// values are hidden by a summary
+		let shown = if row_template(&columns).is_some() {
+			2
+		} else {
+			3
+		};
+		self.highlights.retain(|_, (column, _, _)| *column < shown);
+		self.corrupted = self
+			.pairs
+			.iter()
+			.enumerate()
+			.filter(|(_, (key, value))| !columns.verify(key, value))
+			.map(|(index, _)| index)
+			.collect();
+		self.load_row_notes();
+	}
+
+	/// Rendering of the keys of the displayed keyspace
+	fn key_display(&self) -> KeyDisplay {
+		match &self.view {
+			Some(view) => self.config.key_display(&view.path, &view.cf),
+			None => self.config.key_display,
+		}
+	}
+
+	/// Read the notes of the displayed keyspace and attach them to their rows
+	fn load_row_notes(&mut self) {
+		self.notes.clear();
+		let view = match &self.view {
+			Some(view) => view,
+			None => return,
+		};
+		let notes = load_notes(&notes_path(&self.config.path));
+		let notes = column_notes(notes, &view.path, &Self::column_name(&view.cf));
+		if notes.is_empty() {
+			return;
+		}
+		for (index, (key, _)) in self.pairs.iter().enumerate() {
+			let key = format_byte_literal(key);
+			if let Some(note) = notes.iter().find(|n| n.key == key) {
+				self.notes.insert(index, note.text.clone());
+			}
+		}
+	}
+
+	fn column_name(cf: &CF) -> Option<String> {
+		cf.as_ref().map(|cf| String::from_utf8_lossy(cf).to_string())
+	}
+
+	/// Select the layouts last used on the keyspace when it is opened
+	fn apply_remembered_layout(&mut self, db_path: &str, cf: &CF) {
+		let path = layouts_path(&self.config.path);
+		if let Some(layout) = find_layout(&path, db_path, &Self::column_name(cf)) {
+			let (key, value) = (layout.key_template, layout.value_template);
+			self.preview.select_templates(key.as_deref(), value.as_deref());
+		}
+	}
+
+	/// Remember the layouts selected on the displayed keyspace
+	fn remember_selected_layout(&mut self) {
+		let view = match &self.view {
+			Some(view) => view,
+			None => return,
+		};
+		let layout = RememberedLayout {
+			database: view.path.clone(),
+			column: Self::column_name(&view.cf),
+			key_template: self.preview.key_template().map(|t| t.name),
+			value_template: self.preview.value_template().map(|t| t.name),
+		};
+		if let Err(err) = remember_layout(&layouts_path(&self.config.path), layout) {
+			self.set_err(format!("Failed to remember layout: {}", err));
+		}
+	}
+
+	/// Re-run the scan of the displayed pairs after a write, keeping the selected row
+	pub async fn refresh_view(&mut self) {
+		let view = match self.view.clone() {
+			Some(view) => view,
+			None => return,
+		};
+		let selected = self.table.state.selected();
+		self.show_view(view).await;
+		if let Some(selected) = selected.filter(|_| !self.pairs.is_empty()) {
+			self.table.state.select(Some(selected.min(self.pairs.len() - 1)));
+		}
+		self.update_preview();
+	}
+
+	/// Open the value form on the selected pair of a scan of the live database. Derived
+	/// views and snapshots are read only.
+	fn edit_selected_value(&mut self) {
+		let view = match &self.view {
+			Some(view) if !matches!(view.scan, ViewScan::Derived(_)) => view,
+			_ => return self.set_err("Only scanned pairs can be edited".to_string()),
+		};
+		if self.used_snapshot(&view.path).is_some() {
+			return self.set_err("Pairs of a snapshot are read only".to_string());
+		}
+		if let Some((key, value)) = self.table.state.selected().and_then(|i| self.pairs.get(i)) {
+			self.value_form.open(key.clone(), value, self.preview.value_template());
+		}
+	}
+
+	/// Write the value saved in the form in one transaction, then re-run the scan
+	async fn write_edited_value(&mut self, key: Vec<u8>, value: Vec<u8>) {
+		let (db_path, cf) = match &self.view {
+			Some(view) => (view.path.clone(), view.cf.clone()),
+			None => return,
+		};
+		let value = match self.value_ttl(&db_path) {
+			Some(_) => append_ttl_suffix(value, now_timestamp()),
+			None => value,
+		};
+		let written = async {
+			let ds = open_datastore(&self.config, &db_path)?;
+			let mut tx = ds.transaction(true).await?;
+			tx.set(cf, key, value).await?;
+			tx.commit().await
+		};
+		let written = written.await;
+		match written {
+			Ok(()) => self.refresh_view().await,
+			Err(err) => self.set_err(err.to_string()),
+		}
+	}
+
+	/// Window the following scans with OFFSET and LIMIT
+	pub fn set_page(&mut self, page: Page) {
+		self.page = page;
+	}
+
+	/// Hide expired pairs, or keep only them, with FILTER EXPIRED
+	pub fn set_expired_filter(&mut self, filter: Option<ExpiredFilter>) {
+		self.expired_filter = filter;
+	}
+
+	/// Seconds values of a RocksDB TTL database live
+	fn value_ttl(&self, db_path: &str) -> Option<i64> {
+		self.config.database(db_path).and_then(|database| database.value_ttl)
+	}
+
+	/// Expiration of scanned pairs. Values of a RocksDB TTL database expire `value_ttl`
+	/// seconds after their write time suffix, which is stripped before they are decoded.
+	/// An expiration set by EDMA takes precedence.
+	async fn read_expiries(
+		&mut self,
+		path: &str,
+		cf: &CF,
+		pairs: &mut [KeyValuePair],
+	) -> Vec<Option<i64>> {
+		let mut expiries = vec![None; pairs.len()];
+		if let Some(ttl) = self.value_ttl(path) {
+			for ((_, value), expiry) in pairs.iter_mut().zip(expiries.iter_mut()) {
+				let split = split_ttl_suffix(value).map(|(v, written)| (v.to_vec(), written));
+				if let Some((stripped, written)) = split {
+					*value = stripped;
+					*expiry = Some(written + ttl);
+				}
+			}
+		}
+		let metadata = async {
+			let ds = Datastore::open(path)?;
+			let tx = ds.transaction(false).await?;
+			let mut metadata = Vec::with_capacity(pairs.len());
+			for (key, _) in pairs.iter() {
+				metadata.push(get_expiry(&tx, cf.clone(), key.clone()).await?);
+			}
+			Ok::<_, Error>(metadata)
+		};
+		let metadata = metadata.await;
+		match metadata {
+			Ok(metadata) => {
+				for (expiry, expires_at) in expiries.iter_mut().zip(metadata) {
+					*expiry = expires_at.or(*expiry);
+				}
+			}
+			Err(err) => self.set_err(err.to_string()),
+		}
+		expiries
+	}
+
+	/// Apply FILTER EXPIRED to scanned pairs and their expiration
+	fn filter_expired(
+		&self,
+		pairs: Vec<KeyValuePair>,
+		expiries: Vec<Option<i64>>,
+	) -> (Vec<KeyValuePair>, Vec<Option<i64>>) {
+		let filter = match self.expired_filter {
+			Some(filter) => filter,
+			None => return (pairs, expiries),
+		};
+		let now = now_timestamp();
+		pairs.into_iter().zip(expiries).filter(|(_, at)| filter.keeps(*at, now)).unzip()
+	}
+
+	/// Append the next LIMIT keys of the displayed scan, read after its last key
+	async fn load_more(&mut self) {
+		let (mut cursor, view) = match (self.more.take(), self.view.as_ref()) {
+			(Some(cursor), Some(view)) => (cursor, view),
+			_ => return,
+		};
+		let limit = self.page.limit.unwrap_or_default();
+		let (path, cf) = (view.path.clone(), view.cf.clone());
+		let batch = async {
+			let ds = Datastore::open(&path)?;
+			let tx = ds.transaction(false).await?;
+			cursor.next_batch(&tx, limit).await
+		};
+		let batch = batch.await;
+		match batch {
+			Ok(mut batch) => {
+				let selected = self.table.state.selected();
+				let expiries = self.read_expiries(&path, &cf, &mut batch).await;
+				let (batch, expiries) = self.filter_expired(batch, expiries);
+				self.pairs.extend(batch);
+				self.expiries.extend(expiries);
+				self.rebuild_table();
+				self.table.state.select(selected);
+				if cursor.has_next() {
+					self.more = Some(cursor);
+				}
+			}
+			Err(err) => self.set_err(err.to_string()),
+		}
+	}
+
+	/// Move the window of a LIMIT scan to the next or previous page and re-run the scan.
+	/// The last page is reached when the displayed one is not full.
+	async fn turn_page(&mut self, next: bool) {
+		let limit = match self.page.limit {
+			Some(limit) => limit,
+			None => return,
+		};
+		let offset = match next {
+			true if self.pairs.len() == limit => self.page.offset + limit,
+			false if self.page.offset > 0 => self.page.offset.saturating_sub(limit),
+			_ => return,
+		};
+		self.page.offset = offset;
+		self.refresh_view().await;
+	}
+
+	/// Set the key order of the scans and re-run the displayed one
+	pub async fn set_order(&mut self, order: Order) {
+		self.order = order;
+		self.refresh_view().await;
+	}
+
+	/// Whether the displayed pairs are scanned from column family `cf` of `db_path`
+	fn is_viewing(&self, db_path: &str, cf: &CF) -> bool {
+		self.view.as_ref().map_or(false, |view| view.path == db_path && &view.cf == cf)
+	}
+
+	/// Show the result of a write: the view is refreshed when it displays the written
+	/// column family, otherwise the written column family is displayed
+	async fn show_written(&mut self, cf: CF, name: &str, path: &str) {
+		if self.is_viewing(&format!("{}:{}", name, path), &cf) {
+			self.refresh_view().await;
+		} else {
+			self.scan_database(cf, name, path).await;
+		}
+	}
+
+	pub async fn prefix_scan_database(&mut self, cf: CF, name: &str, path: &str, prefix: Vec<u8>) {
+		let path = format!("{}:{}", name, path);
+		self.show_view(View {
+			path,
+			cf,
+			scan: ViewScan::Prefix(prefix),
+		})
+		.await;
+	}
+
+	/// Display the keys of a partition with the layouts of its config
+	pub async fn partition_scan_database(
+		&mut self,
+		name: &str,
+		path: &str,
+		partition: &PartitionConfig,
+	) {
+		let cf = partition.column.as_ref().map(|c| c.as_bytes().to_vec());
+		self.prefix_scan_database(cf, name, path, partition.prefix.clone()).await;
+		let (key, value) = (&partition.key_layout, &partition.value_layout);
+		self.preview.select_templates(key.as_deref(), value.as_deref());
+		self.rebuild_table();
+		self.update_preview();
+	}
+
+	pub async fn suffix_scan_database(&mut self, cf: CF, name: &str, path: &str, suffix: Vec<u8>) {
+		let path = format!("{}:{}", name, path);
+		self.show_view(View {
+			path,
+			cf,
+			scan: ViewScan::Suffix(suffix),
+		})
+		.await;
+	}
+
+	/// Encode a key written in a command with the selected key layout
+	fn encode_key(&self, text: &str) -> Option<Vec<u8>> {
+		encode_text(self.preview.key_template().as_ref(), text)
+	}
+
+	/// Encode a value written in a command like a key, with the selected value layout
+	fn encode_value(&self, text: &str) -> Option<Vec<u8>> {
+		encode_text(self.preview.value_template().as_ref(), text)
+	}
+
+	fn encode_bound(&self, bound: &Bound<String>) -> Result<Bound<Vec<u8>>, String> {
+		let encode = |text: &String| {
+			self.encode_key(text).ok_or_else(|| format!("Invalid RANGE bound {}", text))
+		};
+		Ok(match bound {
+			Bound::Included(text) => Bound::Included(encode(text)?),
+			Bound::Excluded(text) => Bound::Excluded(encode(text)?),
+			Bound::Unbounded => Bound::Unbounded,
+		})
+	}
+
+	/// Keys targeted by DELETE, encoded like RANGE bounds
+	pub fn deletion(&mut self, key: Option<&String>, range: Option<&KeyRange>) -> Option<Deletion> {
+		let deletion = match (key, range) {
+			(Some(key), _) => match self.encode_key(key) {
+				Some(key) => Ok(Deletion::Key(key)),
+				None => Err(format!("Invalid KEY {}", key)),
+			},
+			(None, Some(range)) => self.encode_bound(&range.start).and_then(|start| {
+				self.encode_bound(&range.end).map(|end| Deletion::Range(start, end))
+			}),
+			(None, None) => return None,
+		};
+		match deletion {
+			Ok(deletion) => Some(deletion),
+			Err(err) => {
+				self.set_err(err);
+				None
+			}
+		}
+	}
+
+	/// Delete the targeted keys in one transaction and show the column family. Returns
+	/// the number of deleted keys.
+	pub async fn delete_database(
+		&mut self,
+		cf: CF,
+		name: &str,
+		path: &str,
+		deletion: &Deletion,
+	) -> usize {
+		let db_path = format!("{}:{}", name, path);
+		let deleted = async {
+			let ds = open_datastore(&self.config, &db_path)?;
+			let mut tx = ds.transaction(true).await?;
+			let deleted = match deletion.clone() {
+				Deletion::Key(key) => {
+					let exists = tx.exi(cf.clone(), key.clone()).await?;
+					if exists {
+						tx.del(cf.clone(), key).await?;
+					}
+					usize::from(exists)
+				}
+				Deletion::Range(start, end) => tx.delete_range(cf.clone(), start, end).await?,
+			};
+			tx.commit().await?;
+			Ok::<usize, Error>(deleted)
+		};
+		let deleted = deleted.await;
+		match deleted {
+			Ok(deleted) => {
+				self.show_written(cf, name, path).await;
+				deleted
+			}
+			Err(err) if is_conflict(&err.to_string()) => {
+				if let Deletion::Key(key) = deletion {
+					let retry = self.conflict_write(&cf, key, None, None);
+					let writes = vec![(cf, key.clone(), None)];
+					self.show_conflict(&db_path, writes, retry, err.to_string()).await;
+				} else {
+					self.set_err(err.to_string());
+				}
+				0
+			}
+			Err(err) => {
+				self.set_err(err.to_string());
+				0
+			}
+		}
+	}
+
+	/// Write `value` at `key` in one transaction and show the column family. With `ttl`,
+	/// the key expires after that many seconds. Returns whether the pair was written.
+	pub async fn set_database(
+		&mut self,
+		cf: CF,
+		name: &str,
+		path: &str,
+		key: &str,
+		value: &str,
+		ttl: Option<i64>,
+	) -> bool {
+		let (key, value) = match (self.encode_key(key), self.encode_value(value)) {
+			(Some(key), Some(value)) => (key, value),
+			(None, _) => {
+				self.set_err(format!("Invalid KEY {}", key));
+				return false;
+			}
+			(_, None) => {
+				self.set_err(format!("Invalid VALUE {}", value));
+				return false;
+			}
+		};
+		let db_path = format!("{}:{}", name, path);
+		let retry = self.conflict_write(&cf, &key, Some(&value), ttl);
+		let value = match self.value_ttl(&db_path) {
+			Some(_) => append_ttl_suffix(value, now_timestamp()),
+			None => value,
+		};
+		let written = async {
+			let ds = open_datastore(&self.config, &db_path)?;
+			let mut tx = ds.transaction(true).await?;
+			tx.set(cf.clone(), key.clone(), value.clone()).await?;
+			if let Some(ttl) = ttl {
+				set_expiry(&mut tx, cf.clone(), key.clone(), now_timestamp() + ttl).await?;
+			}
+			tx.commit().await
+		};
+		let written = written.await;
+		match written {
+			Ok(()) => {
+				self.show_written(cf, name, path).await;
+				true
+			}
+			Err(err) if is_conflict(&err.to_string()) => {
+				let writes = vec![(cf, key, Some(value))];
+				self.show_conflict(&db_path, writes, retry, err.to_string()).await;
+				false
+			}
+			Err(err) => {
+				self.set_err(err.to_string());
+				false
+			}
+		}
+	}
+
+	/// Command repeating a single write, with its TTL
+	fn conflict_write(
+		&self,
+		cf: &CF,
+		key: &[u8],
+		value: Option<&[u8]>,
+		ttl: Option<i64>,
+	) -> String {
+		let column = Self::column_name(cf).map(|c| format!("COLUMN={} ", c)).unwrap_or_default();
+		let conflict = WriteConflict {
+			key: key.to_vec(),
+			intended: value.map(|v| v.to_vec()),
+			stored: None,
+		};
+		let ttl = ttl.map(|ttl| format!(" TTL={}", ttl)).unwrap_or_default();
+		format!("{}{}", conflict.overwrite_command(&column), ttl)
+	}
+
+	/// Display the keys of a write that failed on an optimistic conflict, with the value
+	/// written and the value now stored. Each row prepares a command: `retry` runs the whole
+	/// write again, reload writes back the stored value to edit it and overwrite writes the
+	/// intended value of the key alone.
+	async fn show_conflict(
+		&mut self,
+		db_path: &str,
+		writes: Vec<(CF, Vec<u8>, Option<Vec<u8>>)>,
+		retry: String,
+		err: String,
+	) {
+		let ds = open_datastore(&self.config, db_path).ok();
+		let mut conflicts = vec![];
+		for (cf, key, intended) in writes {
+			let stored = async {
+				let tx = ds.as_ref()?.transaction(false).await.ok()?;
+				tx.get(cf.clone(), key.clone()).await.ok()?
+			};
+			let stored = stored.await;
+			conflicts.push((
+				cf,
+				WriteConflict {
+					key,
+					intended,
+					stored,
+				},
+			));
+		}
+		drop(ds);
+		// Values of a RocksDB TTL database are shown and written back without their suffix
+		if self.value_ttl(db_path).is_some() {
+			for (_, conflict) in conflicts.iter_mut() {
+				for value in [&mut conflict.intended, &mut conflict.stored].into_iter().flatten() {
+					let stripped = split_ttl_suffix(value).map(|(stripped, _)| stripped.to_vec());
+					if let Some(stripped) = stripped {
+						*value = stripped;
+					}
+				}
+			}
+		}
+
+		let text = |value: &Option<Vec<u8>>| match value {
+			Some(value) => format_byte_literal(value),
+			None => "(none)".to_string(),
+		};
+		let mut items = vec![vec![
+			"all keys".to_string(),
+			String::new(),
+			String::new(),
+			format!("retry: {}", retry),
+		]];
+		let mut actions = vec![Some(retry)];
+		for (cf, conflict) in conflicts.iter() {
+			let column =
+				Self::column_name(cf).map(|c| format!("COLUMN={} ", c)).unwrap_or_default();
+			let (key, intended, stored) = (
+				format_byte_literal(&conflict.key),
+				text(&conflict.intended),
+				text(&conflict.stored),
+			);
+			for (label, command) in [
+				("reload", conflict.reload_command(&column)),
+				("overwrite", conflict.overwrite_command(&column)),
+			] {
+				items.push(vec![
+					key.clone(),
+					intended.clone(),
+					stored.clone(),
+					format!("{}: {}", label, command),
+				]);
+				actions.push(Some(command));
+			}
+		}
+
+		let table = StatefulTable::default()
+			.with_items(items)
+			.with_headers(vec!["Key", "Written", "Stored", "Command [ENTER]"])
+			.build();
+		let title = format!("Write conflict on {} ({} keys): {}", db_path, conflicts.len(), err);
+		let report = ReportComponent::new(self.config.clone(), &title, table, vec![25, 20, 20, 35]);
+		self.report = Some(report.with_actions(actions));
+		self.clear_err();
+	}
+
+	/// Queue `value` at `key` of the database at `db_path` in the pending changeset instead
+	/// of writing it. Returns whether the pair was queued.
+	pub fn queue_set(
+		&mut self,
+		overlay: &mut Overlay,
+		cf: CF,
+		db_path: &str,
+		key: &str,
+		value: &str,
+	) -> bool {
+		match (self.encode_key(key), self.encode_value(value)) {
+			(Some(key), Some(value)) => {
+				let value = match self.value_ttl(db_path) {
+					Some(_) => append_ttl_suffix(value, now_timestamp()),
+					None => value,
+				};
+				overlay.set(cf, key, value);
+				true
+			}
+			(None, _) => {
+				self.set_err(format!("Invalid KEY {}", key));
+				false
+			}
+			(_, None) => {
+				self.set_err(format!("Invalid VALUE {}", value));
+				false
+			}
+		}
+	}
+
+	/// Queue the deletion of a key in the pending changeset. Ranges are not queued, their
+	/// keys are only known once they are scanned.
+	pub fn queue_deletion(&mut self, overlay: &mut Overlay, cf: CF, deletion: &Deletion) -> bool {
+		match deletion {
+			Deletion::Key(key) => {
+				overlay.del(cf, key.clone());
+				true
+			}
+			Deletion::Range(..) => {
+				self.set_err("A changeset only queues the deletion of a KEY".to_string());
+				false
+			}
+		}
+	}
+
+	/// Write the pending changeset in one transaction and show the first written column
+	/// family. The changeset is kept when the commit fails.
+	pub async fn commit_changeset(
+		&mut self,
+		name: &str,
+		path: &str,
+		overlay: &mut Overlay,
+	) -> bool {
+		let written = overlay.column_families();
+		let writes = written
+			.iter()
+			.flat_map(|cf| overlay.changes(cf).into_iter().map(|(k, v)| (cf.clone(), k, v)))
+			.collect::<Vec<_>>();
+		let db_path = format!("{}:{}", name, path);
+		let committed = async {
+			let ds = open_datastore(&self.config, &db_path)?;
+			let mut tx = ds.transaction(true).await?;
+			overlay.commit(&mut tx).await
+		};
+		let committed = committed.await;
+		match committed {
+			Ok(()) => {
+				match self.view.as_ref().filter(|view| view.path == db_path) {
+					Some(_) => self.refresh_view().await,
+					None => {
+						let cf = written.into_iter().next().unwrap_or_default();
+						self.scan_database(cf, name, path).await;
+					}
+				}
+				true
+			}
+			Err(err) if is_conflict(&err.to_string()) => {
+				self.show_conflict(&db_path, writes, "COMMIT".to_string(), err.to_string()).await;
+				false
+			}
+			Err(err) => {
+				self.set_err(err.to_string());
+				false
+			}
+		}
+	}
+
+	/// Display the pairs with keys between the bounds of `range`
+	pub async fn range_scan_database(&mut self, cf: CF, name: &str, path: &str, range: &KeyRange) {
+		let bounds = self
+			.encode_bound(&range.start)
+			.and_then(|start| self.encode_bound(&range.end).map(|end| (start, end)));
+		let (start, end) = match bounds {
+			Ok(bounds) => bounds,
+			Err(err) => return self.set_err(err),
+		};
+		let path = format!("{}:{}", name, path);
+		self.show_view(View {
+			path,
+			cf,
+			scan: ViewScan::Range(start, end),
+		})
+		.await;
+	}
+
+	pub async fn scan_database(&mut self, cf: CF, name: &str, path: &str) {
+		let path = format!("{}:{}", name, path);
+		self.show_view(View {
+			path,
+			cf,
+			scan: ViewScan::All,
+		})
+		.await;
+	}
+
+	/// Display the pairs matching a filter expression
+	pub async fn filter_database(&mut self, cf: CF, name: &str, path: &str, expression: &str) {
+		let path = format!("{}:{}", name, path);
+		self.show_view(View {
+			path,
+			cf,
+			scan: ViewScan::Filter(expression.to_string()),
+		})
+		.await;
+	}
+
+	/// Save a filter expression under `label` to recall it with FILTER
+	pub fn save_filter(&mut self, label: &str, expression: &str) -> bool {
+		let filter = SavedFilter {
+			name: label.to_string(),
+			expression: expression.to_string(),
+		};
+		match save_filter(&filters_path(&self.config.path), filter) {
+			Ok(()) => true,
+			Err(err) => {
+				self.set_err(err.to_string());
+				false
+			}
+		}
+	}
+
+	/// Expression of the filter saved under `label`
+	pub fn saved_filter(&self, label: &str) -> Option<String> {
+		let filters = load_filters(&filters_path(&self.config.path));
+		filters.into_iter().find(|f| f.name == label).map(|f| f.expression)
+	}
+
+	/// Byte layout of a key selector: a byte length or `SEGMENT` for the first segment of
+	/// the key layout
+	fn key_selector(&self, selector: &KeySelector) -> Result<ByteLayout, String> {
+		match selector {
+			KeySelector::Segment => {
+				match self.preview.key_template().and_then(|t| t.layout.first().cloned()) {
+					Some(layout) => Ok(layout),
+					None => Err("No key layout selected".to_string()),
+				}
+			}
+			KeySelector::Length(len) => Ok(ByteLayout::default().with_range(0, *len).build()),
+		}
+	}
+
+	/// Stream the whole keyspace through a space-saving summary and display the prefixes
+	/// with the most keys, or the most bytes when `weight` is `BYTES`
+	pub async fn heavy_hitters_database(
+		&mut self,
+		cf: CF,
+		name: &str,
+		path: &str,
+		selector: &KeySelector,
+		weight: &Weight,
+	) {
+		let (from, to, layout) = match self.key_selector(selector) {
+			Ok(layout) => (layout.from, layout.to, layout),
+			Err(err) => return self.set_err(err),
+		};
+
+		let db_path = format!("{}:{}", name, path);
+		let scanned =
+			async { Datastore::open(&db_path)?.transaction(false).await?.iterate(cf).await };
+		let pairs = match scanned.await {
+			Ok(pairs) => pairs,
+			Err(err) => return self.set_err(err.to_string()),
+		};
+		let mut summary = SpaceSaving::new(HEAVY_HITTERS_CAPACITY);
+		let mut total = 0;
+		for (key, value) in pairs.into_iter().flatten() {
+			let end = min(to, key.len());
+			let size = match weight {
+				Weight::Bytes => key.len() + value.len(),
+				Weight::Keys => 1,
+			};
+			summary.insert(&key[min(from, end)..end], size);
+			total += 1;
+		}
+
+		let hitters = summary.top(ANALYSIS_TOP_ROWS);
+		let max = hitters.first().map(|h| h.weight).unwrap_or(0);
+		let format_weight = |w: usize| match weight {
+			Weight::Bytes => format_bytes(w),
+			Weight::Keys => w.to_string(),
+		};
+		let items = hitters
+			.iter()
+			.enumerate()
+			.map(|(index, hitter)| {
+				vec![
+					index.to_string(),
+					hitter.item.format_variant(layout.variant.clone(), &layout.format),
+					format_weight(hitter.weight),
+					format!("±{}", format_weight(hitter.error)),
+					render_bar(hitter.weight, max),
+				]
+			})
+			.collect();
+		let table = StatefulTable::default()
+			.with_items(items)
+			.with_headers(vec!["#", "Prefix", "Weight", "Error", "Share"])
+			.build();
+		let by = match weight {
+			Weight::Bytes => "bytes",
+			Weight::Keys => "keys",
+		};
+		let title = format!("Heavy hitters by {} ({} keys streamed)", by, total);
+		self.report =
+			Some(ReportComponent::new(self.config.clone(), &title, table, vec![5, 35, 15, 15, 30]));
+	}
+
+	/// Sample the keyspace and display the most frequent key prefixes. `selector` is
+	/// either a byte length or `SEGMENT` for the first segment of the key layout.
+	pub async fn histogram_database(
+		&mut self,
+		cf: CF,
+		name: &str,
+		path: &str,
+		selector: &KeySelector,
+	) {
+		let (from, to, layout) = match self.key_selector(selector) {
+			Ok(layout) => (layout.from, layout.to, layout),
+			Err(err) => return self.set_err(err),
+		};
+
+		let db_path = format!("{}:{}", name, path);
+		let pairs = self.scan_from_path(cf, &db_path).await;
+		let total = pairs.len();
+		let sampled = sample_pairs(pairs, ANALYSIS_SAMPLE_SIZE);
+		let buckets = prefix_histogram(&sampled, from, to, ANALYSIS_TOP_ROWS);
+		let max = buckets.first().map(|b| b.count).unwrap_or(0);
+
+		let items = buckets
+			.iter()
+			.enumerate()
+			.map(|(index, bucket)| {
+				vec![
+					index.to_string(),
+					bucket.prefix.format_variant(layout.variant.clone(), &layout.format),
+					bucket.count.to_string(),
+					format_bytes(bucket.size),
+					render_bar(bucket.count, max),
+				]
+			})
+			.collect();
+		let table = StatefulTable::default()
+			.with_items(items)
+			.with_headers(vec!["#", "Prefix", "Count", "Size", "Histogram"])
+			.build();
+		let title = format!("Prefix histogram (sampled {} of {} keys)", sampled.len(), total);
+		self.report =
+			Some(ReportComponent::new(self.config.clone(), &title, table, vec![5, 35, 10, 10, 40]));
+	}
+
+	/// Display the value size distribution of the keyspace and its largest values.
+	/// `scan` is `ALL` to scan every pair or `SAMPLE` to analyze a random sample.
+	pub async fn sizes_database(&mut self, cf: CF, name: &str, path: &str, scan: &SizeScan) {
+		let db_path = format!("{}:{}", name, path);
+		let key_display = self.config.key_display(&db_path, &cf);
+		let pairs = self.scan_from_path(cf, &db_path).await;
+		let total = pairs.len();
+		let pairs = match scan {
+			SizeScan::Sample => sample_pairs(pairs, ANALYSIS_SAMPLE_SIZE),
+			SizeScan::All => pairs,
+		};
+
+		let distribution = size_distribution(&pairs);
+		let mut items = vec![
+			vec!["count".to_string(), String::new(), distribution.count.to_string()],
+			vec!["total".to_string(), String::new(), format_bytes(distribution.total)],
+			vec!["min".to_string(), String::new(), format_bytes(distribution.min)],
+			vec!["p50".to_string(), String::new(), format_bytes(distribution.p50)],
+			vec!["p95".to_string(), String::new(), format_bytes(distribution.p95)],
+			vec!["p99".to_string(), String::new(), format_bytes(distribution.p99)],
+			vec!["max".to_string(), String::new(), format_bytes(distribution.max)],
+		];
+		for (index, (key, size)) in largest_values(&pairs, ANALYSIS_TOP_ROWS).iter().enumerate() {
+			let key = key_display.render(key);
+			items.push(vec![format!("#{}", index + 1), key, format_bytes(*size)]);
+		}
+
+		let table = StatefulTable::default()
+			.with_items(items)
+			.with_headers(vec!["Metric", "Key", "Value size"])
+			.build();
+		let title = format!("Value sizes ({} of {} pairs)", pairs.len(), total);
+		self.report =
+			Some(ReportComponent::new(self.config.clone(), &title, table, vec![10, 70, 20]));
+	}
+
+	/// Display the layout proposed for the sampled keys or values of the column family. With
+	/// `save`, the layout is saved as a template under that name. Returns whether it was saved.
+	pub async fn infer_layout_database(
+		&mut self,
+		cf: CF,
+		name: &str,
+		path: &str,
+		target: &InferTarget,
+		save: Option<&str>,
+	) -> bool {
+		let db_path = format!("{}:{}", name, path);
+		let pairs = self.scan_from_path(cf, &db_path).await;
+		let total = pairs.len();
+		let samples: Vec<Vec<u8>> = sample_pairs(pairs, INFER_SAMPLE_SIZE)
+			.into_iter()
+			.map(|(key, value)| match target {
+				InferTarget::Key => key,
+				InferTarget::Value => value,
+			})
+			.collect();
+		let segments = infer_layout(&samples);
+
+		let items = segments
+			.iter()
+			.map(|segment| {
+				vec![
+					segment.name.clone(),
+					format!("{}..{}", segment.from, segment.to),
+					segment.variant.to_string(),
+					segment.evidence.clone(),
+				]
+			})
+			.collect();
+		let table = StatefulTable::default()
+			.with_items(items)
+			.with_headers(vec!["Segment", "Bytes", "Variant", "Evidence"])
+			.build();
+		let kind = match target {
+			InferTarget::Key => "key",
+			InferTarget::Value => "value",
+		};
+		let title =
+			format!("Inferred {} layout ({} of {} {}s sampled)", kind, samples.len(), total, kind);
+		self.report =
+			Some(ReportComponent::new(self.config.clone(), &title, table, vec![20, 15, 15, 50]));
+
+		let name = match save {
+			Some(name) => name,
+			None => return false,
+		};
+		if segments.is_empty() {
+			self.set_err("No layout to save, the column family is empty".to_string());
+			return false;
+		}
+		match save_inferred_template(&inferred_templates_path(&self.config.path), name, &segments) {
+			Ok(()) => true,
+			Err(err) => {
+				self.set_err(format!("Unable to save the template: {}", err));
+				false
+			}
+		}
+	}
+
+	/// Display groups of keys sharing identical values. Unless `target` is `SHOW`, the
+	/// report is also exported as JSON to the `target` path.
+	pub async fn duplicates_database(&mut self, cf: CF, name: &str, path: &str, target: &str) {
+		let db_path = format!("{}:{}", name, path);
+		let key_display = self.config.key_display(&db_path, &cf);
+		let pairs = self.scan_from_path(cf, &db_path).await;
+		let report = duplicate_values(&pairs);
+
+		if target != "SHOW" {
+			let exported = serde_json::to_string_pretty(&report)
+				.map_err(|e| e.to_string())
+				.and_then(|json| fs::write(target, json).map_err(|e| e.to_string()));
+			if let Err(err) = exported {
+				return self.set_err(format!("Unable to export duplicates: {}", err));
+			}
+		}
+
+		let items = report
+			.groups
+			.iter()
+			.take(ANALYSIS_TOP_ROWS)
+			.enumerate()
+			.map(|(index, group)| {
+				let keys: Vec<_> =
+					group.keys.iter().take(3).map(|k| key_display.render(k)).collect();
+				vec![
+					index.to_string(),
+					format_bytes(group.value_size),
+					group.keys.len().to_string(),
+					format_bytes(group.wasted),
+					keys.join(", "),
+				]
+			})
+			.collect();
+		let table = StatefulTable::default()
+			.with_items(items)
+			.with_headers(vec!["#", "Value size", "Keys", "Wasted", "Sample keys"])
+			.build();
+		let title = format!(
+			"Duplicate values ({} groups, {} wasted in {} pairs)",
+			report.groups.len(),
+			format_bytes(report.wasted),
+			report.scanned
+		);
+		self.report =
+			Some(ReportComponent::new(self.config.clone(), &title, table, vec![5, 10, 10, 10, 65]));
+	}
+
+	/// Segment of the selected key layout template with the given name
+	fn key_segment(&self, segment: &str) -> Option<ByteLayout> {
+		self.preview.key_template()?.layout.into_iter().find(|l| l.name == segment)
+	}
+
+	/// Join the keyspace with column family `right` of the same database: the `segment`
+	/// layout of each key, or of each value when the key layout has no such segment, is
+	/// matched against the keys of `right`
+	pub async fn join_database(
+		&mut self,
+		cf: CF,
+		name: &str,
+		path: &str,
+		right: &str,
+		segment: &str,
+	) {
+		let (layout, on_value) = match self.key_segment(segment) {
+			Some(layout) => (layout, false),
+			None => {
+				let layout = self
+					.preview
+					.value_template()
+					.and_then(|t| t.layout.into_iter().find(|l| l.name == segment));
+				match layout {
+					Some(layout) => (layout, true),
+					None => return self.set_err(format!("No layout segment named {}", segment)),
+				}
+			}
+		};
+
+		let db_path = format!("{}:{}", name, path);
+		let left = self.scan_from_path(cf.clone(), &db_path).await;
+		let right_cf = Some(right.as_bytes().to_vec());
+		let key_display =
+			(self.config.key_display(&db_path, &cf), self.config.key_display(&db_path, &right_cf));
+		let right_pairs = self.scan_from_path(right_cf, &db_path).await;
+		let (rows, unmatched) = hash_join(left, right_pairs, |(key, value)| {
+			let raw = if on_value {
+				value
+			} else {
+				key
+			};
+			segment_bytes(raw, layout.from, layout.to)
+		});
+
+		let text = |raw: &[u8]| String::from_utf8_lossy(raw).to_string();
+		let items = rows
+			.iter()
+			.map(|row| {
+				vec![
+					key_display.0.render(&row.left.0),
+					text(&row.left.1),
+					key_display.1.render(&row.right.0),
+					text(&row.right.1),
+				]
+			})
+			.collect();
+		let table = StatefulTable::default()
+			.with_items(items)
+			.with_headers(vec!["Key", "Value", "Joined key", "Joined value"])
+			.build();
+		let title = format!(
+			"Join on {} with {} ({} rows, {} without match)",
+			segment,
+			right,
+			rows.len(),
+			unmatched
+		);
+		self.report =
+			Some(ReportComponent::new(self.config.clone(), &title, table, vec![20, 30, 20, 30]));
+	}
+
+	/// Classify keys into age buckets using a timestamp segment of the key layout. Every
+	/// "older than" bucket generates a PURGE command which is picked with ENTER.
+	pub async fn age_database(&mut self, cf: CF, name: &str, path: &str, segment: &str) {
+		let layout = match self.key_segment(segment) {
+			Some(layout) => layout,
+			None => return self.set_err(format!("No key layout segment named {}", segment)),
+		};
+
+		let db_path = format!("{}:{}", name, path);
+		let pairs = self.scan_from_path(cf.clone(), &db_path).await;
+		let (buckets, invalid) = age_buckets(&pairs, layout.from, layout.to, now_timestamp());
+		let column = match &cf {
+			Some(c) => format!("COLUMN={} ", String::from_utf8_lossy(c)),
+			None => String::new(),
+		};
+
+		let mut items = vec![];
+		let mut actions = vec![];
+		for bucket in buckets.iter() {
+			let (label, action) = match bucket.older_than {
+				Some(days) => (
+					format!("older than {} days", days),
+					Some(format!("{}PURGE={}:{}", column, segment, days)),
+				),
+				None => (format!("newer than {} days", AGE_THRESHOLDS[0]), None),
+			};
+			items.push(vec![
+				label,
+				bucket.count.to_string(),
+				format_bytes(bucket.size),
+				action.clone().unwrap_or_default(),
+			]);
+			actions.push(action);
+		}
+		items.push(vec![
+			"no timestamp".to_string(),
+			invalid.to_string(),
+			String::new(),
+			String::new(),
+		]);
+		actions.push(None);
+
+		let table = StatefulTable::default()
+			.with_items(items)
+			.with_headers(vec!["Age", "Keys", "Size", "Delete command [ENTER]"])
+			.build();
+		let title = format!("Key age of segment {} ({} pairs)", segment, pairs.len());
+		let report = ReportComponent::new(self.config.clone(), &title, table, vec![20, 10, 10, 60]);
+		self.report = Some(report.with_actions(actions));
+	}
+
+	/// Delete keys whose timestamp segment is older than `days` in batched transactions
+	/// and rescan the keyspace. Returns the number of deleted keys.
+	pub async fn purge_database(
+		&mut self,
+		cf: CF,
+		name: &str,
+		path: &str,
+		segment: &str,
+		days: i64,
+	) -> usize {
+		let layout = match self.key_segment(segment) {
+			Some(layout) => layout,
+			None => {
+				self.set_err(format!("No key layout segment named {}", segment));
+				return 0;
+			}
+		};
+
+		let db_path = format!("{}:{}", name, path);
+		let now = now_timestamp();
+		let keys: Vec<_> = self
+			.scan_from_path(cf.clone(), &db_path)
+			.await
+			.into_iter()
+			.map(|(key, _)| key)
+			.filter(|key| is_older_than(key, layout.from, layout.to, now, days))
+			.collect();
+
+		let deleted = async {
+			let ds = open_datastore(&self.config, &db_path)?;
+			delete_keys(&ds, cf.clone(), &keys).await
+		};
+		let deleted = deleted.await;
+		match deleted {
+			Ok(_) => {
+				self.show_written(cf, name, path).await;
+				keys.len()
+			}
+			Err(err) => {
+				self.set_err(err.to_string());
+				0
+			}
+		}
+	}
+
+	/// Run the retention rules of the config and display the keys each of them expires.
+	/// The keys are only deleted when `apply` is set. Returns the number of expired keys.
+	pub async fn retention_database(&mut self, apply: bool) -> usize {
+		let rules = self.config.retention.clone();
+		if rules.is_empty() {
+			self.set_err("No retention rule configured".to_string());
+			return 0;
+		}
+		let mut items = vec![];
+		let mut total = 0;
+		for rule in rules.iter() {
+			let column = rule.column.clone().unwrap_or_else(|| "default".to_string());
+			let policy = format!("{} older than {} days", rule.segment, rule.days);
+			let (database, scanned, expired) = match run_retention(&self.config, rule, apply).await
+			{
+				Ok(outcome) => {
+					total += outcome.expired;
+					let (scanned, expired) = (outcome.scanned, outcome.expired);
+					(outcome.db_path, scanned.to_string(), expired.to_string())
+				}
+				Err(err) => (rule.database.clone(), "-".to_string(), err),
+			};
+			items.push(vec![database, column, policy, scanned, expired]);
+		}
+		let expired = if apply {
+			"Deleted"
+		} else {
+			"Expired"
+		};
+		let table = StatefulTable::default()
+			.with_items(items)
+			.with_headers(vec!["Database", "Column", "Rule", "Scanned", expired])
+			.build();
+		let title = match apply {
+			true => format!("Retention applied: {} keys deleted", total),
+			false => {
+				format!("Retention preview: {} keys expired, RETENTION=APPLY deletes them", total)
+			}
+		};
+		self.report = Some(ReportComponent::new(
+			self.config.clone(),
+			&title,
+			table,
+			vec![30, 15, 25, 10, 20],
+		));
+		if apply {
+			self.refresh_view().await;
+		}
+		total
+	}
+
+	/// Refresh the view when it displays the column family a copy wrote into
+	pub async fn show_copied(&mut self, db_path: &str, cf: &CF) {
+		if self.is_viewing(db_path, cf) {
+			self.refresh_view().await;
+		}
+	}
+
+	/// Rewrite keys matching `pattern` with `replacement`, optionally restricted to a segment
+	/// of the selected key layout. Without `confirm` only a preview of the rewrites is
+	/// displayed, its first row generates the confirmed command. Returns the number of
+	/// rewritten keys.
+	#[allow(clippy::too_many_arguments)]
+	pub async fn rewrite_database(
+		&mut self,
+		cf: CF,
+		name: &str,
+		path: &str,
+		pattern: &str,
+		replacement: &str,
+		segment: Option<&str>,
+		confirm: bool,
+	) -> usize {
+		let regex = match Regex::new(pattern) {
+			Ok(regex) => regex,
+			Err(err) => {
+				self.set_err(err.to_string());
+				return 0;
+			}
+		};
+		let range = match segment {
+			Some(segment) => match self.key_segment(segment) {
+				Some(layout) => Some((layout.from, layout.to)),
+				None => {
+					self.set_err(format!("No key layout segment named {}", segment));
+					return 0;
+				}
+			},
+			None => None,
+		};
+
+		let db_path = format!("{}:{}", name, path);
+		let pairs = self.scan_from_path(cf.clone(), &db_path).await;
+		let rewrites = match plan_rewrites(&pairs, &regex, replacement, range) {
+			Ok(rewrites) => rewrites,
+			Err(err) => {
+				self.set_err(err);
+				return 0;
+			}
+		};
+
+		if confirm {
+			let rewritten = async {
+				let ds = open_datastore(&self.config, &db_path)?;
+				apply_rewrites(&ds, cf.clone(), &rewrites, |_| {}).await
+			};
+			return match rewritten.await {
+				Ok(rewritten) => {
+					self.show_written(cf, name, path).await;
+					rewritten
+				}
+				Err(err) => {
+					self.set_err(err.to_string());
+					0
+				}
+			};
+		}
+
+		let mut command = match &cf {
+			Some(c) => format!("COLUMN={} ", String::from_utf8_lossy(c)),
+			None => String::new(),
+		};
+		command.push_str(&format!("REWRITE={} WITH={}", pattern, replacement));
+		if let Some(segment) = segment {
+			command.push_str(&format!(" SEGMENT={}", segment));
+		}
+		command.push_str(" CONFIRM");
+
+		let mut items = vec![vec![
+			String::new(),
+			format!("Execute {} rewrites [ENTER]", rewrites.len()),
+			String::new(),
+		]];
+		let mut actions = vec![Some(command)];
+		for (index, rewrite) in rewrites.iter().take(ANALYSIS_TOP_ROWS).enumerate() {
+			items.push(vec![
+				(index + 1).to_string(),
+				format!("{:?}", rewrite.from),
+				format!("{:?}", rewrite.to),
+			]);
+			actions.push(None);
+		}
+
+		let table = StatefulTable::default()
+			.with_items(items)
+			.with_headers(vec!["#", "Key", "Rewritten key"])
+			.build();
+		let title = format!("Rewrite preview ({} of {} keys)", rewrites.len(), pairs.len());
+		let report = ReportComponent::new(self.config.clone(), &title, table, vec![5, 45, 50]);
+		self.report = Some(report.with_actions(actions));
+		0
+	}
+
+	/// Count keys of the keyspace. `ESTIMATE` reads storage statistics instead of iterating
+	/// every key. Returns a status text with the accuracy of the count.
+	pub async fn count_database(
+		&mut self,
+		cf: CF,
+		name: &str,
+		path: &str,
+		mode: &CountMode,
+	) -> Option<String> {
+		let db_path = format!("{}:{}", name, path);
+		let tx = async { Datastore::open(&db_path)?.transaction(false).await };
+		let mut tx = match tx.await {
+			Ok(tx) => tx,
+			Err(err) => {
+				self.set_err(err.to_string());
+				return None;
+			}
+		};
+		let result = match mode {
+			CountMode::Estimate => tx.estimate_count(cf).await,
+			CountMode::Exact => tx.count(cf).await,
+		};
+
+		match result {
+			Ok(count) => Some(match (mode, name) {
+				(CountMode::Estimate, "rocksdb") => {
+					format!("~{} keys (estimated, includes keys not compacted yet)", count)
+				}
+				_ => format!("{} keys", count),
+			}),
+			Err(err) => {
+				self.set_err(err.to_string());
+				None
+			}
+		}
+	}
+
+	/// Capture the current state of the keyspace under `label`. Returns the number of
+	/// captured pairs.
+	pub async fn snapshot_database(
+		&mut self,
+		cf: CF,
+		name: &str,
+		path: &str,
+		label: &str,
+	) -> usize {
+		let db_path = format!("{}:{}", name, path);
+		let taken = async {
+			Datastore::open(&db_path)?.transaction(false).await?.snapshot(vec![cf.clone()]).await
+		};
+		let taken = taken.await;
+		match taken {
+			Ok(data) => {
+				let count = data.len();
+				let snapshot = Snapshot {
+					path: db_path,
+					cf,
+					data,
+				};
+				self.snapshots.insert(label.to_string(), snapshot);
+				self.account_snapshots();
+				count
+			}
+			Err(err) => {
+				self.set_err(err.to_string());
+				0
+			}
+		}
+	}
+
+	/// Take, list or read the point-in-time snapshots of SNAPSHOT. Returns the status of
+	/// the action.
+	pub async fn point_in_time_database(
+		&mut self,
+		cf: CF,
+		name: &str,
+		path: &str,
+		action: &SnapshotAction,
+	) -> Option<String> {
+		match action {
+			SnapshotAction::Create(label) => self.create_snapshot(cf, name, path, label).await,
+			SnapshotAction::List => {
+				self.list_snapshots();
+				None
+			}
+			SnapshotAction::Use(label) => self.use_snapshot(label.as_ref()).await,
+		}
+	}
+
+	/// Copy every column family of the database at one point in time under `label`. DIFF
+	/// compares it on column family `cf`.
+	async fn create_snapshot(
+		&mut self,
+		cf: CF,
+		name: &str,
+		path: &str,
+		label: &str,
+	) -> Option<String> {
+		let db_path = format!("{}:{}", name, path);
+		let taken = async {
+			let ds = Datastore::open(&db_path)?;
+			let cfs = ds
+				.column_families()?
+				.into_iter()
+				.map(|name| match name.as_str() {
+					"default" => None,
+					_ => Some(name.into_bytes()),
+				})
+				.collect();
+			ds.transaction(false).await?.snapshot(cfs).await
+		};
+		let taken = taken.await;
+		match taken {
+			Ok(data) => {
+				let text = format!("Snapshot {} taken ({} pairs)", label, data.len());
+				let snapshot = Snapshot {
+					path: db_path,
+					cf,
+					data,
+				};
+				self.snapshots.insert(label.to_string(), snapshot);
+				self.account_snapshots();
+				Some(text)
+			}
+			Err(err) => {
+				self.set_err(err.to_string());
+				None
+			}
+		}
+	}
+
+	/// Display the snapshots of the session, the used one is marked
+	fn list_snapshots(&mut self) {
+		let mut labels: Vec<_> = self.snapshots.keys().cloned().collect();
+		labels.sort_unstable();
+		let items = labels
+			.into_iter()
+			.map(|label| {
+				let snapshot = &self.snapshots[&label];
+				let used = self.used_snapshot.as_ref() == Some(&label);
+				let taken = snapshot.data.timestamp();
+				vec![
+					if used {
+						"*"
+					} else {
+						""
+					}
+					.to_string(),
+					label,
+					snapshot.path.clone(),
+					snapshot.data.column_families().len().to_string(),
+					snapshot.data.len().to_string(),
+					self.config.display.timestamp(taken).unwrap_or_else(|| taken.to_string()),
+				]
+			})
+			.collect();
+		let table = StatefulTable::default()
+			.with_items(items)
+			.with_headers(vec!["", "Name", "Database", "Columns", "Pairs", "Taken at"])
+			.build();
+		let title =
+			format!("Snapshots ({} in use)", self.used_snapshot.as_deref().unwrap_or("none"));
+		self.report = Some(ReportComponent::new(
+			self.config.clone(),
+			&title,
+			table,
+			vec![5, 15, 35, 10, 10, 25],
+		));
+	}
+
+	/// Read the scans of the database of snapshot `label` from it, `None` reads the live
+	/// state again. The displayed scan is re-run.
+	async fn use_snapshot(&mut self, label: Option<&String>) -> Option<String> {
+		let text = match label {
+			Some(label) => match self.snapshots.get(label) {
+				Some(snapshot) => format!("Scans of {} read snapshot {}", snapshot.path, label),
+				None => {
+					self.set_err(format!("No snapshot named {}", label));
+					return None;
+				}
+			},
+			None => "Scans read the live database".to_string(),
+		};
+		self.used_snapshot = label.cloned();
+		self.refresh_view().await;
+		Some(text)
+	}
+
+	/// Diff the live state of a snapshotted keyspace against the snapshot `label`
+	pub async fn diff_database(&mut self, label: &str) {
+		let (path, cf) = match self.snapshots.get(label) {
+			Some(snapshot) => (snapshot.path.clone(), snapshot.cf.clone()),
+			None => return self.set_err(format!("No snapshot named {}", label)),
+		};
+		let key_display = self.config.key_display(&path, &cf);
+		let before = self.snapshots[label].data.iterate(&cf);
+		let pairs = self.scan_from_path(cf, &path).await;
+		let changes = diff_pairs(&before, &pairs);
+
+		let (mut added, mut removed, mut changed) = (0, 0, 0);
+		let items = changes
+			.iter()
+			.map(|change| match change {
+				PairChange::Added((key, value)) => {
+					added += 1;
+					vec!["+".to_string(), key_display.render(key), format!("{:?}", value)]
+				}
+				PairChange::Removed((key, value)) => {
+					removed += 1;
+					vec!["-".to_string(), key_display.render(key), format!("{:?}", value)]
+				}
+				PairChange::Changed {
+					key,
+					before,
+					after,
+				} => {
+					changed += 1;
+					vec![
+						"~".to_string(),
+						key_display.render(key),
+						format!("{:?} -> {:?}", before, after),
+					]
+				}
+			})
+			.collect();
+
+		let table = StatefulTable::default()
+			.with_items(items)
+			.with_headers(vec!["", "Key", "Value"])
+			.build();
+		let title = format!(
+			"Diff against snapshot {} ({} added, {} removed, {} changed)",
+			label, added, removed, changed
+		);
+		self.report =
+			Some(ReportComponent::new(self.config.clone(), &title, table, vec![5, 40, 55]));
+	}
 
-use super::PreviewComponent;
+	/// Display the comparison of two keyspaces in three panes
+	pub fn show_keyspace_diff(&mut self, diff: &BackgroundDiff) {
+		let (left, right) = (diff.side(true), diff.side(false));
+		let key_display = self.config.key_display(&diff.left, diff.job.left_cf());
+		self.report = None;
+		self.diff = Some(DiffComponent::new(
+			self.config.clone(),
+			&left,
+			&right,
+			diff.job.changes(),
+			key_display,
+		));
+	}
 
-enum Focus {
-	Table,
-	Container,
-}
+	/// List the keys of a finished OVERLAP in both keyspaces, those whose values differ
+	/// first. Returns the summary of the comparison.
+	pub fn show_keyspace_overlap(&mut self, diff: &BackgroundDiff) -> String {
+		let mut shared = diff.job.shared().unwrap_or_default().to_vec();
+		shared.sort_by_key(|s| s.same_value);
+		let different = shared.iter().filter(|s| !s.same_value).count();
+		let items = shared
+			.iter()
+			.enumerate()
+			.map(|(index, s)| {
+				let value = if s.same_value {
+					"same"
+				} else {
+					"different"
+				};
+				vec![(index + 1).to_string(), format_byte_literal(&s.key), value.to_string()]
+			})
+			.collect();
+		let table = StatefulTable::default()
+			.with_items(items)
+			.with_headers(vec!["#", "Key", "Value"])
+			.build();
+		let summary = format!(
+			"Compared {} keys, {} in both, {} with different values",
+			diff.job.compared(),
+			shared.len(),
+			different
+		);
+		let title =
+			format!("Overlap of {} and {} ({})", diff.side(true), diff.side(false), summary);
+		self.diff = None;
+		self.report =
+			Some(ReportComponent::new(self.config.clone(), &title, table, vec![10, 70, 20]));
+		summary
+	}
 
-pub struct DatabaseEditorComponent<'a> {
-	config: Config,
-	preview: PreviewComponent<'a>,
-	table: StatefulTable,
-	err: Option<String>,
-	pairs: Vec<KeyValuePair>,
-	focus: Focus,
-}
+	/// Display the storage options of every column family of the database
+	pub fn internals_database(&mut self, name: &str, path: &str) {
+		let db_path = format!("{}:{}", name, path);
+		let options = match Datastore::open(&db_path).and_then(|ds| ds.column_family_options()) {
+			Ok(options) => options,
+			Err(err) => return self.set_err(err.to_string()),
+		};
 
-fn build_table(pairs: Vec<KeyValuePair>) -> StatefulTable {
-	let mut items = vec![];
-	for (index, (key, value)) in pairs.iter().enumerate() {
-		let index = format!("{:?}", index);
-		let key = format!("{:?}", key.to_vec());
-		let value = format!("{:?}", value.to_vec());
-		items.push(vec![index, key, value])
-	}
-	StatefulTable::default()
-		.with_items(items.to_vec())
-		.with_headers(vec!["#", "Key", "Value"])
-		.build()
-}
+		let items = options
+			.iter()
+			.map(|cf| {
+				vec![
+					cf.name.clone(),
+					cf.compression.clone(),
+					cf.comparator.clone(),
+					cf.merge_operator.clone().unwrap_or_else(|| "-".to_string()),
+					cf.block_size.map(|size| format_bytes(size as usize)).unwrap_or_default(),
+				]
+			})
+			.collect();
+		let table = StatefulTable::default()
+			.with_items(items)
+			.with_headers(vec!["Column", "Compression", "Comparator", "Merge operator", "Block"])
+			.build();
+		let title = format!("Internals of {}", db_path);
+		self.report = Some(ReportComponent::new(
+			self.config.clone(),
+			&title,
+			table,
+			vec![25, 20, 25, 20, 10],
+		));
+	}
 
-impl DatabaseEditorComponent<'_> {
-	async fn suffix_scan_from_path(
+	/// Attach a note to a key of a column family, or remove it with an empty note. The
+	/// displayed rows are marked again when the column family is displayed.
+	pub fn annotate_key(
 		&mut self,
 		cf: CF,
+		name: &str,
 		path: &str,
-		prefix: Vec<u8>,
-	) -> Vec<KeyValuePair> {
-		let mut result = vec![];
-		let ds = Datastore::new(path);
-		let tx = ds.transaction(false).await.unwrap();
-		let data = tx.suffix_iterate(cf, prefix).await;
-		self.clear_err();
-		match data {
-			Ok(pairs) => {
-				for pair in pairs {
-					result.push(pair.unwrap());
-				}
+		key: &str,
+		text: &str,
+	) -> Option<String> {
+		let bytes = match self.encode_key(key) {
+			Some(bytes) => bytes,
+			None => {
+				self.set_err(format!("Invalid KEY {}", key));
+				return None;
+			}
+		};
+		let note = KeyNote {
+			database: format!("{}:{}", name, path),
+			column: Self::column_name(&cf),
+			key: format_byte_literal(&bytes),
+			text: text.to_string(),
+		};
+		if let Err(err) = save_note(&notes_path(&self.config.path), note) {
+			return Some(format!("Failed to save note: {}", err));
+		}
+		if self.is_viewing(&format!("{}:{}", name, path), &cf) {
+			self.load_row_notes();
+			self.update_preview();
+		}
+		match text.is_empty() {
+			true => Some(format!("Note of {} removed", key)),
+			false => Some(format!("Note attached to {}", key)),
+		}
+	}
+
+	/// List the notes of every database containing a term. ENTER prepares the command
+	/// displaying the annotated key.
+	pub fn show_notes(&mut self, term: &str) -> usize {
+		let notes = search_notes(load_notes(&notes_path(&self.config.path)), term);
+		let items = notes
+			.iter()
+			.map(|n| {
+				let column = n.column.clone().unwrap_or_default();
+				vec![n.database.clone(), column, n.key.clone(), n.text.clone()]
+			})
+			.collect();
+		let actions = notes.iter().map(|n| Some(n.command_text())).collect();
+		let table = StatefulTable::default()
+			.with_items(items)
+			.with_headers(vec!["Database", "Column", "Key [ENTER]", "Note"])
+			.build();
+		let title = match term.is_empty() {
+			true => format!("Notes ({})", notes.len()),
+			false => format!("Notes containing {} ({})", term, notes.len()),
+		};
+		let report = ReportComponent::new(self.config.clone(), &title, table, vec![25, 15, 20, 40]);
+		self.report = Some(report.with_actions(actions));
+		notes.len()
+	}
+
+	/// List the commands of the session, most recent first
+	pub fn show_timeline(&mut self, entries: &[TimelineEntry]) {
+		let display = &self.config.display;
+		let items = entries.iter().rev().map(|entry| entry.cells(display)).collect();
+		let table = StatefulTable::default()
+			.with_items(items)
+			.with_headers(vec!["Time", "Duration", "Database", "Command", "Outcome"])
+			.build();
+		let title = format!("Session timeline ({} commands)", entries.len());
+		self.report = Some(ReportComponent::new(
+			self.config.clone(),
+			&title,
+			table,
+			vec![15, 10, 20, 30, 25],
+		));
+	}
+
+	/// Create or drop a column family, then display the column families of the database so
+	/// the list follows the change. Returns the status of the change.
+	pub fn column_families_database(
+		&mut self,
+		name: &str,
+		path: &str,
+		action: &CfAction,
+	) -> Option<String> {
+		let db_path = format!("{}:{}", name, path);
+		let ds = match Datastore::open(&db_path) {
+			Ok(ds) => ds,
+			Err(err) => {
+				self.set_err(err.to_string());
+				return None;
 			}
+		};
+		let changed = match action {
+			CfAction::Create(cf) => {
+				ds.create_column_family(cf).map(|_| Some(format!("Column family {} created", cf)))
+			}
+			CfAction::Drop(cf) => {
+				ds.drop_column_family(cf).map(|_| Some(format!("Column family {} dropped", cf)))
+			}
+			CfAction::List => Ok(None),
+		};
+		let names = changed.and_then(|text| Ok((text, ds.column_families()?)));
+		let (text, names) = match names {
+			Ok(names) => names,
 			Err(err) => {
 				self.set_err(err.to_string());
+				return None;
+			}
+		};
+
+		// Column families documented in the config are listed with their description and tags
+		let columns = self.config.database(&db_path).map(|d| d.columns.clone()).unwrap_or_default();
+		let items = names
+			.into_iter()
+			.map(|name| match columns.iter().find(|c| c.name == name) {
+				Some(column) => {
+					let description = column.description.clone().unwrap_or_default();
+					vec![name, description, column.tags.join(", ")]
+				}
+				None => vec![name, String::new(), String::new()],
+			})
+			.collect();
+		let table = StatefulTable::default()
+			.with_items(items)
+			.with_headers(vec!["Column", "Description", "Tags"])
+			.build();
+		let title = format!("Column families of {}", db_path);
+		let widths = vec![30, 50, 20];
+		self.report = Some(ReportComponent::new(self.config.clone(), &title, table, widths));
+		text
+	}
+
+	/// Display the runtime properties of the storage engine in the Metrics panel, refreshed
+	/// every `METRICS_INTERVAL` while the panel is open
+	pub fn metrics_database(&mut self, name: &str, path: &str) {
+		let db_path = format!("{}:{}", name, path);
+		let table = match Datastore::open(&db_path).and_then(|ds| ds.properties()) {
+			Ok(properties) => metrics_table(properties),
+			Err(err) => return self.set_err(err.to_string()),
+		};
+		let title = format!("Metrics of {}", db_path);
+		self.report = Some(ReportComponent::new(self.config.clone(), &title, table, vec![60, 40]));
+		self.metrics = Some((db_path, Instant::now()));
+	}
+
+	/// Record the memory held by the displayed rows and report
+	pub fn tick_memory(&self) {
+		let rows = pairs_size(&self.pairs) + cells_size(&self.table.items);
+		self.memory.record(MemoryComponent::Rows, rows);
+		let results = self.report.as_ref().map(|r| r.memory_size()).unwrap_or_default();
+		self.memory.record(MemoryComponent::Results, results);
+	}
+
+	/// Record the memory held by the session snapshots, once one is taken
+	fn account_snapshots(&self) {
+		let size = self.snapshots.values().map(|s| s.data.size()).sum();
+		self.memory.record(MemoryComponent::Snapshots, size);
+	}
+
+	/// Refresh the Metrics panel once the interval elapsed, stopped when the panel is closed
+	pub fn tick_metrics(&mut self) {
+		let (db_path, refreshed) = match (&self.metrics, &self.report) {
+			(Some((db_path, refreshed)), Some(_)) => (db_path.clone(), *refreshed),
+			_ => {
+				self.metrics = None;
+				return;
+			}
+		};
+		if refreshed.elapsed() < METRICS_INTERVAL {
+			return;
+		}
+		// A failed refresh keeps the last values, the database may be busy
+		if let Ok(properties) = Datastore::open(&db_path).and_then(|ds| ds.properties()) {
+			if let Some(report) = self.report.as_mut() {
+				report.set_table(metrics_table(properties));
 			}
 		}
-		result
+		self.metrics = Some((db_path, Instant::now()));
 	}
 
-	async fn prefix_scan_from_path(
+	/// Repeat the scan of the displayed pairs every `interval`. Returns false when no scan is
+	/// displayed.
+	pub fn watch_view(&mut self, interval: Duration) -> bool {
+		if self.view.is_none() {
+			return false;
+		}
+		self.watch = Some(Watch {
+			interval,
+			refreshed: Instant::now(),
+			added: HashSet::new(),
+			changed: HashSet::new(),
+			removed: 0,
+		});
+		true
+	}
+
+	pub fn stop_watch(&mut self) {
+		self.watch = None;
+	}
+
+	/// Re-run the watched scan once the interval elapsed and mark the rows added and changed
+	/// since the previous run. Returns a summary when the pairs changed.
+	pub async fn tick_watch(&mut self) -> Option<String> {
+		if self.watch.as_ref()?.refreshed.elapsed() < self.watch.as_ref()?.interval {
+			return None;
+		}
+		let before: HashMap<_, _> = self.pairs.iter().cloned().collect();
+		self.refresh_view().await;
+
+		let watch = self.watch.as_mut()?;
+		watch.refreshed = Instant::now();
+		let mut kept = 0;
+		for (index, (key, value)) in self.pairs.iter().enumerate() {
+			match before.get(key) {
+				Some(previous) => {
+					kept += 1;
+					if previous != value {
+						watch.changed.insert(index);
+					}
+				}
+				None => {
+					watch.added.insert(index);
+				}
+			}
+		}
+		watch.removed = before.len().saturating_sub(kept);
+		if watch.added.is_empty() && watch.changed.is_empty() && watch.removed == 0 {
+			return None;
+		}
+		Some(format!(
+			"{} added, {} removed, {} changed",
+			watch.added.len(),
+			watch.removed,
+			watch.changed.len()
+		))
+	}
+
+	/// Text marker and color of a corrupted row, or of a row added or changed by WATCH
+	fn row_mark(&self, index: usize) -> Option<(&'static str, Color)> {
+		if self.corrupted.contains(&index) {
+			return Some(("!", Color::Red));
+		}
+		if let Some(watch) = &self.watch {
+			if watch.added.contains(&index) {
+				return Some(("+", Color::Green));
+			}
+			if watch.changed.contains(&index) {
+				return Some(("~", Color::Cyan));
+			}
+		}
+		if self.notes.contains_key(&index) {
+			return Some(("*", Color::Yellow));
+		}
+		None
+	}
+
+	/// Decode `LAYOUT_SAMPLE_SIZE` pairs sampled across the whole displayed keyspace, not only
+	/// the displayed page, with the selected key and value layouts. Pairs are sampled once
+	/// per keyspace.
+	async fn show_layout_sample(&mut self) {
+		let view = match &self.view {
+			Some(view) => view.clone(),
+			None => return,
+		};
+		let sampled = self.sample.as_ref().filter(|s| s.path == view.path && s.cf == view.cf);
+		let pairs = match sampled {
+			Some(sample) => sample.pairs.clone(),
+			None => {
+				let pairs = self.scan_from_path(view.cf.clone(), &view.path).await;
+				sample_pairs(pairs, LAYOUT_SAMPLE_SIZE)
+			}
+		};
+
+		let (key, value) = (self.preview.key_template(), self.preview.value_template());
+		let decode = |template: &Option<LayoutTemplate>, bytes: &[u8]| match template {
+			Some(template) => template
+				.names()
+				.into_iter()
+				.zip(template.decode(bytes))
+				.map(|(name, value)| format!("{}: {}", name, value))
+				.collect::<Vec<_>>()
+				.join(", "),
+			None => "-".to_string(),
+		};
+		let items = pairs
+			.iter()
+			.map(|(k, v)| vec![format!("{:?}", k), decode(&key, k), decode(&value, v)])
+			.collect();
+		let table = StatefulTable::default()
+			.with_items(items)
+			.with_headers(vec!["Key", "Key layout", "Value layout"])
+			.build();
+		let name = |template: &Option<LayoutTemplate>| {
+			template.as_ref().map_or("none".to_string(), |t| t.name.clone())
+		};
+		let title =
+			format!("Layouts {} and {} on {} sampled pairs", name(&key), name(&value), pairs.len());
+		let report = ReportComponent::new(self.config.clone(), &title, table, vec![30, 35, 35]);
+		self.sample = Some(LayoutSample {
+			path: view.path,
+			cf: view.cf,
+			pairs,
+			report: Some(report),
+		});
+	}
+
+	/// Export the displayed pairs, decoded with the selected key and value layouts, to
+	/// `target` in `format`. Rows are streamed to the file. Returns the number of exported
+	/// rows.
+	pub fn export_view(
 		&mut self,
-		cf: CF,
-		path: &str,
-		prefix: Vec<u8>,
-	) -> Vec<KeyValuePair> {
-		let mut result = vec![];
-		let ds = Datastore::new(path);
-		let tx = ds.transaction(false).await.unwrap();
-		let data = tx.prefix_iterate(cf, prefix).await;
-		self.clear_err();
-		match data {
-			Ok(pairs) => {
-				for pair in pairs {
-					result.push(pair.unwrap());
+		target: &str,
+		format: ExportFormat,
+		order: ExportOrder,
+	) -> Option<usize> {
+		let (key_template, value_template) =
+			(self.preview.key_template(), self.preview.value_template());
+		let mut headers = vec!["#".to_string(), "Key".to_string(), "Value".to_string()];
+		for (label, template) in [("Key", &key_template), ("Value", &value_template)] {
+			if let Some(template) = template {
+				headers.extend(template.names().iter().map(|name| format!("{} {}", label, name)));
+			}
+		}
+
+		let key_display = self.key_display();
+		let export = || -> std::io::Result<usize> {
+			let file = BufWriter::new(File::create(target)?);
+			write_pair_table(file, format, order, headers, &self.pairs, |index, (key, value)| {
+				let key_cell = key_display.render(key);
+				let mut row = vec![index.to_string(), key_cell, format!("{:?}", value)];
+				if let Some(template) = &key_template {
+					row.extend(template.decode(key));
+				}
+				if let Some(template) = &value_template {
+					row.extend(template.decode(value));
 				}
+				row
+			})
+		};
+		match export() {
+			Ok(rows) => Some(rows),
+			Err(err) => {
+				self.set_err(format!("Unable to export view: {}", err));
+				None
+			}
+		}
+	}
+
+	/// Read the records of `source` and encode them with the selected key and value layouts
+	/// into an import job of column family `cf`
+	pub fn import_file(&mut self, cf: CF, source: &str, format: ExportFormat) -> Option<ImportJob> {
+		let records = fs::read_to_string(source)
+			.map_err(|err| err.to_string())
+			.and_then(|data| parse_records(&data, format));
+		match records {
+			Ok(records) => {
+				let (key_template, value_template) =
+					(self.preview.key_template(), self.preview.value_template());
+				Some(ImportJob::new(cf, records, key_template.as_ref(), value_template.as_ref()))
 			}
 			Err(err) => {
-				self.set_err(err.to_string());
+				self.set_err(format!("Unable to import {}: {}", source, err));
+				None
 			}
 		}
-		result
 	}
 
-	async fn scan_from_path(&mut self, cf: CF, path: &str) -> Vec<KeyValuePair> {
-		let mut result = vec![];
-		let ds = Datastore::new(path);
-		let tx = ds.transaction(false).await.unwrap();
-		let data = tx.iterate(cf).await;
-		self.clear_err();
-		match data {
-			Ok(pairs) => {
-				for pair in pairs {
-					result.push(pair.unwrap());
-				}
+	/// Pairs with random segment values matching the selected layouts, written in the
+	/// background like an import
+	pub fn generate_job(&mut self, cf: CF, count: usize, seed: Option<u64>) -> Option<ImportJob> {
+		let (key_template, value_template) =
+			(self.preview.key_template(), self.preview.value_template());
+		match generate_pairs(count, seed, key_template.as_ref(), value_template.as_ref()) {
+			Ok(pairs) => Some(ImportJob::from_pairs(cf, pairs)),
+			Err(err) => {
+				self.set_err(format!("Unable to generate pairs: {}", err));
+				None
 			}
+		}
+	}
+
+	/// Show the column family an import wrote into, with the malformed records of the file
+	pub async fn show_imported(&mut self, name: &str, path: &str, job: &ImportJob) {
+		self.show_written(job.cf().clone(), name, path).await;
+		if job.errors.is_empty() {
+			return;
+		}
+		let items: Vec<_> =
+			job.errors.iter().map(|(row, err)| vec![row.to_string(), err.clone()]).collect();
+		let table = StatefulTable::default()
+			.with_items(items)
+			.with_headers(vec!["Record", "Error"])
+			.build();
+		let title = format!(
+			"Malformed records ({} of {} skipped)",
+			job.errors.len(),
+			job.errors.len() + job.total()
+		);
+		self.report = Some(ReportComponent::new(self.config.clone(), &title, table, vec![10, 90]));
+	}
+
+	/// Search of column family `cf` of the database at `db_path` with the selected layouts
+	pub fn search_job(&self, db_path: &str, cf: CF, term: &str, scope: SearchScope) -> SearchJob {
+		let (key_template, value_template) =
+			(self.preview.key_template(), self.preview.value_template());
+		let key_display = self.config.key_display(db_path, &cf);
+		SearchJob::new(cf, term, scope, key_display, key_template, value_template)
+	}
+
+	/// Search of every column family of a database, listed when the search starts
+	fn global_search(
+		&self,
+		name: &str,
+		path: &str,
+		term: &str,
+		scope: SearchScope,
+	) -> Result<GlobalSearch, Error> {
+		let db_path = format!("{}:{}", name, path);
+		let names = Datastore::open(&db_path)?.column_families()?;
+		let jobs = names
+			.into_iter()
+			.map(|name| match name.as_str() {
+				"default" => None,
+				_ => Some(name.into_bytes()),
+			})
+			.map(|cf| self.search_job(&db_path, cf, term, scope))
+			.collect();
+		Ok(GlobalSearch::new(term, jobs))
+	}
+
+	/// Search of every column family of the database for SEARCH=ALL
+	pub fn global_search_job(
+		&mut self,
+		name: &str,
+		path: &str,
+		term: &str,
+		scope: SearchScope,
+	) -> Option<GlobalSearch> {
+		match self.global_search(name, path, term, scope) {
+			Ok(search) => Some(search),
 			Err(err) => {
 				self.set_err(err.to_string());
+				None
 			}
 		}
+	}
 
-		result
+	/// Search of every configured database for SEARCH=DATABASES. Databases which cannot
+	/// be opened are reported unreachable instead of failing the search.
+	pub fn databases_search_job(&self, term: &str, scope: SearchScope) -> DatabasesSearch {
+		let databases = self
+			.config
+			.databases
+			.iter()
+			.flat_map(|(name, dbs)| dbs.iter().map(move |db| (name.clone(), db.path.clone())))
+			.map(|(name, path)| {
+				let search = self.global_search(&name, &path, term, scope);
+				DatabaseSearch::new(name, path, search)
+			})
+			.collect();
+		DatabasesSearch::new(term, databases)
 	}
 
-	pub async fn prefix_scan_database(&mut self, cf: CF, name: &str, path: &str, prefix: Vec<u8>) {
-		let db_path = format!("{}:{}", name, path);
-		let pairs = self.prefix_scan_from_path(cf, &db_path, prefix).await;
-		self.table = build_table(pairs.to_vec());
-		self.pairs = pairs;
+	/// Rows of the matches of a global search grouped by column family, and the number of
+	/// matches of each column family with at least one
+	fn global_search_rows(search: &GlobalSearch) -> (Vec<Vec<String>>, Vec<String>) {
+		let mut items = vec![];
+		let mut counts = vec![];
+		for job in search.jobs().iter().filter(|job| !job.hits().is_empty()) {
+			let column = column_label(job.cf());
+			counts.push(format!("{} {}", column, job.hits().len()));
+			for hit in job.hits() {
+				let (key, value) = &hit.pair;
+				let key = job.key_display().render(key);
+				items.push(vec![column.clone(), key, format!("{:?}", value)]);
+			}
+		}
+		(items, counts)
 	}
 
-	pub async fn suffix_scan_database(&mut self, cf: CF, name: &str, path: &str, suffix: Vec<u8>) {
-		let db_path = format!("{}:{}", name, path);
-		let pairs = self.suffix_scan_from_path(cf, &db_path, suffix).await;
-		self.table = build_table(pairs.to_vec());
-		self.pairs = pairs;
+	/// Display the matches of a SEARCH=ALL grouped by column family. Column families
+	/// without match are left out.
+	pub fn show_global_search(&mut self, name: &str, path: &str, search: &GlobalSearch) {
+		let (items, counts) = Self::global_search_rows(search);
+		let table = StatefulTable::default()
+			.with_items(items)
+			.with_headers(vec!["Column", "Key", "Value"])
+			.build();
+		let title = match counts.is_empty() {
+			true => format!("No match of \"{}\" in {}:{}", search.term(), name, path),
+			false => {
+				format!(
+					"Matches of \"{}\" in {}:{} ({})",
+					search.term(),
+					name,
+					path,
+					counts.join(", ")
+				)
+			}
+		};
+		self.report =
+			Some(ReportComponent::new(self.config.clone(), &title, table, vec![20, 40, 40]));
 	}
 
-	pub async fn scan_database(&mut self, cf: CF, name: &str, path: &str) {
+	/// Display the matches of a SEARCH=DATABASES grouped by database then column family,
+	/// with the databases which could not be searched in the title
+	pub fn show_databases_search(&mut self, search: &DatabasesSearch) {
+		let mut items = vec![];
+		let mut counts = vec![];
+		let mut unreachable = vec![];
+		for database in search.databases() {
+			let db_path = format!("{}:{}", database.name(), database.path());
+			let rows = match database.search() {
+				Some(search) => Self::global_search_rows(search).0,
+				None => {
+					unreachable.push(db_path);
+					continue;
+				}
+			};
+			if !rows.is_empty() {
+				counts.push(format!("{} {}", db_path, rows.len()));
+			}
+			items.extend(rows.into_iter().map(|row| [vec![db_path.clone()], row].concat()));
+		}
+		let table = StatefulTable::default()
+			.with_items(items)
+			.with_headers(vec!["Database", "Column", "Key", "Value"])
+			.build();
+		let mut title = match counts.is_empty() {
+			true => format!("No match of \"{}\" in any database", search.term()),
+			false => format!("Matches of \"{}\" in {}", search.term(), counts.join(", ")),
+		};
+		if !unreachable.is_empty() {
+			title.push_str(&format!(", unreachable: {}", unreachable.join(", ")));
+		}
+		self.report =
+			Some(ReportComponent::new(self.config.clone(), &title, table, vec![20, 15, 35, 30]));
+	}
+
+	/// Show the pairs matching a finished or cancelled SEARCH with the match highlighted.
+	/// The hits are not a scan, they are not refreshed after writes.
+	pub fn show_search(&mut self, job: &SearchJob) {
+		self.clear_err();
+		self.pairs = job.hits().iter().map(|hit| hit.pair.clone()).collect();
+		self.expiries.clear();
+		self.highlights = job
+			.hits()
+			.iter()
+			.enumerate()
+			.map(|(row, hit)| (row, (hit.column, hit.start, hit.end)))
+			.collect();
+		self.rebuild_table();
+		self.table.state = TableState::default();
+		self.update_preview();
+		self.more = None;
+		self.report = None;
+		self.searched = Some(job.term().to_string());
+		self.view = None;
+	}
+
+	/// Expire every displayed pair in `seconds` by writing TTL metadata in batched
+	/// transactions. Returns the number of updated keys.
+	pub async fn expire_view(&mut self, cf: CF, name: &str, path: &str, seconds: i64) -> usize {
+		if matches!(self.view.as_ref().map(|v| &v.scan), Some(ViewScan::Derived(_))) {
+			self.set_err("Derived views are read only".to_string());
+			return 0;
+		}
 		let db_path = format!("{}:{}", name, path);
-		let pairs = self.scan_from_path(cf, &db_path).await;
-		self.table = build_table(pairs.to_vec());
-		self.pairs = pairs;
+		let ds = match Datastore::open(&db_path) {
+			Ok(ds) => ds,
+			Err(err) => {
+				self.err = Some(err.to_string());
+				return 0;
+			}
+		};
+		let expires_at = now_timestamp() + seconds;
+		for batch in self.pairs.chunks(WRITE_BATCH_SIZE) {
+			let result = async {
+				let mut tx = ds.transaction(true).await?;
+				for (key, _) in batch {
+					set_expiry(&mut tx, cf.clone(), key.clone(), expires_at).await?;
+				}
+				tx.commit().await
+			};
+			if let Err(err) = result.await {
+				self.err = Some(err.to_string());
+				return 0;
+			}
+		}
+		self.pairs.len()
+	}
+
+	/// Persist the keyspace filtered by `prefix` and `contains` as the view `label`,
+	/// projected with the selected key and value layouts
+	pub fn define_view(
+		&mut self,
+		cf: CF,
+		name: &str,
+		path: &str,
+		label: &str,
+		prefix: Option<String>,
+		contains: Option<String>,
+	) -> bool {
+		let view = DerivedView {
+			name: label.to_string(),
+			database: format!("{}:{}", name, path),
+			column: cf.map(|c| String::from_utf8_lossy(&c).to_string()),
+			prefix,
+			contains,
+			key_template: self.preview.key_template().map(|t| t.name),
+			value_template: self.preview.value_template().map(|t| t.name),
+		};
+		match save_view(&views_path(&self.config.path), view) {
+			Ok(()) => true,
+			Err(err) => {
+				self.set_err(err.to_string());
+				false
+			}
+		}
+	}
+
+	/// Browse the persisted view `label` like a keyspace, its rows are computed on display
+	pub async fn browse_view(&mut self, label: &str) {
+		let views = load_views(&views_path(&self.config.path));
+		match views.into_iter().find(|v| v.name == label) {
+			Some(derived) => {
+				let view = View {
+					path: derived.database.clone(),
+					cf: derived.column.as_ref().map(|c| c.as_bytes().to_vec()),
+					scan: ViewScan::Derived(derived),
+				};
+				self.show_view(view).await;
+			}
+			None => self.set_err(format!("No view named {}", label)),
+		}
+	}
+
+	/// Command prepared by a key binding or picked from the displayed report, if any
+	pub fn take_generated_command(&mut self) -> Option<String> {
+		self.generated.take().or_else(|| self.report.as_mut().and_then(|r| r.take_action()))
 	}
 
 	fn pairs_empty(&self) -> bool {
@@ -142,13 +2753,81 @@ impl DatabaseEditorComponent<'_> {
 	}
 
 	fn generate_label(&self) -> String {
-		format!("Editor ({} key-value pairs)", self.pairs.len())
+		let read_only = matches!(self.view.as_ref().map(|v| &v.scan), Some(ViewScan::Derived(_)));
+		let marker = if read_only {
+			text_marker("[RO] ")
+		} else {
+			""
+		};
+		let order = match self.order {
+			Order::Asc => "",
+			Order::Desc => ", descending",
+		};
+		let page = match (self.page.offset, self.page.limit) {
+			(0, None) => String::new(),
+			(offset, None) => format!(", offset {}", offset),
+			(offset, Some(limit)) => format!(", offset {} limit {}", offset, limit),
+		};
+		let more = match self.more {
+			Some(_) => format!(", {} loads more", get_key_char(self.config.key_config.load_more)),
+			None => String::new(),
+		};
+		if let Some(term) = &self.searched {
+			return format!("Search \"{}\" ({} key-value pairs)", term, self.pairs.len());
+		}
+		let snapshot = match (&self.view, &self.used_snapshot) {
+			(Some(view), Some(label)) if self.used_snapshot(&view.path).is_some() => {
+				format!(", snapshot {}", label)
+			}
+			_ => String::new(),
+		};
+		let watch = match &self.watch {
+			Some(watch) => format!(
+				", watching every {:?}: {} added, {} removed, {} changed",
+				watch.interval,
+				watch.added.len(),
+				watch.removed,
+				watch.changed.len()
+			),
+			None => String::new(),
+		};
+		format!(
+			"{}Editor ({} key-value pairs{}{}{}{}{})",
+			marker,
+			self.pairs.len(),
+			snapshot,
+			order,
+			page,
+			more,
+			watch
+		)
 	}
 
 	pub fn new(config: Config) -> Self {
 		DatabaseEditorComponent {
 			preview: PreviewComponent::new(config.clone()),
+			value_form: ValueFormComponent::new(config.clone()),
 			pairs: vec![],
+			report: None,
+			diff: None,
+			hex: None,
+			snapshots: HashMap::new(),
+			used_snapshot: None,
+			view: None,
+			order: Order::default(),
+			page: Page::default(),
+			more: None,
+			expiries: vec![],
+			expired_filter: None,
+			corrupted: HashSet::new(),
+			notes: HashMap::new(),
+			metrics: None,
+			watch: None,
+			sample: None,
+			generated: None,
+			searched: None,
+			highlights: HashMap::new(),
+			memory: MemoryAccount::default(),
 			table: StatefulTable::default(),
 			focus: Focus::Container,
 			err: None,
@@ -168,9 +2847,16 @@ impl DatabaseEditorComponent<'_> {
 		match self.table.state.selected() {
 			Some(selected) if !self.pairs_empty() => {
 				let pair = Some(self.pairs[selected].clone());
-				self.preview.set_pair(pair)
+				self.preview.set_pair(pair);
+				self.preview.set_expiry(self.expiries.get(selected).copied().flatten());
+				self.preview.set_note(self.notes.get(&selected).cloned());
+				self.preview.set_key_display(self.key_display());
+			}
+			_ => {
+				self.preview.set_pair(None);
+				self.preview.set_expiry(None);
+				self.preview.set_note(None);
 			}
-			_ => self.preview.set_pair(None),
 		}
 	}
 
@@ -202,7 +2888,155 @@ impl DatabaseEditorComponent<'_> {
 	}
 
 	pub async fn event(&mut self, key: Key) -> Result<EventState> {
+		// The form is modal, every key is consumed until it is saved or closed
+		if self.value_form.is_open() {
+			self.value_form.event(key).await?;
+			if let Some((key, value)) = self.value_form.take_saved() {
+				self.write_edited_value(key, value).await;
+			}
+			return Ok(EventState::Consumed);
+		}
+		if let Some(report) = self.report.as_mut() {
+			if key == self.config.key_config.escape {
+				self.report = None;
+				return Ok(EventState::Consumed);
+			}
+			return report.event(key).await;
+		}
+		if let Some(diff) = self.diff.as_mut() {
+			if key == self.config.key_config.escape {
+				self.diff = None;
+				return Ok(EventState::Consumed);
+			}
+			return diff.event(key).await;
+		}
+		if let Some(hex) = self.hex.as_mut() {
+			if key == self.config.key_config.escape && !hex.is_editing() {
+				self.hex = None;
+				return Ok(EventState::Consumed);
+			}
+			return hex.event(key).await;
+		}
+
+		let keys = &self.config.key_config;
+		let layout_key = [
+			keys.key_layout_up,
+			keys.key_layout_down,
+			keys.value_layout_up,
+			keys.value_layout_down,
+		]
+		.contains(&key);
 		if self.preview.event(key).await?.is_consumed() {
+			self.remember_selected_layout();
+			// Computed columns follow the selected layouts
+			let selected = self.table.state.selected();
+			self.rebuild_table();
+			self.table.state.select(selected);
+			if layout_key {
+				self.show_layout_sample().await;
+			}
+			return Ok(EventState::Consumed);
+		}
+
+		// The sample stays displayed while layouts are switched
+		if let Some(report) = self.sample.as_mut().and_then(|s| s.report.as_mut()) {
+			if key == self.config.key_config.escape {
+				self.sample.as_mut().unwrap().report = None;
+				return Ok(EventState::Consumed);
+			}
+			return report.event(key).await;
+		}
+
+		if key == self.config.key_config.toggle_order && self.view.is_some() {
+			self.set_order(self.order.toggle()).await;
+			return Ok(EventState::Consumed);
+		}
+
+		// The export command is prepared in the command editor so its path can be edited
+		if key == self.config.key_config.export && !self.pairs.is_empty() {
+			let path = format!("export-{}.json", now_timestamp());
+			self.generated = Some(format!("EXPORT FORMAT=json PATH={}", path));
+			return Ok(EventState::Consumed);
+		}
+
+		if key == self.config.key_config.hex_view
+			&& matches!(self.focus, Focus::Table)
+			&& !self.pairs_empty()
+		{
+			if let Some((key, value)) = self.table.state.selected().and_then(|i| self.pairs.get(i))
+			{
+				let title = format!("Hex {}", format_byte_literal(key));
+				let template = self.preview.value_template();
+				self.hex = Some(HexViewComponent::new(
+					self.config.clone(),
+					&title,
+					value.clone(),
+					template,
+				));
+			}
+			return Ok(EventState::Consumed);
+		}
+
+		if key == self.config.key_config.edit_value
+			&& matches!(self.focus, Focus::Table)
+			&& !self.pairs_empty()
+		{
+			self.edit_selected_value();
+			return Ok(EventState::Consumed);
+		}
+
+		// The bookmark of the selected key is prepared in the command editor to be named
+		if key == self.config.key_config.bookmark
+			&& matches!(self.focus, Focus::Table)
+			&& !self.pairs_empty()
+		{
+			let column = self.view.as_ref().and_then(|view| Self::column_name(&view.cf));
+			let column = column.map(|c| format!("COLUMN={} ", c)).unwrap_or_default();
+			if let Some((key, _)) = self.table.state.selected().and_then(|i| self.pairs.get(i)) {
+				let prefix = format_byte_literal(key);
+				self.generated = Some(format!("{}PREFIX={} BOOKMARK=", column, prefix));
+			}
+			return Ok(EventState::Consumed);
+		}
+
+		// The note of the selected key is prepared in the command editor, with its current text
+		if key == self.config.key_config.annotate
+			&& matches!(self.focus, Focus::Table)
+			&& !self.pairs_empty()
+		{
+			let column = self.view.as_ref().and_then(|view| Self::column_name(&view.cf));
+			let column = column.map(|c| format!("COLUMN={} ", c)).unwrap_or_default();
+			if let Some(index) = self.table.state.selected().filter(|i| *i < self.pairs.len()) {
+				let key = format_byte_literal(&self.pairs[index].0);
+				let text = self.notes.get(&index).cloned().unwrap_or_default();
+				self.generated = Some(format!("{}KEY={} NOTE=\"{}\"", column, key, text));
+			}
+			return Ok(EventState::Consumed);
+		}
+
+		// The deletion goes through the command editor and its confirmation prompt. The key
+		// is written in hex, text would be encoded with the selected key layout.
+		if key == self.config.key_config.delete_pair
+			&& matches!(self.focus, Focus::Table)
+			&& !self.pairs_empty()
+		{
+			let column = self.view.as_ref().and_then(|view| Self::column_name(&view.cf));
+			let column = column.map(|c| format!("COLUMN={} ", c)).unwrap_or_default();
+			if let Some((key, _)) = self.table.state.selected().and_then(|i| self.pairs.get(i)) {
+				let hex: String = key.iter().map(|b| format!("{:02x}", b)).collect();
+				self.generated = Some(format!("{}DELETE KEY=0x{}", column, hex));
+			}
+			return Ok(EventState::Consumed);
+		}
+
+		if key == self.config.key_config.load_more && self.more.is_some() {
+			self.load_more().await;
+			return Ok(EventState::Consumed);
+		}
+
+		let paging = [self.config.key_config.next_page, self.config.key_config.previous_page];
+		if paging.contains(&key) && self.view.is_some() && self.page.limit.is_some() {
+			self.turn_page(key == self.config.key_config.next_page).await;
 			return Ok(EventState::Consumed);
 		}
 
@@ -240,33 +3074,79 @@ impl RenderAbleComponent for DatabaseEditorComponent<'_> {
 			.constraints([Constraint::Percentage(100), Constraint::Percentage(0)])
 			.split(rect);
 
+		if let Some(report) = &self.report {
+			if self.err.is_none() {
+				return report.render(f, rect, focused);
+			}
+		}
+		if let Some(diff) = &self.diff {
+			if self.err.is_none() {
+				return diff.render(f, rect, focused);
+			}
+		}
+		if let Some(report) = self.sample.as_ref().and_then(|s| s.report.as_ref()) {
+			if self.err.is_none() {
+				return report.render(f, rect, focused);
+			}
+		}
+		if let Some(hex) = &self.hex {
+			if self.err.is_none() {
+				return hex.render(f, rect, focused);
+			}
+		}
+
 		if !self.pairs_empty() && self.err.is_none() {
 			if self.table.state.selected().is_some() && self.preview.pair().is_some() {
 				chunks = Layout::default()
 					.direction(Direction::Vertical)
 					.constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
 					.split(rect);
-				self.preview.render(f, chunks[1], focused).unwrap();
+				self.preview.render(f, chunks[1], focused)?;
 			}
 
 			let header_cells = self
 				.table
 				.headers
 				.iter()
-				.map(|h| Cell::from(*h).style(Style::default().fg(Color::Black)));
+				.map(|h| Cell::from(h.as_str()).style(Style::default().fg(Color::Black)));
 			let normal_style = Style::default().bg(Color::DarkGray);
 			let header = Row::new(header_cells).style(normal_style).height(1).bottom_margin(1);
 
-			let rows = self.table.items.iter().map(|item| {
+			let rows = self.table.items.iter().enumerate().map(|(index, item)| {
 				let height = item
 					.iter()
 					.map(|content| content.chars().filter(|c| *c == '\n').count())
 					.max()
 					.unwrap_or(0) + 1;
-				let cells = item.iter().map(|c| Cell::from(c.clone()));
-				Row::new(cells).height(height as u16).bottom_margin(1)
+				let mark = self.row_mark(index);
+				let highlight = self.highlights.get(&index);
+				let cells = item.iter().enumerate().map(|(column, c)| match (mark, column) {
+					(Some((marker, _)), 0) => Cell::from(format!("{}{}", text_marker(marker), c)),
+					_ => match highlight {
+						Some((cell, start, end)) if *cell == column => {
+							Cell::from(highlight_span(c, *start, *end))
+						}
+						_ => Cell::from(c.clone()),
+					},
+				});
+				let row = Row::new(cells).height(height as u16).bottom_margin(1);
+				match mark {
+					Some((_, color)) => row.style(Style::default().fg(color)),
+					None => row,
+				}
 			});
 
+			// Computed columns take up to 15% each, key and value share the rest
+			let computed = self.table.headers.len().saturating_sub(3) as u16;
+			let column = 60u16.checked_div(computed).map_or(0, |c| c.min(15));
+			let key = (95 - column * computed) * 35 / 95;
+			let mut widths = vec![
+				Constraint::Percentage(5),
+				Constraint::Percentage(key),
+				Constraint::Percentage(95 - column * computed - key),
+			];
+			widths.extend((0..computed).map(|_| Constraint::Percentage(column)));
+
 			let label = self.generate_label();
 			let table = Table::new(rows)
 				.header(header)
@@ -277,21 +3157,30 @@ impl RenderAbleComponent for DatabaseEditorComponent<'_> {
 						.fg(Color::Black)
 						.add_modifier(Modifier::BOLD),
 				)
-				.widths(&[
-					Constraint::Percentage(5),
-					Constraint::Percentage(35),
-					Constraint::Percentage(60),
-				]);
+				.highlight_symbol(selection_symbol())
+				.widths(&widths);
 			f.render_stateful_widget(table, chunks[0], &mut self.table.state.clone());
+			self.value_form.render(f, rect, focused)?;
 		} else {
 			let text =
 				self.err.clone().unwrap_or_else(|| "No data found in this database".to_string());
-			let not_found_widget = Paragraph::new(vec![
-				Spans::from(vec![Span::raw("")]),
-				Spans::from(vec![Span::raw(text)]),
-			])
-			.alignment(Alignment::Center)
-			.block(render_container("Editor", focused));
+			let mut lines =
+				vec![Spans::from(vec![Span::raw("")]), Spans::from(vec![Span::raw(text.clone())])];
+			// Backend errors are followed by their likely cause and remediation
+			if let Some(diagnosis) = self.err.as_deref().and_then(diagnose) {
+				lines.push(Spans::from(vec![Span::raw("")]));
+				lines.push(Spans::from(vec![Span::styled(
+					diagnosis.cause,
+					Style::default().add_modifier(Modifier::BOLD),
+				)]));
+				lines.push(Spans::from(vec![Span::raw(diagnosis.remediation)]));
+			}
+			let not_found_widget = Paragraph::new(lines)
+				.wrap(Wrap {
+					trim: true,
+				})
+				.alignment(Alignment::Center)
+				.block(render_container("Editor", focused));
 			f.render_widget(not_found_widget, chunks[0]);
 		};
 
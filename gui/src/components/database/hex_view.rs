@@ -0,0 +1,240 @@
+use std::cell::Cell;
+
+use anyhow::Result;
+use tui::{
+	backend::Backend,
+	layout::Rect,
+	style::{Modifier, Style},
+	text::{Span, Spans},
+	widgets::Paragraph,
+	Frame,
+};
+
+use crate::{
+	components::{render_container, RenderAbleComponent},
+	config::Config,
+	constants::HIGHLIGHT_COLOR,
+	events::{EventState, Key},
+	utils::{copy_to_clipboard, ByteLayout, LayoutTemplate},
+};
+
+/// Bytes of a row of the dump
+const ROW_WIDTH: usize = 16;
+/// Bytes skipped by the page keys
+const PAGE_WIDTH: usize = ROW_WIDTH * 16;
+
+/// Offset typed after the goto key, `0x` for hex or decimal
+fn parse_offset(input: &str) -> Option<usize> {
+	match input.strip_prefix("0x") {
+		Some(hex) => usize::from_str_radix(hex, 16).ok(),
+		None => input.parse().ok(),
+	}
+}
+
+/// Hex dump of a value: offsets, rows of 16 bytes and their ASCII. The cursor moves by
+/// byte or row, the bytes of the value layout field under the cursor are highlighted and
+/// a range selected from an anchor can be copied to the clipboard.
+pub struct HexViewComponent {
+	config: Config,
+	title: String,
+	bytes: Vec<u8>,
+	template: Option<LayoutTemplate>,
+	cursor: usize,
+	anchor: Option<usize>,
+	/// Offset being typed after the goto key
+	goto: Option<String>,
+	message: Option<String>,
+	/// First displayed row, moved by the render to keep the cursor visible
+	top: Cell<usize>,
+}
+
+impl HexViewComponent {
+	pub fn new(
+		config: Config,
+		title: &str,
+		bytes: Vec<u8>,
+		template: Option<LayoutTemplate>,
+	) -> Self {
+		HexViewComponent {
+			config,
+			title: title.to_string(),
+			bytes,
+			template,
+			cursor: 0,
+			anchor: None,
+			goto: None,
+			message: None,
+			top: Cell::new(0),
+		}
+	}
+
+	/// Whether an offset is being typed, ESC cancels it instead of closing the view
+	pub fn is_editing(&self) -> bool {
+		self.goto.is_some()
+	}
+
+	fn move_to(&mut self, offset: usize) {
+		self.cursor = offset.min(self.bytes.len().saturating_sub(1));
+	}
+
+	/// Bytes selected from the anchor to the cursor, or the byte under the cursor
+	fn selection(&self) -> (usize, usize) {
+		let anchor = self.anchor.unwrap_or(self.cursor);
+		(anchor.min(self.cursor), anchor.max(self.cursor) + 1)
+	}
+
+	/// Layout field of the value template holding the byte under the cursor
+	fn field(&self) -> Option<&ByteLayout> {
+		let template = self.template.as_ref()?;
+		template.layout.iter().find(|l| l.from <= self.cursor && self.cursor < l.to)
+	}
+
+	fn copy_selection(&mut self) {
+		let (start, end) = self.selection();
+		let bytes = match self.bytes.get(start..end) {
+			Some(bytes) => bytes,
+			None => return,
+		};
+		let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+		self.message = Some(match copy_to_clipboard(&format!("0x{}", hex)) {
+			Ok(()) => format!("{} bytes copied", end - start),
+			Err(err) => format!("Unable to copy: {}", err),
+		});
+		self.anchor = None;
+	}
+
+	fn goto_event(&mut self, key: Key) -> EventState {
+		match key {
+			k if k == self.config.key_config.escape => self.goto = None,
+			k if k == self.config.key_config.backspace => {
+				self.goto.as_mut().unwrap().pop();
+			}
+			k if k == self.config.key_config.enter => {
+				let input = self.goto.take().unwrap();
+				match parse_offset(&input) {
+					Some(offset) if offset < self.bytes.len() => self.move_to(offset),
+					_ => self.message = Some(format!("No byte at offset {}", input)),
+				}
+			}
+			Key::Char(c) if c.is_ascii_hexdigit() || c == 'x' => {
+				self.goto.as_mut().unwrap().push(c)
+			}
+			_ => {}
+		}
+		EventState::Consumed
+	}
+
+	pub async fn event(&mut self, key: Key) -> Result<EventState> {
+		if self.goto.is_some() {
+			return Ok(self.goto_event(key));
+		}
+		self.message = None;
+		let keys = &self.config.key_config;
+		match key {
+			k if k == keys.up => self.move_to(self.cursor.saturating_sub(ROW_WIDTH)),
+			k if k == keys.down => self.move_to(self.cursor + ROW_WIDTH),
+			k if k == keys.left => self.move_to(self.cursor.saturating_sub(1)),
+			k if k == keys.right => self.move_to(self.cursor + 1),
+			k if k == keys.previous_page => self.move_to(self.cursor.saturating_sub(PAGE_WIDTH)),
+			k if k == keys.next_page => self.move_to(self.cursor + PAGE_WIDTH),
+			k if k == keys.hex_goto => self.goto = Some(String::new()),
+			k if k == keys.hex_select => {
+				self.anchor = match self.anchor {
+					Some(_) => None,
+					None => Some(self.cursor),
+				}
+			}
+			k if k == keys.hex_copy => self.copy_selection(),
+			_ => return Ok(EventState::NotConsumed),
+		}
+		Ok(EventState::Consumed)
+	}
+
+	fn row(&self, offset: usize) -> Spans<'static> {
+		let (start, end) = self.selection();
+		let field = self.field().map(|l| l.from..l.to);
+		let style = |index: usize| {
+			let mut style = Style::default();
+			if field.as_ref().map_or(false, |f| f.contains(&index)) {
+				style = style.fg(HIGHLIGHT_COLOR);
+			}
+			if self.anchor.is_some() && (start..end).contains(&index) {
+				style = style.add_modifier(Modifier::UNDERLINED);
+			}
+			if index == self.cursor {
+				style = style.add_modifier(Modifier::REVERSED | Modifier::BOLD);
+			}
+			style
+		};
+
+		let row = &self.bytes[offset..(offset + ROW_WIDTH).min(self.bytes.len())];
+		let mut spans = vec![Span::raw(format!("{:08x}  ", offset))];
+		for column in 0..ROW_WIDTH {
+			let separator = if column == ROW_WIDTH / 2 - 1 {
+				"  "
+			} else {
+				" "
+			};
+			match row.get(column) {
+				Some(byte) => {
+					spans.push(Span::styled(format!("{:02x}", byte), style(offset + column)))
+				}
+				None => spans.push(Span::raw("  ")),
+			}
+			spans.push(Span::raw(separator));
+		}
+		spans.push(Span::raw(" |"));
+		for (column, byte) in row.iter().enumerate() {
+			let c = match byte.is_ascii_graphic() || *byte == b' ' {
+				true => *byte as char,
+				false => '.',
+			};
+			spans.push(Span::styled(c.to_string(), style(offset + column)));
+		}
+		spans.push(Span::raw("|"));
+		Spans::from(spans)
+	}
+
+	fn status(&self) -> String {
+		if let Some(input) = &self.goto {
+			return format!("Go to offset: {}_", input);
+		}
+		if let Some(message) = &self.message {
+			return message.clone();
+		}
+		let mut status = format!("0x{:x} of {} bytes", self.cursor, self.bytes.len());
+		if let Some(field) = self.field() {
+			status.push_str(&format!(", {} {}", field.name, field.variant.to_string()));
+		}
+		if self.anchor.is_some() {
+			let (start, end) = self.selection();
+			status.push_str(&format!(", {} selected", end - start));
+		}
+		status
+	}
+}
+
+impl RenderAbleComponent for HexViewComponent {
+	fn render<B: Backend>(
+		&self,
+		f: &mut Frame<B>,
+		rect: Rect,
+		focused: bool,
+	) -> Result<(), anyhow::Error> {
+		let visible = rect.height.saturating_sub(2).max(1) as usize;
+		let cursor_row = self.cursor / ROW_WIDTH;
+		let top = self.top.get().min(cursor_row).max((cursor_row + 1).saturating_sub(visible));
+		self.top.set(top);
+
+		let rows = (0..self.bytes.len())
+			.step_by(ROW_WIDTH)
+			.skip(top)
+			.take(visible)
+			.map(|offset| self.row(offset))
+			.collect::<Vec<_>>();
+		let title = format!("{} ({})", self.title, self.status());
+		let content = Paragraph::new(rows).block(render_container(&title, focused));
+		f.render_widget(content, rect);
+		Ok(())
+	}
+}
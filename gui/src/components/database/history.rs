@@ -0,0 +1,128 @@
+use anyhow::Result;
+use tui::{
+	backend::Backend,
+	layout::{Constraint, Rect},
+	style::{Color, Modifier, Style},
+	widgets::{Cell, Clear, Row, Table, TableState},
+	Frame,
+};
+
+use crate::{
+	components::{render_container, RenderAbleComponent},
+	config::Config,
+	constants::HIGHLIGHT_COLOR,
+	events::{EventState, Key},
+	ui::selection_symbol,
+	utils::{search_history, HistoryEntry},
+};
+
+/// Popup searching the command history. Typed text narrows the commands, ENTER picks the
+/// selected command and ESC closes the popup.
+pub struct HistoryComponent {
+	config: Config,
+	entries: Option<Vec<HistoryEntry>>,
+	query: String,
+	state: TableState,
+	picked: Option<String>,
+}
+
+impl HistoryComponent {
+	pub fn new(config: Config) -> Self {
+		HistoryComponent {
+			config,
+			entries: None,
+			query: String::new(),
+			state: TableState::default(),
+			picked: None,
+		}
+	}
+
+	pub fn open(&mut self, entries: Vec<HistoryEntry>) {
+		self.entries = Some(entries);
+		self.query.clear();
+		self.state.select(Some(0));
+		self.picked = None;
+	}
+
+	pub fn is_open(&self) -> bool {
+		self.entries.is_some()
+	}
+
+	/// Take the command picked from the popup once it is closed
+	pub fn take_picked(&mut self) -> Option<String> {
+		self.picked.take()
+	}
+
+	fn matches(&self) -> Vec<&HistoryEntry> {
+		match &self.entries {
+			Some(entries) => search_history(entries, &self.query),
+			None => vec![],
+		}
+	}
+
+	fn select(&mut self, index: usize) {
+		self.state.select(Some(index.min(self.matches().len().saturating_sub(1))));
+	}
+
+	pub async fn event(&mut self, key: Key) -> Result<EventState> {
+		let selected = self.state.selected().unwrap_or(0);
+		match key {
+			k if k == self.config.key_config.escape => self.entries = None,
+			k if k == self.config.key_config.enter => {
+				self.picked = self.matches().get(selected).map(|entry| entry.command.clone());
+				self.entries = None;
+			}
+			// Characters are typed in the query even when they are bound
+			Key::Char(c) => {
+				self.query.push(c);
+				self.select(0);
+			}
+			k if k == self.config.key_config.up => self.select(selected.saturating_sub(1)),
+			k if k == self.config.key_config.down => self.select(selected + 1),
+			k if k == self.config.key_config.backspace => {
+				self.query.pop();
+				self.select(0);
+			}
+			_ => return Ok(EventState::NotConsumed),
+		}
+		Ok(EventState::Consumed)
+	}
+}
+
+impl RenderAbleComponent for HistoryComponent {
+	fn render<B: Backend>(
+		&self,
+		f: &mut Frame<B>,
+		rect: Rect,
+		focused: bool,
+	) -> Result<(), anyhow::Error> {
+		if !self.is_open() {
+			return Ok(());
+		}
+		let width = rect.width.min(100);
+		let height = rect.height.min(16);
+		let area = Rect::new(rect.x + (rect.width - width) / 2, rect.y + 2, width, height)
+			.intersection(rect);
+
+		let header_cells = ["Command", "Database"]
+			.into_iter()
+			.map(|h| Cell::from(h).style(Style::default().fg(Color::Black)));
+		let header =
+			Row::new(header_cells).style(Style::default().bg(Color::DarkGray)).bottom_margin(1);
+		let rows = self.matches().into_iter().map(|entry| {
+			Row::new(vec![Cell::from(entry.command.clone()), Cell::from(entry.database.clone())])
+		});
+		let label = format!("History search: {} [ESC]", self.query);
+		let table = Table::new(rows)
+			.header(header)
+			.block(render_container(&label, focused))
+			.highlight_style(
+				Style::default().bg(HIGHLIGHT_COLOR).fg(Color::Black).add_modifier(Modifier::BOLD),
+			)
+			.highlight_symbol(selection_symbol())
+			.widths(&[Constraint::Percentage(65), Constraint::Percentage(35)]);
+		f.render_widget(Clear, area);
+		f.render_stateful_widget(table, area, &mut self.state.clone());
+		Ok(())
+	}
+}
@@ -0,0 +1,67 @@
+use tui::{
+	backend::Backend,
+	layout::{Constraint, Rect},
+	widgets::{Row, Table},
+	Frame,
+};
+
+use crate::{
+	components::{render_container, RenderAbleComponent},
+	config::Config,
+	utils::{format_duration, JobSummary},
+};
+
+/// Panel listing the running background jobs with their progress
+pub struct JobsComponent {
+	config: Config,
+	jobs: Vec<JobSummary>,
+}
+
+impl JobsComponent {
+	pub fn new(config: Config) -> Self {
+		JobsComponent {
+			config,
+			jobs: vec![],
+		}
+	}
+
+	pub fn set_jobs(&mut self, jobs: Vec<JobSummary>) {
+		self.jobs = jobs;
+	}
+
+	/// Rows taken by the panel, none when no job runs
+	pub fn height(&self) -> u16 {
+		match self.jobs.len() {
+			0 => 0,
+			jobs => jobs as u16 + 2,
+		}
+	}
+}
+
+impl RenderAbleComponent for JobsComponent {
+	fn render<B: Backend>(
+		&self,
+		f: &mut Frame<B>,
+		rect: Rect,
+		focused: bool,
+	) -> Result<(), anyhow::Error> {
+		let rows = self.jobs.iter().map(|job| {
+			Row::new(vec![
+				job.id.to_string(),
+				job.label.clone(),
+				job.progress.clone(),
+				format_duration(job.elapsed, false),
+			])
+		});
+		let title = format!("Jobs ({} cancels the last one)", self.config.key_config.cancel_job);
+		let widths = [
+			Constraint::Percentage(5),
+			Constraint::Percentage(35),
+			Constraint::Percentage(50),
+			Constraint::Percentage(10),
+		];
+		let table = Table::new(rows).block(render_container(&title, focused)).widths(&widths);
+		f.render_widget(table, rect);
+		Ok(())
+	}
+}
@@ -1,15 +1,31 @@
+mod changeset;
 mod command;
+mod confirm;
 mod container;
 mod database_explorer;
 mod database_selection;
+mod diff;
 mod editor;
+mod hex_view;
+mod history;
+mod jobs;
 mod preview;
+mod report;
 mod status;
+mod value_form;
 
+pub use changeset::*;
 pub use command::*;
+pub use confirm::*;
 pub use container::*;
 pub use database_explorer::*;
 pub use database_selection::*;
+pub use diff::*;
 pub use editor::*;
+pub use hex_view::*;
+pub use history::*;
+pub use jobs::*;
 pub use preview::*;
+pub use report::*;
 pub use status::*;
+pub use value_form::*;
@@ -1,5 +1,5 @@
 use anyhow::Result;
-use db::KeyValuePair;
+use db::{decoder_names, get_decoder, KeyValuePair};
 use tui::{
 	backend::Backend,
 	layout::{Constraint, Direction, Layout, Rect},
@@ -10,12 +10,12 @@ use tui::{
 };
 
 use crate::{
-	components::{render_container, RenderAbleComponent},
+	components::{panel_direction, render_container, RenderAbleComponent},
 	config::Config,
 	constants::HIGHLIGHT_COLOR,
 	events::{EventState, Key},
-	ui::StatefulList,
-	utils::{get_key_char, FromLayoutVariant},
+	ui::{selection_symbol, StatefulList},
+	utils::{describe_expiry, get_key_char, now_timestamp, KeyDisplay, LayoutTemplate},
 };
 
 pub struct PreviewComponent<'a> {
@@ -23,6 +23,14 @@ pub struct PreviewComponent<'a> {
 	pair: Option<KeyValuePair>,
 	key_layout: StatefulList<'a>,
 	value_layout: StatefulList<'a>,
+	/// Decoder of the value preview, the value layout is used when `None`
+	decoder: Option<String>,
+	/// Time the previewed key expires at
+	expiry: Option<i64>,
+	/// Note attached to the previewed key
+	note: Option<String>,
+	/// Rendering of the raw key when no key layout is selected
+	key_display: KeyDisplay,
 }
 
 fn build_list(config: Config) -> StatefulList<'static> {
@@ -45,6 +53,10 @@ impl PreviewComponent<'_> {
 			key_layout: build_list(config.clone()),
 			value_layout: build_list(config.clone()),
 			pair: None,
+			decoder: None,
+			expiry: None,
+			note: None,
+			key_display: config.key_display,
 			config,
 		}
 	}
@@ -57,25 +69,52 @@ impl PreviewComponent<'_> {
 		self.pair = pair;
 	}
 
+	pub fn set_expiry(&mut self, expiry: Option<i64>) {
+		self.expiry = expiry;
+	}
+
+	pub fn set_note(&mut self, note: Option<String>) {
+		self.note = note;
+	}
+
+	pub fn set_key_display(&mut self, key_display: KeyDisplay) {
+		self.key_display = key_display;
+	}
+
+	/// Template currently selected in the key layout list
+	pub fn key_template(&self) -> Option<LayoutTemplate> {
+		let selected = self.key_layout.state.selected()?;
+		self.config.templates.get(selected).cloned()
+	}
+
+	/// Template currently selected in the value layout list
+	pub fn value_template(&self) -> Option<LayoutTemplate> {
+		let selected = self.value_layout.state.selected()?;
+		self.config.templates.get(selected).cloned()
+	}
+
+	/// Select the templates by name, a list is left as is when its template is not found
+	pub fn select_templates(&mut self, key: Option<&str>, value: Option<&str>) {
+		let position = |name: Option<&str>| {
+			let name = name?;
+			self.config.templates.iter().position(|t| t.name == name)
+		};
+		if let Some(index) = position(key) {
+			self.key_layout.state.select(Some(index));
+		}
+		if let Some(index) = position(value) {
+			self.value_layout.state.select(Some(index));
+		}
+	}
+
 	fn deserialize_key(&self, layout: &StatefulList, raw: Vec<u8>) -> Vec<(String, String)> {
 		let selected_layout = layout.state.selected();
-		let default = ("*".to_string(), format!("{:?}", raw));
+		let default = ("*".to_string(), self.key_display.render(&raw));
 		let mut data = vec![default];
 		if let Some(layout) = selected_layout {
 			let index = layout;
 			let l = &self.config.templates[index];
-			let mut items: Vec<(String, String)> = vec![];
-			for item in l.layout.iter() {
-				let (start, end) = (item.from, std::cmp::min(item.to, raw.len()));
-				if start > end {
-					items.push((item.name.clone(), "OVERFLOW".to_string()));
-				} else {
-					let slice = raw[start..end].to_vec();
-					let converted = slice.from_variant(item.variant.clone());
-					items.push((item.name.clone(), converted));
-				}
-			}
-			data = items;
+			data = l.names().into_iter().zip(l.decode(&raw)).collect();
 		}
 		data
 	}
@@ -90,7 +129,8 @@ impl PreviewComponent<'_> {
 	) {
 		let list = List::new(layout.items.clone())
 			.block(render_container(title, focused))
-			.highlight_style(Style::default().fg(HIGHLIGHT_COLOR).add_modifier(Modifier::BOLD));
+			.highlight_style(Style::default().fg(HIGHLIGHT_COLOR).add_modifier(Modifier::BOLD))
+			.highlight_symbol(selection_symbol());
 
 		f.render_stateful_widget(list, rect, &mut layout.state.clone());
 	}
@@ -135,11 +175,22 @@ impl PreviewComponent<'_> {
 	}
 
 	fn render_key_preview<B: Backend>(&self, f: &mut Frame<B>, rect: Rect, focused: bool) {
+		let mut details = vec![];
+		if let Some(expires_at) = self.expiry {
+			details.push(describe_expiry(expires_at, now_timestamp()));
+		}
+		if let Some(note) = &self.note {
+			details.push(format!("note: {}", note));
+		}
+		let title = match details.is_empty() {
+			true => "Key Preview".to_string(),
+			false => format!("Key Preview ({})", details.join(", ")),
+		};
 		self.render_preview(
 			f,
 			rect,
 			focused,
-			"Key Preview",
+			&title,
 			&self.key_layout,
 			self.pair.clone().unwrap().0,
 		);
@@ -157,7 +208,46 @@ impl PreviewComponent<'_> {
 		);
 	}
 
+	/// Switch the value preview to the next registered decoder, then back to the layout
+	fn toggle_decoder(&mut self) {
+		let names = decoder_names();
+		let next = match &self.decoder {
+			Some(decoder) => names.iter().position(|n| n == decoder).map_or(0, |i| i + 1),
+			None => 0,
+		};
+		self.decoder = names.get(next).cloned();
+	}
+
+	fn render_decoded_value<B: Backend>(
+		&self,
+		f: &mut Frame<B>,
+		rect: Rect,
+		focused: bool,
+		decoder: &str,
+	) {
+		let value = self.pair.clone().unwrap().1;
+		let decoded = match get_decoder(decoder) {
+			Some(decode) => decode(&value).map_err(|err| err.to_string()),
+			None => Err(format!("No decoder named {}", decoder)),
+		};
+		let text = match decoded {
+			Ok(document) => serde_json::to_string_pretty(&document).unwrap_or_default(),
+			Err(err) => err,
+		};
+		let toggle = get_key_char(self.config.key_config.toggle_decoder);
+		let title = format!("Value Preview ({}) [{}]", decoder, toggle);
+		let content = Paragraph::new(text)
+			.wrap(Wrap {
+				trim: false,
+			})
+			.block(render_container(&title, focused));
+		f.render_widget(content, rect);
+	}
+
 	fn render_value_preview<B: Backend>(&self, f: &mut Frame<B>, rect: Rect, focused: bool) {
+		if let Some(decoder) = &self.decoder {
+			return self.render_decoded_value(f, rect, focused, decoder);
+		}
 		self.render_preview(
 			f,
 			rect,
@@ -186,6 +276,10 @@ impl PreviewComponent<'_> {
 				self.value_layout.next();
 				return Ok(EventState::Consumed);
 			}
+			k if k == self.config.key_config.toggle_decoder => {
+				self.toggle_decoder();
+				return Ok(EventState::Consumed);
+			}
 			_ => {}
 		}
 		Ok(EventState::NotConsumed)
@@ -199,15 +293,30 @@ impl RenderAbleComponent for PreviewComponent<'_> {
 		rect: Rect,
 		focused: bool,
 	) -> Result<(), anyhow::Error> {
-		let main_chunks = Layout::default()
-			.direction(Direction::Horizontal)
-			.constraints([
-				Constraint::Percentage(20),
-				Constraint::Percentage(30),
-				Constraint::Percentage(20),
-				Constraint::Percentage(30),
-			])
-			.split(rect);
+		// Narrow terminals show the key above the value
+		let main_chunks = match panel_direction(rect) {
+			Direction::Horizontal => Layout::default()
+				.direction(Direction::Horizontal)
+				.constraints([
+					Constraint::Percentage(20),
+					Constraint::Percentage(30),
+					Constraint::Percentage(20),
+					Constraint::Percentage(30),
+				])
+				.split(rect),
+			Direction::Vertical => Layout::default()
+				.direction(Direction::Vertical)
+				.constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+				.split(rect)
+				.into_iter()
+				.flat_map(|row| {
+					Layout::default()
+						.direction(Direction::Horizontal)
+						.constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+						.split(row)
+				})
+				.collect(),
+		};
 
 		self.render_key_layout(f, main_chunks[0], focused);
 		self.render_key_preview(f, main_chunks[1], focused);
@@ -0,0 +1,127 @@
+use anyhow::Result;
+use tui::{
+	backend::Backend,
+	layout::{Constraint, Rect},
+	style::{Color, Modifier, Style},
+	widgets::{Cell, Row, Table},
+	Frame,
+};
+
+use crate::{
+	components::{render_container, RenderAbleComponent},
+	config::Config,
+	constants::HIGHLIGHT_COLOR,
+	events::{EventState, Key},
+	ui::{selection_symbol, text_marker, StatefulTable},
+	utils::cells_size,
+};
+
+/// Read-only table displaying the result of an analysis command. Rows can carry
+/// a generated command which is picked with ENTER.
+pub struct ReportComponent {
+	config: Config,
+	title: String,
+	table: StatefulTable,
+	widths: Vec<Constraint>,
+	actions: Vec<Option<String>>,
+	picked: Option<String>,
+}
+
+impl ReportComponent {
+	pub fn new(config: Config, title: &str, table: StatefulTable, widths: Vec<u16>) -> Self {
+		ReportComponent {
+			config,
+			title: title.to_string(),
+			table,
+			widths: widths.into_iter().map(Constraint::Percentage).collect(),
+			actions: vec![],
+			picked: None,
+		}
+	}
+
+	/// Replace the rows, keeping the selected row
+	pub fn set_table(&mut self, table: StatefulTable) {
+		let selected = self.table.state.selected().filter(|i| *i < table.items.len());
+		self.table = table;
+		self.table.state.select(selected);
+	}
+
+	/// Approximate bytes of the rows
+	pub fn memory_size(&self) -> usize {
+		cells_size(&self.table.items)
+	}
+
+	/// Index of the selected row
+	pub fn selected(&self) -> Option<usize> {
+		self.table.state.selected()
+	}
+
+	/// Attach a command to each row, in row order
+	pub fn with_actions(mut self, actions: Vec<Option<String>>) -> Self {
+		self.actions = actions;
+		self
+	}
+
+	/// Take the command picked from the report, if any
+	pub fn take_action(&mut self) -> Option<String> {
+		self.picked.take()
+	}
+
+	pub async fn event(&mut self, key: Key) -> Result<EventState> {
+		match key {
+			k if k == self.config.key_config.up => {
+				self.table.previous();
+				Ok(EventState::Consumed)
+			}
+			k if k == self.config.key_config.down => {
+				self.table.next();
+				Ok(EventState::Consumed)
+			}
+			k if k == self.config.key_config.enter => {
+				let selected = self.table.state.selected();
+				match selected.and_then(|i| self.actions.get(i).cloned().flatten()) {
+					Some(action) => {
+						self.picked = Some(action);
+						Ok(EventState::Consumed)
+					}
+					None => Ok(EventState::NotConsumed),
+				}
+			}
+			_ => Ok(EventState::NotConsumed),
+		}
+	}
+}
+
+impl RenderAbleComponent for ReportComponent {
+	fn render<B: Backend>(
+		&self,
+		f: &mut Frame<B>,
+		rect: Rect,
+		focused: bool,
+	) -> Result<(), anyhow::Error> {
+		let header_cells = self
+			.table
+			.headers
+			.iter()
+			.map(|h| Cell::from(h.as_str()).style(Style::default().fg(Color::Black)));
+		let normal_style = Style::default().bg(Color::DarkGray);
+		let header = Row::new(header_cells).style(normal_style).height(1).bottom_margin(1);
+
+		let rows = self.table.items.iter().map(|item| {
+			let cells = item.iter().map(|c| Cell::from(c.clone()));
+			Row::new(cells).height(1)
+		});
+
+		let label = format!("{}{} [ESC]", text_marker("[RO] "), self.title);
+		let table = Table::new(rows)
+			.header(header)
+			.block(render_container(&label, focused))
+			.highlight_style(
+				Style::default().bg(HIGHLIGHT_COLOR).fg(Color::Black).add_modifier(Modifier::BOLD),
+			)
+			.highlight_symbol(selection_symbol())
+			.widths(&self.widths);
+		f.render_stateful_widget(table, rect, &mut self.table.state.clone());
+		Ok(())
+	}
+}
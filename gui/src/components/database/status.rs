@@ -28,6 +28,10 @@ impl<'a> StatusComponent<'a> {
 		self.text = text;
 	}
 
+	pub fn text(&self) -> String {
+		self.text.content.to_string()
+	}
+
 	pub fn reset(&mut self) {
 		self.text = Span::raw(DEFAULT_STATUS_TEXT);
 	}
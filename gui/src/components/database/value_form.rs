@@ -0,0 +1,191 @@
+use anyhow::Result;
+use tui::{
+	backend::Backend,
+	layout::{Constraint, Rect},
+	style::{Color, Modifier, Style},
+	widgets::{Cell, Clear, Row, Table, TableState},
+	Frame,
+};
+
+use crate::{
+	components::{render_container, RenderAbleComponent},
+	config::Config,
+	constants::HIGHLIGHT_COLOR,
+	events::{EventState, Key},
+	ui::selection_symbol,
+	utils::{decode_hex, LayoutTemplate},
+};
+
+/// Editable text of a segment of the value, or of the whole value in hex
+struct Field {
+	name: String,
+	text: String,
+}
+
+struct ValueForm {
+	key: Vec<u8>,
+	/// Template the fields are encoded with, none for the raw hex field
+	template: Option<LayoutTemplate>,
+	fields: Vec<Field>,
+	state: TableState,
+	err: Option<String>,
+}
+
+/// Fields of the segments of `value`, checksum segments excepted as they are computed on
+/// save. None when the value doesn't match the template, its segments can't be written
+/// back to the same bytes.
+fn segment_fields(template: &LayoutTemplate, value: &[u8]) -> Option<Vec<Field>> {
+	let fields = template
+		.layout
+		.iter()
+		.filter(|layout| layout.checksum.is_none())
+		.map(|layout| {
+			let bytes = value.get(layout.from..layout.to.min(value.len()))?;
+			let text = layout.variant.decode_text(bytes)?;
+			Some(Field {
+				name: layout.name.clone(),
+				text,
+			})
+		})
+		.collect::<Option<Vec<_>>>()?;
+	let lookup = |name: &str| fields.iter().find(|f| f.name == name).map(|f| f.text.clone());
+	match template.encode(lookup) {
+		Ok(encoded) if encoded == value => Some(fields),
+		_ => None,
+	}
+}
+
+/// Popup editing the value of the selected pair. Every segment of the selected value
+/// layout is a field, values which don't match the layout are edited as raw hex. UP and
+/// DOWN move between fields, ENTER saves the value and ESC closes the popup.
+pub struct ValueFormComponent {
+	config: Config,
+	form: Option<ValueForm>,
+	saved: Option<(Vec<u8>, Vec<u8>)>,
+}
+
+impl ValueFormComponent {
+	pub fn new(config: Config) -> Self {
+		ValueFormComponent {
+			config,
+			form: None,
+			saved: None,
+		}
+	}
+
+	pub fn open(&mut self, key: Vec<u8>, value: &[u8], template: Option<LayoutTemplate>) {
+		let fields = template.as_ref().and_then(|t| segment_fields(t, value));
+		let (template, fields) = match fields {
+			Some(fields) => (template, fields),
+			None => {
+				let hex: String = value.iter().map(|b| format!("{:02x}", b)).collect();
+				let field = Field {
+					name: "hex".to_string(),
+					text: format!("0x{}", hex),
+				};
+				(None, vec![field])
+			}
+		};
+		let mut state = TableState::default();
+		state.select(Some(0));
+		self.form = Some(ValueForm {
+			key,
+			template,
+			fields,
+			state,
+			err: None,
+		});
+		self.saved = None;
+	}
+
+	pub fn is_open(&self) -> bool {
+		self.form.is_some()
+	}
+
+	/// Take the key and the encoded value once the form is saved
+	pub fn take_saved(&mut self) -> Option<(Vec<u8>, Vec<u8>)> {
+		self.saved.take()
+	}
+
+	fn encode(form: &ValueForm) -> Result<Vec<u8>, String> {
+		let text = |name: &str| form.fields.iter().find(|f| f.name == name).map(|f| f.text.clone());
+		match &form.template {
+			Some(template) => template.encode(text),
+			None => {
+				let hex = text("hex").unwrap_or_default();
+				decode_hex(&hex).ok_or_else(|| format!("Invalid hex {}", hex))
+			}
+		}
+	}
+
+	pub async fn event(&mut self, key: Key) -> Result<EventState> {
+		let form = match self.form.as_mut() {
+			Some(form) => form,
+			None => return Ok(EventState::NotConsumed),
+		};
+		let selected = form.state.selected().unwrap_or(0);
+		let last = form.fields.len().saturating_sub(1);
+		match key {
+			k if k == self.config.key_config.escape => self.form = None,
+			k if k == self.config.key_config.enter => match Self::encode(form) {
+				Ok(value) => {
+					self.saved = Some((form.key.clone(), value));
+					self.form = None;
+				}
+				Err(err) => form.err = Some(err),
+			},
+			// Characters are typed in the field even when they are bound
+			Key::Char(c) => form.fields[selected].text.push(c),
+			k if k == self.config.key_config.up => {
+				form.state.select(Some(selected.saturating_sub(1)))
+			}
+			k if k == self.config.key_config.down => {
+				form.state.select(Some((selected + 1).min(last)))
+			}
+			k if k == self.config.key_config.backspace => {
+				form.fields[selected].text.pop();
+			}
+			_ => return Ok(EventState::NotConsumed),
+		}
+		Ok(EventState::Consumed)
+	}
+}
+
+impl RenderAbleComponent for ValueFormComponent {
+	fn render<B: Backend>(
+		&self,
+		f: &mut Frame<B>,
+		rect: Rect,
+		focused: bool,
+	) -> Result<(), anyhow::Error> {
+		let form = match &self.form {
+			Some(form) => form,
+			None => return Ok(()),
+		};
+		let width = rect.width.min(80);
+		let height = rect.height.min(form.fields.len() as u16 + 4);
+		let area = Rect::new(rect.x + (rect.width - width) / 2, rect.y + 2, width, height)
+			.intersection(rect);
+
+		let rows = form.fields.iter().map(|field| {
+			Row::new(vec![Cell::from(field.name.clone()), Cell::from(field.text.clone())])
+		});
+		let label = match (&form.err, &form.template) {
+			(Some(err), _) => format!("Edit value: {}", err),
+			(None, Some(template)) => {
+				format!("Edit value ({}) [ENTER save, ESC cancel]", template.name)
+			}
+			(None, None) => "Edit value (raw hex) [ENTER save, ESC cancel]".to_string(),
+		};
+		let table = Table::new(rows)
+			.block(render_container(&label, focused))
+			.highlight_style(
+				Style::default().bg(HIGHLIGHT_COLOR).fg(Color::Black).add_modifier(Modifier::BOLD),
+			)
+			.highlight_symbol(selection_symbol())
+			.widths(&[Constraint::Percentage(30), Constraint::Percentage(70)]);
+		f.render_widget(Clear, area);
+		f.render_stateful_widget(table, area, &mut form.state.clone());
+		Ok(())
+	}
+}
@@ -0,0 +1,86 @@
+use tui::{
+	buffer::Buffer,
+	layout::{Constraint, Rect},
+	widgets::{Row, Table, Widget},
+};
+
+use crate::{config::Config, events::Key};
+
+use super::{render_container, Panel};
+
+/// Panel listing the key bindings of the config
+pub struct KeymapPanel {
+	config: Config,
+}
+
+impl KeymapPanel {
+	pub fn new(config: Config) -> Self {
+		KeymapPanel {
+			config,
+		}
+	}
+}
+
+impl Panel for KeymapPanel {
+	fn title(&self) -> &str {
+		"Keys"
+	}
+
+	fn key(&self) -> Key {
+		Key::Char('?')
+	}
+
+	fn render(&self, area: Rect, buf: &mut Buffer, focused: bool) {
+		let keys = &self.config.key_config;
+		let bindings = [
+			(keys.enter, "Enter focused section"),
+			(keys.escape, "Escape from focused section"),
+			(keys.up, "Move up, or focus the section above"),
+			(keys.down, "Move down, or focus the section below"),
+			(keys.left, "Focus the section on the left"),
+			(keys.right, "Focus the section on the right"),
+			(keys.database_select_up, "Scroll up databases"),
+			(keys.database_select_down, "Scroll down databases"),
+			(keys.key_layout_up, "Scroll up key byte layout"),
+			(keys.key_layout_down, "Scroll down key byte layout"),
+			(keys.value_layout_up, "Scroll up value byte layout"),
+			(keys.value_layout_down, "Scroll down value byte layout"),
+			(keys.home_tab, "Switch to home"),
+			(keys.database_tab, "Switch to databases"),
+			(keys.layout_tab, "Switch to layouts"),
+			(keys.stats_tab, "Switch to database statistics"),
+			(keys.bookmarks_tab, "Switch to bookmarks"),
+			(keys.toggle_order, "Toggle ascending/descending key order"),
+			(keys.next_page, "Next page of a LIMIT scan"),
+			(keys.previous_page, "Previous page of a LIMIT scan"),
+			(keys.load_more, "Load the next keys of a LIMIT scan"),
+			(keys.export, "Prepare an EXPORT of the displayed pairs"),
+			(keys.edit_value, "Edit the value of the selected pair"),
+			(keys.bookmark, "Prepare a BOOKMARK of the selected key"),
+			(keys.annotate, "Prepare a NOTE of the selected key"),
+			(keys.delete_pair, "Prepare a DELETE of the selected key"),
+			(keys.toggle_decoder, "Switch the value preview between layout and decoders"),
+			(keys.hex_view, "Open the hex dump of the selected value"),
+			(keys.hex_goto, "Go to an offset of the hex dump"),
+			(keys.hex_select, "Start or clear a selection in the hex dump"),
+			(keys.hex_copy, "Copy the selected bytes of the hex dump"),
+			(keys.cancel_job, "Cancel the most recent background job"),
+			(keys.history_search, "Search the command history"),
+			(keys.search, "Filter the databases of the explorer"),
+			(keys.command_mode, "Write a new command"),
+			(keys.toggle_sidebar, "Show or hide the sidebar of narrow terminals"),
+			(keys.relative_time, "Show timestamps and durations relative to now"),
+			(keys.new_workspace, "Open a new workspace tab"),
+			(keys.next_workspace, "Switch to the next workspace tab"),
+			(keys.close_workspace, "Close the workspace tab"),
+			(keys.save_config, "Save the config edited in the File tab"),
+			(keys.quit, "Quit"),
+		];
+		let rows = bindings
+			.iter()
+			.map(|(key, description)| Row::new(vec![key.to_string(), description.to_string()]));
+		let widths = [Constraint::Percentage(20), Constraint::Percentage(80)];
+		let table = Table::new(rows).block(render_container("Keys", focused)).widths(&widths);
+		Widget::render(table, area, buf);
+	}
+}
@@ -0,0 +1,48 @@
+use tui::{
+	buffer::Buffer,
+	layout::{Constraint, Rect},
+	widgets::{Row, Table, Widget},
+};
+
+use crate::{
+	events::Key,
+	utils::{format_bytes, memory_usage},
+};
+
+use super::{render_container, Panel};
+
+/// Panel of the approximate memory held by the loaded rows, reports and snapshots of every
+/// workspace, to tune LIMIT and snapshots on small machines
+#[derive(Default)]
+pub struct MemoryPanel {
+	usage: Vec<(String, usize)>,
+}
+
+impl Panel for MemoryPanel {
+	fn title(&self) -> &str {
+		"Memory"
+	}
+
+	fn key(&self) -> Key {
+		Key::Char('M')
+	}
+
+	fn render(&self, area: Rect, buf: &mut Buffer, focused: bool) {
+		let total: usize = self.usage.iter().map(|(_, bytes)| bytes).sum();
+		let rows = self
+			.usage
+			.iter()
+			.map(|(label, bytes)| Row::new(vec![label.clone(), format_bytes(*bytes)]))
+			.chain([Row::new(vec!["Total".to_string(), format_bytes(total)])]);
+		let widths = [Constraint::Percentage(40), Constraint::Percentage(60)];
+		let table = Table::new(rows)
+			.header(Row::new(vec!["Component", "Approximate size"]))
+			.block(render_container("Memory", focused))
+			.widths(&widths);
+		Widget::render(table, area, buf);
+	}
+
+	fn tick(&mut self) {
+		self.usage = memory_usage().into_iter().map(|(c, b)| (c.label().to_string(), b)).collect();
+	}
+}
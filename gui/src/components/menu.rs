@@ -12,6 +12,7 @@ use crate::{
 	config::Config,
 	constants::{Focus, HIGHLIGHT_COLOR},
 	events::{EventState, Key},
+	ui::selection_symbol,
 };
 
 use super::{container::render_container, RenderAbleComponent};
@@ -21,6 +22,10 @@ pub enum MenuItem {
 	Home,
 	Database,
 	Layout,
+	Stats,
+	Bookmarks,
+	/// Registered panel, by registration index
+	Panel(usize),
 }
 
 impl From<MenuItem> for usize {
@@ -29,12 +34,17 @@ impl From<MenuItem> for usize {
 			MenuItem::Home => 0,
 			MenuItem::Database => 1,
 			MenuItem::Layout => 2,
+			MenuItem::Stats => 3,
+			MenuItem::Bookmarks => 4,
+			MenuItem::Panel(index) => 5 + index,
 		}
 	}
 }
 
 pub struct MenuContainerComponent {
 	pub active_menu_item: MenuItem,
+	/// Title and key of the registered panel tabs
+	panels: Vec<(String, Key)>,
 	config: Config,
 }
 
@@ -44,6 +54,7 @@ impl MenuContainerComponent {
 	pub fn new(config: Config) -> Self {
 		MenuContainerComponent {
 			active_menu_item: DEFAULT_ACTIVE_TAB,
+			panels: vec![],
 			config,
 		}
 	}
@@ -53,9 +64,16 @@ impl MenuContainerComponent {
 			MenuItem::Home => Focus::HomeTabBody,
 			MenuItem::Database => Focus::DatabaseTabBody,
 			MenuItem::Layout => Focus::LayoutTabBody,
+			MenuItem::Stats => Focus::StatsTabBody,
+			MenuItem::Bookmarks => Focus::BookmarksTabBody,
+			MenuItem::Panel(_) => Focus::PanelBody,
 		}
 	}
 
+	pub fn set_panels(&mut self, panels: Vec<(String, Key)>) {
+		self.panels = panels;
+	}
+
 	pub fn set_active(&mut self, active_menu_item: MenuItem) {
 		self.active_menu_item = active_menu_item;
 	}
@@ -73,6 +91,18 @@ impl MenuContainerComponent {
 			self.set_active(MenuItem::Layout);
 			return Ok(EventState::Consumed);
 		}
+		if key == self.config.key_config.stats_tab {
+			self.set_active(MenuItem::Stats);
+			return Ok(EventState::Consumed);
+		}
+		if key == self.config.key_config.bookmarks_tab {
+			self.set_active(MenuItem::Bookmarks);
+			return Ok(EventState::Consumed);
+		}
+		if let Some(index) = self.panels.iter().position(|(_, k)| *k == key) {
+			self.set_active(MenuItem::Panel(index));
+			return Ok(EventState::Consumed);
+		}
 		Ok(EventState::NotConsumed)
 	}
 }
@@ -84,17 +114,38 @@ impl RenderAbleComponent for MenuContainerComponent {
 		area: Rect,
 		focused: bool,
 	) -> Result<(), anyhow::Error> {
-		let menu_titles = vec!["EDMA", "Home", "Database", "Layout", "Quit"];
+		let panel_titles = self.panels.iter().map(|(title, _)| title.as_str());
+		let menu_titles: Vec<_> = ["EDMA", "Home", "Database", "Layout", "Stats", "Bookmarks"]
+			.into_iter()
+			.chain(panel_titles)
+			.chain(["Quit"])
+			.collect();
+		// Titles start with the brand, menu items follow
+		let active = usize::from(self.active_menu_item) + 1;
+		let panels = 6..6 + self.panels.len();
 
 		let menu = menu_titles
 			.iter()
 			.enumerate()
 			.map(|(index, t)| {
+				let marker = if index == active {
+					selection_symbol()
+				} else {
+					""
+				};
 				if index == 0 {
 					Spans::from(vec![Span::styled(*t, Style::default().fg(HIGHLIGHT_COLOR))])
+				} else if panels.contains(&index) {
+					// Panel keys are not the first letter of the title
+					let (_, key) = &self.panels[index - panels.start];
+					Spans::from(vec![
+						Span::raw(marker),
+						Span::styled(format!("{} [{}]", t, key), Style::default().fg(Color::White)),
+					])
 				} else {
 					let (first, rest) = t.split_at(1);
 					Spans::from(vec![
+						Span::raw(marker),
 						Span::styled(
 							first,
 							Style::default().fg(HIGHLIGHT_COLOR).add_modifier(Modifier::UNDERLINED),
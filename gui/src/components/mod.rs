@@ -1,13 +1,25 @@
+mod bookmarks;
+mod config_panel;
 mod container;
 mod database;
 mod home;
+mod keymap;
+mod memory_panel;
 mod menu;
+mod panel;
+mod stats;
 mod template;
 mod traits;
 
+pub use bookmarks::*;
+pub use config_panel::*;
 pub use container::*;
 pub use database::*;
 pub use home::*;
+pub use keymap::*;
+pub use memory_panel::*;
 pub use menu::*;
+pub use panel::*;
+pub use stats::*;
 pub use template::*;
 pub use traits::*;
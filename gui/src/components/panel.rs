@@ -0,0 +1,93 @@
+use anyhow::Result;
+use tui::{backend::Backend, buffer::Buffer, layout::Rect, widgets::Widget, Frame};
+
+use crate::{
+	config::Config,
+	events::{EventState, Key},
+};
+
+/// Custom panel displayed as an extra menu tab. Panels draw into the frame buffer instead of
+/// a `Frame` so they can be stored as trait objects whatever the terminal backend.
+pub trait Panel {
+	/// Title of the menu tab
+	fn title(&self) -> &str;
+
+	/// Key switching to the panel tab from the menu
+	fn key(&self) -> Key;
+
+	fn render(&self, area: Rect, buf: &mut Buffer, focused: bool);
+
+	/// Handle a key event while the panel has the focus. Up moves the focus back to the menu
+	/// when the event is not consumed.
+	fn event(&mut self, _key: Key) -> Result<EventState> {
+		Ok(EventState::NotConsumed)
+	}
+
+	/// Run background work between key events
+	fn tick(&mut self) {}
+
+	/// Config changed by the panel, applied to the whole app once taken
+	fn take_config(&mut self) -> Option<Config> {
+		None
+	}
+}
+
+/// Panels keyed by id, kept in registration order
+#[derive(Default)]
+pub struct PanelRegistry {
+	panels: Vec<(String, Box<dyn Panel>)>,
+}
+
+impl PanelRegistry {
+	/// Register `panel` under `id`, replacing the panel already registered with this id
+	pub fn register(&mut self, id: &str, panel: Box<dyn Panel>) {
+		match self.panels.iter_mut().find(|(panel_id, _)| panel_id == id) {
+			Some((_, existing)) => *existing = panel,
+			None => self.panels.push((id.to_string(), panel)),
+		}
+	}
+
+	pub fn get(&self, index: usize) -> Option<&dyn Panel> {
+		self.panels.get(index).map(|(_, panel)| panel.as_ref())
+	}
+
+	pub fn get_mut(&mut self, index: usize) -> Option<&mut (dyn Panel + 'static)> {
+		self.panels.get_mut(index).map(|(_, panel)| panel.as_mut())
+	}
+
+	/// Title and key of every panel tab
+	pub fn tabs(&self) -> Vec<(String, Key)> {
+		self.panels.iter().map(|(_, panel)| (panel.title().to_string(), panel.key())).collect()
+	}
+
+	pub fn tick(&mut self) {
+		for (_, panel) in self.panels.iter_mut() {
+			panel.tick();
+		}
+	}
+
+	pub fn take_config(&mut self) -> Option<Config> {
+		self.panels.iter_mut().find_map(|(_, panel)| panel.take_config())
+	}
+}
+
+struct PanelWidget<'a> {
+	panel: &'a dyn Panel,
+	focused: bool,
+}
+
+impl Widget for PanelWidget<'_> {
+	fn render(self, area: Rect, buf: &mut Buffer) {
+		self.panel.render(area, buf, self.focused);
+	}
+}
+
+pub fn render_panel<B: Backend>(f: &mut Frame<B>, panel: &dyn Panel, area: Rect, focused: bool) {
+	f.render_widget(
+		PanelWidget {
+			panel,
+			focused,
+		},
+		area,
+	);
+}
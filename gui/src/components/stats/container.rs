@@ -0,0 +1,182 @@
+use anyhow::Result;
+use db::ColumnFamilyStats;
+use tui::{
+	backend::Backend,
+	layout::{Constraint, Layout, Rect},
+	Frame,
+};
+
+use crate::{
+	components::{metrics_table, panel_direction, RenderAbleComponent, ReportComponent},
+	config::Config,
+	events::{EventState, Key},
+	ui::StatefulTable,
+	utils::{format_bytes, StorageStats, StorageStatsRefresher},
+};
+
+enum Focus {
+	Container,
+	Inner,
+}
+
+fn count(value: Option<u64>) -> String {
+	value.map_or("-".to_string(), |value| value.to_string())
+}
+
+fn size(value: Option<u64>) -> String {
+	value.map_or("-".to_string(), |value| format_bytes(value as usize))
+}
+
+/// Row of a database, counters are totals of its column families
+fn database_row(db_path: &str, stats: &StorageStats) -> Vec<String> {
+	let cfs = &stats.stats.column_families;
+	vec![
+		db_path.to_string(),
+		count(cfs.iter().map(|cf| cf.keys).sum()),
+		format_bytes(stats.stats.disk_size as usize),
+		count(cfs.iter().map(|cf| cf.files).sum()),
+	]
+}
+
+fn column_family_row(cf: &ColumnFamilyStats) -> Vec<String> {
+	vec![format!("  {}", cf.name), count(cf.keys), size(cf.size), count(cf.files)]
+}
+
+/// Storage statistics of every configured database: keys, size on disk and SST files by
+/// column family, with the engine properties of the selected database
+pub struct StatsTabComponent {
+	config: Config,
+	refresher: StorageStatsRefresher,
+	storage: ReportComponent,
+	properties: ReportComponent,
+	/// Database of every row of the storage table
+	rows: Vec<String>,
+	/// Database whose properties are displayed
+	selected: Option<String>,
+	focus: Focus,
+}
+
+impl StatsTabComponent {
+	pub fn new(config: Config) -> Self {
+		StatsTabComponent {
+			storage: ReportComponent::new(
+				config.clone(),
+				"Storage",
+				StatefulTable::default()
+					.with_headers(vec!["Database", "Keys", "Size", "SST files"])
+					.build(),
+				vec![40, 20, 20, 20],
+			),
+			properties: ReportComponent::new(
+				config.clone(),
+				"Properties",
+				metrics_table(vec![]),
+				vec![60, 40],
+			),
+			config,
+			refresher: StorageStatsRefresher::default(),
+			rows: vec![],
+			selected: None,
+			focus: Focus::Container,
+		}
+	}
+
+	/// Refresh the statistics of the next configured database
+	pub fn tick(&mut self) {
+		if self.refresher.tick(&self.config) {
+			self.update();
+		}
+	}
+
+	/// Rebuild the storage table from the latest statistics, keeping the selected row
+	fn update(&mut self) {
+		let mut databases: Vec<_> = self.refresher.stats().iter().collect();
+		databases.sort_unstable_by_key(|(db_path, _)| *db_path);
+
+		let (mut rows, mut items) = (vec![], vec![]);
+		for (db_path, stats) in databases {
+			rows.push(db_path.clone());
+			items.push(database_row(db_path, stats));
+			for cf in stats.stats.column_families.iter() {
+				rows.push(db_path.clone());
+				items.push(column_family_row(cf));
+			}
+		}
+		self.rows = rows;
+		self.storage.set_table(
+			StatefulTable::default()
+				.with_items(items)
+				.with_headers(vec!["Database", "Keys", "Size", "SST files"])
+				.build(),
+		);
+		self.update_properties(true);
+	}
+
+	/// Display the properties of the database of the selected row. Properties of the same
+	/// database are only replaced when `refreshed`.
+	fn update_properties(&mut self, refreshed: bool) {
+		let db_path = self.storage.selected().and_then(|i| self.rows.get(i)).cloned();
+		let db_path = match db_path.or_else(|| self.rows.first().cloned()) {
+			Some(db_path) => db_path,
+			None => return,
+		};
+		let properties = match self.refresher.stats().get(&db_path) {
+			Some(stats) => stats.properties.clone(),
+			None => return,
+		};
+		if self.selected.as_ref() == Some(&db_path) {
+			if refreshed {
+				self.properties.set_table(metrics_table(properties));
+			}
+			return;
+		}
+		let title = format!("Properties of {}", db_path);
+		self.properties = ReportComponent::new(
+			self.config.clone(),
+			&title,
+			metrics_table(properties),
+			vec![60, 40],
+		);
+		self.selected = Some(db_path);
+	}
+
+	pub async fn event(&mut self, key: Key) -> Result<EventState> {
+		match self.focus {
+			Focus::Container => {
+				if key == self.config.key_config.enter {
+					self.focus = Focus::Inner;
+					return Ok(EventState::Consumed);
+				}
+			}
+			Focus::Inner => {
+				if key == self.config.key_config.escape {
+					self.focus = Focus::Container;
+					return Ok(EventState::Consumed);
+				}
+				if self.storage.event(key).await?.is_consumed() {
+					self.update_properties(false);
+					return Ok(EventState::Consumed);
+				}
+			}
+		}
+		Ok(EventState::NotConsumed)
+	}
+}
+
+impl RenderAbleComponent for StatsTabComponent {
+	fn render<B: Backend>(
+		&self,
+		f: &mut Frame<B>,
+		rect: Rect,
+		focused: bool,
+	) -> Result<(), anyhow::Error> {
+		let chunks = Layout::default()
+			.direction(panel_direction(rect))
+			.constraints([Constraint::Percentage(60), Constraint::Percentage(40)].as_ref())
+			.split(rect);
+
+		self.storage.render(f, chunks[0], focused)?;
+		self.properties.render(f, chunks[1], false)?;
+		Ok(())
+	}
+}
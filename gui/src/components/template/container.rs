@@ -1,14 +1,10 @@
 use crate::{
-	components::RenderAbleComponent,
+	components::{sidebar_layout, RenderAbleComponent},
 	config::Config,
 	events::{EventState, Key},
 };
 use anyhow::Result;
-use tui::{
-	backend::Backend,
-	layout::{Constraint, Direction, Layout, Rect},
-	Frame,
-};
+use tui::{backend::Backend, layout::Rect, widgets::Clear, Frame};
 
 use super::{TemplateExplorerComponent, TemplateLayoutComponent};
 
@@ -35,9 +31,16 @@ impl LayoutTabComponent<'_> {
 	}
 
 	pub async fn event(&mut self, key: Key) -> Result<EventState> {
+		if key == self.config.key_config.toggle_sidebar {
+			self.focus = match self.focus {
+				Focus::Explorer => Focus::Layout,
+				Focus::Layout => Focus::Explorer,
+			};
+			return Ok(EventState::Consumed);
+		}
 		match self.focus {
 			Focus::Explorer => {
-				if key == Key::Right {
+				if key == self.config.key_config.right {
 					self.focus = Focus::Layout;
 					return Ok(EventState::Consumed);
 				}
@@ -52,7 +55,7 @@ impl LayoutTabComponent<'_> {
 				Ok(EventState::NotConsumed)
 			}
 			Focus::Layout => {
-				if key == Key::Left {
+				if key == self.config.key_config.left {
 					self.focus = Focus::Explorer;
 					return Ok(EventState::Consumed);
 				}
@@ -74,23 +77,23 @@ impl RenderAbleComponent for LayoutTabComponent<'_> {
 		rect: Rect,
 		focused: bool,
 	) -> Result<(), anyhow::Error> {
-		let main_chunks = Layout::default()
-			.direction(Direction::Horizontal)
-			.constraints([Constraint::Percentage(20), Constraint::Percentage(80)])
-			.split(rect);
+		let (sidebar, main, overlay) = sidebar_layout(rect);
 		// let stack_chunks = Layout::default()
 		// 	.direction(Direction::Vertical)
 		// 	.constraints([Constraint::Length(3), Constraint::Length(main_chunks[0].height - 3)])
 		// 	.split(main_chunks[1]);
 
-		self.explorer.render(
-			f,
-			main_chunks[0],
-			focused && matches!(self.focus, Focus::Explorer),
-		)?;
-
 		// self.editor.render(f, stack_chunks[0], focused && matches!(self.focus, Focus::Editor))?;
-		self.layout.render(f, main_chunks[1], focused && matches!(self.focus, Focus::Layout))?;
+		self.layout.render(f, main, focused && matches!(self.focus, Focus::Layout))?;
+
+		// The overlay of narrow terminals is drawn over the layout
+		let explorer_focused = matches!(self.focus, Focus::Explorer);
+		if !overlay || explorer_focused {
+			if overlay {
+				f.render_widget(Clear, sidebar);
+			}
+			self.explorer.render(f, sidebar, focused && explorer_focused)?;
+		}
 		Ok(())
 	}
 }
@@ -3,7 +3,7 @@ use crate::{
 	config::Config,
 	constants::HIGHLIGHT_COLOR,
 	events::{EventState, Key},
-	ui::StatefulList,
+	ui::{selection_symbol, StatefulList},
 };
 use anyhow::Result;
 use tui::{
@@ -88,7 +88,8 @@ impl<'a> RenderAbleComponent for TemplateExplorerComponent<'a> {
 	) -> Result<(), anyhow::Error> {
 		let list = List::new(self.list.items.clone())
 			.block(render_container("Templates", focused))
-			.highlight_style(Style::default().fg(HIGHLIGHT_COLOR).add_modifier(Modifier::BOLD));
+			.highlight_style(Style::default().fg(HIGHLIGHT_COLOR).add_modifier(Modifier::BOLD))
+			.highlight_symbol(selection_symbol());
 
 		f.render_stateful_widget(list, rect, &mut self.list.state.clone());
 		Ok(())
@@ -12,7 +12,7 @@ use crate::{
 	config::Config,
 	constants::HIGHLIGHT_COLOR,
 	events::{EventState, Key},
-	ui::StatefulTable,
+	ui::{selection_symbol, StatefulTable},
 	utils::{ByteLayout, LayoutTemplate},
 };
 
@@ -76,8 +76,10 @@ impl RenderAbleComponent for TemplateLayoutComponent {
 		focused: bool,
 	) -> Result<(), anyhow::Error> {
 		if let Some(t) = &self.table {
-			let header_cells =
-				t.headers.iter().map(|h| Cell::from(*h).style(Style::default().fg(Color::Black)));
+			let header_cells = t
+				.headers
+				.iter()
+				.map(|h| Cell::from(h.as_str()).style(Style::default().fg(Color::Black)));
 			let normal_style = Style::default().bg(Color::DarkGray);
 			let header = Row::new(header_cells).style(normal_style).height(1).bottom_margin(1);
 
@@ -95,6 +97,7 @@ impl RenderAbleComponent for TemplateLayoutComponent {
 				.header(header)
 				.block(render_container("Layout", focused))
 				.highlight_style(Style::default().fg(HIGHLIGHT_COLOR).add_modifier(Modifier::BOLD))
+				.highlight_symbol(selection_symbol())
 				.widths(&[
 					Constraint::Percentage(5),
 					Constraint::Percentage(20),
@@ -1,16 +1,108 @@
-use std::{collections::HashMap, fs, path::Path};
+use std::{
+	collections::HashMap,
+	fs,
+	panic::{self, AssertUnwindSafe},
+	path::Path,
+	time::Duration,
+};
 
+use db::{bincode_decoder, protobuf_decoder, register_decoder, BincodeType, WriteLimit, CF};
+use regex::bytes::Regex;
 use serde_json::Value;
 use structopt::StructOpt;
 
 use crate::{
 	events::Key,
-	utils::{get_absolute_path_buf, sanitize, ByteLayout, LayoutTemplate, LayoutVariant},
+	utils::{
+		column_label, discover_databases, get_absolute_path, get_absolute_path_buf,
+		inferred_templates_path, is_date_format, parse_byte_literal, parse_utc_offset, sanitize,
+		ByteLayout, Checksum, ChecksumAlgorithm, ComputedColumn, DisplayFormat, KeyDisplay,
+		LayoutTemplate, LayoutVariant, RowTemplate,
+	},
 };
 
 #[derive(Clone, Debug)]
 pub struct DatabaseConfig {
 	pub path: String,
+	/// Name used by the DB command to address the database
+	pub alias: Option<String>,
+	/// Rate limit of the writes made by EDMA to the database
+	pub write_limit: WriteLimit,
+	/// Comparator ordering the displayed keys, RocksDB keys are already ordered by the
+	/// comparator of the database
+	pub comparator: Option<String>,
+	/// Seconds values of a RocksDB TTL database live, their write time suffix is stripped
+	/// before decoding and appended by SET
+	pub value_ttl: Option<i64>,
+	/// Named key ranges listed under the database in the explorer, sorted by name
+	pub partitions: Vec<PartitionConfig>,
+	/// Documented column families listed under the database in the explorer, sorted by name
+	pub columns: Vec<ColumnConfig>,
+	/// Found under a `discover` root instead of listed in `databases`
+	pub discovered: bool,
+}
+
+/// # Partition
+/// Virtual keyspace of the keys of `column` starting with `prefix`, for databases which
+/// multiplex record types in one column family. Opening it selects its own layouts.
+#[derive(Clone, Debug)]
+pub struct PartitionConfig {
+	pub name: String,
+	pub column: Option<String>,
+	pub prefix: Vec<u8>,
+	pub key_layout: Option<String>,
+	pub value_layout: Option<String>,
+}
+
+/// Description and tags of a column family, documenting what it holds
+#[derive(Clone, Debug)]
+pub struct ColumnConfig {
+	pub name: String,
+	pub description: Option<String>,
+	pub tags: Vec<String>,
+	/// Rendering of the keys, the `key_display` of the `display` config by default
+	pub key_display: Option<KeyDisplay>,
+}
+
+impl ColumnConfig {
+	/// Whether the name, description or a tag contains `query`, lower case
+	pub fn matches(&self, query: &str) -> bool {
+		let description = self.description.as_deref().unwrap_or_default();
+		[self.name.as_str(), description]
+			.into_iter()
+			.chain(self.tags.iter().map(String::as_str))
+			.any(|text| text.to_lowercase().contains(query))
+	}
+}
+
+/// Shell command run after committed writes of `column` whose key starts with `prefix`
+#[derive(Clone, Debug)]
+pub struct HookConfig {
+	pub prefix: String,
+	pub column: Option<String>,
+	pub command: String,
+}
+
+/// Checks of the values written by EDMA to `column` whose key starts with `prefix`: the
+/// value matches `pattern` and, when fields are `required`, is a JSON object holding them
+#[derive(Clone, Debug)]
+pub struct ValidationConfig {
+	pub prefix: String,
+	pub column: Option<String>,
+	pub pattern: Option<Regex>,
+	pub required: Vec<String>,
+}
+
+/// Keys of `column` in `database` deleted once the timestamp of the `segment` of the
+/// `layout` key template is older than `days`
+#[derive(Clone, Debug)]
+pub struct RetentionConfig {
+	/// Database alias or address
+	pub database: String,
+	pub column: Option<String>,
+	pub layout: String,
+	pub segment: String,
+	pub days: i64,
 }
 
 #[derive(StructOpt, Debug)]
@@ -38,21 +130,281 @@ pub struct KeyConfig {
 	pub home_tab: Key,
 	pub database_tab: Key,
 	pub layout_tab: Key,
+	pub stats_tab: Key,
+	pub bookmarks_tab: Key,
+	pub toggle_order: Key,
+	pub next_page: Key,
+	pub previous_page: Key,
+	pub load_more: Key,
+	pub export: Key,
+	pub edit_value: Key,
+	pub bookmark: Key,
+	pub annotate: Key,
+	pub toggle_decoder: Key,
+	pub cancel_job: Key,
+	pub history_search: Key,
+	pub search: Key,
+	pub command_mode: Key,
+	pub delete_pair: Key,
+	pub toggle_sidebar: Key,
+	pub relative_time: Key,
+	pub hex_view: Key,
+	pub hex_goto: Key,
+	pub hex_select: Key,
+	pub hex_copy: Key,
+	pub new_workspace: Key,
+	pub next_workspace: Key,
+	pub close_workspace: Key,
+	pub save_config: Key,
 	pub quit: Key,
 }
 
+/// Actions always active, a key can't be bound to one of them and to an action of a scope
+const NAVIGATION_ACTIONS: [&str; 7] =
+	["enter", "backspace", "escape", "up", "down", "left", "right"];
+
+/// Actions handled by the same panel: the menu, the database explorer, the editor and its
+/// hex view
+const ACTION_SCOPES: [&[&str]; 4] = [
+	&["home_tab", "database_tab", "layout_tab", "stats_tab", "bookmarks_tab", "quit"],
+	&[
+		"database_select_up",
+		"database_select_down",
+		"search",
+		"command_mode",
+		"cancel_job",
+		"toggle_sidebar",
+		"relative_time",
+		"new_workspace",
+		"next_workspace",
+		"close_workspace",
+		"quit",
+	],
+	&[
+		"key_layout_up",
+		"key_layout_down",
+		"value_layout_up",
+		"value_layout_down",
+		"toggle_order",
+		"next_page",
+		"previous_page",
+		"load_more",
+		"export",
+		"edit_value",
+		"bookmark",
+		"annotate",
+		"delete_pair",
+		"toggle_decoder",
+		"hex_view",
+		"cancel_job",
+		"command_mode",
+		"toggle_sidebar",
+		"relative_time",
+		"new_workspace",
+		"next_workspace",
+		"close_workspace",
+		"quit",
+	],
+	&["next_page", "previous_page", "hex_goto", "hex_select", "hex_copy"],
+];
+
+impl Default for KeyConfig {
+	fn default() -> Self {
+		KeyConfig {
+			backspace: Key::Backspace,
+			enter: Key::Enter,
+			escape: Key::Esc,
+			up: Key::Up,
+			down: Key::Down,
+			left: Key::Left,
+			right: Key::Right,
+			key_layout_up: Key::Char('h'),
+			key_layout_down: Key::Char('j'),
+			value_layout_up: Key::Char('k'),
+			value_layout_down: Key::Char('l'),
+			database_select_up: Key::Char('9'),
+			database_select_down: Key::Char('0'),
+			home_tab: Key::Char('h'),
+			database_tab: Key::Char('d'),
+			layout_tab: Key::Char('l'),
+			stats_tab: Key::Char('s'),
+			bookmarks_tab: Key::Char('b'),
+			toggle_order: Key::Char('o'),
+			next_page: Key::Char(']'),
+			previous_page: Key::Char('['),
+			load_more: Key::Char('m'),
+			export: Key::Char('e'),
+			edit_value: Key::Char('E'),
+			bookmark: Key::Char('b'),
+			annotate: Key::Char('n'),
+			toggle_decoder: Key::Char('x'),
+			cancel_job: Key::Ctrl('k'),
+			history_search: Key::Ctrl('r'),
+			search: Key::Char('/'),
+			command_mode: Key::Char(':'),
+			delete_pair: Key::Char('D'),
+			toggle_sidebar: Key::Ctrl('b'),
+			relative_time: Key::Ctrl('e'),
+			hex_view: Key::Char('X'),
+			hex_goto: Key::Char('g'),
+			hex_select: Key::Char('v'),
+			hex_copy: Key::Char('y'),
+			new_workspace: Key::Ctrl('t'),
+			next_workspace: Key::Ctrl('n'),
+			close_workspace: Key::Ctrl('w'),
+			save_config: Key::Ctrl('s'),
+			quit: Key::Char('q'),
+		}
+	}
+}
+
+impl KeyConfig {
+	/// Built-in profile, `default` or `vim`. The vim profile navigates with hjkl, switches
+	/// tabs with the digits and scrolls the byte layouts with the capital letters.
+	pub fn profile(name: &str) -> Option<Self> {
+		match name {
+			"default" => Some(KeyConfig::default()),
+			"vim" => Some(KeyConfig {
+				up: Key::Char('k'),
+				down: Key::Char('j'),
+				left: Key::Char('h'),
+				right: Key::Char('l'),
+				key_layout_up: Key::Char('K'),
+				key_layout_down: Key::Char('J'),
+				value_layout_up: Key::Char('H'),
+				value_layout_down: Key::Char('L'),
+				home_tab: Key::Char('1'),
+				database_tab: Key::Char('2'),
+				layout_tab: Key::Char('3'),
+				stats_tab: Key::Char('4'),
+				bookmarks_tab: Key::Char('5'),
+				..KeyConfig::default()
+			}),
+			_ => None,
+		}
+	}
+
+	/// Bindings by action name, as written in the `keys` object of the config
+	fn actions(&mut self) -> [(&'static str, &mut Key); 43] {
+		[
+			("enter", &mut self.enter),
+			("backspace", &mut self.backspace),
+			("escape", &mut self.escape),
+			("up", &mut self.up),
+			("down", &mut self.down),
+			("left", &mut self.left),
+			("right", &mut self.right),
+			("key_layout_up", &mut self.key_layout_up),
+			("key_layout_down", &mut self.key_layout_down),
+			("value_layout_up", &mut self.value_layout_up),
+			("value_layout_down", &mut self.value_layout_down),
+			("database_select_up", &mut self.database_select_up),
+			("database_select_down", &mut self.database_select_down),
+			("home_tab", &mut self.home_tab),
+			("database_tab", &mut self.database_tab),
+			("layout_tab", &mut self.layout_tab),
+			("stats_tab", &mut self.stats_tab),
+			("bookmarks_tab", &mut self.bookmarks_tab),
+			("toggle_order", &mut self.toggle_order),
+			("next_page", &mut self.next_page),
+			("previous_page", &mut self.previous_page),
+			("load_more", &mut self.load_more),
+			("export", &mut self.export),
+			("edit_value", &mut self.edit_value),
+			("bookmark", &mut self.bookmark),
+			("annotate", &mut self.annotate),
+			("toggle_decoder", &mut self.toggle_decoder),
+			("cancel_job", &mut self.cancel_job),
+			("history_search", &mut self.history_search),
+			("search", &mut self.search),
+			("command_mode", &mut self.command_mode),
+			("delete_pair", &mut self.delete_pair),
+			("toggle_sidebar", &mut self.toggle_sidebar),
+			("relative_time", &mut self.relative_time),
+			("hex_view", &mut self.hex_view),
+			("hex_goto", &mut self.hex_goto),
+			("hex_select", &mut self.hex_select),
+			("hex_copy", &mut self.hex_copy),
+			("new_workspace", &mut self.new_workspace),
+			("next_workspace", &mut self.next_workspace),
+			("close_workspace", &mut self.close_workspace),
+			("save_config", &mut self.save_config),
+			("quit", &mut self.quit),
+		]
+	}
+
+	/// Key bound to an action, none for an unknown action
+	pub fn binding(&self, action: &str) -> Option<Key> {
+		let mut keys = self.clone();
+		let binding = keys.actions().into_iter().find(|(name, _)| *name == action);
+		binding.map(|(_, key)| *key)
+	}
+
+	/// Bind a key to an action, false for an unknown action
+	pub fn bind(&mut self, action: &str, key: Key) -> bool {
+		match self.actions().into_iter().find(|(name, _)| *name == action) {
+			Some((_, binding)) => {
+				*binding = key;
+				true
+			}
+			None => false,
+		}
+	}
+
+	/// Actions of a scope, or navigation actions, bound to the same key
+	pub fn conflicts(&self) -> Vec<(&'static str, &'static str, Key)> {
+		let mut conflicts = vec![];
+		for scope in ACTION_SCOPES {
+			let actions: Vec<&'static str> =
+				NAVIGATION_ACTIONS.iter().chain(scope.iter()).copied().collect();
+			for (index, first) in actions.iter().enumerate() {
+				let key = self.binding(first);
+				for second in actions[index + 1..].iter() {
+					if let Some(key) = key.filter(|key| Some(*key) == self.binding(second)) {
+						if !conflicts.contains(&(*first, *second, key)) {
+							conflicts.push((*first, *second, key));
+						}
+					}
+				}
+			}
+		}
+		conflicts
+	}
+}
+
 #[derive(Clone, Debug)]
 pub struct Config {
 	pub databases: HashMap<String, Vec<DatabaseConfig>>,
 	pub templates: Vec<LayoutTemplate>,
+	/// Rendering of decoded numbers and timestamps, overridden per byte layout
+	pub display: DisplayFormat,
+	/// Rendering of raw keys, overridden per column family
+	pub key_display: KeyDisplay,
 	pub path: String,
 	pub key_config: KeyConfig,
+	/// Interval between two refreshes of the database stats, disabled when `None`
+	pub stats_interval: Option<Duration>,
+	/// Interval between two purges of keys expired through TTL metadata, disabled when `None`
+	pub expiry_interval: Option<Duration>,
+	/// Interval between two health checks of the databases, disabled when `None`
+	pub health_interval: Option<Duration>,
+	pub hooks: Vec<HookConfig>,
+	/// Rules rejecting invalid writes before they are committed
+	pub validation: Vec<ValidationConfig>,
+	/// Retention rules evaluated by RETENTION and `edma retention`
+	pub retention: Vec<RetentionConfig>,
+	/// Replace color cues with text markers, set by `no_color` or the `NO_COLOR` variable
+	pub no_color: bool,
+	/// Look for a newer release when EDMA starts
+	pub check_updates: bool,
 }
 
-fn build_template(name: &str, variant: LayoutVariant) -> LayoutTemplate {
+const DEFAULT_HEALTH_INTERVAL: Duration = Duration::from_secs(30);
+
+fn build_template(name: &str, variant: LayoutVariant, display: &DisplayFormat) -> LayoutTemplate {
 	LayoutTemplate::new(
 		&format!("SYSTEM:{}", name),
-		vec![ByteLayout::default().with_variant(variant).build()],
+		vec![ByteLayout::default().with_variant(variant).with_format(display.clone()).build()],
 	)
 }
 
@@ -63,32 +415,45 @@ impl Config {
 			Some(c) => c,
 			None => &binding,
 		};
+		Config::with_path(get_absolute_path_buf(path.to_path_buf()))
+	}
+
+	/// Default settings of the config file at `path`
+	fn with_path(path: String) -> Self {
 		Config {
 			databases: Default::default(),
-			path: get_absolute_path_buf(path.to_path_buf()),
+			path,
 			templates: Default::default(),
-			key_config: KeyConfig {
-				backspace: Key::Backspace,
-				enter: Key::Enter,
-				escape: Key::Esc,
-				up: Key::Up,
-				down: Key::Down,
-				left: Key::Left,
-				right: Key::Right,
-				key_layout_up: Key::Char('h'),
-				key_layout_down: Key::Char('j'),
-				value_layout_up: Key::Char('k'),
-				value_layout_down: Key::Char('l'),
-				database_select_up: Key::Char('9'),
-				database_select_down: Key::Char('0'),
-				home_tab: Key::Char('h'),
-				database_tab: Key::Char('d'),
-				layout_tab: Key::Char('l'),
-				quit: Key::Char('q'),
-			},
+			display: Default::default(),
+			key_display: KeyDisplay::default(),
+			stats_interval: None,
+			expiry_interval: None,
+			health_interval: Some(DEFAULT_HEALTH_INTERVAL),
+			hooks: Default::default(),
+			validation: Default::default(),
+			retention: Default::default(),
+			no_color: std::env::var_os("NO_COLOR").filter(|v| !v.is_empty()).is_some(),
+			check_updates: false,
+			key_config: KeyConfig::default(),
 		}
 	}
 
+	/// Config of a database addressed as `name:path`
+	pub fn database(&self, db_path: &str) -> Option<&DatabaseConfig> {
+		let (name, path) = db_path.split_once(':')?;
+		self.databases.get(name)?.iter().find(|d| d.path == path)
+	}
+
+	/// Rendering of the raw keys of column family `cf` of the database at `db_path`, the
+	/// default column family is documented as `default`
+	pub fn key_display(&self, db_path: &str, cf: &CF) -> KeyDisplay {
+		let name = column_label(cf);
+		let column = self
+			.database(db_path)
+			.and_then(|database| database.columns.iter().find(|c| c.name == name));
+		column.and_then(|c| c.key_display).unwrap_or(self.key_display)
+	}
+
 	pub fn set_databases(&mut self, databases: HashMap<String, Vec<DatabaseConfig>>) {
 		self.databases = databases;
 	}
@@ -99,50 +464,227 @@ impl Config {
 }
 
 pub fn load_config(cli: &CliConfig) -> Config {
-	let mut config = Config::new(cli);
+	let config = Config::new(cli);
 	let data = fs::read_to_string(config.clone().path).expect("Unable to read file");
-	let res: serde_json::Value = serde_json::from_str(&data).expect("Unable to parse");
+	parse_config(config, &data)
+}
+
+/// Load the text of the config file at the path of `config` as it would be loaded at
+/// startup, without the crash report of the loading errors. Returns the loaded config or
+/// the first loading error.
+pub fn validate_config(config: &Config, data: &str) -> Result<Config, String> {
+	serde_json::from_str::<Value>(data).map_err(|err| format!("Unable to parse: {}", err))?;
+	let config = Config::with_path(config.path.clone());
+	let hook = panic::take_hook();
+	panic::set_hook(Box::new(|_| {}));
+	let loaded = panic::catch_unwind(AssertUnwindSafe(|| parse_config(config, data)));
+	panic::set_hook(hook);
+	loaded.map_err(|payload| match payload.downcast::<String>() {
+		Ok(message) => *message,
+		Err(payload) => {
+			payload.downcast_ref::<&str>().map_or("Invalid config".to_string(), |m| m.to_string())
+		}
+	})
+}
+
+fn parse_config(mut config: Config, data: &str) -> Config {
+	let res: serde_json::Value = serde_json::from_str(data).expect("Unable to parse");
 
 	if let Some(d) = res.get("databases") {
 		let databases = load_databases(d);
 		config.set_databases(databases);
 	}
 
-	if let Some(t) = res.get("templates") {
+	// Databases found under the discovery roots, unless already configured
+	if let Some(roots) = res.get("discover").and_then(|d| d.as_array()) {
+		let roots: Vec<String> = roots.iter().map(|r| sanitize(&r.to_string())).collect();
+		for (name, path) in discover_databases(&roots) {
+			let databases = config.databases.entry(name).or_default();
+			let path = get_absolute_path(&path);
+			if databases.iter().all(|d| get_absolute_path(&d.path) != path) {
+				databases.push(DatabaseConfig {
+					path,
+					alias: None,
+					write_limit: WriteLimit::default(),
+					comparator: None,
+					value_ttl: None,
+					partitions: vec![],
+					columns: vec![],
+					discovered: true,
+				});
+			}
+		}
+	}
+
+	if let Some(d) = res.get("display") {
+		config.display = load_display(d, &config.display);
+		if let Some(keys) = d.get("key_display").and_then(|k| k.as_str()) {
+			config.key_display = KeyDisplay::from_name(keys)
+				.unwrap_or_else(|| panic!("Invalid key_display {}", keys));
+		}
+	}
+
+	// Templates saved with INFER SAVE are kept apart from the config file
+	let inferred = fs::read_to_string(inferred_templates_path(&config.path))
+		.ok()
+		.and_then(|data| serde_json::from_str::<Value>(&data).ok())
+		.filter(|templates| templates.is_array());
+	if res.get("templates").is_some() || inferred.is_some() {
+		let display = &config.display;
 		let system_templates = vec![
-			build_template("Bytes", LayoutVariant::Bytes),
-			build_template("String", LayoutVariant::String),
-			build_template("Int32", LayoutVariant::Int32),
-			build_template("Int64", LayoutVariant::Int64),
-			build_template("Float32", LayoutVariant::Float32),
-			build_template("Float64", LayoutVariant::Float64),
-			build_template("Boolean", LayoutVariant::Boolean),
+			build_template("Bytes", LayoutVariant::Bytes, display),
+			build_template("String", LayoutVariant::String, display),
+			build_template("Int32", LayoutVariant::Int32, display),
+			build_template("Int64", LayoutVariant::Int64, display),
+			build_template("Float32", LayoutVariant::Float32, display),
+			build_template("Float64", LayoutVariant::Float64, display),
+			build_template("Boolean", LayoutVariant::Boolean, display),
+			build_template("Timestamp", LayoutVariant::Timestamp, display),
 		];
-		let templates = load_templates(t);
+		let mut templates =
+			res.get("templates").map(|t| load_templates(t, display)).unwrap_or_default();
+		if let Some(inferred) = &inferred {
+			templates.extend(load_templates(inferred, display));
+		}
 		config.set_layouts([system_templates, templates].concat());
 	}
 
+	if let Some(interval) = res.get("stats_interval").and_then(|i| i.as_u64()) {
+		config.stats_interval = Some(Duration::from_secs(interval)).filter(|i| !i.is_zero());
+	}
+
+	if let Some(h) = res.get("hooks") {
+		config.hooks = load_hooks(h);
+	}
+
+	if let Some(v) = res.get("validation") {
+		config.validation = load_validation(v);
+	}
+
+	if let Some(r) = res.get("retention") {
+		config.retention = load_retention(r);
+	}
+
+	if let Some(d) = res.get("decoders") {
+		load_decoders(d);
+	}
+
+	if let Some(interval) = res.get("health_interval").and_then(|i| i.as_u64()) {
+		config.health_interval = Some(Duration::from_secs(interval)).filter(|i| !i.is_zero());
+	}
+
+	if let Some(interval) = res.get("expiry_interval").and_then(|i| i.as_u64()) {
+		config.expiry_interval = Some(Duration::from_secs(interval)).filter(|i| !i.is_zero());
+	}
+
+	if let Some(no_color) = res.get("no_color").and_then(|n| n.as_bool()) {
+		config.no_color |= no_color;
+	}
+
+	if let Some(check_updates) = res.get("check_updates").and_then(|c| c.as_bool()) {
+		config.check_updates = check_updates;
+	}
+
+	if let Some(k) = res.get("keys") {
+		config.key_config = load_keys(k);
+	}
+
 	config
 }
 
+/// Load the key bindings of a `keys` object: a built-in `profile` whose actions are rebound
+/// by name, e.g. `"quit": "Ctrl+q"`. Conflicting bindings are rejected.
+fn load_keys(json_keys: &Value) -> KeyConfig {
+	let mut keys = match json_keys.get("profile") {
+		Some(profile) => {
+			let profile = sanitize(&profile.to_string());
+			KeyConfig::profile(&profile)
+				.unwrap_or_else(|| panic!("Unknown key profile {}", profile))
+		}
+		None => KeyConfig::default(),
+	};
+	for (action, name) in json_keys.as_object().expect("keys is not an object").iter() {
+		if action == "profile" {
+			continue;
+		}
+		let name = sanitize(&name.to_string());
+		let key = Key::from_name(&name)
+			.unwrap_or_else(|| panic!("Invalid key {} of action {}", name, action));
+		if !keys.bind(action, key) {
+			panic!("Unknown key action {}", action);
+		}
+	}
+	if let Some((first, second, key)) = keys.conflicts().first() {
+		panic!("Key {} is bound to both {} and {}", key, first, second);
+	}
+	keys
+}
+
+/// Load the rendering settings of a `display` object, keys missing or invalid are taken
+/// from `base`
+fn load_display(json_display: &Value, base: &DisplayFormat) -> DisplayFormat {
+	let mut display = base.clone();
+	if let Some(separator) = json_display.get("thousands_separator").and_then(|s| s.as_str()) {
+		display.thousands_separator = Some(separator.to_string()).filter(|s| !s.is_empty());
+	}
+	if let Some(format) = json_display.get("date_format").and_then(|f| f.as_str()) {
+		if is_date_format(format) {
+			display.date_format = format.to_string();
+		}
+	}
+	if let Some(offset) =
+		json_display.get("timezone").and_then(|t| t.as_str()).and_then(parse_utc_offset)
+	{
+		display.utc_offset = offset;
+	}
+	display
+}
+
 /// Load byte layout template from JSON config file
-fn load_templates(json_templates: &Value) -> Vec<LayoutTemplate> {
+fn load_templates(json_templates: &Value, display: &DisplayFormat) -> Vec<LayoutTemplate> {
 	let templates = json_templates.as_array();
 	let mut layout_templates = Vec::<LayoutTemplate>::new();
 	for template in templates.unwrap().iter() {
 		let mut t = LayoutTemplate::default();
 		let name = sanitize(&template.get("name").unwrap().to_string());
 		t.set_name(&name);
+		if let Some(row) = template.get("row").and_then(|r| r.as_str()) {
+			let row = RowTemplate::parse(row)
+				.unwrap_or_else(|err| panic!("Invalid row of template {}: {}", name, err));
+			t.row = Some(row);
+		}
 		let layouts = template.get("layouts").unwrap().as_array();
 		// Load layout from json template
 		for layout in layouts.unwrap().iter() {
 			let mut l = ByteLayout::default();
 			let name = sanitize(&layout.get("name").unwrap().to_string());
-			let variant = sanitize(&layout.get("variant").unwrap().to_string());
+			if let Some(expr) = layout.get("expr").and_then(|e| e.as_str()) {
+				let column = ComputedColumn::new(&name, expr)
+					.unwrap_or_else(|err| panic!("Invalid expression of column {}: {}", name, err));
+				t.push_column(column);
+				continue;
+			}
 			let from = layout.get("from").unwrap().as_i64().unwrap() as usize;
 			let to = layout.get("to").unwrap().as_i64().unwrap() as usize;
-			let variant = LayoutVariant::from_string(&variant);
-			t.push_layout(l.with_name(name).with_variant(variant).with_range(from, to).build());
+			let checksum = layout.get("checksum").map(|c| load_checksum(&name, c, layout));
+			// Checksum segments are shown as hex, their variant is optional
+			let variant = match layout.get("variant") {
+				Some(variant) => LayoutVariant::from_string(&sanitize(&variant.to_string())),
+				None if checksum.is_some() => LayoutVariant::Bytes,
+				None => panic!("Missing variant of layout {}", name),
+			};
+			let format = match layout.get("display") {
+				Some(d) => load_display(d, display),
+				None => display.clone(),
+			};
+			t.push_layout(
+				l.with_name(name)
+					.with_variant(variant)
+					.with_range(from, to)
+					.with_format(format)
+					.with_checksum(checksum)
+					.build(),
+			);
 		}
 		layout_templates.push(t);
 	}
@@ -150,6 +692,115 @@ fn load_templates(json_templates: &Value) -> Vec<LayoutTemplate> {
 	layout_templates
 }
 
+/// Load the checksum of a layout, computed over the segments listed in `over`
+fn load_checksum(name: &str, json_checksum: &Value, layout: &Value) -> Checksum {
+	let algorithm = sanitize(&json_checksum.to_string());
+	let algorithm = ChecksumAlgorithm::from_string(&algorithm)
+		.unwrap_or_else(|| panic!("Unsupported checksum {} of layout {}", algorithm, name));
+	let over = match layout.get("over").and_then(|o| o.as_array()) {
+		Some(over) => over.iter().map(|s| sanitize(&s.to_string())).collect(),
+		None => vec![],
+	};
+	Checksum {
+		algorithm,
+		over,
+	}
+}
+
+/// Load write hooks from JSON config file
+fn load_hooks(json_hooks: &Value) -> Vec<HookConfig> {
+	let hooks = json_hooks.as_array();
+	let mut hooks_config = Vec::<HookConfig>::new();
+	for hook in hooks.unwrap().iter() {
+		let prefix = hook.get("prefix").map(|p| sanitize(&p.to_string())).unwrap_or_default();
+		let column = hook.get("column").map(|c| sanitize(&c.to_string()));
+		let command = sanitize(&hook.get("command").unwrap().to_string());
+		hooks_config.push(HookConfig {
+			prefix,
+			column,
+			command,
+		});
+	}
+
+	hooks_config
+}
+
+/// Load validation rules from JSON config file
+fn load_validation(json_rules: &Value) -> Vec<ValidationConfig> {
+	let mut rules = Vec::<ValidationConfig>::new();
+	for rule in json_rules.as_array().unwrap().iter() {
+		let pattern = rule.get("pattern").map(|p| {
+			let pattern = sanitize(&p.to_string());
+			Regex::new(&pattern)
+				.unwrap_or_else(|err| panic!("Invalid validation pattern {}: {}", pattern, err))
+		});
+		let required = rule
+			.get("required")
+			.and_then(|r| r.as_array())
+			.map(|fields| fields.iter().map(|f| sanitize(&f.to_string())).collect());
+		rules.push(ValidationConfig {
+			prefix: rule.get("prefix").map(|p| sanitize(&p.to_string())).unwrap_or_default(),
+			column: rule.get("column").map(|c| sanitize(&c.to_string())),
+			pattern,
+			required: required.unwrap_or_default(),
+		});
+	}
+
+	rules
+}
+
+/// Load retention rules from JSON config file
+fn load_retention(json_rules: &Value) -> Vec<RetentionConfig> {
+	let mut rules = Vec::<RetentionConfig>::new();
+	for rule in json_rules.as_array().unwrap().iter() {
+		let field = |name: &str| {
+			let value = rule.get(name).unwrap_or_else(|| panic!("Retention rule has no {}", name));
+			sanitize(&value.to_string())
+		};
+		rules.push(RetentionConfig {
+			database: field("database"),
+			column: rule.get("column").map(|c| sanitize(&c.to_string())),
+			layout: field("layout"),
+			segment: field("segment"),
+			days: rule.get("days").and_then(|d| d.as_i64()).expect("Retention rule has no days"),
+		});
+	}
+
+	rules
+}
+
+/// Register the value decoders of the JSON config file, a decoder reads either the
+/// `protobuf` message `message` of a `.proto` file or the `bincode` fields `[name, type]`
+fn load_decoders(json_decoders: &Value) {
+	for decoder in json_decoders.as_array().unwrap().iter() {
+		let name = sanitize(&decoder.get("name").unwrap().to_string());
+		let decode = if let Some(path) = decoder.get("protobuf") {
+			let path = sanitize(&path.to_string());
+			let proto = fs::read_to_string(&path).unwrap_or_else(|err| {
+				panic!("Unable to read {} of decoder {}: {}", path, name, err)
+			});
+			let message = sanitize(&decoder.get("message").unwrap().to_string());
+			protobuf_decoder(&proto, &message)
+				.unwrap_or_else(|err| panic!("Invalid decoder {}: {}", name, err))
+		} else {
+			let fields = decoder.get("bincode").and_then(|f| f.as_array()).unwrap();
+			let fields = fields
+				.iter()
+				.map(|field| {
+					let field = field.as_array().unwrap();
+					let kind = sanitize(&field[1].to_string());
+					let kind = BincodeType::from_string(&kind).unwrap_or_else(|| {
+						panic!("Invalid bincode type {} of decoder {}", kind, name)
+					});
+					(sanitize(&field[0].to_string()), kind)
+				})
+				.collect();
+			bincode_decoder(fields)
+		};
+		register_decoder(&name, decode);
+	}
+}
+
 /// Load databases from JSON config file
 fn load_databases(json_database: &Value) -> HashMap<String, Vec<DatabaseConfig>> {
 	let databases = json_database.as_array();
@@ -157,10 +808,73 @@ fn load_databases(json_database: &Value) -> HashMap<String, Vec<DatabaseConfig>>
 	for database in databases.unwrap().iter() {
 		let path = sanitize(&database.get("path").unwrap().to_string());
 		let name = sanitize(&database.get("name").unwrap().to_string());
+		let alias = database.get("alias").map(|a| sanitize(&a.to_string()));
+		let throttle = database.get("throttle");
+		let write_limit = WriteLimit {
+			ops_per_sec: throttle.and_then(|t| t.get("ops_per_sec")).and_then(|v| v.as_u64()),
+			bytes_per_sec: throttle.and_then(|t| t.get("bytes_per_sec")).and_then(|v| v.as_u64()),
+		};
+		let comparator = database.get("comparator").map(|c| sanitize(&c.to_string()));
+		let value_ttl = database.get("value_ttl").and_then(|t| t.as_i64()).filter(|t| *t > 0);
+		let partitions = database.get("partitions").map(load_partitions).unwrap_or_default();
+		let columns = database.get("columns").map(load_columns).unwrap_or_default();
 		databases_config.entry(name).or_default().push(DatabaseConfig {
 			path,
+			alias,
+			write_limit,
+			comparator,
+			value_ttl,
+			partitions,
+			columns,
+			discovered: false,
 		});
 	}
 
 	databases_config
 }
+
+/// Load the column family descriptions of a database, an object of columns by name with an
+/// optional `description` and `tags`
+fn load_columns(json_columns: &Value) -> Vec<ColumnConfig> {
+	let columns = json_columns.as_object().expect("columns is not an object");
+	columns
+		.iter()
+		.map(|(name, column)| ColumnConfig {
+			name: name.clone(),
+			description: column.get("description").map(|d| sanitize(&d.to_string())),
+			tags: column
+				.get("tags")
+				.and_then(|t| t.as_array())
+				.map(|tags| tags.iter().map(|t| sanitize(&t.to_string())).collect())
+				.unwrap_or_default(),
+			key_display: column.get("key_display").and_then(|k| k.as_str()).map(|keys| {
+				KeyDisplay::from_name(keys)
+					.unwrap_or_else(|| panic!("Invalid key_display {} of {}", keys, name))
+			}),
+		})
+		.collect()
+}
+
+/// Load the partitions of a database, an object of partitions by name whose `prefix` is a
+/// byte literal or text, e.g. `"users": { "prefix": "0x01", "key_layout": "User" }`
+fn load_partitions(json_partitions: &Value) -> Vec<PartitionConfig> {
+	let partitions = json_partitions.as_object().expect("partitions is not an object");
+	let field = |partition: &Value, name: &str| {
+		partition.get(name).map(|value| sanitize(&value.to_string()))
+	};
+	partitions
+		.iter()
+		.map(|(name, partition)| {
+			let prefix = field(partition, "prefix")
+				.unwrap_or_else(|| panic!("Partition {} has no prefix", name));
+			PartitionConfig {
+				name: name.clone(),
+				column: field(partition, "column"),
+				prefix: parse_byte_literal(&prefix)
+					.unwrap_or_else(|err| panic!("Partition {}: {}", name, err)),
+				key_layout: field(partition, "key_layout"),
+				value_layout: field(partition, "value_layout"),
+			}
+		})
+		.collect()
+}
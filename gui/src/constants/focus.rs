@@ -4,4 +4,7 @@ pub enum Focus {
 	HomeTabBody,
 	DatabaseTabBody,
 	LayoutTabBody,
+	StatsTabBody,
+	BookmarksTabBody,
+	PanelBody,
 }
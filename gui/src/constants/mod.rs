@@ -1,12 +1,39 @@
 mod focus;
 
 pub use focus::*;
+use std::time::Duration;
+
 use tui::{style::Color, widgets::BorderType};
 
 pub const BORDER_TYPE: BorderType = BorderType::Rounded;
 pub const PRIMARY_COLOR: Color = Color::DarkGray;
 pub const HIGHLIGHT_COLOR: Color = Color::Yellow;
 pub const DEFAULT_STATUS_TEXT: &str = "No status displayed...";
+/// Terminal width under which the tabs stack their panels and the sidebar of the
+/// databases and layouts tabs becomes an overlay
+pub const NARROW_WIDTH: u16 = 100;
+/// Width of the sidebar overlay of narrow terminals
+pub const SIDEBAR_OVERLAY_WIDTH: u16 = 32;
+/// Number of mutations committed per transaction by bulk write commands
+pub const WRITE_BATCH_SIZE: usize = 1_000;
+/// Number of pairs of an SST file ingested by `edma import --ingest` or written by
+/// `edma export-sst`
+pub const INGEST_BATCH_SIZE: usize = 1_000_000;
+/// Time spent on the batches of the background jobs per tick of the event loop, shared by
+/// the running jobs
+pub const BACKGROUND_TICK_BUDGET: Duration = Duration::from_millis(150);
+/// Number of pairs read per transaction by the SEARCH and COUNT jobs
+pub const SCAN_BATCH_SIZE: usize = 1_000;
+/// Number of matching pairs after which SEARCH stops
+pub const SEARCH_MAX_HITS: usize = 1_000;
+/// Rows of the changeset panel, the latest changes are listed
+pub const CHANGESET_PANEL_ROWS: usize = 5;
+/// Interval between two refreshes of the Metrics panel
+pub const METRICS_INTERVAL: Duration = Duration::from_secs(2);
+/// Interval between two runs of a WATCH command without INTERVAL
+pub const DEFAULT_WATCH_INTERVAL: Duration = Duration::from_secs(2);
+/// Interval between two refreshes of the Stats tab, while it is displayed
+pub const STATS_TAB_INTERVAL: Duration = Duration::from_secs(5);
 pub const BANNER: &str = "
 ███████╗██████╗ ███╗   ███╗ █████╗ 
 ██╔════╝██╔══██╗████╗ ████║██╔══██╗
@@ -18,7 +18,7 @@ impl Default for EventConfig {
 	}
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq, Eq)]
 pub enum Event<I> {
 	Input(I),
 	Tick,
@@ -39,20 +39,26 @@ impl Events {
 		})
 	}
 
+	/// Keys are read and ticks are timed on their own threads, so a tick is not delayed by
+	/// input and key presses do not produce extra ticks
 	pub fn with_config(config: EventConfig) -> Events {
 		let (tx, rx) = mpsc::channel();
 
-		let event_tx = tx.clone();
+		let input_tx = tx.clone();
 		thread::spawn(move || loop {
-			if event::poll(config.tick_rate).unwrap() {
-				if let event::Event::Key(event) = event::read().unwrap() {
-					let key = Key::from(event);
-
-					event_tx.send(Event::Input(key)).unwrap();
+			if let event::Event::Key(event) = event::read().unwrap() {
+				if input_tx.send(Event::Input(Key::from(event))).is_err() {
+					break;
 				}
 			}
+		});
 
-			event_tx.send(Event::Tick).unwrap();
+		let tick_tx = tx.clone();
+		thread::spawn(move || loop {
+			thread::sleep(config.tick_rate);
+			if tick_tx.send(Event::Tick).is_err() {
+				break;
+			}
 		});
 
 		Events {
@@ -61,9 +67,38 @@ impl Events {
 		}
 	}
 
-	pub fn next(&self) -> Result<Message, mpsc::RecvError> {
-		self.rx.recv()
+	/// Wait for an event, then take the events queued meanwhile so a frame is drawn once
+	/// for all of them. Queued events are coalesced with `coalesce`.
+	pub fn next_batch(&self) -> Result<Vec<Message>, mpsc::RecvError> {
+		let mut messages = vec![self.rx.recv()?];
+		messages.extend(self.rx.try_iter());
+		Ok(coalesce(messages))
+	}
+}
+
+/// Keys repeated by the terminal while held down, coalesced into a single key
+fn is_repeatable(key: &Key) -> bool {
+	matches!(key, Key::Up | Key::Down | Key::Left | Key::Right | Key::PageUp | Key::PageDown)
+}
+
+/// Collapse a run of the same navigation key queued before a frame into one key, so a
+/// held down arrow on a slow terminal scrolls once per frame instead of replaying the
+/// backlog after the key is released. Characters are kept to not alter typed or pasted
+/// text. Ticks of the batch are collapsed into the last one.
+fn coalesce(messages: Vec<Message>) -> Vec<Message> {
+	let ticked = messages.contains(&Event::Tick);
+	let mut coalesced: Vec<Message> = vec![];
+	for message in messages {
+		match message {
+			Event::Input(key) if is_repeatable(&key) && coalesced.last() == Some(&message) => {}
+			Event::Input(_) => coalesced.push(message),
+			Event::Tick => {}
+		}
+	}
+	if ticked {
+		coalesced.push(Event::Tick);
 	}
+	coalesced
 }
 
 #[derive(Debug, PartialEq, Eq)]
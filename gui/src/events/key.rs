@@ -98,6 +98,47 @@ impl Key {
 			_ => panic!("unknown function key: F{}", n),
 		}
 	}
+
+	/// Parse a key of the config: a character, `Space`, `Ctrl+c`, `Alt+c` or a key name like
+	/// `Enter`, `Up` or `F5`. Keys are also parsed as they are displayed, e.g. `<Ctrl+k>`.
+	pub fn from_name(name: &str) -> Option<Key> {
+		let name = name.strip_prefix('<').and_then(|n| n.strip_suffix('>')).unwrap_or(name);
+		let character = |text: &str| {
+			let mut chars = text.chars();
+			match (text, chars.next(), chars.next()) {
+				("Space", ..) => Some(' '),
+				(_, Some(c), None) => Some(c),
+				_ => None,
+			}
+		};
+		if let Some(c) = name.strip_prefix("Ctrl+") {
+			return character(c).map(Key::Ctrl);
+		}
+		if let Some(c) = name.strip_prefix("Alt+") {
+			return character(c).map(Key::Alt);
+		}
+		let key = match name {
+			"Enter" => Key::Enter,
+			"Tab" => Key::Tab,
+			"Backspace" => Key::Backspace,
+			"Esc" => Key::Esc,
+			"Left" | "\u{2190}" => Key::Left,
+			"Right" | "\u{2192}" => Key::Right,
+			"Up" | "\u{2191}" => Key::Up,
+			"Down" | "\u{2193}" => Key::Down,
+			"Ins" => Key::Ins,
+			"Delete" => Key::Delete,
+			"Home" => Key::Home,
+			"End" => Key::End,
+			"PageUp" => Key::PageUp,
+			"PageDown" => Key::PageDown,
+			_ => match name.strip_prefix('F').and_then(|n| n.parse::<u8>().ok()) {
+				Some(n) if n <= 12 => Key::from_f(n),
+				_ => Key::Char(character(name)?),
+			},
+		};
+		Some(key)
+	}
 }
 
 impl fmt::Display for Key {
@@ -211,3 +252,27 @@ impl From<event::KeyEvent> for Key {
 		}
 	}
 }
+
+#[cfg(test)]
+mod test {
+	use super::Key;
+
+	#[test]
+	fn should_parse_key_names() {
+		assert_eq!(Key::from_name("k"), Some(Key::Char('k')));
+		assert_eq!(Key::from_name("Space"), Some(Key::Char(' ')));
+		assert_eq!(Key::from_name("Ctrl+q"), Some(Key::Ctrl('q')));
+		assert_eq!(Key::from_name("PageDown"), Some(Key::PageDown));
+		assert_eq!(Key::from_name("F5"), Some(Key::F5));
+		assert_eq!(Key::from_name("F"), Some(Key::Char('F')));
+		assert_eq!(Key::from_name("Ctrl+qq"), None);
+		assert_eq!(Key::from_name("Enterprise"), None);
+	}
+
+	#[test]
+	fn should_parse_displayed_keys() {
+		for key in [Key::Enter, Key::Up, Key::Ctrl('k'), Key::Alt(' '), Key::Char('/'), Key::F12] {
+			assert_eq!(Key::from_name(&key.to_string()), Some(key));
+		}
+	}
+}
@@ -1,13 +1,21 @@
 use anyhow::Result;
 use app::AppComponent;
-use config::load_config;
+use components::{ConfigPanel, KeymapPanel, MemoryPanel};
+use config::{load_config, Config};
 use crossterm::{
 	execute,
-	terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+	terminal::{
+		self, disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+	},
 };
-use events::{Event, Events, Key};
-use std::io;
-use tui::{backend::CrosstermBackend, Terminal};
+use events::{Event, Events};
+use std::io::{self, Stdout, Write};
+use tui::{
+	backend::{CrosstermBackend, TestBackend},
+	buffer::Buffer,
+	Terminal,
+};
+use ui::MonochromeBackend;
 
 mod app;
 mod cli;
@@ -21,46 +29,156 @@ mod utils;
 #[tokio::main]
 async fn main() -> Result<()> {
 	let value = crate::cli::parse();
+	if let Some(command) = value.command {
+		return crate::cli::run(command, &value.config).await;
+	}
 	let config = load_config(&value.config);
+	utils::install_panic_hook(&config.path);
+	ui::set_no_color(config.no_color || value.plain);
+	if value.record.is_some() {
+		db::start_recording(value.hash_keys);
+	}
+	if value.plain {
+		run_plain(config).await?;
+	} else {
+		run_terminal(config).await?;
+	}
+	match value.record {
+		Some(path) => crate::cli::save_recording(&path),
+		None => Ok(()),
+	}
+}
 
+async fn run_terminal(mut config: Config) -> Result<()> {
 	setup_terminal()?;
 
 	let stdout = io::stdout();
-	let backend = CrosstermBackend::new(stdout);
+	let backend = MonochromeBackend::new(CrosstermBackend::new(stdout));
 	let mut terminal = Terminal::new(backend)?;
 	let events = Events::new(200);
 
-	let mut app = AppComponent::new(config);
+	let mut app = AppComponent::new(config.clone());
+	register_panels(&mut app, &config);
+
+	// The terminal is restored before an error is returned, so it is reported on a usable
+	// terminal
+	let result = run_terminal_loop(&mut terminal, &events, &mut app, &mut config).await;
+	shutdown_terminal()?;
+	terminal.show_cursor()?;
+	result
+}
+
+async fn run_terminal_loop(
+	terminal: &mut Terminal<MonochromeBackend<CrosstermBackend<Stdout>>>,
+	events: &Events,
+	app: &mut AppComponent<'_>,
+	config: &mut Config,
+) -> Result<()> {
 	terminal.clear()?;
 
 	loop {
-		terminal.draw(|f| {
-			if let Err(err) = app.render(f) {
-				println!("Error thrown: {:?}", err);
-				std::process::exit(1);
-			}
-		})?;
+		let mut rendered = Ok(());
+		terminal.draw(|f| rendered = app.render(f))?;
+		rendered?;
 
-		match events.next()? {
-			Event::Input(key) => match app.event(key).await {
-				Ok(state) => {
-					if !state.is_consumed() && (key == Key::Char('q')) {
-						break;
+		// Every queued event is handled before the next frame is drawn
+		for event in events.next_batch()? {
+			match event {
+				Event::Input(key) => {
+					let state = app.event(key).await?;
+					if !state.is_consumed() && key == config.key_config.quit {
+						return Ok(());
 					}
+					apply_saved_config(app, config);
 				}
-				Err(_) => unimplemented!(),
-			},
 
-			Event::Tick => {}
+				Event::Tick => app.tick().await,
+			}
 		}
 	}
+}
 
-	shutdown_terminal()?;
-	terminal.show_cursor()?;
+/// Plain mode shares the components of the terminal UI, but frames are drawn in memory
+/// and printed as text lines after every key event, without alternate screen or colors
+async fn run_plain(mut config: Config) -> Result<()> {
+	let (width, height) = terminal::size().unwrap_or((120, 40));
+	let mut terminal = Terminal::new(TestBackend::new(width, height))?;
+	let events = Events::new(200);
+	let mut app = AppComponent::new(config.clone());
+	register_panels(&mut app, &config);
+	enable_raw_mode()?;
 
+	// Raw mode is left before an error is returned, so it is reported on a usable terminal
+	let result = run_plain_loop(&mut terminal, &events, &mut app, &mut config).await;
+	disable_raw_mode()?;
+	result
+}
+
+async fn run_plain_loop(
+	terminal: &mut Terminal<TestBackend>,
+	events: &Events,
+	app: &mut AppComponent<'_>,
+	config: &mut Config,
+) -> Result<()> {
+	let mut changed = true;
+
+	loop {
+		if changed {
+			let mut rendered = Ok(());
+			terminal.draw(|f| rendered = app.render(f))?;
+			rendered?;
+			print_buffer(terminal.backend().buffer())?;
+			changed = false;
+		}
+
+		for event in events.next_batch()? {
+			match event {
+				Event::Input(key) => {
+					let state = app.event(key).await?;
+					if !state.is_consumed() && key == config.key_config.quit {
+						return Ok(());
+					}
+					apply_saved_config(app, config);
+					changed = true;
+				}
+
+				Event::Tick => app.tick().await,
+			}
+		}
+	}
+}
+
+/// Print every line of a frame, trailing spaces trimmed. Lines end with CRLF as raw mode
+/// does not translate line feeds.
+fn print_buffer(buffer: &Buffer) -> Result<()> {
+	let mut stdout = io::stdout().lock();
+	let area = buffer.area;
+	for y in area.top()..area.bottom() {
+		let line: String =
+			(area.left()..area.right()).map(|x| buffer.get(x, y).symbol.as_str()).collect();
+		write!(stdout, "{}\r\n", line.trim_end())?;
+	}
+	write!(stdout, "{}\r\n", "-".repeat(area.width as usize))?;
+	stdout.flush()?;
 	Ok(())
 }
 
+/// Panels added to the menu after the built-in tabs. Forks add their own panels here.
+fn register_panels(app: &mut AppComponent, config: &Config) {
+	app.register_panel("keymap", Box::new(KeymapPanel::new(config.clone())));
+	app.register_panel("config", Box::new(ConfigPanel::new(config.clone())));
+	app.register_panel("memory", Box::new(MemoryPanel::default()));
+}
+
+/// Rebuild the app with the config saved in the File tab, the tabs start over
+fn apply_saved_config(app: &mut AppComponent, config: &mut Config) {
+	if let Some(saved) = app.take_config() {
+		*config = saved;
+		*app = AppComponent::new(config.clone());
+		register_panels(app, config);
+	}
+}
+
 fn setup_terminal() -> Result<()> {
 	enable_raw_mode()?;
 	let mut stdout = io::stdout();
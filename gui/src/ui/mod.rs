@@ -1,5 +1,7 @@
 mod list;
+mod monochrome;
 mod table;
 
 pub use list::*;
+pub use monochrome::*;
 pub use table::*;
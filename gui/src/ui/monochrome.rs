@@ -0,0 +1,92 @@
+use std::{
+	io,
+	sync::atomic::{AtomicBool, Ordering},
+};
+
+use tui::{backend::Backend, buffer::Cell, layout::Rect, style::Color};
+
+static NO_COLOR: AtomicBool = AtomicBool::new(false);
+
+/// Render without colors, color cues are replaced by text markers
+pub fn set_no_color(enabled: bool) {
+	NO_COLOR.store(enabled, Ordering::Relaxed);
+}
+
+pub fn no_color() -> bool {
+	NO_COLOR.load(Ordering::Relaxed)
+}
+
+/// Symbol of the selected row of lists and tables, colors mark the row otherwise
+pub fn selection_symbol() -> &'static str {
+	text_marker("* ")
+}
+
+/// `marker` in no-color mode, empty otherwise
+pub fn text_marker(marker: &'static str) -> &'static str {
+	if no_color() {
+		marker
+	} else {
+		""
+	}
+}
+
+/// Backend drawing every cell with the default colors of the terminal in no-color mode.
+/// Modifiers like bold or underline are kept.
+pub struct MonochromeBackend<B: Backend> {
+	inner: B,
+}
+
+impl<B: Backend> MonochromeBackend<B> {
+	pub fn new(inner: B) -> Self {
+		MonochromeBackend {
+			inner,
+		}
+	}
+}
+
+impl<B: Backend> Backend for MonochromeBackend<B> {
+	fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
+	where
+		I: Iterator<Item = (u16, u16, &'a Cell)>,
+	{
+		if !no_color() {
+			return self.inner.draw(content);
+		}
+		let cells: Vec<_> = content
+			.map(|(x, y, cell)| {
+				let mut cell = cell.clone();
+				cell.set_fg(Color::Reset).set_bg(Color::Reset);
+				(x, y, cell)
+			})
+			.collect();
+		self.inner.draw(cells.iter().map(|(x, y, cell)| (*x, *y, cell)))
+	}
+
+	fn hide_cursor(&mut self) -> io::Result<()> {
+		self.inner.hide_cursor()
+	}
+
+	fn show_cursor(&mut self) -> io::Result<()> {
+		self.inner.show_cursor()
+	}
+
+	fn get_cursor(&mut self) -> io::Result<(u16, u16)> {
+		self.inner.get_cursor()
+	}
+
+	fn set_cursor(&mut self, x: u16, y: u16) -> io::Result<()> {
+		self.inner.set_cursor(x, y)
+	}
+
+	fn clear(&mut self) -> io::Result<()> {
+		self.inner.clear()
+	}
+
+	fn size(&self) -> io::Result<Rect> {
+		self.inner.size()
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		self.inner.flush()
+	}
+}
@@ -3,7 +3,7 @@ use tui::widgets::TableState;
 #[derive(Default, Clone)]
 pub struct StatefulTable {
 	pub state: TableState,
-	pub headers: Vec<&'static str>,
+	pub headers: Vec<String>,
 	pub items: Vec<Vec<String>>,
 }
 
@@ -13,8 +13,8 @@ impl StatefulTable {
 		self
 	}
 
-	pub fn with_headers(&mut self, headers: Vec<&'static str>) -> &mut Self {
-		self.headers = headers;
+	pub fn with_headers<S: Into<String>>(&mut self, headers: Vec<S>) -> &mut Self {
+		self.headers = headers.into_iter().map(Into::into).collect();
 		self
 	}
 
@@ -0,0 +1,328 @@
+use std::{
+	cmp::min,
+	collections::{hash_map::DefaultHasher, HashMap},
+	hash::{Hash, Hasher},
+	time::{SystemTime, UNIX_EPOCH},
+};
+
+use db::KeyValuePair;
+use rand::Rng;
+use serde::Serialize;
+
+use super::decode_timestamp;
+
+/// Maximum number of key-value pairs kept for keyspace analysis
+pub const ANALYSIS_SAMPLE_SIZE: usize = 10_000;
+/// Number of pairs sampled to try a layout on
+pub const LAYOUT_SAMPLE_SIZE: usize = 10;
+/// Number of rows displayed by analysis reports
+pub const ANALYSIS_TOP_ROWS: usize = 50;
+/// Number of counters of the heavy hitters summary
+pub const HEAVY_HITTERS_CAPACITY: usize = 500;
+/// Age thresholds in days used by the expiry simulation
+pub const AGE_THRESHOLDS: [i64; 3] = [30, 90, 365];
+const BAR_WIDTH: usize = 30;
+const SECONDS_PER_DAY: i64 = 86_400;
+
+#[derive(Clone, Debug)]
+pub struct PrefixBucket {
+	pub prefix: Vec<u8>,
+	pub count: usize,
+	pub size: usize,
+}
+
+/// Reservoir sampling (algorithm R) of scanned pairs, keeps at most `size` pairs
+pub fn sample_pairs(pairs: Vec<KeyValuePair>, size: usize) -> Vec<KeyValuePair> {
+	if pairs.len() <= size {
+		return pairs;
+	}
+
+	let mut rng = rand::thread_rng();
+	let mut reservoir = Vec::with_capacity(size);
+	for (index, pair) in pairs.into_iter().enumerate() {
+		if index < size {
+			reservoir.push(pair);
+		} else {
+			let slot = rng.gen_range(0..=index);
+			if slot < size {
+				reservoir[slot] = pair;
+			}
+		}
+	}
+	reservoir
+}
+
+/// Group keys by the bytes in range `from..to` and return the `top` most frequent groups
+pub fn prefix_histogram(
+	pairs: &[KeyValuePair],
+	from: usize,
+	to: usize,
+	top: usize,
+) -> Vec<PrefixBucket> {
+	let mut buckets = HashMap::<Vec<u8>, PrefixBucket>::new();
+	for (key, value) in pairs.iter() {
+		let end = min(to, key.len());
+		let start = min(from, end);
+		let prefix = key[start..end].to_vec();
+		let bucket = buckets.entry(prefix.clone()).or_insert(PrefixBucket {
+			prefix,
+			count: 0,
+			size: 0,
+		});
+		bucket.count += 1;
+		bucket.size += key.len() + value.len();
+	}
+
+	let mut buckets: Vec<_> = buckets.into_values().collect();
+	buckets.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.prefix.cmp(&b.prefix)));
+	buckets.truncate(top);
+	buckets
+}
+
+/// Counter of the space-saving summary, `weight` overestimates the real weight by at most
+/// `error`
+#[derive(Clone, Debug)]
+pub struct HeavyHitter {
+	pub item: Vec<u8>,
+	pub weight: usize,
+	pub error: usize,
+}
+
+/// # Heavy hitters
+/// Weighted space-saving summary: at most `capacity` items are counted, a new item evicts
+/// the lightest counter and inherits its weight as error. Items heavier than
+/// `total / capacity` are guaranteed to be kept, in constant memory.
+pub struct SpaceSaving {
+	capacity: usize,
+	counters: HashMap<Vec<u8>, (usize, usize)>,
+}
+
+impl SpaceSaving {
+	pub fn new(capacity: usize) -> Self {
+		SpaceSaving {
+			capacity: capacity.max(1),
+			counters: HashMap::new(),
+		}
+	}
+
+	pub fn insert(&mut self, item: &[u8], weight: usize) {
+		if let Some((count, _)) = self.counters.get_mut(item) {
+			*count += weight;
+			return;
+		}
+		if self.counters.len() < self.capacity {
+			self.counters.insert(item.to_vec(), (weight, 0));
+			return;
+		}
+		let lightest = self.counters.iter().min_by_key(|(_, (count, _))| *count);
+		if let Some((evicted, (min, _))) = lightest.map(|(k, v)| (k.clone(), *v)) {
+			self.counters.remove(&evicted);
+			self.counters.insert(item.to_vec(), (min + weight, min));
+		}
+	}
+
+	/// The `top` heaviest items, heaviest first
+	pub fn top(&self, top: usize) -> Vec<HeavyHitter> {
+		let mut hitters: Vec<_> = self
+			.counters
+			.iter()
+			.map(|(item, (weight, error))| HeavyHitter {
+				item: item.clone(),
+				weight: *weight,
+				error: *error,
+			})
+			.collect();
+		hitters.sort_by(|a, b| b.weight.cmp(&a.weight).then_with(|| a.item.cmp(&b.item)));
+		hitters.truncate(top);
+		hitters
+	}
+}
+
+/// Render a horizontal bar proportional to `value / max`
+pub fn render_bar(value: usize, max: usize) -> String {
+	if max == 0 {
+		return String::new();
+	}
+	let width = (value * BAR_WIDTH).div_ceil(max);
+	"█".repeat(width)
+}
+
+/// Human readable byte size
+pub fn format_bytes(bytes: usize) -> String {
+	const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+	let mut size = bytes as f64;
+	let mut unit = 0;
+	while size >= 1024.0 && unit < UNITS.len() - 1 {
+		size /= 1024.0;
+		unit += 1;
+	}
+	if unit == 0 {
+		format!("{}{}", bytes, UNITS[unit])
+	} else {
+		format!("{:.1}{}", size, UNITS[unit])
+	}
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SizeDistribution {
+	pub count: usize,
+	pub total: usize,
+	pub min: usize,
+	pub max: usize,
+	pub p50: usize,
+	pub p95: usize,
+	pub p99: usize,
+}
+
+/// Nearest-rank percentile of an ascending sorted slice
+pub fn percentile(sorted: &[usize], p: f64) -> usize {
+	if sorted.is_empty() {
+		return 0;
+	}
+	let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+	sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Distribution of value sizes of the given pairs
+pub fn size_distribution(pairs: &[KeyValuePair]) -> SizeDistribution {
+	let mut sizes: Vec<usize> = pairs.iter().map(|(_, v)| v.len()).collect();
+	sizes.sort_unstable();
+	SizeDistribution {
+		count: sizes.len(),
+		total: sizes.iter().sum(),
+		min: sizes.first().copied().unwrap_or(0),
+		max: sizes.last().copied().unwrap_or(0),
+		p50: percentile(&sizes, 50.0),
+		p95: percentile(&sizes, 95.0),
+		p99: percentile(&sizes, 99.0),
+	}
+}
+
+/// Keys holding the `top` largest values, largest first
+pub fn largest_values(pairs: &[KeyValuePair], top: usize) -> Vec<(Vec<u8>, usize)> {
+	let mut sizes: Vec<_> = pairs.iter().map(|(k, v)| (k.clone(), v.len())).collect();
+	sizes.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+	sizes.truncate(top);
+	sizes
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct DuplicateGroup {
+	pub hash: String,
+	pub value_size: usize,
+	pub keys: Vec<Vec<u8>>,
+	pub wasted: usize,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct DuplicateReport {
+	pub scanned: usize,
+	pub wasted: usize,
+	pub groups: Vec<DuplicateGroup>,
+}
+
+/// Group keys sharing an identical value, compared byte for byte. Groups are sorted by
+/// wasted bytes, which is the size of every copy beyond the first one.
+pub fn duplicate_values(pairs: &[KeyValuePair]) -> DuplicateReport {
+	let mut groups = HashMap::<&[u8], Vec<Vec<u8>>>::new();
+	for (key, value) in pairs.iter() {
+		groups.entry(value.as_slice()).or_default().push(key.clone());
+	}
+
+	let mut groups: Vec<_> = groups
+		.into_iter()
+		.filter(|(_, keys)| keys.len() > 1)
+		.map(|(value, keys)| {
+			let mut hasher = DefaultHasher::new();
+			value.hash(&mut hasher);
+			DuplicateGroup {
+				hash: format!("{:016x}", hasher.finish()),
+				value_size: value.len(),
+				wasted: value.len() * (keys.len() - 1),
+				keys,
+			}
+		})
+		.collect();
+	groups.sort_by(|a, b| b.wasted.cmp(&a.wasted).then_with(|| a.hash.cmp(&b.hash)));
+
+	DuplicateReport {
+		scanned: pairs.len(),
+		wasted: groups.iter().map(|g| g.wasted).sum(),
+		groups,
+	}
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct AgeBucket {
+	pub older_than: Option<i64>,
+	pub count: usize,
+	pub size: usize,
+}
+
+pub fn now_timestamp() -> i64 {
+	SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or(0)
+}
+
+/// Timestamp stored in the key bytes `from..to`
+pub fn segment_timestamp(key: &[u8], from: usize, to: usize) -> Option<i64> {
+	let end = min(to, key.len());
+	if from >= end {
+		return None;
+	}
+	decode_timestamp(&key[from..end])
+}
+
+/// Whether the timestamp segment of `key` is older than `days`
+pub fn is_older_than(key: &[u8], from: usize, to: usize, now: i64, days: i64) -> bool {
+	match segment_timestamp(key, from, to) {
+		Some(ts) => now - ts > days * SECONDS_PER_DAY,
+		None => false,
+	}
+}
+
+/// Classify pairs by the age of their key timestamp. The first bucket holds pairs newer
+/// than every threshold, the following buckets are cumulative "older than" buckets.
+/// Pairs without a decodable timestamp are counted separately.
+pub fn age_buckets(
+	pairs: &[KeyValuePair],
+	from: usize,
+	to: usize,
+	now: i64,
+) -> (Vec<AgeBucket>, usize) {
+	let mut recent = AgeBucket::default();
+	let mut buckets: Vec<_> = AGE_THRESHOLDS
+		.iter()
+		.map(|days| AgeBucket {
+			older_than: Some(*days),
+			..Default::default()
+		})
+		.collect();
+	let mut invalid = 0;
+
+	for (key, value) in pairs.iter() {
+		let ts = match segment_timestamp(key, from, to) {
+			Some(ts) => ts,
+			None => {
+				invalid += 1;
+				continue;
+			}
+		};
+		let age = now - ts;
+		let size = key.len() + value.len();
+		let mut matched = false;
+		for bucket in buckets.iter_mut() {
+			if age > bucket.older_than.unwrap_or(0) * SECONDS_PER_DAY {
+				bucket.count += 1;
+				bucket.size += size;
+				matched = true;
+			}
+		}
+		if !matched {
+			recent.count += 1;
+			recent.size += size;
+		}
+	}
+
+	buckets.insert(0, recent);
+	(buckets, invalid)
+}
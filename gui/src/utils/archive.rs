@@ -0,0 +1,253 @@
+use std::{
+	io::{Read, Seek, SeekFrom, Write},
+	ops::Bound,
+};
+
+use anyhow::{anyhow, Result};
+use db::{Cursor, Datastore, KeyValuePair, CF};
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+use serde_json::{json, Value};
+
+use super::{now_timestamp, write_pairs, ExportOrder};
+use crate::constants::WRITE_BATCH_SIZE;
+
+const ARCHIVE_MAGIC: &[u8; 8] = b"EDMAARCH";
+const ARCHIVE_VERSION: u32 = 1;
+/// Magic bytes then the version
+const HEADER_SIZE: u64 = 12;
+/// Keyspace index, number of pairs and payload length of a block
+const BLOCK_HEADER_SIZE: usize = 12;
+
+/// Column family of an archived keyspace, `default` is the default column family
+fn keyspace_cf(name: &str) -> CF {
+	match name {
+		"default" => None,
+		_ => Some(name.as_bytes().to_vec()),
+	}
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ArchiveKeyspace {
+	pub name: String,
+	pub pairs: usize,
+	pub blocks: usize,
+}
+
+/// # Archives
+/// Portable snapshot of a database written by `edma archive create`. The file starts with
+/// `EDMAARCH` and the format version, followed by blocks of up to `WRITE_BATCH_SIZE` pairs
+/// of one keyspace, then the JSON manifest and its offset in the last 8 bytes. A block is
+/// the index of its keyspace in the manifest, its number of pairs and the length of its
+/// payload, then the payload: the deflate compressed keys and values of the pairs, each
+/// prefixed with its length. Integers are big endian.
+#[derive(Clone, Debug)]
+pub struct ArchiveManifest {
+	pub source: String,
+	pub created_at: i64,
+	pub keyspaces: Vec<ArchiveKeyspace>,
+	/// Layout templates of the config the archive was created with
+	pub templates: Value,
+}
+
+impl ArchiveManifest {
+	fn to_json(&self) -> Value {
+		let keyspaces: Vec<_> = self
+			.keyspaces
+			.iter()
+			.map(|k| json!({ "name": k.name, "pairs": k.pairs, "blocks": k.blocks }))
+			.collect();
+		json!({
+			"version": ARCHIVE_VERSION,
+			"source": self.source,
+			"created_at": self.created_at,
+			"keyspaces": keyspaces,
+			"templates": self.templates,
+		})
+	}
+
+	fn from_json(json: &Value) -> Result<ArchiveManifest> {
+		let field = |name: &str| json.get(name).ok_or_else(|| anyhow!("Manifest has no {}", name));
+		let keyspaces = field("keyspaces")?
+			.as_array()
+			.ok_or_else(|| anyhow!("Manifest keyspaces is not a list"))?
+			.iter()
+			.map(|k| ArchiveKeyspace {
+				name: k.get("name").and_then(|n| n.as_str()).unwrap_or("default").to_string(),
+				pairs: k.get("pairs").and_then(|p| p.as_u64()).unwrap_or_default() as usize,
+				blocks: k.get("blocks").and_then(|b| b.as_u64()).unwrap_or_default() as usize,
+			})
+			.collect();
+		Ok(ArchiveManifest {
+			source: field("source")?.as_str().unwrap_or_default().to_string(),
+			created_at: field("created_at")?.as_i64().unwrap_or_default(),
+			keyspaces,
+			templates: json.get("templates").cloned().unwrap_or_else(|| json!([])),
+		})
+	}
+
+	/// Number of pairs of every keyspace
+	pub fn pairs(&self) -> usize {
+		self.keyspaces.iter().map(|k| k.pairs).sum()
+	}
+}
+
+fn encode_block(pairs: &[&KeyValuePair]) -> Result<Vec<u8>> {
+	let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+	for (key, value) in pairs {
+		for bytes in [key, value] {
+			encoder.write_all(&(bytes.len() as u32).to_be_bytes())?;
+			encoder.write_all(bytes)?;
+		}
+	}
+	Ok(encoder.finish()?)
+}
+
+fn decode_block(payload: &[u8], count: usize) -> Result<Vec<KeyValuePair>> {
+	let mut decoder = DeflateDecoder::new(payload);
+	let mut read_bytes = || -> Result<Vec<u8>> {
+		let mut len = [0; 4];
+		decoder.read_exact(&mut len)?;
+		let mut bytes = vec![0; u32::from_be_bytes(len) as usize];
+		decoder.read_exact(&mut bytes)?;
+		Ok(bytes)
+	};
+	let mut pairs = Vec::with_capacity(count);
+	for _ in 0..count {
+		let key = read_bytes()?;
+		pairs.push((key, read_bytes()?));
+	}
+	Ok(pairs)
+}
+
+/// Archive every column family of `ds` with `templates`, one block per batch of
+/// `WRITE_BATCH_SIZE` pairs read with a `Cursor` so the database never sits in memory.
+/// The pairs of a block are written in the default export order.
+/// `progress` is called with the keyspace after every block.
+pub async fn create_archive<W, F>(
+	ds: &Datastore,
+	source: &str,
+	templates: Value,
+	mut writer: W,
+	mut progress: F,
+) -> Result<ArchiveManifest>
+where
+	W: Write,
+	F: FnMut(&ArchiveKeyspace),
+{
+	writer.write_all(ARCHIVE_MAGIC)?;
+	writer.write_all(&ARCHIVE_VERSION.to_be_bytes())?;
+	let mut offset = HEADER_SIZE;
+	let mut keyspaces = vec![];
+	for (index, name) in ds.column_families()?.into_iter().enumerate() {
+		let mut cursor = Cursor::new(keyspace_cf(&name), Bound::Unbounded, Bound::Unbounded, false);
+		let mut keyspace = ArchiveKeyspace {
+			name,
+			..Default::default()
+		};
+		while cursor.has_next() {
+			let tx = ds.transaction(false).await?;
+			let pairs = cursor.next_batch(&tx, WRITE_BATCH_SIZE).await?;
+			drop(tx);
+			if pairs.is_empty() {
+				continue;
+			}
+			let payload = encode_block(&ExportOrder::default().sort(&pairs))?;
+			for field in [index, pairs.len(), payload.len()] {
+				writer.write_all(&(field as u32).to_be_bytes())?;
+			}
+			writer.write_all(&payload)?;
+			offset += (BLOCK_HEADER_SIZE + payload.len()) as u64;
+			keyspace.pairs += pairs.len();
+			keyspace.blocks += 1;
+			progress(&keyspace);
+		}
+		keyspaces.push(keyspace);
+	}
+	let manifest = ArchiveManifest {
+		source: source.to_string(),
+		created_at: now_timestamp(),
+		keyspaces,
+		templates,
+	};
+	serde_json::to_writer(&mut writer, &manifest.to_json())?;
+	writer.write_all(&offset.to_be_bytes())?;
+	writer.flush()?;
+	Ok(manifest)
+}
+
+/// Manifest of an archive, the reader is left at the first block
+pub fn read_manifest<R: Read + Seek>(reader: &mut R) -> Result<(ArchiveManifest, u64)> {
+	let mut header = [0; HEADER_SIZE as usize];
+	reader.read_exact(&mut header)?;
+	if &header[..8] != ARCHIVE_MAGIC {
+		return Err(anyhow!("Not an EDMA archive"));
+	}
+	let version = u32::from_be_bytes([header[8], header[9], header[10], header[11]]);
+	if version != ARCHIVE_VERSION {
+		return Err(anyhow!("Unsupported archive version {}", version));
+	}
+	let end = reader.seek(SeekFrom::End(-8))?;
+	let mut offset = [0; 8];
+	reader.read_exact(&mut offset)?;
+	let offset = u64::from_be_bytes(offset);
+	if offset < HEADER_SIZE || offset > end {
+		return Err(anyhow!("Invalid manifest offset {}", offset));
+	}
+	reader.seek(SeekFrom::Start(offset))?;
+	let json: Value = serde_json::from_reader(reader.by_ref().take(end - offset))?;
+	reader.seek(SeekFrom::Start(HEADER_SIZE))?;
+	Ok((ArchiveManifest::from_json(&json)?, offset))
+}
+
+/// Write the pairs of an archive into `ds`, one transaction per block. `progress` is called
+/// with the keyspace after every block. Fails when a keyspace doesn't hold the number of
+/// pairs of the manifest.
+pub async fn extract_archive<R, F>(
+	mut reader: R,
+	ds: &Datastore,
+	mut progress: F,
+) -> Result<ArchiveManifest>
+where
+	R: Read + Seek,
+	F: FnMut(&ArchiveKeyspace),
+{
+	let (manifest, end) = read_manifest(&mut reader)?;
+	let mut written: Vec<_> = manifest
+		.keyspaces
+		.iter()
+		.map(|k| ArchiveKeyspace {
+			name: k.name.clone(),
+			..Default::default()
+		})
+		.collect();
+	let mut position = HEADER_SIZE;
+	while position < end {
+		let mut header = [0; BLOCK_HEADER_SIZE];
+		reader.read_exact(&mut header)?;
+		let field = |i: usize| {
+			u32::from_be_bytes([header[i], header[i + 1], header[i + 2], header[i + 3]]) as usize
+		};
+		let (index, count, len) = (field(0), field(4), field(8));
+		let keyspace =
+			written.get_mut(index).ok_or_else(|| anyhow!("Block of unknown keyspace {}", index))?;
+		let mut payload = vec![0; len];
+		reader.read_exact(&mut payload)?;
+		let pairs = decode_block(&payload, count)?;
+		write_pairs(ds, keyspace_cf(&keyspace.name), &pairs, |_| {}).await?;
+		keyspace.pairs += count;
+		keyspace.blocks += 1;
+		progress(keyspace);
+		position += (BLOCK_HEADER_SIZE + len) as u64;
+	}
+	for (keyspace, expected) in written.iter().zip(manifest.keyspaces.iter()) {
+		if keyspace.pairs != expected.pairs {
+			return Err(anyhow!(
+				"{}: {} pairs extracted, the manifest lists {}",
+				keyspace.name,
+				keyspace.pairs,
+				expected.pairs
+			));
+		}
+	}
+	Ok(manifest)
+}
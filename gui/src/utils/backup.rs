@@ -0,0 +1,59 @@
+use std::{
+	sync::{
+		atomic::{AtomicU64, Ordering},
+		Arc,
+	},
+	thread::{self, JoinHandle},
+	time::Duration,
+};
+
+use db::{Datastore, Error};
+
+use crate::config::Config;
+
+/// Backup of a database written by BACKUP. The backup runs on its own thread, the native
+/// mechanisms of the backends can't be split in batches. The database stays open until the
+/// backup ends.
+pub struct BackgroundBackup {
+	pub db_path: String,
+	pub path: String,
+	written: Arc<AtomicU64>,
+	handle: Option<JoinHandle<Result<(), Error>>>,
+}
+
+impl BackgroundBackup {
+	pub fn new(db_path: &str, path: &str) -> Self {
+		let written = Arc::new(AtomicU64::new(0));
+		let (db, backup, progress) = (db_path.to_string(), path.to_string(), written.clone());
+		let handle = thread::spawn(move || Datastore::open(&db)?.backup(&backup, &progress));
+		BackgroundBackup {
+			db_path: db_path.to_string(),
+			path: path.to_string(),
+			written,
+			handle: Some(handle),
+		}
+	}
+
+	/// Bytes of the backup written so far
+	pub fn written(&self) -> u64 {
+		self.written.load(Ordering::Relaxed)
+	}
+
+	pub fn is_done(&self) -> bool {
+		self.handle.is_none()
+	}
+
+	/// Collect the result of the backup once its thread ended
+	pub async fn tick(&mut self, _config: &Config, _budget: Duration) -> Result<(), Error> {
+		match self.handle.take() {
+			Some(handle) if handle.is_finished() => match handle.join() {
+				Ok(result) => result,
+				Err(_) => Err(Error::Ds(format!("Backup of {} panicked", self.db_path))),
+			},
+			handle => {
+				self.handle = handle;
+				Ok(())
+			}
+		}
+	}
+}
@@ -0,0 +1,67 @@
+use std::{
+	fs, io,
+	path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// # Bookmarks
+/// Named command saved with the database and column family it runs against, listed in the
+/// Bookmarks tab. Picking a bookmark selects its database and column family and runs the
+/// command again.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bookmark {
+	pub name: String,
+	/// Database addressed as `name:path`
+	pub database: String,
+	pub column: Option<String>,
+	/// Command tokens, without `DB`, `COLUMN` and `BOOKMARK`
+	pub command: String,
+}
+
+impl Bookmark {
+	/// Command of the command editor selecting the database and column family then running
+	/// the bookmarked tokens
+	pub fn command_text(&self) -> String {
+		let mut tokens = vec![format!("DB={}", self.database)];
+		if let Some(column) = &self.column {
+			tokens.push(format!("COLUMN={}", column));
+		}
+		if !self.command.is_empty() {
+			tokens.push(self.command.clone());
+		}
+		tokens.join(" ")
+	}
+}
+
+/// Bookmarks are persisted next to the config file
+pub fn bookmarks_path(config_path: &str) -> PathBuf {
+	Path::new(config_path).with_file_name("bookmarks.json")
+}
+
+/// Load persisted bookmarks, none when the file does not exist yet
+pub fn load_bookmarks(path: &Path) -> Vec<Bookmark> {
+	fs::read_to_string(path)
+		.ok()
+		.and_then(|data| serde_json::from_str(&data).ok())
+		.unwrap_or_default()
+}
+
+fn write_bookmarks(path: &Path, bookmarks: &[Bookmark]) -> io::Result<()> {
+	let data = serde_json::to_string_pretty(bookmarks)?;
+	fs::write(path, data)
+}
+
+/// Persist a bookmark, replacing the bookmark with the same name
+pub fn save_bookmark(path: &Path, bookmark: Bookmark) -> io::Result<()> {
+	let mut bookmarks = load_bookmarks(path);
+	bookmarks.retain(|b| b.name != bookmark.name);
+	bookmarks.push(bookmark);
+	write_bookmarks(path, &bookmarks)
+}
+
+pub fn delete_bookmark(path: &Path, name: &str) -> io::Result<()> {
+	let mut bookmarks = load_bookmarks(path);
+	bookmarks.retain(|b| b.name != name);
+	write_bookmarks(path, &bookmarks)
+}
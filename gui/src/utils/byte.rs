@@ -1,5 +1,7 @@
 use uuid::Uuid;
 
+use super::{Checksum, ComputedColumn, DisplayFormat, RowTemplate};
+
 #[derive(Clone, Debug)]
 pub enum LayoutVariant {
 	String,
@@ -9,6 +11,7 @@ pub enum LayoutVariant {
 	Float32,
 	Float64,
 	Boolean,
+	Timestamp,
 	Bytes,
 }
 
@@ -48,15 +51,239 @@ impl_convert_string!(
 	(Float32, "Float32"),
 	(Float64, "Float64"),
 	(Boolean, "Boolean"),
+	(Timestamp, "Timestamp"),
 	(Bytes, "Bytes")
 );
 
+impl LayoutVariant {
+	/// Encode a value written as text into the bytes of the variant, the inverse of
+	/// `format_variant`. Timestamps are written as unix seconds.
+	pub fn encode(&self, text: &str) -> Option<Vec<u8>> {
+		Some(match self {
+			LayoutVariant::String | LayoutVariant::Bytes => text.as_bytes().to_vec(),
+			LayoutVariant::Int32 => text.parse::<i32>().ok()?.to_be_bytes().to_vec(),
+			LayoutVariant::Int64 | LayoutVariant::Timestamp => {
+				text.parse::<i64>().ok()?.to_be_bytes().to_vec()
+			}
+			LayoutVariant::Float32 => text.parse::<f32>().ok()?.to_be_bytes().to_vec(),
+			LayoutVariant::Float64 => text.parse::<f64>().ok()?.to_be_bytes().to_vec(),
+			LayoutVariant::Boolean => vec![text.to_lowercase().parse::<bool>().ok()? as u8],
+			LayoutVariant::UuidV4 => Uuid::parse_str(text).ok()?.as_bytes().to_vec(),
+		})
+	}
+
+	/// Text of bytes encoded by the variant, written back by `encode`. None when the bytes
+	/// don't have the width of the variant.
+	pub fn decode_text(&self, bytes: &[u8]) -> Option<String> {
+		Some(match self {
+			LayoutVariant::String | LayoutVariant::Bytes => {
+				String::from_utf8(bytes.to_vec()).ok()?
+			}
+			LayoutVariant::Int32 => i32::from_be_bytes(bytes.try_into().ok()?).to_string(),
+			LayoutVariant::Int64 | LayoutVariant::Timestamp => {
+				i64::from_be_bytes(bytes.try_into().ok()?).to_string()
+			}
+			LayoutVariant::Float32 => f32::from_be_bytes(bytes.try_into().ok()?).to_string(),
+			LayoutVariant::Float64 => f64::from_be_bytes(bytes.try_into().ok()?).to_string(),
+			LayoutVariant::Boolean => match bytes {
+				[0] => "false".to_string(),
+				[1] => "true".to_string(),
+				_ => return None,
+			},
+			LayoutVariant::UuidV4 => Uuid::from_slice(bytes).ok()?.to_string(),
+		})
+	}
+}
+
+/// Decode a hex string prefixed with `0x`
+pub fn decode_hex(text: &str) -> Option<Vec<u8>> {
+	let digits = text.strip_prefix("0x")?;
+	if digits.len() % 2 != 0 {
+		return None;
+	}
+	(0..digits.len())
+		.step_by(2)
+		.map(|i| u8::from_str_radix(digits.get(i..i + 2)?, 16).ok())
+		.collect()
+}
+
+/// Decode standard base64, with or without padding
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+	let digits = text.trim_end_matches('=');
+	let mut bytes = Vec::with_capacity(digits.len() * 3 / 4);
+	let (mut buffer, mut bits) = (0u32, 0);
+	for c in digits.bytes() {
+		let digit = match c {
+			b'A'..=b'Z' => c - b'A',
+			b'a'..=b'z' => c - b'a' + 26,
+			b'0'..=b'9' => c - b'0' + 52,
+			b'+' => 62,
+			b'/' => 63,
+			_ => return None,
+		};
+		buffer = (buffer << 6) | digit as u32;
+		bits += 6;
+		if bits >= 8 {
+			bits -= 8;
+			bytes.push((buffer >> bits) as u8);
+			buffer &= (1 << bits) - 1;
+		}
+	}
+	Some(bytes)
+}
+
+/// Decode a string with `\xNN`, `\0`, `\n`, `\r`, `\t` and `\\` escapes
+fn unescape(text: &str) -> Option<Vec<u8>> {
+	let mut bytes = vec![];
+	let mut chars = text.chars();
+	while let Some(c) = chars.next() {
+		if c != '\\' {
+			let mut buffer = [0; 4];
+			bytes.extend_from_slice(c.encode_utf8(&mut buffer).as_bytes());
+			continue;
+		}
+		let byte = match chars.next()? {
+			'x' => {
+				let digits: String = chars.by_ref().take(2).collect();
+				u8::from_str_radix(digits.get(..2)?, 16).ok()?
+			}
+			'0' => 0,
+			'n' => b'\n',
+			'r' => b'\r',
+			't' => b'\t',
+			'\\' => b'\\',
+			_ => return None,
+		};
+		bytes.push(byte);
+	}
+	Some(bytes)
+}
+
+/// Whether the text is written as bytes rather than encoded with a layout
+pub fn is_byte_literal(text: &str) -> bool {
+	text.starts_with("0x") || text.starts_with("b64:") || text.contains('\\')
+}
+
+/// Normalize key bytes pasted in a command: `0x` prefixed hex, `b64:` prefixed base64 or
+/// a string with escapes like `\x00`. Other text is taken as its UTF-8 bytes.
+pub fn parse_byte_literal(text: &str) -> Result<Vec<u8>, String> {
+	let bytes = if text.starts_with("0x") {
+		decode_hex(text)
+	} else if let Some(digits) = text.strip_prefix("b64:") {
+		decode_base64(digits)
+	} else if text.contains('\\') {
+		unescape(text)
+	} else {
+		Some(text.as_bytes().to_vec())
+	};
+	bytes.ok_or_else(|| format!("Invalid byte literal {}", text))
+}
+
+/// Write key bytes as a byte literal of a command: printable text as is, other bytes as
+/// `0x` prefixed hex
+pub fn format_byte_literal(bytes: &[u8]) -> String {
+	let text = std::str::from_utf8(bytes).ok().filter(|text| {
+		!text.is_empty()
+			&& text.chars().all(|c| c.is_ascii_graphic() && c != '"')
+			&& !is_byte_literal(text)
+	});
+	match text {
+		Some(text) => text.to_string(),
+		None => format!("0x{}", bytes.iter().map(|b| format!("{:02x}", b)).collect::<String>()),
+	}
+}
+
+/// Key bytes as a byte literal keeping printable ASCII inline, with other bytes and quotes
+/// written as `\xNN` escapes and backslashes as `\\`. Unlike a lossy UTF-8 conversion the
+/// text reads back to the same bytes, so keys can be copied into commands.
+pub fn escape_bytes(bytes: &[u8]) -> String {
+	(0..bytes.len()).map(|index| escape_byte(bytes, index)).collect()
+}
+
+fn escape_byte(bytes: &[u8], index: usize) -> String {
+	match bytes[index] {
+		b'\\' => "\\\\".to_string(),
+		// Text read as hex or base64 starts with an escape to stay text
+		b if index == 0 && (bytes.starts_with(b"0x") || bytes.starts_with(b"b64:")) => {
+			format!("\\x{:02x}", b)
+		}
+		b @ b' '..=b'~' if b != b'"' => (b as char).to_string(),
+		b => format!("\\x{:02x}", b),
+	}
+}
+
+/// # KeyDisplay
+/// Rendering of raw keys in the table, the key preview and exports, set in the `display`
+/// config and per column family
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum KeyDisplay {
+	/// Printable ASCII with `\xNN` escapes, see `escape_bytes`
+	#[default]
+	Escaped,
+	/// `0x` prefixed hex
+	Hex,
+	/// List of the byte numbers, e.g. `[117, 0]`
+	Bytes,
+}
+
+impl KeyDisplay {
+	pub fn from_name(name: &str) -> Option<KeyDisplay> {
+		match name.to_lowercase().as_str() {
+			"escaped" => Some(KeyDisplay::Escaped),
+			"hex" => Some(KeyDisplay::Hex),
+			"bytes" => Some(KeyDisplay::Bytes),
+			_ => None,
+		}
+	}
+
+	pub fn render(&self, key: &[u8]) -> String {
+		match self {
+			KeyDisplay::Escaped => escape_bytes(key),
+			KeyDisplay::Hex => {
+				format!("0x{}", key.iter().map(|b| format!("{:02x}", b)).collect::<String>())
+			}
+			KeyDisplay::Bytes => format!("{:?}", key),
+		}
+	}
+
+	/// Char offsets of bytes `start..end` of the key in its rendered text
+	pub fn span(&self, key: &[u8], start: usize, end: usize) -> (usize, usize) {
+		match self {
+			KeyDisplay::Escaped => {
+				let width = |index: usize| escape_byte(key, index).len();
+				let from = (0..start).map(width).sum::<usize>();
+				(from, from + (start..end).map(width).sum::<usize>())
+			}
+			KeyDisplay::Hex => (2 + 2 * start, 2 + 2 * end),
+			KeyDisplay::Bytes => {
+				let width = |b: &u8| b.to_string().len();
+				let from = 1 + key[..start].iter().map(|b| width(b) + 2).sum::<usize>();
+				let to = from + key[start..end].iter().map(|b| width(b) + 2).sum::<usize>() - 2;
+				(from, to)
+			}
+		}
+	}
+}
+
+/// Encode text written in a command: byte literals are taken as is, other text is encoded
+/// with the variant of the first segment of the template
+pub fn encode_text(template: Option<&LayoutTemplate>, text: &str) -> Option<Vec<u8>> {
+	let variant =
+		template.and_then(|t| t.layout.first().map(|l| l.variant.clone())).unwrap_or_default();
+	match is_byte_literal(text) {
+		true => parse_byte_literal(text).ok(),
+		false => variant.encode(text),
+	}
+}
+
 #[derive(Clone, Debug)]
 pub struct ByteLayout {
 	pub variant: LayoutVariant,
 	pub name: String,
 	pub from: usize,
 	pub to: usize,
+	pub format: DisplayFormat,
+	pub checksum: Option<Checksum>,
 }
 
 impl Default for ByteLayout {
@@ -66,6 +293,8 @@ impl Default for ByteLayout {
 			name: "*".to_string(),
 			from: usize::MIN,
 			to: usize::MAX,
+			format: Default::default(),
+			checksum: None,
 		}
 	}
 }
@@ -74,6 +303,8 @@ impl Default for ByteLayout {
 pub struct LayoutTemplate {
 	pub name: String,
 	pub layout: Vec<ByteLayout>,
+	pub columns: Vec<ComputedColumn>,
+	pub row: Option<RowTemplate>,
 }
 
 impl LayoutTemplate {
@@ -81,6 +312,8 @@ impl LayoutTemplate {
 		LayoutTemplate {
 			name: name.to_string(),
 			layout,
+			columns: vec![],
+			row: None,
 		}
 	}
 
@@ -91,6 +324,47 @@ impl LayoutTemplate {
 	pub fn push_layout(&mut self, layout: ByteLayout) {
 		self.layout.push(layout);
 	}
+
+	pub fn push_column(&mut self, column: ComputedColumn) {
+		self.columns.push(column);
+	}
+
+	/// Names of the byte layouts then of the computed columns, in the order of `decode`
+	pub fn names(&self) -> Vec<String> {
+		let layouts = self.layout.iter().map(|l| l.name.clone());
+		layouts.chain(self.columns.iter().map(|c| c.name.clone())).collect()
+	}
+
+	/// Decode every byte layout of the template from raw bytes, in layout order, followed
+	/// by the computed columns
+	pub fn decode(&self, raw: &[u8]) -> Vec<String> {
+		let mut values: Vec<String> = self
+			.layout
+			.iter()
+			.map(|item| {
+				let (start, end) = (item.from, std::cmp::min(item.to, raw.len()));
+				if start > end {
+					"OVERFLOW".to_string()
+				} else if item.checksum.is_some() {
+					let hex = |bytes: &[u8]| -> String {
+						bytes.iter().map(|b| format!("{:02x}", b)).collect()
+					};
+					let stored = hex(&raw[start..end]);
+					match self.expected_checksum(item, raw) {
+						Some(expected) if expected == raw[start..end] => stored,
+						Some(expected) => {
+							format!("{} MISMATCH, expected {}", stored, hex(&expected))
+						}
+						None => format!("{} UNSUPPORTED WIDTH", stored),
+					}
+				} else {
+					raw[start..end].to_vec().format_variant(item.variant.clone(), &item.format)
+				}
+			})
+			.collect();
+		values.extend(self.compute_all(raw));
+		values
+	}
 }
 
 impl ByteLayout {
@@ -113,14 +387,24 @@ impl ByteLayout {
 		self.to = to;
 		self
 	}
+
+	pub fn with_format(&mut self, format: DisplayFormat) -> &mut Self {
+		self.format = format;
+		self
+	}
+
+	pub fn with_checksum(&mut self, checksum: Option<Checksum>) -> &mut Self {
+		self.checksum = checksum;
+		self
+	}
 }
 
 pub trait FromLayoutVariant {
-	fn from_variant(&self, variant: LayoutVariant) -> String;
+	fn format_variant(&self, variant: LayoutVariant, format: &DisplayFormat) -> String;
 }
 
 impl FromLayoutVariant for Vec<u8> {
-	fn from_variant(&self, variant: LayoutVariant) -> String {
+	fn format_variant(&self, variant: LayoutVariant, format: &DisplayFormat) -> String {
 		let default_value = format!("{:?}", self);
 		match variant {
 			LayoutVariant::String => {
@@ -135,28 +419,28 @@ impl FromLayoutVariant for Vec<u8> {
 					return format!("Unable to parse: {}", default_value);
 				}
 				let c = i64::from_be_bytes(self[0..8].try_into().unwrap());
-				c.to_string()
+				format.number(&c.to_string())
 			}
 			LayoutVariant::Int32 => {
 				if self.len() < 4 {
 					return format!("Unable to parse: {}", default_value);
 				}
 				let c = i32::from_be_bytes(self[0..4].try_into().unwrap());
-				c.to_string()
+				format.number(&c.to_string())
 			}
 			LayoutVariant::Float64 => {
 				if self.len() < 8 {
 					return format!("Unable to parse: {}", default_value);
 				}
 				let c = f64::from_be_bytes(self[0..8].try_into().unwrap());
-				c.to_string()
+				format.number(&c.to_string())
 			}
 			LayoutVariant::Float32 => {
 				if self.len() < 4 {
 					return format!("Unable to parse: {}", default_value);
 				}
 				let c = f32::from_be_bytes(self[0..4].try_into().unwrap());
-				c.to_string()
+				format.number(&c.to_string())
 			}
 			LayoutVariant::Boolean => {
 				if self.iter().any(|b| b > &1) {
@@ -184,7 +468,28 @@ impl FromLayoutVariant for Vec<u8> {
 				let uuid = Uuid::from_bytes(vec);
 				uuid.to_string()
 			}
+			LayoutVariant::Timestamp => {
+				match decode_timestamp(self).and_then(|secs| format.timestamp(secs)) {
+					Some(time) => time,
+					None => format!("Unable to parse: {}", default_value),
+				}
+			}
 			_ => default_value,
 		}
 	}
 }
+
+/// Decode a big endian unix timestamp from 4 or 8 bytes. Values too large to be
+/// seconds are treated as milliseconds.
+pub fn decode_timestamp(bytes: &[u8]) -> Option<i64> {
+	let value = match bytes.len() {
+		4 => i32::from_be_bytes(bytes.try_into().ok()?) as i64,
+		8 => i64::from_be_bytes(bytes.try_into().ok()?),
+		_ => return None,
+	};
+	if value > 100_000_000_000 {
+		Some(value / 1000)
+	} else {
+		Some(value)
+	}
+}
@@ -0,0 +1,226 @@
+use super::{ByteLayout, LayoutTemplate};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChecksumAlgorithm {
+	/// CRC-32 (IEEE) stored on 4 big endian bytes
+	Crc32,
+	/// XXH32 on a 4 bytes segment, XXH64 on an 8 bytes segment, big endian with seed 0
+	XxHash,
+}
+
+impl ChecksumAlgorithm {
+	pub fn from_string(s: &str) -> Option<ChecksumAlgorithm> {
+		match s.to_lowercase().as_str() {
+			"crc32" => Some(ChecksumAlgorithm::Crc32),
+			"xxhash" => Some(ChecksumAlgorithm::XxHash),
+			_ => None,
+		}
+	}
+}
+
+/// # Checksum segments
+/// A byte layout holding the checksum of the segments named in `over`, or of every other
+/// byte of the key or value when `over` is empty. Mismatching rows are highlighted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Checksum {
+	pub algorithm: ChecksumAlgorithm,
+	pub over: Vec<String>,
+}
+
+const CRC32_POLYNOMIAL: u32 = 0xedb8_8320;
+
+pub fn crc32(bytes: &[u8]) -> u32 {
+	let mut crc = !0u32;
+	for byte in bytes {
+		crc ^= *byte as u32;
+		for _ in 0..8 {
+			crc = (crc >> 1) ^ (CRC32_POLYNOMIAL & 0u32.wrapping_sub(crc & 1));
+		}
+	}
+	!crc
+}
+
+const XXH32_PRIMES: [u32; 5] = [2654435761, 2246822519, 3266489917, 668265263, 374761393];
+const XXH64_PRIMES: [u64; 5] = [
+	11400714785074694791,
+	14029467366897019727,
+	1609587929392839161,
+	9650029242287828579,
+	2870177450012600261,
+];
+
+fn read32(bytes: &[u8], at: usize) -> u32 {
+	u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+}
+
+fn read64(bytes: &[u8], at: usize) -> u64 {
+	u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap())
+}
+
+pub fn xxh32(bytes: &[u8], seed: u32) -> u32 {
+	let [p1, p2, p3, p4, p5] = XXH32_PRIMES;
+	let round = |acc: u32, input: u32| {
+		acc.wrapping_add(input.wrapping_mul(p2)).rotate_left(13).wrapping_mul(p1)
+	};
+	let mut at = 0;
+	let mut hash = if bytes.len() >= 16 {
+		let mut v = [
+			seed.wrapping_add(p1).wrapping_add(p2),
+			seed.wrapping_add(p2),
+			seed,
+			seed.wrapping_sub(p1),
+		];
+		while at + 16 <= bytes.len() {
+			for (lane, acc) in v.iter_mut().enumerate() {
+				*acc = round(*acc, read32(bytes, at + lane * 4));
+			}
+			at += 16;
+		}
+		v[0].rotate_left(1)
+			.wrapping_add(v[1].rotate_left(7))
+			.wrapping_add(v[2].rotate_left(12))
+			.wrapping_add(v[3].rotate_left(18))
+	} else {
+		seed.wrapping_add(p5)
+	};
+	hash = hash.wrapping_add(bytes.len() as u32);
+	while at + 4 <= bytes.len() {
+		hash =
+			hash.wrapping_add(read32(bytes, at).wrapping_mul(p3)).rotate_left(17).wrapping_mul(p4);
+		at += 4;
+	}
+	for byte in &bytes[at..] {
+		hash = hash.wrapping_add((*byte as u32).wrapping_mul(p5)).rotate_left(11).wrapping_mul(p1);
+	}
+	hash ^= hash >> 15;
+	hash = hash.wrapping_mul(p2);
+	hash ^= hash >> 13;
+	hash = hash.wrapping_mul(p3);
+	hash ^ (hash >> 16)
+}
+
+pub fn xxh64(bytes: &[u8], seed: u64) -> u64 {
+	let [p1, p2, p3, p4, p5] = XXH64_PRIMES;
+	let round = |acc: u64, input: u64| {
+		acc.wrapping_add(input.wrapping_mul(p2)).rotate_left(31).wrapping_mul(p1)
+	};
+	let merge = |acc: u64, lane: u64| (acc ^ round(0, lane)).wrapping_mul(p1).wrapping_add(p4);
+	let mut at = 0;
+	let mut hash = if bytes.len() >= 32 {
+		let mut v = [
+			seed.wrapping_add(p1).wrapping_add(p2),
+			seed.wrapping_add(p2),
+			seed,
+			seed.wrapping_sub(p1),
+		];
+		while at + 32 <= bytes.len() {
+			for (lane, acc) in v.iter_mut().enumerate() {
+				*acc = round(*acc, read64(bytes, at + lane * 8));
+			}
+			at += 32;
+		}
+		let hash = v[0]
+			.rotate_left(1)
+			.wrapping_add(v[1].rotate_left(7))
+			.wrapping_add(v[2].rotate_left(12))
+			.wrapping_add(v[3].rotate_left(18));
+		v.iter().fold(hash, |hash, lane| merge(hash, *lane))
+	} else {
+		seed.wrapping_add(p5)
+	};
+	hash = hash.wrapping_add(bytes.len() as u64);
+	while at + 8 <= bytes.len() {
+		hash ^= round(0, read64(bytes, at));
+		hash = hash.rotate_left(27).wrapping_mul(p1).wrapping_add(p4);
+		at += 8;
+	}
+	if at + 4 <= bytes.len() {
+		hash ^= (read32(bytes, at) as u64).wrapping_mul(p1);
+		hash = hash.rotate_left(23).wrapping_mul(p2).wrapping_add(p3);
+		at += 4;
+	}
+	for byte in &bytes[at..] {
+		hash ^= (*byte as u64).wrapping_mul(p5);
+		hash = hash.rotate_left(11).wrapping_mul(p1);
+	}
+	hash ^= hash >> 33;
+	hash = hash.wrapping_mul(p2);
+	hash ^= hash >> 29;
+	hash = hash.wrapping_mul(p3);
+	hash ^ (hash >> 32)
+}
+
+impl Checksum {
+	/// Checksum of `data` encoded on `width` bytes, `None` when the algorithm does not
+	/// produce that width
+	fn digest(&self, data: &[u8], width: usize) -> Option<Vec<u8>> {
+		Some(match (self.algorithm, width) {
+			(ChecksumAlgorithm::Crc32, 4) => crc32(data).to_be_bytes().to_vec(),
+			(ChecksumAlgorithm::XxHash, 4) => xxh32(data, 0).to_be_bytes().to_vec(),
+			(ChecksumAlgorithm::XxHash, 8) => xxh64(data, 0).to_be_bytes().to_vec(),
+			_ => return None,
+		})
+	}
+}
+
+impl LayoutTemplate {
+	/// Bytes covered by the checksum of `layout`
+	fn checksum_data(&self, layout: &ByteLayout, checksum: &Checksum, raw: &[u8]) -> Vec<u8> {
+		if checksum.over.is_empty() {
+			let (start, end) = (layout.from.min(raw.len()), layout.to.min(raw.len()));
+			return [&raw[..start], &raw[end..]].concat();
+		}
+		checksum
+			.over
+			.iter()
+			.filter_map(|name| self.layout.iter().find(|l| &l.name == name))
+			.flat_map(|l| raw.get(l.from.min(raw.len())..l.to.min(raw.len())).unwrap_or_default())
+			.copied()
+			.collect()
+	}
+
+	/// Expected checksum of a checksum layout, `None` for other layouts or when the segment
+	/// does not fit in the bytes
+	pub fn expected_checksum(&self, layout: &ByteLayout, raw: &[u8]) -> Option<Vec<u8>> {
+		let checksum = layout.checksum.as_ref()?;
+		let stored = raw.get(layout.from..layout.to.min(raw.len()))?;
+		checksum.digest(&self.checksum_data(layout, checksum, raw), stored.len())
+	}
+
+	/// Whether every checksum segment of the template matches the bytes. Bytes too short
+	/// for a checksum segment do not match.
+	pub fn verify(&self, raw: &[u8]) -> bool {
+		self.layout.iter().filter(|l| l.checksum.is_some()).all(|layout| {
+			let stored = raw.get(layout.from..layout.to.min(raw.len()));
+			stored.is_some() && self.expected_checksum(layout, raw).as_deref() == stored
+		})
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	const LONG: &[u8] = b"Nobody inspects the spammish repetition";
+
+	#[test]
+	fn should_match_crc32_check_values() {
+		assert_eq!(crc32(b""), 0);
+		assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+		assert_eq!(crc32(b"The quick brown fox jumps over the lazy dog"), 0x414f_a339);
+	}
+
+	#[test]
+	fn should_match_xxh32_reference_values() {
+		assert_eq!(xxh32(b"", 0), 0x02cc_5d05);
+		assert_eq!(xxh32(b"abc", 0), 0x32d1_53ff);
+		assert_eq!(xxh32(LONG, 0), 0xe229_3b2f);
+	}
+
+	#[test]
+	fn should_match_xxh64_reference_values() {
+		assert_eq!(xxh64(b"", 0), 0xef46_db37_51d8_e999);
+		assert_eq!(xxh64(b"abc", 0), 0x44bc_2cf5_ad77_0999);
+		assert_eq!(xxh64(LONG, 0), 0xfbce_a83c_8a37_8bf1);
+	}
+}
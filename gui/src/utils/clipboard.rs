@@ -0,0 +1,26 @@
+use std::io::{self, Write};
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64(bytes: &[u8]) -> String {
+	let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+	for chunk in bytes.chunks(3) {
+		let group =
+			chunk.iter().enumerate().fold(0u32, |g, (i, b)| g | ((*b as u32) << (16 - i * 8)));
+		for i in 0..4 {
+			match i <= chunk.len() {
+				true => encoded.push(BASE64[(group >> (18 - i * 6)) as usize & 0x3f] as char),
+				false => encoded.push('='),
+			}
+		}
+	}
+	encoded
+}
+
+/// Copy text to the clipboard of the terminal with the OSC 52 escape sequence, which also
+/// reaches the local clipboard over SSH. Terminals without OSC 52 support ignore it.
+pub fn copy_to_clipboard(text: &str) -> io::Result<()> {
+	let mut stdout = io::stdout();
+	write!(stdout, "\x1b]52;c;{}\x07", base64(text.as_bytes()))?;
+	stdout.flush()
+}
@@ -0,0 +1,98 @@
+use std::io::Write;
+
+use db::{Datastore, Error, CF};
+use serde_json::json;
+
+use super::{column_label, KeyspaceDiff, KeyspaceDigest, PairChange};
+
+/// Differences of a column family between the databases `a` and `b`
+pub struct ColumnComparison {
+	pub cf: CF,
+	pub a: KeyspaceDigest,
+	pub b: KeyspaceDigest,
+	pub only_in_a: usize,
+	pub only_in_b: usize,
+	pub changed: usize,
+}
+
+impl ColumnComparison {
+	pub fn is_identical(&self) -> bool {
+		self.only_in_a + self.only_in_b + self.changed == 0
+	}
+}
+
+/// Column families of both databases, those of `a` first then those only in `b`, with
+/// whether `a` and `b` hold them
+pub fn compared_column_families(
+	a: &Datastore,
+	b: &Datastore,
+) -> Result<Vec<(String, bool, bool)>, Error> {
+	let (in_a, in_b) = (a.column_families()?, b.column_families()?);
+	let mut names = in_a.clone();
+	names.extend(in_b.iter().filter(|name| !in_a.contains(name)).cloned());
+	let sides = names.into_iter().map(|name| {
+		let sides = (in_a.contains(&name), in_b.contains(&name));
+		(name, sides.0, sides.1)
+	});
+	Ok(sides.collect())
+}
+
+/// Compare a column family of two databases with ordered scans of both, one batch at a
+/// time. Differences are passed to `report` once both sides are read past their key and
+/// are not kept, the memory used doesn't grow with the databases.
+pub async fn compare_column<F: FnMut(&PairChange)>(
+	a: &Datastore,
+	b: &Datastore,
+	cf: CF,
+	mut report: F,
+) -> Result<ColumnComparison, Error> {
+	let mut diff = KeyspaceDiff::new(cf.clone(), cf.clone(), &[]);
+	let (mut only_in_a, mut only_in_b, mut changed) = (0, 0, 0);
+	while !diff.is_done() {
+		diff.diff_batch(a, b).await?;
+		for change in diff.take_changes() {
+			match change {
+				PairChange::Removed(_) => only_in_a += 1,
+				PairChange::Added(_) => only_in_b += 1,
+				PairChange::Changed {
+					..
+				} => changed += 1,
+			}
+			report(&change);
+		}
+	}
+	let (a, b) = diff.digests();
+	Ok(ColumnComparison {
+		cf,
+		a,
+		b,
+		only_in_a,
+		only_in_b,
+		changed,
+	})
+}
+
+/// Write the manifest of a comparison as JSON: the keys and checksum of every column family
+/// on both sides, with the number of differences
+pub fn write_manifest<W: Write>(
+	writer: W,
+	a: &str,
+	b: &str,
+	comparisons: &[ColumnComparison],
+) -> serde_json::Result<()> {
+	let digest = |digest: &KeyspaceDigest| json!({ "keys": digest.keys, "checksum": format!("{:016x}", digest.checksum) });
+	let columns: Vec<_> = comparisons
+		.iter()
+		.map(|comparison| {
+			json!({
+				"column": column_label(&comparison.cf),
+				"a": digest(&comparison.a),
+				"b": digest(&comparison.b),
+				"only_in_a": comparison.only_in_a,
+				"only_in_b": comparison.only_in_b,
+				"changed": comparison.changed,
+			})
+		})
+		.collect();
+	serde_json::to_writer_pretty(writer, &json!({ "a": a, "b": b, "columns": columns }))
+}
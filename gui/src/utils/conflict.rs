@@ -0,0 +1,46 @@
+use db::{Key, Val};
+
+use super::format_byte_literal;
+
+/// Whether the message of a backend error reports an optimistic transaction conflict, the
+/// engines do not name the keys involved
+pub fn is_conflict(err: &str) -> bool {
+	let err = err.to_lowercase();
+	["conflict", "resource busy", "busy", "try again"].iter().any(|p| err.contains(p))
+}
+
+/// # WriteConflict
+/// Write of a key that failed on an optimistic conflict, with the value stored when the
+/// conflict was shown
+#[derive(Clone, Debug)]
+pub struct WriteConflict {
+	pub key: Key,
+	/// Value written by the failed transaction, none for a deletion
+	pub intended: Option<Val>,
+	/// Value stored by the transaction that won, none when the key is absent
+	pub stored: Option<Val>,
+}
+
+impl WriteConflict {
+	/// Command writing the intended value, or deleting the key
+	pub fn overwrite_command(&self, column: &str) -> String {
+		let key = format_byte_literal(&self.key);
+		match &self.intended {
+			Some(value) => {
+				format!("{}SET KEY={} VALUE={}", column, key, format_byte_literal(value))
+			}
+			None => format!("{}DELETE KEY={}", column, key),
+		}
+	}
+
+	/// Command writing back the stored value, to be edited before it is run
+	pub fn reload_command(&self, column: &str) -> String {
+		let key = format_byte_literal(&self.key);
+		match &self.stored {
+			Some(value) => {
+				format!("{}SET KEY={} VALUE={}", column, key, format_byte_literal(value))
+			}
+			None => format!("{}SET KEY={} VALUE=", column, key),
+		}
+	}
+}
@@ -0,0 +1,90 @@
+use std::{
+	backtrace::Backtrace,
+	fmt, fs,
+	io::{self, Write},
+	panic,
+	path::{Path, PathBuf},
+	sync::Mutex,
+};
+
+use crossterm::{
+	cursor::Show,
+	execute,
+	terminal::{disable_raw_mode, LeaveAlternateScreen},
+};
+
+use super::now_timestamp;
+
+/// Number of commands kept for the crash report
+const CRASH_COMMANDS: usize = 20;
+
+static LAST_COMMANDS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+static ACTIVE_DATABASE: Mutex<Option<String>> = Mutex::new(None);
+
+/// Keep a command run from the command editor for the crash report
+pub fn record_command(command: &str) {
+	if let Ok(mut commands) = LAST_COMMANDS.lock() {
+		if commands.len() == CRASH_COMMANDS {
+			commands.remove(0);
+		}
+		commands.push(command.to_string());
+	}
+}
+
+/// Keep the database displayed by the editor, addressed as `name:path`, for the crash report
+pub fn record_database(database: &str) {
+	if let Ok(mut active) = ACTIVE_DATABASE.lock() {
+		*active = Some(database.to_string());
+	}
+}
+
+/// Crash reports are written next to the config file
+pub fn crash_report_path(config_path: &str) -> PathBuf {
+	Path::new(config_path).with_file_name(format!("crash-{}.log", now_timestamp()))
+}
+
+/// Leave raw mode and the alternate screen, errors are ignored as the terminal may not have
+/// been set up yet
+fn restore_terminal() {
+	let _ = disable_raw_mode();
+	let _ = execute!(io::stdout(), LeaveAlternateScreen, Show);
+}
+
+fn crash_report(message: &dyn fmt::Display) -> String {
+	// The panic may have happened while a lock was held, the hook must not wait on it
+	let commands = LAST_COMMANDS.try_lock().map(|c| c.clone()).unwrap_or_default();
+	let database = ACTIVE_DATABASE.try_lock().ok().and_then(|d| d.clone());
+	let mut report = format!("EDMA {} crashed: {}\n\n", env!("CARGO_PKG_VERSION"), message);
+	report += &format!("Active database: {}\n\n", database.as_deref().unwrap_or("none"));
+	report += "Last commands:\n";
+	for command in commands.iter() {
+		report += &format!("  {}\n", command);
+	}
+	report += &format!("\nBacktrace:\n{}\n", Backtrace::force_capture());
+	report
+}
+
+/// Restore the terminal when EDMA panics, then write a crash report next to the config file
+/// and print how to recover instead of leaving the shell in raw mode
+pub fn install_panic_hook(config_path: &str) {
+	let config_path = config_path.to_string();
+	panic::set_hook(Box::new(move |info| {
+		restore_terminal();
+		let path = crash_report_path(&config_path);
+		let mut stderr = io::stderr();
+		let _ = writeln!(stderr, "EDMA crashed: {}", info);
+		match fs::write(&path, crash_report(info)) {
+			Ok(()) => {
+				let _ = writeln!(stderr, "A crash report was written to {}", path.display());
+			}
+			Err(err) => {
+				let _ = writeln!(stderr, "The crash report could not be written: {}", err);
+			}
+		}
+		let _ = writeln!(
+			stderr,
+			"Run `reset` if the terminal still misbehaves. Please attach the report to an issue \
+			 at https://github.com/nomadiz/edma/issues"
+		);
+	}));
+}
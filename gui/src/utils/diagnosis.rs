@@ -0,0 +1,68 @@
+use super::is_conflict;
+
+/// # Diagnosis
+/// Likely cause of an error of a storage backend and what to do about it, shown under the
+/// error in the editor
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnosis {
+	pub cause: &'static str,
+	pub remediation: &'static str,
+}
+
+/// Diagnose the message of a backend error from the wording the engines use, none when the
+/// error is not recognized
+pub fn diagnose(err: &str) -> Option<Diagnosis> {
+	let err = err.to_lowercase();
+	let has = |patterns: &[&str]| patterns.iter().any(|p| err.contains(p));
+	let diagnosis = if has(&[
+		"lock file",
+		"acquire lock",
+		"locked",
+		"already open",
+		"resource temporarily unavailable",
+		"wouldblock",
+	]) {
+		Diagnosis {
+			cause: "The database is opened by another process",
+			remediation: "Close the application or EDMA instance holding it, or open a copy \
+			              of the database",
+		}
+	} else if has(&["permission denied", "(os error 13)", "access is denied"]) {
+		Diagnosis {
+			cause: "The database files are not readable and writable by this user",
+			remediation: "Run EDMA as the user owning the database files, or grant this user \
+			              read and write access to them",
+		}
+	} else if has(&["read-only file system", "(os error 30)"]) {
+		Diagnosis {
+			cause: "The database is on a read-only file system",
+			remediation: "Copy the database to a writable directory and open the copy",
+		}
+	} else if has(&["column family", "table does not exist", "tree does not exist"])
+		&& has(&["not found", "does not exist", "not valid", "invalid"])
+	{
+		Diagnosis {
+			cause: "The column family is missing from the database",
+			remediation: "List the column families with CF LIST, or create it with CF CREATE \
+			              <name>",
+		}
+	} else if is_conflict(&err) {
+		Diagnosis {
+			cause: "Another transaction wrote the same keys before this one committed",
+			remediation: "Run the write again once the other transaction is done",
+		}
+	} else if has(&["no such file", "(os error 2)", "path not found"]) {
+		Diagnosis {
+			cause: "The database path does not exist",
+			remediation: "Check the path of the database in the config file",
+		}
+	} else if has(&["corrupt", "checksum mismatch"]) {
+		Diagnosis {
+			cause: "The database files are damaged",
+			remediation: "Restore the database from a backup with RESTORE PATH=<backup>",
+		}
+	} else {
+		return None;
+	};
+	Some(diagnosis)
+}
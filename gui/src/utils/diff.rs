@@ -0,0 +1,281 @@
+use std::{
+	cmp::Ordering,
+	collections::VecDeque,
+	ops::Bound,
+	time::{Duration, Instant},
+};
+
+use db::{Cursor, Datastore, Error, Key, KeyValuePair, Val, CF};
+
+use super::{column_label, open_datastore, prefix_end, xxh64};
+use crate::{config::Config, constants::SCAN_BATCH_SIZE};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PairChange {
+	Added(KeyValuePair),
+	Removed(KeyValuePair),
+	Changed {
+		key: Key,
+		before: Val,
+		after: Val,
+	},
+}
+
+/// Merge-scan two keyspaces sorted by key and return the changes from `before` to `after`
+pub fn diff_pairs(before: &[KeyValuePair], after: &[KeyValuePair]) -> Vec<PairChange> {
+	let mut changes = vec![];
+	let (mut old, mut new) = (before.iter().peekable(), after.iter().peekable());
+	loop {
+		let ordering = match (old.peek(), new.peek()) {
+			(Some((old_key, _)), Some((new_key, _))) => old_key.cmp(new_key),
+			(Some(_), None) => Ordering::Less,
+			(None, Some(_)) => Ordering::Greater,
+			(None, None) => break,
+		};
+		match ordering {
+			Ordering::Less => changes.push(PairChange::Removed(old.next().unwrap().clone())),
+			Ordering::Greater => changes.push(PairChange::Added(new.next().unwrap().clone())),
+			Ordering::Equal => {
+				let (key, before) = old.next().unwrap();
+				let (_, after) = new.next().unwrap();
+				if before != after {
+					changes.push(PairChange::Changed {
+						key: key.clone(),
+						before: before.clone(),
+						after: after.clone(),
+					});
+				}
+			}
+		}
+	}
+	changes
+}
+
+/// Bytes `start..end` of `before` and of `after` which differ, the common prefix and the
+/// common suffix of the two values excluded
+pub fn changed_range(before: &[u8], after: &[u8]) -> (usize, usize, usize) {
+	let prefix = before.iter().zip(after).take_while(|(b, a)| b == a).count();
+	let suffix = before[prefix..]
+		.iter()
+		.rev()
+		.zip(after[prefix..].iter().rev())
+		.take_while(|(b, a)| b == a)
+		.count();
+	(prefix, before.len() - suffix, after.len() - suffix)
+}
+
+/// Keys read on one side of a comparison and an XXH64 checksum chained over its pairs in
+/// key order, equal on both sides when they hold the same pairs
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct KeyspaceDigest {
+	pub keys: usize,
+	pub checksum: u64,
+}
+
+impl KeyspaceDigest {
+	fn add(&mut self, key: &[u8], value: &[u8]) {
+		let mut bytes = Vec::with_capacity(key.len() + value.len() + 8);
+		for part in [key, value] {
+			bytes.extend((part.len() as u32).to_be_bytes());
+			bytes.extend(part);
+		}
+		self.keys += 1;
+		self.checksum = xxh64(&bytes, self.checksum);
+	}
+}
+
+/// Key read on both sides of an OVERLAP comparison
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SharedKey {
+	pub key: Key,
+	pub same_value: bool,
+}
+
+/// Side by side comparison of two keyspaces, restricted to the keys starting with a prefix.
+/// Both column families are read in key order
+/// one batch of `SCAN_BATCH_SIZE` pairs at a time and merged, pairs only in the left
+/// keyspace are `Removed`, pairs only in the right one `Added`.
+pub struct KeyspaceDiff {
+	left_cf: CF,
+	right_cf: CF,
+	left: Cursor,
+	right: Cursor,
+	left_pairs: VecDeque<KeyValuePair>,
+	right_pairs: VecDeque<KeyValuePair>,
+	compared: usize,
+	changes: Vec<PairChange>,
+	/// Keys read on both sides, collected instead of the changes by OVERLAP
+	shared: Option<Vec<SharedKey>>,
+	digests: (KeyspaceDigest, KeyspaceDigest),
+}
+
+impl KeyspaceDiff {
+	pub fn new(left_cf: CF, right_cf: CF, prefix: &[u8]) -> Self {
+		let start = match prefix.is_empty() {
+			true => Bound::Unbounded,
+			false => Bound::Included(prefix.to_vec()),
+		};
+		let cursor = |cf: &CF| Cursor::new(cf.clone(), start.clone(), prefix_end(prefix), false);
+		KeyspaceDiff {
+			left: cursor(&left_cf),
+			right: cursor(&right_cf),
+			left_cf,
+			right_cf,
+			left_pairs: VecDeque::new(),
+			right_pairs: VecDeque::new(),
+			compared: 0,
+			changes: vec![],
+			shared: None,
+			digests: Default::default(),
+		}
+	}
+
+	/// Collect the keys read on both sides and whether their values match, instead of the
+	/// changes. Keys only read on one side are counted without being kept.
+	pub fn overlap(mut self) -> Self {
+		self.shared = Some(vec![]);
+		self
+	}
+
+	/// Keys read on both sides so far, `None` unless the comparison is an overlap
+	pub fn shared(&self) -> Option<&[SharedKey]> {
+		self.shared.as_deref()
+	}
+
+	pub fn left_cf(&self) -> &CF {
+		&self.left_cf
+	}
+
+	pub fn right_cf(&self) -> &CF {
+		&self.right_cf
+	}
+
+	/// Number of distinct keys compared so far
+	pub fn compared(&self) -> usize {
+		self.compared
+	}
+
+	pub fn changes(&self) -> &[PairChange] {
+		&self.changes
+	}
+
+	/// Take the changes found so far, a long comparison doesn't have to keep them
+	pub fn take_changes(&mut self) -> Vec<PairChange> {
+		std::mem::take(&mut self.changes)
+	}
+
+	/// Digests of the pairs read so far on the left and on the right
+	pub fn digests(&self) -> (KeyspaceDigest, KeyspaceDigest) {
+		self.digests
+	}
+
+	pub fn is_done(&self) -> bool {
+		!self.left.has_next()
+			&& !self.right.has_next()
+			&& self.left_pairs.is_empty()
+			&& self.right_pairs.is_empty()
+	}
+
+	/// Read the next batch of the side which ran out of pairs and merge the pairs of both
+	/// sides up to the last key read on either side. Both datastores can be the same
+	/// instance.
+	pub async fn diff_batch(&mut self, left: &Datastore, right: &Datastore) -> Result<(), Error> {
+		if self.left_pairs.is_empty() && self.left.has_next() {
+			let tx = left.transaction(false).await?;
+			let pairs = self.left.next_batch(&tx, SCAN_BATCH_SIZE).await?;
+			pairs.iter().for_each(|(key, value)| self.digests.0.add(key, value));
+			self.left_pairs.extend(pairs);
+		}
+		if self.right_pairs.is_empty() && self.right.has_next() {
+			let tx = right.transaction(false).await?;
+			let pairs = self.right.next_batch(&tx, SCAN_BATCH_SIZE).await?;
+			pairs.iter().for_each(|(key, value)| self.digests.1.add(key, value));
+			self.right_pairs.extend(pairs);
+		}
+		loop {
+			let ordering = match (self.left_pairs.front(), self.right_pairs.front()) {
+				(Some((left_key, _)), Some((right_key, _))) => left_key.cmp(right_key),
+				(Some(_), None) if !self.right.has_next() => Ordering::Less,
+				(None, Some(_)) if !self.left.has_next() => Ordering::Greater,
+				_ => break,
+			};
+			self.compared += 1;
+			if let Some(shared) = self.shared.as_mut() {
+				match ordering {
+					Ordering::Less => {
+						self.left_pairs.pop_front();
+					}
+					Ordering::Greater => {
+						self.right_pairs.pop_front();
+					}
+					Ordering::Equal => {
+						let (key, before) = self.left_pairs.pop_front().unwrap();
+						let (_, after) = self.right_pairs.pop_front().unwrap();
+						shared.push(SharedKey {
+							key,
+							same_value: before == after,
+						});
+					}
+				}
+				continue;
+			}
+			match ordering {
+				Ordering::Less => {
+					self.changes.push(PairChange::Removed(self.left_pairs.pop_front().unwrap()))
+				}
+				Ordering::Greater => {
+					self.changes.push(PairChange::Added(self.right_pairs.pop_front().unwrap()))
+				}
+				Ordering::Equal => {
+					let (key, before) = self.left_pairs.pop_front().unwrap();
+					let (_, after) = self.right_pairs.pop_front().unwrap();
+					if before != after {
+						self.changes.push(PairChange::Changed {
+							key,
+							before,
+							after,
+						});
+					}
+				}
+			}
+		}
+		Ok(())
+	}
+}
+
+/// Comparison of two keyspaces of databases addressed as `name:path`, advanced from the
+/// event loop
+pub struct BackgroundDiff {
+	pub left: String,
+	pub right: String,
+	pub job: KeyspaceDiff,
+}
+
+impl BackgroundDiff {
+	/// Side of the comparison in labels, the database is left out when both sides are in
+	/// the same database
+	pub fn side(&self, left: bool) -> String {
+		let (db_path, cf) = match left {
+			true => (&self.left, self.job.left_cf()),
+			false => (&self.right, self.job.right_cf()),
+		};
+		match self.left == self.right {
+			true => column_label(cf),
+			false => format!("{} {}", db_path, column_label(cf)),
+		}
+	}
+
+	/// Compare batches until `budget` elapsed, the databases are opened for the tick only
+	pub async fn tick(&mut self, config: &Config, budget: Duration) -> Result<(), Error> {
+		let started = Instant::now();
+		let left = open_datastore(config, &self.left)?;
+		let right = match self.right == self.left {
+			true => None,
+			false => Some(open_datastore(config, &self.right)?),
+		};
+		while !self.job.is_done() && started.elapsed() < budget {
+			self.job.diff_batch(&left, right.as_ref().unwrap_or(&left)).await?;
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,70 @@
+use std::{
+	env, fs,
+	io::Read,
+	path::{Path, PathBuf},
+};
+
+/// Directory levels searched under a discovery root
+const DISCOVER_DEPTH: usize = 4;
+
+/// Expand a leading `~` to the home directory
+fn expand_home(root: &str) -> PathBuf {
+	match (root.strip_prefix('~'), env::var_os("HOME")) {
+		(Some(rest), Some(home)) => Path::new(&home).join(rest.trim_start_matches('/')),
+		_ => PathBuf::from(root),
+	}
+}
+
+/// Backend of the database stored at a path, from the files each engine writes
+pub fn detect_backend(path: &Path) -> Option<&'static str> {
+	if path.is_file() {
+		let mut magic = [0u8; 4];
+		let mut file = fs::File::open(path).ok()?;
+		file.read_exact(&mut magic).ok()?;
+		return match &magic {
+			b"redb" => Some("redb"),
+			_ => None,
+		};
+	}
+	let has = |name: &str| path.join(name).exists();
+	if has("CURRENT") && has("IDENTITY") {
+		return Some("rocksdb");
+	}
+	if has("data.mdb") {
+		return Some("lmdb");
+	}
+	if has("conf") && has("db") {
+		return Some("sled");
+	}
+	None
+}
+
+fn discover_under(dir: &Path, depth: usize, found: &mut Vec<(String, String)>) {
+	let entries = match fs::read_dir(dir) {
+		Ok(entries) => entries,
+		Err(_) => return,
+	};
+	let mut paths: Vec<_> = entries.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+	paths.sort();
+	for path in paths {
+		match detect_backend(&path) {
+			Some(backend) => found.push((backend.to_string(), path.display().to_string())),
+			None if path.is_dir() && depth > 1 => discover_under(&path, depth - 1, found),
+			None => {}
+		}
+	}
+}
+
+/// Databases found under the `discover` roots of the config, as backend name and path. The
+/// files of a detected database are not searched further.
+pub fn discover_databases(roots: &[String]) -> Vec<(String, String)> {
+	let mut found = vec![];
+	for root in roots {
+		let root = expand_home(root);
+		match detect_backend(&root) {
+			Some(backend) => found.push((backend.to_string(), root.display().to_string())),
+			None => discover_under(&root, DISCOVER_DEPTH, &mut found),
+		}
+	}
+	found
+}
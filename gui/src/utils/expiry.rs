@@ -0,0 +1,91 @@
+use std::{
+	collections::VecDeque,
+	time::{Duration, Instant},
+};
+
+use db::purge_expired;
+
+use crate::{config::Config, constants::WRITE_BATCH_SIZE};
+
+use super::{format_span, now_timestamp, open_datastore};
+
+/// Periodic deletion of keys expired through EDMA TTL metadata. Like the stats refresh,
+/// databases are purged one per tick.
+#[derive(Default)]
+pub struct ExpiryWorker {
+	interval: Option<Duration>,
+	last_round: Option<Instant>,
+	pending: VecDeque<String>,
+	expired: usize,
+}
+
+impl ExpiryWorker {
+	pub fn new(config: &Config) -> Self {
+		ExpiryWorker {
+			interval: config.expiry_interval,
+			..Default::default()
+		}
+	}
+
+	/// Number of keys deleted since startup
+	pub fn expired(&self) -> usize {
+		self.expired
+	}
+
+	/// Purge the next pending database and start a new round once the interval elapsed.
+	/// Returns the number of keys deleted by this tick.
+	pub async fn tick(&mut self, config: &Config) -> usize {
+		let interval = match self.interval {
+			Some(interval) => interval,
+			None => return 0,
+		};
+		if self.pending.is_empty() {
+			if self.last_round.map_or(false, |last| last.elapsed() < interval) {
+				return 0;
+			}
+			self.last_round = Some(Instant::now());
+			self.pending = config
+				.databases
+				.iter()
+				.flat_map(|(name, dbs)| dbs.iter().map(move |db| format!("{}:{}", name, db.path)))
+				.collect();
+		}
+
+		let db_path = match self.pending.pop_front() {
+			Some(db_path) => db_path,
+			None => return 0,
+		};
+		// A database which fails to open is skipped, the health check reports it
+		let expired = match open_datastore(config, &db_path) {
+			Ok(ds) => purge_expired(&ds, now_timestamp(), WRITE_BATCH_SIZE).await.unwrap_or(0),
+			Err(_) => 0,
+		};
+		self.expired += expired;
+		expired
+	}
+}
+
+/// Bytes of the write time RocksDB TTL databases append to values, in seconds little endian
+pub const TTL_SUFFIX_LEN: usize = 4;
+
+/// Split the write time off a value of a RocksDB TTL database, `None` when the value is
+/// too short to carry it
+pub fn split_ttl_suffix(value: &[u8]) -> Option<(&[u8], i64)> {
+	let at = value.len().checked_sub(TTL_SUFFIX_LEN)?;
+	let written = u32::from_le_bytes(value[at..].try_into().ok()?);
+	Some((&value[..at], written as i64))
+}
+
+/// Append the write time to a value like a RocksDB TTL database
+pub fn append_ttl_suffix(mut value: Vec<u8>, written: i64) -> Vec<u8> {
+	value.extend_from_slice(&(written as u32).to_le_bytes());
+	value
+}
+
+/// Time left before an expiration, e.g. `expires in 1h 5m` or `expired 30s ago`
+pub fn describe_expiry(expires_at: i64, now: i64) -> String {
+	match expires_at > now {
+		true => format!("expires in {}", format_span(expires_at - now)),
+		false => format!("expired {} ago", format_span(now - expires_at)),
+	}
+}
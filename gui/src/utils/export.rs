@@ -0,0 +1,201 @@
+use std::{
+	io::{self, Write},
+	path::Path,
+};
+
+use db::KeyValuePair;
+use serde_json::json;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+	Markdown,
+	Html,
+	Json,
+	Csv,
+	/// One JSON object per line
+	Ndjson,
+}
+
+impl ExportFormat {
+	/// Export format named by the `FORMAT` option, case insensitive
+	pub fn from_name(name: &str) -> Option<ExportFormat> {
+		match name.to_lowercase().as_str() {
+			"md" | "markdown" => Some(ExportFormat::Markdown),
+			"html" => Some(ExportFormat::Html),
+			"json" => Some(ExportFormat::Json),
+			"csv" => Some(ExportFormat::Csv),
+			"ndjson" | "jsonl" => Some(ExportFormat::Ndjson),
+			_ => None,
+		}
+	}
+
+	/// Export format inferred from the file extension of `path`
+	pub fn from_path(path: &str) -> Option<ExportFormat> {
+		match Path::new(path).extension()?.to_str()? {
+			"htm" => Some(ExportFormat::Html),
+			extension => ExportFormat::from_name(extension),
+		}
+	}
+}
+
+/// Order of the exported rows
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ExportOrder {
+	/// Ascending byte order of the keys, whatever the scan order, comparator or locale, so
+	/// exports of the same pairs can be diffed across runs
+	#[default]
+	Key,
+	/// Order the pairs were scanned and displayed in
+	Insertion,
+}
+
+impl ExportOrder {
+	/// Export order named by the `ORDER` option, case insensitive
+	pub fn from_name(name: &str) -> Option<ExportOrder> {
+		match name.to_lowercase().as_str() {
+			"key" => Some(ExportOrder::Key),
+			"insertion" => Some(ExportOrder::Insertion),
+			_ => None,
+		}
+	}
+
+	/// Pairs in export order. The sort is stable, pairs with the same key keep their order.
+	pub fn sort<'a>(&self, pairs: &'a [KeyValuePair]) -> Vec<&'a KeyValuePair> {
+		let mut sorted: Vec<_> = pairs.iter().collect();
+		if *self == ExportOrder::Key {
+			sorted.sort_by(|a, b| a.0.cmp(&b.0));
+		}
+		sorted
+	}
+}
+
+fn escape_markdown(cell: &str) -> String {
+	cell.replace('|', "\\|").replace('\n', "<br>")
+}
+
+fn escape_html(cell: &str) -> String {
+	cell.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn escape_csv(cell: &str) -> String {
+	match cell.contains([',', '"', '\n', '\r']) {
+		true => format!("\"{}\"", cell.replace('"', "\"\"")),
+		false => cell.to_string(),
+	}
+}
+
+/// JSON object of a row keyed by the headers, in header order
+fn json_object(headers: &[String], row: &[String]) -> String {
+	let fields: Vec<_> = headers
+		.iter()
+		.zip(row.iter())
+		.map(|(header, cell)| format!("{}:{}", json!(header), json!(cell)))
+		.collect();
+	format!("{{{}}}", fields.join(","))
+}
+
+/// # Table writer
+/// Writes a table of string cells in an export format one row at a time, so an export
+/// never holds more than a row in memory.
+pub struct TableWriter<W: Write> {
+	writer: W,
+	format: ExportFormat,
+	headers: Vec<String>,
+	rows: usize,
+}
+
+impl<W: Write> TableWriter<W> {
+	/// Write the header of the table
+	pub fn new(mut writer: W, format: ExportFormat, headers: Vec<String>) -> io::Result<Self> {
+		match format {
+			ExportFormat::Markdown => {
+				let separator = vec!["---".to_string(); headers.len()];
+				write_markdown_line(&mut writer, &headers)?;
+				write_markdown_line(&mut writer, &separator)?;
+			}
+			ExportFormat::Html => {
+				writer.write_all(b"<table>\n  <thead>\n")?;
+				write_html_line(&mut writer, "th", &headers)?;
+				writer.write_all(b"  </thead>\n  <tbody>\n")?;
+			}
+			ExportFormat::Csv => {
+				let cells: Vec<_> = headers.iter().map(|c| escape_csv(c)).collect();
+				writeln!(writer, "{}", cells.join(","))?;
+			}
+			ExportFormat::Json => writer.write_all(b"[")?,
+			ExportFormat::Ndjson => {}
+		}
+		Ok(TableWriter {
+			writer,
+			format,
+			headers,
+			rows: 0,
+		})
+	}
+
+	pub fn write_row(&mut self, row: &[String]) -> io::Result<()> {
+		match self.format {
+			ExportFormat::Markdown => write_markdown_line(&mut self.writer, row)?,
+			ExportFormat::Html => write_html_line(&mut self.writer, "td", row)?,
+			ExportFormat::Csv => {
+				let cells: Vec<_> = row.iter().map(|c| escape_csv(c)).collect();
+				writeln!(self.writer, "{}", cells.join(","))?;
+			}
+			ExportFormat::Json => {
+				let separator = if self.rows == 0 {
+					"\n"
+				} else {
+					",\n"
+				};
+				write!(self.writer, "{}  {}", separator, json_object(&self.headers, row))?;
+			}
+			ExportFormat::Ndjson => writeln!(self.writer, "{}", json_object(&self.headers, row))?,
+		}
+		self.rows += 1;
+		Ok(())
+	}
+
+	/// Write the end of the table and flush it. Returns the number of written rows.
+	pub fn finish(mut self) -> io::Result<usize> {
+		match self.format {
+			ExportFormat::Html => self.writer.write_all(b"  </tbody>\n</table>\n")?,
+			ExportFormat::Json => self.writer.write_all(b"\n]\n")?,
+			_ => {}
+		}
+		self.writer.flush()?;
+		Ok(self.rows)
+	}
+}
+
+/// Write `pairs` in `format` and `order` under `headers`, `row` gives the cells of a pair
+/// from its index in export order. Every export of pairs goes through here, so all of
+/// them share the same order. Returns the number of written rows.
+pub fn write_pair_table<W, F>(
+	writer: W,
+	format: ExportFormat,
+	order: ExportOrder,
+	headers: Vec<String>,
+	pairs: &[KeyValuePair],
+	mut row: F,
+) -> io::Result<usize>
+where
+	W: Write,
+	F: FnMut(usize, &KeyValuePair) -> Vec<String>,
+{
+	let mut writer = TableWriter::new(writer, format, headers)?;
+	for (index, pair) in order.sort(pairs).into_iter().enumerate() {
+		writer.write_row(&row(index, pair))?;
+	}
+	writer.finish()
+}
+
+fn write_markdown_line<W: Write>(writer: &mut W, cells: &[String]) -> io::Result<()> {
+	let cells: Vec<_> = cells.iter().map(|c| escape_markdown(c)).collect();
+	writeln!(writer, "| {} |", cells.join(" | "))
+}
+
+fn write_html_line<W: Write>(writer: &mut W, tag: &str, cells: &[String]) -> io::Result<()> {
+	let cells: Vec<_> =
+		cells.iter().map(|c| format!("<{}>{}</{}>", tag, escape_html(c), tag)).collect();
+	writeln!(writer, "    <tr>{}</tr>", cells.join(""))
+}
@@ -0,0 +1,304 @@
+use std::fmt;
+
+use super::{ByteLayout, FromLayoutVariant, LayoutTemplate, LayoutVariant};
+
+/// Value of an expression, numbers are computed as `f64`
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExprValue {
+	Number(f64),
+	Text(String),
+}
+
+impl fmt::Display for ExprValue {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			// Whole numbers are shown without a fraction
+			ExprValue::Number(n) if n.fract() == 0.0 && n.abs() < 1e15 => {
+				write!(f, "{}", *n as i64)
+			}
+			ExprValue::Number(n) => write!(f, "{}", n),
+			ExprValue::Text(text) => write!(f, "{}", text),
+		}
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operator {
+	Add,
+	Subtract,
+	Multiply,
+	Divide,
+	Remainder,
+}
+
+/// # Expressions
+/// Arithmetic on the segments of a layout template, e.g. `ts / 1000`. Segments are
+/// referenced by name, optionally written `segment.<name>`. `+` concatenates when one side
+/// is text, e.g. `kind + ":" + id`. `*`, `/` and `%` bind tighter than `+` and `-`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+	Number(f64),
+	Text(String),
+	Segment(String),
+	Negate(Box<Expr>),
+	Binary(Operator, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+	Number(f64),
+	Text(String),
+	Ident(String),
+	Operator(Operator),
+	Open,
+	Close,
+}
+
+fn lex(text: &str) -> Result<Vec<Token>, String> {
+	let mut tokens = vec![];
+	let mut chars = text.chars().peekable();
+	while let Some(&c) = chars.peek() {
+		let token = match c {
+			c if c.is_whitespace() => {
+				chars.next();
+				continue;
+			}
+			'0'..='9' => {
+				let mut number = String::new();
+				while let Some(&c) = chars.peek().filter(|c| c.is_ascii_digit() || **c == '.') {
+					number.push(c);
+					chars.next();
+				}
+				Token::Number(number.parse().map_err(|_| format!("Invalid number {}", number))?)
+			}
+			'"' | '\'' => {
+				chars.next();
+				let text: String = chars.by_ref().take_while(|q| *q != c).collect();
+				Token::Text(text)
+			}
+			c if c.is_alphabetic() || c == '_' => {
+				let mut ident = String::new();
+				while let Some(&c) =
+					chars.peek().filter(|c| c.is_alphanumeric() || **c == '_' || **c == '.')
+				{
+					ident.push(c);
+					chars.next();
+				}
+				let name = ident.strip_prefix("segment.").unwrap_or(&ident);
+				Token::Ident(name.to_string())
+			}
+			_ => {
+				chars.next();
+				match c {
+					'+' => Token::Operator(Operator::Add),
+					'-' => Token::Operator(Operator::Subtract),
+					'*' => Token::Operator(Operator::Multiply),
+					'/' => Token::Operator(Operator::Divide),
+					'%' => Token::Operator(Operator::Remainder),
+					'(' => Token::Open,
+					')' => Token::Close,
+					_ => return Err(format!("Unexpected {} in expression", c)),
+				}
+			}
+		};
+		tokens.push(token);
+	}
+	Ok(tokens)
+}
+
+struct Parser {
+	tokens: Vec<Token>,
+	position: usize,
+}
+
+impl Parser {
+	fn next(&mut self) -> Option<Token> {
+		let token = self.tokens.get(self.position).cloned();
+		self.position += 1;
+		token
+	}
+
+	/// Next token when it is one of the operators
+	fn operator(&mut self, operators: &[Operator]) -> Option<Operator> {
+		match self.tokens.get(self.position) {
+			Some(Token::Operator(operator)) if operators.contains(operator) => {
+				self.position += 1;
+				Some(*operator)
+			}
+			_ => None,
+		}
+	}
+
+	fn sum(&mut self) -> Result<Expr, String> {
+		let mut expr = self.product()?;
+		while let Some(operator) = self.operator(&[Operator::Add, Operator::Subtract]) {
+			expr = Expr::Binary(operator, Box::new(expr), Box::new(self.product()?));
+		}
+		Ok(expr)
+	}
+
+	fn product(&mut self) -> Result<Expr, String> {
+		let mut expr = self.unary()?;
+		let operators = [Operator::Multiply, Operator::Divide, Operator::Remainder];
+		while let Some(operator) = self.operator(&operators) {
+			expr = Expr::Binary(operator, Box::new(expr), Box::new(self.unary()?));
+		}
+		Ok(expr)
+	}
+
+	fn unary(&mut self) -> Result<Expr, String> {
+		match self.next() {
+			Some(Token::Operator(Operator::Subtract)) => Ok(Expr::Negate(Box::new(self.unary()?))),
+			Some(Token::Number(n)) => Ok(Expr::Number(n)),
+			Some(Token::Text(text)) => Ok(Expr::Text(text)),
+			Some(Token::Ident(name)) => Ok(Expr::Segment(name)),
+			Some(Token::Open) => {
+				let expr = self.sum()?;
+				match self.next() {
+					Some(Token::Close) => Ok(expr),
+					_ => Err("Missing closing parenthesis".to_string()),
+				}
+			}
+			_ => Err("Incomplete expression".to_string()),
+		}
+	}
+}
+
+/// Parse an expression
+pub fn parse_expression(text: &str) -> Result<Expr, String> {
+	let mut parser = Parser {
+		tokens: lex(text)?,
+		position: 0,
+	};
+	let expr = parser.sum()?;
+	match parser.position < parser.tokens.len() {
+		true => Err(format!("Unexpected token in expression {}", text)),
+		false => Ok(expr),
+	}
+}
+
+impl Expr {
+	/// Evaluate the expression, segments are resolved with `segment`
+	pub fn evaluate(
+		&self,
+		segment: &dyn Fn(&str) -> Result<ExprValue, String>,
+	) -> Result<ExprValue, String> {
+		Ok(match self {
+			Expr::Number(n) => ExprValue::Number(*n),
+			Expr::Text(text) => ExprValue::Text(text.clone()),
+			Expr::Segment(name) => segment(name)?,
+			Expr::Negate(expr) => match expr.evaluate(segment)? {
+				ExprValue::Number(n) => ExprValue::Number(-n),
+				ExprValue::Text(text) => return Err(format!("Cannot negate text {}", text)),
+			},
+			Expr::Binary(operator, left, right) => {
+				match (operator, left.evaluate(segment)?, right.evaluate(segment)?) {
+					(_, ExprValue::Number(l), ExprValue::Number(r)) => {
+						ExprValue::Number(match operator {
+							Operator::Add => l + r,
+							Operator::Subtract => l - r,
+							Operator::Multiply => l * r,
+							Operator::Divide | Operator::Remainder if r == 0.0 => {
+								return Err("Division by zero".to_string())
+							}
+							Operator::Divide => l / r,
+							Operator::Remainder => l % r,
+						})
+					}
+					(Operator::Add, l, r) => ExprValue::Text(format!("{}{}", l, r)),
+					_ => return Err("Arithmetic on text".to_string()),
+				}
+			}
+		})
+	}
+}
+
+/// Column of a layout template computed from its segments, configured with `expr`
+#[derive(Clone, Debug)]
+pub struct ComputedColumn {
+	pub name: String,
+	pub expr: Expr,
+}
+
+impl ComputedColumn {
+	pub fn new(name: &str, expression: &str) -> Result<Self, String> {
+		Ok(ComputedColumn {
+			name: name.to_string(),
+			expr: parse_expression(expression)?,
+		})
+	}
+}
+
+/// Leading bytes of a segment, as read by `format_variant`
+fn leading<const N: usize>(bytes: &[u8]) -> Option<[u8; N]> {
+	bytes.get(..N)?.try_into().ok()
+}
+
+/// Numbers keep their raw value, a timestamp is not converted to seconds. Other variants
+/// are text formatted like in the preview.
+fn segment_value(layout: &ByteLayout, raw: &[u8]) -> Result<ExprValue, String> {
+	let (start, end) = (layout.from, std::cmp::min(layout.to, raw.len()));
+	let bytes = raw.get(start..end).ok_or_else(|| format!("Segment {} overflows", layout.name))?;
+	let number = match layout.variant {
+		LayoutVariant::Int32 => leading(bytes).map(|b| i32::from_be_bytes(b) as f64),
+		LayoutVariant::Int64 => leading(bytes).map(|b| i64::from_be_bytes(b) as f64),
+		LayoutVariant::Float32 => leading(bytes).map(|b| f32::from_be_bytes(b) as f64),
+		LayoutVariant::Float64 => leading(bytes).map(f64::from_be_bytes),
+		LayoutVariant::Timestamp => match bytes.len() {
+			4 => leading(bytes).map(|b| i32::from_be_bytes(b) as f64),
+			_ => leading(bytes).map(|b| i64::from_be_bytes(b) as f64),
+		},
+		_ => {
+			let text = bytes.to_vec().format_variant(layout.variant.clone(), &layout.format);
+			return Ok(ExprValue::Text(text));
+		}
+	};
+	number.map(ExprValue::Number).ok_or_else(|| format!("Segment {} is not a number", layout.name))
+}
+
+impl LayoutTemplate {
+	/// Value of the computed column `name` over raw bytes, `None` when the template has no
+	/// such column
+	pub fn compute(&self, name: &str, raw: &[u8]) -> Option<Result<ExprValue, String>> {
+		let column = self.columns.iter().find(|c| c.name == name)?;
+		Some(column.expr.evaluate(&|segment: &str| {
+			match self.layout.iter().find(|l| l.name == segment) {
+				Some(layout) => segment_value(layout, raw),
+				None => Err(format!("Unknown segment {}", segment)),
+			}
+		}))
+	}
+
+	/// Every computed column of the template formatted over raw bytes
+	pub fn compute_all(&self, raw: &[u8]) -> Vec<String> {
+		self.columns
+			.iter()
+			.map(|column| match self.compute(&column.name, raw) {
+				Some(Ok(value)) => value.to_string(),
+				Some(Err(err)) => format!("Unable to compute: {}", err),
+				None => String::new(),
+			})
+			.collect()
+	}
+}
+
+/// Computed columns of the selected key and value templates, evaluated on the key and the
+/// value of a pair respectively. A key column shadows a value column of the same name.
+#[derive(Clone, Copy, Default)]
+pub struct ColumnScope<'a> {
+	pub key: Option<&'a LayoutTemplate>,
+	pub value: Option<&'a LayoutTemplate>,
+}
+
+impl ColumnScope<'_> {
+	pub fn compute(&self, name: &str, key: &[u8], value: &[u8]) -> Result<ExprValue, String> {
+		self.key
+			.and_then(|t| t.compute(name, key))
+			.or_else(|| self.value.and_then(|t| t.compute(name, value)))
+			.unwrap_or_else(|| Err(format!("Unknown column {}", name)))
+	}
+	/// Whether the checksum segments of the key and value layouts match the pair
+	pub fn verify(&self, key: &[u8], value: &[u8]) -> bool {
+		self.key.map_or(true, |t| t.verify(key)) && self.value.map_or(true, |t| t.verify(value))
+	}
+}
@@ -0,0 +1,298 @@
+use std::{
+	fs, io,
+	path::{Path, PathBuf},
+};
+
+use db::KeyValuePair;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{ColumnScope, ExprValue, JsonPath};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Comparison {
+	Less,
+	LessOrEqual,
+	Equal,
+	GreaterOrEqual,
+	Greater,
+}
+
+impl Comparison {
+	fn compare<T: PartialOrd>(&self, left: T, right: T) -> bool {
+		match self {
+			Comparison::Less => left < right,
+			Comparison::LessOrEqual => left <= right,
+			Comparison::Equal => left == right,
+			Comparison::GreaterOrEqual => left >= right,
+			Comparison::Greater => left > right,
+		}
+	}
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Predicate {
+	Prefix(Vec<u8>),
+	Suffix(Vec<u8>),
+	KeyContains(String),
+	ValueContains(String),
+	/// Value size in bytes
+	Size(Comparison, usize),
+	/// Node of the JSON decoded value selected by the path equals the expected value
+	Json(JsonPath, Value),
+	/// Computed column of the selected layouts compared with a number or a text
+	Column(String, Comparison, String),
+}
+
+/// # Filter expressions
+/// Predicates on pairs composed with `AND`, `OR`, `NOT` and parentheses, e.g.
+/// `PREFIX=a AND (VALUE~"error" OR SIZE>1024)`. `AND` binds tighter than `OR`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FilterExpr {
+	Predicate(Predicate),
+	Not(Box<FilterExpr>),
+	And(Box<FilterExpr>, Box<FilterExpr>),
+	Or(Box<FilterExpr>, Box<FilterExpr>),
+}
+
+/// Compare numbers when both sides are numbers, text otherwise
+fn compare_column(value: ExprValue, comparison: Comparison, expected: &str) -> bool {
+	match (value, expected.parse::<f64>()) {
+		(ExprValue::Number(n), Ok(expected)) => comparison.compare(n, expected),
+		(value, _) => comparison.compare(value.to_string().as_str(), expected),
+	}
+}
+
+impl FilterExpr {
+	pub fn matches(&self, key: &[u8], value: &[u8], columns: ColumnScope) -> bool {
+		match self {
+			FilterExpr::Predicate(predicate) => match predicate {
+				Predicate::Prefix(prefix) => key.starts_with(prefix),
+				Predicate::Suffix(suffix) => key.ends_with(suffix),
+				Predicate::KeyContains(text) => {
+					String::from_utf8_lossy(key).contains(text.as_str())
+				}
+				Predicate::ValueContains(text) => {
+					String::from_utf8_lossy(value).contains(text.as_str())
+				}
+				Predicate::Size(comparison, size) => comparison.compare(value.len(), *size),
+				Predicate::Json(path, expected) => serde_json::from_slice::<Value>(value)
+					.ok()
+					.map_or(false, |json| path.select(&json) == Some(expected)),
+				// A column that cannot be computed on the pair never matches
+				Predicate::Column(name, comparison, expected) => columns
+					.compute(name, key, value)
+					.map_or(false, |computed| compare_column(computed, *comparison, expected)),
+			},
+			FilterExpr::Not(expr) => !expr.matches(key, value, columns),
+			FilterExpr::And(left, right) => {
+				left.matches(key, value, columns) && right.matches(key, value, columns)
+			}
+			FilterExpr::Or(left, right) => {
+				left.matches(key, value, columns) || right.matches(key, value, columns)
+			}
+		}
+	}
+
+	/// Keep the pairs matching the expression. Pairs are filtered as they are consumed, so
+	/// only matching pairs are collected. Computed columns are read from `columns`.
+	pub fn apply<I>(&self, pairs: I, columns: ColumnScope) -> Vec<KeyValuePair>
+	where
+		I: IntoIterator<Item = KeyValuePair>,
+	{
+		pairs.into_iter().filter(|(key, value)| self.matches(key, value, columns)).collect()
+	}
+}
+
+/// Split on whitespace, keeping quoted text in a single token
+pub fn split_quoted(text: &str) -> Vec<String> {
+	let mut tokens = vec![];
+	let mut current = String::new();
+	let mut quoted = false;
+	for c in text.chars() {
+		match c {
+			'"' => {
+				quoted = !quoted;
+				current.push(c);
+			}
+			c if c.is_whitespace() && !quoted => {
+				if !current.is_empty() {
+					tokens.push(std::mem::take(&mut current));
+				}
+			}
+			c => current.push(c),
+		}
+	}
+	if !current.is_empty() {
+		tokens.push(current);
+	}
+	tokens
+}
+
+/// Whether the command text is a filter expression rather than a list of commands
+pub fn is_filter_expression(text: &str) -> bool {
+	split_quoted(text).iter().any(|token| {
+		["AND", "OR", "NOT"].contains(&token.as_str())
+			|| token.starts_with('(')
+			|| token.starts_with("KEY~")
+			|| token.starts_with("VALUE~")
+			|| token.starts_with("VALUE.$")
+			|| token.starts_with("COMPUTED.")
+			|| ["SIZE<", "SIZE>", "SIZE="].iter().any(|p| token.starts_with(p))
+	})
+}
+
+fn lex(text: &str) -> Vec<String> {
+	let mut tokens = vec![];
+	for token in split_quoted(text) {
+		let body = token.trim_start_matches('(');
+		let opened = token.len() - body.len();
+		let trimmed = body.trim_end_matches(')');
+		let closed = body.len() - trimmed.len();
+		tokens.extend(std::iter::repeat("(".to_string()).take(opened));
+		if !trimmed.is_empty() {
+			tokens.push(trimmed.to_string());
+		}
+		tokens.extend(std::iter::repeat(")".to_string()).take(closed));
+	}
+	tokens
+}
+
+fn parse_predicate(token: &str) -> Result<Predicate, String> {
+	let unquote = |value: &str| value.trim_matches('"').to_string();
+	if let Some(value) = token.strip_prefix("PREFIX=") {
+		return Ok(Predicate::Prefix(unquote(value).into_bytes()));
+	}
+	if let Some(value) = token.strip_prefix("SUFFIX=") {
+		return Ok(Predicate::Suffix(unquote(value).into_bytes()));
+	}
+	if let Some(value) = token.strip_prefix("KEY~") {
+		return Ok(Predicate::KeyContains(unquote(value)));
+	}
+	if let Some(value) = token.strip_prefix("VALUE~") {
+		return Ok(Predicate::ValueContains(unquote(value)));
+	}
+	if let Some(rest) = token.strip_prefix("VALUE.") {
+		let (path, expected) = rest.split_once('=').ok_or(format!("Invalid filter {}", token))?;
+		// Expected values are JSON literals, bare text is compared as a string
+		let expected =
+			serde_json::from_str(expected).unwrap_or_else(|_| Value::String(expected.to_string()));
+		return Ok(Predicate::Json(JsonPath::parse(path)?, expected));
+	}
+	let comparisons = [
+		(">=", Comparison::GreaterOrEqual),
+		("<=", Comparison::LessOrEqual),
+		(">", Comparison::Greater),
+		("<", Comparison::Less),
+		("=", Comparison::Equal),
+	];
+	if let Some(rest) = token.strip_prefix("COMPUTED.") {
+		let end = rest.find(|c| c == '<' || c == '>' || c == '=').unwrap_or(rest.len());
+		let (name, condition) = rest.split_at(end);
+		for (operator, comparison) in comparisons {
+			if let Some(expected) = condition.strip_prefix(operator).filter(|_| !name.is_empty()) {
+				return Ok(Predicate::Column(name.to_string(), comparison, unquote(expected)));
+			}
+		}
+	}
+	if let Some(rest) = token.strip_prefix("SIZE") {
+		for (operator, comparison) in comparisons {
+			if let Some(size) = rest.strip_prefix(operator) {
+				let size = size.parse().map_err(|_| format!("Invalid size in {}", token))?;
+				return Ok(Predicate::Size(comparison, size));
+			}
+		}
+	}
+	Err(format!("Invalid filter {}", token))
+}
+
+struct Parser {
+	tokens: Vec<String>,
+	position: usize,
+}
+
+impl Parser {
+	fn peek(&self) -> Option<&str> {
+		self.tokens.get(self.position).map(|t| t.as_str())
+	}
+
+	fn next(&mut self) -> Option<String> {
+		let token = self.tokens.get(self.position).cloned();
+		self.position += 1;
+		token
+	}
+
+	fn or(&mut self) -> Result<FilterExpr, String> {
+		let mut expr = self.and()?;
+		while self.peek() == Some("OR") {
+			self.position += 1;
+			expr = FilterExpr::Or(Box::new(expr), Box::new(self.and()?));
+		}
+		Ok(expr)
+	}
+
+	fn and(&mut self) -> Result<FilterExpr, String> {
+		let mut expr = self.unary()?;
+		while self.peek() == Some("AND") {
+			self.position += 1;
+			expr = FilterExpr::And(Box::new(expr), Box::new(self.unary()?));
+		}
+		Ok(expr)
+	}
+
+	fn unary(&mut self) -> Result<FilterExpr, String> {
+		match self.next().as_deref() {
+			Some("NOT") => Ok(FilterExpr::Not(Box::new(self.unary()?))),
+			Some("(") => {
+				let expr = self.or()?;
+				match self.next().as_deref() {
+					Some(")") => Ok(expr),
+					_ => Err("Missing closing parenthesis".to_string()),
+				}
+			}
+			Some(token) => Ok(FilterExpr::Predicate(parse_predicate(token)?)),
+			None => Err("Incomplete filter expression".to_string()),
+		}
+	}
+}
+
+/// Parse a filter expression
+pub fn parse_filter(text: &str) -> Result<FilterExpr, String> {
+	let mut parser = Parser {
+		tokens: lex(text),
+		position: 0,
+	};
+	let expr = parser.or()?;
+	match parser.peek() {
+		Some(token) => Err(format!("Unexpected {} in filter", token)),
+		None => Ok(expr),
+	}
+}
+
+/// Filter expression saved with SAVE and recalled with FILTER
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedFilter {
+	pub name: String,
+	pub expression: String,
+}
+
+/// Saved filters are persisted next to the config file
+pub fn filters_path(config_path: &str) -> PathBuf {
+	Path::new(config_path).with_file_name("filters.json")
+}
+
+pub fn load_filters(path: &Path) -> Vec<SavedFilter> {
+	fs::read_to_string(path)
+		.ok()
+		.and_then(|data| serde_json::from_str(&data).ok())
+		.unwrap_or_default()
+}
+
+/// Persist a filter, replacing the filter with the same name
+pub fn save_filter(path: &Path, filter: SavedFilter) -> io::Result<()> {
+	let mut filters = load_filters(path);
+	filters.retain(|f| f.name != filter.name);
+	filters.push(filter);
+	let data = serde_json::to_string_pretty(&filters)?;
+	fs::write(path, data)
+}
@@ -0,0 +1,139 @@
+use std::{
+	sync::atomic::{AtomicBool, Ordering},
+	time::Duration,
+};
+
+use chrono::{
+	format::{Item, StrftimeItems},
+	FixedOffset, TimeZone,
+};
+
+use super::now_timestamp;
+
+/// `strftime` format of timestamp segments when none is configured
+pub const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+static RELATIVE_TIME: AtomicBool = AtomicBool::new(false);
+
+/// Render timestamps relative to now and durations as spans like `2d 4h`, toggled with a
+/// key binding for every table, detail pane and report
+pub fn set_relative_time(enabled: bool) {
+	RELATIVE_TIME.store(enabled, Ordering::Relaxed);
+}
+
+pub fn relative_time() -> bool {
+	RELATIVE_TIME.load(Ordering::Relaxed)
+}
+
+/// Span of seconds with its two largest units, e.g. `2d 4h`, `1h 5m` or `30s`
+pub fn format_span(secs: i64) -> String {
+	match secs {
+		s if s >= 86_400 => format!("{}d {}h", s / 86_400, s % 86_400 / 3600),
+		s if s >= 3600 => format!("{}h {}m", s / 3600, s % 3600 / 60),
+		s if s >= 60 => format!("{}m {}s", s / 60, s % 60),
+		s => format!("{}s", s),
+	}
+}
+
+/// Unix seconds relative to `now`, e.g. `3h 2m ago` or `in 5m 0s`
+pub fn relative_timestamp(secs: i64, now: i64) -> String {
+	match secs > now {
+		true => format!("in {}", format_span(secs - now)),
+		false if now - secs < 1 => "now".to_string(),
+		false => format!("{} ago", format_span(now - secs)),
+	}
+}
+
+/// Duration of a command or a job, in `ms` with `millis` or in `s`, and as a span when
+/// relative time is on
+pub fn format_duration(duration: Duration, millis: bool) -> String {
+	match (relative_time(), millis) {
+		(true, _) if duration.as_secs() == 0 => format!("{} ms", duration.as_millis()),
+		(true, _) => format_span(duration.as_secs() as i64),
+		(false, true) => format!("{} ms", duration.as_millis()),
+		(false, false) => format!("{}s", duration.as_secs()),
+	}
+}
+
+/// Rendering settings of decoded numbers and timestamps, set globally with the `display`
+/// config and overridden per byte layout
+#[derive(Clone, Debug)]
+pub struct DisplayFormat {
+	/// Separator inserted between groups of three digits, digits are not grouped when `None`
+	pub thousands_separator: Option<String>,
+	/// `strftime` format of timestamp segments
+	pub date_format: String,
+	/// Offset east of UTC, in seconds, timestamp segments are displayed in
+	pub utc_offset: i32,
+}
+
+impl Default for DisplayFormat {
+	fn default() -> Self {
+		DisplayFormat {
+			thousands_separator: None,
+			date_format: DEFAULT_DATE_FORMAT.to_string(),
+			utc_offset: 0,
+		}
+	}
+}
+
+impl DisplayFormat {
+	/// Group the digits of the integer part of a decimal number with the thousands separator
+	pub fn number(&self, value: &str) -> String {
+		let separator = match &self.thousands_separator {
+			Some(separator) => separator,
+			None => return value.to_string(),
+		};
+		let (sign, unsigned) = match value.strip_prefix('-') {
+			Some(unsigned) => ("-", unsigned),
+			None => ("", value),
+		};
+		let (integer, fraction) = unsigned.split_at(unsigned.find('.').unwrap_or(unsigned.len()));
+		// Leave NaN and infinity untouched
+		if integer.is_empty() || !integer.bytes().all(|b| b.is_ascii_digit()) {
+			return value.to_string();
+		}
+		let mut grouped = String::new();
+		for (index, digit) in integer.chars().enumerate() {
+			if index > 0 && (integer.len() - index) % 3 == 0 {
+				grouped.push_str(separator);
+			}
+			grouped.push(digit);
+		}
+		format!("{}{}{}", sign, grouped, fraction)
+	}
+
+	/// Render unix seconds with the date format in the configured timezone, or relative
+	/// to now when relative time is on
+	pub fn timestamp(&self, secs: i64) -> Option<String> {
+		if relative_time() {
+			return Some(relative_timestamp(secs, now_timestamp()));
+		}
+		let offset = FixedOffset::east_opt(self.utc_offset)?;
+		let time = offset.timestamp_opt(secs, 0).single()?;
+		Some(time.format(&self.date_format).to_string())
+	}
+}
+
+/// Whether `format` only holds valid `strftime` specifiers
+pub fn is_date_format(format: &str) -> bool {
+	!StrftimeItems::new(format).any(|item| matches!(item, Item::Error))
+}
+
+/// Parse a timezone written `UTC` or as an offset like `+02:00` into seconds east of UTC
+pub fn parse_utc_offset(timezone: &str) -> Option<i32> {
+	if timezone.eq_ignore_ascii_case("UTC") || timezone == "Z" {
+		return Some(0);
+	}
+	let (sign, offset) = match timezone.chars().next()? {
+		'+' => (1, &timezone[1..]),
+		'-' => (-1, &timezone[1..]),
+		_ => return None,
+	};
+	let (hours, minutes) = offset.split_once(':').unwrap_or((offset, "0"));
+	let (hours, minutes): (i32, i32) = (hours.parse().ok()?, minutes.parse().ok()?);
+	if !(0..24).contains(&hours) || !(0..60).contains(&minutes) {
+		return None;
+	}
+	Some(sign * (hours * 3600 + minutes * 60))
+}
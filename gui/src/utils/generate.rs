@@ -0,0 +1,71 @@
+use db::KeyValuePair;
+use rand::{distributions::Alphanumeric, rngs::StdRng, Rng, SeedableRng};
+use uuid::Builder;
+
+use super::{now_timestamp, LayoutTemplate, LayoutVariant};
+
+/// Characters of the text of unbounded String and Bytes segments, and of keys and values
+/// generated without layout
+pub const GENERATED_TEXT_LEN: usize = 16;
+/// Generated timestamps fall within this many seconds before now
+const GENERATED_TIMESTAMP_SPAN: i64 = 365 * 24 * 3600;
+
+fn random_text(rng: &mut StdRng, len: usize) -> String {
+	rng.sample_iter(&Alphanumeric).take(len).map(char::from).collect()
+}
+
+/// Random text of a segment of `width` bytes, encoded by the variant like text written in a
+/// command. Text segments fill their width, unbounded ones take `GENERATED_TEXT_LEN`.
+fn random_segment(rng: &mut StdRng, variant: &LayoutVariant, width: Option<usize>) -> String {
+	match variant {
+		LayoutVariant::String | LayoutVariant::Bytes => {
+			random_text(rng, width.unwrap_or(GENERATED_TEXT_LEN))
+		}
+		LayoutVariant::Int32 => rng.gen::<i32>().to_string(),
+		LayoutVariant::Int64 => rng.gen::<i64>().to_string(),
+		LayoutVariant::Float32 => rng.gen_range(-1e6f32..1e6).to_string(),
+		LayoutVariant::Float64 => rng.gen_range(-1e6f64..1e6).to_string(),
+		LayoutVariant::Boolean => rng.gen::<bool>().to_string(),
+		LayoutVariant::Timestamp => {
+			(now_timestamp() - rng.gen_range(0..GENERATED_TIMESTAMP_SPAN)).to_string()
+		}
+		LayoutVariant::UuidV4 => Builder::from_random_bytes(rng.gen()).into_uuid().to_string(),
+	}
+}
+
+/// Random bytes matching the segments of a template, checksum segments are computed over
+/// the generated ones. Without template, random text is generated.
+fn random_bytes(rng: &mut StdRng, template: Option<&LayoutTemplate>) -> Result<Vec<u8>, String> {
+	let template = match template {
+		Some(template) => template,
+		None => return Ok(random_text(rng, GENERATED_TEXT_LEN).into_bytes()),
+	};
+	let segments: Vec<_> = template
+		.layout
+		.iter()
+		.map(|l| {
+			let width = (l.to != usize::MAX).then(|| l.to.saturating_sub(l.from));
+			(l.name.clone(), random_segment(rng, &l.variant, width))
+		})
+		.collect();
+	template.encode(|name| segments.iter().find(|(n, _)| n == name).map(|(_, t)| t.clone()))
+}
+
+/// `count` pairs with random segment values matching the key and value layouts. The same
+/// `seed` generates the same pairs, keys generated twice are written once.
+pub fn generate_pairs(
+	count: usize,
+	seed: Option<u64>,
+	key_template: Option<&LayoutTemplate>,
+	value_template: Option<&LayoutTemplate>,
+) -> Result<Vec<KeyValuePair>, String> {
+	let mut rng = match seed {
+		Some(seed) => StdRng::seed_from_u64(seed),
+		None => StdRng::from_entropy(),
+	};
+	(0..count)
+		.map(|_| {
+			Ok((random_bytes(&mut rng, key_template)?, random_bytes(&mut rng, value_template)?))
+		})
+		.collect()
+}
@@ -0,0 +1,1434 @@
+use std::{ops::Bound, time::Duration};
+
+use nom::{
+	branch::alt,
+	bytes::complete::{take_while, take_while1},
+	character::complete::{char, multispace0, multispace1},
+	combinator::{all_consuming, opt},
+	multi::separated_list0,
+	sequence::{delimited, pair, preceded},
+	IResult,
+};
+
+use crate::constants::DEFAULT_WATCH_INTERVAL;
+
+use super::{
+	is_byte_literal, is_filter_expression, parse_byte_literal, parse_filter, split_quoted,
+	ExportFormat, ExportOrder,
+};
+
+/// Key bytes analyzed by HISTOGRAM and HITTERS
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum KeySelector {
+	/// First bytes of the key
+	Length(usize),
+	/// First segment of the selected key layout
+	Segment,
+}
+
+/// Weight of a key in the HITTERS summary
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Weight {
+	Keys,
+	Bytes,
+}
+
+/// Pairs analyzed by SIZES
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SizeScan {
+	All,
+	Sample,
+}
+
+/// Accuracy of COUNT
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CountMode {
+	Estimate,
+	Exact,
+}
+
+/// Bytes matched by SEARCH
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SearchScope {
+	Key,
+	Value,
+	Both,
+}
+
+/// Bytes whose layout is proposed by INFER
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InferTarget {
+	Key,
+	Value,
+}
+
+/// Column family management of CF
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CfAction {
+	Create(String),
+	Drop(String),
+	List,
+}
+
+/// Point-in-time snapshots of SNAPSHOT, `Use(None)` reads the live database again
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SnapshotAction {
+	Create(String),
+	List,
+	Use(Option<String>),
+}
+
+/// Expired pairs hidden by `FILTER EXPIRED`, or the only ones kept with `EXPIRED=ONLY`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExpiredFilter {
+	Hide,
+	Only,
+}
+
+impl ExpiredFilter {
+	/// Whether a pair expiring at `expires_at` is kept at `now`
+	pub fn keeps(&self, expires_at: Option<i64>, now: i64) -> bool {
+		let expired = expires_at.map_or(false, |at| at <= now);
+		expired == (*self == ExpiredFilter::Only)
+	}
+}
+
+/// Key order of the scans
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Order {
+	#[default]
+	Asc,
+	Desc,
+}
+
+impl Order {
+	pub fn toggle(self) -> Order {
+		match self {
+			Order::Asc => Order::Desc,
+			Order::Desc => Order::Asc,
+		}
+	}
+}
+
+/// Key bounds of RANGE, written `[start,end)` with brackets for included bounds and
+/// parentheses for excluded ones. An empty bound is unbounded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyRange {
+	pub start: Bound<String>,
+	pub end: Bound<String>,
+}
+
+impl KeyRange {
+	pub fn parse(text: &str) -> Option<KeyRange> {
+		let (start_included, rest) = match text.get(..1)? {
+			"[" => (true, &text[1..]),
+			"(" => (false, &text[1..]),
+			_ => return None,
+		};
+		let (end_included, body) = match rest.get(rest.len().checked_sub(1)?..)? {
+			"]" => (true, &rest[..rest.len() - 1]),
+			")" => (false, &rest[..rest.len() - 1]),
+			_ => return None,
+		};
+		let (start, end) = body.split_once(',')?;
+		Some(KeyRange {
+			start: bound(start, start_included),
+			end: bound(end, end_included),
+		})
+	}
+}
+
+fn bound(value: &str, included: bool) -> Bound<String> {
+	match (value, included) {
+		("", _) => Bound::Unbounded,
+		(value, true) => Bound::Included(value.to_string()),
+		(value, false) => Bound::Excluded(value.to_string()),
+	}
+}
+
+/// Token of the command editor, with its parsed value
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+	/// `COLUMN` for RocksDB, `TABLE` for Redb, `TREE` for Sled
+	Column(String),
+	Db(String),
+	Prefix(String),
+	Suffix(String),
+	Range(KeyRange),
+	Order(Order),
+	Limit(usize),
+	Offset(usize),
+	Histogram(KeySelector),
+	Hitters(KeySelector),
+	By(Weight),
+	Sizes(SizeScan),
+	Duplicates(String),
+	Age(String),
+	Purge {
+		segment: String,
+		days: i64,
+	},
+	Expire(i64),
+	Snapshot(String),
+	Diff(String),
+	/// DIFF without a snapshot compares the keyspace with the TO column family or the TARGET
+	/// database
+	DiffKeyspaces,
+	/// Report the keys of the keyspace also in the TO column family or the TARGET database
+	Overlap,
+	Define(String),
+	Contains(String),
+	Filter(String),
+	View(String),
+	Join(String),
+	On(String),
+	Export {
+		path: String,
+		format: ExportFormat,
+		order: ExportOrder,
+	},
+	Import {
+		path: String,
+		format: ExportFormat,
+	},
+	Count(CountMode),
+	/// Write a number of random pairs matching the selected layouts
+	Generate(usize),
+	/// Seed of the random pairs of GENERATE
+	Seed(u64),
+	/// Seconds before the pair written by SET expires
+	Ttl(i64),
+	/// Hide expired pairs of the scans, or keep only them
+	Expired(ExpiredFilter),
+	Rewrite(String),
+	With(String),
+	Segment(String),
+	Confirm,
+	Internals,
+	Metrics,
+	Copy,
+	To(String),
+	Into(String),
+	Source(String),
+	Target(String),
+	Where(String),
+	Save(String),
+	Delete,
+	Key(String),
+	Set,
+	Value(String),
+	Search,
+	/// SEARCH=ALL searches every column family of the database
+	SearchAll,
+	/// SEARCH=DATABASES searches every column family of every configured database
+	SearchDatabases,
+	Term(String),
+	In(SearchScope),
+	Cf(CfAction),
+	/// Repeat the scan of the command at an interval
+	Watch(Duration),
+	/// Take, list or read point-in-time snapshots
+	PointInTime(SnapshotAction),
+	/// Queue the following SET and DELETE in a changeset
+	Begin,
+	/// Write the queued changeset in one transaction
+	Commit,
+	/// Drop the queued changeset
+	Discard,
+	/// Save the other tokens of the command with the database and column family
+	Bookmark(String),
+	/// Run the configured retention rules, deleting the expired keys with RETENTION=APPLY
+	Retention(bool),
+	/// Write a backup of the database at a path
+	Backup(String),
+	/// Replace the database with the backup at a path, once confirmed
+	Restore(String),
+	/// Propose a layout of the sampled keys or values, saved as a template with SAVE
+	Infer(InferTarget),
+	/// Attach a note to the KEY of the command, an empty note removes it
+	Note(String),
+	/// List the notes containing a term, every note without a term
+	Notes(String),
+	/// Show the commands of the session, or write them as a Markdown report to a path
+	Timeline(Option<String>),
+}
+
+impl Command {
+	/// Keyword of the token, `TABLE` is reported as `COLUMN`
+	pub fn keyword(&self) -> &'static str {
+		match self {
+			Command::Column(_) => "COLUMN",
+			Command::Db(_) => "DB",
+			Command::Prefix(_) => "PREFIX",
+			Command::Suffix(_) => "SUFFIX",
+			Command::Range(_) => "RANGE",
+			Command::Order(_) => "ORDER",
+			Command::Limit(_) => "LIMIT",
+			Command::Offset(_) => "OFFSET",
+			Command::Histogram(_) => "HISTOGRAM",
+			Command::Hitters(_) => "HITTERS",
+			Command::By(_) => "BY",
+			Command::Sizes(_) => "SIZES",
+			Command::Duplicates(_) => "DUPLICATES",
+			Command::Age(_) => "AGE",
+			Command::Purge {
+				..
+			} => "PURGE",
+			Command::Expire(_) => "EXPIRE",
+			Command::Snapshot(_) => "SNAPSHOT",
+			Command::Diff(_) | Command::DiffKeyspaces => "DIFF",
+			Command::Overlap => "OVERLAP",
+			Command::Define(_) => "DEFINE",
+			Command::Contains(_) => "CONTAINS",
+			Command::Filter(_) => "FILTER",
+			Command::View(_) => "VIEW",
+			Command::Join(_) => "JOIN",
+			Command::On(_) => "ON",
+			Command::Export {
+				..
+			} => "EXPORT",
+			Command::Import {
+				..
+			} => "IMPORT",
+			Command::Count(_) => "COUNT",
+			Command::Generate(_) => "GENERATE",
+			Command::Seed(_) => "SEED",
+			Command::Ttl(_) => "TTL",
+			Command::Expired(_) => "EXPIRED",
+			Command::Rewrite(_) => "REWRITE",
+			Command::With(_) => "WITH",
+			Command::Segment(_) => "SEGMENT",
+			Command::Confirm => "CONFIRM",
+			Command::Internals => "INTERNALS",
+			Command::Metrics => "METRICS",
+			Command::Copy => "COPY",
+			Command::To(_) => "TO",
+			Command::Into(_) => "INTO",
+			Command::Source(_) => "SOURCE",
+			Command::Target(_) => "TARGET",
+			Command::Where(_) => "WHERE",
+			Command::Save(_) => "SAVE",
+			Command::Delete => "DELETE",
+			Command::Key(_) => "KEY",
+			Command::Set => "SET",
+			Command::Value(_) => "VALUE",
+			Command::Search | Command::SearchAll | Command::SearchDatabases => "SEARCH",
+			Command::Term(_) => "TERM",
+			Command::In(_) => "IN",
+			Command::Cf(_) => "CF",
+			Command::Watch(_) => "WATCH",
+			Command::PointInTime(_) => "SNAPSHOT",
+			Command::Begin => "BEGIN",
+			Command::Commit => "COMMIT",
+			Command::Discard => "DISCARD",
+			Command::Bookmark(_) => "BOOKMARK",
+			Command::Retention(_) => "RETENTION",
+			Command::Backup(_) => "BACKUP",
+			Command::Restore(_) => "RESTORE",
+			Command::Infer(_) => "INFER",
+			Command::Note(_) => "NOTE",
+			Command::Notes(_) => "NOTES",
+			Command::Timeline(_) => "TIMELINE",
+		}
+	}
+}
+
+/// Upper case keyword of a token
+fn keyword(input: &str) -> IResult<&str, &str> {
+	take_while1(|c: char| c.is_ascii_uppercase())(input)
+}
+
+/// Value of a token, quoted values may hold spaces
+fn value(input: &str) -> IResult<&str, &str> {
+	alt((
+		delimited(char('"'), take_while(|c| c != '"'), char('"')),
+		take_while(|c: char| !c.is_whitespace()),
+	))(input)
+}
+
+/// `KEYWORD` or `KEYWORD=value`
+fn token(input: &str) -> IResult<&str, (&str, Option<&str>)> {
+	pair(keyword, opt(preceded(char('='), value)))(input)
+}
+
+fn tokens(input: &str) -> IResult<&str, Vec<(&str, Option<&str>)>> {
+	delimited(multispace0, separated_list0(multispace1, token), multispace0)(input)
+}
+
+fn key_selector(value: &str) -> Option<KeySelector> {
+	match value {
+		"SEGMENT" => Some(KeySelector::Segment),
+		v => v.parse().ok().map(KeySelector::Length),
+	}
+}
+
+/// Check the bytes pasted in a key value can be decoded
+fn literal(value: String) -> Result<String, String> {
+	match is_byte_literal(&value) {
+		true => parse_byte_literal(&value).map(|_| value),
+		false => Ok(value),
+	}
+}
+
+/// Build the command of a token, checking its value
+fn build(keyword: &str, value: Option<&str>) -> Result<Command, String> {
+	let value = value.map(|v| v.to_string());
+	// Tokens whose value can't be empty
+	let required =
+		|message: &str| value.clone().filter(|v| !v.is_empty()).ok_or(message.to_string());
+	let command = match keyword {
+		"COLUMN" | "TABLE" | "TREE" => Command::Column(value.ok_or("No COLUMN value found")?),
+		"DB" => Command::Db(required("No DB value found")?),
+		"PREFIX" => Command::Prefix(literal(value.ok_or("No PREFIX value found")?)?),
+		"SUFFIX" => Command::Suffix(literal(value.ok_or("No SUFFIX value found")?)?),
+		"FROM" => return Err("FROM is an option of RANGE".to_string()),
+		"INTERVAL" => return Err("INTERVAL is an option of WATCH".to_string()),
+		"WATCH" => return Err("WATCH expects INTERVAL=<duration>".to_string()),
+		"RANGE" => Command::Range(
+			value.and_then(|v| KeyRange::parse(&v)).ok_or("RANGE expects [start,end)")?,
+		),
+		"ORDER" => match value.map(|v| v.to_lowercase()).as_deref() {
+			Some("asc") => Command::Order(Order::Asc),
+			Some("desc") => Command::Order(Order::Desc),
+			_ => return Err("ORDER expects asc or desc".to_string()),
+		},
+		"REVERSE" if value.is_some() => return Err("REVERSE takes no value".to_string()),
+		"REVERSE" => Command::Order(Order::Desc),
+		"LIMIT" => Command::Limit(
+			value
+				.and_then(|v| v.parse().ok())
+				.filter(|l| *l > 0)
+				.ok_or("LIMIT expects a number")?,
+		),
+		"OFFSET" => {
+			Command::Offset(value.and_then(|v| v.parse().ok()).ok_or("OFFSET expects a number")?)
+		}
+		"HISTOGRAM" => Command::Histogram(
+			value.and_then(|v| key_selector(&v)).ok_or("Invalid HISTOGRAM value")?,
+		),
+		"HITTERS" => {
+			Command::Hitters(value.and_then(|v| key_selector(&v)).ok_or("Invalid HITTERS value")?)
+		}
+		"BY" => match value.as_deref() {
+			Some("KEYS") => Command::By(Weight::Keys),
+			Some("BYTES") => Command::By(Weight::Bytes),
+			_ => return Err("BY expects KEYS or BYTES".to_string()),
+		},
+		"SIZES" => match value.as_deref() {
+			Some("ALL") => Command::Sizes(SizeScan::All),
+			Some("SAMPLE") => Command::Sizes(SizeScan::Sample),
+			_ => return Err("Invalid SIZES value".to_string()),
+		},
+		"DUPLICATES" => Command::Duplicates(required("No DUPLICATES value found")?),
+		"AGE" => Command::Age(required("No AGE segment found")?),
+		"PURGE" => {
+			let purge = value
+				.as_deref()
+				.and_then(|v| v.split_once(':'))
+				.and_then(|(segment, days)| Some((segment.to_string(), days.parse().ok()?)));
+			match purge {
+				Some((segment, days)) => Command::Purge {
+					segment,
+					days,
+				},
+				None => return Err("PURGE expects SEGMENT:DAYS".to_string()),
+			}
+		}
+		"EXPIRE" => Command::Expire(
+			value.and_then(|v| v.parse().ok()).ok_or("EXPIRE expects a number of seconds")?,
+		),
+		"SNAPSHOT" => Command::Snapshot(required("No snapshot name found")?),
+		"DIFF" if value.is_none() => Command::DiffKeyspaces,
+		"DIFF" => Command::Diff(required("No snapshot name found")?),
+		"OVERLAP" if value.is_some() => return Err("OVERLAP takes no value".to_string()),
+		"OVERLAP" => Command::Overlap,
+		"DEFINE" => Command::Define(required("No DEFINE value found")?),
+		"CONTAINS" => Command::Contains(required("No CONTAINS value found")?),
+		"FILTER" => Command::Filter(required("No FILTER value found")?),
+		"VIEW" => Command::View(required("No VIEW value found")?),
+		"JOIN" => Command::Join(required("No JOIN value found")?),
+		"ON" => Command::On(required("No ON value found")?),
+		"EXPORT" | "IMPORT" => {
+			let path = required(&format!("No {} path found", keyword))?;
+			let format = ExportFormat::from_path(&path).ok_or_else(|| {
+				format!("{} expects a .md, .html, .json, .csv or .ndjson path", keyword)
+			})?;
+			file_command(keyword, path, format, ExportOrder::default())?
+		}
+		"COUNT" => match value.as_deref() {
+			Some("ESTIMATE") => Command::Count(CountMode::Estimate),
+			Some("EXACT") => Command::Count(CountMode::Exact),
+			_ => return Err("Invalid COUNT value".to_string()),
+		},
+		"GENERATE" => Command::Generate(
+			value
+				.and_then(|v| v.parse().ok())
+				.filter(|count| *count > 0)
+				.ok_or("GENERATE expects a number of pairs")?,
+		),
+		"TTL" => Command::Ttl(
+			value
+				.and_then(|v| v.parse().ok())
+				.filter(|ttl| *ttl > 0)
+				.ok_or("TTL expects a number of seconds")?,
+		),
+		"EXPIRED" => return Err("EXPIRED is an option of FILTER, e.g. FILTER EXPIRED".to_string()),
+		"SEED" => Command::Seed(value.and_then(|v| v.parse().ok()).ok_or("SEED expects a number")?),
+		"REWRITE" => Command::Rewrite(
+			value
+				.filter(|v| regex::bytes::Regex::new(v).is_ok())
+				.ok_or("Invalid REWRITE pattern")?,
+		),
+		"WITH" => Command::With(value.ok_or("No WITH value found")?),
+		"SEGMENT" => Command::Segment(required("No SEGMENT value found")?),
+		"TO" => Command::To(required("No TO column found")?),
+		"INTO" => Command::Into(
+			value
+				.filter(|v| {
+					["rocksdb:", "redb:", "sled:", "lmdb:", "memory:"]
+						.iter()
+						.any(|p| v.starts_with(p))
+				})
+				.ok_or(
+					"INTO expects rocksdb:PATH, redb:PATH, sled:PATH, lmdb:PATH or memory:NAME",
+				)?,
+		),
+		"SOURCE" => Command::Source(required("No SOURCE database found")?),
+		"TARGET" => Command::Target(required("No TARGET database found")?),
+		"KEY" => Command::Key(literal(required("No KEY value found")?)?),
+		"VALUE" => Command::Value(value.ok_or("No VALUE value found")?),
+		"TERM" => Command::Term(literal(required("No TERM value found")?)?),
+		"IN" => match value.map(|v| v.to_lowercase()).as_deref() {
+			Some("key") => Command::In(SearchScope::Key),
+			Some("value") => Command::In(SearchScope::Value),
+			Some("both") => Command::In(SearchScope::Both),
+			_ => return Err("IN expects key, value or both".to_string()),
+		},
+		"SEARCH" if value.as_deref() == Some("ALL") => Command::SearchAll,
+		"SEARCH" if value.as_deref() == Some("DATABASES") => Command::SearchDatabases,
+		"CONFIRM" | "INTERNALS" | "METRICS" | "COPY" | "DELETE" | "SET" | "SEARCH"
+			if value.is_some() =>
+		{
+			return Err(format!("{} takes no value", keyword));
+		}
+		"CONFIRM" => Command::Confirm,
+		"INTERNALS" => Command::Internals,
+		"METRICS" => Command::Metrics,
+		"COPY" => Command::Copy,
+		"DELETE" => Command::Delete,
+		"SET" => Command::Set,
+		"SEARCH" => Command::Search,
+		"BEGIN" => Command::Begin,
+		"COMMIT" => Command::Commit,
+		"DISCARD" => Command::Discard,
+		"BOOKMARK" => Command::Bookmark(required("No BOOKMARK name found")?),
+		"BACKUP" => Command::Backup(required("No BACKUP path found")?),
+		"RESTORE" => Command::Restore(required("No RESTORE path found")?),
+		"INFER" => match value.as_deref() {
+			Some("KEY") => Command::Infer(InferTarget::Key),
+			Some("VALUE") => Command::Infer(InferTarget::Value),
+			_ => return Err("INFER expects KEY or VALUE".to_string()),
+		},
+		"SAVE" => Command::Save(required("No SAVE label found")?),
+		"NOTE" => Command::Note(value.ok_or("No NOTE text found")?),
+		"NOTES" => Command::Notes(value.unwrap_or_default()),
+		"TIMELINE" => Command::Timeline(value.filter(|v| !v.is_empty())),
+		"RETENTION" => match value.as_deref() {
+			None => Command::Retention(false),
+			Some("APPLY") => Command::Retention(true),
+			_ => return Err("RETENTION expects APPLY or no value".to_string()),
+		},
+		_ => return Err("Mismatch command".to_string()),
+	};
+	Ok(command)
+}
+
+/// `RANGE FROM=<key> TO=<key>` writes the bounds `RANGE=[from,to)` as options, either
+/// option can be left out. The options are taken out of the tokens.
+fn range_options(tokens: &mut Vec<(&str, Option<&str>)>) -> Result<Option<KeyRange>, String> {
+	if !tokens.contains(&("RANGE", None)) {
+		return Ok(None);
+	}
+	let mut option = |name: &str| -> Result<Option<String>, String> {
+		let index = match tokens.iter().position(|(keyword, _)| *keyword == name) {
+			Some(index) => index,
+			None => return Ok(None),
+		};
+		let value = tokens.remove(index).1.filter(|v| !v.is_empty());
+		let value = value.ok_or(format!("No {} value found", name))?;
+		literal(value.to_string()).map(Some)
+	};
+	let start = option("FROM")?.map_or(Bound::Unbounded, Bound::Included);
+	let end = option("TO")?.map_or(Bound::Unbounded, Bound::Excluded);
+	Ok(Some(KeyRange {
+		start,
+		end,
+	}))
+}
+
+/// Duration of a WATCH interval, in `ms`, `s` or `m`, e.g. `500ms` or `2s`
+fn interval(value: &str) -> Option<Duration> {
+	let (number, unit) = value.split_at(value.find(|c: char| !c.is_ascii_digit())?);
+	let number = number.parse::<u64>().ok().filter(|n| *n > 0)?;
+	match unit {
+		"ms" => Some(Duration::from_millis(number)),
+		"s" => Some(Duration::from_secs(number)),
+		"m" => Some(Duration::from_secs(number * 60)),
+		_ => None,
+	}
+}
+
+/// `WATCH INTERVAL=<duration>` repeats the scan of the other tokens, every
+/// `DEFAULT_WATCH_INTERVAL` when the interval is left out. The option is taken out of the
+/// tokens.
+fn watch_options(tokens: &mut Vec<(&str, Option<&str>)>) -> Result<Option<Duration>, String> {
+	if !tokens.contains(&("WATCH", None)) {
+		return Ok(None);
+	}
+	let index = match tokens.iter().position(|(keyword, _)| *keyword == "INTERVAL") {
+		Some(index) => index,
+		None => return Ok(Some(DEFAULT_WATCH_INTERVAL)),
+	};
+	let value = tokens.remove(index).1.unwrap_or_default();
+	interval(value).map(Some).ok_or_else(|| "INTERVAL expects a duration like 2s".to_string())
+}
+
+/// `FILTER EXPIRED` hides the expired pairs of the scans, `FILTER EXPIRED=ONLY` keeps only
+/// them. The option is taken out of the tokens.
+fn expired_options(
+	tokens: &mut Vec<(&str, Option<&str>)>,
+) -> Result<Option<ExpiredFilter>, String> {
+	if !tokens.contains(&("FILTER", None)) {
+		return Ok(None);
+	}
+	let index = match tokens.iter().position(|(keyword, _)| *keyword == "EXPIRED") {
+		Some(index) => index,
+		None => return Ok(None),
+	};
+	match tokens.remove(index).1 {
+		None | Some("HIDE") => Ok(Some(ExpiredFilter::Hide)),
+		Some("ONLY") => Ok(Some(ExpiredFilter::Only)),
+		Some(_) => Err("EXPIRED expects HIDE or ONLY".to_string()),
+	}
+}
+
+/// `EXPORT FORMAT=<format> PATH=<file>` writes `EXPORT=<file>` with an explicit format, the
+/// format is inferred from the extension when left out. `EXPORT ORDER=<KEY|INSERTION>` sets
+/// the row order, an ORDER of the scan is left to the token grammar. `IMPORT` takes the
+/// format and path options, `BACKUP PATH=<path>` and `RESTORE PATH=<path>` only the path.
+/// The options are taken out of the tokens.
+fn file_options(tokens: &mut Vec<(&str, Option<&str>)>) -> Result<Option<Command>, String> {
+	let keywords = ["EXPORT", "IMPORT", "BACKUP", "RESTORE"];
+	let keyword = match keywords.into_iter().find(|k| tokens.contains(&(*k, None))) {
+		Some(keyword) => keyword,
+		None => return Ok(None),
+	};
+	let mut option = |name: &str| {
+		let index = tokens.iter().position(|(keyword, _)| *keyword == name)?;
+		tokens.remove(index).1.filter(|v| !v.is_empty()).map(|v| v.to_string())
+	};
+	if matches!(keyword, "BACKUP" | "RESTORE") {
+		let path = option("PATH").ok_or_else(|| format!("{} expects PATH=<path>", keyword))?;
+		return Ok(Some(match keyword {
+			"BACKUP" => Command::Backup(path),
+			_ => Command::Restore(path),
+		}));
+	}
+	let format = option("FORMAT");
+	let path = option("PATH").ok_or_else(|| format!("{} expects PATH=<file>", keyword))?;
+	let order = tokens.iter().position(|(k, v)| {
+		*k == "ORDER" && keyword == "EXPORT" && v.and_then(ExportOrder::from_name).is_some()
+	});
+	let order = order.and_then(|index| tokens.remove(index).1.and_then(ExportOrder::from_name));
+	let format = match format {
+		Some(format) => ExportFormat::from_name(&format)
+			.ok_or("FORMAT expects md, html, json, csv or ndjson")?,
+		None => ExportFormat::from_path(&path)
+			.ok_or_else(|| format!("{} expects FORMAT=<format>", keyword))?,
+	};
+	file_command(keyword, path, format, order.unwrap_or_default()).map(Some)
+}
+
+fn file_command(
+	keyword: &str,
+	path: String,
+	format: ExportFormat,
+	order: ExportOrder,
+) -> Result<Command, String> {
+	match (keyword, format) {
+		("IMPORT", ExportFormat::Markdown | ExportFormat::Html) => {
+			Err("IMPORT reads json, csv or ndjson files".to_string())
+		}
+		("IMPORT", _) => Ok(Command::Import {
+			path,
+			format,
+		}),
+		_ => Ok(Command::Export {
+			path,
+			format,
+			order,
+		}),
+	}
+}
+
+/// Check the options are given with the token they belong to
+fn validate(commands: &[Command]) -> Result<(), String> {
+	let has_token = |token: &str| commands.iter().any(|c| c.keyword() == token);
+	let copy_options = ["TO", "INTO", "SOURCE", "TARGET"];
+	let compared = has_token("OVERLAP") || commands.contains(&Command::DiffKeyspaces);
+	if compared
+		&& (!["TO", "TARGET"].iter().any(|t| has_token(t))
+			|| has_token("INTO")
+			|| has_token("COPY"))
+	{
+		let keyword = if has_token("OVERLAP") {
+			"OVERLAP"
+		} else {
+			"DIFF"
+		};
+		return Err(format!("{} expects TO=<column> or TARGET=<database>", keyword));
+	}
+	// The copy options name the keyspaces compared by DIFF
+	if !compared
+		&& (has_token("COPY") != ["TO", "INTO", "TARGET"].iter().any(|t| has_token(t))
+			|| (copy_options.iter().any(|t| has_token(t)) && !has_token("COPY"))
+			|| (has_token("INTO") && has_token("TARGET")))
+	{
+		return Err("COPY expects TO=<column> or TARGET=<database>".to_string());
+	}
+	let rewrite_options = ["WITH", "SEGMENT", "CONFIRM"];
+	if has_token("REWRITE") != has_token("WITH")
+		|| (rewrite_options.iter().any(|t| has_token(t)) && !has_token("REWRITE"))
+	{
+		return Err("REWRITE expects WITH=<replacement>".to_string());
+	}
+	if has_token("TTL") && !has_token("SET") {
+		return Err("TTL is an option of SET".to_string());
+	}
+	if has_token("SEED") && !has_token("GENERATE") {
+		return Err("SEED is an option of GENERATE=<count>".to_string());
+	}
+	if has_token("BY") && !has_token("HITTERS") {
+		return Err("BY is an option of HITTERS=<selector>".to_string());
+	}
+	if has_token("JOIN") != has_token("ON") {
+		return Err("JOIN expects ON=<segment>".to_string());
+	}
+	if has_token("SAVE") && !has_token("INFER") {
+		return Err("SAVE is an option of INFER=<KEY|VALUE>".to_string());
+	}
+	if has_token("CONTAINS") && !has_token("DEFINE") {
+		return Err("CONTAINS is an option of DEFINE=<view>".to_string());
+	}
+	if has_token("DELETE") && !has_token("KEY") && !has_token("RANGE") {
+		return Err("DELETE expects KEY=<key> or RANGE=<range>".to_string());
+	}
+	if has_token("SET") && (!has_token("KEY") || !has_token("VALUE") || has_token("DELETE")) {
+		return Err("SET expects KEY=<key> and VALUE=<value>".to_string());
+	}
+	if has_token("NOTE") && (!has_token("KEY") || has_token("DELETE") || has_token("SET")) {
+		return Err("NOTE expects KEY=<key>".to_string());
+	}
+	if has_token("KEY") && !has_token("DELETE") && !has_token("SET") && !has_token("NOTE") {
+		return Err("KEY is an option of DELETE, SET or NOTE".to_string());
+	}
+	if has_token("VALUE") && !has_token("SET") {
+		return Err("VALUE is an option of SET".to_string());
+	}
+	let changeset = ["BEGIN", "COMMIT", "DISCARD"];
+	if changeset.iter().any(|t| has_token(t)) && commands.len() > 1 {
+		return Err("BEGIN, COMMIT and DISCARD are written alone".to_string());
+	}
+	let watched = [
+		"WATCH", "COLUMN", "DB", "PREFIX", "SUFFIX", "RANGE", "FILTER", "VIEW", "ORDER", "LIMIT",
+		"OFFSET", "EXPIRED",
+	];
+	if has_token("WATCH") && commands.iter().any(|c| !watched.contains(&c.keyword())) {
+		return Err("WATCH repeats scans, like COLUMN, PREFIX, RANGE, FILTER or VIEW".to_string());
+	}
+	if has_token("SEARCH") != has_token("TERM") || (has_token("IN") && !has_token("SEARCH")) {
+		return Err("SEARCH expects TERM=<text>".to_string());
+	}
+	Ok(())
+}
+
+/// Filter expressions are parsed as a whole into a WHERE command. Only COLUMN, TABLE and
+/// DB tokens, applied before the filter, and SAVE are kept apart.
+fn parse_filter_command(input: &str) -> Result<Vec<Command>, String> {
+	let mut commands = vec![];
+	let mut expression = vec![];
+	let mut save = None;
+	for token in split_quoted(input) {
+		match token.split_once('=') {
+			Some(("COLUMN" | "TABLE", value)) => commands.push(Command::Column(value.to_string())),
+			Some(("DB", value)) => commands.push(Command::Db(value.replace('"', ""))),
+			Some(("SAVE", value)) if !value.is_empty() => save = Some(value.to_string()),
+			_ => expression.push(token),
+		}
+	}
+
+	let expression = expression.join(" ");
+	parse_filter(&expression)?;
+	commands.push(Command::Where(expression));
+	if let Some(label) = save {
+		commands.push(Command::Save(label));
+	}
+	Ok(commands)
+}
+
+/// `CF CREATE <name>`, `CF DROP <name>` and `CF LIST` manage the column families of the
+/// selected database, or of the database of a leading DB token. Column family names are not
+/// keywords, the command is parsed apart from the tokens.
+fn parse_cf_command(input: &str) -> Option<Result<Vec<Command>, String>> {
+	let words = split_quoted(input);
+	let (db, words) = match words.first()?.split_once('=') {
+		Some(("DB", value)) => (Some(value.replace('"', "")), &words[1..]),
+		_ => (None, &words[..]),
+	};
+	if words.first().map(String::as_str) != Some("CF") {
+		return None;
+	}
+	let name = || words.get(2).map(|name| name.replace('"', "")).filter(|name| !name.is_empty());
+	let action = match (words.get(1).map(String::as_str), words.len()) {
+		(Some("CREATE"), 3) => name().map(CfAction::Create),
+		(Some("DROP"), 3) => name().map(CfAction::Drop),
+		(Some("LIST"), 2) => Some(CfAction::List),
+		_ => None,
+	};
+	let action = match action {
+		Some(action) => action,
+		None => return Some(Err("CF expects CREATE <name>, DROP <name> or LIST".to_string())),
+	};
+	let mut commands: Vec<_> = db.map(Command::Db).into_iter().collect();
+	commands.push(Command::Cf(action));
+	Some(Ok(commands))
+}
+
+/// Parse `SNAPSHOT CREATE <name>`, `SNAPSHOT LIST` and `SNAPSHOT USE <name>`, optionally
+/// preceded by `DB`. `SNAPSHOT=<name>` is left to the token grammar.
+fn parse_snapshot_command(input: &str) -> Option<Result<Vec<Command>, String>> {
+	let words = split_quoted(input);
+	let (db, words) = match words.first()?.split_once('=') {
+		Some(("DB", value)) => (Some(value.replace('"', "")), &words[1..]),
+		_ => (None, &words[..]),
+	};
+	if words.first().map(String::as_str) != Some("SNAPSHOT") {
+		return None;
+	}
+	let name = || words.get(2).map(|name| name.replace('"', "")).filter(|name| !name.is_empty());
+	let action = match (words.get(1).map(String::as_str), words.len()) {
+		(Some("CREATE"), 3) => name().map(SnapshotAction::Create),
+		(Some("LIST"), 2) => Some(SnapshotAction::List),
+		(Some("USE"), 3) if words[2] == "HEAD" => Some(SnapshotAction::Use(None)),
+		(Some("USE"), 3) => name().map(|name| SnapshotAction::Use(Some(name))),
+		_ => None,
+	};
+	let action = match action {
+		Some(action) => action,
+		None => {
+			let text = "SNAPSHOT expects CREATE <name>, LIST, USE <name> or USE HEAD";
+			return Some(Err(text.to_string()));
+		}
+	};
+	let mut commands: Vec<_> = db.map(Command::Db).into_iter().collect();
+	commands.push(Command::PointInTime(action));
+	Some(Ok(commands))
+}
+
+/// Parse the text of the command editor into commands, in typed order
+pub fn parse_command(input: &str) -> Result<Vec<Command>, String> {
+	if let Some(commands) = parse_cf_command(input) {
+		return commands;
+	}
+	if let Some(commands) = parse_snapshot_command(input) {
+		return commands;
+	}
+	if is_filter_expression(input) {
+		return parse_filter_command(input);
+	}
+	let (_, mut tokens) =
+		all_consuming(tokens)(input).map_err(|_| "Mismatch command".to_string())?;
+	let range = range_options(&mut tokens)?;
+	let file = file_options(&mut tokens)?;
+	let watch = watch_options(&mut tokens)?;
+	let expired = expired_options(&mut tokens)?;
+	let commands = tokens
+		.into_iter()
+		.map(|(keyword, value)| match (keyword, value, &range, &file) {
+			("RANGE", None, Some(range), _) => Ok(Command::Range(range.clone())),
+			("FILTER", None, ..) if expired.is_some() => Ok(Command::Expired(expired.unwrap())),
+			("WATCH", None, ..) => Ok(Command::Watch(watch.unwrap_or(DEFAULT_WATCH_INTERVAL))),
+			(keyword, None, _, Some(file)) if keyword == file.keyword() => Ok(file.clone()),
+			_ => build(keyword, value),
+		})
+		.collect::<Result<Vec<_>, _>>()?;
+	validate(&commands)?;
+	Ok(commands)
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn should_parse_scan_tokens() {
+		assert_eq!(
+			parse_command("COLUMN=users PREFIX=\"user 1\" SUFFIX=x"),
+			Ok(vec![
+				Command::Column("users".to_string()),
+				Command::Prefix("user 1".to_string()),
+				Command::Suffix("x".to_string()),
+			])
+		);
+		assert_eq!(parse_command("TABLE=t"), Ok(vec![Command::Column("t".to_string())]));
+		assert_eq!(parse_command("  DB=prod  "), Ok(vec![Command::Db("prod".to_string())]));
+		assert_eq!(parse_command("PREFIX="), Ok(vec![Command::Prefix(String::new())]));
+	}
+
+	#[test]
+	fn should_parse_typed_values() {
+		assert_eq!(
+			parse_command("HITTERS=SEGMENT BY=BYTES"),
+			Ok(vec![Command::Hitters(KeySelector::Segment), Command::By(Weight::Bytes)])
+		);
+		assert_eq!(
+			parse_command("HISTOGRAM=4"),
+			Ok(vec![Command::Histogram(KeySelector::Length(4))])
+		);
+		assert_eq!(parse_command("SIZES=SAMPLE"), Ok(vec![Command::Sizes(SizeScan::Sample)]));
+		assert_eq!(parse_command("COUNT=EXACT"), Ok(vec![Command::Count(CountMode::Exact)]));
+		assert_eq!(
+			parse_command("PURGE=created:30"),
+			Ok(vec![Command::Purge {
+				segment: "created".to_string(),
+				days: 30,
+			}])
+		);
+		assert_eq!(parse_command("EXPIRE=-1"), Ok(vec![Command::Expire(-1)]));
+		assert_eq!(parse_command("INTERNALS"), Ok(vec![Command::Internals]));
+		assert_eq!(parse_command("METRICS"), Ok(vec![Command::Metrics]));
+		assert_eq!(parse_command("ORDER=desc"), Ok(vec![Command::Order(Order::Desc)]));
+		assert_eq!(
+			parse_command("LIMIT=10 OFFSET=20"),
+			Ok(vec![Command::Limit(10), Command::Offset(20)])
+		);
+	}
+
+	#[test]
+	fn should_parse_range_bounds() {
+		let range = |start, end| {
+			Ok(vec![Command::Range(KeyRange {
+				start,
+				end,
+			})])
+		};
+		assert_eq!(
+			parse_command("RANGE=[0x01,0x02)"),
+			range(Bound::Included("0x01".to_string()), Bound::Excluded("0x02".to_string()))
+		);
+		assert_eq!(
+			parse_command("RANGE=(1670000000,]"),
+			range(Bound::Excluded("1670000000".to_string()), Bound::Unbounded)
+		);
+		assert_eq!(
+			parse_command("RANGE=\"[user 1,user 2]\""),
+			range(Bound::Included("user 1".to_string()), Bound::Included("user 2".to_string()))
+		);
+		let err = |input: &str| parse_command(input).unwrap_err();
+		assert_eq!(err("RANGE=a,b"), "RANGE expects [start,end)");
+		assert_eq!(err("RANGE=[a)"), "RANGE expects [start,end)");
+		assert_eq!(err("RANGE=["), "RANGE expects [start,end)");
+	}
+
+	#[test]
+	fn should_reject_invalid_values() {
+		let err = |input: &str| parse_command(input).unwrap_err();
+		assert_eq!(err("COLUMN"), "No COLUMN value found");
+		assert_eq!(err("DB="), "No DB value found");
+		assert_eq!(err("HISTOGRAM=first"), "Invalid HISTOGRAM value");
+		assert_eq!(err("HITTERS=4 BY=VALUES"), "BY expects KEYS or BYTES");
+		assert_eq!(err("SIZES=SOME"), "Invalid SIZES value");
+		assert_eq!(err("PURGE=created"), "PURGE expects SEGMENT:DAYS");
+		assert_eq!(err("EXPIRE=soon"), "EXPIRE expects a number of seconds");
+		assert_eq!(
+			err("EXPORT=out.txt"),
+			"EXPORT expects a .md, .html, .json, .csv or .ndjson path"
+		);
+		assert_eq!(err("EXPORT FORMAT=json"), "EXPORT expects PATH=<file>");
+		assert_eq!(
+			err("EXPORT FORMAT=xml PATH=out"),
+			"FORMAT expects md, html, json, csv or ndjson"
+		);
+		assert_eq!(err("EXPORT PATH=out"), "EXPORT expects FORMAT=<format>");
+		assert_eq!(err("IMPORT=users.md"), "IMPORT reads json, csv or ndjson files");
+		assert_eq!(err("IMPORT FORMAT=csv"), "IMPORT expects PATH=<file>");
+		assert_eq!(err("COUNT=ALL"), "Invalid COUNT value");
+		assert_eq!(err("REWRITE=( WITH=x"), "Invalid REWRITE pattern");
+		assert_eq!(
+			err("COPY TO=b INTO=echodb:x"),
+			"INTO expects rocksdb:PATH, redb:PATH, sled:PATH, lmdb:PATH or memory:NAME"
+		);
+		assert_eq!(err("INTERNALS=yes"), "INTERNALS takes no value");
+		assert_eq!(err("METRICS=yes"), "METRICS takes no value");
+		assert_eq!(err("ORDER=newest"), "ORDER expects asc or desc");
+		assert_eq!(err("LIMIT=0"), "LIMIT expects a number");
+		assert_eq!(err("OFFSET=-1"), "OFFSET expects a number");
+	}
+
+	#[test]
+	fn should_reject_unknown_tokens() {
+		let err = |input: &str| parse_command(input).unwrap_err();
+		assert_eq!(err("TOP=10"), "Mismatch command");
+		assert_eq!(err("column=users"), "Mismatch command");
+		assert_eq!(err("COLUMN:users"), "Mismatch command");
+		// Keywords are matched whole, not by prefix
+		assert_eq!(err("TOKEN=x"), "Mismatch command");
+	}
+
+	#[test]
+	fn should_validate_options() {
+		let err = |input: &str| parse_command(input).unwrap_err();
+		assert_eq!(err("COPY"), "COPY expects TO=<column> or TARGET=<database>");
+		assert_eq!(err("SOURCE=users"), "COPY expects TO=<column> or TARGET=<database>");
+		assert_eq!(err("REWRITE=a"), "REWRITE expects WITH=<replacement>");
+		assert_eq!(err("CONFIRM"), "REWRITE expects WITH=<replacement>");
+		assert_eq!(err("BY=KEYS"), "BY is an option of HITTERS=<selector>");
+		assert_eq!(err("JOIN=users"), "JOIN expects ON=<segment>");
+		assert_eq!(err("CONTAINS=x"), "CONTAINS is an option of DEFINE=<view>");
+		assert!(parse_command("COPY TO=b INTO=redb:/tmp/db").is_ok());
+		assert!(parse_command("COPY SOURCE=users TARGET=archive PREFIX=u1").is_ok());
+		assert!(parse_command("REWRITE=^a WITH= SEGMENT=id CONFIRM").is_ok());
+		assert_eq!(err("DELETE"), "DELETE expects KEY=<key> or RANGE=<range>");
+		assert_eq!(err("KEY=0x01"), "KEY is an option of DELETE, SET or NOTE");
+		assert!(parse_command("TREE=users DELETE KEY=0x01").is_ok());
+		assert!(parse_command("DELETE RANGE=[a,b)").is_ok());
+		assert_eq!(err("SET KEY=a"), "SET expects KEY=<key> and VALUE=<value>");
+		assert_eq!(err("SET DELETE KEY=a VALUE=b"), "SET expects KEY=<key> and VALUE=<value>");
+		assert_eq!(err("VALUE=b"), "VALUE is an option of SET");
+	}
+
+	#[test]
+	fn should_parse_search() {
+		let err = |input: &str| parse_command(input).unwrap_err();
+		assert_eq!(
+			parse_command("SEARCH TERM=\"jane doe\" IN=value COLUMN=users"),
+			Ok(vec![
+				Command::Search,
+				Command::Term("jane doe".to_string()),
+				Command::In(SearchScope::Value),
+				Command::Column("users".to_string()),
+			])
+		);
+		assert!(parse_command("SEARCH TERM=0x00ff").is_ok());
+		assert_eq!(err("SEARCH"), "SEARCH expects TERM=<text>");
+		assert_eq!(err("TERM=a"), "SEARCH expects TERM=<text>");
+		assert_eq!(err("SEARCH TERM=a IN=all"), "IN expects key, value or both");
+		assert_eq!(err("SEARCH=a TERM=a"), "SEARCH takes no value");
+		assert_eq!(err("SEARCH TERM=0x0"), "Invalid byte literal 0x0");
+	}
+
+	#[test]
+	fn should_parse_keyspace_diff() {
+		assert_eq!(
+			parse_command("DIFF COLUMN=users TO=users_v2"),
+			Ok(vec![
+				Command::DiffKeyspaces,
+				Command::Column("users".to_string()),
+				Command::To("users_v2".to_string()),
+			])
+		);
+		assert_eq!(
+			parse_command("DIFF SOURCE=primary TARGET=replica"),
+			Ok(vec![
+				Command::DiffKeyspaces,
+				Command::Source("primary".to_string()),
+				Command::Target("replica".to_string()),
+			])
+		);
+		assert_eq!(parse_command("DIFF=before"), Ok(vec![Command::Diff("before".to_string())]));
+		let err = "DIFF expects TO=<column> or TARGET=<database>";
+		assert_eq!(parse_command("DIFF").unwrap_err(), err);
+		assert_eq!(parse_command("DIFF COPY TO=archive").unwrap_err(), err);
+	}
+
+	#[test]
+	fn should_parse_overlap() {
+		assert_eq!(
+			parse_command("OVERLAP COLUMN=orders TO=orders_eu"),
+			Ok(vec![
+				Command::Overlap,
+				Command::Column("orders".to_string()),
+				Command::To("orders_eu".to_string()),
+			])
+		);
+		let err = "OVERLAP expects TO=<column> or TARGET=<database>";
+		assert_eq!(parse_command("OVERLAP COLUMN=orders").unwrap_err(), err);
+		assert_eq!(parse_command("OVERLAP=orders").unwrap_err(), "OVERLAP takes no value");
+	}
+
+	#[test]
+	fn should_parse_bookmark() {
+		assert_eq!(
+			parse_command("COLUMN=users PREFIX=user:42 BOOKMARK=jane"),
+			Ok(vec![
+				Command::Column("users".to_string()),
+				Command::Prefix("user:42".to_string()),
+				Command::Bookmark("jane".to_string()),
+			])
+		);
+		assert_eq!(parse_command("BOOKMARK").unwrap_err(), "No BOOKMARK name found");
+	}
+
+	#[test]
+	fn should_parse_backup() {
+		let backup = Ok(vec![Command::Backup("./backups/users".to_string())]);
+		assert_eq!(parse_command("BACKUP PATH=./backups/users"), backup);
+		assert_eq!(parse_command("BACKUP=./backups/users"), backup);
+		assert_eq!(
+			parse_command("DB=users RESTORE PATH=./backups/users"),
+			Ok(vec![
+				Command::Db("users".to_string()),
+				Command::Restore("./backups/users".to_string()),
+			])
+		);
+		assert_eq!(parse_command("RESTORE").unwrap_err(), "RESTORE expects PATH=<path>");
+	}
+
+	#[test]
+	fn should_parse_infer() {
+		assert_eq!(parse_command("INFER=KEY"), Ok(vec![Command::Infer(InferTarget::Key)]));
+		assert_eq!(
+			parse_command("COLUMN=users INFER=VALUE SAVE=user_value"),
+			Ok(vec![
+				Command::Column("users".to_string()),
+				Command::Infer(InferTarget::Value),
+				Command::Save("user_value".to_string()),
+			])
+		);
+		assert_eq!(parse_command("INFER=PAIR").unwrap_err(), "INFER expects KEY or VALUE");
+		assert_eq!(
+			parse_command("PREFIX=user SAVE=users").unwrap_err(),
+			"SAVE is an option of INFER=<KEY|VALUE>"
+		);
+	}
+
+	#[test]
+	fn should_parse_note() {
+		assert_eq!(
+			parse_command("COLUMN=orders KEY=order:42 NOTE=\"caused incident #42\""),
+			Ok(vec![
+				Command::Column("orders".to_string()),
+				Command::Key("order:42".to_string()),
+				Command::Note("caused incident #42".to_string()),
+			])
+		);
+		assert_eq!(
+			parse_command("KEY=0x01 NOTE="),
+			Ok(vec![Command::Key("0x01".to_string()), Command::Note(String::new())])
+		);
+		assert_eq!(parse_command("NOTE=stale").unwrap_err(), "NOTE expects KEY=<key>");
+		assert_eq!(parse_command("NOTES"), Ok(vec![Command::Notes(String::new())]));
+		assert_eq!(
+			parse_command("NOTES=incident"),
+			Ok(vec![Command::Notes("incident".to_string())])
+		);
+	}
+
+	#[test]
+	fn should_parse_timeline() {
+		assert_eq!(parse_command("TIMELINE"), Ok(vec![Command::Timeline(None)]));
+		assert_eq!(
+			parse_command("TIMELINE=incident-42.md"),
+			Ok(vec![Command::Timeline(Some("incident-42.md".to_string()))])
+		);
+	}
+
+	#[test]
+	fn should_parse_retention() {
+		assert_eq!(parse_command("RETENTION"), Ok(vec![Command::Retention(false)]));
+		assert_eq!(parse_command("RETENTION=APPLY"), Ok(vec![Command::Retention(true)]));
+		assert_eq!(
+			parse_command("RETENTION=NOW").unwrap_err(),
+			"RETENTION expects APPLY or no value"
+		);
+	}
+
+	#[test]
+	fn should_parse_global_search() {
+		assert_eq!(
+			parse_command("SEARCH=ALL TERM=jane IN=key"),
+			Ok(vec![
+				Command::SearchAll,
+				Command::Term("jane".to_string()),
+				Command::In(SearchScope::Key),
+			])
+		);
+		assert_eq!(parse_command("SEARCH=ALL").unwrap_err(), "SEARCH expects TERM=<text>");
+		assert_eq!(
+			parse_command("SEARCH=DATABASES TERM=user:1"),
+			Ok(vec![Command::SearchDatabases, Command::Term("user:1".to_string())])
+		);
+	}
+
+	#[test]
+	fn should_parse_cf() {
+		let err = |input: &str| parse_command(input).unwrap_err();
+		assert_eq!(
+			parse_command("CF CREATE orders:v1"),
+			Ok(vec![Command::Cf(CfAction::Create("orders:v1".to_string()))])
+		);
+		assert_eq!(
+			parse_command("DB=archive CF DROP \"old orders\""),
+			Ok(vec![
+				Command::Db("archive".to_string()),
+				Command::Cf(CfAction::Drop("old orders".to_string())),
+			])
+		);
+		assert_eq!(parse_command("CF LIST"), Ok(vec![Command::Cf(CfAction::List)]));
+		assert_eq!(err("CF CREATE"), "CF expects CREATE <name>, DROP <name> or LIST");
+		assert_eq!(err("CF LIST orders"), "CF expects CREATE <name>, DROP <name> or LIST");
+		assert_eq!(err("CF=LIST"), "Mismatch command");
+	}
+
+	#[test]
+	fn should_check_byte_literals() {
+		let err = |input: &str| parse_command(input).unwrap_err();
+		assert!(parse_command("PREFIX=0x00ff SUFFIX=b64:AP8=").is_ok());
+		assert!(parse_command("PREFIX=user\\x00").is_ok());
+		assert_eq!(err("PREFIX=0x0"), "Invalid byte literal 0x0");
+		assert_eq!(err("SUFFIX=b64:A*"), "Invalid byte literal b64:A*");
+		assert_eq!(err("DELETE KEY=a\\q"), "Invalid byte literal a\\q");
+		assert_eq!(parse_byte_literal("b64:AP8="), Ok(vec![0x00, 0xff]));
+		assert_eq!(parse_byte_literal("b64:dXNlcg"), Ok(b"user".to_vec()));
+		assert_eq!(parse_byte_literal("a\\x00\\n"), Ok(vec![b'a', 0, b'\n']));
+		assert_eq!(parse_byte_literal("user"), Ok(b"user".to_vec()));
+		let format = crate::utils::format_byte_literal;
+		assert_eq!(format(b"user:42"), "user:42");
+		assert_eq!(format(&[b'u', 0, 0xff]), "0x7500ff");
+		assert_eq!(format(b"0xff"), "0x30786666");
+		assert_eq!(parse_byte_literal(&format(&[b'u', 0, 0xff])), Ok(vec![b'u', 0, 0xff]));
+		let escape = crate::utils::escape_bytes;
+		assert_eq!(escape(b"user:42"), "user:42");
+		assert_eq!(escape(&[b'u', 0, 0xff, b'\\', b'"']), "u\\x00\\xff\\\\\\x22");
+		assert_eq!(escape(b"0xff"), "\\x30xff");
+		for key in [&b"u\x00\xff\\\""[..], b"0xff", b"b64:AP8=", b"a b"] {
+			assert_eq!(parse_byte_literal(&escape(key)), Ok(key.to_vec()));
+		}
+		let key = [b'u', 0, b'k'];
+		assert_eq!(crate::utils::KeyDisplay::Escaped.span(&key, 1, 3), (1, 6));
+		assert_eq!(crate::utils::KeyDisplay::Hex.span(&key, 1, 3), (4, 8));
+		assert_eq!(crate::utils::KeyDisplay::Bytes.span(&key, 1, 3), (6, 12));
+	}
+
+	#[test]
+	fn should_parse_file_options() {
+		let export = |path: &str, format| Command::Export {
+			path: path.to_string(),
+			format,
+			order: ExportOrder::Key,
+		};
+		assert_eq!(
+			parse_command("EXPORT=users.md"),
+			Ok(vec![export("users.md", ExportFormat::Markdown)])
+		);
+		assert_eq!(
+			parse_command("PREFIX=user EXPORT FORMAT=ndjson PATH=users.log"),
+			Ok(vec![
+				Command::Prefix("user".to_string()),
+				export("users.log", ExportFormat::Ndjson)
+			])
+		);
+		assert_eq!(
+			parse_command("EXPORT PATH=users.csv"),
+			Ok(vec![export("users.csv", ExportFormat::Csv)])
+		);
+		assert_eq!(
+			parse_command("ORDER=DESC EXPORT ORDER=INSERTION PATH=users.csv"),
+			Ok(vec![
+				Command::Order(Order::Desc),
+				Command::Export {
+					path: "users.csv".to_string(),
+					format: ExportFormat::Csv,
+					order: ExportOrder::Insertion,
+				},
+			])
+		);
+		assert_eq!(
+			parse_command("COLUMN=users IMPORT FORMAT=json PATH=users.txt"),
+			Ok(vec![
+				Command::Column("users".to_string()),
+				Command::Import {
+					path: "users.txt".to_string(),
+					format: ExportFormat::Json,
+				},
+			])
+		);
+	}
+
+	#[test]
+	fn should_parse_range_options() {
+		let range = |start, end| {
+			Command::Range(KeyRange {
+				start,
+				end,
+			})
+		};
+		assert_eq!(
+			parse_command("RANGE FROM=a TO=0x0f LIMIT=10 REVERSE"),
+			Ok(vec![
+				range(Bound::Included("a".to_string()), Bound::Excluded("0x0f".to_string())),
+				Command::Limit(10),
+				Command::Order(Order::Desc),
+			])
+		);
+		assert_eq!(
+			parse_command("TO=b RANGE"),
+			Ok(vec![range(Bound::Unbounded, Bound::Excluded("b".to_string()))])
+		);
+		let err = |input: &str| parse_command(input).unwrap_err();
+		assert_eq!(err("FROM=a"), "FROM is an option of RANGE");
+		assert_eq!(err("RANGE FROM="), "No FROM value found");
+		assert_eq!(err("RANGE TO=0x0"), "Invalid byte literal 0x0");
+		assert_eq!(err("REVERSE=yes"), "REVERSE takes no value");
+	}
+
+	#[test]
+	fn should_parse_set() {
+		assert_eq!(
+			parse_command("TREE=users SET KEY=user:1 VALUE=\"Jane Doe\""),
+			Ok(vec![
+				Command::Column("users".to_string()),
+				Command::Set,
+				Command::Key("user:1".to_string()),
+				Command::Value("Jane Doe".to_string()),
+			])
+		);
+		assert_eq!(
+			parse_command("SET KEY=0x01 VALUE="),
+			Ok(vec![Command::Set, Command::Key("0x01".to_string()), Command::Value(String::new())])
+		);
+	}
+
+	#[test]
+	fn should_parse_filter_expression() {
+		assert_eq!(
+			parse_command("COLUMN=users KEY~admin AND SIZE>10 SAVE=admins"),
+			Ok(vec![
+				Command::Column("users".to_string()),
+				Command::Where("KEY~admin AND SIZE>10".to_string()),
+				Command::Save("admins".to_string()),
+			])
+		);
+	}
+
+	#[test]
+	fn should_parse_computed_filter() {
+		assert_eq!(
+			parse_command("COMPUTED.seconds>=1000 OR COMPUTED.label=\"a b\""),
+			Ok(vec![Command::Where(
+				"COMPUTED.seconds>=1000 OR COMPUTED.label=\"a b\"".to_string()
+			)])
+		);
+		assert!(parse_command("COMPUTED.>1").is_err());
+		assert!(parse_command("COMPUTED.seconds").is_err());
+	}
+
+	#[test]
+	fn should_parse_watch() {
+		assert_eq!(
+			parse_command("WATCH INTERVAL=500ms COLUMN=users PREFIX=user"),
+			Ok(vec![
+				Command::Watch(Duration::from_millis(500)),
+				Command::Column("users".to_string()),
+				Command::Prefix("user".to_string()),
+			])
+		);
+		assert_eq!(
+			parse_command("COLUMN=users WATCH"),
+			Ok(vec![Command::Column("users".to_string()), Command::Watch(DEFAULT_WATCH_INTERVAL)])
+		);
+		assert_eq!(
+			parse_command("WATCH INTERVAL=1m"),
+			Ok(vec![Command::Watch(Duration::from_secs(60))])
+		);
+		assert!(parse_command("WATCH INTERVAL=0s").is_err());
+		assert!(parse_command("WATCH INTERVAL=2h").is_err());
+		assert!(parse_command("INTERVAL=2s COLUMN=users").is_err());
+		assert!(parse_command("WATCH DELETE KEY=a").is_err());
+	}
+
+	#[test]
+	fn should_parse_changeset() {
+		assert_eq!(parse_command("BEGIN"), Ok(vec![Command::Begin]));
+		assert_eq!(parse_command("COMMIT"), Ok(vec![Command::Commit]));
+		assert_eq!(parse_command("DISCARD"), Ok(vec![Command::Discard]));
+		assert!(parse_command("BEGIN COLUMN=users").is_err());
+	}
+
+	#[test]
+	fn should_parse_snapshot_actions() {
+		let create = Command::PointInTime(SnapshotAction::Create("before".to_string()));
+		assert_eq!(parse_command("SNAPSHOT CREATE before"), Ok(vec![create]));
+		assert_eq!(
+			parse_command("SNAPSHOT USE before"),
+			Ok(vec![Command::PointInTime(SnapshotAction::Use(Some("before".to_string())))])
+		);
+		assert_eq!(
+			parse_command("SNAPSHOT USE HEAD"),
+			Ok(vec![Command::PointInTime(SnapshotAction::Use(None))])
+		);
+		assert_eq!(
+			parse_command("DB=archive SNAPSHOT LIST"),
+			Ok(vec![
+				Command::Db("archive".to_string()),
+				Command::PointInTime(SnapshotAction::List)
+			])
+		);
+		assert!(parse_command("SNAPSHOT DROP before").is_err());
+		// The token form captures a keyspace for DIFF
+		assert_eq!(
+			parse_command("SNAPSHOT=before"),
+			Ok(vec![Command::Snapshot("before".to_string())])
+		);
+	}
+
+	#[test]
+	fn should_parse_generate() {
+		assert_eq!(
+			parse_command("COLUMN=users GENERATE=1000 SEED=42"),
+			Ok(vec![
+				Command::Column("users".to_string()),
+				Command::Generate(1000),
+				Command::Seed(42)
+			])
+		);
+		assert!(parse_command("GENERATE=0").is_err());
+		assert!(parse_command("SEED=42").is_err());
+	}
+
+	#[test]
+	fn should_parse_expiration_options() {
+		assert_eq!(
+			parse_command("SET KEY=a VALUE=b TTL=3600"),
+			Ok(vec![
+				Command::Set,
+				Command::Key("a".to_string()),
+				Command::Value("b".to_string()),
+				Command::Ttl(3600)
+			])
+		);
+		assert!(parse_command("PREFIX=a TTL=3600").is_err());
+		assert_eq!(
+			parse_command("PREFIX=session FILTER EXPIRED"),
+			Ok(vec![Command::Prefix("session".to_string()), Command::Expired(ExpiredFilter::Hide)])
+		);
+		assert_eq!(
+			parse_command("FILTER EXPIRED=ONLY"),
+			Ok(vec![Command::Expired(ExpiredFilter::Only)])
+		);
+		assert!(parse_command("EXPIRED=ONLY").is_err());
+		assert!(ExpiredFilter::Hide.keeps(None, 10));
+		assert!(ExpiredFilter::Hide.keeps(Some(11), 10));
+		assert!(ExpiredFilter::Only.keeps(Some(10), 10));
+	}
+}
@@ -0,0 +1,99 @@
+use std::{
+	collections::{HashMap, VecDeque},
+	path::Path,
+	time::{Duration, Instant},
+};
+
+use db::Datastore;
+
+use crate::config::Config;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DatabaseHealth {
+	Openable,
+	/// Opened by another process
+	Locked,
+	Missing,
+	Failed(String),
+}
+
+/// Check whether the database at `name:path` can be opened. Missing paths are reported
+/// without opening, as opening creates the database.
+pub fn check_health(db_path: &str) -> DatabaseHealth {
+	let path = db_path.split_once(':').map_or(db_path, |(_, path)| path);
+	if !Path::new(path).exists() {
+		return DatabaseHealth::Missing;
+	}
+	match Datastore::check(db_path) {
+		Ok(()) => DatabaseHealth::Openable,
+		Err(err) if err.to_string().to_lowercase().contains("lock") => DatabaseHealth::Locked,
+		Err(err) => DatabaseHealth::Failed(err.to_string()),
+	}
+}
+
+/// Periodic health check of every configured database, one database per tick
+#[derive(Default)]
+pub struct HealthChecker {
+	interval: Option<Duration>,
+	last_round: Option<Instant>,
+	pending: VecDeque<String>,
+	health: HashMap<String, DatabaseHealth>,
+}
+
+impl HealthChecker {
+	pub fn new(config: &Config) -> Self {
+		HealthChecker {
+			interval: config.health_interval,
+			..Default::default()
+		}
+	}
+
+	/// Check every configured database once, at startup whatever the interval, so a broken
+	/// database is marked before anything opens it
+	pub fn check_all(&mut self, config: &Config) {
+		for (name, databases) in config.databases.iter() {
+			for database in databases.iter() {
+				self.recheck(&format!("{}:{}", name, database.path));
+			}
+		}
+	}
+
+	/// Check the database at `name:path` again, e.g. when the user retries it
+	pub fn recheck(&mut self, db_path: &str) -> &DatabaseHealth {
+		self.health.insert(db_path.to_string(), check_health(db_path));
+		&self.health[db_path]
+	}
+
+	/// Latest health keyed by database path, e.g. `rocksdb:./temp`
+	pub fn health(&self) -> &HashMap<String, DatabaseHealth> {
+		&self.health
+	}
+
+	/// Check the next pending database and start a new round once the interval elapsed.
+	/// Returns true when the health of a database changed.
+	pub async fn tick(&mut self, config: &Config) -> bool {
+		let interval = match self.interval {
+			Some(interval) => interval,
+			None => return false,
+		};
+		if self.pending.is_empty() {
+			if self.last_round.map_or(false, |last| last.elapsed() < interval) {
+				return false;
+			}
+			self.last_round = Some(Instant::now());
+			self.pending = config
+				.databases
+				.iter()
+				.flat_map(|(name, dbs)| dbs.iter().map(move |db| format!("{}:{}", name, db.path)))
+				.collect();
+		}
+
+		match self.pending.pop_front() {
+			Some(db_path) => {
+				let health = check_health(&db_path);
+				self.health.insert(db_path, health.clone()) != Some(health)
+			}
+			None => false,
+		}
+	}
+}
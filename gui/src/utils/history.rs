@@ -0,0 +1,55 @@
+use std::{
+	fs, io,
+	path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Number of commands kept in the history file, the oldest are dropped first
+pub const HISTORY_SIZE: usize = 500;
+
+/// # Command history
+/// Command run from the command editor, with the database it ran against addressed as
+/// `name:path`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+	pub command: String,
+	pub database: String,
+	pub timestamp: i64,
+}
+
+/// The command history is persisted next to the config file
+pub fn history_path(config_path: &str) -> PathBuf {
+	Path::new(config_path).with_file_name("history.json")
+}
+
+/// Commands of the history file, oldest first
+pub fn load_history(path: &Path) -> Vec<HistoryEntry> {
+	fs::read_to_string(path)
+		.ok()
+		.and_then(|data| serde_json::from_str(&data).ok())
+		.unwrap_or_default()
+}
+
+/// Persist the history, keeping the last `HISTORY_SIZE` commands
+pub fn save_history(path: &Path, history: &[HistoryEntry]) -> io::Result<()> {
+	let history = &history[history.len().saturating_sub(HISTORY_SIZE)..];
+	let data = serde_json::to_string_pretty(history)?;
+	fs::write(path, data)
+}
+
+/// Commands containing `query`, ignoring case, most recent first. Repeated commands are
+/// listed once.
+pub fn search_history<'a>(history: &'a [HistoryEntry], query: &str) -> Vec<&'a HistoryEntry> {
+	let query = query.to_lowercase();
+	let mut found: Vec<&HistoryEntry> = vec![];
+	for entry in history.iter().rev() {
+		if !entry.command.to_lowercase().contains(&query) {
+			continue;
+		}
+		if !found.iter().any(|e| e.command == entry.command && e.database == entry.database) {
+			found.push(entry);
+		}
+	}
+	found
+}
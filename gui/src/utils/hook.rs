@@ -0,0 +1,112 @@
+use std::{
+	io::Write,
+	process::{Command, Stdio},
+	thread,
+};
+
+use db::{Datastore, Error, WriteEvent};
+use serde_json::Value;
+
+use crate::config::{Config, ValidationConfig};
+
+/// Run a configured shell command for the matching writes of a committed transaction. The
+/// command receives the first write through `EDMA_*` environment variables and every
+/// write on stdin, one tab separated `operation column key` line each. It is not awaited, a
+/// helper thread feeds its stdin and reaps it.
+fn run_shell_hook(command: &str, database: &str, events: &[WriteEvent]) {
+	let (shell, flag) = if cfg!(windows) {
+		("cmd", "/C")
+	} else {
+		("sh", "-c")
+	};
+	let column = |event: &WriteEvent| {
+		event.cf.as_ref().map(|c| String::from_utf8_lossy(c).to_string()).unwrap_or_default()
+	};
+	let first = match events.first() {
+		Some(event) => event,
+		None => return,
+	};
+	let child = Command::new(shell)
+		.args([flag, command])
+		.env("EDMA_DATABASE", database)
+		.env("EDMA_WRITES", events.len().to_string())
+		.env("EDMA_OPERATION", first.operation.to_string())
+		.env("EDMA_COLUMN", column(first))
+		.env("EDMA_KEY", String::from_utf8_lossy(&first.key).to_string())
+		.stdin(Stdio::piped())
+		.stdout(Stdio::null())
+		.stderr(Stdio::null())
+		.spawn();
+	let mut child = match child {
+		Ok(child) => child,
+		Err(_) => return,
+	};
+	let lines: String = events
+		.iter()
+		.map(|event| {
+			let key = String::from_utf8_lossy(&event.key);
+			format!("{}\t{}\t{}\n", event.operation, column(event), key)
+		})
+		.collect();
+	thread::spawn(move || {
+		// The command may exit without reading its stdin
+		if let Some(mut stdin) = child.stdin.take() {
+			let _ = stdin.write_all(lines.as_bytes());
+		}
+		let _ = child.wait();
+	});
+}
+
+/// Check a written value against a validation rule of the config, deletions always pass
+fn validate_value(rule: &ValidationConfig, value: Option<&[u8]>) -> Result<(), String> {
+	let value = match value {
+		Some(value) => value,
+		None => return Ok(()),
+	};
+	if let Some(pattern) = &rule.pattern {
+		if !pattern.is_match(value) {
+			return Err(format!("the value does not match {}", pattern));
+		}
+	}
+	if rule.required.is_empty() {
+		return Ok(());
+	}
+	let object = match serde_json::from_slice::<Value>(value) {
+		Ok(Value::Object(object)) => object,
+		_ => return Err("the value is not a JSON object".to_string()),
+	};
+	let missing: Vec<&str> =
+		rule.required.iter().filter(|f| !object.contains_key(*f)).map(|f| f.as_str()).collect();
+	match missing.is_empty() {
+		true => Ok(()),
+		false => Err(format!("the value has no {}", missing.join(", "))),
+	}
+}
+
+/// Open a datastore used for writes, with the shell hooks, the validation rules and the
+/// write limit of the config applied. The failure to open is returned, a broken database
+/// must never stop EDMA.
+pub fn open_datastore(config: &Config, db_path: &str) -> Result<Datastore, Error> {
+	Ok(configure_datastore(config, db_path, Datastore::open(db_path)?))
+}
+
+fn configure_datastore(config: &Config, db_path: &str, mut ds: Datastore) -> Datastore {
+	if let Some(database) = config.database(db_path) {
+		ds.set_write_limit(database.write_limit);
+	}
+	for hook in config.hooks.iter() {
+		let (command, database) = (hook.command.clone(), db_path.to_string());
+		let cf = hook.column.as_ref().map(|c| c.as_bytes().to_vec());
+		ds.register_commit_hook(cf, hook.prefix.as_bytes(), move |events| {
+			run_shell_hook(&command, &database, events)
+		});
+	}
+	for rule in config.validation.iter() {
+		let cf = rule.column.as_ref().map(|c| c.as_bytes().to_vec());
+		let rule = rule.clone();
+		ds.register_validator(cf, rule.prefix.clone().into_bytes(), move |write| {
+			validate_value(&rule, write.value)
+		});
+	}
+	ds
+}
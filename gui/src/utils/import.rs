@@ -0,0 +1,251 @@
+use std::{
+	collections::HashMap,
+	time::{Duration, Instant},
+};
+
+use db::{Datastore, Error, KeyValuePair, CF};
+use serde_json::Value;
+
+use super::{encode_text, open_datastore, write_pairs, ExportFormat, LayoutTemplate};
+use crate::{
+	config::Config,
+	constants::{INGEST_BATCH_SIZE, WRITE_BATCH_SIZE},
+};
+
+/// Fields of an imported record by name
+pub type Record = HashMap<String, String>;
+
+/// JSON values are imported as their text, strings without quotes. `null` fields are left out.
+fn json_record(value: Value) -> Result<Record, String> {
+	let object = match value {
+		Value::Object(object) => object,
+		_ => return Err("Record is not a JSON object".to_string()),
+	};
+	Ok(object
+		.into_iter()
+		.filter_map(|(field, value)| match value {
+			Value::Null => None,
+			Value::String(text) => Some((field, text)),
+			value => Some((field, value.to_string())),
+		})
+		.collect())
+}
+
+/// Rows of a CSV file, quoted cells may hold separators, escaped quotes and line breaks
+fn csv_rows(data: &str) -> Vec<Vec<String>> {
+	let (mut rows, mut row, mut cell) = (vec![], vec![], String::new());
+	let (mut quoted, mut chars) = (false, data.chars().peekable());
+	while let Some(c) = chars.next() {
+		match (c, quoted) {
+			('"', true) if chars.peek() == Some(&'"') => {
+				chars.next();
+				cell.push('"');
+			}
+			('"', _) => quoted = !quoted,
+			(',', false) => row.push(std::mem::take(&mut cell)),
+			('\r', false) => {}
+			('\n', false) => {
+				row.push(std::mem::take(&mut cell));
+				rows.push(std::mem::take(&mut row));
+			}
+			_ => cell.push(c),
+		}
+	}
+	if !cell.is_empty() || !row.is_empty() {
+		row.push(cell);
+		rows.push(row);
+	}
+	rows
+}
+
+/// Records of a JSON array, NDJSON or CSV file with a header row. A malformed record is
+/// returned as an error, a file that can't be read as a whole fails the import.
+pub fn parse_records(
+	data: &str,
+	format: ExportFormat,
+) -> Result<Vec<Result<Record, String>>, String> {
+	Ok(match format {
+		ExportFormat::Json => {
+			let values: Vec<Value> =
+				serde_json::from_str(data).map_err(|e| format!("Invalid JSON array: {}", e))?;
+			values.into_iter().map(json_record).collect()
+		}
+		ExportFormat::Ndjson => data
+			.lines()
+			.filter(|line| !line.trim().is_empty())
+			.map(|line| serde_json::from_str(line).map_err(|e| e.to_string()).and_then(json_record))
+			.collect(),
+		ExportFormat::Csv => {
+			let mut rows = csv_rows(data).into_iter();
+			let headers = rows.next().ok_or("Missing CSV header row")?;
+			rows.map(|row| match row.len() == headers.len() {
+				true => Ok(headers.iter().cloned().zip(row).collect()),
+				false => Err(format!("Expected {} cells, found {}", headers.len(), row.len())),
+			})
+			.collect()
+		}
+		ExportFormat::Markdown | ExportFormat::Html => {
+			return Err("IMPORT reads json, csv or ndjson files".to_string())
+		}
+	})
+}
+
+/// Bytes written by `EXPORT` as a list of numbers, e.g. `[117, 49]`
+fn parse_byte_list(text: &str) -> Option<Vec<u8>> {
+	let numbers = text.strip_prefix('[')?.strip_suffix(']')?;
+	numbers.split(',').filter(|n| !n.trim().is_empty()).map(|n| n.trim().parse().ok()).collect()
+}
+
+impl LayoutTemplate {
+	/// Encode bytes from the text of every segment, the inverse of `decode`. Segments are
+	/// fixed width except the last one. Checksum segments are computed over the other
+	/// segments rather than read.
+	pub fn encode(&self, segment: impl Fn(&str) -> Option<String>) -> Result<Vec<u8>, String> {
+		let end = self.layout.iter().map(|l| l.to).max().unwrap_or(0);
+		let mut raw = vec![];
+		for layout in self.layout.iter() {
+			if layout.checksum.is_some() {
+				raw.resize(raw.len().max(layout.to), 0);
+				continue;
+			}
+			let text =
+				segment(&layout.name).ok_or_else(|| format!("Missing segment {}", layout.name))?;
+			let bytes = layout
+				.variant
+				.encode(&text)
+				.ok_or_else(|| format!("Invalid {} segment {}", layout.name, text))?;
+			let width = layout.to.saturating_sub(layout.from);
+			if bytes.len() > width || (bytes.len() < width && layout.to != end) {
+				return Err(format!("Segment {} expects {} bytes", layout.name, width));
+			}
+			raw.resize(raw.len().max(layout.from + bytes.len()), 0);
+			raw[layout.from..layout.from + bytes.len()].copy_from_slice(&bytes);
+		}
+		for layout in self.layout.iter().filter(|l| l.checksum.is_some()) {
+			let checksum = self
+				.expected_checksum(layout, &raw)
+				.ok_or_else(|| format!("Checksum segment {} can't be computed", layout.name))?;
+			raw[layout.from..layout.to].copy_from_slice(&checksum);
+		}
+		Ok(raw)
+	}
+}
+
+/// Key and value of a record. A `Key` or `Value` field is encoded whole like in a command,
+/// otherwise the fields `Key <segment>` or `<segment>` are encoded with the template.
+pub fn record_pair(
+	record: &Record,
+	key_template: Option<&LayoutTemplate>,
+	value_template: Option<&LayoutTemplate>,
+) -> Result<KeyValuePair, String> {
+	let encode = |label: &str, template: Option<&LayoutTemplate>| match record.get(label) {
+		Some(text) => parse_byte_list(text)
+			.or_else(|| encode_text(template, text))
+			.ok_or_else(|| format!("Invalid {} {}", label, text)),
+		None => template.ok_or_else(|| format!("Missing field {}", label))?.encode(|name| {
+			record.get(&format!("{} {}", label, name)).or_else(|| record.get(name)).cloned()
+		}),
+	};
+	Ok((encode("Key", key_template)?, encode("Value", value_template)?))
+}
+
+/// # Import jobs
+/// Pairs encoded from the records of a file, written into a column family one batch of
+/// `WRITE_BATCH_SIZE` pairs at a time. Malformed records are kept as errors by row.
+pub struct ImportJob {
+	cf: CF,
+	pairs: Vec<KeyValuePair>,
+	written: usize,
+	pub errors: Vec<(usize, String)>,
+}
+
+impl ImportJob {
+	pub fn new(
+		cf: CF,
+		records: Vec<Result<Record, String>>,
+		key_template: Option<&LayoutTemplate>,
+		value_template: Option<&LayoutTemplate>,
+	) -> Self {
+		let (mut pairs, mut errors) = (vec![], vec![]);
+		for (row, record) in records.into_iter().enumerate() {
+			match record.and_then(|r| record_pair(&r, key_template, value_template)) {
+				Ok(pair) => pairs.push(pair),
+				Err(err) => errors.push((row + 1, err)),
+			}
+		}
+		ImportJob {
+			cf,
+			pairs,
+			written: 0,
+			errors,
+		}
+	}
+
+	/// Write pairs as they are, e.g. generated ones
+	pub fn from_pairs(cf: CF, pairs: Vec<KeyValuePair>) -> Self {
+		ImportJob {
+			cf,
+			pairs,
+			written: 0,
+			errors: vec![],
+		}
+	}
+
+	pub fn cf(&self) -> &CF {
+		&self.cf
+	}
+
+	pub fn written(&self) -> usize {
+		self.written
+	}
+
+	pub fn total(&self) -> usize {
+		self.pairs.len()
+	}
+
+	pub fn is_done(&self) -> bool {
+		self.written == self.pairs.len()
+	}
+
+	/// Write the next batch in its own transaction
+	pub async fn write_batch(&mut self, ds: &Datastore) -> Result<(), Error> {
+		let end = self.pairs.len().min(self.written + WRITE_BATCH_SIZE);
+		let batch = &self.pairs[self.written..end];
+		self.written += write_pairs(ds, self.cf.clone(), batch, |_| {}).await?;
+		Ok(())
+	}
+
+	/// Ingest the pairs as SST files of `INGEST_BATCH_SIZE` pairs, without the WAL. Returns
+	/// false when the database can't ingest files, nothing is written and the pairs are
+	/// left to `write_batch`.
+	pub fn ingest(&mut self, ds: &Datastore) -> Result<bool, Error> {
+		while !self.is_done() {
+			let end = self.pairs.len().min(self.written + INGEST_BATCH_SIZE);
+			if !ds.ingest(self.cf.clone(), self.pairs[self.written..end].to_vec())? {
+				return Ok(false);
+			}
+			self.written = end;
+		}
+		Ok(true)
+	}
+}
+
+/// Import into a database addressed by `name` and `path`, advanced from the event loop so
+/// the interface keeps drawing its progress
+pub struct BackgroundImport {
+	pub name: String,
+	pub path: String,
+	pub job: ImportJob,
+}
+
+impl BackgroundImport {
+	/// Write batches until `budget` elapsed, the database is opened for the tick only
+	pub async fn tick(&mut self, config: &Config, budget: Duration) -> Result<(), Error> {
+		let started = Instant::now();
+		let ds = open_datastore(config, &format!("{}:{}", self.name, self.path))?;
+		while !self.job.is_done() && started.elapsed() < budget {
+			self.job.write_batch(&ds).await?;
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,173 @@
+use std::{
+	fs, io,
+	path::{Path, PathBuf},
+};
+
+use serde_json::{json, Value};
+
+use super::LayoutVariant;
+
+/// Number of keys or values sampled by INFER
+pub const INFER_SAMPLE_SIZE: usize = 1_000;
+/// Seconds of 2000-01-01 and 2100-01-01, bounds of the integers taken for timestamps
+const TIMESTAMP_BOUNDS: (i64, i64) = (946_684_800, 4_102_444_800);
+/// Bytes ending a text segment when they are at the same offset in every sample
+const DELIMITERS: [u8; 6] = [b':', b'/', b'|', b'#', b'-', b'_'];
+
+/// Segment of a layout proposed by INFER, with the evidence it was detected from
+#[derive(Clone, Debug)]
+pub struct InferredSegment {
+	pub name: String,
+	pub from: usize,
+	pub to: usize,
+	pub variant: LayoutVariant,
+	pub evidence: String,
+}
+
+fn is_text(bytes: &[u8]) -> bool {
+	!bytes.is_empty() && bytes.iter().all(|b| b.is_ascii_graphic() || *b == b' ')
+}
+
+fn is_timestamp(bytes: &[u8]) -> bool {
+	let value = match bytes.len() {
+		4 => i32::from_be_bytes(bytes.try_into().unwrap()) as i64,
+		8 => i64::from_be_bytes(bytes.try_into().unwrap()),
+		_ => return false,
+	};
+	let (start, end) = TIMESTAMP_BOUNDS;
+	(start..end).contains(&value) || (start * 1000..end * 1000).contains(&value)
+}
+
+fn is_uuid_v4(bytes: &[u8]) -> bool {
+	bytes.len() == 16 && bytes[6] >> 4 == 4 && bytes[8] & 0xc0 == 0x80
+}
+
+/// Segments starting at `at` in every sample, none once every sample ends before `at`
+fn detect(samples: &[&[u8]], at: usize) -> Vec<InferredSegment> {
+	let rests: Vec<&[u8]> = samples.iter().map(|s| s.get(at..).unwrap_or_default()).collect();
+	let longest = rests.iter().map(|r| r.len()).max().unwrap_or(0);
+	if longest == 0 {
+		return vec![];
+	}
+	let segment = |name: &str, width: usize, variant: LayoutVariant, evidence: String| {
+		vec![InferredSegment {
+			name: name.to_string(),
+			from: at,
+			to: at + width,
+			variant,
+			evidence,
+		}]
+	};
+	let fixed = |width: usize, check: &dyn Fn(&[u8]) -> bool| {
+		rests.iter().all(|rest| rest.len() >= width && check(&rest[..width]))
+	};
+
+	// Bytes shared by every sample, e.g. a record type or a namespace
+	let common = (0..longest)
+		.take_while(|i| rests.iter().all(|rest| rest.get(*i) == rests[0].get(*i)))
+		.count();
+	if common > 0 && samples.len() > 1 {
+		let variant = match is_text(&rests[0][..common]) {
+			true => LayoutVariant::String,
+			false => LayoutVariant::Bytes,
+		};
+		return segment("prefix", common, variant, "same bytes in every sample".to_string());
+	}
+	if fixed(16, &is_uuid_v4) {
+		return segment("uuid", 16, LayoutVariant::UuidV4, "UUID v4 version bits".to_string());
+	}
+	for width in [8, 4] {
+		if fixed(width, &is_timestamp) {
+			let evidence = format!("{} bytes big-endian between 2000 and 2100", width);
+			return segment("timestamp", width, LayoutVariant::Timestamp, evidence);
+		}
+	}
+	// A length byte followed by as many bytes of text, only as the last segment since the
+	// following segments would move with the length
+	if rests
+		.iter()
+		.all(|rest| rest.len() > 1 && rest[0] as usize + 1 == rest.len() && is_text(&rest[1..]))
+	{
+		let mut segments =
+			segment("length", 1, LayoutVariant::Bytes, "length of the text".to_string());
+		let text = "text after its length".to_string();
+		segments.extend(segment("text", longest, LayoutVariant::String, text));
+		segments[1].from = at + 1;
+		return segments;
+	}
+	// Text up to a delimiter found at the same offset in every sample
+	if fixed(1, &is_text) {
+		let delimiter = (1..longest).find(|i| {
+			let byte = rests[0].get(*i).copied().unwrap_or_default();
+			DELIMITERS.contains(&byte)
+				&& rests.iter().all(|rest| rest.get(*i) == Some(&byte) && is_text(&rest[..*i]))
+		});
+		if let Some(end) = delimiter {
+			let evidence = format!("text ended by '{}'", rests[0][end] as char);
+			return segment("text", end + 1, LayoutVariant::String, evidence);
+		}
+		if rests.iter().all(|rest| is_text(rest)) {
+			let evidence = "printable text up to the end".to_string();
+			return segment("text", longest, LayoutVariant::String, evidence);
+		}
+	}
+	if rests.iter().all(|rest| rest.len() == 8) {
+		return segment("int", 8, LayoutVariant::Int64, "8 remaining bytes".to_string());
+	}
+	if rests.iter().all(|rest| rest.len() == 4) {
+		return segment("int", 4, LayoutVariant::Int32, "4 remaining bytes".to_string());
+	}
+	segment("bytes", longest, LayoutVariant::Bytes, "no pattern found".to_string())
+}
+
+/// Propose the segments of a byte layout matching the sampled keys or values, from the
+/// first byte: shared prefixes, UUIDs, timestamps, length-prefixed and delimited text, then
+/// integers filling the rest. The last segment covers the longest sample. Segment names
+/// are numbered when a kind is found twice.
+pub fn infer_layout(samples: &[Vec<u8>]) -> Vec<InferredSegment> {
+	let samples: Vec<&[u8]> = samples.iter().map(|s| s.as_slice()).collect();
+	let mut segments: Vec<InferredSegment> = vec![];
+	loop {
+		let at = segments.last().map(|s| s.to).unwrap_or(0);
+		let detected = detect(&samples, at);
+		if detected.is_empty() {
+			break;
+		}
+		for mut segment in detected {
+			let found = segments.iter().filter(|s| s.name.starts_with(&segment.name)).count();
+			if found > 0 {
+				segment.name = format!("{}{}", segment.name, found + 1);
+			}
+			segments.push(segment);
+		}
+	}
+	segments
+}
+
+/// Templates accepted with INFER SAVE are kept next to the config file
+pub fn inferred_templates_path(config_path: &str) -> PathBuf {
+	Path::new(config_path).with_file_name("templates.json")
+}
+
+/// Save the segments as the template `name`, in the format of the `templates` config,
+/// replacing a template saved before under the same name
+pub fn save_inferred_template(
+	path: &Path,
+	name: &str,
+	segments: &[InferredSegment],
+) -> io::Result<()> {
+	let mut templates: Vec<Value> = fs::read_to_string(path)
+		.ok()
+		.and_then(|data| serde_json::from_str(&data).ok())
+		.unwrap_or_default();
+	templates.retain(|t| t.get("name").and_then(|n| n.as_str()) != Some(name));
+	let layouts: Vec<_> = segments
+		.iter()
+		.map(|s| {
+			let variant = s.variant.to_string();
+			json!({ "name": s.name, "from": s.from, "to": s.to, "variant": variant })
+		})
+		.collect();
+	templates.push(json!({ "name": name, "layouts": layouts }));
+	fs::write(path, serde_json::to_string_pretty(&templates)?)
+}
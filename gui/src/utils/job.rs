@@ -0,0 +1,265 @@
+use std::{
+	ops::Bound,
+	time::{Duration, Instant},
+};
+
+use db::{Cursor, Datastore, Error, CF};
+
+use super::{
+	format_bytes, open_datastore, render_bar, BackgroundBackup, BackgroundCopy, BackgroundDiff,
+	BackgroundGlobalSearch, BackgroundImport, BackgroundSearch, DatabasesSearch,
+};
+use crate::{
+	config::Config,
+	constants::{BACKGROUND_TICK_BUDGET, SCAN_BATCH_SIZE},
+};
+
+/// Name of a column family in job labels
+pub fn column_label(cf: &CF) -> String {
+	match cf {
+		Some(cf) => String::from_utf8_lossy(cf).to_string(),
+		None => "default".to_string(),
+	}
+}
+
+/// Exact count of the keys of a column family, one batch of `SCAN_BATCH_SIZE` pairs at a
+/// time
+pub struct CountJob {
+	cf: CF,
+	cursor: Cursor,
+	counted: usize,
+}
+
+impl CountJob {
+	pub fn new(cf: CF) -> Self {
+		CountJob {
+			cursor: Cursor::new(cf.clone(), Bound::Unbounded, Bound::Unbounded, false),
+			cf,
+			counted: 0,
+		}
+	}
+
+	pub fn cf(&self) -> &CF {
+		&self.cf
+	}
+
+	pub fn counted(&self) -> usize {
+		self.counted
+	}
+
+	pub fn is_done(&self) -> bool {
+		!self.cursor.has_next()
+	}
+
+	/// Count the next batch in its own transaction
+	pub async fn count_batch(&mut self, ds: &Datastore) -> Result<(), Error> {
+		let tx = ds.transaction(false).await?;
+		self.counted += self.cursor.next_batch(&tx, SCAN_BATCH_SIZE).await?.len();
+		Ok(())
+	}
+}
+
+/// Count of a database addressed by `name` and `path`, advanced from the event loop
+pub struct BackgroundCount {
+	pub name: String,
+	pub path: String,
+	pub job: CountJob,
+}
+
+impl BackgroundCount {
+	/// Count batches until `budget` elapsed, the database is opened for the tick only
+	pub async fn tick(&mut self, config: &Config, budget: Duration) -> Result<(), Error> {
+		let started = Instant::now();
+		let ds = open_datastore(config, &format!("{}:{}", self.name, self.path))?;
+		while !self.job.is_done() && started.elapsed() < budget {
+			self.job.count_batch(&ds).await?;
+		}
+		Ok(())
+	}
+}
+
+/// # Background jobs
+/// Long operations advanced from the event loop a few batches per tick, so the interface
+/// keeps drawing while they run and they can be cancelled between two batches. Databases
+/// are opened for a tick only: they lock their files, so they can't be held open by a
+/// job while the command editor uses them.
+pub enum Job {
+	Copy(BackgroundCopy),
+	Import(BackgroundImport),
+	/// Generated pairs, written like an import
+	Generate(BackgroundImport),
+	Search(BackgroundSearch),
+	/// Search of every column family with SEARCH=ALL
+	SearchAll(BackgroundGlobalSearch),
+	/// Search of every configured database with SEARCH=DATABASES
+	SearchDatabases(DatabasesSearch),
+	Count(BackgroundCount),
+	/// Comparison of two keyspaces with DIFF or OVERLAP
+	Diff(BackgroundDiff),
+	Backup(BackgroundBackup),
+}
+
+impl Job {
+	/// Command of the job shown in the jobs panel
+	pub fn label(&self) -> String {
+		match self {
+			Job::Copy(copy) => format!("COPY into {}", column_label(copy.job.to())),
+			Job::Import(import) => format!("IMPORT into {}", column_label(import.job.cf())),
+			Job::Generate(import) => format!("GENERATE into {}", column_label(import.job.cf())),
+			Job::Search(search) => format!("SEARCH \"{}\"", search.job.term()),
+			Job::SearchAll(search) => format!("SEARCH=ALL \"{}\"", search.job.term()),
+			Job::SearchDatabases(search) => format!("SEARCH=DATABASES \"{}\"", search.term()),
+			Job::Count(count) => format!("COUNT {}", column_label(count.job.cf())),
+			Job::Diff(diff) => {
+				let keyword = if diff.job.shared().is_some() {
+					"OVERLAP"
+				} else {
+					"DIFF"
+				};
+				format!("{} {} with {}", keyword, diff.side(true), diff.side(false))
+			}
+			Job::Backup(backup) => format!("BACKUP {} to {}", backup.db_path, backup.path),
+		}
+	}
+
+	pub fn progress(&self) -> String {
+		match self {
+			Job::Copy(copy) => format!("{} pairs copied", copy.job.copied()),
+			Job::Import(import) | Job::Generate(import) => {
+				let (written, total) = (import.job.written(), import.job.total());
+				format!("{} {}/{} pairs", render_bar(written, total), written, total)
+			}
+			Job::Search(search) => {
+				format!("{} matches in {} pairs", search.job.hits().len(), search.job.scanned())
+			}
+			Job::SearchAll(search) => format!(
+				"{} matches in {} pairs of {} column families",
+				search.job.matches(),
+				search.job.scanned(),
+				search.job.jobs().len()
+			),
+			Job::SearchDatabases(search) => search.progress(),
+			Job::Count(count) => format!("{} keys counted", count.job.counted()),
+			Job::Diff(diff) => match diff.job.shared() {
+				Some(shared) => {
+					format!("{} keys compared, {} shared", diff.job.compared(), shared.len())
+				}
+				None => format!(
+					"{} keys compared, {} differences",
+					diff.job.compared(),
+					diff.job.changes().len()
+				),
+			},
+			Job::Backup(backup) => format!("{} written", format_bytes(backup.written() as usize)),
+		}
+	}
+
+	fn is_done(&self) -> bool {
+		match self {
+			Job::Copy(copy) => copy.job.is_done(),
+			Job::Import(import) | Job::Generate(import) => import.job.is_done(),
+			Job::Search(search) => search.job.is_done(),
+			Job::SearchAll(search) => search.job.is_done(),
+			Job::SearchDatabases(search) => search.is_done(),
+			Job::Count(count) => count.job.is_done(),
+			Job::Diff(diff) => diff.job.is_done(),
+			Job::Backup(backup) => backup.is_done(),
+		}
+	}
+
+	async fn tick(&mut self, config: &Config, budget: Duration) -> Result<(), Error> {
+		match self {
+			Job::Copy(copy) => copy.tick(config, budget).await,
+			Job::Import(import) | Job::Generate(import) => import.tick(config, budget).await,
+			Job::Search(search) => search.tick(config, budget).await,
+			Job::SearchAll(search) => search.tick(config, budget).await,
+			Job::SearchDatabases(search) => search.tick(config, budget).await,
+			Job::Count(count) => count.tick(config, budget).await,
+			Job::Diff(diff) => diff.tick(config, budget).await,
+			Job::Backup(backup) => backup.tick(config, budget).await,
+		}
+	}
+}
+
+/// Event of a job after a tick. Finished, failed and cancelled jobs are handed back.
+pub enum JobEvent {
+	/// Label and progress of the most recently started job still running
+	Progress(String),
+	Finished(Job),
+	Failed(Job, Error),
+	Cancelled(Job),
+}
+
+/// Running job as listed in the jobs panel
+pub struct JobSummary {
+	pub id: usize,
+	pub label: String,
+	pub progress: String,
+	pub elapsed: Duration,
+}
+
+struct RunningJob {
+	id: usize,
+	job: Job,
+	started: Instant,
+}
+
+/// Jobs running in the background, in start order
+#[derive(Default)]
+pub struct Jobs {
+	next_id: usize,
+	running: Vec<RunningJob>,
+}
+
+impl Jobs {
+	pub fn spawn(&mut self, job: Job) {
+		self.next_id += 1;
+		self.running.push(RunningJob {
+			id: self.next_id,
+			job,
+			started: Instant::now(),
+		});
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.running.is_empty()
+	}
+
+	/// Stop the most recently started job
+	pub fn cancel_last(&mut self) -> Option<JobEvent> {
+		self.running.pop().map(|running| JobEvent::Cancelled(running.job))
+	}
+
+	pub fn summaries(&self) -> Vec<JobSummary> {
+		self.running
+			.iter()
+			.map(|running| JobSummary {
+				id: running.id,
+				label: running.job.label(),
+				progress: running.job.progress(),
+				elapsed: running.started.elapsed(),
+			})
+			.collect()
+	}
+
+	/// Advance every running job, `BACKGROUND_TICK_BUDGET` is shared between them. Finished
+	/// and failed jobs are removed and handed back in their event, the progress of the last
+	/// running job is reported when no job ended.
+	pub async fn tick(&mut self, config: &Config) -> Vec<JobEvent> {
+		let budget = BACKGROUND_TICK_BUDGET / self.running.len().max(1) as u32;
+		let mut events = vec![];
+		for mut running in std::mem::take(&mut self.running) {
+			match running.job.tick(config, budget).await {
+				Err(err) => events.push(JobEvent::Failed(running.job, err)),
+				Ok(()) if running.job.is_done() => events.push(JobEvent::Finished(running.job)),
+				Ok(()) => self.running.push(running),
+			}
+		}
+		// The result of an ended job stays in the status bar until the next tick
+		if let Some(running) = self.running.last().filter(|_| events.is_empty()) {
+			let text = format!("{}: {}", running.job.label(), running.job.progress());
+			events.push(JobEvent::Progress(text));
+		}
+		events
+	}
+}
@@ -0,0 +1,39 @@
+use std::{cmp::min, collections::HashMap};
+
+use db::KeyValuePair;
+
+#[derive(Clone, Debug)]
+pub struct JoinedRow {
+	pub left: KeyValuePair,
+	pub right: KeyValuePair,
+}
+
+/// Bytes of `raw` in `from..to`, `None` when `raw` is shorter than `from`
+pub fn segment_bytes(raw: &[u8], from: usize, to: usize) -> Option<Vec<u8>> {
+	raw.get(from..min(to, raw.len())).filter(|s| !s.is_empty()).map(|s| s.to_vec())
+}
+
+/// Hash join two keyspaces: `right` is indexed by key, then `left` is streamed and each
+/// pair probes the index with the join key extracted by `join_key`. Returns the matched
+/// rows and the number of left pairs without match.
+pub fn hash_join<L, R, F>(left: L, right: R, join_key: F) -> (Vec<JoinedRow>, usize)
+where
+	L: IntoIterator<Item = KeyValuePair>,
+	R: IntoIterator<Item = KeyValuePair>,
+	F: Fn(&KeyValuePair) -> Option<Vec<u8>>,
+{
+	let index: HashMap<Vec<u8>, Vec<u8>> = right.into_iter().collect();
+	let mut rows = vec![];
+	let mut unmatched = 0;
+	for pair in left {
+		let matched = join_key(&pair).and_then(|key| index.get_key_value(&key));
+		match matched {
+			Some((key, value)) => rows.push(JoinedRow {
+				right: (key.clone(), value.clone()),
+				left: pair,
+			}),
+			None => unmatched += 1,
+		}
+	}
+	(rows, unmatched)
+}
@@ -0,0 +1,56 @@
+use serde_json::Value;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Step {
+	Field(String),
+	Index(usize),
+}
+
+/// Subset of JSONPath selecting a single node: `$.field`, `$.a.b`, `$.items[0].id` and
+/// `$["field with spaces"]`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JsonPath {
+	steps: Vec<Step>,
+}
+
+impl JsonPath {
+	pub fn parse(path: &str) -> Result<JsonPath, String> {
+		let invalid = || format!("Invalid JSONPath {}", path);
+		let mut rest = path.strip_prefix('$').ok_or_else(invalid)?;
+		let mut steps = vec![];
+		while !rest.is_empty() {
+			if let Some(field) = rest.strip_prefix('.') {
+				let end = field.find(|c| c == '.' || c == '[').unwrap_or(field.len());
+				if end == 0 {
+					return Err(invalid());
+				}
+				steps.push(Step::Field(field[..end].to_string()));
+				rest = &field[end..];
+			} else if let Some(bracket) = rest.strip_prefix('[') {
+				let end = bracket.find(']').ok_or_else(invalid)?;
+				let inner = &bracket[..end];
+				let step = match inner.parse::<usize>() {
+					Ok(index) => Step::Index(index),
+					Err(_) => {
+						Step::Field(inner.trim_matches(|c| c == '"' || c == '\'').to_string())
+					}
+				};
+				steps.push(step);
+				rest = &bracket[end + 1..];
+			} else {
+				return Err(invalid());
+			}
+		}
+		Ok(JsonPath {
+			steps,
+		})
+	}
+
+	/// Node selected by the path, `None` when a step does not exist
+	pub fn select<'a>(&self, value: &'a Value) -> Option<&'a Value> {
+		self.steps.iter().try_fold(value, |node, step| match step {
+			Step::Field(field) => node.get(field),
+			Step::Index(index) => node.get(index),
+		})
+	}
+}
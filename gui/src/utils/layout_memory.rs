@@ -0,0 +1,48 @@
+use std::{
+	fs, io,
+	path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// # Remembered layouts
+/// Key and value templates last selected while browsing `column` of `database`, applied
+/// again when the keyspace is opened later.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RememberedLayout {
+	/// Database addressed as `name:path`
+	pub database: String,
+	pub column: Option<String>,
+	pub key_template: Option<String>,
+	pub value_template: Option<String>,
+}
+
+/// Remembered layouts are persisted next to the config file
+pub fn layouts_path(config_path: &str) -> PathBuf {
+	Path::new(config_path).with_file_name("layouts.json")
+}
+
+pub fn load_layouts(path: &Path) -> Vec<RememberedLayout> {
+	fs::read_to_string(path)
+		.ok()
+		.and_then(|data| serde_json::from_str(&data).ok())
+		.unwrap_or_default()
+}
+
+/// Layout remembered for the keyspace, if any
+pub fn find_layout(
+	path: &Path,
+	database: &str,
+	column: &Option<String>,
+) -> Option<RememberedLayout> {
+	load_layouts(path).into_iter().find(|l| l.database == database && &l.column == column)
+}
+
+/// Persist the layout of a keyspace, replacing the one remembered before
+pub fn remember_layout(path: &Path, layout: RememberedLayout) -> io::Result<()> {
+	let mut layouts = load_layouts(path);
+	layouts.retain(|l| !(l.database == layout.database && l.column == layout.column));
+	layouts.push(layout);
+	let data = serde_json::to_string_pretty(&layouts)?;
+	fs::write(path, data)
+}
@@ -0,0 +1,94 @@
+use std::{
+	mem,
+	sync::{
+		atomic::{AtomicU64, Ordering},
+		Mutex,
+	},
+};
+
+use db::KeyValuePair;
+
+/// Parts of the interface holding data in memory
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MemoryComponent {
+	/// Pairs and table cells displayed by the editor
+	Rows,
+	/// Tables of analysis reports
+	Results,
+	/// Session snapshots of SNAPSHOT
+	Snapshots,
+}
+
+impl MemoryComponent {
+	pub const ALL: [MemoryComponent; 3] =
+		[MemoryComponent::Rows, MemoryComponent::Results, MemoryComponent::Snapshots];
+
+	pub fn label(&self) -> &'static str {
+		match self {
+			MemoryComponent::Rows => "Loaded rows",
+			MemoryComponent::Results => "Report results",
+			MemoryComponent::Snapshots => "Session snapshots",
+		}
+	}
+}
+
+static NEXT_ACCOUNT: AtomicU64 = AtomicU64::new(0);
+static USAGE: Mutex<Vec<(u64, MemoryComponent, usize)>> = Mutex::new(Vec::new());
+
+/// # Memory accounting
+/// Approximate bytes held by the components of an owner, e.g. the editor of a workspace.
+/// The owner records the size of a component when its data changes, the usage is forgotten
+/// once the account is dropped with its owner. Sizes count the bytes of keys, values and
+/// text plus their buffer headers, not the allocator overhead.
+pub struct MemoryAccount {
+	id: u64,
+}
+
+impl Default for MemoryAccount {
+	fn default() -> Self {
+		MemoryAccount {
+			id: NEXT_ACCOUNT.fetch_add(1, Ordering::Relaxed),
+		}
+	}
+}
+
+impl MemoryAccount {
+	pub fn record(&self, component: MemoryComponent, bytes: usize) {
+		if let Ok(mut usage) = USAGE.lock() {
+			match usage.iter_mut().find(|(id, c, _)| *id == self.id && *c == component) {
+				Some((_, _, size)) => *size = bytes,
+				None => usage.push((self.id, component, bytes)),
+			}
+		}
+	}
+}
+
+impl Drop for MemoryAccount {
+	fn drop(&mut self) {
+		if let Ok(mut usage) = USAGE.lock() {
+			usage.retain(|(id, ..)| *id != self.id);
+		}
+	}
+}
+
+/// Bytes of every component, summed over their owners
+pub fn memory_usage() -> Vec<(MemoryComponent, usize)> {
+	let usage = USAGE.lock().map(|usage| usage.clone()).unwrap_or_default();
+	MemoryComponent::ALL
+		.iter()
+		.map(|component| {
+			let bytes = usage.iter().filter(|(_, c, _)| c == component).map(|(.., b)| b).sum();
+			(*component, bytes)
+		})
+		.collect()
+}
+
+pub fn pairs_size(pairs: &[KeyValuePair]) -> usize {
+	let header = 2 * mem::size_of::<Vec<u8>>();
+	pairs.iter().map(|(key, value)| header + key.len() + value.len()).sum()
+}
+
+pub fn cells_size(rows: &[Vec<String>]) -> usize {
+	let cells = rows.iter().flat_map(|row| row.iter());
+	cells.map(|cell| mem::size_of::<String>() + cell.len()).sum()
+}
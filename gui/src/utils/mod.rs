@@ -1,8 +1,88 @@
+mod analysis;
+mod archive;
+mod backup;
+mod bookmark;
 mod byte;
+mod checksum;
+mod clipboard;
+mod compare;
+mod conflict;
+mod crash;
+mod diagnosis;
+mod diff;
+mod discover;
+mod expiry;
+mod export;
+mod expression;
 mod file;
+mod filter;
+mod format;
+mod generate;
+mod grammar;
+mod health;
+mod history;
+mod hook;
+mod import;
+mod infer;
+mod job;
+mod join;
+mod json_path;
+mod layout_memory;
+mod memory;
+mod note;
+mod query;
+mod retention;
+mod rewrite;
+mod row_template;
+mod search;
+mod stats;
+mod timeline;
+mod transfer;
+mod update;
+mod view;
 
+pub use analysis::*;
+pub use archive::*;
+pub use backup::*;
+pub use bookmark::*;
 pub use byte::*;
+pub use checksum::*;
+pub use clipboard::*;
+pub use compare::*;
+pub use conflict::*;
+pub use crash::*;
+pub use diagnosis::*;
+pub use diff::*;
+pub use discover::*;
+pub use expiry::*;
+pub use export::*;
+pub use expression::*;
 pub use file::*;
+pub use filter::*;
+pub use format::*;
+pub use generate::*;
+pub use grammar::*;
+pub use health::*;
+pub use history::*;
+pub use hook::*;
+pub use import::*;
+pub use infer::*;
+pub use job::*;
+pub use join::*;
+pub use json_path::*;
+pub use layout_memory::*;
+pub use memory::*;
+pub use note::*;
+pub use query::*;
+pub use retention::*;
+pub use rewrite::*;
+pub use row_template::*;
+pub use search::*;
+pub use stats::*;
+pub use timeline::*;
+pub use transfer::*;
+pub use update::*;
+pub use view::*;
 
 use crate::events::Key;
 
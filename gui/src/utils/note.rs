@@ -0,0 +1,76 @@
+use std::{
+	fs, io,
+	path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// # Notes
+/// Text attached to a key by NOTE, kept in a local file instead of the database so the
+/// annotated keyspace is never written. Annotated rows are marked in the editor and the
+/// notes of every database are searched with NOTES.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyNote {
+	/// Database addressed as `name:path`
+	pub database: String,
+	pub column: Option<String>,
+	/// Key written as a byte literal
+	pub key: String,
+	pub text: String,
+}
+
+impl KeyNote {
+	fn is_on(&self, database: &str, column: &Option<String>, key: &str) -> bool {
+		self.database == database && &self.column == column && self.key == key
+	}
+
+	/// Command of the command editor showing the annotated key
+	pub fn command_text(&self) -> String {
+		let mut tokens = vec![format!("DB={}", self.database)];
+		if let Some(column) = &self.column {
+			tokens.push(format!("COLUMN={}", column));
+		}
+		tokens.push(format!("PREFIX={}", self.key));
+		tokens.join(" ")
+	}
+}
+
+/// Notes are persisted next to the config file
+pub fn notes_path(config_path: &str) -> PathBuf {
+	Path::new(config_path).with_file_name("notes.json")
+}
+
+/// Load persisted notes, none when the file does not exist yet
+pub fn load_notes(path: &Path) -> Vec<KeyNote> {
+	fs::read_to_string(path)
+		.ok()
+		.and_then(|data| serde_json::from_str(&data).ok())
+		.unwrap_or_default()
+}
+
+/// Persist a note, replacing the note of the same key. A note without text removes it.
+pub fn save_note(path: &Path, note: KeyNote) -> io::Result<()> {
+	let mut notes = load_notes(path);
+	notes.retain(|n| !n.is_on(&note.database, &note.column, &note.key));
+	if !note.text.is_empty() {
+		notes.push(note);
+	}
+	let data = serde_json::to_string_pretty(&notes)?;
+	fs::write(path, data)
+}
+
+/// Notes of a column family of a database
+pub fn column_notes(notes: Vec<KeyNote>, database: &str, column: &Option<String>) -> Vec<KeyNote> {
+	notes.into_iter().filter(|n| n.database == database && &n.column == column).collect()
+}
+
+/// Notes whose text, key or database contains a term, case insensitive
+pub fn search_notes(notes: Vec<KeyNote>, term: &str) -> Vec<KeyNote> {
+	let term = term.to_lowercase();
+	notes
+		.into_iter()
+		.filter(|n| {
+			[&n.text, &n.key, &n.database].iter().any(|field| field.to_lowercase().contains(&term))
+		})
+		.collect()
+}
@@ -0,0 +1,150 @@
+use std::{io::Write, ops::Bound};
+
+use db::{Datastore, Error, KeyValuePair, Page, SimpleTransaction, CF};
+
+use super::{
+	encode_text, parse_byte_literal, parse_filter, write_pair_table, ColumnScope, Command,
+	ExportFormat, ExportOrder, FilterExpr, KeyDisplay, Order,
+};
+use crate::config::Config;
+
+/// Find a configured database by alias, or parse a `rocksdb:PATH`, `redb:PATH`,
+/// `sled:PATH`, `lmdb:PATH` or `memory:NAME` address
+pub fn resolve_database(config: &Config, target: &str) -> Option<(String, String)> {
+	for (name, databases) in config.databases.iter() {
+		if let Some(db) = databases.iter().find(|db| db.alias.as_deref() == Some(target)) {
+			return Some((name.clone(), db.path.clone()));
+		}
+	}
+	match target.split_once(':') {
+		Some((name, path)) if ["rocksdb", "redb", "sled", "lmdb", "memory"].contains(&name) => {
+			Some((name.to_string(), path.to_string()))
+		}
+		_ => None,
+	}
+}
+
+enum QueryScan {
+	All,
+	Prefix(Vec<u8>),
+	Suffix(Vec<u8>),
+	Range(Bound<Vec<u8>>, Bound<Vec<u8>>),
+	Where(FilterExpr),
+}
+
+/// # Headless query
+/// Scan described by the tokens of a command, run by `edma query` without the terminal
+/// interface. Only reads are supported: `DB`, `COLUMN`, `PREFIX`, `SUFFIX`, `RANGE`,
+/// `WHERE`, `ORDER`, `LIMIT` and `OFFSET`. Keys and bounds are byte literals or text, no
+/// layout is selected headless.
+pub struct Query {
+	pub name: String,
+	pub path: String,
+	pub cf: CF,
+	scan: QueryScan,
+	page: Page,
+	order: Order,
+}
+
+impl Query {
+	/// Scan of the parsed `commands` on the database `db`, an alias or an address. `DB`
+	/// tokens of the command take precedence.
+	pub fn from_commands(config: &Config, db: &str, commands: &[Command]) -> Result<Self, String> {
+		let unknown = |db: &str| format!("Unknown database {}", db);
+		let (name, path) = resolve_database(config, db).ok_or_else(|| unknown(db))?;
+		let mut query = Query {
+			name,
+			path,
+			cf: None,
+			scan: QueryScan::All,
+			page: Page::default(),
+			order: Order::Asc,
+		};
+		let bound = |bound: &Bound<String>| {
+			let encode = |text: &String| {
+				encode_text(None, text).ok_or_else(|| format!("Invalid RANGE bound {}", text))
+			};
+			Ok::<_, String>(match bound {
+				Bound::Included(text) => Bound::Included(encode(text)?),
+				Bound::Excluded(text) => Bound::Excluded(encode(text)?),
+				Bound::Unbounded => Bound::Unbounded,
+			})
+		};
+		for command in commands {
+			match command {
+				Command::Db(db) => {
+					(query.name, query.path) =
+						resolve_database(config, db).ok_or_else(|| unknown(db))?
+				}
+				Command::Column(cf) => query.cf = Some(cf.as_bytes().to_vec()),
+				Command::Prefix(prefix) => {
+					query.scan = QueryScan::Prefix(parse_byte_literal(prefix)?);
+				}
+				Command::Suffix(suffix) => {
+					query.scan = QueryScan::Suffix(parse_byte_literal(suffix)?);
+				}
+				Command::Range(range) => {
+					query.scan = QueryScan::Range(bound(&range.start)?, bound(&range.end)?);
+				}
+				Command::Where(expression) => {
+					query.scan = QueryScan::Where(parse_filter(expression)?);
+				}
+				Command::Order(order) => query.order = *order,
+				Command::Limit(limit) => query.page.limit = Some(*limit),
+				Command::Offset(offset) => query.page.offset = *offset,
+				command => {
+					return Err(format!("{} is not supported by edma query", command.keyword()))
+				}
+			}
+		}
+		Ok(query)
+	}
+
+	/// Run the scan in one read transaction
+	pub async fn run(&self) -> Result<Vec<KeyValuePair>, Error> {
+		let ds = Datastore::open(&format!("{}:{}", self.name, self.path))?;
+		let tx = ds.transaction(false).await?;
+		let (cf, reverse) = (self.cf.clone(), self.order == Order::Desc);
+		let pairs = match &self.scan {
+			QueryScan::All => tx.page_iterate(cf, self.page, reverse).await?,
+			QueryScan::Range(start, end) => {
+				let (start, end) = (start.clone(), end.clone());
+				tx.scan_range(cf, start, end, self.page, reverse).await?
+			}
+			QueryScan::Prefix(prefix) => tx.prefix_iterate(cf, prefix.clone()).await?,
+			QueryScan::Suffix(suffix) => tx.suffix_iterate(cf, suffix.clone()).await?,
+			QueryScan::Where(_) => tx.iterate(cf).await?,
+		};
+		let mut pairs = pairs.into_iter().collect::<Result<Vec<_>, Error>>()?;
+		match &self.scan {
+			QueryScan::All | QueryScan::Range(..) => return Ok(pairs),
+			QueryScan::Where(expr) => {
+				let columns = ColumnScope {
+					key: None,
+					value: None,
+				};
+				pairs = expr.apply(pairs, columns);
+			}
+			QueryScan::Prefix(_) | QueryScan::Suffix(_) => {}
+		}
+		if reverse {
+			pairs.reverse();
+		}
+		Ok(self.page.apply(pairs.into_iter()).collect())
+	}
+}
+
+/// Write the pairs of a query in `format` and `order`, formatted like the rows of EXPORT.
+/// Returns the number of written rows.
+pub fn write_query_pairs<W: Write>(
+	writer: W,
+	format: ExportFormat,
+	order: ExportOrder,
+	key_display: KeyDisplay,
+	pairs: &[KeyValuePair],
+) -> std::io::Result<usize> {
+	let headers = vec!["#".to_string(), "Key".to_string(), "Value".to_string()];
+	write_pair_table(writer, format, order, headers, pairs, |index, (key, value)| {
+		vec![index.to_string(), key_display.render(key), format!("{:?}", value)]
+	})
+}
@@ -0,0 +1,77 @@
+use db::{Datastore, Error, KeyValuePair, SimpleTransaction, WriteBatch, CF};
+
+use super::{is_older_than, now_timestamp, open_datastore, resolve_database};
+use crate::{
+	config::{Config, RetentionConfig},
+	constants::WRITE_BATCH_SIZE,
+};
+
+/// Delete keys with one transaction per batch of `WRITE_BATCH_SIZE` keys
+pub async fn delete_keys(ds: &Datastore, cf: CF, keys: &[Vec<u8>]) -> Result<(), Error> {
+	let mut batch = WriteBatch::new(ds, WRITE_BATCH_SIZE);
+	for key in keys {
+		batch.del(cf.clone(), key.clone()).await?;
+		batch.advance().await?;
+	}
+	batch.commit().await?;
+	Ok(())
+}
+
+async fn scan(db_path: &str, cf: CF) -> Result<Vec<KeyValuePair>, Error> {
+	let ds = Datastore::open(db_path)?;
+	let tx = ds.transaction(false).await?;
+	tx.iterate(cf).await?.into_iter().collect()
+}
+
+/// Keys of a retention rule: `scanned` keys of the column family, `expired` of them past
+/// the retention and deleted when the rule is applied
+pub struct RetentionOutcome {
+	pub db_path: String,
+	pub scanned: usize,
+	pub expired: usize,
+}
+
+/// Evaluate a retention rule on its column family. The keys past the retention are only
+/// counted unless `apply` is set, they are then deleted in batches with the hooks and
+/// write limits of the config.
+pub async fn run_retention(
+	config: &Config,
+	rule: &RetentionConfig,
+	apply: bool,
+) -> Result<RetentionOutcome, String> {
+	let (name, path) = resolve_database(config, &rule.database)
+		.ok_or_else(|| format!("Unknown database {}", rule.database))?;
+	let template = config
+		.templates
+		.iter()
+		.find(|t| t.name == rule.layout)
+		.ok_or_else(|| format!("Unknown layout {}", rule.layout))?;
+	let segment = template
+		.layout
+		.iter()
+		.find(|l| l.name == rule.segment)
+		.ok_or_else(|| format!("No segment {} in layout {}", rule.segment, rule.layout))?;
+
+	let db_path = format!("{}:{}", name, path);
+	let cf = rule.column.as_ref().map(|c| c.as_bytes().to_vec());
+	let pairs = scan(&db_path, cf.clone()).await.map_err(|e| e.to_string())?;
+	let now = now_timestamp();
+	let mut scanned = 0;
+	let mut expired = vec![];
+	for (key, _) in pairs {
+		scanned += 1;
+		if is_older_than(&key, segment.from, segment.to, now, rule.days) {
+			expired.push(key);
+		}
+	}
+
+	if apply {
+		let ds = open_datastore(config, &db_path).map_err(|e| e.to_string())?;
+		delete_keys(&ds, cf, &expired).await.map_err(|e| e.to_string())?;
+	}
+	Ok(RetentionOutcome {
+		db_path,
+		scanned,
+		expired: expired.len(),
+	})
+}
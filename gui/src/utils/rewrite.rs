@@ -0,0 +1,89 @@
+use std::{cmp::min, collections::HashSet};
+
+use db::{Datastore, Error, Key, KeyValuePair, Val, WriteBatch, CF};
+use regex::bytes::Regex;
+
+use crate::constants::WRITE_BATCH_SIZE;
+
+#[derive(Clone, Debug)]
+pub struct KeyRewrite {
+	pub from: Key,
+	pub to: Key,
+	pub value: Val,
+}
+
+/// Rewrite a key by replacing every match of `regex`. When `range` is set only the bytes
+/// in `from..to` are matched, the rest of the key is kept as is.
+pub fn rewrite_key(
+	key: &[u8],
+	regex: &Regex,
+	replacement: &str,
+	range: Option<(usize, usize)>,
+) -> Vec<u8> {
+	let (start, end) = match range {
+		Some((from, to)) => {
+			let end = min(to, key.len());
+			(min(from, end), end)
+		}
+		None => (0, key.len()),
+	};
+	let replaced = regex.replace_all(&key[start..end], replacement.as_bytes());
+	[&key[..start], &replaced, &key[end..]].concat()
+}
+
+/// Compute the rewrites of the given pairs. Keys left unchanged are skipped. The plan is
+/// rejected when two keys are rewritten to the same key or when a rewritten key already
+/// exists, as executing it would silently overwrite data.
+pub fn plan_rewrites(
+	pairs: &[KeyValuePair],
+	regex: &Regex,
+	replacement: &str,
+	range: Option<(usize, usize)>,
+) -> Result<Vec<KeyRewrite>, String> {
+	let existing: HashSet<&[u8]> = pairs.iter().map(|(k, _)| k.as_slice()).collect();
+	let mut targets = HashSet::new();
+	let mut rewrites = vec![];
+	for (key, value) in pairs.iter() {
+		let to = rewrite_key(key, regex, replacement, range);
+		if &to == key {
+			continue;
+		}
+		if existing.contains(to.as_slice()) {
+			return Err(format!("Rewritten key {:?} already exists", to));
+		}
+		if !targets.insert(to.clone()) {
+			return Err(format!("Several keys are rewritten to {:?}", to));
+		}
+		rewrites.push(KeyRewrite {
+			from: key.clone(),
+			to,
+			value: value.clone(),
+		});
+	}
+	Ok(rewrites)
+}
+
+/// Execute rewrites as delete and put with one transaction per batch of `WRITE_BATCH_SIZE`
+/// rewrites. `progress` is called with the number of rewritten keys after every batch.
+pub async fn apply_rewrites<F>(
+	ds: &Datastore,
+	cf: CF,
+	rewrites: &[KeyRewrite],
+	mut progress: F,
+) -> Result<usize, Error>
+where
+	F: FnMut(usize),
+{
+	let mut batch = WriteBatch::new(ds, WRITE_BATCH_SIZE);
+	for rewrite in rewrites {
+		batch.del(cf.clone(), rewrite.from.clone()).await?;
+		batch.set(cf.clone(), rewrite.to.clone(), rewrite.value.clone()).await?;
+		if batch.advance().await? {
+			progress(batch.committed());
+		}
+	}
+	if batch.commit().await? {
+		progress(batch.committed());
+	}
+	Ok(batch.committed())
+}
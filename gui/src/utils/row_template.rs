@@ -0,0 +1,92 @@
+use serde_json::Value;
+
+use super::{format_byte_literal, ColumnScope, JsonPath, LayoutTemplate};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum RowPart {
+	Text(String),
+	Field(String),
+}
+
+/// # Row template
+/// Summary of a pair displayed in place of its value, configured with `row` on a layout
+/// template, e.g. `"{user_id} — {value.name} ({size}B)"`. A field is a segment or computed
+/// column of the selected key or value layout, `key.<segment>` or `value.<segment>` to pick
+/// the side, a field of a JSON value, `size` and `key_size` for the byte lengths, or `key`
+/// and `value` for the raw bytes. Unknown fields are written `?`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RowTemplate {
+	parts: Vec<RowPart>,
+}
+
+impl RowTemplate {
+	pub fn parse(text: &str) -> Result<RowTemplate, String> {
+		let mut parts = vec![];
+		let mut rest = text;
+		while let Some(open) = rest.find('{') {
+			if open > 0 {
+				parts.push(RowPart::Text(rest[..open].to_string()));
+			}
+			let close = rest[open..].find('}').ok_or("Unclosed { in row template")? + open;
+			let field = rest[open + 1..close].trim();
+			if field.is_empty() {
+				return Err("Empty field in row template".to_string());
+			}
+			parts.push(RowPart::Field(field.to_string()));
+			rest = &rest[close + 1..];
+		}
+		if !rest.is_empty() {
+			parts.push(RowPart::Text(rest.to_string()));
+		}
+		Ok(RowTemplate {
+			parts,
+		})
+	}
+
+	/// Summary of a pair, with the segments decoded by the selected layouts
+	pub fn render(&self, columns: ColumnScope, key: &[u8], value: &[u8]) -> String {
+		self.parts
+			.iter()
+			.map(|part| match part {
+				RowPart::Text(text) => text.clone(),
+				RowPart::Field(field) => {
+					resolve(field, columns, key, value).unwrap_or_else(|| "?".to_string())
+				}
+			})
+			.collect()
+	}
+}
+
+/// Decoded segment or computed column of a template
+fn segment(template: Option<&LayoutTemplate>, name: &str, raw: &[u8]) -> Option<String> {
+	let template = template?;
+	let index = template.names().iter().position(|n| n == name)?;
+	template.decode(raw).into_iter().nth(index)
+}
+
+/// Field of a JSON value, e.g. `name` or `address.city`
+fn json_field(name: &str, raw: &[u8]) -> Option<String> {
+	let json: Value = serde_json::from_slice(raw).ok()?;
+	let node = JsonPath::parse(&format!("$.{}", name)).ok()?.select(&json)?.clone();
+	Some(match node {
+		Value::String(text) => text,
+		node => node.to_string(),
+	})
+}
+
+fn resolve(field: &str, columns: ColumnScope, key: &[u8], value: &[u8]) -> Option<String> {
+	match field {
+		"size" => return Some(value.len().to_string()),
+		"key_size" => return Some(key.len().to_string()),
+		"key" => return Some(format_byte_literal(key)),
+		"value" => return Some(String::from_utf8_lossy(value).to_string()),
+		_ => {}
+	}
+	if let Some(name) = field.strip_prefix("key.") {
+		return segment(columns.key, name, key);
+	}
+	if let Some(name) = field.strip_prefix("value.") {
+		return segment(columns.value, name, value).or_else(|| json_field(name, value));
+	}
+	segment(columns.key, field, key).or_else(|| segment(columns.value, field, value))
+}
@@ -0,0 +1,349 @@
+use std::{
+	ops::Bound,
+	time::{Duration, Instant},
+};
+
+use db::{Cursor, Datastore, Error, KeyValuePair, CF};
+
+use super::{
+	is_byte_literal, open_datastore, parse_byte_literal, KeyDisplay, LayoutTemplate, SearchScope,
+};
+use crate::{
+	config::Config,
+	constants::{SCAN_BATCH_SIZE, SEARCH_MAX_HITS},
+};
+
+/// Pair matching a SEARCH term. The match is located in a cell of the editor table: 1 for
+/// the key, 2 for the value, then the computed columns of the key and value layouts.
+/// `start` and `end` are char offsets in the text of the cell.
+#[derive(Clone, Debug)]
+pub struct SearchHit {
+	pub pair: KeyValuePair,
+	pub column: usize,
+	pub start: usize,
+	pub end: usize,
+}
+
+/// Offset of `needle` in `haystack`, ASCII letters compared without case when asked
+fn find_bytes(haystack: &[u8], needle: &[u8], ignore_case: bool) -> Option<usize> {
+	if needle.is_empty() {
+		return None;
+	}
+	haystack.windows(needle.len()).position(|window| match ignore_case {
+		true => window.eq_ignore_ascii_case(needle),
+		false => window == needle,
+	})
+}
+
+/// # Search jobs
+/// Scan of a column family for a term, one batch of `SCAN_BATCH_SIZE` pairs at a time. The
+/// term is matched against the raw bytes of the keys and values, without case unless it is
+/// a byte literal, then against the computed columns of the selected layouts.
+pub struct SearchJob {
+	cf: CF,
+	term: String,
+	needle: Vec<u8>,
+	ignore_case: bool,
+	scope: SearchScope,
+	key_display: KeyDisplay,
+	key_template: Option<LayoutTemplate>,
+	value_template: Option<LayoutTemplate>,
+	cursor: Cursor,
+	scanned: usize,
+	hits: Vec<SearchHit>,
+}
+
+impl SearchJob {
+	pub fn new(
+		cf: CF,
+		term: &str,
+		scope: SearchScope,
+		key_display: KeyDisplay,
+		key_template: Option<LayoutTemplate>,
+		value_template: Option<LayoutTemplate>,
+	) -> Self {
+		SearchJob {
+			cursor: Cursor::new(cf.clone(), Bound::Unbounded, Bound::Unbounded, false),
+			cf,
+			term: term.to_string(),
+			needle: parse_byte_literal(term).unwrap_or_default(),
+			ignore_case: !is_byte_literal(term),
+			scope,
+			key_display,
+			key_template,
+			value_template,
+			scanned: 0,
+			hits: vec![],
+		}
+	}
+
+	pub fn cf(&self) -> &CF {
+		&self.cf
+	}
+
+	pub fn term(&self) -> &str {
+		&self.term
+	}
+
+	pub fn key_display(&self) -> KeyDisplay {
+		self.key_display
+	}
+
+	pub fn scanned(&self) -> usize {
+		self.scanned
+	}
+
+	pub fn hits(&self) -> &[SearchHit] {
+		&self.hits
+	}
+
+	/// The scan ends with the keyspace or once `SEARCH_MAX_HITS` pairs matched
+	pub fn is_done(&self) -> bool {
+		!self.cursor.has_next() || self.hits.len() >= SEARCH_MAX_HITS
+	}
+
+	/// Location of the first match of the term in a pair
+	fn locate(&self, key: &[u8], value: &[u8]) -> Option<(usize, usize, usize)> {
+		let (in_key, in_value) = match self.scope {
+			SearchScope::Key => (true, false),
+			SearchScope::Value => (false, true),
+			SearchScope::Both => (true, true),
+		};
+		// Values are shown as byte lists whatever the key display
+		let raw = [(1, key, in_key, self.key_display), (2, value, in_value, KeyDisplay::Bytes)];
+		for (column, bytes, _, display) in raw.into_iter().filter(|(_, _, searched, _)| *searched) {
+			if let Some(start) = find_bytes(bytes, &self.needle, self.ignore_case) {
+				let (from, to) = display.span(bytes, start, start + self.needle.len());
+				return Some((column, from, to));
+			}
+		}
+
+		let term = self.term.to_ascii_lowercase();
+		let key_columns = self.key_template.as_ref().map(|t| t.compute_all(key));
+		let value_columns = self.value_template.as_ref().map(|t| t.compute_all(value));
+		let columns = key_columns
+			.unwrap_or_default()
+			.into_iter()
+			.map(|text| (text, in_key))
+			.chain(value_columns.unwrap_or_default().into_iter().map(|text| (text, in_value)));
+		for (index, (text, _)) in columns.enumerate().filter(|(_, (_, searched))| *searched) {
+			if let Some(start) = text.to_ascii_lowercase().find(&term) {
+				let from = text[..start].chars().count();
+				let to = from + text[start..start + term.len()].chars().count();
+				return Some((index + 3, from, to));
+			}
+		}
+		None
+	}
+
+	/// Scan the next batch in its own transaction
+	pub async fn search_batch(&mut self, ds: &Datastore) -> Result<(), Error> {
+		let tx = ds.transaction(false).await?;
+		let pairs = self.cursor.next_batch(&tx, SCAN_BATCH_SIZE).await?;
+		drop(tx);
+
+		self.scanned += pairs.len();
+		for (key, value) in pairs {
+			if self.hits.len() >= SEARCH_MAX_HITS {
+				break;
+			}
+			if let Some((column, start, end)) = self.locate(&key, &value) {
+				self.hits.push(SearchHit {
+					pair: (key, value),
+					column,
+					start,
+					end,
+				});
+			}
+		}
+		Ok(())
+	}
+}
+
+/// # Global search
+/// SEARCH=ALL fans a search job out to every column family of the database. Each round
+/// scans one batch of every column family still searched, so matches of all of them come
+/// in together instead of one keyspace after the other.
+pub struct GlobalSearch {
+	term: String,
+	jobs: Vec<SearchJob>,
+}
+
+impl GlobalSearch {
+	pub fn new(term: &str, jobs: Vec<SearchJob>) -> Self {
+		GlobalSearch {
+			term: term.to_string(),
+			jobs,
+		}
+	}
+
+	pub fn term(&self) -> &str {
+		&self.term
+	}
+
+	/// Search of every column family, in the order they are listed by the database
+	pub fn jobs(&self) -> &[SearchJob] {
+		&self.jobs
+	}
+
+	pub fn scanned(&self) -> usize {
+		self.jobs.iter().map(|job| job.scanned()).sum()
+	}
+
+	pub fn matches(&self) -> usize {
+		self.jobs.iter().map(|job| job.hits().len()).sum()
+	}
+
+	pub fn is_done(&self) -> bool {
+		self.jobs.iter().all(|job| job.is_done())
+	}
+
+	/// Scan the next batch of every column family not done yet
+	pub async fn search_round(&mut self, ds: &Datastore) -> Result<(), Error> {
+		for job in self.jobs.iter_mut().filter(|job| !job.is_done()) {
+			job.search_batch(ds).await?;
+		}
+		Ok(())
+	}
+}
+
+/// Search of a database addressed by `name` and `path`, advanced from the event loop
+pub struct BackgroundSearch {
+	pub name: String,
+	pub path: String,
+	pub job: SearchJob,
+}
+
+impl BackgroundSearch {
+	/// Scan batches until `budget` elapsed, the database is opened for the tick only
+	pub async fn tick(&mut self, config: &Config, budget: Duration) -> Result<(), Error> {
+		let started = Instant::now();
+		let ds = open_datastore(config, &format!("{}:{}", self.name, self.path))?;
+		while !self.job.is_done() && started.elapsed() < budget {
+			self.job.search_batch(&ds).await?;
+		}
+		Ok(())
+	}
+}
+
+/// Global search of one database of SEARCH=DATABASES. A database which cannot be opened,
+/// or fails while it is searched, keeps the failure and is not searched further.
+pub struct DatabaseSearch {
+	search: BackgroundGlobalSearch,
+	failure: Option<String>,
+}
+
+impl DatabaseSearch {
+	pub fn new(name: String, path: String, search: Result<GlobalSearch, Error>) -> Self {
+		let (job, failure) = match search {
+			Ok(job) => (job, None),
+			Err(err) => (GlobalSearch::new("", vec![]), Some(err.to_string())),
+		};
+		DatabaseSearch {
+			search: BackgroundGlobalSearch {
+				name,
+				path,
+				job,
+			},
+			failure,
+		}
+	}
+
+	pub fn name(&self) -> &str {
+		&self.search.name
+	}
+
+	pub fn path(&self) -> &str {
+		&self.search.path
+	}
+
+	/// Search of the database, none when it is unreachable
+	pub fn search(&self) -> Option<&GlobalSearch> {
+		self.failure.is_none().then_some(&self.search.job)
+	}
+
+	fn is_done(&self) -> bool {
+		self.failure.is_some() || self.search.job.is_done()
+	}
+
+	/// Matches so far, `done` once searched or `unreachable`
+	fn progress(&self) -> String {
+		let state = match (&self.failure, self.search.job.is_done()) {
+			(Some(_), _) => return format!("{}:{} unreachable", self.name(), self.path()),
+			(None, true) => " done",
+			(None, false) => "",
+		};
+		format!("{}:{} {}{}", self.name(), self.path(), self.search.job.matches(), state)
+	}
+}
+
+/// # Search across databases
+/// SEARCH=DATABASES runs a global search on every configured database at once. Each tick
+/// shares its budget between the databases still searched, so the progress of every
+/// database moves together and a cancelled search keeps the matches of all of them.
+pub struct DatabasesSearch {
+	term: String,
+	databases: Vec<DatabaseSearch>,
+}
+
+impl DatabasesSearch {
+	pub fn new(term: &str, databases: Vec<DatabaseSearch>) -> Self {
+		DatabasesSearch {
+			term: term.to_string(),
+			databases,
+		}
+	}
+
+	pub fn term(&self) -> &str {
+		&self.term
+	}
+
+	pub fn databases(&self) -> &[DatabaseSearch] {
+		&self.databases
+	}
+
+	pub fn matches(&self) -> usize {
+		self.databases.iter().filter_map(|db| db.search()).map(|search| search.matches()).sum()
+	}
+
+	pub fn is_done(&self) -> bool {
+		self.databases.iter().all(|db| db.is_done())
+	}
+
+	/// Matches of every database
+	pub fn progress(&self) -> String {
+		self.databases.iter().map(|db| db.progress()).collect::<Vec<_>>().join(", ")
+	}
+
+	/// Advance the search of every database not done yet, a failing database is set
+	/// unreachable without stopping the others
+	pub async fn tick(&mut self, config: &Config, budget: Duration) -> Result<(), Error> {
+		let searched = self.databases.iter().filter(|db| !db.is_done()).count();
+		let budget = budget / searched.max(1) as u32;
+		for database in self.databases.iter_mut().filter(|db| !db.is_done()) {
+			if let Err(err) = database.search.tick(config, budget).await {
+				database.failure = Some(err.to_string());
+			}
+		}
+		Ok(())
+	}
+}
+
+/// Global search of a database addressed by `name` and `path`
+pub struct BackgroundGlobalSearch {
+	pub name: String,
+	pub path: String,
+	pub job: GlobalSearch,
+}
+
+impl BackgroundGlobalSearch {
+	/// Scan rounds until `budget` elapsed, the database is opened for the tick only
+	pub async fn tick(&mut self, config: &Config, budget: Duration) -> Result<(), Error> {
+		let started = Instant::now();
+		let ds = open_datastore(config, &format!("{}:{}", self.name, self.path))?;
+		while !self.job.is_done() && started.elapsed() < budget {
+			self.job.search_round(&ds).await?;
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,143 @@
+use std::{
+	collections::{HashMap, VecDeque},
+	time::{Duration, Instant},
+};
+
+use db::{Datastore, DatastoreStats, Error, SimpleTransaction};
+
+use crate::{config::Config, constants::STATS_TAB_INTERVAL};
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DatabaseStats {
+	pub keys: usize,
+	pub size: usize,
+}
+
+/// Count keys and bytes stored in the default column family of a database
+pub async fn collect_stats(db_path: &str) -> Result<DatabaseStats, Error> {
+	let ds = Datastore::open(db_path)?;
+	let tx = ds.transaction(false).await?;
+	let mut stats = DatabaseStats::default();
+	for pair in tx.iterate(None).await? {
+		let (key, value) = pair?;
+		stats.keys += 1;
+		stats.size += key.len() + value.len();
+	}
+	Ok(stats)
+}
+
+/// Periodic refresh of the stats of every configured database. Databases are refreshed
+/// one per tick, so a refresh round never holds the event loop for long.
+#[derive(Default)]
+pub struct StatsRefresher {
+	interval: Option<Duration>,
+	last_round: Option<Instant>,
+	pending: VecDeque<String>,
+	stats: HashMap<String, DatabaseStats>,
+}
+
+impl StatsRefresher {
+	pub fn new(config: &Config) -> Self {
+		StatsRefresher {
+			interval: config.stats_interval,
+			..Default::default()
+		}
+	}
+
+	/// Latest stats keyed by database path, e.g. `rocksdb:./temp`
+	pub fn stats(&self) -> &HashMap<String, DatabaseStats> {
+		&self.stats
+	}
+
+	/// Refresh the next pending database and start a new round once the interval elapsed.
+	/// Returns true when stats were updated.
+	pub async fn tick(&mut self, config: &Config) -> bool {
+		let interval = match self.interval {
+			Some(interval) => interval,
+			None => return false,
+		};
+		if self.pending.is_empty() {
+			if self.last_round.map_or(false, |last| last.elapsed() < interval) {
+				return false;
+			}
+			self.last_round = Some(Instant::now());
+			self.pending = config
+				.databases
+				.iter()
+				.flat_map(|(name, dbs)| dbs.iter().map(move |db| format!("{}:{}", name, db.path)))
+				.collect();
+		}
+
+		match self.pending.pop_front() {
+			Some(db_path) => match collect_stats(&db_path).await {
+				Ok(stats) => {
+					self.stats.insert(db_path, stats);
+					true
+				}
+				Err(_) => false,
+			},
+			None => false,
+		}
+	}
+}
+
+/// Storage statistics and engine properties of a database, as shown by the Stats tab
+#[derive(Clone, Debug, Default)]
+pub struct StorageStats {
+	pub stats: DatastoreStats,
+	pub properties: Vec<(String, String)>,
+}
+
+/// Read the storage statistics of a database from its engine
+pub fn collect_storage_stats(db_path: &str) -> Result<StorageStats, Error> {
+	let ds = Datastore::open(db_path)?;
+	Ok(StorageStats {
+		stats: ds.stats()?,
+		properties: ds.properties()?,
+	})
+}
+
+/// Periodic refresh of the storage statistics of every configured database, every
+/// `STATS_TAB_INTERVAL` while the Stats tab is displayed. Databases are refreshed one per
+/// tick like `StatsRefresher`.
+#[derive(Default)]
+pub struct StorageStatsRefresher {
+	last_round: Option<Instant>,
+	pending: VecDeque<String>,
+	stats: HashMap<String, StorageStats>,
+}
+
+impl StorageStatsRefresher {
+	/// Latest statistics keyed by database path, e.g. `rocksdb:./temp`
+	pub fn stats(&self) -> &HashMap<String, StorageStats> {
+		&self.stats
+	}
+
+	/// Refresh the next pending database and start a new round once the interval elapsed.
+	/// Returns true when statistics were updated.
+	pub fn tick(&mut self, config: &Config) -> bool {
+		if self.pending.is_empty() {
+			if self.last_round.map_or(false, |last| last.elapsed() < STATS_TAB_INTERVAL) {
+				return false;
+			}
+			self.last_round = Some(Instant::now());
+			self.pending = config
+				.databases
+				.iter()
+				.flat_map(|(name, dbs)| dbs.iter().map(move |db| format!("{}:{}", name, db.path)))
+				.collect();
+		}
+
+		match self.pending.pop_front() {
+			Some(db_path) => match collect_storage_stats(&db_path) {
+				Ok(stats) => {
+					self.stats.insert(db_path, stats);
+					true
+				}
+				// A failed refresh keeps the last values, the database may be busy
+				Err(_) => false,
+			},
+			None => false,
+		}
+	}
+}
@@ -0,0 +1,45 @@
+use std::{fs, io, path::Path, time::Duration};
+
+use super::{format_duration, DisplayFormat};
+
+/// # Session timeline
+/// Command run during the session, with the time it started, how long it ran and the
+/// status it ended with
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TimelineEntry {
+	pub started_at: i64,
+	pub duration: Duration,
+	/// Database addressed as `name:path`
+	pub database: String,
+	pub command: String,
+	pub outcome: String,
+}
+
+impl TimelineEntry {
+	/// Cells of the timeline report: time, duration, database, command and outcome
+	pub fn cells(&self, display: &DisplayFormat) -> Vec<String> {
+		vec![
+			display.timestamp(self.started_at).unwrap_or_else(|| self.started_at.to_string()),
+			format_duration(self.duration, true),
+			self.database.clone(),
+			self.command.clone(),
+			self.outcome.clone(),
+		]
+	}
+}
+
+/// Write the timeline as a Markdown session report, e.g. to document an investigation
+pub fn write_timeline(
+	path: &Path,
+	entries: &[TimelineEntry],
+	display: &DisplayFormat,
+) -> io::Result<()> {
+	let mut report = String::from("# EDMA session report\n\n");
+	report.push_str("| Time | Duration | Database | Command | Outcome |\n");
+	report.push_str("| --- | --- | --- | --- | --- |\n");
+	for entry in entries {
+		let cells: Vec<_> = entry.cells(display).iter().map(|c| c.replace('|', "\\|")).collect();
+		report.push_str(&format!("| {} |\n", cells.join(" | ")));
+	}
+	fs::write(path, report)
+}
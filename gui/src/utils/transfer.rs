@@ -0,0 +1,142 @@
+use std::{
+	ops::Bound,
+	time::{Duration, Instant},
+};
+
+use db::{Cursor, Datastore, Error, KeyValuePair, WriteBatch, CF};
+
+use super::open_datastore;
+use crate::{config::Config, constants::WRITE_BATCH_SIZE};
+
+/// Write pairs with one transaction per batch of `WRITE_BATCH_SIZE` pairs. `progress` is
+/// called with the number of written pairs after every committed batch.
+pub async fn write_pairs<F>(
+	ds: &Datastore,
+	cf: CF,
+	pairs: &[KeyValuePair],
+	mut progress: F,
+) -> Result<usize, Error>
+where
+	F: FnMut(usize),
+{
+	let mut batch = WriteBatch::new(ds, WRITE_BATCH_SIZE);
+	for (key, value) in pairs {
+		batch.set(cf.clone(), key.clone(), value.clone()).await?;
+		if batch.advance().await? {
+			progress(batch.committed());
+		}
+	}
+	if batch.commit().await? {
+		progress(batch.committed());
+	}
+	Ok(batch.committed())
+}
+
+/// Upper bound of the keys starting with `prefix`, unbounded when every byte is `0xff`
+pub fn prefix_end(prefix: &[u8]) -> Bound<Vec<u8>> {
+	let mut end = prefix.to_vec();
+	while let Some(last) = end.pop() {
+		if last < u8::MAX {
+			end.push(last + 1);
+			return Bound::Excluded(end);
+		}
+	}
+	Bound::Unbounded
+}
+
+/// # Copy jobs
+/// Copy of the keys of a column family starting with a prefix into another column family,
+/// one batch of `WRITE_BATCH_SIZE` pairs at a time: a batch is read with a `Cursor` in its
+/// own transaction, then written, so large column families never sit in memory.
+pub struct CopyJob {
+	to: CF,
+	cursor: Cursor,
+	copied: usize,
+}
+
+impl CopyJob {
+	pub fn new(from: CF, to: CF, prefix: &[u8]) -> Self {
+		let start = match prefix.is_empty() {
+			true => Bound::Unbounded,
+			false => Bound::Included(prefix.to_vec()),
+		};
+		CopyJob {
+			to,
+			cursor: Cursor::new(from, start, prefix_end(prefix), false),
+			copied: 0,
+		}
+	}
+
+	/// Column family the pairs are copied into
+	pub fn to(&self) -> &CF {
+		&self.to
+	}
+
+	pub fn copied(&self) -> usize {
+		self.copied
+	}
+
+	pub fn is_done(&self) -> bool {
+		!self.cursor.has_next()
+	}
+
+	/// Copy the next batch. Both datastores can be the same instance.
+	pub async fn copy_batch(
+		&mut self,
+		source: &Datastore,
+		target: &Datastore,
+	) -> Result<(), Error> {
+		let tx = source.transaction(false).await?;
+		let pairs = self.cursor.next_batch(&tx, WRITE_BATCH_SIZE).await?;
+		drop(tx);
+
+		self.copied += write_pairs(target, self.to.clone(), &pairs, |_| {}).await?;
+		Ok(())
+	}
+}
+
+/// Copy every pair of column family `from` in `source` into column family `to` of
+/// `target`. Both datastores can be the same instance. `progress` is called with the number
+/// of copied pairs after every batch. Returns the number of copied pairs.
+pub async fn copy_column<F>(
+	source: &Datastore,
+	from: CF,
+	target: &Datastore,
+	to: CF,
+	mut progress: F,
+) -> Result<usize, Error>
+where
+	F: FnMut(usize),
+{
+	let mut job = CopyJob::new(from, to, &[]);
+	while !job.is_done() {
+		job.copy_batch(source, target).await?;
+		progress(job.copied());
+	}
+	Ok(job.copied())
+}
+
+/// Copy job between two databases addressed as `name:path`, advanced from the event loop
+/// so the interface keeps drawing its progress
+pub struct BackgroundCopy {
+	pub source: String,
+	pub target: String,
+	pub job: CopyJob,
+}
+
+impl BackgroundCopy {
+	/// Copy batches until `budget` elapsed. The databases are opened for the tick only, so
+	/// they stay usable from the command editor between two ticks.
+	pub async fn tick(&mut self, config: &Config, budget: Duration) -> Result<(), Error> {
+		let started = Instant::now();
+		let source = open_datastore(config, &self.source)?;
+		let target = match self.target == self.source {
+			true => None,
+			false => Some(open_datastore(config, &self.target)?),
+		};
+		while !self.job.is_done() && started.elapsed() < budget {
+			self.job.copy_batch(&source, target.as_ref().unwrap_or(&source)).await?;
+		}
+		Ok(())
+	}
+}
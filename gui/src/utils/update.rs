@@ -0,0 +1,155 @@
+use std::{
+	env, fs,
+	io::{self, Write},
+	sync::mpsc::{self, Receiver},
+	thread,
+};
+
+use anyhow::{anyhow, Result};
+use reqwest::header::{HeaderValue, ACCEPT};
+use self_update::{
+	backends::github, cargo_crate_version, get_target, update::ReleaseAsset,
+	version::bump_is_greater, Download, Extract, Move, TempDir,
+};
+use sha2::{Digest, Sha256};
+
+use crate::config::Config;
+
+const REPO_OWNER: &str = "nomadiz";
+const REPO_NAME: &str = "edma";
+const BIN_NAME: &str = "edma";
+
+/// Version of the latest GitHub release when it is newer than the running binary
+pub fn newer_version() -> Result<Option<String>> {
+	let releases = github::ReleaseList::configure()
+		.repo_owner(REPO_OWNER)
+		.repo_name(REPO_NAME)
+		.build()?
+		.fetch()?;
+	let current = cargo_crate_version!();
+	for release in releases {
+		if bump_is_greater(current, &release.version)? {
+			return Ok(Some(release.version));
+		}
+	}
+	Ok(None)
+}
+
+/// Download the latest release binary for the platform and replace the running executable,
+/// after a confirmation. The archive must match the SHA-256 published next to it as
+/// `<archive>.sha256`, nothing is replaced otherwise. Returns the version installed, the
+/// current one when up to date.
+pub fn update_executable() -> Result<(String, bool)> {
+	let current = cargo_crate_version!();
+	let release = github::Update::configure()
+		.repo_owner(REPO_OWNER)
+		.repo_name(REPO_NAME)
+		.bin_name(BIN_NAME)
+		.current_version(current)
+		.build()?
+		.get_latest_release()?;
+	if !bump_is_greater(current, &release.version)? {
+		return Ok((current.to_string(), false));
+	}
+
+	let target = get_target();
+	let asset =
+		release.asset_for(target).ok_or_else(|| anyhow!("No release asset for {}", target))?;
+	let checksum_name = format!("{}.sha256", asset.name);
+	let checksum = release
+		.assets
+		.iter()
+		.find(|a| a.name == checksum_name)
+		.ok_or_else(|| anyhow!("Release {} publishes no {}", release.version, checksum_name))?;
+
+	println!("Update EDMA {} to {} with {}", current, release.version, asset.name);
+	if !confirm("Do you want to continue? [Y/n] ")? {
+		return Ok((current.to_string(), false));
+	}
+
+	let executable = env::current_exe()?;
+	let parent = executable.parent().ok_or_else(|| anyhow!("Executable has no directory"))?;
+	// The download sits next to the executable so the final move never crosses devices
+	let download_dir = TempDir::new_in(parent)?;
+	let archive_path = download_dir.path().join(&asset.name);
+	download_asset(&asset, fs::File::create(&archive_path)?, true)?;
+	let mut published = vec![];
+	download_asset(checksum, &mut published, false)?;
+
+	let expected = String::from_utf8_lossy(&published)
+		.split_whitespace()
+		.next()
+		.map(str::to_lowercase)
+		.ok_or_else(|| anyhow!("{} is empty", checksum_name))?;
+	let actual = format!("{:x}", Sha256::digest(fs::read(&archive_path)?));
+	if actual != expected {
+		return Err(anyhow!(
+			"Checksum mismatch for {}: expected {}, downloaded {}",
+			asset.name,
+			expected,
+			actual
+		));
+	}
+
+	let bin_path = format!("{}{}", BIN_NAME, env::consts::EXE_SUFFIX);
+	Extract::from_source(&archive_path).extract_file(download_dir.path(), &bin_path)?;
+	let new_executable = download_dir.path().join(&bin_path);
+	#[cfg(unix)]
+	{
+		use std::os::unix::fs::PermissionsExt;
+		fs::set_permissions(&new_executable, fs::Permissions::from_mode(0o755))?;
+	}
+	let backup = download_dir.path().join(format!("__{}_backup", BIN_NAME));
+	Move::from_source(&new_executable).replace_using_temp(&backup).to_dest(&executable)?;
+	Ok((release.version, true))
+}
+
+/// Download a release asset of the GitHub API, which serves the file itself only to an
+/// `application/octet-stream` request
+fn download_asset<W: Write>(asset: &ReleaseAsset, writer: W, progress: bool) -> Result<()> {
+	Download::from_url(&asset.download_url)
+		.set_header(ACCEPT, HeaderValue::from_static("application/octet-stream"))
+		.show_progress(progress)
+		.download_to(writer)?;
+	Ok(())
+}
+
+fn confirm(message: &str) -> Result<bool> {
+	print!("{}", message);
+	io::stdout().flush()?;
+	let mut answer = String::new();
+	io::stdin().read_line(&mut answer)?;
+	Ok(matches!(answer.trim().to_lowercase().as_str(), "" | "y" | "yes"))
+}
+
+/// Startup check of a newer release, enabled with the `check_updates` config. The check
+/// runs on its own thread so a slow network never delays the interface.
+#[derive(Default)]
+pub struct UpdateChecker {
+	receiver: Option<Receiver<String>>,
+}
+
+impl UpdateChecker {
+	pub fn new(config: &Config) -> Self {
+		if !config.check_updates {
+			return UpdateChecker::default();
+		}
+		let (sender, receiver) = mpsc::channel();
+		thread::spawn(move || {
+			// A failed check is silent, the update is only a hint
+			if let Ok(Some(version)) = newer_version() {
+				let _ = sender.send(version);
+			}
+		});
+		UpdateChecker {
+			receiver: Some(receiver),
+		}
+	}
+
+	/// Newer version found by the check, returned once
+	pub fn tick(&mut self) -> Option<String> {
+		let version = self.receiver.as_ref()?.try_recv().ok()?;
+		self.receiver = None;
+		Some(version)
+	}
+}
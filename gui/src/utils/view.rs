@@ -0,0 +1,83 @@
+use std::{
+	fs, io,
+	path::{Path, PathBuf},
+};
+
+use db::KeyValuePair;
+use serde::{Deserialize, Serialize};
+
+use super::LayoutTemplate;
+
+/// # Derived views
+/// Named transformation over a keyspace: pairs of `column` in `database` are filtered by
+/// key prefix and value text, then projected through the key and value layout templates.
+/// Only the definition is persisted, rows are computed while the view is browsed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DerivedView {
+	pub name: String,
+	/// Database addressed as `name:path`
+	pub database: String,
+	pub column: Option<String>,
+	pub prefix: Option<String>,
+	/// Text the projected value must contain
+	pub contains: Option<String>,
+	pub key_template: Option<String>,
+	pub value_template: Option<String>,
+}
+
+/// Decode bytes with the template named `name`, segments joined with `|`. Bytes are kept
+/// as is without template.
+fn project(raw: Vec<u8>, name: &Option<String>, templates: &[LayoutTemplate]) -> Vec<u8> {
+	match name.as_ref().and_then(|name| templates.iter().find(|t| &t.name == name)) {
+		Some(template) => template.decode(&raw).join(" | ").into_bytes(),
+		None => raw,
+	}
+}
+
+impl DerivedView {
+	/// Filter and project pairs lazily, nothing is computed until the rows are consumed
+	pub fn evaluate<'a, I>(
+		&'a self,
+		pairs: I,
+		templates: &'a [LayoutTemplate],
+	) -> impl Iterator<Item = KeyValuePair> + 'a
+	where
+		I: IntoIterator<Item = KeyValuePair> + 'a,
+	{
+		let prefix = self.prefix.clone().unwrap_or_default().into_bytes();
+		pairs
+			.into_iter()
+			.filter(move |(key, _)| key.starts_with(&prefix))
+			.map(|(key, value)| {
+				let key = project(key, &self.key_template, templates);
+				let value = project(value, &self.value_template, templates);
+				(key, value)
+			})
+			.filter(|(_, value)| match &self.contains {
+				Some(text) => String::from_utf8_lossy(value).contains(text.as_str()),
+				None => true,
+			})
+	}
+}
+
+/// Views are persisted next to the config file
+pub fn views_path(config_path: &str) -> PathBuf {
+	Path::new(config_path).with_file_name("views.json")
+}
+
+/// Load persisted views, none when the file does not exist yet
+pub fn load_views(path: &Path) -> Vec<DerivedView> {
+	fs::read_to_string(path)
+		.ok()
+		.and_then(|data| serde_json::from_str(&data).ok())
+		.unwrap_or_default()
+}
+
+/// Persist a view, replacing the view with the same name
+pub fn save_view(path: &Path, view: DerivedView) -> io::Result<()> {
+	let mut views = load_views(path);
+	views.retain(|v| v.name != view.name);
+	views.push(view);
+	let data = serde_json::to_string_pretty(&views)?;
+	fs::write(path, data)
+}